@@ -1,4 +1,5 @@
 mod db;
+mod hooks;
 mod legacy_thread;
 mod native_agent_server;
 pub mod outline;
@@ -8,9 +9,11 @@ mod templates;
 #[cfg(test)]
 mod tests;
 mod thread;
+mod thread_search;
 mod thread_store;
 mod tool_permissions;
 mod tools;
+mod usage;
 
 use context_server::ContextServerId;
 pub use db::*;
@@ -24,9 +27,11 @@ pub use sandboxing::{
 pub use shell_command_parser::extract_commands;
 pub use templates::*;
 pub use thread::*;
+pub use thread_search::*;
 pub use thread_store::*;
 pub use tool_permissions::*;
 pub use tools::*;
+pub use usage::*;
 
 use acp_thread::{
     AcpThread, AgentModelId, AgentModelSelector, AgentSessionInfo, AgentSessionList,
@@ -1,14 +1,17 @@
-use crate::{AgentMessage, AgentMessageContent, UserMessage, UserMessageContent};
+use crate::{
+    AgentMessage, AgentMessageContent, ThreadSearchDocument, ThreadSearchMatch, ThreadSearchQuery,
+    ThreadSearchSnippet, UsageRecord, UsageSummary, UserMessage, UserMessageContent,
+};
 use acp_thread::ClientUserMessageId;
 use agent_client_protocol::schema::v1 as acp;
 use agent_settings::AgentProfileId;
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use collections::{HashMap, IndexMap};
 use futures::{FutureExt, future::Shared};
 use gpui::{BackgroundExecutor, Global, Task};
 use indoc::indoc;
-use language_model::Speed;
+use language_model::{Speed, TokenUsage};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -61,6 +64,10 @@ pub struct DbThread {
     pub initial_project_snapshot: Option<Arc<crate::ProjectSnapshot>>,
     #[serde(default)]
     pub cumulative_token_usage: language_model::TokenUsage,
+    /// Estimated cost of `cumulative_token_usage` in US dollars, for the
+    /// models whose pricing is known.
+    #[serde(default)]
+    pub cumulative_cost: f64,
     #[serde(default)]
     pub request_token_usage: HashMap<acp_thread::ClientUserMessageId, language_model::TokenUsage>,
     #[serde(default)]
@@ -158,6 +165,7 @@ impl SharedThread {
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
+            cumulative_cost: 0.0,
             request_token_usage: Default::default(),
             model: self.model,
             profile: None,
@@ -344,6 +352,7 @@ impl DbThread {
             },
             initial_project_snapshot: thread.initial_project_snapshot,
             cumulative_token_usage: thread.cumulative_token_usage,
+            cumulative_cost: 0.0,
             request_token_usage,
             model: thread.model,
             profile: thread.profile,
@@ -389,6 +398,8 @@ impl Column for DataType {
     }
 }
 
+const USAGE_DAY_FORMAT: &str = "%Y-%m-%d";
+
 pub(crate) struct ThreadsDatabase {
     executor: BackgroundExecutor,
     connection: Arc<Mutex<Connection>>,
@@ -478,6 +489,56 @@ impl ThreadsDatabase {
             }
         }
 
+        if let Ok(mut s) = connection.exec(indoc! {"
+            ALTER TABLE threads ADD COLUMN model TEXT;
+        "})
+        {
+            s().ok();
+        }
+
+        if let Ok(mut s) = connection.exec(indoc! {"
+            ALTER TABLE threads ADD COLUMN search_index_failed INTEGER NOT NULL DEFAULT 0;
+        "})
+        {
+            s().ok();
+        }
+
+        connection.exec(indoc! {"
+            CREATE VIRTUAL TABLE IF NOT EXISTS threads_search USING fts5(
+                thread_id UNINDEXED,
+                title,
+                messages,
+                tool_inputs,
+                file_paths,
+                tokenize = 'porter unicode61'
+            )
+        "})?()
+        .map_err(|e| e.context("Failed to create threads_search table"))?;
+
+        Self::index_unindexed_threads(&connection)
+            .map_err(|e| e.context("Failed to index threads for search"))?;
+
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS token_usage (
+                thread_id TEXT NOT NULL,
+                day TEXT NOT NULL,
+                profile TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_input_tokens INTEGER NOT NULL,
+                cache_read_input_tokens INTEGER NOT NULL,
+                cost REAL
+            )
+        "})?()
+        .map_err(|e| e.context("Failed to create token_usage table"))?;
+
+        connection.exec(indoc! {"
+            CREATE INDEX IF NOT EXISTS token_usage_day ON token_usage (day)
+        "})?()
+        .map_err(|e| e.context("Failed to create token_usage index"))?;
+
         let db = Self {
             executor,
             connection: Arc::new(Mutex::new(connection)),
@@ -517,6 +578,7 @@ impl ThreadsDatabase {
                     Some(serialized_folder_paths.order),
                 )
             };
+        let search_document = ThreadSearchDocument::from_thread(&thread);
         let json_data = serde_json::to_string(&SerializedThread {
             thread,
             version: DbThread::VERSION,
@@ -533,9 +595,9 @@ impl ThreadsDatabase {
         // created, not when it was saved to the database.
         let created_at = updated_at.clone();
 
-        let mut insert = connection.exec_bound::<(Arc<str>, Option<Arc<str>>, Option<String>, Option<String>, String, String, DataType, Vec<u8>, String)>(indoc! {"
-            INSERT INTO threads (id, parent_id, folder_paths, folder_paths_order, summary, updated_at, data_type, data, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        let mut insert = connection.exec_bound::<(Arc<str>, Option<Arc<str>>, Option<String>, Option<String>, String, String, DataType, Vec<u8>, String, Option<String>)>(indoc! {"
+            INSERT INTO threads (id, parent_id, folder_paths, folder_paths_order, summary, updated_at, data_type, data, created_at, model)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             ON CONFLICT(id) DO UPDATE SET
                 parent_id = excluded.parent_id,
                 folder_paths = excluded.folder_paths,
@@ -543,11 +605,13 @@ impl ThreadsDatabase {
                 summary = excluded.summary,
                 updated_at = excluded.updated_at,
                 data_type = excluded.data_type,
-                data = excluded.data
+                data = excluded.data,
+                model = excluded.model,
+                search_index_failed = 0
         "})?;
 
         insert((
-            id.0,
+            id.0.clone(),
             parent_id,
             folder_paths_str,
             folder_paths_order_str,
@@ -556,8 +620,72 @@ impl ThreadsDatabase {
             data_type,
             data,
             created_at,
+            search_document.model.clone(),
         ))?;
 
+        Self::index_thread_sync(&connection, id.0, search_document)?;
+
+        Ok(())
+    }
+
+    /// Replaces the full-text index entry for a thread.
+    fn index_thread_sync(
+        connection: &Connection,
+        id: Arc<str>,
+        document: ThreadSearchDocument,
+    ) -> Result<()> {
+        let mut delete = connection.exec_bound::<Arc<str>>(indoc! {"
+            DELETE FROM threads_search WHERE thread_id = ?
+        "})?;
+        delete(id.clone())?;
+
+        let mut insert =
+            connection.exec_bound::<(Arc<str>, String, String, String, String)>(indoc! {"
+            INSERT INTO threads_search (thread_id, title, messages, tool_inputs, file_paths)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+        "})?;
+        insert((
+            id,
+            document.title,
+            document.messages,
+            document.tool_inputs,
+            document.file_paths,
+        ))
+    }
+
+    /// Adds search index entries for threads saved before the index existed.
+    /// Threads that can't be deserialized are marked, so that they aren't
+    /// retried on every startup. Saving such a thread again indexes it.
+    fn index_unindexed_threads(connection: &Connection) -> Result<()> {
+        let mut select = connection.select::<(Arc<str>, DataType, Vec<u8>)>(indoc! {"
+            SELECT id, data_type, data FROM threads
+            WHERE search_index_failed = 0
+                AND id NOT IN (SELECT thread_id FROM threads_search)
+        "})?;
+        let rows = select()?;
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let mut update_model = connection.exec_bound::<(Option<String>, Arc<str>)>(indoc! {"
+            UPDATE threads SET model = ?1 WHERE id = ?2
+        "})?;
+        let mut mark_failed = connection.exec_bound::<Arc<str>>(indoc! {"
+            UPDATE threads SET search_index_failed = 1 WHERE id = ?
+        "})?;
+        for (id, data_type, data) in rows {
+            let thread = match Self::deserialize_thread(data_type, data) {
+                Ok(thread) => thread,
+                Err(error) => {
+                    log::warn!("failed to deserialize thread {id} for indexing: {error:#}");
+                    mark_failed(id)?;
+                    continue;
+                }
+            };
+            let document = ThreadSearchDocument::from_thread(&thread);
+            update_model((document.model.clone(), id.clone()))?;
+            Self::index_thread_sync(connection, id, document)?;
+        }
         Ok(())
     }
 
@@ -604,6 +732,191 @@ impl ThreadsDatabase {
         })
     }
 
+    /// Searches the titles, messages, tool-call inputs and touched file paths
+    /// of top-level threads. Results are ranked by relevance when the query
+    /// has search text, and by recency otherwise. Without a `limit`, every
+    /// matching thread is returned.
+    pub fn search_threads(
+        &self,
+        query: ThreadSearchQuery,
+        limit: Option<usize>,
+    ) -> Task<Result<Vec<ThreadSearchMatch>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let model = query.model.as_deref().map(escape_like_pattern);
+            let worktree = query.worktree.as_deref().map(escape_like_pattern);
+            let updated_after = query.updated_after.map(|date| date.to_rfc3339());
+            let updated_before = query.updated_before.map(|date| date.to_rfc3339());
+            // SQLite treats a negative limit as no limit.
+            let limit = limit.map_or(-1, |limit| limit as i64);
+
+            let rows = if let Some(match_expression) = query.fts_match_expression() {
+                let mut select = connection.select_bound::<
+                    (String, Option<String>, Option<String>, Option<String>, Option<String>, i64),
+                    (Arc<str>, String, String, Option<String>, Option<String>, Option<String>),
+                >(indoc! {"
+                    SELECT threads.id, threads.summary, threads.updated_at, threads.folder_paths, threads.folder_paths_order,
+                        snippet(threads_search, -1, char(1), char(2), '…', 16)
+                    FROM threads_search
+                    JOIN threads ON threads.id = threads_search.thread_id
+                    WHERE threads_search MATCH ?1
+                        AND threads.parent_id IS NULL
+                        AND (?2 IS NULL OR threads.model LIKE '%' || ?2 || '%' ESCAPE '\\')
+                        AND (?3 IS NULL OR threads.folder_paths LIKE '%' || ?3 || '%' ESCAPE '\\')
+                        AND (?4 IS NULL OR threads.updated_at >= ?4)
+                        AND (?5 IS NULL OR threads.updated_at < ?5)
+                    ORDER BY bm25(threads_search, 0.0, 10.0, 2.0, 1.0, 4.0), threads.updated_at DESC
+                    LIMIT ?6
+                "})?;
+                select((
+                    match_expression,
+                    model,
+                    worktree,
+                    updated_after,
+                    updated_before,
+                    limit,
+                ))?
+            } else {
+                let mut select = connection.select_bound::<
+                    (Option<String>, Option<String>, Option<String>, Option<String>, i64),
+                    (Arc<str>, String, String, Option<String>, Option<String>, Option<String>),
+                >(indoc! {"
+                    SELECT id, summary, updated_at, folder_paths, folder_paths_order, NULL
+                    FROM threads
+                    WHERE parent_id IS NULL
+                        AND (?1 IS NULL OR model LIKE '%' || ?1 || '%' ESCAPE '\\')
+                        AND (?2 IS NULL OR folder_paths LIKE '%' || ?2 || '%' ESCAPE '\\')
+                        AND (?3 IS NULL OR updated_at >= ?3)
+                        AND (?4 IS NULL OR updated_at < ?4)
+                    ORDER BY updated_at DESC
+                    LIMIT ?5
+                "})?;
+                select((model, worktree, updated_after, updated_before, limit))?
+            };
+
+            let mut matches = Vec::new();
+            for (id, summary, updated_at, folder_paths, folder_paths_order, snippet) in rows {
+                let folder_paths = folder_paths
+                    .map(|paths| {
+                        PathList::deserialize(&util::path_list::SerializedPathList {
+                            paths,
+                            order: folder_paths_order.unwrap_or_default(),
+                        })
+                    })
+                    .unwrap_or_default();
+                matches.push(ThreadSearchMatch {
+                    id: acp::SessionId::new(id),
+                    title: summary.into(),
+                    updated_at: DateTime::parse_from_rfc3339(&updated_at)?.with_timezone(&Utc),
+                    folder_paths,
+                    snippet: snippet.map(|snippet| ThreadSearchSnippet::from_marked(&snippet)),
+                });
+            }
+
+            Ok(matches)
+        })
+    }
+
+    /// Records token usage for budgets and the usage dashboard. Usage is kept
+    /// when its thread is deleted, so daily totals stay accurate.
+    pub fn record_usage(&self, record: UsageRecord) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let mut insert = connection.exec_bound::<(
+                Arc<str>,
+                String,
+                Arc<str>,
+                String,
+                String,
+                u64,
+                u64,
+                u64,
+                u64,
+                Option<f64>,
+            )>(indoc! {"
+                INSERT INTO token_usage (thread_id, day, profile, provider, model, input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens, cost)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "})?;
+            insert((
+                record.thread_id.0,
+                record.day.format(USAGE_DAY_FORMAT).to_string(),
+                record.profile.0,
+                record.provider.to_string(),
+                record.model.to_string(),
+                record.usage.input_tokens,
+                record.usage.output_tokens,
+                record.usage.cache_creation_input_tokens,
+                record.usage.cache_read_input_tokens,
+                record.cost,
+            ))
+        })
+    }
+
+    /// Usage recorded on or after `since`, aggregated by day, profile and
+    /// model, most recent day first.
+    pub fn usage_summaries(&self, since: NaiveDate) -> Task<Result<Vec<UsageSummary>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+            let mut select = connection.select_bound::<String, (
+                String,
+                Arc<str>,
+                String,
+                String,
+                u64,
+                u64,
+                u64,
+                u64,
+                Option<f64>,
+            )>(indoc! {"
+                SELECT day, profile, provider, model,
+                    SUM(input_tokens), SUM(output_tokens),
+                    SUM(cache_creation_input_tokens), SUM(cache_read_input_tokens),
+                    SUM(cost)
+                FROM token_usage
+                WHERE day >= ?
+                GROUP BY day, profile, provider, model
+                ORDER BY day DESC, SUM(cost) DESC
+            "})?;
+
+            select(since.format(USAGE_DAY_FORMAT).to_string())?
+                .into_iter()
+                .map(
+                    |(
+                        day,
+                        profile,
+                        provider,
+                        model,
+                        input_tokens,
+                        output_tokens,
+                        cache_creation_input_tokens,
+                        cache_read_input_tokens,
+                        cost,
+                    )| {
+                        Ok(UsageSummary {
+                            day: NaiveDate::parse_from_str(&day, USAGE_DAY_FORMAT)?,
+                            profile: AgentProfileId(profile),
+                            provider: provider.into(),
+                            model: model.into(),
+                            usage: TokenUsage {
+                                input_tokens,
+                                output_tokens,
+                                cache_creation_input_tokens,
+                                cache_read_input_tokens,
+                            },
+                            cost,
+                        })
+                    },
+                )
+                .collect()
+        })
+    }
+
     pub fn load_thread(&self, id: acp::SessionId) -> Task<Result<Option<DbThread>>> {
         let connection = self.connection.clone();
 
@@ -700,6 +1013,10 @@ impl ThreadsDatabase {
                     DELETE FROM threads WHERE id = ?
                 "})?;
 
+                let mut delete_search_entry = connection.exec_bound::<Arc<str>>(indoc! {"
+                    DELETE FROM threads_search WHERE thread_id = ?
+                "})?;
+
                 let mut sandboxed_terminal_temp_dirs = Vec::new();
                 for thread_id in ids_to_delete {
                     if let Some(temp_dir) = select(thread_id.clone())?.into_iter().next().and_then(
@@ -707,6 +1024,7 @@ impl ThreadsDatabase {
                     ) {
                         sandboxed_terminal_temp_dirs.push(temp_dir);
                     }
+                    delete_search_entry(thread_id.clone())?;
                     delete(thread_id)?;
                 }
 
@@ -745,6 +1063,12 @@ impl ThreadsDatabase {
 
                 delete(())?;
 
+                let mut delete_search_entries = connection.exec_bound::<()>(indoc! {"
+                    DELETE FROM threads_search
+                "})?;
+
+                delete_search_entries(())?;
+
                 sandboxed_terminal_temp_dirs
             };
 
@@ -757,6 +1081,19 @@ impl ThreadsDatabase {
     }
 }
 
+/// Escapes the wildcards in text matched with `LIKE ... ESCAPE '\'`, so it
+/// only matches literally.
+fn escape_like_pattern(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        if matches!(char, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -795,6 +1132,7 @@ mod tests {
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
+            cumulative_cost: 0.0,
             request_token_usage: HashMap::default(),
             model: None,
             profile: None,
@@ -1246,4 +1584,261 @@ mod tests {
         assert_eq!(scroll.item_ix, 42);
         assert!((scroll.offset_in_item - 13.5).abs() < f32::EPSILON);
     }
+
+    fn make_thread_with_messages(
+        title: &str,
+        updated_at: DateTime<Utc>,
+        user_text: &str,
+        tool_input: serde_json::Value,
+    ) -> DbThread {
+        let mut thread = make_thread(title, updated_at);
+        thread.messages = vec![
+            Arc::new(crate::Message::User(UserMessage {
+                id: ClientUserMessageId::new(),
+                content: Arc::from(vec![UserMessageContent::Text(user_text.to_string())]),
+            })),
+            Arc::new(crate::Message::Agent(AgentMessage {
+                content: vec![AgentMessageContent::ToolUse(
+                    language_model::LanguageModelToolUse {
+                        id: "tool-1".into(),
+                        name: "edit_file".into(),
+                        raw_input: tool_input.to_string(),
+                        input: language_model::LanguageModelToolUseInput::Json(tool_input),
+                        is_input_complete: true,
+                        thought_signature: None,
+                    },
+                )],
+                tool_results: IndexMap::default(),
+                reasoning_details: None,
+            })),
+        ];
+        thread
+    }
+
+    #[gpui::test]
+    async fn test_search_threads_matches_messages_tool_inputs_and_paths(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let migration_id = session_id("migration-thread");
+        let other_id = session_id("other-thread");
+
+        database
+            .save_thread(
+                migration_id.clone(),
+                make_thread_with_messages(
+                    "Database work",
+                    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                    "The migrations are failing on startup",
+                    serde_json::json!({
+                        "path": "crates/db/src/kvp.rs",
+                        "display_description": "Add missing ALTER TABLE statement",
+                    }),
+                ),
+                PathList::default(),
+            )
+            .await
+            .unwrap();
+        database
+            .save_thread(
+                other_id.clone(),
+                make_thread_with_messages(
+                    "Theme tweaks",
+                    Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+                    "Make the tab bar darker",
+                    serde_json::json!({ "path": "assets/themes/one/one.json" }),
+                ),
+                PathList::default(),
+            )
+            .await
+            .unwrap();
+
+        let search = |text: &str| database.search_threads(ThreadSearchQuery::parse(text), Some(10));
+
+        let results = search("migration").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, migration_id);
+        let snippet = results[0].snippet.as_ref().expect("should have a snippet");
+        assert_eq!(
+            snippet
+                .highlights
+                .iter()
+                .map(|range| &snippet.text[range.clone()])
+                .collect::<Vec<_>>(),
+            vec!["migrations"]
+        );
+
+        let results = search("ALTER TABLE").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, migration_id);
+
+        let results = search("kvp.rs").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, migration_id);
+
+        let results = search("themes").await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, other_id);
+
+        let results = search("\"unbalanced").await.unwrap();
+        assert!(results.is_empty());
+
+        database.delete_thread(migration_id).await.unwrap();
+        assert!(search("migration").await.unwrap().is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_search_threads_filters(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let older_id = session_id("older-thread");
+        let newer_id = session_id("newer-thread");
+
+        let mut older_thread = make_thread_with_messages(
+            "Older",
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            "Refactor the parser",
+            serde_json::json!({}),
+        );
+        older_thread.model = Some(DbLanguageModel {
+            provider: "anthropic".into(),
+            model: "claude-sonnet".into(),
+        });
+        let mut newer_thread = make_thread_with_messages(
+            "Newer",
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+            "Refactor the lexer",
+            serde_json::json!({}),
+        );
+        newer_thread.model = Some(DbLanguageModel {
+            provider: "openai".into(),
+            model: "gpt".into(),
+        });
+
+        database
+            .save_thread(
+                older_id.clone(),
+                older_thread,
+                PathList::new(&[PathBuf::from("/home/user/compiler")]),
+            )
+            .await
+            .unwrap();
+        database
+            .save_thread(
+                newer_id.clone(),
+                newer_thread,
+                PathList::new(&[PathBuf::from("/home/user/website")]),
+            )
+            .await
+            .unwrap();
+
+        let search = |text: &str| database.search_threads(ThreadSearchQuery::parse(text), Some(10));
+        let ids = |results: Vec<ThreadSearchMatch>| {
+            results
+                .into_iter()
+                .map(|result| result.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            ids(search("refactor").await.unwrap()),
+            vec![newer_id.clone(), older_id.clone()]
+        );
+        assert_eq!(
+            ids(search("refactor model:sonnet").await.unwrap()),
+            vec![older_id.clone()]
+        );
+        assert_eq!(
+            ids(search("refactor worktree:website").await.unwrap()),
+            vec![newer_id.clone()]
+        );
+        assert_eq!(
+            ids(search("after:2024-02-01").await.unwrap()),
+            vec![newer_id.clone()]
+        );
+        assert_eq!(
+            ids(search("before:2024-02-01").await.unwrap()),
+            vec![older_id.clone()]
+        );
+        // Wildcards in filters match literally.
+        assert!(ids(search("refactor worktree:%").await.unwrap()).is_empty());
+        assert!(ids(search("refactor model:_pt").await.unwrap()).is_empty());
+    }
+
+    #[gpui::test]
+    async fn test_search_threads_filters_without_limit(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        for (ix, folder) in ["website", "compiler", "website", "website"]
+            .into_iter()
+            .enumerate()
+        {
+            database
+                .save_thread(
+                    session_id(&format!("thread-{ix}")),
+                    make_thread(
+                        &format!("Thread {ix}"),
+                        Utc.with_ymd_and_hms(2024, 1, ix as u32 + 1, 0, 0, 0)
+                            .unwrap(),
+                    ),
+                    PathList::new(&[PathBuf::from(format!("/home/user/{folder}"))]),
+                )
+                .await
+                .unwrap();
+        }
+
+        let ids = |results: Vec<ThreadSearchMatch>| {
+            results
+                .into_iter()
+                .map(|result| result.id)
+                .collect::<Vec<_>>()
+        };
+        let query = ThreadSearchQuery::parse("worktree:website");
+
+        assert_eq!(
+            ids(database.search_threads(query.clone(), None).await.unwrap()),
+            vec![
+                session_id("thread-3"),
+                session_id("thread-2"),
+                session_id("thread-0"),
+            ]
+        );
+        // The limit applies to the threads that pass the filters.
+        assert_eq!(
+            ids(database.search_threads(query, Some(2)).await.unwrap()),
+            vec![session_id("thread-3"), session_id("thread-2")]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_unreadable_threads_are_indexed_once(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+        let connection = database.connection.lock();
+        connection
+            .exec(indoc! {"
+                INSERT INTO threads (id, summary, updated_at, data_type, data)
+                VALUES ('broken-thread', 'Broken', '2024-01-01T00:00:00+00:00', 'json', X'7B7B')
+            "})
+            .unwrap()()
+        .unwrap();
+
+        ThreadsDatabase::index_unindexed_threads(&connection).unwrap();
+
+        let mut select_failed = connection
+            .select::<(Arc<str>, i64)>(indoc! {"
+                SELECT id, search_index_failed FROM threads
+            "})
+            .unwrap();
+        assert_eq!(
+            select_failed().unwrap(),
+            vec![(Arc::<str>::from("broken-thread"), 1)]
+        );
+        let mut select_pending = connection
+            .select::<Arc<str>>(indoc! {"
+                SELECT id FROM threads
+                WHERE search_index_failed = 0
+                    AND id NOT IN (SELECT thread_id FROM threads_search)
+            "})
+            .unwrap();
+        assert!(select_pending().unwrap().is_empty());
+    }
 }
@@ -0,0 +1,387 @@
+//! Runs the user-defined lifecycle hooks configured under `agent.hooks`.
+//!
+//! Each hook is a shell command that receives a JSON [`HookPayload`] on stdin.
+//! `pre_tool_use` hooks can veto or rewrite a tool call (see
+//! [`HookRun::pre_tool_use_decision`]); every other hook is observational,
+//! except that a `post_tool_use` hook exiting with status 2 sends its stderr to
+//! the model.
+
+use agent_client_protocol::schema::v1 as acp;
+use agent_settings::{AgentHook, AgentHookEvent};
+use futures::{AsyncRead, AsyncReadExt as _, AsyncWriteExt as _, future::Either};
+use gpui::BackgroundExecutor;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, pin::pin};
+use util::command::Stdio;
+
+/// Exit status a hook uses to block the action it was invoked for.
+const BLOCKING_EXIT_CODE: i32 = 2;
+
+/// Everything needed to run hooks on behalf of one thread.
+#[derive(Clone)]
+pub(crate) struct HookContext {
+    pub session_id: acp::SessionId,
+    pub cwd: Option<PathBuf>,
+    pub executor: BackgroundExecutor,
+}
+
+/// The JSON written to a hook's stdin.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct HookPayload {
+    pub event: AgentHookEvent,
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_error: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+impl HookPayload {
+    pub fn new(event: AgentHookEvent, context: &HookContext) -> Self {
+        Self {
+            event,
+            session_id: context.session_id.0.to_string(),
+            cwd: context.cwd.clone(),
+            tool_name: None,
+            tool_input: None,
+            tool_output: None,
+            is_error: None,
+            stop_reason: None,
+        }
+    }
+}
+
+/// What a `pre_tool_use` hook decided about a tool call.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PreToolUseDecision {
+    Allow,
+    /// Run the tool with this input instead of the model's.
+    Modify(serde_json::Value),
+    Deny(String),
+}
+
+/// The optional JSON object a hook may print to stdout.
+#[derive(Debug, Default, Deserialize)]
+struct HookResponse {
+    #[serde(default)]
+    decision: Option<HookResponseDecision>,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    tool_input: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum HookResponseDecision {
+    Allow,
+    #[serde(alias = "block")]
+    Deny,
+}
+
+/// The result of running a single hook.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HookRun {
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    /// Set when the hook couldn't be spawned or didn't finish in time.
+    pub error: Option<String>,
+}
+
+impl HookRun {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none() && self.exit_code == Some(0)
+    }
+
+    pub fn is_blocking(&self) -> bool {
+        self.error.is_none() && self.exit_code == Some(BLOCKING_EXIT_CODE)
+    }
+
+    /// Interprets the hook's result as a `pre_tool_use` decision. Hooks that
+    /// fail in any way other than exiting with status 2 don't block the call.
+    pub fn pre_tool_use_decision(&self) -> PreToolUseDecision {
+        if self.is_blocking() {
+            let reason = self.stderr.trim();
+            return PreToolUseDecision::Deny(if reason.is_empty() {
+                format!("Blocked by hook `{}`", self.command)
+            } else {
+                reason.to_string()
+            });
+        }
+        if !self.succeeded() {
+            return PreToolUseDecision::Allow;
+        }
+
+        let stdout = self.stdout.trim();
+        if !stdout.starts_with('{') {
+            return PreToolUseDecision::Allow;
+        }
+        let response = match serde_json::from_str::<HookResponse>(stdout) {
+            Ok(response) => response,
+            Err(error) => {
+                log::warn!("hook `{}` printed invalid JSON: {error}", self.command);
+                return PreToolUseDecision::Allow;
+            }
+        };
+        match response.decision {
+            Some(HookResponseDecision::Deny) => PreToolUseDecision::Deny(
+                response
+                    .reason
+                    .unwrap_or_else(|| format!("Blocked by hook `{}`", self.command)),
+            ),
+            Some(HookResponseDecision::Allow) | None => match response.tool_input {
+                Some(input) => PreToolUseDecision::Modify(input),
+                None => PreToolUseDecision::Allow,
+            },
+        }
+    }
+
+    /// Whether there's anything worth showing in the thread for this run.
+    pub fn has_output(&self) -> bool {
+        !self.succeeded() || !self.stdout.trim().is_empty() || !self.stderr.trim().is_empty()
+    }
+
+    /// A Markdown rendering of the run for display in the thread.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        if let Some(error) = &self.error {
+            markdown.push_str(error);
+            markdown.push('\n');
+        } else if let Some(exit_code) = self.exit_code.filter(|code| *code != 0) {
+            markdown.push_str(&format!("Exited with status {exit_code}\n"));
+        }
+        for output in [&self.stdout, &self.stderr] {
+            let output = output.trim();
+            if !output.is_empty() {
+                markdown.push_str(&format!("```\n{output}\n```\n"));
+            }
+        }
+        markdown
+    }
+}
+
+/// Runs `hook` through the platform shell with `payload` as JSON on stdin,
+/// killing it if it outlives the hook's timeout.
+pub(crate) async fn run_hook(
+    hook: &AgentHook,
+    payload: &HookPayload,
+    context: &HookContext,
+) -> HookRun {
+    let mut run = HookRun {
+        command: hook.command.clone(),
+        ..Default::default()
+    };
+
+    let input = match serde_json::to_vec(payload) {
+        Ok(input) => input,
+        Err(error) => {
+            run.error = Some(format!("Failed to serialize hook input: {error}"));
+            return run;
+        }
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = util::command::new_command("cmd");
+        command.arg("/C").arg(&hook.command);
+        command
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut command = {
+        let mut command = util::command::new_command("sh");
+        command.arg("-c").arg(&hook.command);
+        command
+    };
+    command
+        .env("ZED_HOOK_EVENT", payload.event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(cwd) = &context.cwd {
+        command.current_dir(cwd);
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(error) => {
+            run.error = Some(format!("Failed to run hook: {error}"));
+            return run;
+        }
+    };
+
+    // The input is written inside the timed future, so a hook that never
+    // reads its stdin can't stall the thread past its timeout.
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let output = {
+        let status = child.status();
+        let output = async {
+            let write_input = async {
+                if let Some(mut stdin) = stdin {
+                    // A hook is free to ignore its input and exit early, which
+                    // surfaces here as a broken pipe, so write failures aren't
+                    // treated as errors.
+                    stdin.write_all(&input).await.ok();
+                    stdin.close().await.ok();
+                }
+            };
+            let ((), stdout, stderr) =
+                futures::join!(write_input, read_to_end(stdout), read_to_end(stderr));
+            std::io::Result::Ok((status.await?, stdout?, stderr?))
+        };
+        let timeout = pin!(context.executor.timer(hook.timeout));
+        match futures::future::select(pin!(output), timeout).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(_) => None,
+        }
+    };
+
+    match output {
+        Some(Ok((status, stdout, stderr))) => {
+            run.exit_code = status.code();
+            run.stdout = String::from_utf8_lossy(&stdout).into_owned();
+            run.stderr = String::from_utf8_lossy(&stderr).into_owned();
+            if run.exit_code.is_none() {
+                run.error = Some("Hook was terminated by a signal".to_string());
+            }
+        }
+        Some(Err(error)) => {
+            run.error = Some(format!("Failed to run hook: {error}"));
+        }
+        None => {
+            child.kill().ok();
+            run.error = Some(format!(
+                "Hook timed out after {}ms",
+                hook.timeout.as_millis()
+            ));
+        }
+    }
+
+    if !run.succeeded() && !run.is_blocking() {
+        log::warn!(
+            "{} hook `{}` failed: {}",
+            payload.event,
+            run.command,
+            run.error
+                .clone()
+                .unwrap_or_else(|| run.stderr.trim().to_string())
+        );
+    }
+
+    run
+}
+
+async fn read_to_end(stream: Option<impl AsyncRead + Unpin>) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if let Some(mut stream) = stream {
+        stream.read_to_end(&mut bytes).await?;
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(exit_code: i32, stdout: &str, stderr: &str) -> HookRun {
+        HookRun {
+            command: "check".to_string(),
+            exit_code: Some(exit_code),
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_pre_tool_use_decision_from_exit_code() {
+        assert_eq!(
+            run(0, "formatted 3 files\n", "").pre_tool_use_decision(),
+            PreToolUseDecision::Allow
+        );
+        assert_eq!(
+            run(
+                2,
+                "",
+                "src/generated.rs is generated; edit schema.json instead\n"
+            )
+            .pre_tool_use_decision(),
+            PreToolUseDecision::Deny(
+                "src/generated.rs is generated; edit schema.json instead".to_string()
+            )
+        );
+        assert_eq!(
+            run(2, "", "").pre_tool_use_decision(),
+            PreToolUseDecision::Deny("Blocked by hook `check`".to_string())
+        );
+        // Other failures don't block the tool call.
+        assert_eq!(
+            run(1, "", "command not found").pre_tool_use_decision(),
+            PreToolUseDecision::Allow
+        );
+        let timed_out = HookRun {
+            command: "check".to_string(),
+            error: Some("Hook timed out after 10ms".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(timed_out.pre_tool_use_decision(), PreToolUseDecision::Allow);
+    }
+
+    #[test]
+    fn test_pre_tool_use_decision_from_json() {
+        assert_eq!(
+            run(0, r#"{"decision": "deny", "reason": "no"}"#, "").pre_tool_use_decision(),
+            PreToolUseDecision::Deny("no".to_string())
+        );
+        assert_eq!(
+            run(0, r#"{"decision": "block"}"#, "").pre_tool_use_decision(),
+            PreToolUseDecision::Deny("Blocked by hook `check`".to_string())
+        );
+        assert_eq!(
+            run(0, r#"{"tool_input": {"command": "ls -la"}}"#, "").pre_tool_use_decision(),
+            PreToolUseDecision::Modify(serde_json::json!({"command": "ls -la"}))
+        );
+        assert_eq!(
+            run(0, r#"{"decision": "allow"}"#, "").pre_tool_use_decision(),
+            PreToolUseDecision::Allow
+        );
+        assert_eq!(
+            run(0, "{not json", "").pre_tool_use_decision(),
+            PreToolUseDecision::Allow
+        );
+    }
+
+    #[test]
+    fn test_payload_omits_unset_fields() {
+        let payload = HookPayload {
+            event: AgentHookEvent::TurnEnd,
+            session_id: "session-1".to_string(),
+            cwd: None,
+            tool_name: None,
+            tool_input: None,
+            tool_output: None,
+            is_error: None,
+            stop_reason: Some("end_turn".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(&payload).unwrap(),
+            serde_json::json!({
+                "event": "turn_end",
+                "session_id": "session-1",
+                "stop_reason": "end_turn",
+            })
+        );
+    }
+}
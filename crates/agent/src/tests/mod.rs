@@ -30,6 +30,7 @@ use language_model::{
     LanguageModelRegistry, LanguageModelRequest, LanguageModelRequestMessage,
     LanguageModelToolResult, LanguageModelToolSchemaFormat, LanguageModelToolUse, MessageContent,
     Role, StopReason, TokenUsage,
+    cassette::{Cassette, CassetteMode},
     fake_provider::{FakeLanguageModel, FakeLanguageModelProvider},
 };
 use pretty_assertions::assert_eq;
@@ -1076,6 +1077,288 @@ async fn test_tool_hallucination(cx: &mut TestAppContext) {
     assert_eq!(update.fields.status, Some(acp::ToolCallStatus::Failed));
 }
 
+#[gpui::test]
+async fn test_pre_tool_use_hook_vetoes_tool_call(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        project_context,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    cx.run_until_parked();
+
+    // Hooks run from the project's first worktree, so it has to exist on disk.
+    let project_dir = tempfile::tempdir().unwrap();
+    fs.insert_tree(project_dir.path(), json!({})).await;
+    let project = Project::test(fs.clone(), [project_dir.path()], cx).await;
+    let context_server_store = project.read_with(cx, |project, _| project.context_server_store());
+    let context_server_registry = cx.new(|cx| ContextServerRegistry::new(context_server_store, cx));
+    let thread = cx.new(|cx| {
+        Thread::new(
+            project,
+            project_context,
+            context_server_registry,
+            Templates::new(),
+            Some(model.clone()),
+            cx,
+        )
+    });
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |content| {
+                content.agent.get_or_insert_default().hooks = Some(settings::AgentHooksContent {
+                    pre_tool_use: Some(vec![settings::AgentHookContent {
+                        command: "exit 2".to_string(),
+                        tools: Some(vec![EchoTool::NAME.to_string()]),
+                        timeout_ms: None,
+                    }]),
+                    ..Default::default()
+                });
+            });
+        });
+    });
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.add_tool(EchoTool);
+            thread.send(ClientUserMessageId::new(), ["Echo something"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "hello"}).to_string(),
+            input: language_model::LanguageModelToolUseInput::Json(json!({"text": "hello"})),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+
+    let hook_run = loop {
+        let tool_call = next_tool_call(&mut events).await;
+        if tool_call.title.contains("pre_tool_use") {
+            break tool_call;
+        }
+    };
+    assert_eq!(hook_run.status, acp::ToolCallStatus::Failed);
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    let message = completion.messages.last().unwrap();
+    assert_eq!(
+        message.content,
+        vec![language_model::MessageContent::ToolResult(
+            LanguageModelToolResult {
+                tool_use_id: "tool_1".into(),
+                tool_name: EchoTool::NAME.into(),
+                is_error: true,
+                content: vec![
+                    "The `exit 2` hook blocked this tool call: Blocked by hook `exit 2`".into()
+                ],
+                output: None,
+            }
+        )]
+    );
+}
+
+#[gpui::test]
+async fn test_thread_start_hook_runs_once_after_failed_first_turn(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        project_context,
+        fs,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    cx.run_until_parked();
+
+    let project_dir = tempfile::tempdir().unwrap();
+    fs.insert_tree(project_dir.path(), json!({})).await;
+    let project = Project::test(fs.clone(), [project_dir.path()], cx).await;
+    let context_server_store = project.read_with(cx, |project, _| project.context_server_store());
+    let context_server_registry = cx.new(|cx| ContextServerRegistry::new(context_server_store, cx));
+    let thread = cx.new(|cx| {
+        Thread::new(
+            project,
+            project_context,
+            context_server_registry,
+            Templates::new(),
+            Some(model.clone()),
+            cx,
+        )
+    });
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |content| {
+                content.agent.get_or_insert_default().hooks = Some(settings::AgentHooksContent {
+                    thread_start: Some(vec![settings::AgentHookContent {
+                        command: "echo started".to_string(),
+                        tools: None,
+                        timeout_ms: None,
+                    }]),
+                    ..Default::default()
+                });
+            });
+        });
+    });
+
+    // The first turn fails before the model says anything.
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(ClientUserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    let hook_run = next_tool_call(&mut events).await;
+    assert!(hook_run.title.contains("thread_start"));
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_error(LanguageModelCompletionError::PromptTooLarge {
+        tokens: None,
+    });
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(ClientUserMessageId::new(), ["Hello again"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Hi");
+    fake_model.end_last_completion_stream();
+    let mut hook_runs = Vec::new();
+    while let Some(event) = events.next().await {
+        match event.unwrap() {
+            ThreadEvent::ToolCall(tool_call) => hook_runs.push(tool_call.title),
+            ThreadEvent::Stop(_) => break,
+            _ => {}
+        }
+    }
+    assert_eq!(hook_runs, Vec::<String>::new());
+}
+
+#[gpui::test]
+async fn test_thread_budget_pauses_turn_over_max_cost(cx: &mut TestAppContext) {
+    let ThreadTest { thread, .. } = setup(cx, TestModel::Fake).await;
+    let model = Arc::new(FakeLanguageModel::with_id_and_thinking(
+        "anthropic",
+        "claude-sonnet-4-5",
+        "Claude Sonnet 4.5",
+        false,
+    ));
+    thread.update(cx, |thread, cx| thread.set_model(model.clone(), cx));
+    set_thread_budget(1.0, cx);
+
+    // 1M input tokens cost $3, well over the $1 budget.
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(ClientUserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    model.send_last_completion_stream_text_chunk("Hey!");
+    model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 10,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(ClientUserMessageId::new(), ["Keep going"], cx)
+        })
+        .unwrap();
+    let authorization = next_budget_authorization(&mut events).await;
+    assert_eq!(
+        authorization.tool_call.fields.title.as_deref(),
+        Some("Thread budget reached")
+    );
+    assert!(model.pending_completions().is_empty());
+
+    authorization
+        .response
+        .send(acp_thread::SelectedPermissionOutcome::new(
+            acp::PermissionOptionId::new("deny"),
+            acp::PermissionOptionKind::RejectOnce,
+        ))
+        .unwrap();
+    cx.run_until_parked();
+    assert!(model.pending_completions().is_empty());
+}
+
+#[gpui::test]
+async fn test_cost_budget_pauses_turn_for_unpriced_model(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    set_thread_budget(1.0, cx);
+
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(ClientUserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    let authorization = next_budget_authorization(&mut events).await;
+    assert_eq!(
+        authorization
+            .tool_call
+            .fields
+            .title
+            .map(|title| title.to_string()),
+        Some(format!("No pricing for model `{}`", model.id().0))
+    );
+    assert!(fake_model.pending_completions().is_empty());
+
+    authorization
+        .response
+        .send(acp_thread::SelectedPermissionOutcome::new(
+            acp::PermissionOptionId::new("allow"),
+            acp::PermissionOptionKind::AllowOnce,
+        ))
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(fake_model.pending_completions().len(), 1);
+}
+
+fn set_thread_budget(max_cost: f64, cx: &mut TestAppContext) {
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |content| {
+                content.agent.get_or_insert_default().budgets =
+                    Some(settings::AgentBudgetsContent {
+                        per_thread: Some(settings::AgentBudgetContent {
+                            max_tokens: None,
+                            max_cost: Some(max_cost),
+                        }),
+                        per_day: None,
+                    });
+            });
+        });
+    });
+}
+
+async fn next_budget_authorization(
+    events: &mut UnboundedReceiver<Result<ThreadEvent>>,
+) -> ToolCallAuthorization {
+    loop {
+        let event = events
+            .next()
+            .await
+            .expect("no budget confirmation received")
+            .unwrap();
+        if let ThreadEvent::ToolCallAuthorization(authorization) = event {
+            return authorization;
+        }
+    }
+}
+
 /// Regression test: some providers (confirmed on Bedrock Mantle/GPT-5.x)
 /// reset their raw `tool_use` id counter every request/response cycle, so
 /// the same id (e.g. `call_1`) can recur within one turn. Used verbatim as
@@ -1250,6 +1533,83 @@ async fn test_replayed_tool_call_ids_scoped_across_messages(cx: &mut TestAppCont
     );
 }
 
+#[gpui::test]
+async fn test_replay_tool_use_from_cassette(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        project_context,
+        context_server_store,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let cassette_dir = tempfile::tempdir().unwrap();
+
+    // Record a turn in which the model calls the echo tool and then answers.
+    let recorder = Arc::new(Cassette::new(cassette_dir.path(), CassetteMode::Record))
+        .wrap_model(model.clone());
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.set_model(recorder, cx);
+            thread.add_tool(EchoTool);
+            thread.send(ClientUserMessageId::new(), ["Echo hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "hello"}).to_string(),
+            input: language_model::LanguageModelToolUseInput::Json(json!({"text": "hello"})),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("The tool echoed hello.");
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    assert_eq!(
+        stop_events(events.collect().await),
+        vec![acp::StopReason::EndTurn]
+    );
+
+    // Replaying the cassette in a new thread runs the tool again without
+    // reaching the model.
+    fake_model.forbid_requests();
+    let player = Arc::new(Cassette::new(cassette_dir.path(), CassetteMode::Replay))
+        .wrap_model(model.clone());
+    let project = thread.read_with(cx, |thread, _| thread.project.clone());
+    let context_server_registry = cx.new(|cx| ContextServerRegistry::new(context_server_store, cx));
+    let replayed_thread = cx.new(|cx| {
+        Thread::new(
+            project,
+            project_context,
+            context_server_registry,
+            Templates::new(),
+            Some(player),
+            cx,
+        )
+    });
+    let events = replayed_thread
+        .update(cx, |thread, cx| {
+            thread.add_tool(EchoTool);
+            thread.send(ClientUserMessageId::new(), ["Echo hello"], cx)
+        })
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+
+    // The answer is only recorded for a request carrying the tool's result,
+    // so getting it back means the replayed tool call ran.
+    let markdown = replayed_thread.read_with(cx, |thread, _| thread.to_markdown());
+    assert!(markdown.contains("The tool echoed hello."), "{markdown}");
+}
+
 async fn expect_tool_call(events: &mut UnboundedReceiver<Result<ThreadEvent>>) -> acp::ToolCall {
     let event = events
         .next()
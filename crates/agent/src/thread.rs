@@ -1,16 +1,19 @@
 use crate::{
-    ApplyCodeActionTool, AskUserTool, CodeActionStore, ContextServerRegistry, CopyPathTool,
-    CreateDirectoryTool, CreateThreadTool, DbLanguageModel, DbThread, DeletePathTool,
-    DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, FindReferencesTool, GetCodeActionsTool,
+    ApplyCodeActionTool, AskUserTool, BudgetOverrun, BudgetScope, CodeActionStore,
+    ContextServerRegistry, CopyPathTool, CreateDirectoryTool, CreateThreadTool, DbLanguageModel,
+    DbThread, DebuggerControlTool, DebuggerInspectTool, DeletePathTool, DiagnosticsTool,
+    EditFileTool, FetchTool, FindPathTool, FindReferencesTool, GetCodeActionsTool,
     GoToDefinitionTool, GrepTool, ListAgentsAndModelsTool, ListDirectoryTool, MovePathTool,
-    ProjectSnapshot, ReadFileTool, RenameTool, SandboxedTerminalTool, SpawnAgentTool,
-    SystemPromptTemplate, Template, Templates, TerminalTool, ToolPermissionDecision, WebSearchTool,
-    WriteFileTool, decide_permission_from_settings,
+    ProjectSnapshot, ReadFileTool, RenameTool, SandboxedTerminalTool, SetBreakpointTool,
+    SpawnAgentTool, Spend, StartDebuggingTool, SystemPromptTemplate, Template, Templates,
+    TerminalTool, ThreadsDatabase, ToolPermissionDecision, UsageRecord, WebSearchTool,
+    WriteFileTool, check_budget, decide_permission_from_settings,
 };
 use acp_thread::{ClientUserMessageId, MentionUri};
 use action_log::ActionLog;
 use agent_settings::UserAgentsMd;
 
+use crate::hooks::{HookContext, HookPayload, PreToolUseDecision, run_hook};
 use crate::sandboxing::{
     SandboxRequest, ThreadSandbox, ThreadSandboxGrants, sandbox_git_dirs,
     sandbox_worktree_writable_paths, sandboxing_available_for_project,
@@ -18,8 +21,9 @@ use crate::sandboxing::{
 };
 use agent_client_protocol::schema::v1 as acp;
 use agent_settings::{
-    AgentProfileId, AgentProfileSettings, AgentSettings, AutoCompactThreshold, COMPACTION_PROMPT,
-    SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT, builtin_profiles,
+    AgentHook, AgentHookEvent, AgentProfileId, AgentProfileSettings, AgentSettings,
+    AutoCompactThreshold, COMPACTION_PROMPT, SUMMARIZE_THREAD_DETAILED_PROMPT,
+    SUMMARIZE_THREAD_PROMPT, builtin_profiles,
 };
 use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Local, Utc};
@@ -1055,6 +1059,7 @@ impl ToolPermissionContext {
                 || tool_name == WriteFileTool::NAME
                 || tool_name == DeletePathTool::NAME
                 || tool_name == CreateDirectoryTool::NAME
+                || tool_name == SetBreakpointTool::NAME
             {
                 (
                     extract_path_pattern(value),
@@ -1259,10 +1264,18 @@ pub struct Thread {
     /// running to completion. The UI sets this to deliver a "steering" queued
     /// message mid-task; by default queued messages wait for the turn to finish.
     end_turn_at_next_boundary: bool,
+    /// Whether the `thread_start` hooks have been started for this thread,
+    /// so they run once even when the first turn fails.
+    thread_start_hooks_ran: bool,
     pending_message: Option<AgentMessage>,
     pub(crate) tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
     request_token_usage: HashMap<ClientUserMessageId, language_model::TokenUsage>,
     cumulative_token_usage: TokenUsage,
+    /// Estimated cost of `cumulative_token_usage` in US dollars.
+    cumulative_cost: f64,
+    /// The model serving the in-flight completion request, used to price its
+    /// usage.
+    current_request_model: Option<Arc<dyn LanguageModel>>,
     /// The per-field maximum usage snapshot already added to
     /// `cumulative_token_usage` for the in-flight completion request. Reset at
     /// the start of each request.
@@ -1403,10 +1416,13 @@ impl Thread {
             user_store: project.read(cx).user_store(),
             running_turn: None,
             end_turn_at_next_boundary: false,
+            thread_start_hooks_ran: false,
             pending_message: None,
             tools: BTreeMap::default(),
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
+            cumulative_cost: 0.0,
+            current_request_model: None,
             current_request_token_usage: TokenUsage::default(),
             pending_compaction_telemetry: None,
             initial_project_snapshot: {
@@ -1769,6 +1785,7 @@ impl Thread {
         );
 
         let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let thread_start_hooks_ran = !db_thread.messages.is_empty();
 
         Self {
             id,
@@ -1786,10 +1803,13 @@ impl Thread {
             user_store: project.read(cx).user_store(),
             running_turn: None,
             end_turn_at_next_boundary: false,
+            thread_start_hooks_ran,
             pending_message: None,
             tools: BTreeMap::default(),
             request_token_usage: db_thread.request_token_usage.clone(),
             cumulative_token_usage: db_thread.cumulative_token_usage,
+            cumulative_cost: db_thread.cumulative_cost,
+            current_request_model: None,
             current_request_token_usage: TokenUsage::default(),
             pending_compaction_telemetry: None,
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
@@ -1901,6 +1921,7 @@ impl Thread {
             detailed_summary: self.summary.clone(),
             initial_project_snapshot: None,
             cumulative_token_usage: self.cumulative_token_usage,
+            cumulative_cost: self.cumulative_cost,
             request_token_usage: self.request_token_usage.clone(),
             model: (&self.model).into(),
             profile: Some(self.profile_id.clone()),
@@ -2182,6 +2203,11 @@ impl Thread {
         self.add_tool(GoToDefinitionTool::new(self.project.clone()));
         self.add_tool(RenameTool::new(self.project.clone()));
 
+        self.add_tool(StartDebuggingTool::new(self.project.clone()));
+        self.add_tool(SetBreakpointTool::new(self.project.clone()));
+        self.add_tool(DebuggerControlTool::new(self.project.clone()));
+        self.add_tool(DebuggerInspectTool::new(self.project.clone()));
+
         if self.depth() < MAX_SUBAGENT_DEPTH {
             self.add_tool(SpawnAgentTool::new(environment.clone()));
         }
@@ -2300,7 +2326,18 @@ impl Thread {
         self.end_turn_at_next_boundary
     }
 
-    fn accumulate_token_usage(&mut self, update: language_model::TokenUsage) {
+    /// Resets the per-request usage accounting before a completion request
+    /// to `model` is sent.
+    fn begin_request_usage(&mut self, model: &Arc<dyn LanguageModel>) {
+        self.current_request_token_usage = TokenUsage::default();
+        self.current_request_model = Some(model.clone());
+    }
+
+    fn accumulate_token_usage(
+        &mut self,
+        update: language_model::TokenUsage,
+        cx: &mut Context<Self>,
+    ) {
         let previous_accounted_usage = self.current_request_token_usage;
         let current_accounted_usage = TokenUsage {
             input_tokens: previous_accounted_usage
@@ -2317,25 +2354,72 @@ impl Thread {
                 .max(update.cache_read_input_tokens),
         };
         self.current_request_token_usage = current_accounted_usage;
-        self.cumulative_token_usage = self.cumulative_token_usage
-            + TokenUsage {
-                input_tokens: current_accounted_usage
-                    .input_tokens
-                    .saturating_sub(previous_accounted_usage.input_tokens),
-                output_tokens: current_accounted_usage
-                    .output_tokens
-                    .saturating_sub(previous_accounted_usage.output_tokens),
-                cache_creation_input_tokens: current_accounted_usage
-                    .cache_creation_input_tokens
-                    .saturating_sub(previous_accounted_usage.cache_creation_input_tokens),
-                cache_read_input_tokens: current_accounted_usage
-                    .cache_read_input_tokens
-                    .saturating_sub(previous_accounted_usage.cache_read_input_tokens),
-            };
+        let new_usage = TokenUsage {
+            input_tokens: current_accounted_usage
+                .input_tokens
+                .saturating_sub(previous_accounted_usage.input_tokens),
+            output_tokens: current_accounted_usage
+                .output_tokens
+                .saturating_sub(previous_accounted_usage.output_tokens),
+            cache_creation_input_tokens: current_accounted_usage
+                .cache_creation_input_tokens
+                .saturating_sub(previous_accounted_usage.cache_creation_input_tokens),
+            cache_read_input_tokens: current_accounted_usage
+                .cache_read_input_tokens
+                .saturating_sub(previous_accounted_usage.cache_read_input_tokens),
+        };
+        self.cumulative_token_usage = self.cumulative_token_usage + new_usage;
+        self.record_usage(new_usage, cx);
+    }
+
+    /// Adds usage of the in-flight request to the thread's estimated cost and
+    /// to the usage ledger behind daily budgets and the usage dashboard.
+    fn record_usage(&mut self, usage: TokenUsage, cx: &mut Context<Self>) {
+        if usage.total_tokens() == 0 {
+            return;
+        }
+        let Some(model) = self.current_request_model.clone() else {
+            return;
+        };
+        let cost = model.pricing().map(|pricing| pricing.cost(&usage));
+        self.cumulative_cost += cost.unwrap_or_default();
+
+        let record = UsageRecord {
+            thread_id: self.id.clone(),
+            day: Local::now().date_naive(),
+            profile: self.profile_id.clone(),
+            provider: model.provider_id().0,
+            model: model.id().0,
+            usage,
+            cost,
+        };
+        let database = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database.await.map_err(|err| anyhow!(err))?;
+            database.record_usage(record).await
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Estimates what the next request will spend: it resends roughly the
+    /// input of the previous request.
+    fn next_request_spend(&self) -> Spend {
+        let Some(usage) = self.latest_request_token_usage() else {
+            return Spend::default();
+        };
+        let input = TokenUsage {
+            output_tokens: 0,
+            ..usage
+        };
+        let cost = self
+            .model()
+            .and_then(|model| model.pricing())
+            .map(|pricing| pricing.cost(&input));
+        Spend::new(&input, cost)
     }
 
     fn update_token_usage(&mut self, update: language_model::TokenUsage, cx: &mut Context<Self>) {
-        self.accumulate_token_usage(update);
+        self.accumulate_token_usage(update, cx);
 
         let Some(last_user_message) = self.last_user_message() else {
             return;
@@ -2557,7 +2641,7 @@ impl Thread {
         let compaction = self.forced_compaction_target_ix().map(|request_end_ix| {
             self.advance_prompt_id();
             let request = self.build_compaction_request(request_end_ix, &model, cx);
-            self.current_request_token_usage = TokenUsage::default();
+            self.begin_request_usage(&model);
             (model.clone(), request)
         });
 
@@ -2683,12 +2767,28 @@ impl Thread {
         let message_ix = self.messages.len().saturating_sub(1);
         self.clear_summary();
         let tools = self.enabled_tools(cx);
+        let hook_context = self.hook_context(cx);
+        let is_first_turn = !self.thread_start_hooks_ran && hook_context.is_some();
+        if is_first_turn {
+            self.thread_start_hooks_ran = true;
+        }
+        let hooks = AgentSettings::get_global(cx).hooks.clone();
         let (cancellation_tx, mut cancellation_rx) = watch::channel(false);
         let task = cx.spawn({
             let event_stream = event_stream.clone();
             async move |this, cx| {
                 log::debug!("Starting agent turn execution");
 
+                if is_first_turn && let Some(hook_context) = &hook_context {
+                    run_lifecycle_hooks(
+                        &hooks.thread_start,
+                        HookPayload::new(AgentHookEvent::ThreadStart, hook_context),
+                        hook_context,
+                        &event_stream,
+                    )
+                    .await;
+                }
+
                 let turn_result =
                     Self::run_turn_internal(&this, &event_stream, cancellation_rx.clone(), cx)
                         .await;
@@ -2698,11 +2798,38 @@ impl Thread {
                 let was_cancelled = *cancellation_rx.borrow();
                 if was_cancelled {
                     log::debug!("Turn was cancelled, skipping cleanup");
+                    if let Some(hook_context) = hook_context {
+                        // Cancelling waits for this task, so don't hold it up.
+                        cx.foreground_executor()
+                            .spawn(async move {
+                                run_turn_end_hooks(
+                                    &hooks.turn_end,
+                                    "cancelled",
+                                    &hook_context,
+                                    &event_stream,
+                                )
+                                .await
+                            })
+                            .detach();
+                    }
                     return;
                 }
 
                 _ = this.update(cx, |this, cx| this.flush_pending_message(cx));
 
+                if let Some(hook_context) = &hook_context {
+                    let stop_reason = match &turn_result {
+                        Ok(()) => "end_turn",
+                        Err(error) => match error.downcast_ref::<CompletionError>() {
+                            Some(CompletionError::Refusal) => "refusal",
+                            Some(CompletionError::MaxTokens) => "max_tokens",
+                            Some(CompletionError::Other(_)) | None => "error",
+                        },
+                    };
+                    run_turn_end_hooks(&hooks.turn_end, stop_reason, hook_context, &event_stream)
+                        .await;
+                }
+
                 match turn_result {
                     Ok(()) => {
                         log::debug!("Turn execution completed");
@@ -2742,6 +2869,8 @@ impl Thread {
         let mut intent = CompletionIntent::UserPrompt;
         // Set when a refusal fallback occurs so subsequent iterations use the fallback model.
         let mut refusal_fallback_model: Option<Arc<dyn LanguageModel>> = None;
+        // Budgets the user agreed to exceed for the rest of this turn.
+        let mut exceeded_budgets = HashSet::default();
         loop {
             match Self::perform_compaction_if_needed(
                 this,
@@ -2818,6 +2947,13 @@ impl Thread {
                 }
             }
 
+            if Self::enforce_budgets(this, event_stream, &mut exceeded_budgets, cx)
+                .await?
+                .is_break()
+            {
+                return Ok(());
+            }
+
             // Re-read the model and refresh tools on each iteration so that
             // mid-turn changes (e.g. the user switches model, toggles tools,
             // or changes profile) take effect between tool-call rounds.
@@ -2829,7 +2965,7 @@ impl Thread {
                     .ok_or_else(|| anyhow!(NoModelConfiguredError))?;
                 this.refresh_turn_tools(cx);
                 let request = this.build_completion_request(intent, cx)?;
-                this.current_request_token_usage = TokenUsage::default();
+                this.begin_request_usage(&model);
                 anyhow::Ok((model, request))
             })??;
 
@@ -3083,6 +3219,90 @@ impl Thread {
         }
     }
 
+    /// Checks the thread, profile and daily budgets before a request and asks
+    /// the user whether to continue if the request would exceed one, or if one
+    /// limits cost and the model's pricing is unknown. Once the user lets a
+    /// budget be exceeded, it isn't checked again this turn.
+    async fn enforce_budgets(
+        this: &WeakEntity<Self>,
+        event_stream: &ThreadEventStream,
+        exceeded_budgets: &mut HashSet<BudgetScope>,
+        cx: &mut AsyncApp,
+    ) -> Result<ControlFlow<()>> {
+        let (budgets, thread_spend, next_request) = this.read_with(cx, |this, cx| {
+            let settings = AgentSettings::get_global(cx);
+            let profile_budget = settings
+                .profiles
+                .get(&this.profile_id)
+                .map(|profile| profile.budget)
+                .unwrap_or_default();
+            let budgets = [
+                (BudgetScope::Thread, settings.budgets.per_thread),
+                (
+                    BudgetScope::Profile(this.profile_id.clone()),
+                    profile_budget,
+                ),
+                (BudgetScope::Day, settings.budgets.per_day),
+            ];
+            let thread_spend = Spend {
+                tokens: this.cumulative_token_usage.total_tokens(),
+                cost: this.cumulative_cost,
+            };
+            (budgets, thread_spend, this.next_request_spend())
+        })?;
+        let unpriced_model = this.read_with(cx, |this, _| {
+            this.model()
+                .filter(|model| model.pricing().is_none())
+                .map(|model| model.id().0)
+        })?;
+        let budgets = budgets
+            .into_iter()
+            .filter(|(scope, budget)| !budget.is_unlimited() && !exceeded_budgets.contains(scope))
+            .collect::<Vec<_>>();
+        if budgets.is_empty() {
+            return Ok(ControlFlow::Continue(()));
+        }
+
+        let today = if budgets
+            .iter()
+            .any(|(scope, _)| *scope != BudgetScope::Thread)
+        {
+            let database = cx
+                .update(ThreadsDatabase::connect)
+                .await
+                .map_err(|err| anyhow!(err))?;
+            database.usage_summaries(Local::now().date_naive()).await?
+        } else {
+            Vec::new()
+        };
+
+        for (scope, budget) in budgets {
+            let spent = match &scope {
+                BudgetScope::Thread => thread_spend,
+                BudgetScope::Profile(profile_id) => today
+                    .iter()
+                    .filter(|summary| summary.profile == *profile_id)
+                    .sum(),
+                BudgetScope::Day => today.iter().sum(),
+            };
+            let Some(overrun) = check_budget(
+                scope.clone(),
+                budget,
+                spent,
+                next_request,
+                unpriced_model.as_ref(),
+            ) else {
+                continue;
+            };
+            if !event_stream.confirm_budget_overrun(&overrun).await? {
+                log::info!("Turn stopped at budget: {}", overrun.title());
+                return Ok(ControlFlow::Break(()));
+            }
+            exceeded_budgets.insert(scope);
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
     /// Computes the retry status for a failed completion, notifies listeners,
     /// and waits out the backoff delay (or returns early if the turn is
     /// cancelled while waiting). Returns an error if the completion is not
@@ -3123,7 +3343,7 @@ impl Thread {
             let insertion_ix = this.compaction_message_target_ix(cx)?;
             let model = this.compaction_model(cx)?;
             let request = this.build_compaction_request(insertion_ix, &model, cx);
-            this.current_request_token_usage = TokenUsage::default();
+            this.begin_request_usage(&model);
             // Preserve telemetry across retries so the retry count keeps
             // accumulating rather than resetting on each attempt.
             if this.pending_compaction_telemetry.is_none() {
@@ -3198,8 +3418,8 @@ impl Thread {
                     event_stream.send_context_compaction_update(compaction_id.clone(), &text);
                 }
                 LanguageModelCompletionEvent::UsageUpdate(usage) => {
-                    this.update(cx, |this, _cx| {
-                        this.accumulate_token_usage(usage);
+                    this.update(cx, |this, cx| {
+                        this.accumulate_token_usage(usage, cx);
                     })?;
                 }
                 LanguageModelCompletionEvent::Stop(_)
@@ -3523,8 +3743,10 @@ impl Thread {
             }
         };
 
+        // Hooks need the complete input, so tools they apply to never stream.
+        let has_tool_hooks = self.has_tool_hooks(&tool_use.name, cx);
         if !tool_use.is_input_complete {
-            if tool.supports_input_streaming() {
+            if tool.supports_input_streaming() && !has_tool_hooks {
                 let running_turn = self.running_turn.as_mut()?;
                 if let Some(sender) = running_turn.streaming_tool_inputs.get_mut(&tool_use.id) {
                     sender.send_partial(input);
@@ -3565,6 +3787,18 @@ impl Thread {
         }
 
         log::debug!("Running tool {}", tool_use.name);
+        if has_tool_hooks {
+            return Some(self.run_tool_with_hooks(
+                tool,
+                input,
+                tool_use.id,
+                tool_use.name,
+                owning_message_ix,
+                event_stream,
+                cancellation_rx,
+                cx,
+            ));
+        }
         let tool_input = ToolInput::ready(input);
         Some(self.run_tool(
             tool,
@@ -3578,6 +3812,182 @@ impl Thread {
         ))
     }
 
+    /// Returns the context hooks run in, or `None` when hooks can't run for
+    /// this thread: the project is remote, or the workspace isn't trusted.
+    fn hook_context(&self, cx: &App) -> Option<HookContext> {
+        let project = self.project.read(cx);
+        if !project.is_local()
+            || TrustedWorktrees::has_restricted_worktrees(&project.worktree_store(), cx)
+        {
+            return None;
+        }
+        let cwd = project
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+        Some(HookContext {
+            session_id: self.id.clone(),
+            cwd,
+            executor: cx.background_executor().clone(),
+        })
+    }
+
+    fn has_tool_hooks(&self, tool_name: &str, cx: &App) -> bool {
+        let hooks = &AgentSettings::get_global(cx).hooks;
+        let has_hooks = hooks
+            .matching(AgentHookEvent::PreToolUse, Some(tool_name))
+            .chain(hooks.matching(AgentHookEvent::PostToolUse, Some(tool_name)))
+            .next()
+            .is_some();
+        has_hooks && self.hook_context(cx).is_some()
+    }
+
+    /// Runs a tool surrounded by its `pre_tool_use` and `post_tool_use` hooks.
+    /// A pre-tool-use hook can veto the call or replace its input.
+    fn run_tool_with_hooks(
+        &self,
+        tool: Arc<dyn AnyAgentTool>,
+        input: serde_json::Value,
+        tool_use_id: LanguageModelToolUseId,
+        tool_name: Arc<str>,
+        owning_message_ix: usize,
+        event_stream: &ThreadEventStream,
+        cancellation_rx: watch::Receiver<bool>,
+        cx: &mut Context<Self>,
+    ) -> Task<(usize, LanguageModelToolResult)> {
+        let Some(hook_context) = self.hook_context(cx) else {
+            return self.run_tool(
+                tool,
+                ToolInput::ready(input),
+                tool_use_id,
+                tool_name,
+                owning_message_ix,
+                event_stream,
+                cancellation_rx,
+                cx,
+            );
+        };
+        let hooks = &AgentSettings::get_global(cx).hooks;
+        let pre_tool_use_hooks = hooks
+            .matching(AgentHookEvent::PreToolUse, Some(&tool_name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let post_tool_use_hooks = hooks
+            .matching(AgentHookEvent::PostToolUse, Some(&tool_name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let event_stream = event_stream.clone();
+
+        cx.spawn(async move |this, cx| {
+            let tool_call_id = scoped_tool_call_id(owning_message_ix, &tool_use_id);
+            let mut input = input;
+            for hook in &pre_tool_use_hooks {
+                let mut payload = HookPayload::new(AgentHookEvent::PreToolUse, &hook_context);
+                payload.tool_name = Some(tool_name.to_string());
+                payload.tool_input = Some(input.clone());
+                let run = run_hook(hook, &payload, &hook_context).await;
+                event_stream.send_hook_run(AgentHookEvent::PreToolUse, &run);
+                match run.pre_tool_use_decision() {
+                    PreToolUseDecision::Allow => {}
+                    PreToolUseDecision::Modify(modified_input) => {
+                        input = modified_input;
+                        event_stream.update_tool_call_fields(
+                            &tool_call_id,
+                            acp::ToolCallUpdateFields::new().raw_input(input.clone()),
+                            None,
+                        );
+                    }
+                    PreToolUseDecision::Deny(reason) => {
+                        let content = format!(
+                            "The `{}` hook blocked this tool call: {reason}",
+                            hook.command
+                        );
+                        event_stream.update_tool_call_fields(
+                            &tool_call_id,
+                            acp::ToolCallUpdateFields::new()
+                                .status(acp::ToolCallStatus::Failed)
+                                .content(vec![acp::ToolCallContent::from(content.clone())]),
+                            None,
+                        );
+                        return (
+                            owning_message_ix,
+                            LanguageModelToolResult {
+                                tool_use_id,
+                                tool_name,
+                                is_error: true,
+                                content: vec![LanguageModelToolResultContent::Text(Arc::from(
+                                    content,
+                                ))],
+                                output: None,
+                            },
+                        );
+                    }
+                }
+            }
+
+            let tool_task = this.update(cx, |this, cx| {
+                this.run_tool(
+                    tool,
+                    ToolInput::ready(input.clone()),
+                    tool_use_id.clone(),
+                    tool_name.clone(),
+                    owning_message_ix,
+                    &event_stream,
+                    cancellation_rx,
+                    cx,
+                )
+            });
+            let (owning_message_ix, mut result) = match tool_task {
+                Ok(tool_task) => tool_task.await,
+                Err(error) => {
+                    return (
+                        owning_message_ix,
+                        LanguageModelToolResult {
+                            tool_use_id,
+                            tool_name,
+                            is_error: true,
+                            content: vec![LanguageModelToolResultContent::Text(Arc::from(
+                                error.to_string(),
+                            ))],
+                            output: None,
+                        },
+                    );
+                }
+            };
+
+            for hook in &post_tool_use_hooks {
+                let mut payload = HookPayload::new(AgentHookEvent::PostToolUse, &hook_context);
+                payload.tool_name = Some(tool_name.to_string());
+                payload.tool_input = Some(input.clone());
+                payload.tool_output = Some(
+                    result
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            LanguageModelToolResultContent::Text(text) => Some(text.as_ref()),
+                            LanguageModelToolResultContent::Image(_) => None,
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                );
+                payload.is_error = Some(result.is_error);
+                let run = run_hook(hook, &payload, &hook_context).await;
+                event_stream.send_hook_run(AgentHookEvent::PostToolUse, &run);
+                if run.is_blocking() && !run.stderr.trim().is_empty() {
+                    result
+                        .content
+                        .push(LanguageModelToolResultContent::Text(Arc::from(format!(
+                            "Feedback from the `{}` hook:\n{}",
+                            hook.command,
+                            run.stderr.trim()
+                        ))));
+                }
+            }
+
+            (owning_message_ix, result)
+        })
+    }
+
     fn run_tool(
         &self,
         tool: Arc<dyn AnyAgentTool>,
@@ -5319,7 +5729,107 @@ pub(crate) fn scoped_tool_call_id(
 #[derive(Clone)]
 struct ThreadEventStream(mpsc::UnboundedSender<Result<ThreadEvent>>);
 
+/// Runs hooks that aren't tied to a tool call, one after another, showing
+/// each run's output in the thread.
+async fn run_lifecycle_hooks(
+    hooks: &[AgentHook],
+    payload: HookPayload,
+    context: &HookContext,
+    event_stream: &ThreadEventStream,
+) {
+    for hook in hooks {
+        let run = run_hook(hook, &payload, context).await;
+        event_stream.send_hook_run(payload.event, &run);
+    }
+}
+
+/// Runs the `turn_end` hooks, telling them why the turn ended.
+async fn run_turn_end_hooks(
+    hooks: &[AgentHook],
+    stop_reason: &str,
+    context: &HookContext,
+    event_stream: &ThreadEventStream,
+) {
+    let mut payload = HookPayload::new(AgentHookEvent::TurnEnd, context);
+    payload.stop_reason = Some(stop_reason.to_string());
+    run_lifecycle_hooks(hooks, payload, context, event_stream).await;
+}
+
 impl ThreadEventStream {
+    /// Shows a hook's output in the thread as a completed (or failed) tool
+    /// call. Runs that succeed silently aren't shown.
+    fn send_hook_run(&self, event: AgentHookEvent, run: &crate::hooks::HookRun) {
+        if !run.has_output() {
+            return;
+        }
+        let id = acp::ToolCallId::new(format!("hook:{}", Uuid::new_v4()));
+        let status = if run.succeeded() {
+            acp::ToolCallStatus::Completed
+        } else {
+            acp::ToolCallStatus::Failed
+        };
+        let tool_call = acp::ToolCall::new(id, format!("`{event}` hook: `{}`", run.command))
+            .kind(acp::ToolKind::Execute)
+            .status(status)
+            .content(vec![acp::ToolCallContent::from(run.to_markdown())])
+            .meta(acp_thread::meta_with_tool_name("hook"));
+        self.0
+            .unbounded_send(Ok(ThreadEvent::ToolCall(tool_call)))
+            .ok();
+    }
+
+    /// Shows a budget overrun in the thread and waits for the user to decide
+    /// whether the agent may keep going. Returns whether it may.
+    async fn confirm_budget_overrun(&self, overrun: &BudgetOverrun) -> Result<bool> {
+        let id = acp::ToolCallId::new(format!("budget:{}", Uuid::new_v4()));
+        let tool_call = acp::ToolCall::new(id.clone(), overrun.title())
+            .kind(acp::ToolKind::Other)
+            .content(vec![acp::ToolCallContent::from(overrun.to_markdown())])
+            .meta(acp_thread::meta_with_tool_name("budget"));
+        self.0
+            .unbounded_send(Ok(ThreadEvent::ToolCall(tool_call)))
+            .ok();
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .unbounded_send(Ok(ThreadEvent::ToolCallAuthorization(
+                ToolCallAuthorization {
+                    tool_call: acp::ToolCallUpdate::new(
+                        id.clone(),
+                        acp::ToolCallUpdateFields::new().title(overrun.title()),
+                    ),
+                    options: acp_thread::PermissionOptions::Flat(vec![
+                        acp::PermissionOption::new(
+                            acp::PermissionOptionId::new("allow"),
+                            "Continue",
+                            acp::PermissionOptionKind::AllowOnce,
+                        ),
+                        acp::PermissionOption::new(
+                            acp::PermissionOptionId::new("deny"),
+                            "Stop",
+                            acp::PermissionOptionKind::RejectOnce,
+                        ),
+                    ]),
+                    response: response_tx,
+                    context: None,
+                    kind: acp_thread::AuthorizationKind::PermissionGrant,
+                },
+            )))
+            .map_err(|error| anyhow!("Failed to request budget confirmation: {error}"))?;
+
+        let outcome = response_rx
+            .await
+            .map_err(|_| anyhow!("authorization channel closed"))?;
+        let approved = outcome.option_id.0.as_ref() == "allow";
+        let status = if approved {
+            acp::ToolCallStatus::Completed
+        } else {
+            acp::ToolCallStatus::Failed
+        };
+        self.update_tool_call_fields(&id, acp::ToolCallUpdateFields::new().status(status), None);
+        Ok(approved)
+    }
+
     fn send_user_message(&self, message: &UserMessage) {
         self.0
             .unbounded_send(Ok(ThreadEvent::UserMessage(message.clone())))
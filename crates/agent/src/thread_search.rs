@@ -0,0 +1,381 @@
+use crate::{AgentMessageContent, DbThread, Message, UserMessageContent};
+use acp_thread::MentionUri;
+use agent_client_protocol::schema::v1 as acp;
+use chrono::{DateTime, NaiveDate, TimeZone as _, Utc};
+use collections::BTreeSet;
+use language_model::LanguageModelToolUseInput;
+use std::ops::Range;
+use std::path::Path;
+use ui::SharedString;
+use util::path_list::PathList;
+
+/// Marks the start of a matched term inside a snippet returned by SQLite's
+/// `snippet()` function. Control characters are used so they can't collide
+/// with anything a user or model would plausibly write.
+pub(crate) const SNIPPET_MATCH_START: char = '\u{1}';
+pub(crate) const SNIPPET_MATCH_END: char = '\u{2}';
+
+/// A full-text query over saved threads, optionally narrowed by worktree,
+/// model and date.
+///
+/// Filters can be typed inline in the search text, e.g.
+/// `migration model:sonnet worktree:zed after:2024-05-01`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadSearchQuery {
+    pub text: String,
+    /// Matches threads whose folder paths contain this substring (e.g. a
+    /// project name), case-insensitively.
+    pub worktree: Option<String>,
+    /// Matches threads whose model (`provider/model`) contains this
+    /// substring, case-insensitively.
+    pub model: Option<String>,
+    /// Only threads updated at or after this instant.
+    pub updated_after: Option<DateTime<Utc>>,
+    /// Only threads updated strictly before this instant.
+    pub updated_before: Option<DateTime<Utc>>,
+}
+
+impl ThreadSearchQuery {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Parses a query typed into a search box, extracting `worktree:`,
+    /// `model:`, `after:` and `before:` filters. Dates use `YYYY-MM-DD`.
+    /// Unrecognized or malformed filters are kept as search text.
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut text_terms = Vec::new();
+
+        for term in input.split_whitespace() {
+            let Some((key, value)) = term.split_once(':') else {
+                text_terms.push(term);
+                continue;
+            };
+            if value.is_empty() {
+                text_terms.push(term);
+                continue;
+            }
+            match key.to_ascii_lowercase().as_str() {
+                "worktree" | "project" | "in" => query.worktree = Some(value.to_string()),
+                "model" => query.model = Some(value.to_string()),
+                "after" | "since" => match parse_date(value) {
+                    Some(date) => query.updated_after = Some(date),
+                    None => text_terms.push(term),
+                },
+                "before" | "until" => match parse_date(value) {
+                    Some(date) => query.updated_before = Some(date),
+                    None => text_terms.push(term),
+                },
+                _ => text_terms.push(term),
+            }
+        }
+
+        query.text = text_terms.join(" ");
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && !self.has_filters()
+    }
+
+    pub fn has_filters(&self) -> bool {
+        self.worktree.is_some()
+            || self.model.is_some()
+            || self.updated_after.is_some()
+            || self.updated_before.is_some()
+    }
+
+    /// Converts the free-text portion of the query into an FTS5 `MATCH`
+    /// expression. Every term is quoted so that punctuation in the user's
+    /// input can't produce FTS5 syntax errors, and the final term is treated
+    /// as a prefix so results update while typing.
+    pub(crate) fn fts_match_expression(&self) -> Option<String> {
+        let terms = self
+            .text
+            .split_whitespace()
+            .map(|term| term.replace('"', "\"\""))
+            .filter(|term| !term.is_empty())
+            .collect::<Vec<_>>();
+        let (last, rest) = terms.split_last()?;
+        let mut expression = String::new();
+        for term in rest {
+            expression.push('"');
+            expression.push_str(term);
+            expression.push_str("\" ");
+        }
+        expression.push('"');
+        expression.push_str(last);
+        expression.push_str("\"*");
+        Some(expression)
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?))
+}
+
+/// A thread that matched a [`ThreadSearchQuery`].
+#[derive(Debug, Clone)]
+pub struct ThreadSearchMatch {
+    pub id: acp::SessionId,
+    pub title: SharedString,
+    pub updated_at: DateTime<Utc>,
+    pub folder_paths: PathList,
+    /// The best-matching excerpt of the thread, if the query had search text.
+    pub snippet: Option<ThreadSearchSnippet>,
+}
+
+/// An excerpt of a thread's indexed content with the matched terms marked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreadSearchSnippet {
+    pub text: SharedString,
+    /// Byte ranges into `text` covering each matched term.
+    pub highlights: Vec<Range<usize>>,
+}
+
+impl ThreadSearchSnippet {
+    /// The byte offset of every highlighted character, in the form expected
+    /// by `HighlightedLabel`.
+    pub fn highlight_positions(&self) -> Vec<usize> {
+        self.highlights
+            .iter()
+            .flat_map(|range| {
+                self.text[range.clone()]
+                    .char_indices()
+                    .map(move |(offset, _)| range.start + offset)
+            })
+            .collect()
+    }
+
+    /// Parses the output of SQLite's `snippet()` function, which wraps
+    /// matches in [`SNIPPET_MATCH_START`] and [`SNIPPET_MATCH_END`].
+    pub(crate) fn from_marked(marked: &str) -> Self {
+        let mut text = String::with_capacity(marked.len());
+        let mut highlights = Vec::new();
+        let mut match_start = None;
+        for ch in marked.chars() {
+            match ch {
+                SNIPPET_MATCH_START => match_start = Some(text.len()),
+                SNIPPET_MATCH_END => {
+                    if let Some(start) = match_start.take()
+                        && start < text.len()
+                    {
+                        highlights.push(start..text.len());
+                    }
+                }
+                '\n' | '\r' | '\t' => text.push(' '),
+                ch => text.push(ch),
+            }
+        }
+        Self {
+            text: text.into(),
+            highlights,
+        }
+    }
+}
+
+/// The searchable content of a thread, split into the columns of the
+/// `threads_search` full-text index.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ThreadSearchDocument {
+    pub title: String,
+    pub messages: String,
+    pub tool_inputs: String,
+    pub file_paths: String,
+    pub model: Option<String>,
+}
+
+impl ThreadSearchDocument {
+    pub fn from_thread(thread: &DbThread) -> Self {
+        let mut messages = String::new();
+        let mut tool_inputs = String::new();
+        let mut file_paths = BTreeSet::new();
+
+        for message in &thread.messages {
+            match message.as_ref() {
+                Message::User(message) => {
+                    for content in message.content.iter() {
+                        match content {
+                            UserMessageContent::Text(text) => push_line(&mut messages, text),
+                            UserMessageContent::Mention { uri, .. } => {
+                                if let Some(path) = mention_path(uri) {
+                                    file_paths.insert(path.to_string_lossy().into_owned());
+                                }
+                                push_line(&mut messages, &uri.name());
+                            }
+                            UserMessageContent::Image(_) => {}
+                        }
+                    }
+                }
+                Message::Agent(message) => {
+                    for content in &message.content {
+                        match content {
+                            AgentMessageContent::Text(text)
+                            | AgentMessageContent::Thinking { text, .. } => {
+                                push_line(&mut messages, text)
+                            }
+                            AgentMessageContent::RedactedThinking(_) => {}
+                            AgentMessageContent::ToolUse(tool_use) => {
+                                push_line(&mut tool_inputs, &tool_use.name);
+                                match &tool_use.input {
+                                    LanguageModelToolUseInput::Json(input) => {
+                                        collect_tool_input(input, &mut tool_inputs, &mut file_paths)
+                                    }
+                                    LanguageModelToolUseInput::Text(text) => {
+                                        push_line(&mut tool_inputs, text)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Message::Compaction(crate::CompactionInfo::Summary(summary)) => {
+                    push_line(&mut messages, summary)
+                }
+                Message::Compaction(_) | Message::Resume => {}
+            }
+        }
+
+        Self {
+            title: thread.title.to_string(),
+            messages,
+            tool_inputs,
+            file_paths: file_paths.into_iter().collect::<Vec<_>>().join("\n"),
+            model: thread
+                .model
+                .as_ref()
+                .map(|model| format!("{}/{}", model.provider, model.model)),
+        }
+    }
+}
+
+fn mention_path(uri: &MentionUri) -> Option<&Path> {
+    match uri {
+        MentionUri::File { abs_path }
+        | MentionUri::Directory { abs_path }
+        | MentionUri::Symbol { abs_path, .. } => Some(abs_path),
+        MentionUri::Selection { abs_path, .. } => abs_path.as_deref(),
+        _ => None,
+    }
+}
+
+/// Indexes every string in a tool's JSON input, and additionally records the
+/// values of path-like keys (`path`, `source_path`, `destination_path`, …) as
+/// files touched by the thread.
+fn collect_tool_input(
+    value: &serde_json::Value,
+    tool_inputs: &mut String,
+    file_paths: &mut BTreeSet<String>,
+) {
+    match value {
+        serde_json::Value::String(text) => push_line(tool_inputs, text),
+        serde_json::Value::Array(values) => {
+            for value in values {
+                collect_tool_input(value, tool_inputs, file_paths);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                if key == "path" || key.ends_with("_path") {
+                    if let Some(path) = value.as_str().filter(|path| !path.is_empty()) {
+                        file_paths.insert(path.to_string());
+                    }
+                }
+                collect_tool_input(value, tool_inputs, file_paths);
+            }
+        }
+        serde_json::Value::Null | serde_json::Value::Bool(_) | serde_json::Value::Number(_) => {}
+    }
+}
+
+fn push_line(target: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if !target.is_empty() {
+        target.push('\n');
+    }
+    target.push_str(text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extracts_filters() {
+        let query = ThreadSearchQuery::parse(
+            "fix the migration model:sonnet worktree:zed after:2024-05-01",
+        );
+        assert_eq!(query.text, "fix the migration");
+        assert_eq!(query.model.as_deref(), Some("sonnet"));
+        assert_eq!(query.worktree.as_deref(), Some("zed"));
+        assert_eq!(
+            query.updated_after,
+            Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(query.updated_before, None);
+    }
+
+    #[test]
+    fn test_parse_keeps_malformed_filters_as_text() {
+        let query = ThreadSearchQuery::parse("after:yesterday http://example.com model:");
+        assert_eq!(query.text, "after:yesterday http://example.com model:");
+        assert_eq!(query.updated_after, None);
+        assert_eq!(query.model, None);
+    }
+
+    #[test]
+    fn test_fts_match_expression_quotes_terms() {
+        assert_eq!(ThreadSearchQuery::new("   ").fts_match_expression(), None);
+        assert_eq!(
+            ThreadSearchQuery::new("fix \"migration\" -v").fts_match_expression(),
+            Some(r#""fix" """migration""" "-v"*"#.to_string())
+        );
+    }
+
+    #[test]
+    fn test_snippet_from_marked() {
+        let snippet =
+            ThreadSearchSnippet::from_marked("…we \u{1}fixed\u{2} the\n\u{1}migration\u{2} by…");
+        assert_eq!(snippet.text.as_ref(), "…we fixed the migration by…");
+        let highlighted = snippet
+            .highlights
+            .iter()
+            .map(|range| &snippet.text[range.clone()])
+            .collect::<Vec<_>>();
+        assert_eq!(highlighted, vec!["fixed", "migration"]);
+
+        let snippet = ThreadSearchSnippet::from_marked("é \u{1}ün\u{2}");
+        assert_eq!(snippet.highlight_positions(), vec![3, 5]);
+    }
+
+    #[test]
+    fn test_collect_tool_input_records_paths() {
+        let mut tool_inputs = String::new();
+        let mut file_paths = BTreeSet::new();
+        collect_tool_input(
+            &serde_json::json!({
+                "source_path": "crates/db/src/migrations.rs",
+                "destination_path": "crates/db/src/migrations/mod.rs",
+                "display_description": "Move migrations into a module",
+                "overwrite": false,
+            }),
+            &mut tool_inputs,
+            &mut file_paths,
+        );
+        assert_eq!(
+            file_paths.into_iter().collect::<Vec<_>>(),
+            vec![
+                "crates/db/src/migrations.rs".to_string(),
+                "crates/db/src/migrations/mod.rs".to_string(),
+            ]
+        );
+        assert!(tool_inputs.contains("Move migrations into a module"));
+    }
+}
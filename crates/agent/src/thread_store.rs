@@ -1,6 +1,9 @@
-use crate::{DbThread, DbThreadMetadata, ThreadsDatabase};
+use crate::{
+    DbThread, DbThreadMetadata, ThreadSearchMatch, ThreadSearchQuery, ThreadsDatabase, UsageSummary,
+};
 use agent_client_protocol::schema::v1 as acp;
 use anyhow::{Result, anyhow};
+use chrono::NaiveDate;
 use futures::{FutureExt, future::Shared};
 use gpui::{App, Context, Entity, Global, Task, prelude::*};
 use util::path_list::PathList;
@@ -96,6 +99,35 @@ impl ThreadStore {
         })
     }
 
+    /// Full-text search over every saved top-level thread, including ones
+    /// that aren't loaded. See [`ThreadSearchQuery`] for the supported filters.
+    pub fn search_threads(
+        &self,
+        query: ThreadSearchQuery,
+        limit: Option<usize>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<ThreadSearchMatch>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.search_threads(query, limit).await
+        })
+    }
+
+    /// Token usage of all threads since `since`, aggregated by day, profile
+    /// and model. Includes usage of threads that have since been deleted.
+    pub fn usage_summaries(
+        &self,
+        since: NaiveDate,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<UsageSummary>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.usage_summaries(since).await
+        })
+    }
+
     pub fn reload(&mut self, cx: &mut Context<Self>) {
         self.reload_task = Self::spawn_reload(cx);
     }
@@ -157,6 +189,7 @@ mod tests {
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
+            cumulative_cost: 0.0,
             request_token_usage: HashMap::default(),
             model: None,
             profile: None,
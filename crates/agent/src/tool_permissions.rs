@@ -605,6 +605,8 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions,
             sandbox_permissions: Default::default(),
+            hooks: Default::default(),
+            budgets: Default::default(),
             show_turn_stats: false,
             show_merge_conflict_indicator: true,
             sidebar_side: Default::default(),
@@ -4,6 +4,9 @@ mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
 mod create_thread_tool;
+mod debug_session;
+mod debugger_control_tool;
+mod debugger_inspect_tool;
 mod delete_path_tool;
 mod diagnostics_tool;
 mod edit_file_tool;
@@ -21,8 +24,10 @@ mod list_directory_tool;
 mod move_path_tool;
 mod read_file_tool;
 mod rename_tool;
+mod set_breakpoint_tool;
 mod skill_tool;
 mod spawn_agent_tool;
+mod start_debugging_tool;
 mod symbol_locator;
 mod terminal_tool;
 mod tool_permissions;
@@ -76,6 +81,8 @@ pub use context_server_registry::*;
 pub use copy_path_tool::*;
 pub use create_directory_tool::*;
 pub use create_thread_tool::*;
+pub use debugger_control_tool::*;
+pub use debugger_inspect_tool::*;
 pub use delete_path_tool::*;
 pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
@@ -90,8 +97,10 @@ pub use list_directory_tool::*;
 pub use move_path_tool::*;
 pub use read_file_tool::*;
 pub use rename_tool::*;
+pub use set_breakpoint_tool::*;
 pub use skill_tool::*;
 pub use spawn_agent_tool::*;
+pub use start_debugging_tool::*;
 pub use symbol_locator::*;
 
 pub use terminal_tool::*;
@@ -200,6 +209,8 @@ tools! {
     CopyPathTool,
     CreateDirectoryTool,
     CreateThreadTool,
+    DebuggerControlTool,
+    DebuggerInspectTool,
     DeletePathTool,
     DiagnosticsTool,
     EditFileTool,
@@ -214,8 +225,10 @@ tools! {
     MovePathTool,
     ReadFileTool,
     RenameTool,
+    SetBreakpointTool,
     SkillTool,
     SpawnAgentTool,
+    StartDebuggingTool,
     TerminalTool,
     WebSearchTool,
     WriteFileTool,
@@ -247,13 +260,20 @@ mod tests {
     use super::*;
 
     #[test]
-    fn fetch_and_terminal_are_forbidden_in_restricted_mode() {
-        assert!(!tool_allowed_in_restricted_mode(FetchTool::NAME));
-        assert!(!tool_allowed_in_restricted_mode(TerminalTool::NAME));
+    fn tools_that_run_code_are_forbidden_in_restricted_mode() {
+        let forbidden = [
+            FetchTool::NAME,
+            TerminalTool::NAME,
+            StartDebuggingTool::NAME,
+            DebuggerControlTool::NAME,
+        ];
+        for name in forbidden {
+            assert!(!tool_allowed_in_restricted_mode(name));
+        }
 
         // Every other built-in tool, and unknown (e.g. MCP) tools, are allowed.
         for name in ALL_TOOL_NAMES {
-            let expected = *name != FetchTool::NAME && *name != TerminalTool::NAME;
+            let expected = !forbidden.contains(name);
             assert_eq!(
                 tool_allowed_in_restricted_mode(name),
                 expected,
@@ -0,0 +1,567 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use dap::client::SessionId;
+use futures::{FutureExt as _, StreamExt as _, channel::mpsc};
+use gpui::{AsyncApp, Entity};
+use project::Project;
+use project::debugger::session::{
+    Session, SessionEvent, SessionStateEvent, StackFrame, ThreadId, ThreadStatus,
+};
+
+/// How long the debugger tools wait for a running program to stop (e.g. at a
+/// breakpoint) before reporting that it's still running.
+pub(crate) const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many stack frames are included when describing where a thread stopped.
+const MAX_REPORTED_FRAMES: usize = 10;
+
+/// Finds the session with the given id, or the most recently started one that
+/// is still alive.
+pub(crate) fn resolve_session(
+    project: &Entity<Project>,
+    session_id: Option<u32>,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Session>, String> {
+    project.read_with(cx, |project, cx| {
+        let dap_store = project.dap_store().read(cx);
+        match session_id {
+            Some(id) => dap_store
+                .session_by_id(SessionId(id))
+                .ok_or_else(|| format!("There is no debug session with id {id}.")),
+            None => dap_store
+                .sessions()
+                .filter(|session| !session.read(cx).is_terminated())
+                .max_by_key(|session| session.read(cx).session_id().0)
+                .cloned()
+                .ok_or_else(|| {
+                    "There is no active debug session. Start one with the start_debugging tool."
+                        .to_string()
+                }),
+        }
+    })
+}
+
+/// Picks the thread to operate on: the requested one, else a stopped thread,
+/// else the first thread the adapter reports.
+pub(crate) async fn resolve_thread(
+    session: &Entity<Session>,
+    thread_id: Option<i64>,
+    cx: &mut AsyncApp,
+) -> Result<ThreadId, String> {
+    let threads = session
+        .update(cx, |session, cx| session.fetch_threads(cx))
+        .await;
+    if let Some(thread_id) = thread_id {
+        return threads
+            .iter()
+            .any(|(thread, _)| thread.id == thread_id)
+            .then_some(ThreadId(thread_id))
+            .ok_or_else(|| format!("The debug session has no thread with id {thread_id}."));
+    }
+    threads
+        .iter()
+        .find(|(_, status)| *status == ThreadStatus::Stopped)
+        .or_else(|| threads.first())
+        .map(|(thread, _)| ThreadId(thread.id))
+        .ok_or_else(|| "The debug session has no threads.".to_string())
+}
+
+pub(crate) enum StopOutcome {
+    Stopped(Option<ThreadId>),
+    Terminated,
+    StillRunning,
+}
+
+/// Waits until the program stops or the session ends, giving up after
+/// [`STOP_TIMEOUT`].
+pub(crate) async fn wait_for_stop(session: &Entity<Session>, cx: &mut AsyncApp) -> StopOutcome {
+    let (tx, mut rx) = mpsc::unbounded();
+    let _subscriptions = cx.update(|cx| {
+        [
+            cx.subscribe(session, {
+                let tx = tx.clone();
+                move |_, event: &SessionEvent, _| {
+                    if let SessionEvent::Stopped(thread_id) = event {
+                        tx.unbounded_send(StopOutcome::Stopped(*thread_id)).ok();
+                    }
+                }
+            }),
+            cx.subscribe(session, move |_, event: &SessionStateEvent, _| {
+                if let SessionStateEvent::Shutdown = event {
+                    tx.unbounded_send(StopOutcome::Terminated).ok();
+                }
+            }),
+        ]
+    });
+    if session.read_with(cx, |session, _| session.is_terminated()) {
+        return StopOutcome::Terminated;
+    }
+
+    let timeout = cx.background_executor().timer(STOP_TIMEOUT);
+    futures::select! {
+        outcome = rx.next() => outcome.unwrap_or(StopOutcome::Terminated),
+        _ = timeout.fuse() => StopOutcome::StillRunning,
+    }
+}
+
+/// Describes where `thread_id` is stopped, innermost frame first.
+pub(crate) async fn describe_stopped_thread(
+    session: &Entity<Session>,
+    thread_id: ThreadId,
+    cx: &mut AsyncApp,
+) -> String {
+    let frames = session
+        .update(cx, |session, cx| session.fetch_stack_frames(thread_id, cx))
+        .await;
+    let mut output = format!("Thread {} stopped.\n", thread_id.0);
+    match frames {
+        Ok(frames) if frames.is_empty() => {
+            output.push_str("The debug adapter didn't report a stack trace.\n");
+        }
+        Ok(frames) => {
+            output.push_str("\nStack trace:\n");
+            for frame in frames.iter().take(MAX_REPORTED_FRAMES) {
+                writeln!(output, "- {}", format_stack_frame(frame)).ok();
+            }
+            if frames.len() > MAX_REPORTED_FRAMES {
+                writeln!(
+                    output,
+                    "- … {} more frames",
+                    frames.len() - MAX_REPORTED_FRAMES
+                )
+                .ok();
+            }
+        }
+        Err(error) => {
+            writeln!(output, "Failed to fetch the stack trace: {error}").ok();
+        }
+    }
+    output
+}
+
+pub(crate) async fn describe_stop_outcome(
+    session: &Entity<Session>,
+    outcome: StopOutcome,
+    cx: &mut AsyncApp,
+) -> String {
+    match outcome {
+        StopOutcome::Stopped(thread_id) => {
+            let thread_id = match thread_id {
+                Some(thread_id) => Ok(thread_id),
+                None => resolve_thread(session, None, cx).await,
+            };
+            match thread_id {
+                Ok(thread_id) => describe_stopped_thread(session, thread_id, cx).await,
+                Err(error) => format!("The program stopped. {error}"),
+            }
+        }
+        StopOutcome::Terminated => "The debug session ended.".to_string(),
+        StopOutcome::StillRunning => format!(
+            "The program is still running after {} seconds. Use debugger_control to pause it, \
+             or set a breakpoint where you expect it to stop.",
+            STOP_TIMEOUT.as_secs()
+        ),
+    }
+}
+
+/// Formats a frame as `[frame <id>] <name> at <path>:<line>`.
+pub(crate) fn format_stack_frame(frame: &StackFrame) -> String {
+    let frame = &frame.dap;
+    let source = frame
+        .source
+        .as_ref()
+        .and_then(|source| source.path.as_deref().or(source.name.as_deref()));
+    match source {
+        Some(source) => format!(
+            "[frame {}] {} at {}:{}",
+            frame.id, frame.name, source, frame.line
+        ),
+        None => format!("[frame {}] {}", frame.id, frame.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use agent_settings::AgentSettings;
+    use dap::adapters::{DebugAdapterName, DebugTaskDefinition};
+    use dap::{DapRegistry, FakeAdapter};
+    use gpui::{Subscription, TestAppContext};
+    use parking_lot::Mutex;
+    use project::debugger::dap_store::DapStoreEvent;
+    use project::debugger::session::SessionQuirks;
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::{Settings as _, SettingsStore};
+    use util::path;
+
+    use crate::tools::{
+        DebuggerAction, DebuggerControlTool, DebuggerControlToolInput, DebuggerInspectTool,
+        DebuggerInspectToolInput, SetBreakpointTool, SetBreakpointToolInput, StartDebuggingTool,
+        StartDebuggingToolInput,
+    };
+    use crate::{AgentTool as _, ToolCallEventStream, ToolInput};
+
+    use super::*;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            cx.update_default_global(|registry: &mut DapRegistry, _| {
+                registry.add_adapter(Arc::from(FakeAdapter::new()));
+            });
+        });
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.default = settings::ToolPermissionMode::Allow;
+            AgentSettings::override_global(settings, cx);
+        });
+    }
+
+    /// Starts requested sessions the way the debug panel does, so the tools
+    /// can be exercised without a workspace.
+    fn handle_session_requests(project: &Entity<Project>, cx: &mut TestAppContext) -> Subscription {
+        let dap_store = project.read_with(cx, |project, _| project.dap_store());
+        let project = project.downgrade();
+        cx.update(|cx| {
+            cx.subscribe(&dap_store, move |dap_store, event, cx| {
+                let DapStoreEvent::SessionRequested {
+                    scenario, sender, ..
+                } = event
+                else {
+                    return;
+                };
+                let worktree = project
+                    .upgrade()
+                    .and_then(|project| project.read(cx).worktrees(cx).next())
+                    .unwrap();
+                let definition = DebugTaskDefinition {
+                    adapter: DebugAdapterName(scenario.adapter.clone()),
+                    label: scenario.label.clone(),
+                    config: scenario.config.clone(),
+                    tcp_connection: None,
+                };
+                dap_store.update(cx, |dap_store, cx| {
+                    let session = dap_store.new_session(
+                        Some(scenario.label.clone()),
+                        DebugAdapterName(scenario.adapter.clone()),
+                        Default::default(),
+                        None,
+                        SessionQuirks::default(),
+                        cx,
+                    );
+                    dap_store
+                        .boot_session(session.clone(), definition, worktree, cx)
+                        .detach_and_log_err(cx);
+                    sender.clone().try_send(session).ok();
+                });
+            })
+        })
+    }
+
+    fn stopped_event(reason: dap::StoppedEventReason) -> dap::messages::Events {
+        dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: Some(true),
+            hit_breakpoint_ids: None,
+        })
+    }
+
+    #[gpui::test]
+    async fn test_start_debugging_without_debug_panel(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/project"), json!({ "main.rs": "fn main() {}\n" }))
+            .await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+
+        let tool = Arc::new(StartDebuggingTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| {
+                tool.run(
+                    ToolInput::resolved(StartDebuggingToolInput {
+                        scenario: None,
+                        adapter: Some(FakeAdapter::ADAPTER_NAME.to_string()),
+                        config: Some(json!({ "request": "launch" })),
+                    }),
+                    event_stream,
+                    cx,
+                )
+            })
+            .await;
+
+        let error = result.unwrap_err();
+        assert!(
+            error.contains("no debug panel is available to start the session"),
+            "unexpected error: {error}"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_debugger_tools(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                "main.rs": "fn main() {\n    let count = 3;\n    println!(\"{count}\");\n}\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+        let _requests = handle_session_requests(&project, cx);
+
+        let breakpoint_lines = Arc::new(Mutex::new(Vec::new()));
+        let continued = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+        let _intercept = project::debugger::test::intercept_debug_sessions(cx, {
+            let breakpoint_lines = breakpoint_lines.clone();
+            let continued = continued.clone();
+            let paused = paused.clone();
+            move |client| {
+                client.on_request::<dap::requests::SetBreakpoints, _>({
+                    let breakpoint_lines = breakpoint_lines.clone();
+                    move |_, args| {
+                        breakpoint_lines.lock().extend(
+                            args.breakpoints
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|breakpoint| breakpoint.line),
+                        );
+                        Ok(dap::SetBreakpointsResponse {
+                            breakpoints: Vec::default(),
+                        })
+                    }
+                });
+                client.on_request::<dap::requests::Threads, _>(|_, _| {
+                    Ok(dap::ThreadsResponse {
+                        threads: vec![dap::Thread {
+                            id: 1,
+                            name: "main".into(),
+                        }],
+                    })
+                });
+                client.on_request::<dap::requests::StackTrace, _>(|_, args| {
+                    assert_eq!(args.thread_id, 1);
+                    Ok(dap::StackTraceResponse {
+                        stack_frames: vec![dap::StackFrame {
+                            id: 7,
+                            name: "main".into(),
+                            source: Some(dap::Source {
+                                name: Some("main.rs".into()),
+                                path: Some(path!("/project/main.rs").into()),
+                                source_reference: None,
+                                presentation_hint: None,
+                                origin: None,
+                                sources: None,
+                                adapter_data: None,
+                                checksums: None,
+                            }),
+                            line: 2,
+                            column: 1,
+                            end_line: None,
+                            end_column: None,
+                            can_restart: None,
+                            instruction_pointer_reference: None,
+                            module_id: None,
+                            presentation_hint: None,
+                        }],
+                        total_frames: None,
+                    })
+                });
+                client.on_request::<dap::requests::Scopes, _>(|_, args| {
+                    assert_eq!(args.frame_id, 7);
+                    Ok(dap::ScopesResponse {
+                        scopes: vec![dap::Scope {
+                            name: "Locals".into(),
+                            presentation_hint: None,
+                            variables_reference: 11,
+                            named_variables: None,
+                            indexed_variables: None,
+                            expensive: false,
+                            source: None,
+                            line: None,
+                            column: None,
+                            end_line: None,
+                            end_column: None,
+                        }],
+                    })
+                });
+                client.on_request::<dap::requests::Variables, _>(|_, args| {
+                    assert_eq!(args.variables_reference, 11);
+                    Ok(dap::VariablesResponse {
+                        variables: vec![dap::Variable {
+                            name: "count".into(),
+                            value: "3".into(),
+                            type_: Some("i32".into()),
+                            presentation_hint: None,
+                            evaluate_name: None,
+                            variables_reference: 0,
+                            named_variables: None,
+                            indexed_variables: None,
+                            memory_reference: None,
+                            declaration_location_reference: None,
+                            value_location_reference: None,
+                        }],
+                    })
+                });
+                client.on_request::<dap::requests::Continue, _>({
+                    let continued = continued.clone();
+                    move |_, args| {
+                        assert_eq!(args.thread_id, 1);
+                        continued.store(true, Ordering::SeqCst);
+                        Ok(dap::ContinueResponse {
+                            all_threads_continued: Some(true),
+                        })
+                    }
+                });
+                client.on_request::<dap::requests::Pause, _>({
+                    let paused = paused.clone();
+                    move |_, _| {
+                        paused.store(true, Ordering::SeqCst);
+                        Ok(())
+                    }
+                });
+            }
+        });
+
+        let set_breakpoint = Arc::new(SetBreakpointTool::new(project.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| {
+                set_breakpoint.run(
+                    ToolInput::resolved(SetBreakpointToolInput {
+                        path: "project/main.rs".to_string(),
+                        line: 2,
+                        condition: None,
+                        log_message: None,
+                        hit_condition: None,
+                        remove: false,
+                    }),
+                    event_stream,
+                    cx,
+                )
+            })
+            .await;
+        assert_eq!(
+            result.unwrap(),
+            "Set a breakpoint at project/main.rs:2".to_string()
+        );
+
+        let start_debugging = Arc::new(StartDebuggingTool::new(project.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let start = cx.update(|cx| {
+            start_debugging.run(
+                ToolInput::resolved(StartDebuggingToolInput {
+                    scenario: None,
+                    adapter: Some(FakeAdapter::ADAPTER_NAME.to_string()),
+                    config: Some(json!({ "request": "launch" })),
+                }),
+                event_stream,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        assert_eq!(*breakpoint_lines.lock(), vec![2]);
+
+        let session = project.read_with(cx, |project, cx| {
+            project
+                .dap_store()
+                .read(cx)
+                .sessions()
+                .next()
+                .cloned()
+                .unwrap()
+        });
+        let client = session.read_with(cx, |session, _| session.adapter_client().unwrap());
+        client
+            .fake_event(stopped_event(dap::StoppedEventReason::Breakpoint))
+            .await;
+        let output = start.await.unwrap();
+        assert!(
+            output.contains("Thread 1 stopped.") && output.contains("[frame 7] main at"),
+            "unexpected output: {output}"
+        );
+
+        let inspect = Arc::new(DebuggerInspectTool::new(project.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| {
+                inspect.run(
+                    ToolInput::resolved(DebuggerInspectToolInput {
+                        session_id: None,
+                        thread_id: None,
+                        frame_id: None,
+                        variables_reference: None,
+                    }),
+                    event_stream,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(
+            output.contains("- [thread 1] main (Stopped)")
+                && output.contains("Locals:")
+                && output.contains("- count: i32 = 3"),
+            "unexpected output: {output}"
+        );
+
+        // Pausing a thread that is already stopped reports where it is
+        // without asking the adapter, which wouldn't send another stop.
+        let control = Arc::new(DebuggerControlTool::new(project.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| {
+                control.clone().run(
+                    ToolInput::resolved(DebuggerControlToolInput {
+                        action: DebuggerAction::Pause,
+                        session_id: None,
+                        thread_id: None,
+                    }),
+                    event_stream,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(
+            output.contains("Thread 1 stopped."),
+            "unexpected output: {output}"
+        );
+        assert!(!paused.load(Ordering::SeqCst));
+
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let resume = cx.update(|cx| {
+            control.run(
+                ToolInput::resolved(DebuggerControlToolInput {
+                    action: DebuggerAction::Continue,
+                    session_id: None,
+                    thread_id: None,
+                }),
+                event_stream,
+                cx,
+            )
+        });
+        cx.run_until_parked();
+        assert!(continued.load(Ordering::SeqCst));
+
+        client
+            .fake_event(stopped_event(dap::StoppedEventReason::Breakpoint))
+            .await;
+        let output = resume.await.unwrap();
+        assert!(
+            output.contains("Thread 1 stopped.") && output.contains("main.rs:2"),
+            "unexpected output: {output}"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use agent_client_protocol::schema::v1 as acp;
+use dap::debugger_settings::DebuggerSettings;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use project::debugger::session::ThreadStatus;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+
+use super::debug_session::{
+    StopOutcome, describe_stop_outcome, describe_stopped_thread, resolve_session, resolve_thread,
+    wait_for_stop,
+};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+
+/// Controls a running debug session: resumes, steps through, pauses or stops the debugged program.
+///
+/// Resuming and stepping wait until the program stops again (or exits) and report where it stopped, including the stack trace.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DebuggerControlToolInput {
+    /// What to do.
+    pub action: DebuggerAction,
+    /// The debug session to control. Defaults to the most recently started session.
+    #[serde(default)]
+    pub session_id: Option<u32>,
+    /// The thread to resume or step. Defaults to a stopped thread.
+    #[serde(default)]
+    pub thread_id: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebuggerAction {
+    /// Resume until the next breakpoint or exit.
+    Continue,
+    /// Run the current line, stepping over function calls.
+    StepOver,
+    /// Step into the function called on the current line.
+    StepInto,
+    /// Run until the current function returns.
+    StepOut,
+    /// Interrupt the running thread.
+    Pause,
+    /// End the debug session.
+    Stop,
+}
+
+impl DebuggerAction {
+    fn label(self) -> &'static str {
+        match self {
+            DebuggerAction::Continue => "continue",
+            DebuggerAction::StepOver => "step over",
+            DebuggerAction::StepInto => "step into",
+            DebuggerAction::StepOut => "step out",
+            DebuggerAction::Pause => "pause",
+            DebuggerAction::Stop => "stop",
+        }
+    }
+}
+
+pub struct DebuggerControlTool {
+    project: Entity<Project>,
+}
+
+impl DebuggerControlTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for DebuggerControlTool {
+    type Input = DebuggerControlToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "debugger_control";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        if let Ok(input) = input {
+            format!("Debugger: {}", input.action.label()).into()
+        } else {
+            "Control debugger".into()
+        }
+    }
+
+    fn allow_in_restricted_mode() -> bool {
+        false
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String, String>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            let action = input.action;
+
+            let authorize = cx.update(|cx| {
+                let context =
+                    crate::ToolPermissionContext::new(Self::NAME, vec![action.label().to_string()]);
+                event_stream.authorize(format!("Debugger: {}", action.label()), context, cx)
+            });
+            futures::select! {
+                result = authorize.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Debugger action cancelled by user".to_string());
+                }
+            };
+
+            let session = resolve_session(&project, input.session_id, cx)?;
+            if action == DebuggerAction::Stop {
+                session.update(cx, |session, cx| session.shutdown(cx)).await;
+                return Ok("Stopped the debug session.".to_string());
+            }
+
+            let thread_id = resolve_thread(&session, input.thread_id, cx).await?;
+            if action == DebuggerAction::Pause {
+                // A stopped thread won't report another stop, so don't wait for one.
+                let status = session.read_with(cx, |session, _| session.thread_status(thread_id));
+                if status == ThreadStatus::Stopped {
+                    return Ok(describe_stopped_thread(&session, thread_id, cx).await);
+                }
+                session.update(cx, |session, cx| session.pause_thread(thread_id, cx));
+            } else {
+                let granularity =
+                    cx.update(|cx| DebuggerSettings::get_global(cx).stepping_granularity);
+                session.update(cx, |session, cx| match action {
+                    DebuggerAction::Continue => session.continue_thread(thread_id, cx),
+                    DebuggerAction::StepOver => session.step_over(thread_id, granularity, cx),
+                    DebuggerAction::StepInto => session.step_in(thread_id, granularity, cx),
+                    DebuggerAction::StepOut => session.step_out(thread_id, granularity, cx),
+                    DebuggerAction::Pause | DebuggerAction::Stop => {}
+                });
+            }
+
+            let outcome = futures::select! {
+                outcome = wait_for_stop(&session, cx).fuse() => outcome,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Debugger action cancelled by user".to_string());
+                }
+            };
+            // A step can complete without a `stopped` event naming a thread;
+            // in that case report the thread that was stepped.
+            Ok(match outcome {
+                StopOutcome::Stopped(None) => {
+                    describe_stopped_thread(&session, thread_id, cx).await
+                }
+                outcome => describe_stop_outcome(&session, outcome, cx).await,
+            })
+        })
+    }
+}
@@ -0,0 +1,219 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use agent_client_protocol::schema::v1 as acp;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::debug_session::{format_stack_frame, resolve_session, resolve_thread};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+
+/// How many variables are listed per scope before the rest are elided.
+const MAX_VARIABLES_PER_SCOPE: usize = 100;
+
+/// Reads the state of a paused debug session: its threads, a thread's stack frames, and the scopes and variables of a frame.
+///
+/// By default this describes the stopped thread's stack trace and the variables in its innermost frame. Pass `frame_id` to look at another frame, or `variables_reference` to expand a structured variable (objects, arrays, maps) listed in an earlier result.
+///
+/// The program must be stopped (at a breakpoint, after a step, or paused) for stack frames and variables to be available.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DebuggerInspectToolInput {
+    /// The debug session to inspect. Defaults to the most recently started session.
+    #[serde(default)]
+    pub session_id: Option<u32>,
+    /// The thread to inspect. Defaults to a stopped thread.
+    #[serde(default)]
+    pub thread_id: Option<i64>,
+    /// The stack frame whose scopes and variables to list. Defaults to the innermost frame.
+    #[serde(default)]
+    pub frame_id: Option<u64>,
+    /// Lists the children of this structured variable instead of a frame's scopes.
+    #[serde(default)]
+    pub variables_reference: Option<u64>,
+}
+
+pub struct DebuggerInspectTool {
+    project: Entity<Project>,
+}
+
+impl DebuggerInspectTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for DebuggerInspectTool {
+    type Input = DebuggerInspectToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "debugger_inspect";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(DebuggerInspectToolInput {
+                variables_reference: Some(_),
+                ..
+            }) => "Expand debugger variable".into(),
+            _ => "Inspect debugger state".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String, String>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let session = resolve_session(&project, input.session_id, cx)?;
+            let label = session.read_with(cx, |session, _| {
+                session
+                    .label()
+                    .unwrap_or_else(|| session.adapter().0.clone())
+            });
+
+            let authorize = cx.update(|cx| {
+                let context =
+                    crate::ToolPermissionContext::new(Self::NAME, vec![label.to_string()]);
+                event_stream.authorize(format!("Inspect debug session {label}"), context, cx)
+            });
+            futures::select! {
+                result = authorize.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Inspection cancelled by user".to_string());
+                }
+            };
+
+            if let Some(variables_reference) = input.variables_reference {
+                let variables = session
+                    .update(cx, |session, cx| {
+                        session.fetch_variables(variables_reference, cx)
+                    })
+                    .await;
+                if variables.is_empty() {
+                    return Ok(format!(
+                        "Variable reference {variables_reference} has no children, \
+                         or the program is no longer stopped."
+                    ));
+                }
+                let mut output = String::new();
+                write_variables(&mut output, &variables);
+                return Ok(output);
+            }
+
+            let mut output = String::new();
+            let threads = session
+                .update(cx, |session, cx| session.fetch_threads(cx))
+                .await;
+            writeln!(output, "Threads:").ok();
+            for (thread, status) in &threads {
+                writeln!(
+                    output,
+                    "- [thread {}] {} ({})",
+                    thread.id,
+                    thread.name,
+                    status.label()
+                )
+                .ok();
+            }
+
+            let thread_id = resolve_thread(&session, input.thread_id, cx).await?;
+            let frames = session
+                .update(cx, |session, cx| session.fetch_stack_frames(thread_id, cx))
+                .await
+                .map_err(|e| format!("Failed to fetch the stack trace: {e}"))?;
+            if frames.is_empty() {
+                writeln!(
+                    output,
+                    "\nThread {} has no stack frames. Is it stopped?",
+                    thread_id.0
+                )
+                .ok();
+                return Ok(output);
+            }
+            writeln!(output, "\nStack trace of thread {}:", thread_id.0).ok();
+            for frame in &frames {
+                writeln!(output, "- {}", format_stack_frame(frame)).ok();
+            }
+
+            let frame = match input.frame_id {
+                Some(frame_id) => frames
+                    .iter()
+                    .find(|frame| frame.dap.id == frame_id)
+                    .ok_or_else(|| {
+                        format!("Thread {} has no frame with id {frame_id}.", thread_id.0)
+                    })?,
+                None => &frames[0],
+            };
+            writeln!(output, "\nVariables in {}:", format_stack_frame(frame)).ok();
+            let frame_id = frame.dap.id;
+            let scopes = session
+                .update(cx, |session, cx| session.fetch_scopes(frame_id, cx))
+                .await;
+            for scope in scopes {
+                writeln!(output, "\n{}:", scope.name).ok();
+                if scope.expensive {
+                    writeln!(
+                        output,
+                        "(not fetched automatically; expand with variables_reference {})",
+                        scope.variables_reference
+                    )
+                    .ok();
+                    continue;
+                }
+                let variables = session
+                    .update(cx, |session, cx| {
+                        session.fetch_variables(scope.variables_reference, cx)
+                    })
+                    .await;
+                write_variables(&mut output, &variables);
+            }
+
+            Ok(output)
+        })
+    }
+}
+
+fn write_variables(output: &mut String, variables: &[dap::Variable]) {
+    for variable in variables.iter().take(MAX_VARIABLES_PER_SCOPE) {
+        write!(output, "- {}", variable.name).ok();
+        if let Some(type_) = &variable.type_ {
+            write!(output, ": {type_}").ok();
+        }
+        write!(output, " = {}", variable.value).ok();
+        if variable.variables_reference != 0 {
+            write!(
+                output,
+                " (expand with variables_reference {})",
+                variable.variables_reference
+            )
+            .ok();
+        }
+        output.push('\n');
+    }
+    if variables.len() > MAX_VARIABLES_PER_SCOPE {
+        writeln!(
+            output,
+            "- … {} more",
+            variables.len() - MAX_VARIABLES_PER_SCOPE
+        )
+        .ok();
+    }
+}
@@ -184,6 +184,7 @@ impl AgentTool for GrepTool {
             let mut skips_remaining = input.offset;
             let mut matches_found = 0;
             let mut has_more_matches = false;
+            let mut pattern_too_complex = false;
 
             'outer: loop {
                 let search_result = futures::select! {
@@ -199,6 +200,10 @@ impl AgentTool for GrepTool {
                         has_more_matches = true;
                         break;
                     }
+                    Some(SearchResult::PatternTooComplex) => {
+                        pattern_too_complex = true;
+                        continue;
+                    }
                     Some(SearchResult::WaitingForScan | SearchResult::Searching) => continue,
                     None => break,
                 };
@@ -374,17 +379,24 @@ impl AgentTool for GrepTool {
                 );
             }
 
-            if matches_found == 0 {
-                Ok("No matches found".into())
+            let result = if matches_found == 0 {
+                "No matches found".to_string()
             } else if has_more_matches {
-                Ok(format!(
+                format!(
                     "Showing matches {}-{} (there were more matches found; use offset: {} to see next page):\n{output}",
                     input.offset + 1,
                     input.offset + matches_found,
                     input.offset + RESULTS_PER_PAGE,
+                )
+            } else {
+                format!("Found {matches_found} matches:\n{output}")
+            };
+            if pattern_too_complex {
+                Ok(format!(
+                    "{result}\n\nThe search stopped early because the regex needs too much backtracking, so some matches may be missing. Try a simpler pattern."
                 ))
             } else {
-                Ok(format!("Found {matches_found} matches:\n{output}"))
+                Ok(result)
             }
         })
     }
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use agent_client_protocol::schema::v1 as acp;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use language::Point;
+use project::Project;
+use project::debugger::breakpoint_store::{
+    Breakpoint, BreakpointEditAction, BreakpointStore, BreakpointWithPosition,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use util::markdown::MarkdownInlineCode;
+
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+
+/// Sets or removes a breakpoint on a line of a project file.
+///
+/// Breakpoints apply to every running debug session and to sessions started later, and show up in the user's editor. Replaces any breakpoint already on that line.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SetBreakpointToolInput {
+    /// The path of the file, relative to the project root, e.g. `my_project/src/main.rs`.
+    pub path: String,
+    /// The 1-based line number to set the breakpoint on.
+    pub line: u32,
+    /// Only stop when this expression, in the debugged program's language, is true.
+    #[serde(default)]
+    pub condition: Option<String>,
+    /// Log this message instead of stopping. Expressions in `{}` are interpolated.
+    #[serde(default)]
+    pub log_message: Option<String>,
+    /// Only stop after the breakpoint has been hit this many times, e.g. `5` or `>= 5` depending on the adapter.
+    #[serde(default)]
+    pub hit_condition: Option<String>,
+    /// Remove the breakpoint on this line instead of setting one.
+    #[serde(default)]
+    pub remove: bool,
+}
+
+pub struct SetBreakpointTool {
+    project: Entity<Project>,
+}
+
+impl SetBreakpointTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for SetBreakpointTool {
+    type Input = SetBreakpointToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "set_breakpoint";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Other
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) if input.remove => format!(
+                "Remove breakpoint at {}",
+                MarkdownInlineCode(&format!("{}:{}", input.path, input.line))
+            )
+            .into(),
+            Ok(input) => format!(
+                "Set breakpoint at {}",
+                MarkdownInlineCode(&format!("{}:{}", input.path, input.line))
+            )
+            .into(),
+            Err(_) => "Set breakpoint".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String, String>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            if input.line == 0 {
+                return Err("Line numbers are 1-based.".to_string());
+            }
+
+            let authorize = cx.update(|cx| {
+                let context =
+                    crate::ToolPermissionContext::new(Self::NAME, vec![input.path.clone()]);
+                let verb = if input.remove { "Remove" } else { "Set" };
+                event_stream.authorize(
+                    format!(
+                        "{verb} breakpoint at {}",
+                        MarkdownInlineCode(&format!("{}:{}", input.path, input.line))
+                    ),
+                    context,
+                    cx,
+                )
+            });
+            futures::select! {
+                result = authorize.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Breakpoint change cancelled by user".to_string());
+                }
+            };
+
+            let project_path = project
+                .read_with(cx, |project, cx| project.find_project_path(&input.path, cx))
+                .ok_or_else(|| format!("Path {} is not in the project", input.path))?;
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                .await
+                .map_err(|e| format!("Failed to open {}: {e}", input.path))?;
+            let breakpoint_store = project.read_with(cx, |project, _| project.breakpoint_store());
+
+            let row = input.line - 1;
+            breakpoint_store.update(cx, |breakpoint_store, cx| {
+                let abs_path = BreakpointStore::abs_path_from_buffer(&buffer, cx)
+                    .ok_or_else(|| format!("{} is not a file on disk", input.path))?;
+                let max_row = buffer.read(cx).max_point().row;
+                if row > max_row {
+                    return Err(format!("{} only has {} lines", input.path, max_row + 1));
+                }
+
+                let existing = breakpoint_store.breakpoint_at_row(&abs_path, row, cx);
+                if let Some((existing_buffer, existing)) = existing {
+                    breakpoint_store.toggle_breakpoint(
+                        existing_buffer,
+                        existing,
+                        BreakpointEditAction::Toggle,
+                        cx,
+                    );
+                } else if input.remove {
+                    return Err(format!(
+                        "There is no breakpoint at {}:{}",
+                        input.path, input.line
+                    ));
+                }
+                if input.remove {
+                    return Ok(format!(
+                        "Removed the breakpoint at {}:{}",
+                        input.path, input.line
+                    ));
+                }
+
+                let mut breakpoint = match &input.log_message {
+                    Some(message) => Breakpoint::new_log(message),
+                    None => Breakpoint::new_standard(),
+                };
+                breakpoint.condition = input.condition.as_deref().map(Arc::from);
+                breakpoint.hit_condition = input.hit_condition.as_deref().map(Arc::from);
+                let position = buffer.read(cx).anchor_before(Point::new(row, 0));
+                breakpoint_store.toggle_breakpoint(
+                    buffer.clone(),
+                    BreakpointWithPosition {
+                        position,
+                        bp: breakpoint,
+                    },
+                    BreakpointEditAction::Toggle,
+                    cx,
+                );
+
+                let kind = if input.log_message.is_some() {
+                    "log point"
+                } else {
+                    "breakpoint"
+                };
+                Ok(format!("Set a {kind} at {}:{}", input.path, input.line))
+            })
+        })
+    }
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use agent_client_protocol::schema::v1 as acp;
+use futures::FutureExt as _;
+use gpui::{App, AsyncApp, Entity, SharedString, Task};
+use project::{Project, WorktreeId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use task::{DebugScenario, SharedTaskContext, TaskContext, VariableName};
+use util::markdown::MarkdownInlineCode;
+
+use super::debug_session::{describe_stop_outcome, wait_for_stop};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+
+/// Starts a debug session so you can diagnose runtime failures with breakpoints, stepping and variable inspection.
+///
+/// Either pass the `scenario` label of a debug configuration the user has defined in `.zed/debug.json`, or pass an `adapter` with its launch `config`. Set breakpoints with the set_breakpoint tool *before* starting the session so the program stops where you need it to.
+///
+/// The session appears in the user's debug panel. This tool waits until the program stops (e.g. at a breakpoint) or exits, then reports where it stopped and the session id to use with debugger_control and debugger_inspect.
+///
+/// Scenarios that need a build step can't be started from here; build the program with the terminal tool first.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StartDebuggingToolInput {
+    /// The label of a debug scenario defined in the project's or the user's `debug.json`.
+    #[serde(default)]
+    pub scenario: Option<String>,
+    /// The debug adapter to use when not starting a predefined scenario, e.g. `CodeLLDB`, `Debugpy`, `JavaScript` or `Delve`.
+    #[serde(default)]
+    pub adapter: Option<String>,
+    /// The adapter-specific launch or attach configuration when not starting a predefined scenario.
+    ///
+    /// <example>
+    /// {"request": "launch", "program": "target/debug/my_app", "args": ["--verbose"], "cwd": "$ZED_WORKTREE_ROOT"}
+    /// </example>
+    #[serde(default, deserialize_with = "super::deserialize_maybe_stringified")]
+    pub config: Option<serde_json::Value>,
+}
+
+pub struct StartDebuggingTool {
+    project: Entity<Project>,
+}
+
+impl StartDebuggingTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for StartDebuggingTool {
+    type Input = StartDebuggingToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "start_debugging";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(StartDebuggingToolInput {
+                scenario: Some(scenario),
+                ..
+            }) => format!("Debug {}", MarkdownInlineCode(&scenario)).into(),
+            Ok(StartDebuggingToolInput {
+                adapter: Some(adapter),
+                ..
+            }) => format!("Start {} debug session", MarkdownInlineCode(&adapter)).into(),
+            _ => "Start debugging".into(),
+        }
+    }
+
+    fn allow_in_restricted_mode() -> bool {
+        false
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<String, String>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let worktree_id = project.read_with(cx, |project, cx| {
+                project
+                    .visible_worktrees(cx)
+                    .next()
+                    .map(|worktree| worktree.read(cx).id())
+            });
+            let scenario = resolve_scenario(&project, &input, worktree_id, cx)?;
+
+            let authorize = cx.update(|cx| {
+                let context =
+                    crate::ToolPermissionContext::new(Self::NAME, vec![scenario.label.to_string()]);
+                event_stream.authorize(
+                    format!("Debug {}", MarkdownInlineCode(&scenario.label)),
+                    context,
+                    cx,
+                )
+            });
+            futures::select! {
+                result = authorize.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Debugging cancelled by user".to_string());
+                }
+            };
+
+            let task_context = task_context_for_worktree(&project, worktree_id, cx);
+            let label = scenario.label.clone();
+            let session = project
+                .update(cx, |project, cx| {
+                    project.dap_store().update(cx, |dap_store, cx| {
+                        dap_store.request_session(scenario, task_context, worktree_id, cx)
+                    })
+                })
+                .await
+                .map_err(|e| format!("Failed to start debugging {label}: {e}"))?;
+
+            let outcome = futures::select! {
+                outcome = wait_for_stop(&session, cx).fuse() => outcome,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Debugging cancelled by user".to_string());
+                }
+            };
+            let session_id = session.read_with(cx, |session, _| session.session_id().0);
+            let description = describe_stop_outcome(&session, outcome, cx).await;
+            Ok(format!(
+                "Started debug session {session_id} ({label}).\n\n{description}"
+            ))
+        })
+    }
+}
+
+fn resolve_scenario(
+    project: &Entity<Project>,
+    input: &StartDebuggingToolInput,
+    worktree_id: Option<WorktreeId>,
+    cx: &mut AsyncApp,
+) -> Result<DebugScenario, String> {
+    let scenario = if let Some(label) = &input.scenario {
+        let inventory = project
+            .read_with(cx, |project, cx| {
+                project.task_store().read(cx).task_inventory().cloned()
+            })
+            .ok_or_else(|| "Debug scenarios aren't available in this project.".to_string())?;
+        let scenarios = inventory.read_with(cx, |inventory, _| {
+            inventory
+                .debug_scenarios_from_settings(worktree_id)
+                .map(|(_, scenario)| scenario)
+                .collect::<Vec<_>>()
+        });
+        let available = scenarios
+            .iter()
+            .map(|scenario| format!("`{}`", scenario.label))
+            .collect::<Vec<_>>();
+        scenarios
+            .into_iter()
+            .find(|scenario| scenario.label.as_ref() == label)
+            .ok_or_else(|| {
+                if available.is_empty() {
+                    format!("No debug scenario named `{label}`. No scenarios are defined.")
+                } else {
+                    format!(
+                        "No debug scenario named `{label}`. Available scenarios: {}",
+                        available.join(", ")
+                    )
+                }
+            })?
+    } else {
+        let (Some(adapter), Some(config)) = (&input.adapter, &input.config) else {
+            return Err("Provide either `scenario`, or both `adapter` and `config`.".to_string());
+        };
+        DebugScenario {
+            adapter: adapter.clone().into(),
+            label: format!("{adapter} (agent)").into(),
+            build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
+            config: config.clone(),
+            tcp_connection: None,
+        }
+    };
+
+    if scenario.build.is_some() {
+        return Err(format!(
+            "The `{}` scenario has a build step, which this tool can't run. \
+             Build the program with the terminal tool, then start the session with \
+             `adapter` and `config` instead.",
+            scenario.label
+        ));
+    }
+    Ok(scenario)
+}
+
+fn task_context_for_worktree(
+    project: &Entity<Project>,
+    worktree_id: Option<WorktreeId>,
+    cx: &mut AsyncApp,
+) -> SharedTaskContext {
+    let worktree_root = project.read_with(cx, |project, cx| {
+        let worktree = project.worktree_for_id(worktree_id?, cx)?;
+        Some(worktree.read(cx).abs_path().to_path_buf())
+    });
+    let mut context = TaskContext::default();
+    if let Some(worktree_root) = worktree_root {
+        context.task_variables.insert(
+            VariableName::WorktreeRoot,
+            worktree_root.to_string_lossy().into_owned(),
+        );
+        context.cwd = Some(worktree_root);
+    }
+    context.into()
+}
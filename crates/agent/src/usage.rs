@@ -0,0 +1,270 @@
+use std::ops::Add;
+
+use agent_client_protocol::schema::v1 as acp;
+use agent_settings::{AgentBudget, AgentProfileId};
+use chrono::NaiveDate;
+use gpui::SharedString;
+use language_model::TokenUsage;
+
+/// Token usage of one completion request (or part of one, as the provider
+/// reports it), as recorded in the threads database.
+#[derive(Clone, Debug)]
+pub struct UsageRecord {
+    pub thread_id: acp::SessionId,
+    /// The local calendar day the usage happened on.
+    pub day: NaiveDate,
+    pub profile: AgentProfileId,
+    pub provider: SharedString,
+    pub model: SharedString,
+    pub usage: TokenUsage,
+    /// Estimated cost in US dollars, or `None` if the model's pricing is
+    /// unknown.
+    pub cost: Option<f64>,
+}
+
+/// Usage aggregated by day, profile and model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsageSummary {
+    pub day: NaiveDate,
+    pub profile: AgentProfileId,
+    pub provider: SharedString,
+    pub model: SharedString,
+    pub usage: TokenUsage,
+    /// Estimated cost in US dollars, or `None` if the model's pricing is
+    /// unknown.
+    pub cost: Option<f64>,
+}
+
+/// An amount spent (or about to be spent) against a budget.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Spend {
+    pub tokens: u64,
+    /// In US dollars. Usage of models with unknown pricing adds nothing.
+    pub cost: f64,
+}
+
+impl Spend {
+    pub fn new(usage: &TokenUsage, cost: Option<f64>) -> Self {
+        Self {
+            tokens: usage.total_tokens(),
+            cost: cost.unwrap_or_default(),
+        }
+    }
+}
+
+impl Add for Spend {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            tokens: self.tokens + other.tokens,
+            cost: self.cost + other.cost,
+        }
+    }
+}
+
+impl<'a> std::iter::Sum<&'a UsageSummary> for Spend {
+    fn sum<I: Iterator<Item = &'a UsageSummary>>(iter: I) -> Self {
+        iter.fold(Spend::default(), |total, summary| {
+            total + Spend::new(&summary.usage, summary.cost)
+        })
+    }
+}
+
+/// What a budget applies to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BudgetScope {
+    /// All turns of the current thread.
+    Thread,
+    /// Today's usage of every thread using the profile.
+    Profile(AgentProfileId),
+    /// Today's usage of every thread.
+    Day,
+}
+
+/// A budget that the next request would exceed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetOverrun {
+    pub scope: BudgetScope,
+    pub budget: AgentBudget,
+    pub spent: Spend,
+    /// What the next request is expected to cost: it resends roughly the
+    /// context of the previous one.
+    pub next_request: Spend,
+    /// Set when the budget limits cost but the model's pricing is unknown, so
+    /// its spend can't be tracked.
+    pub unpriced_model: Option<SharedString>,
+}
+
+impl BudgetOverrun {
+    pub fn title(&self) -> String {
+        if let Some(model) = &self.unpriced_model {
+            return format!("No pricing for model `{model}`");
+        }
+        match &self.scope {
+            BudgetScope::Thread => "Thread budget reached".to_string(),
+            BudgetScope::Profile(profile_id) => {
+                format!("Daily budget of the `{profile_id}` profile reached")
+            }
+            BudgetScope::Day => "Daily budget reached".to_string(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        if let Some(model) = &self.unpriced_model {
+            let budget = match &self.scope {
+                BudgetScope::Thread => "This thread's budget".to_string(),
+                BudgetScope::Profile(profile_id) => {
+                    format!("The daily budget of the `{profile_id}` profile")
+                }
+                BudgetScope::Day => "The daily budget".to_string(),
+            };
+            return format!(
+                "{budget} limits the estimated cost to ${:.2}, but there is no pricing for \
+                 model `{model}`, so its cost can't be tracked.\n\n\
+                 Continue anyway?",
+                self.budget.max_cost.unwrap_or_default(),
+            );
+        }
+        let subject = match &self.scope {
+            BudgetScope::Thread => "This thread has".to_string(),
+            BudgetScope::Profile(profile_id) => {
+                format!("Threads using the `{profile_id}` profile have today")
+            }
+            BudgetScope::Day => "Agent threads have today".to_string(),
+        };
+        let mut limits = Vec::new();
+        if let Some(max_tokens) = self.budget.max_tokens {
+            limits.push(format!("used {} of {max_tokens} tokens", self.spent.tokens));
+        }
+        if let Some(max_cost) = self.budget.max_cost {
+            limits.push(format!(
+                "spent an estimated ${:.2} of ${max_cost:.2}",
+                self.spent.cost
+            ));
+        }
+        format!(
+            "{subject} {}. The next request is expected to use about {} tokens (${:.2}).\n\n\
+             Continue anyway?",
+            limits.join(" and "),
+            self.next_request.tokens,
+            self.next_request.cost,
+        )
+    }
+}
+
+/// Returns the overrun if spending `next_request` on top of `spent` would
+/// exceed `budget`, or if `budget` limits cost and the model is unpriced.
+pub fn check_budget(
+    scope: BudgetScope,
+    budget: AgentBudget,
+    spent: Spend,
+    next_request: Spend,
+    unpriced_model: Option<&SharedString>,
+) -> Option<BudgetOverrun> {
+    if let Some(model) = unpriced_model
+        && budget.max_cost.is_some()
+    {
+        return Some(BudgetOverrun {
+            scope,
+            budget,
+            spent,
+            next_request,
+            unpriced_model: Some(model.clone()),
+        });
+    }
+
+    let projected = spent + next_request;
+    let exceeds_tokens = budget
+        .max_tokens
+        .is_some_and(|max_tokens| projected.tokens > max_tokens);
+    let exceeds_cost = budget
+        .max_cost
+        .is_some_and(|max_cost| projected.cost > max_cost);
+    (exceeds_tokens || exceeds_cost).then_some(BudgetOverrun {
+        scope,
+        budget,
+        spent,
+        next_request,
+        unpriced_model: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_budget() {
+        let budget = AgentBudget {
+            max_tokens: Some(1000),
+            max_cost: Some(1.0),
+        };
+        let spent = Spend {
+            tokens: 600,
+            cost: 0.5,
+        };
+        let small_request = Spend {
+            tokens: 300,
+            cost: 0.25,
+        };
+        assert_eq!(
+            check_budget(BudgetScope::Thread, budget, spent, small_request, None),
+            None
+        );
+
+        let large_request = Spend {
+            tokens: 500,
+            cost: 0.25,
+        };
+        let overrun = check_budget(BudgetScope::Day, budget, spent, large_request, None).unwrap();
+        assert_eq!(overrun.scope, BudgetScope::Day);
+        assert_eq!(overrun.spent, spent);
+
+        let expensive_request = Spend {
+            tokens: 10,
+            cost: 0.6,
+        };
+        assert!(
+            check_budget(BudgetScope::Thread, budget, spent, expensive_request, None).is_some()
+        );
+
+        assert_eq!(
+            check_budget(
+                BudgetScope::Thread,
+                AgentBudget::default(),
+                spent,
+                large_request,
+                None
+            ),
+            None
+        );
+
+        let unpriced_model = SharedString::from("llama3.2");
+        let overrun = check_budget(
+            BudgetScope::Thread,
+            budget,
+            spent,
+            small_request,
+            Some(&unpriced_model),
+        )
+        .unwrap();
+        assert_eq!(overrun.unpriced_model, Some(unpriced_model.clone()));
+        assert_eq!(overrun.title(), "No pricing for model `llama3.2`");
+
+        let token_budget = AgentBudget {
+            max_tokens: Some(1000),
+            max_cost: None,
+        };
+        assert_eq!(
+            check_budget(
+                BudgetScope::Thread,
+                token_budget,
+                spent,
+                small_request,
+                Some(&unpriced_model)
+            ),
+            None
+        );
+    }
+}
@@ -11,7 +11,7 @@ use settings::{
 };
 use util::ResultExt as _;
 
-use crate::{AgentProfileId, AgentSettings};
+use crate::{AgentBudget, AgentProfileId, AgentSettings};
 
 pub mod builtin_profiles {
     use super::AgentProfileId;
@@ -70,6 +70,10 @@ impl AgentProfile {
         let default_model = base_profile
             .as_ref()
             .and_then(|profile| profile.default_model.clone());
+        let budget = base_profile
+            .as_ref()
+            .map(|profile| profile.budget)
+            .unwrap_or_default();
 
         let profile_settings = AgentProfileSettings {
             name: name.into(),
@@ -77,6 +81,7 @@ impl AgentProfile {
             enable_all_context_servers,
             context_servers,
             default_model,
+            budget,
         };
 
         update_settings_file(fs, cx, {
@@ -109,6 +114,8 @@ pub struct AgentProfileSettings {
     pub context_servers: IndexMap<Arc<str>, ContextServerPreset>,
     /// Default language model to apply when this profile becomes active.
     pub default_model: Option<LanguageModelSelection>,
+    /// Daily budget shared by all threads using this profile.
+    pub budget: AgentBudget,
 }
 
 impl AgentProfileSettings {
@@ -183,6 +190,7 @@ impl AgentProfileSettings {
                     })
                     .collect(),
                 default_model: self.default_model.clone(),
+                budget: self.budget.to_content(),
             },
         );
 
@@ -198,6 +206,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
             enable_all_context_servers,
             context_servers,
             default_model,
+            budget,
         } = content;
 
         Self {
@@ -209,6 +218,7 @@ impl From<AgentProfileContent> for AgentProfileSettings {
                 .map(|(server_id, preset)| (server_id, preset.into()))
                 .collect(),
             default_model,
+            budget: budget.map(Into::into).unwrap_or_default(),
         }
     }
 }
@@ -240,6 +250,7 @@ mod tests {
             enable_all_context_servers,
             context_servers,
             default_model: None,
+            budget: AgentBudget::default(),
         }
     }
 
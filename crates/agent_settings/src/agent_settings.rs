@@ -242,6 +242,8 @@ pub struct AgentSettings {
     pub show_merge_conflict_indicator: bool,
     pub tool_permissions: ToolPermissions,
     pub sandbox_permissions: SandboxPermissions,
+    pub hooks: AgentHooks,
+    pub budgets: AgentBudgets,
 }
 
 impl AgentSettings {
@@ -461,6 +463,159 @@ impl Default for SandboxPermissions {
     }
 }
 
+/// The points in the agent's lifecycle at which user-defined hooks can run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentHookEvent {
+    PreToolUse,
+    PostToolUse,
+    TurnEnd,
+    ThreadStart,
+}
+
+impl AgentHookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PreToolUse => "pre_tool_use",
+            Self::PostToolUse => "post_tool_use",
+            Self::TurnEnd => "turn_end",
+            Self::ThreadStart => "thread_start",
+        }
+    }
+}
+
+impl fmt::Display for AgentHookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// User-defined shell commands run by the agent, grouped by lifecycle event.
+/// See `agent::hooks` for how they are executed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AgentHooks {
+    pub pre_tool_use: Vec<AgentHook>,
+    pub post_tool_use: Vec<AgentHook>,
+    pub turn_end: Vec<AgentHook>,
+    pub thread_start: Vec<AgentHook>,
+}
+
+impl AgentHooks {
+    pub fn for_event(&self, event: AgentHookEvent) -> &[AgentHook] {
+        match event {
+            AgentHookEvent::PreToolUse => &self.pre_tool_use,
+            AgentHookEvent::PostToolUse => &self.post_tool_use,
+            AgentHookEvent::TurnEnd => &self.turn_end,
+            AgentHookEvent::ThreadStart => &self.thread_start,
+        }
+    }
+
+    /// The hooks for `event` that apply to the given tool. Tool filters are
+    /// ignored for events that aren't about a tool call.
+    pub fn matching<'a>(
+        &'a self,
+        event: AgentHookEvent,
+        tool_name: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a AgentHook> + 'a {
+        self.for_event(event)
+            .iter()
+            .filter(move |hook| tool_name.is_none_or(|tool_name| hook.applies_to_tool(tool_name)))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgentHook {
+    pub command: String,
+    /// Tool names this hook is restricted to. Empty means every tool.
+    pub tools: Vec<String>,
+    pub timeout: std::time::Duration,
+}
+
+impl AgentHook {
+    pub const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+    pub fn applies_to_tool(&self, tool_name: &str) -> bool {
+        self.tools.is_empty() || self.tools.iter().any(|tool| tool == tool_name)
+    }
+}
+
+fn compile_hooks(content: Option<settings::AgentHooksContent>) -> AgentHooks {
+    let Some(content) = content else {
+        return AgentHooks::default();
+    };
+
+    let compile = |hooks: Option<Vec<settings::AgentHookContent>>| -> Vec<AgentHook> {
+        hooks
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|hook| !hook.command.trim().is_empty())
+            .map(|hook| AgentHook {
+                command: hook.command,
+                tools: hook.tools.unwrap_or_default(),
+                timeout: hook
+                    .timeout_ms
+                    .map(std::time::Duration::from_millis)
+                    .unwrap_or(AgentHook::DEFAULT_TIMEOUT),
+            })
+            .collect()
+    };
+
+    AgentHooks {
+        pre_tool_use: compile(content.pre_tool_use),
+        post_tool_use: compile(content.post_tool_use),
+        turn_end: compile(content.turn_end),
+        thread_start: compile(content.thread_start),
+    }
+}
+
+/// A spending limit in tokens and estimated cost. Unset limits are unlimited.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AgentBudget {
+    pub max_tokens: Option<u64>,
+    /// In US dollars.
+    pub max_cost: Option<f64>,
+}
+
+impl AgentBudget {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_tokens.is_none() && self.max_cost.is_none()
+    }
+
+    pub fn to_content(&self) -> Option<settings::AgentBudgetContent> {
+        (!self.is_unlimited()).then(|| settings::AgentBudgetContent {
+            max_tokens: self.max_tokens,
+            max_cost: self.max_cost,
+        })
+    }
+}
+
+impl From<settings::AgentBudgetContent> for AgentBudget {
+    fn from(content: settings::AgentBudgetContent) -> Self {
+        Self {
+            max_tokens: content.max_tokens,
+            max_cost: content
+                .max_cost
+                .filter(|cost| cost.is_finite() && *cost >= 0.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AgentBudgets {
+    pub per_thread: AgentBudget,
+    pub per_day: AgentBudget,
+}
+
+fn compile_budgets(content: Option<settings::AgentBudgetsContent>) -> AgentBudgets {
+    let Some(content) = content else {
+        return AgentBudgets::default();
+    };
+    AgentBudgets {
+        per_thread: content.per_thread.map(Into::into).unwrap_or_default(),
+        per_day: content.per_day.map(Into::into).unwrap_or_default(),
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ToolPermissions {
     /// Global default permission when no tool-specific rules or patterns match.
@@ -818,6 +973,8 @@ impl Settings for AgentSettings {
             show_merge_conflict_indicator: agent.show_merge_conflict_indicator.unwrap(),
             tool_permissions: compile_tool_permissions(agent.tool_permissions),
             sandbox_permissions: compile_sandbox_permissions(agent.sandbox_permissions),
+            hooks: compile_hooks(agent.hooks),
+            budgets: compile_budgets(agent.budgets),
         }
     }
 }
@@ -1163,6 +1320,45 @@ mod tests {
         assert_eq!(permissions.default, ToolPermissionMode::Confirm);
     }
 
+    #[test]
+    fn test_hooks_compile_and_match_tools() {
+        let content: settings::AgentHooksContent = serde_json::from_value(serde_json::json!({
+            "pre_tool_use": [
+                { "command": "./scripts/check-generated.sh", "tools": ["edit_file", "write_file"] },
+                { "command": "   " },
+            ],
+            "post_tool_use": [
+                { "command": "cargo fmt", "timeout_ms": 5000 },
+            ],
+        }))
+        .unwrap();
+        let hooks = compile_hooks(Some(content));
+
+        assert_eq!(hooks.pre_tool_use.len(), 1, "blank commands are dropped");
+        assert_eq!(hooks.pre_tool_use[0].timeout, AgentHook::DEFAULT_TIMEOUT);
+        assert_eq!(
+            hooks.post_tool_use[0].timeout,
+            std::time::Duration::from_millis(5000)
+        );
+        assert!(hooks.turn_end.is_empty());
+
+        let matching = |event, tool| {
+            hooks
+                .matching(event, tool)
+                .map(|hook| hook.command.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            matching(AgentHookEvent::PreToolUse, Some("edit_file")),
+            vec!["./scripts/check-generated.sh"]
+        );
+        assert!(matching(AgentHookEvent::PreToolUse, Some("terminal")).is_empty());
+        assert_eq!(
+            matching(AgentHookEvent::PostToolUse, Some("terminal")),
+            vec!["cargo fmt"]
+        );
+    }
+
     #[test]
     fn test_sandbox_permissions_empty() {
         let permissions = compile_sandbox_permissions(None);
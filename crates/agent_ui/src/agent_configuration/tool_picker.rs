@@ -312,6 +312,7 @@ impl PickerDelegate for ToolPickerDelegate {
                             })
                             .collect(),
                         default_model: default_profile.default_model.clone(),
+                        budget: default_profile.budget.to_content(),
                     });
 
                 if let Some(server_id) = server_id {
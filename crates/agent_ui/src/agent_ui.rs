@@ -36,6 +36,7 @@ pub mod thread_worktree_archive;
 pub mod threads_archive_view;
 mod ui;
 mod unicode_confusables;
+mod usage_dashboard;
 
 use std::rc::Rc;
 use std::sync::Arc;
@@ -78,6 +79,7 @@ use crate::agent_registry_ui::AgentRegistryPage;
 pub use crate::inline_assistant::InlineAssistant;
 pub use crate::message_editor::MessageEditorEvent;
 pub use crate::thread_metadata_store::ThreadId;
+use crate::usage_dashboard::AgentUsageDashboard;
 pub use agent_diff::{AgentDiffPane, AgentDiffToolbar};
 pub use conversation_view::open_markdown_in_workspace;
 pub use conversation_view::{ConversationView, StateChange};
@@ -249,6 +251,8 @@ actions!(
         OpenActiveThreadAsMarkdown,
         /// Opens the agent diff view to review changes.
         OpenAgentDiff,
+        /// Opens a dashboard of the agent's token usage and estimated cost.
+        OpenUsageDashboard,
         /// Copies the current thread to the clipboard as JSON for debugging.
         CopyThreadToClipboard,
         /// Loads a thread from the clipboard JSON for debugging.
@@ -653,6 +657,29 @@ pub fn init(
     })
     .detach();
     cx.observe_new(ManageProfilesModal::register).detach();
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(
+            |workspace: &mut Workspace,
+             _: &OpenUsageDashboard,
+             window: &mut Window,
+             cx: &mut Context<Workspace>| {
+                let existing = workspace
+                    .active_pane()
+                    .read(cx)
+                    .items()
+                    .find_map(|item| item.downcast::<AgentUsageDashboard>());
+
+                if let Some(existing) = existing {
+                    existing.update(cx, |dashboard, cx| dashboard.refresh(cx));
+                    workspace.activate_item(&existing, true, true, window, cx);
+                } else {
+                    let dashboard = cx.new(AgentUsageDashboard::new);
+                    workspace.add_item_to_active_pane(Box::new(dashboard), None, true, window, cx);
+                }
+            },
+        );
+    })
+    .detach();
     cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
         workspace.register_action(
             |workspace: &mut Workspace,
@@ -1010,6 +1037,8 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions: Default::default(),
             sandbox_permissions: Default::default(),
+            hooks: Default::default(),
+            budgets: Default::default(),
             show_turn_stats: false,
             show_merge_conflict_indicator: true,
             sidebar_side: Default::default(),
@@ -1841,6 +1841,7 @@ mod tests {
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
+            cumulative_cost: 0.0,
             request_token_usage: Default::default(),
             model: None,
             profile: None,
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::agent_connection_store::AgentConnectionStore;
 
@@ -9,7 +10,7 @@ use crate::thread_metadata_store::{
 };
 use crate::{Agent, ArchiveSelectedThread, DEFAULT_THREAD_TITLE, RemoveSelectedThread};
 
-use agent::ThreadStore;
+use agent::{ThreadSearchQuery, ThreadSearchSnippet, ThreadStore};
 use agent_client_protocol::schema::v1 as acp;
 use agent_settings::AgentSettings;
 use chrono::{DateTime, Datelike as _, Local, NaiveDate, TimeDelta, Utc};
@@ -59,9 +60,13 @@ enum ArchiveListItem {
     Entry {
         thread: ThreadMetadata,
         highlight_positions: Vec<usize>,
+        snippet: Option<ThreadSearchSnippet>,
     },
 }
 
+const CONTENT_SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+const MAX_CONTENT_SEARCH_RESULTS: usize = 200;
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum TimeBucket {
     Today,
@@ -156,6 +161,15 @@ pub struct ThreadsArchiveView {
     archived_branch_names: HashMap<ThreadId, HashMap<PathBuf, String>>,
     _load_branch_names_task: Task<()>,
     thread_filter: ThreadFilter,
+    /// Threads whose saved content matched the current filter text, keyed by
+    /// session, along with the best-matching excerpt.
+    content_matches: HashMap<acp::SessionId, Option<ThreadSearchSnippet>>,
+    /// The query that `content_matches` were found for.
+    content_search_query: ThreadSearchQuery,
+    /// Whether searching the thread database for `content_search_query`
+    /// failed, in which case only titles are matched.
+    content_search_failed: bool,
+    _content_search_task: Task<()>,
 }
 
 impl ThreadsArchiveView {
@@ -178,6 +192,7 @@ impl ThreadsArchiveView {
             cx.subscribe(&filter_editor, |this: &mut Self, _, event, cx| {
                 if let editor::EditorEvent::BufferEdited = event {
                     this.update_items(cx);
+                    this.search_thread_contents(cx);
                 }
             });
 
@@ -230,6 +245,10 @@ impl ThreadsArchiveView {
             archived_branch_names: HashMap::default(),
             _load_branch_names_task: Task::ready(()),
             thread_filter: ThreadFilter::All,
+            content_matches: HashMap::default(),
+            content_search_query: ThreadSearchQuery::default(),
+            content_search_failed: false,
+            _content_search_task: Task::ready(()),
         };
 
         this.update_items(cx);
@@ -291,13 +310,33 @@ impl ThreadsArchiveView {
             .cloned()
             .collect::<Vec<_>>();
 
-        let query = self.filter_editor.read(cx).text(cx).to_lowercase();
+        let search_query = ThreadSearchQuery::parse(&self.filter_editor.read(cx).text(cx));
+        // Only the thread database can evaluate the filters, so keep showing
+        // the previous results until it has answered the new query. When it
+        // couldn't be searched, the filters are ignored instead.
+        let has_search_filters = search_query.has_filters()
+            && !(self.content_search_failed && search_query == self.content_search_query);
+        if has_search_filters && search_query != self.content_search_query {
+            return;
+        }
+        let query = search_query.text.to_lowercase();
         let today = Local::now().naive_local().date();
 
         let mut items = Vec::with_capacity(sessions.len() + 5);
         let mut current_bucket: Option<TimeBucket> = None;
 
         for session in sessions {
+            let content_match = session
+                .session_id
+                .as_ref()
+                .and_then(|session_id| self.content_matches.get(session_id));
+            // Worktree, model and date filters are evaluated by the thread
+            // database, so only threads it returned can satisfy them.
+            if has_search_filters && content_match.is_none() {
+                continue;
+            }
+            let mut snippet = None;
+
             let highlight_positions = if !query.is_empty() {
                 let title = session
                     .title
@@ -317,7 +356,12 @@ impl ThreadsArchiveView {
                             .is_some_and(|name| fuzzy_match_positions(&query, name).is_some())
                     });
                     if !worktree_matched {
-                        continue;
+                        // Finally, fall back to the full-text index of the
+                        // thread's messages, tool calls and touched files.
+                        let Some(content_match) = content_match else {
+                            continue;
+                        };
+                        snippet = content_match.clone();
                     }
                     Vec::new()
                 }
@@ -343,6 +387,7 @@ impl ThreadsArchiveView {
             items.push(ArchiveListItem::Entry {
                 thread: session,
                 highlight_positions,
+                snippet,
             });
         }
 
@@ -380,6 +425,51 @@ impl ThreadsArchiveView {
         cx.notify();
     }
 
+    fn search_thread_contents(&mut self, cx: &mut Context<Self>) {
+        let query = ThreadSearchQuery::parse(&self.filter_editor.read(cx).text(cx));
+        let Some(thread_store) = ThreadStore::try_global(cx).filter(|_| !query.is_empty()) else {
+            self._content_search_task = Task::ready(());
+            self.content_matches.clear();
+            self.content_search_query = query;
+            self.content_search_failed = false;
+            self.update_items(cx);
+            return;
+        };
+        // Every thread that passes the filters has to be known to show the
+        // list, while plain text searches only add matches to it.
+        let limit = (!query.has_filters()).then_some(MAX_CONTENT_SEARCH_RESULTS);
+
+        self._content_search_task = cx.spawn(async move |this, cx| {
+            cx.background_executor()
+                .timer(CONTENT_SEARCH_DEBOUNCE)
+                .await;
+            let search = thread_store.update(cx, |store, cx| {
+                store.search_threads(query.clone(), limit, cx)
+            });
+            let matches = search.await;
+            this.update(cx, |this, cx| {
+                match matches {
+                    Ok(matches) => {
+                        this.content_matches = matches
+                            .into_iter()
+                            .map(|search_match| (search_match.id, search_match.snippet))
+                            .collect();
+                        this.content_search_failed = false;
+                    }
+                    Err(error) => {
+                        log::error!("failed to search thread contents: {error:#}");
+                        this.content_matches.clear();
+                        this.content_search_failed = true;
+                    }
+                }
+                this.content_search_query = query;
+                this.preserve_selection_on_next_update = true;
+                this.update_items(cx);
+            })
+            .log_err();
+        });
+    }
+
     fn reload_branch_names_if_threads_changed(&mut self, cx: &mut Context<Self>) {
         let current_ids: HashSet<ThreadId> = self
             .items
@@ -616,6 +706,7 @@ impl ThreadsArchiveView {
             ArchiveListItem::Entry {
                 thread,
                 highlight_positions,
+                snippet,
             } => {
                 let id = SharedString::from(format!("archive-entry-{}", ix));
 
@@ -671,6 +762,9 @@ impl ThreadsArchiveView {
                     })
                     .timestamp(timestamp)
                     .highlight_positions(highlight_positions.clone())
+                    .when_some(snippet.as_ref(), |this, snippet| {
+                        this.snippet(snippet.text.clone(), snippet.highlight_positions())
+                    })
                     .project_paths(thread.folder_paths().paths_owned())
                     .worktrees(worktrees)
                     .focused(is_focused)
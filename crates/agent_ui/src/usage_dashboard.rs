@@ -0,0 +1,318 @@
+use agent::{Spend, ThreadStore, UsageSummary};
+use agent_settings::{AgentBudget, AgentSettings};
+use chrono::{Local, NaiveDate, TimeDelta};
+use collections::IndexMap;
+use gpui::{App, EventEmitter, FocusHandle, Focusable, SharedString, Task};
+use settings::{Settings, SettingsStore};
+use ui::{Divider, prelude::*};
+use workspace::item::{Item, ItemEvent};
+
+use crate::humanize_token_count;
+
+/// How far back the dashboard reads the usage ledger.
+const HISTORY_DAYS: i64 = 30;
+
+/// Shows the agent's token usage and estimated spend over the last month,
+/// broken down by day, model and profile, next to the configured budgets.
+pub struct AgentUsageDashboard {
+    focus_handle: FocusHandle,
+    summaries: Vec<UsageSummary>,
+    error: Option<SharedString>,
+    load_task: Option<Task<()>>,
+    _settings_subscription: gpui::Subscription,
+}
+
+impl AgentUsageDashboard {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            focus_handle: cx.focus_handle(),
+            summaries: Vec::new(),
+            error: None,
+            load_task: None,
+            _settings_subscription: cx.observe_global::<SettingsStore>(|_, cx| cx.notify()),
+        };
+        this.refresh(cx);
+        this
+    }
+
+    pub fn refresh(&mut self, cx: &mut Context<Self>) {
+        let since = today() - TimeDelta::days(HISTORY_DAYS - 1);
+        let summaries = ThreadStore::global(cx).update(cx, |thread_store, cx| {
+            thread_store.usage_summaries(since, cx)
+        });
+        self.load_task = Some(cx.spawn(async move |this, cx| {
+            let result = summaries.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(summaries) => {
+                        this.summaries = summaries;
+                        this.error = None;
+                    }
+                    Err(error) => {
+                        this.error = Some(format!("Failed to load usage: {error:#}").into());
+                    }
+                }
+                this.load_task = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn spend_since(&self, since: NaiveDate) -> Spend {
+        self.summaries
+            .iter()
+            .filter(|summary| summary.day >= since)
+            .sum()
+    }
+
+    fn spend_by<K: Clone + Eq + std::hash::Hash>(
+        &self,
+        key: impl Fn(&UsageSummary) -> K,
+    ) -> Vec<(K, Spend)> {
+        let mut totals = IndexMap::<K, Spend>::default();
+        for summary in &self.summaries {
+            let total = totals.entry(key(summary)).or_default();
+            *total = *total + Spend::new(&summary.usage, summary.cost);
+        }
+        let mut totals = totals.into_iter().collect::<Vec<_>>();
+        totals.sort_by(|(_, a), (_, b)| b.cost.total_cmp(&a.cost).then(b.tokens.cmp(&a.tokens)));
+        totals
+    }
+
+    fn render_totals(&self) -> impl IntoElement {
+        let today = today();
+        h_flex()
+            .gap_6()
+            .child(render_total("Today", self.spend_since(today)))
+            .child(render_total(
+                "Last 7 days",
+                self.spend_since(today - TimeDelta::days(6)),
+            ))
+            .child(render_total(
+                format!("Last {HISTORY_DAYS} days"),
+                self.spend_since(today - TimeDelta::days(HISTORY_DAYS - 1)),
+            ))
+    }
+
+    fn render_budgets(&self, cx: &App) -> Option<impl IntoElement> {
+        let settings = AgentSettings::get_global(cx);
+        let today = today();
+        let mut rows = Vec::new();
+        if !settings.budgets.per_day.is_unlimited() {
+            rows.push(render_budget_row(
+                "All threads".into(),
+                self.spend_since(today),
+                settings.budgets.per_day,
+            ));
+        }
+        for (profile_id, profile) in &settings.profiles {
+            if profile.budget.is_unlimited() {
+                continue;
+            }
+            let spent = self
+                .summaries
+                .iter()
+                .filter(|summary| summary.day == today && summary.profile == *profile_id)
+                .sum();
+            rows.push(render_budget_row(
+                format!("{} profile", profile.name).into(),
+                spent,
+                profile.budget,
+            ));
+        }
+        if !settings.budgets.per_thread.is_unlimited() {
+            rows.push(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new("Each thread").size(LabelSize::Small))
+                    .child(
+                        Label::new(format_budget(settings.budgets.per_thread))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .into_any_element(),
+            );
+        }
+
+        (!rows.is_empty()).then(|| render_section("Budgets", rows))
+    }
+
+    fn render_breakdowns(&self) -> impl IntoElement {
+        let by_day = self
+            .spend_by(|summary| summary.day)
+            .into_iter()
+            .map(|(day, spend)| render_spend_row(day.format("%a %b %-d").to_string(), spend))
+            .collect::<Vec<_>>();
+        let by_model = self
+            .spend_by(|summary| (summary.provider.clone(), summary.model.clone()))
+            .into_iter()
+            .map(|((provider, model), spend)| {
+                render_spend_row(format!("{provider} / {model}"), spend)
+            })
+            .collect::<Vec<_>>();
+        let by_profile = self
+            .spend_by(|summary| summary.profile.clone())
+            .into_iter()
+            .map(|(profile, spend)| render_spend_row(profile.to_string(), spend))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .gap_4()
+            .child(render_section("By Model", by_model))
+            .child(render_section("By Profile", by_profile))
+            .child(render_section("By Day", by_day))
+    }
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+fn format_spend(spend: Spend) -> String {
+    format!(
+        "{} tokens · ${:.2}",
+        humanize_token_count(spend.tokens),
+        spend.cost
+    )
+}
+
+fn format_budget(budget: AgentBudget) -> String {
+    let mut limits = Vec::new();
+    if let Some(max_tokens) = budget.max_tokens {
+        limits.push(format!("{} tokens", humanize_token_count(max_tokens)));
+    }
+    if let Some(max_cost) = budget.max_cost {
+        limits.push(format!("${max_cost:.2}"));
+    }
+    limits.join(" · ")
+}
+
+fn render_total(label: impl Into<SharedString>, spend: Spend) -> impl IntoElement {
+    v_flex()
+        .gap_0p5()
+        .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+        .child(Headline::new(format!("${:.2}", spend.cost)).size(HeadlineSize::Medium))
+        .child(
+            Label::new(format!("{} tokens", humanize_token_count(spend.tokens)))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+}
+
+fn render_budget_row(label: SharedString, spent: Spend, budget: AgentBudget) -> AnyElement {
+    let exceeded = budget.max_tokens.is_some_and(|max| spent.tokens >= max)
+        || budget.max_cost.is_some_and(|max| spent.cost >= max);
+    h_flex()
+        .gap_2()
+        .child(Label::new(label).size(LabelSize::Small))
+        .child(
+            Label::new(format!(
+                "{} of {} today",
+                format_spend(spent),
+                format_budget(budget)
+            ))
+            .size(LabelSize::Small)
+            .color(if exceeded { Color::Error } else { Color::Muted }),
+        )
+        .into_any_element()
+}
+
+fn render_spend_row(label: String, spend: Spend) -> AnyElement {
+    h_flex()
+        .w_full()
+        .max_w(rems(40.))
+        .justify_between()
+        .child(Label::new(label).size(LabelSize::Small))
+        .child(
+            Label::new(format_spend(spend))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+        .into_any_element()
+}
+
+fn render_section(title: &'static str, rows: Vec<AnyElement>) -> impl IntoElement {
+    v_flex()
+        .gap_1()
+        .child(Headline::new(title).size(HeadlineSize::Small))
+        .children(rows)
+}
+
+impl EventEmitter<ItemEvent> for AgentUsageDashboard {}
+
+impl Focusable for AgentUsageDashboard {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for AgentUsageDashboard {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Agent Usage".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Agent Usage Dashboard Opened")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        f(*event)
+    }
+}
+
+impl Render for AgentUsageDashboard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_loading = self.load_task.is_some();
+        v_flex()
+            .id("agent-usage-dashboard")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .overflow_y_scroll()
+            .p_4()
+            .gap_4()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .w_full()
+                    .justify_between()
+                    .child(Headline::new("Agent Usage").size(HeadlineSize::Large))
+                    .child(
+                        Button::new("refresh-usage", "Refresh")
+                            .disabled(is_loading)
+                            .on_click(cx.listener(|this, _, _, cx| this.refresh(cx))),
+                    ),
+            )
+            .child(
+                Label::new(
+                    "Costs are estimated from published model prices. Usage of models \
+                     with unknown pricing counts toward token totals only.",
+                )
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .when_some(self.error.clone(), |this, error| {
+                this.child(Label::new(error).color(Color::Error))
+            })
+            .child(self.render_totals())
+            .children(self.render_budgets(cx))
+            .child(Divider::horizontal())
+            .map(|this| {
+                if self.summaries.is_empty() && !is_loading {
+                    this.child(
+                        Label::new(format!("No agent usage in the last {HISTORY_DAYS} days."))
+                            .color(Color::Muted),
+                    )
+                } else {
+                    this.child(self.render_breakdowns())
+                }
+            })
+    }
+}
@@ -178,6 +178,15 @@ CREATE TABLE "language_servers" (
 
 CREATE INDEX "index_language_servers_on_project_id" ON "language_servers" ("project_id");
 
+CREATE TABLE "shared_terminals" (
+    "project_id" INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
+    "id" BIGINT NOT NULL,
+    "read_write" BOOLEAN NOT NULL,
+    PRIMARY KEY (project_id, id)
+);
+
+CREATE INDEX "index_shared_terminals_on_project_id" ON "shared_terminals" ("project_id");
+
 CREATE TABLE "project_collaborators" (
     "id" INTEGER PRIMARY KEY AUTOINCREMENT,
     "project_id" INTEGER NOT NULL REFERENCES projects (id) ON DELETE CASCADE,
@@ -410,6 +410,12 @@ CREATE SEQUENCE public.servers_id_seq
 
 ALTER SEQUENCE public.servers_id_seq OWNED BY public.servers.id;
 
+CREATE TABLE public.shared_terminals (
+    project_id integer NOT NULL,
+    id bigint NOT NULL,
+    read_write boolean NOT NULL
+);
+
 CREATE TABLE public.users (
     id integer NOT NULL,
     admin boolean NOT NULL,
@@ -590,6 +596,9 @@ ALTER TABLE ONLY public.rooms
 ALTER TABLE ONLY public.servers
     ADD CONSTRAINT servers_pkey PRIMARY KEY (id);
 
+ALTER TABLE ONLY public.shared_terminals
+    ADD CONSTRAINT shared_terminals_pkey PRIMARY KEY (project_id, id);
+
 ALTER TABLE ONLY public.users
     ADD CONSTRAINT users_pkey PRIMARY KEY (id);
 
@@ -685,6 +694,8 @@ CREATE INDEX index_settings_files_on_project_id ON public.worktree_settings_file
 
 CREATE INDEX index_settings_files_on_project_id_and_wt_id ON public.worktree_settings_files USING btree (project_id, worktree_id);
 
+CREATE INDEX index_shared_terminals_on_project_id ON public.shared_terminals USING btree (project_id);
+
 CREATE INDEX index_users_on_email_address ON public.users USING btree (email_address);
 
 CREATE INDEX index_worktree_diagnostic_summaries_on_project_id ON public.worktree_diagnostic_summaries USING btree (project_id);
@@ -817,6 +828,9 @@ ALTER TABLE ONLY public.room_participants
 ALTER TABLE ONLY public.rooms
     ADD CONSTRAINT rooms_channel_id_fkey FOREIGN KEY (channel_id) REFERENCES public.channels(id) ON DELETE CASCADE;
 
+ALTER TABLE ONLY public.shared_terminals
+    ADD CONSTRAINT shared_terminals_project_id_fkey FOREIGN KEY (project_id) REFERENCES public.projects(id) ON DELETE CASCADE;
+
 ALTER TABLE ONLY public.worktree_diagnostic_summaries
     ADD CONSTRAINT worktree_diagnostic_summaries_project_id_worktree_id_fkey FOREIGN KEY (project_id, worktree_id) REFERENCES public.worktrees(project_id, id) ON DELETE CASCADE;
 
@@ -614,6 +614,108 @@ impl Database {
         .await
     }
 
+    /// Shares one of the host's terminals with the guests of the project, or
+    /// changes what the guests may do with it.
+    pub async fn share_terminal(
+        &self,
+        share: &proto::ShareTerminal,
+        connection: ConnectionId,
+    ) -> Result<TransactionGuard<Vec<ConnectionId>>> {
+        let project_id = ProjectId::from_proto(share.project_id);
+        self.project_transaction(project_id, |tx| async move {
+            // Ensure the update comes from the host.
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .context("no such project")?;
+            if project.host_connection()? != connection {
+                return Err(anyhow!("can't share a terminal of someone else's project"))?;
+            }
+
+            shared_terminal::Entity::insert(shared_terminal::ActiveModel {
+                project_id: ActiveValue::set(project_id),
+                id: ActiveValue::set(share.terminal_id as i64),
+                read_write: ActiveValue::set(
+                    share.permission() == proto::TerminalSharePermission::ReadWrite,
+                ),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    shared_terminal::Column::ProjectId,
+                    shared_terminal::Column::Id,
+                ])
+                .update_columns([shared_terminal::Column::ReadWrite])
+                .to_owned(),
+            )
+            .exec(&*tx)
+            .await?;
+
+            let connection_ids = self.project_guest_connection_ids(project_id, &tx).await?;
+            Ok(connection_ids)
+        })
+        .await
+    }
+
+    /// Stops sharing one of the host's terminals.
+    pub async fn unshare_terminal(
+        &self,
+        unshare: &proto::UnshareTerminal,
+        connection: ConnectionId,
+    ) -> Result<TransactionGuard<Vec<ConnectionId>>> {
+        let project_id = ProjectId::from_proto(unshare.project_id);
+        self.project_transaction(project_id, |tx| async move {
+            // Ensure the update comes from the host.
+            let project = project::Entity::find_by_id(project_id)
+                .one(&*tx)
+                .await?
+                .context("no such project")?;
+            if project.host_connection()? != connection {
+                return Err(anyhow!(
+                    "can't unshare a terminal of someone else's project"
+                ))?;
+            }
+
+            shared_terminal::Entity::delete_many()
+                .filter(
+                    shared_terminal::Column::ProjectId
+                        .eq(project_id)
+                        .and(shared_terminal::Column::Id.eq(unshare.terminal_id as i64)),
+                )
+                .exec(&*tx)
+                .await?;
+
+            let connection_ids = self.project_guest_connection_ids(project_id, &tx).await?;
+            Ok(connection_ids)
+        })
+        .await
+    }
+
+    /// Returns the host connection for input typed by a guest into a shared
+    /// terminal, provided the host let guests write to it.
+    pub async fn host_for_shared_terminal_input(
+        &self,
+        project_id: ProjectId,
+        terminal_id: u64,
+        connection_id: ConnectionId,
+    ) -> Result<ConnectionId> {
+        self.project_transaction(project_id, |tx| async move {
+            let (project, _) = self
+                .access_project(project_id, connection_id, Capability::ReadWrite, &tx)
+                .await?;
+            let shared_terminal =
+                shared_terminal::Entity::find_by_id((project_id, terminal_id as i64))
+                    .one(&*tx)
+                    .await?
+                    .context("no such shared terminal")?;
+            if !shared_terminal.read_write {
+                return Err(anyhow!("the terminal is shared read-only"))?;
+            }
+            project.host_connection()
+        })
+        .await
+        .map(|guard| guard.into_inner())
+    }
+
     /// Updates the worktree settings for the given connection.
     pub async fn update_worktree_settings(
         &self,
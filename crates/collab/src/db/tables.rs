@@ -21,6 +21,7 @@ pub mod project_repository_statuses;
 pub mod room;
 pub mod room_participant;
 pub mod server;
+pub mod shared_terminal;
 pub mod user;
 pub mod worktree;
 pub mod worktree_diagnostic_summary;
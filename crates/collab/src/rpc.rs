@@ -373,6 +373,7 @@ impl Server {
             .add_request_handler(forward_read_only_project_request::<proto::GitGetBranches>)
             .add_request_handler(forward_read_only_project_request::<proto::GetDefaultBranch>)
             .add_request_handler(forward_read_only_project_request::<proto::BlameBufferAtRevision>)
+            .add_request_handler(forward_read_only_project_request::<proto::LoadMergeStages>)
             .add_request_handler(forward_read_only_project_request::<proto::OpenUnstagedDiff>)
             .add_request_handler(forward_read_only_project_request::<proto::OpenUncommittedDiff>)
             .add_request_handler(forward_read_only_project_request::<proto::LspExtExpandMacro>)
@@ -500,6 +501,19 @@ impl Server {
             .add_request_handler(forward_mutating_project_request::<proto::SetIndexText>)
             .add_request_handler(forward_mutating_project_request::<proto::ToggleBreakpoint>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BreakpointsForFile>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::ShareDebugSession>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdateDebugSession>)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UnshareDebugSession>)
+            .add_request_handler(forward_read_only_project_request::<proto::InspectDebugSession>)
+            .add_request_handler(forward_mutating_project_request::<proto::ControlDebugSession>)
+            .add_request_handler(forward_mutating_project_request::<proto::UpdateReviewComment>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::ReviewCommentsForBranch>,
+            )
+            .add_message_handler(share_terminal)
+            .add_message_handler(unshare_terminal)
+            .add_message_handler(broadcast_project_message_from_host::<proto::UpdateSharedTerminal>)
+            .add_request_handler(shared_terminal_input)
             .add_request_handler(forward_mutating_project_request::<proto::OpenCommitMessageBuffer>)
             .add_request_handler(forward_mutating_project_request::<proto::GitDiff>)
             .add_request_handler(forward_mutating_project_request::<proto::GetTreeDiff>)
@@ -2337,6 +2351,64 @@ async fn start_language_server(
     Ok(())
 }
 
+/// Notify the guests of a project that the host shared a terminal with them.
+async fn share_terminal(request: proto::ShareTerminal, session: MessageContext) -> Result<()> {
+    let guest_connection_ids = session
+        .db()
+        .await
+        .share_terminal(&request, session.connection_id)
+        .await?;
+
+    broadcast(
+        Some(session.connection_id),
+        guest_connection_ids.iter().copied(),
+        |connection_id| {
+            session
+                .peer
+                .forward_send(session.connection_id, connection_id, request.clone())
+        },
+    );
+    Ok(())
+}
+
+/// Notify the guests of a project that the host stopped sharing a terminal.
+async fn unshare_terminal(request: proto::UnshareTerminal, session: MessageContext) -> Result<()> {
+    let guest_connection_ids = session
+        .db()
+        .await
+        .unshare_terminal(&request, session.connection_id)
+        .await?;
+
+    broadcast(
+        Some(session.connection_id),
+        guest_connection_ids.iter().copied(),
+        |connection_id| {
+            session
+                .peer
+                .forward_send(session.connection_id, connection_id, request.clone())
+        },
+    );
+    Ok(())
+}
+
+/// Forward what a guest typed into a shared terminal to the host, if the host
+/// shared it read-write.
+async fn shared_terminal_input(
+    request: proto::SharedTerminalInput,
+    response: Response<proto::SharedTerminalInput>,
+    session: MessageContext,
+) -> Result<()> {
+    let project_id = ProjectId::from_proto(request.project_id);
+    let host_connection_id = session
+        .db()
+        .await
+        .host_for_shared_terminal_input(project_id, request.terminal_id, session.connection_id)
+        .await?;
+    let payload = session.forward_request(host_connection_id, request).await?;
+    response.send(payload)?;
+    Ok(())
+}
+
 /// Notify other participants that a language server has changed.
 async fn update_language_server(
     request: proto::UpdateLanguageServer,
@@ -8,6 +8,7 @@ mod channel_guest_tests;
 mod channel_tests;
 mod collab_panel_tests;
 mod db_tests;
+mod debugger_tests;
 mod editor_tests;
 mod following_tests;
 mod git_tests;
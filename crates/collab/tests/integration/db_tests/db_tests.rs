@@ -3,7 +3,7 @@ use crate::test_both_dbs;
 use collab::db::RoomId;
 use collab::db::*;
 use pretty_assertions::assert_eq;
-use rpc::ConnectionId;
+use rpc::{ConnectionId, proto};
 use std::sync::Arc;
 
 test_both_dbs!(
@@ -227,3 +227,87 @@ async fn test_project_count(db: &Arc<Database>) {
         .unwrap();
     assert_eq!(db.project_count_excluding_admins().await.unwrap(), 0);
 }
+
+test_both_dbs!(
+    test_shared_terminal_input,
+    test_shared_terminal_input_postgres,
+    test_shared_terminal_input_sqlite
+);
+
+async fn test_shared_terminal_input(db: &Arc<Database>) {
+    let owner_id = db.create_server("test").await.unwrap().0 as u32;
+    let host = ConnectionId { owner_id, id: 0 };
+    let guest = ConnectionId { owner_id, id: 1 };
+
+    let user1 = db.create_user(false).await.unwrap();
+    let user2 = db.create_user(false).await.unwrap();
+    let room_id = RoomId::from_proto(db.create_room(user1.user_id, host, "").await.unwrap().id);
+    db.call(room_id, user1.user_id, host, user2.user_id, None)
+        .await
+        .unwrap();
+    db.join_room(room_id, user2.user_id, guest).await.unwrap();
+    let project_id = db
+        .share_project(room_id, host, &[], false, false, &[])
+        .await
+        .unwrap()
+        .0;
+    drop(
+        db.join_project(project_id, guest, user2.user_id, None, None)
+            .await
+            .unwrap(),
+    );
+
+    let share = |permission: proto::TerminalSharePermission| proto::ShareTerminal {
+        project_id: project_id.to_proto(),
+        terminal_id: 7,
+        permission: permission.into(),
+        ..Default::default()
+    };
+    let unshare = proto::UnshareTerminal {
+        project_id: project_id.to_proto(),
+        terminal_id: 7,
+    };
+
+    // Only the host can share its terminals.
+    assert!(
+        db.share_terminal(&share(proto::TerminalSharePermission::ReadWrite), guest)
+            .await
+            .is_err()
+    );
+    assert!(
+        db.host_for_shared_terminal_input(project_id, 7, guest)
+            .await
+            .is_err()
+    );
+
+    let guests = db
+        .share_terminal(&share(proto::TerminalSharePermission::ReadOnly), host)
+        .await
+        .unwrap();
+    assert_eq!(*guests, vec![guest]);
+    drop(guests);
+    assert!(
+        db.host_for_shared_terminal_input(project_id, 7, guest)
+            .await
+            .is_err()
+    );
+
+    drop(
+        db.share_terminal(&share(proto::TerminalSharePermission::ReadWrite), host)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(
+        db.host_for_shared_terminal_input(project_id, 7, guest)
+            .await
+            .unwrap(),
+        host
+    );
+
+    drop(db.unshare_terminal(&unshare, host).await.unwrap());
+    assert!(
+        db.host_for_shared_terminal_input(project_id, 7, guest)
+            .await
+            .is_err()
+    );
+}
@@ -0,0 +1,431 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use call::ActiveCall;
+use dap::{
+    Scope, StackFrame, Variable,
+    client::SessionId,
+    requests::{Evaluate, Next, Scopes, SetBreakpoints, StackTrace, Threads, Variables},
+};
+use debugger_ui::debugger_panel::DebugPanel;
+use gpui::{BackgroundExecutor, Entity, TestAppContext};
+use parking_lot::Mutex;
+use project::{
+    Project,
+    debugger::{
+        breakpoint_store::{Breakpoint, BreakpointEditAction, BreakpointWithPosition},
+        session::{OutputToken, Session, ThreadId},
+    },
+};
+use rpc::proto;
+use serde_json::json;
+use text::Point;
+use util::{path, rel_path::rel_path};
+
+use crate::TestServer;
+
+#[gpui::test]
+async fn test_guests_follow_host_debug_session(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+    cx_c: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let client_c = server.create_client(cx_c, "user_c").await;
+    cx_a.update(|cx| {
+        command_palette_hooks::init(cx);
+        dap_adapters::init(cx);
+        debugger_ui::init(cx);
+    });
+
+    // B joins the channel as a member and C as a guest, who can only read
+    // the project.
+    let channel_id = server
+        .make_public_channel("the-channel", &client_a, cx_a)
+        .await;
+    for cx in [&mut *cx_a, &mut *cx_b, &mut *cx_c] {
+        cx.read(ActiveCall::global)
+            .update(cx, |call, cx| call.join_channel(channel_id, cx))
+            .await
+            .unwrap();
+    }
+    executor.run_until_parked();
+    let active_call_a = cx_a.read(ActiveCall::global);
+    active_call_a
+        .update(cx_a, |call, cx| {
+            call.room().unwrap().update(cx, |room, cx| {
+                room.set_participant_role(
+                    client_b.user_id().unwrap(),
+                    proto::ChannelRole::Member,
+                    cx,
+                )
+            })
+        })
+        .await
+        .unwrap();
+
+    client_a
+        .fs()
+        .insert_tree(
+            path!("/project"),
+            json!({ "main.rs": "fn main() {\n    let answer = 42;\n}\n" }),
+        )
+        .await;
+    let (project_a, worktree_id) = client_a.build_local_project(path!("/project"), cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    let project_c = client_c.join_remote_project(project_id, cx_c).await;
+    executor.run_until_parked();
+    assert!(!project_b.read_with(cx_b, |project, cx| project.is_read_only(cx)));
+    assert!(project_c.read_with(cx_c, |project, cx| project.is_read_only(cx)));
+
+    let (workspace_a, cx_a) = client_a.build_workspace(&project_a, cx_a);
+    let debug_panel = workspace_a
+        .update_in(cx_a, |_, window, cx| cx.spawn_in(window, DebugPanel::load))
+        .await
+        .unwrap();
+    workspace_a.update_in(cx_a, |workspace, window, cx| {
+        workspace.add_panel(debug_panel, window, cx);
+    });
+    cx_a.run_until_parked();
+    let workspace_window = cx_a
+        .window_handle()
+        .downcast::<workspace::MultiWorkspace>()
+        .unwrap();
+
+    let next_requests = Arc::new(AtomicUsize::new(0));
+    let evaluate_requests = Arc::new(AtomicUsize::new(0));
+    let breakpoint_requests = Arc::new(Mutex::new(Vec::new()));
+    let session_a = debugger_ui::tests::start_debug_session(&workspace_window, cx_a, {
+        let next_requests = next_requests.clone();
+        let evaluate_requests = evaluate_requests.clone();
+        let breakpoint_requests = breakpoint_requests.clone();
+        move |client| {
+            register_debuggee_handlers(client, evaluate_requests.clone());
+            client.on_request::<Next, _>({
+                let next_requests = next_requests.clone();
+                move |_, _| {
+                    next_requests.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            });
+            client.on_request::<SetBreakpoints, _>({
+                let breakpoint_requests = breakpoint_requests.clone();
+                move |_, args| {
+                    breakpoint_requests.lock().push((
+                        args.source.path.unwrap(),
+                        args.breakpoints
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|breakpoint| breakpoint.line)
+                            .collect::<Vec<_>>(),
+                    ));
+                    Ok(dap::SetBreakpointsResponse {
+                        breakpoints: Vec::new(),
+                    })
+                }
+            });
+        }
+    })
+    .unwrap();
+    cx_a.run_until_parked();
+
+    let client = session_a.read_with(cx_a, |session, _| session.adapter_client().unwrap());
+    client
+        .fake_event(dap::messages::Events::Output(dap::OutputEvent {
+            category: None,
+            output: "hello from the debuggee\n".into(),
+            group: None,
+            variables_reference: None,
+            source: None,
+            line: None,
+            column: None,
+            data: None,
+            location_reference: None,
+        }))
+        .await;
+    client
+        .fake_event(dap::messages::Events::Stopped(dap::StoppedEvent {
+            reason: dap::StoppedEventReason::Pause,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: None,
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+    executor.run_until_parked();
+
+    // Both guests see the host's stack, variables and console output.
+    let session_id = session_a.read_with(cx_a, |session, _| session.session_id());
+    let session_b = guest_session(&project_b, session_id, cx_b);
+    let session_c = guest_session(&project_c, session_id, cx_c);
+    for (session, cx) in [(&session_b, &mut *cx_b), (&session_c, &mut *cx_c)] {
+        assert_eq!(
+            inspect_stopped_thread(session, &executor, cx),
+            (
+                vec!["main".to_string()],
+                vec![("answer".to_string(), "42".to_string())]
+            )
+        );
+        let output = session.read_with(cx, |session, _| {
+            session
+                .output(OutputToken::default())
+                .0
+                .map(|event| event.output.clone())
+                .collect::<Vec<_>>()
+        });
+        assert!(output.contains(&"hello from the debuggee\n".to_string()));
+        assert!(!session.read_with(cx, |session, _| session.can_control()));
+    }
+
+    // Stepping is rejected until the host lets the guests control the
+    // session.
+    let step = || proto::ControlDebugSession {
+        project_id,
+        session_id: session_id.to_proto(),
+        command: "next".into(),
+        arguments: json!({ "threadId": 1 }).to_string(),
+    };
+    assert!(client_b.client().request(step()).await.is_err());
+    let hover_without_control = proto::InspectDebugSession {
+        project_id,
+        session_id: session_id.to_proto(),
+        command: "evaluate".into(),
+        arguments: json!({ "expression": "answer", "context": "hover" }).to_string(),
+    };
+    assert!(
+        client_b
+            .client()
+            .request(hover_without_control)
+            .await
+            .is_err()
+    );
+    executor.run_until_parked();
+    assert_eq!(next_requests.load(Ordering::SeqCst), 0);
+    assert_eq!(evaluate_requests.load(Ordering::SeqCst), 0);
+
+    project_a.update(cx_a, |project, cx| {
+        project.dap_store().update(cx, |dap_store, cx| {
+            dap_store.set_guests_can_control(session_id, true, cx)
+        })
+    });
+    executor.run_until_parked();
+    assert!(session_b.read_with(cx_b, |session, _| session.can_control()));
+    session_b.update(cx_b, |session, cx| {
+        session.step_over(ThreadId(1), dap::SteppingGranularity::Statement, cx)
+    });
+    executor.run_until_parked();
+    assert_eq!(next_requests.load(Ordering::SeqCst), 1);
+    client_b.client().request(step()).await.unwrap();
+    executor.run_until_parked();
+    assert_eq!(next_requests.load(Ordering::SeqCst), 2);
+
+    // Evaluating an expression runs it in the debuggee, so it needs control
+    // even when the guest says it is only hovering over it.
+    let hover = json!({ "expression": "answer", "context": "hover" }).to_string();
+    let evaluation = client_b
+        .client()
+        .request(proto::ControlDebugSession {
+            project_id,
+            session_id: session_id.to_proto(),
+            command: "evaluate".into(),
+            arguments: hover.clone(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&evaluation.body.unwrap()).unwrap()["result"],
+        "42"
+    );
+    assert_eq!(evaluate_requests.load(Ordering::SeqCst), 1);
+
+    // A guest who can only read the project can never step or evaluate.
+    assert!(client_c.client().request(step()).await.is_err());
+    let inspect_hover = proto::InspectDebugSession {
+        project_id,
+        session_id: session_id.to_proto(),
+        command: "evaluate".into(),
+        arguments: hover.clone(),
+    };
+    assert!(client_c.client().request(inspect_hover).await.is_err());
+    let control_hover = proto::ControlDebugSession {
+        project_id,
+        session_id: session_id.to_proto(),
+        command: "evaluate".into(),
+        arguments: hover,
+    };
+    assert!(client_c.client().request(control_hover).await.is_err());
+    executor.run_until_parked();
+    assert_eq!(next_requests.load(Ordering::SeqCst), 2);
+    assert_eq!(evaluate_requests.load(Ordering::SeqCst), 1);
+
+    // A breakpoint set by a guest is sent to the host's debug adapter.
+    breakpoint_requests.lock().clear();
+    let buffer_b = project_b
+        .update(cx_b, |project, cx| {
+            project.open_buffer((worktree_id, rel_path("main.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let breakpoint_store_b = project_b.read_with(cx_b, |project, _| project.breakpoint_store());
+    breakpoint_store_b.update(cx_b, |breakpoint_store, cx| {
+        let position = buffer_b.read(cx).anchor_before(Point::new(1, 0));
+        breakpoint_store.toggle_breakpoint(
+            buffer_b.clone(),
+            BreakpointWithPosition {
+                position,
+                bp: Breakpoint::new_standard(),
+            },
+            BreakpointEditAction::Toggle,
+            cx,
+        );
+    });
+    executor.run_until_parked();
+    assert_eq!(
+        breakpoint_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![(path!("/project/main.rs").to_string(), vec![2])]
+    );
+}
+
+fn register_debuggee_handlers(
+    client: &Arc<dap::client::DebugAdapterClient>,
+    evaluate_requests: Arc<AtomicUsize>,
+) {
+    client.on_request::<Threads, _>(move |_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![dap::Thread {
+                id: 1,
+                name: "main".into(),
+            }],
+        })
+    });
+    client.on_request::<StackTrace, _>(move |_, _| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: vec![StackFrame {
+                id: 1,
+                name: "main".into(),
+                source: Some(dap::Source {
+                    name: Some("main.rs".into()),
+                    path: Some(path!("/project/main.rs").into()),
+                    source_reference: None,
+                    presentation_hint: None,
+                    origin: None,
+                    sources: None,
+                    adapter_data: None,
+                    checksums: None,
+                }),
+                line: 2,
+                column: 1,
+                end_line: None,
+                end_column: None,
+                can_restart: None,
+                instruction_pointer_reference: None,
+                module_id: None,
+                presentation_hint: None,
+            }],
+            total_frames: None,
+        })
+    });
+    client.on_request::<Scopes, _>(move |_, _| {
+        Ok(dap::ScopesResponse {
+            scopes: vec![Scope {
+                name: "Locals".into(),
+                presentation_hint: None,
+                variables_reference: 2,
+                named_variables: None,
+                indexed_variables: None,
+                expensive: false,
+                source: None,
+                line: None,
+                column: None,
+                end_line: None,
+                end_column: None,
+            }],
+        })
+    });
+    client.on_request::<Variables, _>(move |_, _| {
+        Ok(dap::VariablesResponse {
+            variables: vec![Variable {
+                name: "answer".into(),
+                value: "42".into(),
+                type_: None,
+                presentation_hint: None,
+                evaluate_name: None,
+                variables_reference: 0,
+                named_variables: None,
+                indexed_variables: None,
+                memory_reference: None,
+                declaration_location_reference: None,
+                value_location_reference: None,
+            }],
+        })
+    });
+    client.on_request::<Evaluate, _>(move |_, _| {
+        evaluate_requests.fetch_add(1, Ordering::SeqCst);
+        Ok(dap::EvaluateResponse {
+            result: "42".into(),
+            type_: None,
+            presentation_hint: None,
+            variables_reference: 0,
+            named_variables: None,
+            indexed_variables: None,
+            memory_reference: None,
+            value_location_reference: None,
+        })
+    });
+}
+
+fn guest_session(
+    project: &Entity<Project>,
+    session_id: SessionId,
+    cx: &mut TestAppContext,
+) -> Entity<Session> {
+    project.read_with(cx, |project, cx| {
+        let session = project.dap_store().read(cx).session_by_id(session_id);
+        session.expect("the guest should see the host's debug session")
+    })
+}
+
+/// Fetches the stack frames of the stopped thread, and the variables of the
+/// first scope of its top frame, the way the debug panel does.
+fn inspect_stopped_thread(
+    session: &Entity<Session>,
+    executor: &BackgroundExecutor,
+    cx: &mut TestAppContext,
+) -> (Vec<String>, Vec<(String, String)>) {
+    session.update(cx, |session, cx| session.threads(cx));
+    executor.run_until_parked();
+    session.update(cx, |session, cx| session.stack_frames(ThreadId(1), cx).ok());
+    executor.run_until_parked();
+    let frames = session
+        .update(cx, |session, cx| session.stack_frames(ThreadId(1), cx))
+        .unwrap();
+    let frame_id = frames[0].dap.id;
+    session.update(cx, |session, cx| session.scopes(frame_id, cx).to_vec());
+    executor.run_until_parked();
+    let scopes = session.update(cx, |session, cx| session.scopes(frame_id, cx).to_vec());
+    session.update(cx, |session, cx| {
+        session.variables(scopes[0].variables_reference, cx)
+    });
+    executor.run_until_parked();
+    let variables = session.update(cx, |session, cx| {
+        session.variables(scopes[0].variables_reference, cx)
+    });
+    (
+        frames.into_iter().map(|frame| frame.dap.name).collect(),
+        variables
+            .into_iter()
+            .map(|variable| (variable.name, variable.value))
+            .collect(),
+    )
+}
@@ -22,9 +22,11 @@ use gpui::{
 use project::{
     ProjectPath,
     git_store::{CommitDataState, Repository},
+    review_comment_store::{ReviewCommentKey, ReviewCommentStore},
 };
 use rand::{SeedableRng, rngs::StdRng};
 use serde_json::json;
+use text::Point;
 
 use util::{path, rel_path::rel_path};
 use workspace::{MultiWorkspace, Workspace};
@@ -1462,3 +1464,117 @@ async fn test_load_commit_template_over_collab(
 
     assert_eq!(commit_template.template, "feat: add awesome feature");
 }
+
+#[gpui::test]
+async fn test_review_comments_sync_to_guests(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            path!("/project"),
+            json!({ ".git": {}, "a.txt": "one\ntwo\nthree\n" }),
+        )
+        .await;
+    client_a
+        .fs()
+        .set_branch_name(Path::new(path!("/project/.git")), Some("main"));
+
+    let (project_a, worktree_id) = client_a.build_local_project(path!("/project"), cx_a).await;
+    executor.run_until_parked();
+    let key = ReviewCommentKey {
+        repository: Path::new(path!("/project")).into(),
+        branch: Some("main".into()),
+    };
+    let store_a = project_a.read_with(cx_a, |project, _| project.review_comment_store());
+
+    // A comment left before sharing reaches the guest once it joins.
+    let buffer_a = project_a
+        .update(cx_a, |p, cx| {
+            p.open_buffer((worktree_id, rel_path("a.txt")), cx)
+        })
+        .await
+        .unwrap();
+    let host_comment = store_a
+        .update(cx_a, |store, cx| {
+            let range = buffer_a.read_with(cx, |buffer, _| {
+                buffer.anchor_after(Point::new(0, 0))..buffer.anchor_before(Point::new(0, 3))
+            });
+            store.add_comment(&buffer_a, range, "from the host".into(), cx)
+        })
+        .unwrap();
+
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let store_b = project_b.read_with(cx_b, |project, _| project.review_comment_store());
+    let bodies = |store: &Entity<ReviewCommentStore>, cx: &mut TestAppContext| {
+        store.read_with(cx, |store, cx| {
+            store
+                .comments(&key, cx)
+                .into_iter()
+                .map(|comment| (comment.lines.start_row, comment.body))
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(
+        store_b.read_with(cx_b, |store, cx| store.active_key(cx)),
+        Some(key.clone())
+    );
+    assert_eq!(bodies(&store_b, cx_b), [(0, "from the host".to_string())]);
+
+    // Comments added by the guest are stored by the host and sent back to
+    // every guest.
+    let buffer_b = project_b
+        .update(cx_b, |p, cx| {
+            p.open_buffer((worktree_id, rel_path("a.txt")), cx)
+        })
+        .await
+        .unwrap();
+    let guest_comment = store_b
+        .update(cx_b, |store, cx| {
+            let range = buffer_b.read_with(cx, |buffer, _| {
+                buffer.anchor_after(Point::new(2, 0))..buffer.anchor_before(Point::new(2, 5))
+            });
+            store.add_comment(&buffer_b, range, "from the guest".into(), cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+    let expected = [
+        (0, "from the host".to_string()),
+        (2, "from the guest".to_string()),
+    ];
+    assert_eq!(bodies(&store_a, cx_a), expected);
+    assert_eq!(bodies(&store_b, cx_b), expected);
+
+    // Edits and removals by the guest go through the host too.
+    store_b.update(cx_b, |store, cx| {
+        assert!(store.update_comment(host_comment, "edited by the guest".into(), cx));
+        assert!(store.remove_comment(guest_comment, cx));
+    });
+    executor.run_until_parked();
+    let expected = [(0, "edited by the guest".to_string())];
+    assert_eq!(bodies(&store_a, cx_a), expected);
+    assert_eq!(bodies(&store_b, cx_b), expected);
+
+    // The host's own edits are broadcast to the guest.
+    store_a.update(cx_a, |store, cx| {
+        assert!(store.remove_comment(host_comment, cx));
+    });
+    executor.run_until_parked();
+    assert!(bodies(&store_b, cx_b).is_empty());
+}
@@ -15,8 +15,8 @@ use git::{
     status::{FileStatus, StatusCode, TrackedStatus, UnmergedStatus, UnmergedStatusCode},
 };
 use gpui::{
-    App, BackgroundExecutor, Entity, Modifiers, MouseButton, MouseDownEvent, TestAppContext,
-    UpdateGlobal, px, size,
+    App, AppContext as _, BackgroundExecutor, Entity, Modifiers, MouseButton, MouseDownEvent,
+    TestAppContext, UpdateGlobal, px, size,
 };
 use language::{
     Diagnostic, DiagnosticEntry, DiagnosticSourceKind, FakeLspAdapter, Language, LanguageConfig,
@@ -31,8 +31,10 @@ use project::{
     DiagnosticSummary, HoverBlockKind, Project, ProjectPath,
     lsp_store::{FormatTrigger, LspFormatTarget, SymbolLocation},
     search::{SearchQuery, SearchResult},
+    shared_terminal_store::{SharedTerminalStore, TerminalSharePermission, terminal_id},
 };
 use rand::prelude::*;
+use rpc::proto;
 use serde_json::json;
 use settings::{LanguageServerFormatterSpecifier, PrettierSettingsContent, SettingsStore};
 use std::{
@@ -46,8 +48,12 @@ use std::{
     },
     time::Duration,
 };
+use terminal::{
+    Terminal, TerminalBuilder,
+    terminal_settings::{AlternateScroll, CursorShape},
+};
 use unindent::Unindent as _;
-use util::{path, rel_path::rel_path, uri};
+use util::{path, paths::PathStyle, rel_path::rel_path, uri};
 use workspace::{Pane, ParticipantLocation};
 
 #[ctor::ctor(unsafe)]
@@ -5557,7 +5563,9 @@ async fn test_project_search(
                     "Unexpectedly reached search limit in tests. If you do want to assert limit-reached, change this panic call."
                 )
             }
-            SearchResult::WaitingForScan | SearchResult::Searching => {}
+            SearchResult::PatternTooComplex
+            | SearchResult::WaitingForScan
+            | SearchResult::Searching => {}
         };
     }
 
@@ -7578,3 +7586,172 @@ async fn test_guest_can_rejoin_shared_project_after_leaving_call(
         );
     })
 }
+
+#[gpui::test]
+async fn test_shared_terminals(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+    cx_c: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    let client_c = server.create_client(cx_c, "user_c").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b), (&client_c, cx_c)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(path!("/a"), json!({ "a.txt": "" }))
+        .await;
+    let (project_a, _) = client_a.build_local_project(path!("/a"), cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| call.share_project(project_a.clone(), cx))
+        .await
+        .unwrap();
+    let project_b = client_b.join_remote_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    // The host's terminal runs no process, so what the guests type in it is
+    // captured the way a mirror's input is.
+    let (input_tx, mut input_rx) = mpsc::unbounded();
+    let terminal_a = cx_a.new(|cx| {
+        let mut terminal = TerminalBuilder::new_display_only(
+            CursorShape::default(),
+            AlternateScroll::On,
+            None,
+            0,
+            cx.background_executor(),
+            PathStyle::local(),
+        )
+        .subscribe(cx);
+        terminal.set_mirror_input(Some(input_tx));
+        terminal
+    });
+    terminal_a.update(cx_a, |terminal, cx| {
+        let output = (0..100)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        terminal.write_output(output.as_bytes(), cx);
+    });
+    let terminal_id = terminal_id(&terminal_a);
+    let store_a = project_a.read_with(cx_a, |project, _| project.shared_terminal_store());
+    store_a
+        .update(cx_a, |store, cx| {
+            store.share_terminal(&terminal_a, TerminalSharePermission::ReadOnly, cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+
+    // The guest is sent the screen along with the scrollback.
+    let store_b = project_b.read_with(cx_b, |project, _| project.shared_terminal_store());
+    let terminal_b = mirrored_terminal(&store_b, cx_b);
+    assert_eq!(
+        terminal_text(&terminal_b, cx_b),
+        terminal_text(&terminal_a, cx_a)
+    );
+    assert!(terminal_text(&terminal_b, cx_b).starts_with("line 0\nline 1\n"));
+    assert_eq!(
+        store_b.read_with(cx_b, |store, _| store.permission(&terminal_b)),
+        Some(TerminalSharePermission::ReadOnly)
+    );
+
+    terminal_a.update(cx_a, |terminal, cx| {
+        terminal.write_output(b"\nline 100", cx)
+    });
+    executor.run_until_parked();
+    executor.advance_clock(Duration::from_millis(100));
+    executor.run_until_parked();
+    assert_eq!(
+        terminal_text(&terminal_b, cx_b),
+        terminal_text(&terminal_a, cx_a)
+    );
+    assert!(terminal_text(&terminal_b, cx_b).ends_with("line 99\nline 100"));
+
+    // What is typed in a read-only terminal isn't sent, and the server rejects
+    // it when it is.
+    terminal_b.update(cx_b, |terminal, _| terminal.input(b"ls\r".to_vec()));
+    let input = client_b
+        .client()
+        .request(proto::SharedTerminalInput {
+            project_id,
+            terminal_id,
+            input: b"ls\r".to_vec(),
+        })
+        .await;
+    assert!(input.is_err());
+    executor.run_until_parked();
+    assert!(input_rx.try_next().is_err());
+
+    // Once the terminal is shared read-write, the guest's input reaches the
+    // host.
+    store_a
+        .update(cx_a, |store, cx| {
+            store.share_terminal(&terminal_a, TerminalSharePermission::ReadWrite, cx)
+        })
+        .unwrap();
+    executor.run_until_parked();
+    assert_eq!(
+        store_b.read_with(cx_b, |store, _| store.permission(&terminal_b)),
+        Some(TerminalSharePermission::ReadWrite)
+    );
+    terminal_b.update(cx_b, |terminal, _| terminal.input(b"ls\r".to_vec()));
+    executor.run_until_parked();
+    assert_eq!(input_rx.try_next().unwrap(), Some(b"ls\r".to_vec()));
+
+    // A guest joining later is sent the terminals that are already shared.
+    let project_c = client_c.join_remote_project(project_id, cx_c).await;
+    executor.run_until_parked();
+    let store_c = project_c.read_with(cx_c, |project, _| project.shared_terminal_store());
+    let terminal_c = mirrored_terminal(&store_c, cx_c);
+    assert_eq!(
+        terminal_text(&terminal_c, cx_c),
+        terminal_text(&terminal_a, cx_a)
+    );
+    assert_eq!(
+        store_c.read_with(cx_c, |store, _| store.permission(&terminal_c)),
+        Some(TerminalSharePermission::ReadWrite)
+    );
+
+    // Unsharing the terminal removes the guests' mirrors of it.
+    store_a.update(cx_a, |store, cx| store.unshare_terminal(terminal_id, cx));
+    executor.run_until_parked();
+    for (store, cx) in [(&store_b, &mut *cx_b), (&store_c, &mut *cx_c)] {
+        assert_eq!(
+            store.read_with(cx, |store, _| store.mirrored_terminals().count()),
+            0
+        );
+    }
+    assert_eq!(
+        store_b.read_with(cx_b, |store, _| store.permission(&terminal_b)),
+        None
+    );
+}
+
+fn mirrored_terminal(
+    store: &Entity<SharedTerminalStore>,
+    cx: &mut TestAppContext,
+) -> Entity<Terminal> {
+    store.read_with(cx, |store, _| {
+        let terminals = store.mirrored_terminals().collect::<Vec<_>>();
+        assert_eq!(terminals.len(), 1);
+        terminals[0].clone()
+    })
+}
+
+fn terminal_text(terminal: &Entity<Terminal>, cx: &mut TestAppContext) -> String {
+    terminal.read_with(cx, |terminal, _| {
+        terminal
+            .get_content()
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    })
+}
@@ -162,6 +162,8 @@ impl DebugTaskDefinition {
             label: self.label.clone(),
             adapter: self.adapter.clone().into(),
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             tcp_connection: self.tcp_connection.clone(),
             config: self.config.clone(),
         }
@@ -446,6 +448,8 @@ impl DebugAdapter for FakeAdapter {
             adapter: zed_scenario.adapter,
             label: zed_scenario.label,
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             config,
             tcp_connection: None,
         })
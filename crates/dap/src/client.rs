@@ -99,15 +99,27 @@ impl DebugAdapterClient {
     /// Note: This function will block until a response is sent back from the adapter
     pub async fn request<R: Request>(&self, arguments: R::Arguments) -> Result<R::Response> {
         let serialized_arguments = serde_json::to_value(arguments)?;
+        let body = self
+            .request_by_command(R::COMMAND, Some(serialized_arguments))
+            .await?;
+        response_from_body::<R>(body)
+    }
 
+    /// Send a request whose arguments were serialized elsewhere, e.g. by a guest of a shared
+    /// project, and get the body of the response back as it is
+    pub async fn request_by_command(
+        &self,
+        command: &str,
+        arguments: Option<serde_json::Value>,
+    ) -> Result<Option<serde_json::Value>> {
         let (callback_tx, callback_rx) = oneshot::channel::<Result<Response>>();
 
         let sequence_id = self.next_sequence_id();
 
         let request = crate::messages::Request {
             seq: sequence_id,
-            command: R::COMMAND.to_string(),
-            arguments: Some(serialized_arguments),
+            command: command.to_string(),
+            arguments,
         };
         self.transport_delegate
             .pending_requests
@@ -117,15 +129,13 @@ impl DebugAdapterClient {
         log::debug!(
             "Client {} send `{}` request with sequence_id: {}",
             self.id.0,
-            R::COMMAND,
+            command,
             sequence_id
         );
         log::debug!("  request: {request:?}");
 
         self.send_message(Message::Request(request)).await?;
 
-        let command = R::COMMAND.to_string();
-
         let response = callback_rx.await??;
         log::debug!(
             "Client {} received response for: `{}` sequence_id: {}",
@@ -136,19 +146,7 @@ impl DebugAdapterClient {
         log::debug!("  response: {response:?}");
 
         match response.success {
-            true => {
-                if let Some(json) = response.body {
-                    Ok(serde_json::from_value(json)?)
-                // Note: dap types configure themselves to return `None` when an empty object is received,
-                // which then fails here...
-                } else if let Ok(result) =
-                    serde_json::from_value(serde_json::Value::Object(Default::default()))
-                {
-                    Ok(result)
-                } else {
-                    Ok(serde_json::from_value(Default::default())?)
-                }
-            }
+            true => Ok(response.body),
             false => anyhow::bail!("Request failed: {}", response.message.unwrap_or_default()),
         }
     }
@@ -255,6 +253,20 @@ impl DebugAdapterClient {
     }
 }
 
+/// Deserializes the body of a successful response to a request of type `R`.
+pub fn response_from_body<R: Request>(body: Option<serde_json::Value>) -> Result<R::Response> {
+    if let Some(json) = body {
+        Ok(serde_json::from_value(json)?)
+    // Note: dap types configure themselves to return `None` when an empty object is received,
+    // which then fails here...
+    } else if let Ok(result) = serde_json::from_value(serde_json::Value::Object(Default::default()))
+    {
+        Ok(result)
+    } else {
+        Ok(serde_json::from_value(Default::default())?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +129,8 @@ impl DebugAdapter for CodeLldbDebugAdapter {
             label: zed_scenario.label,
             config: configuration,
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             tcp_connection: None,
         })
     }
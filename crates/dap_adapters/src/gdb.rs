@@ -66,6 +66,8 @@ impl DebugAdapter for GdbDebugAdapter {
             adapter: zed_scenario.adapter,
             label: zed_scenario.label,
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             config: serde_json::Value::Object(obj),
             tcp_connection: None,
         })
@@ -431,6 +431,8 @@ impl DebugAdapter for GoDebugAdapter {
             adapter: zed_scenario.adapter,
             label: zed_scenario.label,
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             config: args,
             tcp_connection: None,
         })
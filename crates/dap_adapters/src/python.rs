@@ -475,6 +475,8 @@ impl DebugAdapter for PythonDebugAdapter {
             label: zed_scenario.label,
             config: args,
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             tcp_connection: None,
         })
     }
@@ -1095,6 +1095,7 @@ impl SearchableItem for DapLogView {
             replacement: false,
             selection: false,
             select_all: true,
+            syntax_scope: false,
         }
     }
     fn active_match_index(
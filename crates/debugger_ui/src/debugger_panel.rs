@@ -5,9 +5,10 @@ use crate::session::running::breakpoint_list::BreakpointList;
 
 use crate::{
     ClearAllBreakpoints, Continue, CopyDebugAdapterArguments, Detach, FocusBreakpointList,
-    FocusConsole, FocusFrames, FocusLoadedSources, FocusModules, FocusTerminal, FocusVariables,
-    NewProcessModal, NewProcessMode, Pause, RerunSession, StepInto, StepOut, StepOver, Stop,
-    ToggleExpandItem, ToggleSessionPicker, ToggleThreadPicker, persistence, spawn_task_or_modal,
+    FocusConsole, FocusDisassembly, FocusFrames, FocusLoadedSources, FocusModules, FocusTerminal,
+    FocusThreads, FocusVariables, NewProcessModal, NewProcessMode, Pause, RerunSession, StepInto,
+    StepOut, StepOver, Stop, StopCompound, ToggleExpandItem, ToggleSessionPicker,
+    ToggleThreadPicker, persistence, spawn_task_or_modal,
 };
 use anyhow::{Context as _, Result, anyhow};
 use collections::IndexMap;
@@ -24,13 +25,15 @@ use gpui::{
 
 use itertools::Itertools as _;
 use language::Buffer;
+use project::debugger::dap_store::DapStoreEvent;
 use project::debugger::session::{Session, SessionQuirks, SessionState, SessionStateEvent};
+use project::task_store::TaskStore;
 use project::{DebugScenarioContext, Fs, ProjectPath, TaskSourceKind, WorktreeId};
 use project::{Project, debugger::session::ThreadStatus};
 use rpc::proto::{self};
 use settings::Settings;
 use std::sync::Arc;
-use task::{DebugScenario, SharedTaskContext};
+use task::{DebugCompound, DebugScenario, SharedTaskContext, TaskContext};
 
 use ui::{
     ButtonLike, ContextMenu, Divider, ElevationIndex, PopoverMenu, PopoverMenuHandle, SplitButton,
@@ -41,6 +44,7 @@ use util::rel_path::RelPath;
 use util::{ResultExt, debug_panic, maybe};
 use workspace::SplitDirection;
 use workspace::item::SaveOptions;
+use workspace::notifications::NotifyTaskExt as _;
 use workspace::{
     Item, Pane, Workspace,
     dock::{DockPosition, Panel, PanelEvent},
@@ -70,8 +74,10 @@ pub struct DebugPanel {
     pub(crate) session_picker_menu_handle: PopoverMenuHandle<ContextMenu>,
     fs: Arc<dyn Fs>,
     is_zoomed: bool,
-    _subscriptions: [Subscription; 1],
+    _subscriptions: [Subscription; 2],
     breakpoint_list: Entity<BreakpointList>,
+    /// Sessions started together from compound debug scenarios.
+    compounds: Vec<Vec<WeakEntity<Session>>>,
 }
 
 impl DebugPanel {
@@ -94,6 +100,15 @@ impl DebugPanel {
                 },
             );
 
+            let dap_store = project.read(cx).dap_store();
+            let dap_store_subscription = cx.subscribe_in(
+                &dap_store,
+                window,
+                |this: &mut DebugPanel, _, event, window, cx| {
+                    this.handle_dap_store_event(event, window, cx);
+                },
+            );
+
             Self {
                 sessions_with_children: Default::default(),
                 active_session: None,
@@ -112,8 +127,9 @@ impl DebugPanel {
                 thread_picker_menu_handle,
                 session_picker_menu_handle,
                 is_zoomed: false,
-                _subscriptions: [focus_subscription],
+                _subscriptions: [focus_subscription, dap_store_subscription],
                 debug_scenario_scheduled_last: true,
+                compounds: Vec::new(),
             }
         })
     }
@@ -184,9 +200,170 @@ impl DebugPanel {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.spawn_session(
+            scenario,
+            task_context,
+            active_buffer,
+            worktree_id,
+            window,
+            cx,
+        );
+    }
+
+    /// Starts all scenarios of `compound` together, after running its
+    /// pre-launch task.
+    pub fn start_compound(
+        &mut self,
+        compound: DebugCompound,
+        task_context: SharedTaskContext,
+        worktree_id: Option<WorktreeId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let workspace = self.workspace.clone();
+        let task_store = self.project.read(cx).task_store().downgrade();
+        let task = cx.spawn_in(window, {
+            let workspace = workspace.clone();
+            async move |this, cx| {
+                let scenarios = task_store.read_with(cx, |task_store, cx| {
+                    task_store
+                        .task_inventory()
+                        .map(|inventory| {
+                            inventory
+                                .read(cx)
+                                .debug_scenarios_from_settings(worktree_id)
+                                .map(|(_, scenario)| scenario)
+                                .collect::<Vec<_>>()
+                        })
+                        .unwrap_or_default()
+                })?;
+                let scenarios = compound
+                    .scenarios
+                    .iter()
+                    .map(|label| {
+                        scenarios
+                            .iter()
+                            .find(|scenario| scenario.label == *label)
+                            .cloned()
+                            .with_context(|| {
+                                format!(
+                                    "Debug compound `{}` refers to unknown scenario `{label}`",
+                                    compound.label
+                                )
+                            })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                if let Some(pre_launch_task) = compound.pre_launch_task {
+                    run_task_by_label(
+                        pre_launch_task,
+                        workspace,
+                        task_store,
+                        None,
+                        worktree_id,
+                        &task_context,
+                        cx,
+                    )
+                    .await
+                    .with_context(|| format!("Pre-launch task of `{}` failed", compound.label))?;
+                }
+
+                this.update_in(cx, |this, window, cx| {
+                    let sessions = scenarios
+                        .into_iter()
+                        .filter_map(|scenario| {
+                            this.spawn_session(
+                                scenario,
+                                task_context.clone(),
+                                None,
+                                worktree_id,
+                                window,
+                                cx,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    this.register_compound(sessions, compound.stop_all, window, cx);
+                })
+            }
+        });
+        task.detach_and_notify_err(workspace, window, cx);
+    }
+
+    fn register_compound(
+        &mut self,
+        sessions: Vec<Entity<Session>>,
+        stop_all: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if stop_all {
+            for session in &sessions {
+                cx.subscribe_in(
+                    session,
+                    window,
+                    |this, session, event: &SessionStateEvent, _, cx| {
+                        if let SessionStateEvent::Shutdown = event {
+                            let session_id = session.read(cx).session_id();
+                            this.stop_compound(session_id, cx);
+                        }
+                    },
+                )
+                .detach();
+            }
+        }
+
+        self.compounds.retain(|sessions| {
+            sessions.iter().any(|session| {
+                session
+                    .upgrade()
+                    .is_some_and(|session| !session.read(cx).is_terminated())
+            })
+        });
+        self.compounds
+            .push(sessions.iter().map(|session| session.downgrade()).collect());
+        cx.notify();
+    }
+
+    fn compound_index(&self, session_id: SessionId, cx: &App) -> Option<usize> {
+        self.compounds.iter().position(|sessions| {
+            sessions.iter().any(|session| {
+                session
+                    .upgrade()
+                    .is_some_and(|session| session.read(cx).session_id() == session_id)
+            })
+        })
+    }
+
+    /// Stops every session started from the same compound scenario as
+    /// `session_id`.
+    pub(crate) fn stop_compound(&mut self, session_id: SessionId, cx: &mut Context<Self>) {
+        let Some(ix) = self.compound_index(session_id, cx) else {
+            return;
+        };
+        for session in self.compounds.remove(ix) {
+            if let Some(session) = session.upgrade() {
+                session.update(cx, |session, cx| {
+                    if !session.is_terminated() {
+                        session.shutdown(cx).detach();
+                    }
+                });
+            }
+        }
+        cx.notify();
+    }
+
+    fn spawn_session(
+        &mut self,
+        scenario: DebugScenario,
+        task_context: SharedTaskContext,
+        active_buffer: Option<Entity<Buffer>>,
+        worktree_id: Option<WorktreeId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Session>> {
         let dap_store = self.project.read(cx).dap_store();
         let Some(adapter) = DapRegistry::global(cx).adapter(&scenario.adapter) else {
-            return;
+            return None;
         };
         let quirks = SessionQuirks {
             compact: adapter.compact_child_session(),
@@ -214,9 +391,52 @@ impl DebugPanel {
             .or_else(|| self.project.read(cx).visible_worktrees(cx).next())
         else {
             log::debug!("Could not find a worktree to spawn the debug session in");
-            return;
+            return None;
         };
 
+        if let Some(post_debug_task) = scenario.post_debug_task.clone() {
+            let mut post_debug_task = Some(post_debug_task);
+            let task_context = task_context.clone();
+            let active_buffer = active_buffer.clone();
+            cx.subscribe_in(
+                &session,
+                window,
+                move |this, session, event: &SessionStateEvent, window, cx| {
+                    // Sessions that failed to boot never started the debuggee.
+                    if !matches!(event, SessionStateEvent::Shutdown)
+                        || session.read(cx).is_building()
+                    {
+                        return;
+                    }
+                    let Some(post_debug_task) = post_debug_task.take() else {
+                        return;
+                    };
+                    let workspace = this.workspace.clone();
+                    let task_store = this.project.read(cx).task_store().downgrade();
+                    let task_context = task_context.clone();
+                    let active_buffer = active_buffer.clone();
+                    cx.spawn_in(window, {
+                        let workspace = workspace.clone();
+                        async move |_, cx| {
+                            run_task_by_label(
+                                post_debug_task,
+                                workspace,
+                                task_store,
+                                active_buffer,
+                                worktree_id,
+                                &task_context,
+                                cx,
+                            )
+                            .await
+                            .context("Post-debug task failed")
+                        }
+                    })
+                    .detach_and_notify_err(workspace, window, cx);
+                },
+            )
+            .detach();
+        }
+
         self.debug_scenario_scheduled_last = true;
         if let Some(inventory) = self
             .project
@@ -243,7 +463,7 @@ impl DebugPanel {
                 let definition = debug_session
                     .update_in(cx, |debug_session, window, cx| {
                         debug_session.running_state().update(cx, |running, cx| {
-                            if scenario.build.is_some() {
+                            if scenario.build.is_some() || scenario.pre_launch_task.is_some() {
                                 running.scenario = Some(scenario.clone());
                                 running.scenario_context = Some(DebugScenarioContext {
                                     active_buffer: active_buffer
@@ -297,10 +517,53 @@ impl DebugPanel {
             SessionState::Booting(state_task) => {
                 *state_task = Some(boot_task);
             }
-            SessionState::Running(_) => {
+            SessionState::Running(_) | SessionState::Collab(_) => {
                 debug_panic!("Session state should be in building because we are just starting it");
             }
         });
+        Some(session)
+    }
+
+    /// Starts sessions that were requested without a window, e.g. by the agent.
+    fn handle_dap_store_event(
+        &mut self,
+        event: &DapStoreEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            DapStoreEvent::SessionRequested {
+                scenario,
+                task_context,
+                worktree_id,
+                sender,
+            } => {
+                let scenario = scenario.clone();
+                let task_context = task_context.clone();
+                let worktree_id = *worktree_id;
+                let mut sender = sender.clone();
+                cx.defer_in(window, move |this, window, cx| {
+                    if let Some(session) =
+                        this.spawn_session(scenario, task_context, None, worktree_id, window, cx)
+                    {
+                        sender.try_send(session).ok();
+                    }
+                });
+            }
+            DapStoreEvent::HostSessionShared(session) => {
+                let session = session.clone();
+                cx.spawn_in(window, async move |this, cx| {
+                    let debug_session = register_session_inner(&this, session, cx).await?;
+                    this.update_in(cx, |this, window, cx| {
+                        if this.active_session.is_none() {
+                            this.activate_session(debug_session, window, cx);
+                        }
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+            _ => {}
+        }
     }
 
     pub(crate) fn rerun_last_session(
@@ -386,6 +649,7 @@ impl DebugPanel {
         let adapter = curr_session.read(cx).adapter();
         let binary = curr_session.read(cx).binary().cloned().unwrap();
         let task_context = curr_session.read(cx).task_context().clone();
+        let instruction_breakpoints = curr_session.read(cx).instruction_breakpoints().clone();
 
         let curr_session_id = curr_session.read(cx).session_id();
         self.sessions_with_children
@@ -401,6 +665,7 @@ impl DebugPanel {
                 let session = dap_store.new_session(label, adapter, task_context, None, quirks, cx);
 
                 let task = session.update(cx, |session, cx| {
+                    session.restore_instruction_breakpoints(instruction_breakpoints);
                     session.boot(binary, worktree, dap_store_handle.downgrade(), cx)
                 });
                 (session, task)
@@ -615,6 +880,10 @@ impl DebugPanel {
         let active_session = self.active_session.clone();
         let focus_handle = self.focus_handle.clone();
         let is_side = self.position(window, cx).axis() == gpui::Axis::Horizontal;
+        let is_compound = active_session.as_ref().is_some_and(|session| {
+            let session_id = session.read(cx).session_id(cx);
+            self.compound_index(session_id, cx).is_some()
+        });
         let div = if is_side { v_flex() } else { h_flex() };
 
         let new_session_button = || {
@@ -696,8 +965,19 @@ impl DebugPanel {
                                     .map(|session| session.read(cx).running_state()),
                                 |this, running_state| {
                                     let capabilities = running_state.read(cx).capabilities(cx);
-                                    let supports_detach =
-                                        running_state.read(cx).session().read(cx).is_attached();
+                                    let session = running_state.read(cx).session().read(cx);
+                                    let supports_detach = session.is_attached();
+                                    let can_control = session.can_control();
+                                    let is_via_collab = session.is_via_collab();
+                                    let session_id = session.session_id();
+                                    let is_shared_by_host =
+                                        !is_via_collab && self.project.read(cx).is_shared();
+                                    let guests_can_control = self
+                                        .project
+                                        .read(cx)
+                                        .dap_store()
+                                        .read(cx)
+                                        .guests_can_control(session_id);
 
                                     this.map(|this| {
                                         if thread_status == ThreadStatus::Running {
@@ -707,6 +987,7 @@ impl DebugPanel {
                                                     IconName::DebugPause,
                                                 )
                                                 .icon_size(IconSize::Small)
+                                                .disabled(!can_control)
                                                 .on_click(window.listener_for(
                                                     running_state,
                                                     |this, _, _window, cx| {
@@ -732,7 +1013,10 @@ impl DebugPanel {
                                                     IconName::DebugContinue,
                                                 )
                                                 .icon_size(IconSize::Small)
-                                                .disabled(thread_status != ThreadStatus::Stopped)
+                                                .disabled(
+                                                    thread_status != ThreadStatus::Stopped
+                                                        || !can_control,
+                                                )
                                                 .on_click(window.listener_for(
                                                     running_state,
                                                     |this, _, _window, cx| this.continue_thread(cx),
@@ -760,7 +1044,10 @@ impl DebugPanel {
                                                     this.step_over(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(
+                                                thread_status != ThreadStatus::Stopped
+                                                    || !can_control,
+                                            )
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |_window, cx| {
@@ -782,7 +1069,10 @@ impl DebugPanel {
                                                     this.step_in(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(
+                                                thread_status != ThreadStatus::Stopped
+                                                    || !can_control,
+                                            )
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |_window, cx| {
@@ -804,7 +1094,10 @@ impl DebugPanel {
                                                     this.step_out(cx);
                                                 },
                                             ))
-                                            .disabled(thread_status != ThreadStatus::Stopped)
+                                            .disabled(
+                                                thread_status != ThreadStatus::Stopped
+                                                    || !can_control,
+                                            )
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 move |_window, cx| {
@@ -821,6 +1114,7 @@ impl DebugPanel {
                                     .child(
                                         IconButton::new("debug-restart", IconName::RotateCcw)
                                             .icon_size(IconSize::Small)
+                                            .disabled(is_via_collab)
                                             .on_click(window.listener_for(
                                                 running_state,
                                                 |this, _, window, cx| {
@@ -854,15 +1148,18 @@ impl DebugPanel {
                                                     }
                                                 },
                                             ))
-                                            .disabled(active_session.as_ref().is_none_or(
-                                                |session| {
-                                                    session
-                                                        .read(cx)
-                                                        .session(cx)
-                                                        .read(cx)
-                                                        .is_terminated()
-                                                },
-                                            ))
+                                            .disabled(
+                                                is_via_collab
+                                                    || active_session.as_ref().is_none_or(
+                                                        |session| {
+                                                            session
+                                                                .read(cx)
+                                                                .session(cx)
+                                                                .read(cx)
+                                                                .is_terminated()
+                                                        },
+                                                    ),
+                                            )
                                             .tooltip({
                                                 let focus_handle = focus_handle.clone();
                                                 let label = if capabilities
@@ -883,6 +1180,29 @@ impl DebugPanel {
                                                 }
                                             }),
                                     )
+                                    .when(is_compound, |div| {
+                                        div.child(
+                                            IconButton::new("debug-stop-compound", IconName::Stop)
+                                                .icon_size(IconSize::Small)
+                                                .on_click(|_, window, cx| {
+                                                    window.dispatch_action(
+                                                        StopCompound.boxed_clone(),
+                                                        cx,
+                                                    );
+                                                })
+                                                .tooltip({
+                                                    let focus_handle = focus_handle.clone();
+                                                    move |_window, cx| {
+                                                        Tooltip::for_action_in(
+                                                            "Stop All Sessions of Compound",
+                                                            &StopCompound,
+                                                            &focus_handle,
+                                                            cx,
+                                                        )
+                                                    }
+                                                }),
+                                        )
+                                    })
                                     .when(supports_detach, |div| {
                                         div.child(
                                             IconButton::new(
@@ -913,6 +1233,33 @@ impl DebugPanel {
                                             }),
                                         )
                                     })
+                                    .when(is_shared_by_host, |div| {
+                                        let dap_store = self.project.read(cx).dap_store();
+                                        div.child(
+                                            IconButton::new(
+                                                "debug-guests-can-control",
+                                                IconName::UserGroup,
+                                            )
+                                            .icon_size(IconSize::Small)
+                                            .toggle_state(guests_can_control)
+                                            .on_click(move |_, _, cx| {
+                                                dap_store.update(cx, |dap_store, cx| {
+                                                    dap_store.set_guests_can_control(
+                                                        session_id,
+                                                        !guests_can_control,
+                                                        cx,
+                                                    )
+                                                });
+                                            })
+                                            .tooltip(
+                                                Tooltip::text(if guests_can_control {
+                                                    "Stop Letting Collaborators Step and Continue"
+                                                } else {
+                                                    "Let Collaborators Step and Continue"
+                                                }),
+                                            ),
+                                        )
+                                    })
                                     .when(
                                         cx.has_flag::<DebuggerHistoryFeatureFlag>(),
                                         |this| {
@@ -1372,6 +1719,47 @@ impl DebugPanel {
     }
 }
 
+/// Runs the task labeled `label` in the terminal panel and waits for it to
+/// finish, failing unless it exits successfully.
+pub(crate) async fn run_task_by_label(
+    label: SharedString,
+    workspace: WeakEntity<Workspace>,
+    task_store: WeakEntity<TaskStore>,
+    buffer: Option<Entity<Buffer>>,
+    worktree_id: Option<WorktreeId>,
+    task_context: &TaskContext,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let template = task_store.update(cx, |task_store, cx| {
+        task_store.task_inventory().map(|inventory| {
+            inventory
+                .read(cx)
+                .task_template_by_label(buffer, worktree_id, &label, cx)
+        })
+    })?;
+    let template = match template {
+        Some(template) => template.await,
+        None => None,
+    }
+    .with_context(|| format!("Couldn't find task `{label}`"))?;
+    let task = template
+        .resolve_task("debug-scenario-task", task_context)
+        .with_context(|| format!("Could not resolve task variables of `{label}`"))?;
+
+    Workspace::save_for_task(&workspace, task.resolved.save, cx).await;
+    let exit_status = workspace
+        .update_in(cx, |workspace, window, cx| {
+            workspace.spawn_in_terminal(task.resolved, window, cx)
+        })?
+        .await;
+    match exit_status {
+        Some(Ok(status)) if status.success() => Ok(()),
+        Some(Ok(status)) => Err(anyhow!("Task `{label}` failed with {status}")),
+        Some(Err(error)) => Err(error.context(format!("Failed to spawn task `{label}`"))),
+        None => Err(anyhow!("Task `{label}` was cancelled")),
+    }
+}
+
 async fn register_session_inner(
     this: &WeakEntity<DebugPanel>,
     session: Entity<Session>,
@@ -1676,6 +2064,39 @@ impl Render for DebugPanel {
                     .ok();
                 }
             })
+            .on_action({
+                let this = this.clone();
+                move |_: &StopCompound, _, cx| {
+                    this.update(cx, |this, cx| {
+                        if let Some(session_id) = this
+                            .active_session
+                            .as_ref()
+                            .map(|session| session.read(cx).session_id(cx))
+                        {
+                            this.stop_compound(session_id, cx);
+                        }
+                    })
+                    .ok();
+                }
+            })
+            .on_action({
+                let this = this.clone();
+                move |_: &FocusDisassembly, window, cx| {
+                    this.update(cx, |this, cx| {
+                        this.activate_item(DebuggerPaneItem::Disassembly, window, cx);
+                    })
+                    .ok();
+                }
+            })
+            .on_action({
+                let this = this.clone();
+                move |_: &FocusThreads, window, cx| {
+                    this.update(cx, |this, cx| {
+                        this.activate_item(DebuggerPaneItem::Threads, window, cx);
+                    })
+                    .ok();
+                }
+            })
             .on_action({
                 let this = this.clone();
                 move |_: &ToggleThreadPicker, window, cx| {
@@ -12,7 +12,7 @@ use session::DebugSession;
 use tasks_ui::{Spawn, TaskOverrides};
 use ui::{FluentBuilder, InteractiveElement};
 use util::maybe;
-use workspace::{ShutdownDebugAdapters, Workspace};
+use workspace::{ShutdownDebugAdapters, Workspace, notifications::NotifyTaskExt as _};
 use zed_actions::debug_panel::{Toggle, ToggleFocus};
 
 pub mod attach_modal;
@@ -49,8 +49,15 @@ actions!(
         StepOut,
         /// Steps back to the previous statement.
         StepBack,
+        /// Steps into the next machine instruction.
+        StepIntoInstruction,
+        /// Steps over the next machine instruction.
+        StepOverInstruction,
         /// Stops the debugging session.
         Stop,
+        /// Stops all debugging sessions that were started together with the
+        /// active one from a compound scenario.
+        StopCompound,
         /// Toggles whether to ignore all breakpoints.
         ToggleIgnoreBreakpoints,
         /// Clears all breakpoints in the project.
@@ -69,6 +76,10 @@ actions!(
         FocusLoadedSources,
         /// Focuses on the terminal panel.
         FocusTerminal,
+        /// Focuses on the disassembly panel.
+        FocusDisassembly,
+        /// Focuses on the threads panel.
+        FocusThreads,
         /// Toggles the thread picker dropdown.
         ToggleThreadPicker,
         /// Toggles the session picker dropdown.
@@ -159,8 +170,12 @@ pub fn init(cx: &mut App) {
 
                 let caps = running_state.capabilities(cx);
                 let supports_step_back = caps.supports_step_back.unwrap_or_default();
+                let supports_instruction_stepping =
+                    caps.supports_stepping_granularity.unwrap_or_default();
                 let supports_detach = running_state.session().read(cx).is_attached();
-                let status = running_state.thread_status(cx);
+                // Guests only run the host's session when the host lets them.
+                let can_control = running_state.session().read(cx).can_control();
+                let status = running_state.thread_status(cx).filter(|_| can_control);
 
                 let active_item = active_item.downgrade();
                 div.when(status == Some(ThreadStatus::Running), |div| {
@@ -196,6 +211,24 @@ pub fn init(cx: &mut App) {
                             active_item.update(cx, |item, cx| item.step_back(cx)).ok();
                         })
                     })
+                    .when(supports_instruction_stepping, |div| {
+                        div.on_action({
+                            let active_item = active_item.clone();
+                            move |_: &StepIntoInstruction, _, cx| {
+                                active_item
+                                    .update(cx, |item, cx| item.step_in_instruction(cx))
+                                    .ok();
+                            }
+                        })
+                        .on_action({
+                            let active_item = active_item.clone();
+                            move |_: &StepOverInstruction, _, cx| {
+                                active_item
+                                    .update(cx, |item, cx| item.step_over_instruction(cx))
+                                    .ok();
+                            }
+                        })
+                    })
                     .on_action({
                         let active_item = active_item.clone();
                         move |_: &Continue, _, cx| {
@@ -347,6 +380,62 @@ pub fn init(cx: &mut App) {
                         },
                     );
 
+                    window.on_action_when(
+                        session.any_stopped_thread()
+                            && session
+                                .capabilities()
+                                .supports_goto_targets_request
+                                .unwrap_or_default(),
+                        TypeId::of::<editor::actions::JumpToCursor>(),
+                        {
+                            let editor = editor.clone();
+                            let active_session = active_session.clone();
+                            let workspace = workspace.downgrade();
+                            move |_, phase, window, cx| {
+                                if phase != DispatchPhase::Bubble {
+                                    return;
+                                }
+                                maybe!({
+                                    let (buffer, position) = editor
+                                        .update(cx, |editor, cx| {
+                                            let cursor_point: language::Point = editor
+                                                .selections
+                                                .newest(&editor.display_snapshot(cx))
+                                                .head();
+
+                                            editor
+                                                .buffer()
+                                                .read(cx)
+                                                .point_to_buffer_point(cursor_point, cx)
+                                        })
+                                        .ok()??;
+
+                                    let path =
+                                debugger::breakpoint_store::BreakpointStore::abs_path_from_buffer(
+                                    &buffer, cx,
+                                )?;
+
+                                    let task = active_session.update(cx, |session, cx| {
+                                        session.running_state().update(cx, |state, cx| {
+                                            let thread_id = state.selected_thread_id()?;
+                                            Some(state.session().update(cx, |session, cx| {
+                                                session.jump_to_position(
+                                                    path,
+                                                    position.row,
+                                                    thread_id,
+                                                    cx,
+                                                )
+                                            }))
+                                        })
+                                    })?;
+                                    task.detach_and_notify_err(workspace.clone(), window, cx);
+
+                                    Some(())
+                                });
+                            }
+                        },
+                    );
+
                     window.on_action(
                         TypeId::of::<editor::actions::EvaluateSelectedText>(),
                         move |_, _, window, cx| {
@@ -21,7 +21,9 @@ use gpui::{
 use itertools::Itertools as _;
 use picker::{Picker, PickerDelegate, highlighted_match_with_paths::HighlightedMatch};
 use project::{DebugScenarioContext, Project, TaskContexts, TaskSourceKind, task_store::TaskStore};
-use task::{DebugScenario, RevealTarget, SharedTaskContext, VariableName, ZedDebugConfig};
+use task::{
+    DebugCompound, DebugScenario, RevealTarget, SharedTaskContext, VariableName, ZedDebugConfig,
+};
 use ui::{
     ContextMenu, DropdownMenu, IconWithIndicator, Indicator, KeyBinding, ListItem, ListItemSpacing,
     Switch, SwitchLabelPosition, ToggleButtonGroup, ToggleButtonSimple, ToggleState, Tooltip,
@@ -1014,6 +1016,8 @@ pub(super) struct DebugDelegate {
         DebugScenario,
         Option<DebugScenarioContext>,
     )>,
+    /// Compound scenarios, matched after `candidates`.
+    compounds: Vec<(TaskSourceKind, DebugCompound)>,
     selected_index: usize,
     matches: Vec<StringMatch>,
     prompt: String,
@@ -1028,6 +1032,7 @@ impl DebugDelegate {
         Self {
             task_store,
             candidates: Vec::default(),
+            compounds: Vec::default(),
             selected_index: 0,
             matches: Vec::new(),
             prompt: String::new(),
@@ -1101,6 +1106,58 @@ impl DebugDelegate {
         }
     }
 
+    fn compound_for_candidate(
+        &self,
+        candidate_id: usize,
+    ) -> Option<&(TaskSourceKind, DebugCompound)> {
+        self.compounds
+            .get(candidate_id.checked_sub(self.candidates.len())?)
+    }
+
+    fn render_compound_match(
+        &self,
+        ix: usize,
+        hit: &StringMatch,
+        kind: &TaskSourceKind,
+        compound: &DebugCompound,
+        selected: bool,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> ListItem {
+        let highlighted_location = HighlightedMatch {
+            text: hit.string.clone(),
+            highlight_positions: hit.positions.clone(),
+            color: Color::Default,
+        };
+        let subtitle = compound.scenarios.iter().join(", ");
+        let subtitle = match self.get_task_subtitle(&Some(kind.clone()), &None, cx) {
+            Some(location) => format!("{subtitle} · {location}"),
+            None => subtitle,
+        };
+
+        ListItem::new(format!("debug-scenario-selection-{ix}"))
+            .inset(true)
+            .start_slot(
+                Icon::new(IconName::ListTree)
+                    .color(Color::Muted)
+                    .size(IconSize::Small),
+            )
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected)
+            .child(
+                v_flex()
+                    .w_full()
+                    .min_w_0()
+                    .items_start()
+                    .child(highlighted_location.render(window, cx))
+                    .child(
+                        Label::new(subtitle)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+
     fn get_scenario_language(
         languages: &Arc<LanguageRegistry>,
         dap_registry: &DapRegistry,
@@ -1146,6 +1203,17 @@ impl DebugDelegate {
         });
 
         let valid_adapters: HashSet<_> = cx.global::<DapRegistry>().enumerate_adapters();
+        let compounds = self
+            .task_store
+            .read(cx)
+            .task_inventory()
+            .map(|inventory| {
+                inventory
+                    .read(cx)
+                    .debug_compounds_from_settings(task_contexts.worktree())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
 
         cx.spawn(async move |this, cx| {
             let (recent, scenarios) = if let Some(task) = task {
@@ -1169,6 +1237,7 @@ impl DebugDelegate {
                     _ => false,
                 });
 
+                this.delegate.compounds = compounds;
                 this.delegate.candidates = recent
                     .into_iter()
                     .map(|(scenario, context)| {
@@ -1239,15 +1308,22 @@ impl PickerDelegate for DebugDelegate {
         window: &mut Window,
         cx: &mut Context<picker::Picker<Self>>,
     ) -> gpui::Task<()> {
-        let candidates = self.candidates.clone();
+        let labels = self
+            .candidates
+            .iter()
+            .map(|(_, _, candidate, _)| candidate.label.clone())
+            .chain(
+                self.compounds
+                    .iter()
+                    .map(|(_, compound)| compound.label.clone()),
+            )
+            .collect::<Vec<_>>();
 
         cx.spawn_in(window, async move |picker, cx| {
-            let candidates: Vec<_> = candidates
+            let candidates: Vec<_> = labels
                 .into_iter()
                 .enumerate()
-                .map(|(index, (_, _, candidate, _))| {
-                    StringMatchCandidate::new(index, candidate.label.as_ref())
-                })
+                .map(|(index, label)| StringMatchCandidate::new(index, label.as_ref()))
                 .collect();
 
             let matches = fuzzy::match_strings(
@@ -1412,10 +1488,37 @@ impl PickerDelegate for DebugDelegate {
         window: &mut Window,
         cx: &mut Context<picker::Picker<Self>>,
     ) {
-        let debug_scenario = self
+        let Some(candidate_id) = self
             .matches
             .get(self.selected_index())
-            .and_then(|match_candidate| self.candidates.get(match_candidate.candidate_id).cloned());
+            .map(|match_candidate| match_candidate.candidate_id)
+        else {
+            return;
+        };
+
+        if let Some((_, compound)) = self.compound_for_candidate(candidate_id).cloned() {
+            if !secondary {
+                let (task_context, worktree_id) = self
+                    .task_contexts
+                    .as_ref()
+                    .and_then(|task_contexts| {
+                        Some((
+                            SharedTaskContext::from(task_contexts.active_context().cloned()?),
+                            task_contexts.worktree(),
+                        ))
+                    })
+                    .unwrap_or_default();
+                self.debug_panel
+                    .update(cx, |panel, cx| {
+                        panel.start_compound(compound, task_context, worktree_id, window, cx);
+                    })
+                    .ok();
+            }
+            cx.emit(DismissEvent);
+            return;
+        }
+
+        let debug_scenario = self.candidates.get(candidate_id).cloned();
 
         let Some((kind, _, debug_scenario, context)) = debug_scenario else {
             return;
@@ -1541,6 +1644,9 @@ impl PickerDelegate for DebugDelegate {
         cx: &mut Context<picker::Picker<Self>>,
     ) -> Option<Self::ListItem> {
         let hit = &self.matches.get(ix)?;
+        if let Some((kind, compound)) = self.compound_for_candidate(hit.candidate_id) {
+            return Some(self.render_compound_match(ix, hit, kind, compound, selected, window, cx));
+        }
         let (task_kind, language_name, _scenario, context) = &self.candidates[hit.candidate_id];
 
         let highlighted_location = HighlightedMatch {
@@ -11,8 +11,9 @@ use workspace::{Member, Pane, PaneAxis, Workspace};
 
 use crate::session::running::{
     self, DebugTerminal, RunningState, SubView, breakpoint_list::BreakpointList, console::Console,
-    loaded_source_list::LoadedSourceList, memory_view::MemoryView, module_list::ModuleList,
-    stack_frame_list::StackFrameList, variable_list::VariableList,
+    disassembly_view::DisassemblyView, loaded_source_list::LoadedSourceList,
+    memory_view::MemoryView, module_list::ModuleList, stack_frame_list::StackFrameList,
+    thread_list::ThreadList, variable_list::VariableList,
 };
 
 #[derive(Clone, Hash, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -25,6 +26,8 @@ pub(crate) enum DebuggerPaneItem {
     LoadedSources,
     Terminal,
     MemoryView,
+    Disassembly,
+    Threads,
 }
 
 impl DebuggerPaneItem {
@@ -38,6 +41,8 @@ impl DebuggerPaneItem {
             DebuggerPaneItem::LoadedSources,
             DebuggerPaneItem::Terminal,
             DebuggerPaneItem::MemoryView,
+            DebuggerPaneItem::Disassembly,
+            DebuggerPaneItem::Threads,
         ];
         VARIANTS
     }
@@ -51,6 +56,9 @@ impl DebuggerPaneItem {
             DebuggerPaneItem::LoadedSources => capabilities
                 .supports_loaded_sources_request
                 .unwrap_or_default(),
+            DebuggerPaneItem::Disassembly => capabilities
+                .supports_disassemble_request
+                .unwrap_or_default(),
             _ => true,
         }
     }
@@ -65,6 +73,8 @@ impl DebuggerPaneItem {
             DebuggerPaneItem::LoadedSources => SharedString::new_static("Sources"),
             DebuggerPaneItem::Terminal => SharedString::new_static("Terminal"),
             DebuggerPaneItem::MemoryView => SharedString::new_static("Memory View"),
+            DebuggerPaneItem::Disassembly => SharedString::new_static("Disassembly"),
+            DebuggerPaneItem::Threads => SharedString::new_static("Threads"),
         }
     }
     pub(crate) fn tab_tooltip(self) -> SharedString {
@@ -87,6 +97,12 @@ impl DebuggerPaneItem {
                 "Provides an interactive terminal session within the debugging environment"
             }
             DebuggerPaneItem::MemoryView => "Allows inspection of memory contents",
+            DebuggerPaneItem::Disassembly => {
+                "Shows the machine instructions around the current instruction pointer"
+            }
+            DebuggerPaneItem::Threads => {
+                "Lists all threads with their state and innermost stack frame"
+            }
         };
         SharedString::new_static(tooltip)
     }
@@ -212,6 +228,8 @@ pub(crate) fn deserialize_pane_layout(
     loaded_sources: &Entity<LoadedSourceList>,
     terminal: &Entity<DebugTerminal>,
     memory_view: &Entity<MemoryView>,
+    disassembly_view: &Entity<DisassemblyView>,
+    thread_list: &Entity<ThreadList>,
     subscriptions: &mut HashMap<EntityId, Subscription>,
     window: &mut Window,
     cx: &mut Context<RunningState>,
@@ -237,6 +255,8 @@ pub(crate) fn deserialize_pane_layout(
                     loaded_sources,
                     terminal,
                     memory_view,
+                    disassembly_view,
+                    thread_list,
                     subscriptions,
                     window,
                     cx,
@@ -333,6 +353,22 @@ pub(crate) fn deserialize_pane_layout(
                         pane_handle.clone(),
                         cx,
                     )),
+                    DebuggerPaneItem::Disassembly => Box::new(SubView::new(
+                        disassembly_view.focus_handle(cx),
+                        disassembly_view.clone().into(),
+                        DebuggerPaneItem::Disassembly,
+                        running_state.clone(),
+                        pane_handle.clone(),
+                        cx,
+                    )),
+                    DebuggerPaneItem::Threads => Box::new(SubView::new(
+                        thread_list.focus_handle(cx),
+                        thread_list.clone().into(),
+                        DebuggerPaneItem::Threads,
+                        running_state.clone(),
+                        pane_handle.clone(),
+                        cx,
+                    )),
                 })
                 .collect();
 
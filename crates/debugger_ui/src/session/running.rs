@@ -1,9 +1,11 @@
 pub(crate) mod breakpoint_list;
 pub(crate) mod console;
+pub(crate) mod disassembly_view;
 pub(crate) mod loaded_source_list;
 pub(crate) mod memory_view;
 pub(crate) mod module_list;
 pub mod stack_frame_list;
+pub(crate) mod thread_list;
 pub mod variable_list;
 use std::{
     any::Any,
@@ -15,9 +17,12 @@ use std::{
 use crate::{
     ToggleExpandItem,
     attach_modal::{AttachModal, ModalIntent},
+    debugger_panel::run_task_by_label,
     new_process_modal::resolve_path,
     persistence::{self, DebuggerPaneItem, SerializedLayout},
-    session::running::memory_view::MemoryView,
+    session::running::{
+        disassembly_view::DisassemblyView, memory_view::MemoryView, thread_list::ThreadList,
+    },
 };
 
 use anyhow::{Context as _, Result, anyhow, bail};
@@ -25,7 +30,7 @@ use breakpoint_list::BreakpointList;
 use collections::{HashMap, IndexMap};
 use console::Console;
 use dap::{
-    Capabilities, DapRegistry, RunInTerminalRequestArguments, Thread,
+    Capabilities, DapRegistry, RunInTerminalRequestArguments, SteppingGranularity, Thread,
     adapters::{DebugAdapterName, DebugTaskDefinition},
     client::SessionId,
     debugger_settings::DebuggerSettings,
@@ -89,6 +94,8 @@ pub struct RunningState {
     pub(crate) scenario: Option<DebugScenario>,
     pub(crate) scenario_context: Option<DebugScenarioContext>,
     memory_view: Entity<MemoryView>,
+    disassembly_view: Entity<DisassemblyView>,
+    thread_list: Entity<ThreadList>,
 }
 
 impl RunningState {
@@ -854,6 +861,25 @@ impl RunningState {
 
         let module_list = cx.new(|cx| ModuleList::new(session.clone(), workspace.clone(), cx));
 
+        let disassembly_view = cx.new(|cx| {
+            DisassemblyView::new(
+                session.clone(),
+                stack_frame_list.clone(),
+                weak_state.clone(),
+                workspace.clone(),
+                cx,
+            )
+        });
+
+        let thread_list = cx.new(|cx| {
+            ThreadList::new(
+                session.clone(),
+                stack_frame_list.clone(),
+                weak_state.clone(),
+                cx,
+            )
+        });
+
         let loaded_source_list = cx.new(|cx| LoadedSourceList::new(session.clone(), cx));
 
         let console = cx.new(|cx| {
@@ -963,6 +989,8 @@ impl RunningState {
                 &loaded_source_list,
                 &debug_terminal,
                 &memory_view,
+                &disassembly_view,
+                &thread_list,
                 &mut pane_close_subscriptions,
                 window,
                 cx,
@@ -992,6 +1020,8 @@ impl RunningState {
 
         Self {
             memory_view,
+            disassembly_view,
+            thread_list,
             session,
             workspace,
             project: weak_project,
@@ -1074,9 +1104,26 @@ impl RunningState {
                 adapter,
                 label,
                 build,
+                pre_launch_task,
+                post_debug_task: _,
                 mut config,
                 tcp_connection,
             } = scenario;
+
+            if let Some(pre_launch_task) = pre_launch_task {
+                run_task_by_label(
+                    pre_launch_task,
+                    weak_workspace.clone(),
+                    task_store.clone(),
+                    buffer.clone(),
+                    worktree_id,
+                    &task_context,
+                    cx,
+                )
+                .await
+                .context("Pre-launch task failed")?;
+            }
+
             Self::relativize_paths(None, &mut config, &task_context);
             Self::substitute_variables_in_config(&mut config, &task_context);
 
@@ -1466,6 +1513,22 @@ impl RunningState {
                 host_pane,
                 cx,
             )),
+            DebuggerPaneItem::Disassembly => Box::new(SubView::new(
+                self.disassembly_view.focus_handle(cx),
+                self.disassembly_view.clone().into(),
+                item_kind,
+                running_state,
+                host_pane,
+                cx,
+            )),
+            DebuggerPaneItem::Threads => Box::new(SubView::new(
+                self.thread_list.focus_handle(cx),
+                self.thread_list.clone().into(),
+                item_kind,
+                running_state,
+                host_pane,
+                cx,
+            )),
         }
     }
 
@@ -1687,6 +1750,11 @@ impl RunningState {
         });
     }
 
+    #[cfg(test)]
+    pub(crate) fn thread_list(&self) -> &Entity<ThreadList> {
+        &self.thread_list
+    }
+
     #[cfg(test)]
     pub(crate) fn variable_list(&self) -> &Entity<VariableList> {
         &self.variable_list
@@ -1804,9 +1872,29 @@ impl RunningState {
         });
     }
 
+    pub(crate) fn step_over_instruction(&mut self, cx: &mut Context<Self>) {
+        let Some(thread_id) = self.thread_id else {
+            return;
+        };
+
+        self.session().update(cx, |state, cx| {
+            state.step_over(thread_id, SteppingGranularity::Instruction, cx);
+        });
+    }
+
+    pub(crate) fn step_in_instruction(&mut self, cx: &mut Context<Self>) {
+        let Some(thread_id) = self.thread_id else {
+            return;
+        };
+
+        self.session().update(cx, |state, cx| {
+            state.step_in(thread_id, SteppingGranularity::Instruction, cx);
+        });
+    }
+
     pub fn rerun_session(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if let Some((scenario, context)) = self.scenario.take().zip(self.scenario_context.take())
-            && scenario.build.is_some()
+            && (scenario.build.is_some() || scenario.pre_launch_task.is_some())
         {
             let DebugScenarioContext {
                 task_context,
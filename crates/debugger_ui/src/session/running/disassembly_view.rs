@@ -0,0 +1,464 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use collections::HashMap;
+use dap::DisassembledInstruction;
+use gpui::{
+    AnyElement, Entity, FocusHandle, Focusable, ScrollStrategy, Subscription,
+    UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use language::{Buffer, Point};
+use project::{
+    ProjectPath,
+    debugger::session::{Session, ThreadStatus},
+};
+use ui::{Indicator, Tooltip, WithScrollbar, prelude::*};
+use workspace::Workspace;
+
+use crate::session::running::{RunningState, stack_frame_list::StackFrameList};
+
+/// How many instructions before the instruction pointer are disassembled.
+const INSTRUCTIONS_BEFORE: u64 = 32;
+/// How many instructions from the instruction pointer on are disassembled.
+const INSTRUCTIONS_AFTER: u64 = 96;
+
+enum DisassemblyRow {
+    /// The source line that the following instructions were compiled from.
+    Source {
+        path: Option<Arc<Path>>,
+        line: u64,
+    },
+    Instruction(usize),
+}
+
+pub(crate) struct DisassemblyView {
+    session: Entity<Session>,
+    stack_frame_list: Entity<StackFrameList>,
+    running_state: WeakEntity<RunningState>,
+    workspace: WeakEntity<Workspace>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    instruction_pointer: Option<String>,
+    instructions: Option<Arc<[DisassembledInstruction]>>,
+    rows: Vec<DisassemblyRow>,
+    /// Buffers holding the sources that instructions map to, used to show
+    /// source lines between the instructions. `None` while loading, or if the
+    /// source couldn't be opened.
+    sources: HashMap<Arc<Path>, Option<Entity<Buffer>>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DisassemblyView {
+    pub(crate) fn new(
+        session: Entity<Session>,
+        stack_frame_list: Entity<StackFrameList>,
+        running_state: WeakEntity<RunningState>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let _subscriptions = vec![
+            cx.observe(&session, |_, _, cx| cx.notify()),
+            cx.observe(&stack_frame_list, |_, _, cx| cx.notify()),
+        ];
+
+        Self {
+            session,
+            stack_frame_list,
+            running_state,
+            workspace,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            instruction_pointer: None,
+            instructions: None,
+            rows: Vec::new(),
+            sources: HashMap::default(),
+            _subscriptions,
+        }
+    }
+
+    /// Fetches the instructions around the selected frame's instruction
+    /// pointer, rebuilding the rows when they change.
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let instruction_pointer = self
+            .stack_frame_list
+            .read(cx)
+            .opened_stack_frame()
+            .and_then(|frame| frame.instruction_pointer_reference.clone());
+        let instructions = instruction_pointer.clone().and_then(|instruction_pointer| {
+            self.session.update(cx, |session, cx| {
+                session.disassemble(
+                    instruction_pointer,
+                    -(INSTRUCTIONS_BEFORE as i64),
+                    INSTRUCTIONS_BEFORE + INSTRUCTIONS_AFTER,
+                    cx,
+                )
+            })
+        });
+
+        let unchanged = match (&self.instructions, &instructions) {
+            (Some(old), Some(new)) => Arc::ptr_eq(old, new),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged && self.instruction_pointer == instruction_pointer {
+            return;
+        }
+
+        self.instruction_pointer = instruction_pointer;
+        self.instructions = instructions;
+        self.rebuild_rows(cx);
+        if let Some(ix) = self.instruction_pointer_row() {
+            self.scroll_handle
+                .scroll_to_item(ix, ScrollStrategy::Center);
+        }
+    }
+
+    fn rebuild_rows(&mut self, cx: &mut Context<Self>) {
+        self.rows.clear();
+        let Some(instructions) = self.instructions.clone() else {
+            return;
+        };
+
+        let mut current_location = None;
+        for (ix, instruction) in instructions.iter().enumerate() {
+            let path = instruction
+                .location
+                .as_ref()
+                .and_then(|source| source.path.as_deref())
+                .map(|path| Arc::<Path>::from(Path::new(path)));
+            if let Some(line) = instruction.line {
+                let location = (path.clone(), line);
+                if current_location.as_ref() != Some(&location) {
+                    if let Some(path) = &path {
+                        self.load_source(path.clone(), cx);
+                    }
+                    self.rows.push(DisassemblyRow::Source { path, line });
+                    current_location = Some(location);
+                }
+            }
+            self.rows.push(DisassemblyRow::Instruction(ix));
+        }
+    }
+
+    fn load_source(&mut self, path: Arc<Path>, cx: &mut Context<Self>) {
+        if self.sources.contains_key(&path) {
+            return;
+        }
+        self.sources.insert(path.clone(), None);
+
+        let Some(project) = self
+            .workspace
+            .read_with(cx, |workspace, _| workspace.project().clone())
+            .ok()
+        else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let (worktree, relative_path) = project
+                .update(cx, |project, cx| {
+                    project.find_or_create_worktree(&path, false, cx)
+                })
+                .await?;
+            let buffer = project
+                .update(cx, |project, cx| {
+                    let worktree_id = worktree.read(cx).id();
+                    project.open_buffer(
+                        ProjectPath {
+                            worktree_id,
+                            path: relative_path,
+                        },
+                        cx,
+                    )
+                })
+                .await?;
+            this.update(cx, |this, cx| {
+                this.sources.insert(path, Some(buffer));
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn instruction_pointer_row(&self) -> Option<usize> {
+        let instruction_pointer = parse_address(self.instruction_pointer.as_deref()?)?;
+        let instructions = self.instructions.as_ref()?;
+        self.rows.iter().position(|row| match row {
+            DisassemblyRow::Instruction(ix) => {
+                parse_address(&instructions[*ix].address) == Some(instruction_pointer)
+            }
+            DisassemblyRow::Source { .. } => false,
+        })
+    }
+
+    fn step(&mut self, step_in: bool, cx: &mut Context<Self>) {
+        self.running_state
+            .update(cx, |running_state, cx| {
+                if step_in {
+                    running_state.step_in_instruction(cx);
+                } else {
+                    running_state.step_over_instruction(cx);
+                }
+            })
+            .ok();
+    }
+
+    fn render_source_row(
+        &self,
+        ix: usize,
+        path: Option<&Arc<Path>>,
+        line: u64,
+        cx: &App,
+    ) -> AnyElement {
+        let text = path
+            .and_then(|path| self.sources.get(path)?.as_ref())
+            .and_then(|buffer| {
+                let buffer = buffer.read(cx);
+                let row = u32::try_from(line.checked_sub(1)?).ok()?;
+                (row <= buffer.max_point().row).then(|| {
+                    buffer
+                        .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+                        .collect::<String>()
+                })
+            });
+        let location = match path.and_then(|path| path.file_name()) {
+            Some(file_name) => format!("{}:{line}", file_name.to_string_lossy()),
+            None => format!("line {line}"),
+        };
+
+        h_flex()
+            .id(("disassembly-source", ix))
+            .w_full()
+            .pl_6()
+            .gap_2()
+            .text_ui_sm(cx)
+            .child(
+                Label::new(location)
+                    .size(LabelSize::Small)
+                    .color(Color::Accent),
+            )
+            .when_some(text, |this, text| {
+                this.child(
+                    Label::new(text.trim().to_string())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted)
+                        .buffer_font(cx)
+                        .single_line(),
+                )
+            })
+            .into_any_element()
+    }
+
+    fn render_instruction_row(
+        &self,
+        ix: usize,
+        instruction: &DisassembledInstruction,
+        is_instruction_pointer: bool,
+        supports_breakpoints: bool,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let has_breakpoint = self
+            .session
+            .read(cx)
+            .instruction_breakpoints()
+            .contains(&instruction.address);
+        let address = instruction.address.clone();
+
+        h_flex()
+            .id(("disassembly-instruction", ix))
+            .w_full()
+            .gap_2()
+            .font_buffer(cx)
+            .text_ui_sm(cx)
+            .when(is_instruction_pointer, |this| {
+                this.bg(cx.theme().colors().editor_highlighted_line_background)
+            })
+            .child(
+                h_flex()
+                    .id(("disassembly-gutter", ix))
+                    .w_4()
+                    .flex_none()
+                    .justify_center()
+                    .when(has_breakpoint, |this| {
+                        this.child(Indicator::dot().color(Color::Error))
+                    })
+                    .when(supports_breakpoints, |this| {
+                        this.cursor_pointer()
+                            .tooltip(Tooltip::text("Toggle Instruction Breakpoint"))
+                            .on_click(cx.listener(move |this, _, _, cx| {
+                                this.session.update(cx, |session, cx| {
+                                    session.toggle_instruction_breakpoint(address.clone(), cx);
+                                });
+                            }))
+                    }),
+            )
+            .child(
+                div()
+                    .w_4()
+                    .flex_none()
+                    .when(is_instruction_pointer, |this| {
+                        this.child(
+                            Icon::new(IconName::ArrowRight)
+                                .size(IconSize::Small)
+                                .color(Color::Warning),
+                        )
+                    }),
+            )
+            .child(Label::new(instruction.address.clone()).color(Color::Muted))
+            .when_some(instruction.instruction_bytes.clone(), |this, bytes| {
+                this.child(
+                    div()
+                        .w_32()
+                        .flex_none()
+                        .overflow_hidden()
+                        .child(Label::new(bytes).color(Color::Disabled).single_line()),
+                )
+            })
+            .child(Label::new(instruction.instruction.clone()).single_line())
+            .when_some(instruction.symbol.clone(), |this, symbol| {
+                this.child(Label::new(format!("<{symbol}>")).color(Color::Muted))
+            })
+            .into_any_element()
+    }
+
+    fn render_rows(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let Some(instructions) = self.instructions.clone() else {
+            return Vec::new();
+        };
+        let instruction_pointer = self.instruction_pointer.as_deref().and_then(parse_address);
+        let supports_breakpoints = self
+            .session
+            .read(cx)
+            .capabilities()
+            .supports_instruction_breakpoints
+            .unwrap_or_default();
+
+        range
+            .filter_map(|ix| match self.rows.get(ix)? {
+                DisassemblyRow::Source { path, line } => {
+                    Some(self.render_source_row(ix, path.as_ref(), *line, cx))
+                }
+                DisassemblyRow::Instruction(instruction_ix) => {
+                    let instruction = &instructions[*instruction_ix];
+                    let is_instruction_pointer = instruction_pointer.is_some()
+                        && parse_address(&instruction.address) == instruction_pointer;
+                    Some(self.render_instruction_row(
+                        ix,
+                        instruction,
+                        is_instruction_pointer,
+                        supports_breakpoints,
+                        cx,
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    fn render_controls(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let is_stopped = self
+            .running_state
+            .read_with(cx, |running_state, cx| running_state.thread_status(cx))
+            .ok()
+            .flatten()
+            == Some(ThreadStatus::Stopped);
+        let supports_instruction_stepping = self
+            .session
+            .read(cx)
+            .capabilities()
+            .supports_stepping_granularity
+            .unwrap_or_default();
+        let disabled = !is_stopped || !supports_instruction_stepping;
+
+        h_flex()
+            .w_full()
+            .gap_1()
+            .pb_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                IconButton::new("disassembly-step-over", IconName::DebugStepOver)
+                    .icon_size(IconSize::Small)
+                    .disabled(disabled)
+                    .tooltip(Tooltip::text("Step Over Instruction"))
+                    .on_click(cx.listener(|this, _, _, cx| this.step(false, cx))),
+            )
+            .child(
+                IconButton::new("disassembly-step-into", IconName::DebugStepInto)
+                    .icon_size(IconSize::Small)
+                    .disabled(disabled)
+                    .tooltip(Tooltip::text("Step Into Instruction"))
+                    .on_click(cx.listener(|this, _, _, cx| this.step(true, cx))),
+            )
+    }
+}
+
+/// Parses an instruction address as reported by the debug adapter, which is
+/// usually, but not necessarily, hexadecimal with a `0x` prefix.
+fn parse_address(address: &str) -> Option<u64> {
+    let address = address.trim();
+    match address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => address.parse().ok(),
+    }
+}
+
+impl Focusable for DisassemblyView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for DisassemblyView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        self.refresh(cx);
+
+        let placeholder = if self.instruction_pointer.is_none() {
+            Some("The selected stack frame has no instruction pointer")
+        } else if self.instructions.is_none() {
+            Some("Disassembling…")
+        } else {
+            None
+        };
+
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_1()
+            .child(self.render_controls(cx))
+            .map(|this| match placeholder {
+                Some(placeholder) => this.child(
+                    div()
+                        .p_2()
+                        .child(Label::new(placeholder).color(Color::Muted)),
+                ),
+                None => this
+                    .child(
+                        uniform_list(
+                            "disassembly",
+                            self.rows.len(),
+                            cx.processor(|this, range, _window, cx| this.render_rows(range, cx)),
+                        )
+                        .track_scroll(&self.scroll_handle)
+                        .size_full(),
+                    )
+                    .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_address;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("0x00007ff6a1b2c3d4"),
+            Some(0x00007ff6a1b2c3d4)
+        );
+        assert_eq!(parse_address("0XFF"), Some(0xff));
+        assert_eq!(parse_address("4096"), Some(4096));
+        assert_eq!(parse_address("main+12"), None);
+    }
+}
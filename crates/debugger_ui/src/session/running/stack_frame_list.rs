@@ -219,6 +219,18 @@ impl StackFrameList {
         self.opened_stack_frame_id
     }
 
+    pub(crate) fn opened_stack_frame(&self) -> Option<&dap::StackFrame> {
+        let stack_frame_id = self.opened_stack_frame_id?;
+        self.entries
+            .iter()
+            .flat_map(|entry| match entry {
+                StackFrameEntry::Label(stack_frame) => std::slice::from_ref(stack_frame),
+                StackFrameEntry::Normal(stack_frame) => std::slice::from_ref(stack_frame),
+                StackFrameEntry::Collapsed(stack_frames) => stack_frames.as_slice(),
+            })
+            .find(|stack_frame| stack_frame.id == stack_frame_id)
+    }
+
     pub(super) fn schedule_refresh(
         &mut self,
         select_first: bool,
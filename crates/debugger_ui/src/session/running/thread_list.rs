@@ -0,0 +1,352 @@
+use std::ops::Range;
+
+use gpui::{
+    AnyElement, Entity, FocusHandle, Focusable, ScrollStrategy, Subscription,
+    UniformListScrollHandle, WeakEntity, uniform_list,
+};
+use project::debugger::session::{Session, SessionEvent, ThreadId, ThreadStatus};
+use ui::{Indicator, Tooltip, WithScrollbar, prelude::*};
+
+use crate::session::running::{RunningState, stack_frame_list::StackFrameList};
+
+/// A thread as of the last refresh. Its status is read from the session when
+/// rendering, since continuing and stepping change it without an event.
+struct ThreadEntry {
+    id: ThreadId,
+    name: SharedString,
+    /// The name and location of the innermost stack frame, only known while
+    /// the thread is stopped.
+    top_frame: Option<(SharedString, Option<SharedString>)>,
+}
+
+pub(crate) struct ThreadList {
+    session: Entity<Session>,
+    running_state: WeakEntity<RunningState>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    entries: Vec<ThreadEntry>,
+    selected_ix: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ThreadList {
+    pub(crate) fn new(
+        session: Entity<Session>,
+        stack_frame_list: Entity<StackFrameList>,
+        running_state: WeakEntity<RunningState>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let _subscriptions = vec![
+            cx.observe(&session, |_, _, cx| cx.notify()),
+            cx.observe(&stack_frame_list, |_, _, cx| cx.notify()),
+            cx.subscribe(&session, |this, _, event, cx| match event {
+                SessionEvent::Stopped(_)
+                | SessionEvent::Threads
+                | SessionEvent::StackTrace
+                | SessionEvent::HistoricSnapshotSelected => this.refresh(cx),
+                _ => {}
+            }),
+        ];
+
+        let mut this = Self {
+            session,
+            running_state,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            entries: Vec::new(),
+            selected_ix: None,
+            _subscriptions,
+        };
+        this.refresh(cx);
+        this
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        self.entries = self.session.update(cx, |session, cx| {
+            session
+                .threads(cx)
+                .into_iter()
+                .map(|(thread, status)| {
+                    let id = ThreadId(thread.id);
+                    let top_frame = (status == ThreadStatus::Stopped)
+                        .then(|| session.stack_frames(id, cx).ok())
+                        .flatten()
+                        .and_then(|frames| frames.into_iter().next())
+                        .map(|frame| {
+                            let location = frame
+                                .dap
+                                .source
+                                .as_ref()
+                                .and_then(|source| source.name.clone().or(source.path.clone()))
+                                .map(|file| format!("{file}:{}", frame.dap.line).into());
+                            (frame.dap.name.into(), location)
+                        });
+                    let name = if thread.name.is_empty() {
+                        format!("Tid: {}", thread.id).into()
+                    } else {
+                        thread.name.into()
+                    };
+
+                    ThreadEntry {
+                        id,
+                        name,
+                        top_frame,
+                    }
+                })
+                .collect()
+        });
+
+        if self.selected_ix.is_some_and(|ix| ix >= self.entries.len()) {
+            self.selected_ix = None;
+        }
+        cx.notify();
+    }
+
+    #[cfg(test)]
+    pub(crate) fn entries(&self, cx: &App) -> Vec<String> {
+        let session = self.session.read(cx);
+        self.entries
+            .iter()
+            .map(|entry| {
+                let status = session.thread_status(entry.id);
+                let mut line = format!("{} ({})", entry.name, status.label());
+                if session.is_thread_frozen(entry.id) {
+                    line.push_str(" (Frozen)");
+                }
+                if let Some((name, location)) = entry
+                    .top_frame
+                    .as_ref()
+                    .filter(|_| status == ThreadStatus::Stopped)
+                {
+                    line.push_str(&format!(" {name}"));
+                    if let Some(location) = location {
+                        line.push_str(&format!(" {location}"));
+                    }
+                }
+                line
+            })
+            .collect()
+    }
+
+    fn focus_thread(&mut self, thread_id: ThreadId, window: &mut Window, cx: &mut Context<Self>) {
+        self.running_state
+            .update(cx, |running_state, cx| {
+                running_state.select_thread(thread_id, window, cx);
+            })
+            .ok();
+    }
+
+    pub(crate) fn toggle_frozen(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        self.session.update(cx, |session, cx| {
+            session.toggle_thread_frozen(thread_id, cx);
+        });
+    }
+
+    fn render_entry(
+        &self,
+        ix: usize,
+        focused_thread: Option<ThreadId>,
+        supports_freezing: bool,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let entry = &self.entries[ix];
+        let thread_id = entry.id;
+        let is_focused = focused_thread == Some(thread_id);
+        let (status, frozen) = {
+            let session = self.session.read(cx);
+            (
+                session.thread_status(thread_id),
+                session.is_thread_frozen(thread_id),
+            )
+        };
+        let indicator_color = match status {
+            ThreadStatus::Stopped => Color::Warning,
+            ThreadStatus::Running | ThreadStatus::Stepping => Color::Success,
+            ThreadStatus::Exited | ThreadStatus::Ended => Color::Disabled,
+        };
+        let status_label = if frozen {
+            format!("{} (Frozen)", status.label())
+        } else {
+            status.label().to_string()
+        };
+        let top_frame = entry
+            .top_frame
+            .clone()
+            .filter(|_| status == ThreadStatus::Stopped);
+
+        h_flex()
+            .id(("thread-list-entry", ix))
+            .w_full()
+            .p_1()
+            .gap_2()
+            .rounded_md()
+            .group("")
+            .hover(|this| this.bg(cx.theme().colors().element_hover))
+            .when(Some(ix) == self.selected_ix || is_focused, |this| {
+                this.bg(cx.theme().colors().element_hover)
+            })
+            .on_any_mouse_down(|_, _, cx| {
+                cx.stop_propagation();
+            })
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.selected_ix = Some(ix);
+                this.focus_thread(thread_id, window, cx);
+                cx.notify();
+            }))
+            .child(Indicator::dot().color(indicator_color))
+            .child(
+                v_flex()
+                    .flex_1()
+                    .min_w_0()
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Label::new(entry.name.clone())
+                                    .size(LabelSize::Small)
+                                    .when(is_focused, |this| this.color(Color::Accent))
+                                    .single_line(),
+                            )
+                            .child(
+                                Label::new(status_label)
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .when_some(top_frame, |this, (name, location)| {
+                        this.child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Label::new(name)
+                                        .size(LabelSize::XSmall)
+                                        .color(Color::Muted)
+                                        .buffer_font(cx)
+                                        .single_line(),
+                                )
+                                .when_some(location, |this, location| {
+                                    this.child(
+                                        Label::new(location)
+                                            .size(LabelSize::XSmall)
+                                            .color(Color::Disabled)
+                                            .single_line(),
+                                    )
+                                }),
+                        )
+                    }),
+            )
+            .when(supports_freezing, |this| {
+                this.child(
+                    IconButton::new(("thread-list-freeze", ix), IconName::DebugPause)
+                        .icon_size(IconSize::Small)
+                        .toggle_state(frozen)
+                        .disabled(matches!(status, ThreadStatus::Exited | ThreadStatus::Ended))
+                        .tooltip(Tooltip::text(if frozen {
+                            "Thaw Thread"
+                        } else {
+                            "Freeze Thread"
+                        }))
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.toggle_frozen(thread_id, cx);
+                        })),
+                )
+            })
+            .into_any_element()
+    }
+
+    fn render_entries(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Vec<AnyElement> {
+        let focused_thread = self
+            .running_state
+            .read_with(cx, |running_state, _| running_state.selected_thread_id())
+            .ok()
+            .flatten();
+        let supports_freezing = self
+            .session
+            .read(cx)
+            .capabilities()
+            .supports_single_thread_execution_requests
+            .unwrap_or_default();
+
+        range
+            .filter(|ix| *ix < self.entries.len())
+            .map(|ix| self.render_entry(ix, focused_thread, supports_freezing, cx))
+            .collect()
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.selected_ix.and_then(|ix| self.entries.get(ix)) else {
+            return;
+        };
+        self.focus_thread(entry.id, window, cx);
+    }
+
+    fn select_ix(&mut self, ix: Option<usize>, cx: &mut Context<Self>) {
+        self.selected_ix = ix;
+        if let Some(ix) = ix {
+            self.scroll_handle
+                .scroll_to_item(ix, ScrollStrategy::Center);
+        }
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _window: &mut Window, cx: &mut Context<Self>) {
+        let ix = match self.selected_ix {
+            _ if self.entries.is_empty() => None,
+            None => Some(0),
+            Some(ix) => Some((ix + 1) % self.entries.len()),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = match self.selected_ix {
+            _ if self.entries.is_empty() => None,
+            None | Some(0) => Some(self.entries.len() - 1),
+            Some(ix) => Some(ix - 1),
+        };
+        self.select_ix(ix, cx);
+    }
+}
+
+impl Focusable for ThreadList {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ThreadList {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::confirm))
+            .size_full()
+            .p_1()
+            .map(|this| {
+                if self.entries.is_empty() {
+                    this.child(
+                        div()
+                            .p_2()
+                            .child(Label::new("No threads").color(Color::Muted)),
+                    )
+                } else {
+                    this.child(
+                        uniform_list(
+                            "thread-list",
+                            self.entries.len(),
+                            cx.processor(|this, range, _window, cx| this.render_entries(range, cx)),
+                        )
+                        .track_scroll(&self.scroll_handle)
+                        .size_full(),
+                    )
+                    .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                }
+            })
+    }
+}
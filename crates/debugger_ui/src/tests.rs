@@ -31,6 +31,8 @@ mod persistence;
 #[cfg(test)]
 mod stack_frame_list;
 #[cfg(test)]
+mod thread_list;
+#[cfg(test)]
 mod variable_list;
 
 pub fn init_test(cx: &mut gpui::TestAppContext) {
@@ -10,8 +10,8 @@ use dap::{
     adapters::DebugTaskDefinition,
     client::SessionId,
     requests::{
-        Continue, Disconnect, Launch, Next, RunInTerminal, SetBreakpoints, StackTrace,
-        StartDebugging, StepBack, StepIn, StepOut, Threads,
+        Continue, Disconnect, Launch, Next, RunInTerminal, SetBreakpoints,
+        SetInstructionBreakpoints, StackTrace, StartDebugging, StepBack, StepIn, StepOut, Threads,
     },
 };
 use editor::{
@@ -21,7 +21,10 @@ use editor::{
 use gpui::{BackgroundExecutor, TestAppContext, VisualTestContext};
 use project::{
     FakeFs, Project,
-    debugger::session::{ThreadId, ThreadStatus},
+    debugger::{
+        breakpoint_store::{Breakpoint, BreakpointEditAction, BreakpointWithPosition},
+        session::{ThreadId, ThreadStatus},
+    },
 };
 use serde_json::json;
 use std::{
@@ -2555,3 +2558,273 @@ async fn test_restart_request_is_not_sent_more_than_once_until_response(
         "A second restart should be allowed after the first one completes"
     );
 }
+
+#[gpui::test]
+async fn test_instruction_breakpoints_are_resent_after_restart_request(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    fs.insert_tree(path!("/project"), json!({ "main.rs": "First line" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let instruction_requests = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let session = start_debug_session(&workspace, cx, {
+        let instruction_requests = instruction_requests.clone();
+        move |client| {
+            client.on_request::<dap::requests::Initialize, _>(move |_, _| {
+                Ok(dap::Capabilities {
+                    supports_restart_request: Some(true),
+                    supports_instruction_breakpoints: Some(true),
+                    ..Default::default()
+                })
+            });
+            client.on_request::<SetInstructionBreakpoints, _>({
+                let instruction_requests = instruction_requests.clone();
+                move |_, args| {
+                    instruction_requests
+                        .lock()
+                        .push(instruction_references(&args));
+                    Ok(dap::SetInstructionBreakpointsResponse {
+                        breakpoints: Vec::new(),
+                    })
+                }
+            });
+            client.on_request::<dap::requests::Restart, _>(move |_, _| Ok(()));
+        }
+    })
+    .unwrap();
+    cx.run_until_parked();
+    assert!(instruction_requests.lock().is_empty());
+
+    session.update(cx, |session, cx| {
+        session.toggle_instruction_breakpoint("0x1000".to_string(), cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        instruction_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![vec!["0x1000".to_string()]]
+    );
+
+    session.update(cx, |session, cx| session.restart(None, cx));
+    cx.run_until_parked();
+    assert_eq!(
+        instruction_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![vec!["0x1000".to_string()]],
+        "the adapter should receive the instruction breakpoints again after restarting"
+    );
+}
+
+#[gpui::test]
+async fn test_breakpoints_are_sent_to_restarted_session(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    fs.insert_tree(
+        path!("/project"),
+        json!({
+            "main.rs": "First line\nSecond line\nThird line",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/project/main.rs"), cx)
+        })
+        .await
+        .unwrap();
+    let breakpoint_store = project.read_with(cx, |project, _| project.breakpoint_store());
+    breakpoint_store.update(cx, |breakpoint_store, cx| {
+        let position = buffer.read(cx).anchor_before(language::Point::new(1, 0));
+        breakpoint_store.toggle_breakpoint(
+            buffer.clone(),
+            BreakpointWithPosition {
+                position,
+                bp: Breakpoint::new_standard(),
+            },
+            BreakpointEditAction::Toggle,
+            cx,
+        );
+    });
+
+    let source_requests = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let instruction_requests = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    // Restarting without a restart request starts a new session, which needs
+    // the same handlers as the first one.
+    let configure = Arc::new({
+        let source_requests = source_requests.clone();
+        let instruction_requests = instruction_requests.clone();
+        move |client: &Arc<dap::client::DebugAdapterClient>| {
+            client.on_request::<dap::requests::Initialize, _>(move |_, _| {
+                Ok(dap::Capabilities {
+                    supports_instruction_breakpoints: Some(true),
+                    ..Default::default()
+                })
+            });
+            client.on_request::<SetBreakpoints, _>({
+                let source_requests = source_requests.clone();
+                move |_, args| {
+                    source_requests.lock().push((
+                        args.source.path.unwrap(),
+                        args.breakpoints
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|breakpoint| breakpoint.line)
+                            .collect::<Vec<_>>(),
+                    ));
+                    Ok(dap::SetBreakpointsResponse {
+                        breakpoints: Vec::new(),
+                    })
+                }
+            });
+            client.on_request::<SetInstructionBreakpoints, _>({
+                let instruction_requests = instruction_requests.clone();
+                move |_, args| {
+                    instruction_requests
+                        .lock()
+                        .push(instruction_references(&args));
+                    Ok(dap::SetInstructionBreakpointsResponse {
+                        breakpoints: Vec::new(),
+                    })
+                }
+            });
+        }
+    });
+    let _intercept = project::debugger::test::intercept_debug_sessions(cx, {
+        let configure = configure.clone();
+        move |client| configure(client)
+    });
+    let session = start_debug_session(&workspace, cx, move |client| configure(client)).unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        source_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![(path!("/project/main.rs").to_string(), vec![2])]
+    );
+    assert!(instruction_requests.lock().is_empty());
+
+    session.update(cx, |session, cx| {
+        session.toggle_instruction_breakpoint("0x1000".to_string(), cx);
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        instruction_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![vec!["0x1000".to_string()]]
+    );
+
+    session.update(cx, |session, cx| session.restart(None, cx));
+    cx.run_until_parked();
+
+    let restarted_session = active_debug_session_panel(workspace, cx).update(cx, |item, cx| {
+        item.running_state().read(cx).session().clone()
+    });
+    assert_ne!(
+        restarted_session.read_with(cx, |session, _| session.session_id()),
+        session.read_with(cx, |session, _| session.session_id())
+    );
+    assert_eq!(
+        source_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![(path!("/project/main.rs").to_string(), vec![2])]
+    );
+    assert_eq!(
+        instruction_requests.lock().drain(..).collect::<Vec<_>>(),
+        vec![vec!["0x1000".to_string()]],
+        "the restarted session should receive the instruction breakpoints while initializing"
+    );
+}
+
+fn instruction_references(args: &dap::SetInstructionBreakpointsArguments) -> Vec<String> {
+    args.breakpoints
+        .iter()
+        .map(|breakpoint| breakpoint.instruction_reference.clone())
+        .collect()
+}
+
+#[gpui::test]
+async fn test_failing_pre_launch_task_aborts_compound(
+    executor: BackgroundExecutor,
+    cx: &mut TestAppContext,
+) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    fs.insert_tree(path!("/project"), json!({ "main.rs": "First line" }))
+        .await;
+
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+    let _intercept = project::debugger::test::intercept_debug_sessions(cx, |_| {});
+
+    let inventory = project.read_with(cx, |project, cx| {
+        project
+            .task_store()
+            .read(cx)
+            .task_inventory()
+            .cloned()
+            .unwrap()
+    });
+    inventory.update(cx, |inventory, _| {
+        inventory
+            .update_file_based_scenarios(
+                project::task_store::TaskSettingsLocation::Global(Path::new("")),
+                Some(
+                    r#"[
+                        { "label": "Server", "adapter": "fake-adapter", "request": "launch" },
+                        { "label": "Client", "adapter": "fake-adapter", "request": "launch" }
+                    ]"#,
+                ),
+            )
+            .unwrap();
+    });
+    let session_count = |cx: &mut VisualTestContext| {
+        project.read_with(cx, |project, cx| {
+            project.dap_store().read(cx).sessions().count()
+        })
+    };
+    let start_compound = |pre_launch_task: Option<&str>, cx: &mut VisualTestContext| {
+        let compound = task::DebugCompound {
+            label: "Client and server".into(),
+            scenarios: vec!["Server".into(), "Client".into()],
+            pre_launch_task: pre_launch_task.map(Into::into),
+            stop_all: false,
+        };
+        workspace
+            .update(cx, |multi, window, cx| {
+                multi.workspace().update(cx, |workspace, cx| {
+                    workspace
+                        .panel::<DebugPanel>(cx)
+                        .unwrap()
+                        .update(cx, |panel, cx| {
+                            panel.start_compound(compound, Default::default(), None, window, cx);
+                        });
+                });
+            })
+            .unwrap();
+        cx.run_until_parked();
+    };
+
+    // The pre-launch task doesn't exist, so it fails before any scenario starts.
+    start_compound(Some("start database"), cx);
+    assert_eq!(
+        session_count(cx),
+        0,
+        "no member of the compound should start when its pre-launch task fails"
+    );
+
+    start_compound(None, cx);
+    assert_eq!(session_count(cx), 2);
+}
@@ -136,6 +136,8 @@ async fn test_debug_session_substitutes_variables_and_relativizes_paths(
             adapter: "fake-adapter".into(),
             label: "test-debug-session".into(),
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             config: json!({
                 "request": "launch",
                 "program": input_path,
@@ -0,0 +1,174 @@
+#![expect(clippy::result_large_err)]
+use crate::{
+    persistence::DebuggerPaneItem,
+    tests::{active_debug_session_panel, init_test, init_test_workspace, start_debug_session},
+};
+use dap::{
+    StackFrame, StoppedEvent,
+    requests::{Continue, Initialize, Scopes, StackTrace, Threads},
+};
+use gpui::{BackgroundExecutor, Focusable as _, TestAppContext, VisualTestContext};
+use project::{FakeFs, Project, debugger::session::ThreadId};
+use std::sync::{Arc, Mutex};
+use util::path;
+
+fn stack_frame(thread_id: i64) -> StackFrame {
+    StackFrame {
+        id: thread_id as u64,
+        name: format!("worker_{thread_id}"),
+        source: Some(dap::Source {
+            name: Some("main.rs".into()),
+            path: Some(path!("/project/main.rs").into()),
+            source_reference: None,
+            presentation_hint: None,
+            origin: None,
+            sources: None,
+            adapter_data: None,
+            checksums: None,
+        }),
+        line: thread_id as u64 + 1,
+        column: 1,
+        end_line: None,
+        end_column: None,
+        can_restart: None,
+        instruction_pointer_reference: None,
+        module_id: None,
+        presentation_hint: None,
+    }
+}
+
+#[gpui::test]
+async fn test_thread_list(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(executor.clone());
+    let project = Project::test(fs, [path!("/project").as_ref()], cx).await;
+    let workspace = init_test_workspace(&project, cx).await;
+    let cx = &mut VisualTestContext::from_window(*workspace, cx);
+
+    let session = start_debug_session(&workspace, cx, |client| {
+        client.on_request::<Initialize, _>(move |_, _| {
+            Ok(dap::Capabilities {
+                supports_single_thread_execution_requests: Some(true),
+                ..Default::default()
+            })
+        });
+    })
+    .unwrap();
+    let client = session.update(cx, |session, _| session.adapter_client().unwrap());
+
+    client.on_request::<Threads, _>(move |_, _| {
+        Ok(dap::ThreadsResponse {
+            threads: vec![
+                dap::Thread {
+                    id: 1,
+                    name: "main".into(),
+                },
+                dap::Thread {
+                    id: 2,
+                    name: "worker".into(),
+                },
+            ],
+        })
+    });
+    client.on_request::<StackTrace, _>(move |_, args| {
+        Ok(dap::StackTraceResponse {
+            stack_frames: vec![stack_frame(args.thread_id)],
+            total_frames: None,
+        })
+    });
+    client.on_request::<Scopes, _>(move |_, _| Ok(dap::ScopesResponse { scopes: vec![] }));
+    let continued_threads = Arc::new(Mutex::new(Vec::new()));
+    client.on_request::<Continue, _>({
+        let continued_threads = continued_threads.clone();
+        move |_, args| {
+            continued_threads.lock().unwrap().push(args.thread_id);
+            Ok(dap::ContinueResponse {
+                all_threads_continued: Some(false),
+            })
+        }
+    });
+
+    client
+        .fake_event(dap::messages::Events::Stopped(StoppedEvent {
+            reason: dap::StoppedEventReason::Pause,
+            description: None,
+            thread_id: Some(1),
+            preserve_focus_hint: None,
+            text: None,
+            all_threads_stopped: Some(true),
+            hit_breakpoint_ids: None,
+        }))
+        .await;
+    cx.run_until_parked();
+
+    let running_state =
+        active_debug_session_panel(workspace, cx).update_in(cx, |item, window, cx| {
+            cx.focus_self(window);
+            item.running_state().clone()
+        });
+    running_state.update_in(cx, |this, window, cx| {
+        this.activate_item(DebuggerPaneItem::Threads, window, cx);
+        cx.refresh_windows();
+    });
+    cx.run_until_parked();
+
+    let thread_list = running_state.read_with(cx, |state, _| state.thread_list().clone());
+    thread_list.read_with(cx, |list, cx| {
+        assert_eq!(
+            list.entries(cx),
+            vec![
+                "main (Stopped) worker_1 main.rs:2",
+                "worker (Stopped) worker_2 main.rs:3",
+            ]
+        );
+    });
+
+    // Confirming an entry focuses its thread in the rest of the session.
+    thread_list.update_in(cx, |list, window, cx| {
+        list.focus_handle(cx).focus(window, cx);
+    });
+    cx.dispatch_action(menu::SelectNext);
+    cx.dispatch_action(menu::SelectNext);
+    cx.dispatch_action(menu::Confirm);
+    cx.run_until_parked();
+    running_state.read_with(cx, |state, _| {
+        assert_eq!(state.selected_thread_id(), Some(ThreadId(2)));
+    });
+
+    thread_list.update(cx, |list, cx| list.toggle_frozen(ThreadId(2), cx));
+    cx.run_until_parked();
+    thread_list.read_with(cx, |list, cx| {
+        assert_eq!(
+            list.entries(cx),
+            vec![
+                "main (Stopped) worker_1 main.rs:2",
+                "worker (Stopped) (Frozen) worker_2 main.rs:3",
+            ]
+        );
+    });
+
+    // Continuing changes thread statuses without a session event, so the list
+    // has to pick them up without being refreshed.
+    session.update(cx, |session, cx| session.continue_thread(ThreadId(1), cx));
+    cx.run_until_parked();
+    assert_eq!(*continued_threads.lock().unwrap(), vec![1]);
+    thread_list.read_with(cx, |list, cx| {
+        assert_eq!(
+            list.entries(cx),
+            vec![
+                "main (Running)",
+                "worker (Stopped) (Frozen) worker_2 main.rs:3",
+            ]
+        );
+    });
+
+    thread_list.update(cx, |list, cx| list.toggle_frozen(ThreadId(2), cx));
+    cx.run_until_parked();
+    thread_list.read_with(cx, |list, cx| {
+        assert_eq!(
+            list.entries(cx),
+            vec!["main (Running)", "worker (Stopped) worker_2 main.rs:3",]
+        );
+    });
+}
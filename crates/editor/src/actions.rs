@@ -383,6 +383,9 @@ actions!(
     [
         /// Runs program execution to the current cursor position.
         RunToCursor,
+        /// Moves the stopped thread's execution point to the current cursor
+        /// position without running the code in between.
+        JumpToCursor,
         /// Evaluates the selected text in the debugger context.
         EvaluateSelectedText
     ]
@@ -687,6 +690,10 @@ actions!(
         MoveToStartOfLargerSyntaxNode,
         /// Moves cursor to the end of the next larger syntax node.
         MoveToEndOfLargerSyntaxNode,
+        /// Opens the lines of the newest selection in a new editor restricted to them.
+        NarrowToSelection,
+        /// Opens the symbol enclosing the cursor in a new editor restricted to it.
+        NarrowToEnclosingSymbol,
         /// Inserts a new line and moves cursor to it.
         Newline,
         /// Inserts a new line above the current line.
@@ -936,6 +943,8 @@ actions!(
         /// Removes the surrounding syntax node (for example brackets, or closures)
         /// from the current selections.
         UnwrapSyntaxNode,
+        /// Closes a narrowed editor and returns to the full buffer.
+        Widen,
         /// Wraps selections in tag specified by language.
         WrapSelectionsInTag,
         /// Aligns selections from different rows into the same column
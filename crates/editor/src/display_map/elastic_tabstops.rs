@@ -0,0 +1,356 @@
+//! Elastic tabstops for the [`TabMap`](super::TabMap).
+//!
+//! With elastic tabstops, a tab no longer expands to the next multiple of the
+//! tab size, but to the end of the column it terminates. The text between two
+//! tabs is a cell, and the `n`th cells of a contiguous run of lines that all
+//! have at least `n + 1` tabs form a column, which is as wide as its widest
+//! cell. This keeps comments, tables and other tab-separated text aligned
+//! without padding it with spaces.
+//!
+//! Column widths are rounded up to the next multiple of the tab size, so a
+//! line whose cells don't line up with any neighbor renders exactly as it
+//! would with fixed tabstops.
+
+use std::{ops::Range, sync::Arc};
+
+use language::LanguageAwareStyling;
+use sum_tree::{Bias, SumTree};
+
+use super::{
+    Highlights,
+    fold_map::{FoldEdit, FoldPoint, FoldSnapshot},
+};
+
+/// The column at which every expanded tab ends, for each fold row.
+#[derive(Clone)]
+pub struct ElasticTabstops {
+    rows: SumTree<ElasticRow>,
+}
+
+/// A range of rows whose tabstops changed even though their text didn't,
+/// because a neighboring line in the same block was edited.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElasticRowEdit {
+    pub old_rows: Range<u32>,
+    pub new_rows: Range<u32>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ElasticRow {
+    /// The exclusive end column, in characters, of each tab on this row that
+    /// is within the maximum expansion column.
+    stops: Arc<[u32]>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct ElasticRowSummary {
+    rows: u32,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+struct ElasticRowCount(u32);
+
+impl sum_tree::Item for ElasticRow {
+    type Summary = ElasticRowSummary;
+
+    fn summary(&self, _cx: ()) -> Self::Summary {
+        ElasticRowSummary { rows: 1 }
+    }
+}
+
+impl sum_tree::ContextLessSummary for ElasticRowSummary {
+    fn zero() -> Self {
+        Default::default()
+    }
+
+    fn add_summary(&mut self, summary: &Self) {
+        self.rows += summary.rows;
+    }
+}
+
+impl<'a> sum_tree::Dimension<'a, ElasticRowSummary> for ElasticRowCount {
+    fn zero(_cx: ()) -> Self {
+        Default::default()
+    }
+
+    fn add_summary(&mut self, summary: &'a ElasticRowSummary, _: ()) {
+        self.0 += summary.rows;
+    }
+}
+
+impl ElasticTabstops {
+    pub fn new(fold_snapshot: &FoldSnapshot, tab_size: u32, max_expansion_column: u32) -> Self {
+        let row_count = fold_snapshot.max_point().row() + 1;
+        let cells = row_cells(fold_snapshot, 0..row_count, max_expansion_column);
+        Self {
+            rows: SumTree::from_iter(
+                compute_stops(&cells, tab_size)
+                    .into_iter()
+                    .map(|stops| ElasticRow { stops }),
+                (),
+            ),
+        }
+    }
+
+    /// Returns the column at which each tab on the given row ends.
+    pub fn stops_for_row(&self, row: u32) -> &[u32] {
+        let mut cursor = self.rows.cursor::<ElasticRowCount>(());
+        cursor.seek(&ElasticRowCount(row), Bias::Right);
+        match cursor.item() {
+            Some(row) => &row.stops[..],
+            None => &[],
+        }
+    }
+
+    /// Updates the tabstops for the given edits, recomputing only the blocks of
+    /// tab-separated lines that contain an edited row.
+    ///
+    /// Returns the rows outside of the edits whose tabstops changed as a result.
+    pub fn sync(
+        &self,
+        old_fold_snapshot: &FoldSnapshot,
+        new_fold_snapshot: &FoldSnapshot,
+        fold_edits: &[FoldEdit],
+        tab_size: u32,
+        max_expansion_column: u32,
+    ) -> (Self, Vec<ElasticRowEdit>) {
+        // Convert the edits to row ranges, merging any that touch the same row.
+        let mut row_edits = Vec::<ElasticRowEdit>::with_capacity(fold_edits.len());
+        for edit in fold_edits {
+            let old_rows = edit.old.start.to_point(old_fold_snapshot).row()
+                ..edit.old.end.to_point(old_fold_snapshot).row() + 1;
+            let new_rows = edit.new.start.to_point(new_fold_snapshot).row()
+                ..edit.new.end.to_point(new_fold_snapshot).row() + 1;
+            if let Some(last) = row_edits.last_mut()
+                && old_rows.start < last.old_rows.end
+            {
+                last.old_rows.end = last.old_rows.end.max(old_rows.end);
+                last.new_rows.end = last.new_rows.end.max(new_rows.end);
+            } else {
+                row_edits.push(ElasticRowEdit { old_rows, new_rows });
+            }
+        }
+
+        // Shift the rows that weren't edited, leaving the edited ones empty
+        // until their blocks are recomputed below.
+        let rows = {
+            let mut cursor = self.rows.cursor::<ElasticRowCount>(());
+            let mut rows = SumTree::new(());
+            for edit in &row_edits {
+                rows.append(
+                    cursor.slice(&ElasticRowCount(edit.old_rows.start), Bias::Right),
+                    (),
+                );
+                rows.extend(edit.new_rows.clone().map(|_| ElasticRow::default()), ());
+                cursor.seek(&ElasticRowCount(edit.old_rows.end), Bias::Right);
+            }
+            rows.append(cursor.suffix(), ());
+            rows
+        };
+
+        let row_count = new_fold_snapshot.max_point().row() + 1;
+        let is_edited = |row: u32| {
+            let ix = row_edits.partition_point(|edit| edit.new_rows.end <= row);
+            row_edits
+                .get(ix)
+                .is_some_and(|edit| edit.new_rows.contains(&row))
+        };
+        let has_tabs = |rows: &SumTree<ElasticRow>, row: u32| {
+            if is_edited(row) {
+                row_cells(new_fold_snapshot, row..row + 1, max_expansion_column)
+                    .first()
+                    .is_some_and(|cells| !cells.is_empty())
+            } else {
+                let mut cursor = rows.cursor::<ElasticRowCount>(());
+                cursor.seek(&ElasticRowCount(row), Bias::Right);
+                cursor.item().is_some_and(|row| !row.stops.is_empty())
+            }
+        };
+
+        // Expand every edit to the block of lines with tabs around it, as the
+        // width of a column depends on all of its cells.
+        let mut blocks = Vec::<Range<u32>>::new();
+        for edit in &row_edits {
+            let mut start = edit.new_rows.start;
+            while start > 0 && has_tabs(&rows, start - 1) {
+                start -= 1;
+            }
+            let mut end = edit.new_rows.end.min(row_count);
+            while end < row_count && has_tabs(&rows, end) {
+                end += 1;
+            }
+            if let Some(last) = blocks.last_mut()
+                && start <= last.end
+            {
+                last.end = last.end.max(end);
+            } else {
+                blocks.push(start..end);
+            }
+        }
+
+        let mut changed_rows = Vec::<ElasticRowEdit>::new();
+        let mut new_rows = SumTree::new(());
+        {
+            let mut cursor = rows.cursor::<ElasticRowCount>(());
+            for block in blocks {
+                new_rows.append(cursor.slice(&ElasticRowCount(block.start), Bias::Right), ());
+                let cells = row_cells(new_fold_snapshot, block.clone(), max_expansion_column);
+                for (row, stops) in block.clone().zip(compute_stops(&cells, tab_size)) {
+                    let old_stops = cursor.item().map(|row| row.stops.clone());
+                    if !is_edited(row) && old_stops.as_deref() != Some(&*stops) {
+                        let old_row = old_row_for_new_row(&row_edits, row);
+                        match changed_rows.last_mut() {
+                            Some(last) if last.new_rows.end == row => {
+                                last.old_rows.end = old_row + 1;
+                                last.new_rows.end = row + 1;
+                            }
+                            _ => changed_rows.push(ElasticRowEdit {
+                                old_rows: old_row..old_row + 1,
+                                new_rows: row..row + 1,
+                            }),
+                        }
+                    }
+                    new_rows.push(ElasticRow { stops }, ());
+                    cursor.next();
+                }
+            }
+            new_rows.append(cursor.suffix(), ());
+        }
+
+        (Self { rows: new_rows }, changed_rows)
+    }
+}
+
+/// Maps a row that wasn't edited to its row before the edits.
+fn old_row_for_new_row(row_edits: &[ElasticRowEdit], row: u32) -> u32 {
+    let ix = row_edits.partition_point(|edit| edit.new_rows.end <= row);
+    match ix.checked_sub(1).and_then(|ix| row_edits.get(ix)) {
+        Some(edit) => edit.old_rows.end + (row - edit.new_rows.end),
+        None => row,
+    }
+}
+
+/// Returns the width, in characters, of every tab-terminated cell on each of
+/// the given rows. Tabs at or beyond `max_expansion_column` don't end a cell,
+/// matching how the tab map stops expanding them.
+fn row_cells(
+    fold_snapshot: &FoldSnapshot,
+    rows: Range<u32>,
+    max_expansion_column: u32,
+) -> Vec<Vec<u32>> {
+    let max_point = fold_snapshot.max_point();
+    let start = FoldPoint::new(rows.start, 0).to_offset(fold_snapshot);
+    let end = if rows.end > max_point.row() {
+        max_point
+    } else {
+        FoldPoint::new(rows.end, 0)
+    }
+    .to_offset(fold_snapshot);
+
+    let mut cells = vec![Vec::new()];
+    let mut byte_column = 0;
+    let mut cell_width = 0;
+    for chunk in fold_snapshot.chunks(
+        start..end,
+        LanguageAwareStyling {
+            tree_sitter: false,
+            diagnostics: false,
+        },
+        Highlights::default(),
+    ) {
+        for c in chunk.text.chars() {
+            match c {
+                '\n' => {
+                    cells.push(Vec::new());
+                    byte_column = 0;
+                    cell_width = 0;
+                }
+                '\t' if byte_column < max_expansion_column => {
+                    cells.last_mut().unwrap().push(cell_width);
+                    byte_column += 1;
+                    cell_width = 0;
+                }
+                _ => {
+                    byte_column += c.len_utf8() as u32;
+                    cell_width += 1;
+                }
+            }
+        }
+    }
+    cells.resize_with(rows.len(), Vec::new);
+    cells
+}
+
+/// Computes the end column of every tab, given the widths of the cells they
+/// terminate on each row.
+fn compute_stops(cells: &[Vec<u32>], tab_size: u32) -> Vec<Arc<[u32]>> {
+    let mut stops = vec![Vec::<u32>::new(); cells.len()];
+    let mut column_ix = 0;
+    loop {
+        let mut has_column = false;
+        let mut row = 0;
+        while row < cells.len() {
+            if cells[row].len() <= column_ix {
+                row += 1;
+                continue;
+            }
+
+            let block_start = row;
+            while row < cells.len() && cells[row].len() > column_ix {
+                row += 1;
+            }
+            has_column = true;
+
+            let stop = (block_start..row)
+                .map(|row| {
+                    let cell_start = column_ix
+                        .checked_sub(1)
+                        .map_or(0, |prev_ix| stops[row][prev_ix]);
+                    let cell_end = cell_start + cells[row][column_ix];
+                    (cell_end / tab_size + 1) * tab_size
+                })
+                .max()
+                .unwrap_or(0);
+            for row_stops in &mut stops[block_start..row] {
+                row_stops.push(stop);
+            }
+        }
+
+        if !has_column {
+            break;
+        }
+        column_ix += 1;
+    }
+    stops.into_iter().map(Arc::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_stops() {
+        let stops = |cells: &[&[u32]]| {
+            let cells = cells.iter().map(|row| row.to_vec()).collect::<Vec<_>>();
+            compute_stops(&cells, 4)
+                .into_iter()
+                .map(|stops| stops.to_vec())
+                .collect::<Vec<_>>()
+        };
+
+        // A lone line expands like fixed tabstops.
+        assert_eq!(stops(&[&[1, 6]]), [vec![4, 12]]);
+
+        // Columns are as wide as their widest cell.
+        assert_eq!(
+            stops(&[&[1, 2], &[9], &[0, 0]]),
+            [vec![12, 16], vec![12], vec![12, 16]]
+        );
+
+        // Lines without a cell in a column end its block.
+        assert_eq!(
+            stops(&[&[1, 9], &[1], &[1, 1], &[], &[9]]),
+            [vec![4, 16], vec![4], vec![4, 8], vec![], vec![12]]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use super::{
     Highlights,
+    elastic_tabstops::ElasticTabstops,
     fold_map::{self, Chunk, FoldChunks, FoldEdit, FoldPoint, FoldSnapshot},
 };
 
@@ -17,7 +18,11 @@ const MAX_TABS: NonZeroU32 = NonZeroU32::new(SPACES.len() as u32).unwrap();
 /// Keeps track of hard tabs in a text buffer.
 ///
 /// See the [`display_map` module documentation](crate::display_map) for more information.
-pub struct TabMap(TabSnapshot);
+pub struct TabMap {
+    snapshot: TabSnapshot,
+    /// Whether the next sync should expand tabs with [`ElasticTabstops`].
+    elastic_tabstops: bool,
+}
 
 impl TabMap {
     #[ztracing::instrument(skip_all)]
@@ -26,15 +31,34 @@ impl TabMap {
             fold_snapshot,
             tab_size: tab_size.min(MAX_TABS),
             max_expansion_column: MAX_EXPANSION_COLUMN,
+            elastic_tabstops: None,
             version: 0,
         };
-        (Self(snapshot.clone()), snapshot)
+        (
+            Self {
+                snapshot: snapshot.clone(),
+                elastic_tabstops: false,
+            },
+            snapshot,
+        )
     }
 
     #[cfg(test)]
     pub fn set_max_expansion_column(&mut self, column: u32) -> TabSnapshot {
-        self.0.max_expansion_column = column;
-        self.0.clone()
+        self.snapshot.max_expansion_column = column;
+        if let Some(elastic_tabstops) = self.snapshot.elastic_tabstops.as_mut() {
+            *elastic_tabstops = ElasticTabstops::new(
+                &self.snapshot.fold_snapshot,
+                self.snapshot.tab_size.get(),
+                column,
+            );
+        }
+        self.snapshot.clone()
+    }
+
+    /// Enables or disables elastic tabstops, taking effect on the next sync.
+    pub fn set_elastic_tabstops(&mut self, enabled: bool) {
+        self.elastic_tabstops = enabled;
     }
 
     #[ztracing::instrument(skip_all)]
@@ -46,21 +70,30 @@ impl TabMap {
     ) -> (TabSnapshot, Vec<TabEdit>) {
         let tab_size = tab_size.min(MAX_TABS);
 
-        if self.0.tab_size != tab_size {
-            let old_max_point = self.0.max_point();
-            self.0.version += 1;
-            self.0.fold_snapshot = fold_snapshot;
-            self.0.tab_size = tab_size;
+        if self.snapshot.tab_size != tab_size
+            || self.snapshot.elastic_tabstops.is_some() != self.elastic_tabstops
+        {
+            let old_max_point = self.snapshot.max_point();
+            self.snapshot.version += 1;
+            self.snapshot.elastic_tabstops = self.elastic_tabstops.then(|| {
+                ElasticTabstops::new(
+                    &fold_snapshot,
+                    tab_size.get(),
+                    self.snapshot.max_expansion_column,
+                )
+            });
+            self.snapshot.fold_snapshot = fold_snapshot;
+            self.snapshot.tab_size = tab_size;
             return (
-                self.0.clone(),
+                self.snapshot.clone(),
                 vec![TabEdit {
                     old: TabPoint::zero()..old_max_point,
-                    new: TabPoint::zero()..self.0.max_point(),
+                    new: TabPoint::zero()..self.snapshot.max_point(),
                 }],
             );
         }
 
-        let old_snapshot = &mut self.0;
+        let old_snapshot = &mut self.snapshot;
         let mut new_version = old_snapshot.version;
         if old_snapshot.fold_snapshot.version != fold_snapshot.version {
             new_version += 1;
@@ -143,15 +176,51 @@ impl TabMap {
             }
         }
 
+        // With elastic tabstops, an edit can also change the width of tabs on
+        // the other lines of its block, so those lines are edited too.
+        let elastic_tabstops = if let Some(elastic_tabstops) = &old_snapshot.elastic_tabstops {
+            let (elastic_tabstops, row_edits) = elastic_tabstops.sync(
+                &old_snapshot.fold_snapshot,
+                &fold_snapshot,
+                &fold_edits,
+                tab_size.get(),
+                old_snapshot.max_expansion_column,
+            );
+            if !row_edits.is_empty() {
+                for row_edit in row_edits {
+                    let old_end_row = row_edit.old_rows.end - 1;
+                    let new_end_row = row_edit.new_rows.end - 1;
+                    fold_edits.push(FoldEdit {
+                        old: FoldPoint::new(row_edit.old_rows.start, 0)
+                            .to_offset(&old_snapshot.fold_snapshot)
+                            ..FoldPoint::new(
+                                old_end_row,
+                                old_snapshot.fold_snapshot.line_len(old_end_row),
+                            )
+                            .to_offset(&old_snapshot.fold_snapshot),
+                        new: FoldPoint::new(row_edit.new_rows.start, 0).to_offset(&fold_snapshot)
+                            ..FoldPoint::new(new_end_row, fold_snapshot.line_len(new_end_row))
+                                .to_offset(&fold_snapshot),
+                    });
+                }
+                fold_edits.sort_unstable_by_key(|edit| edit.old.start);
+            }
+            Some(elastic_tabstops)
+        } else {
+            None
+        };
+
         let new_snapshot = TabSnapshot {
             fold_snapshot,
             tab_size,
             max_expansion_column: old_snapshot.max_expansion_column,
+            elastic_tabstops,
             version: new_version,
         };
 
         let _old_alloc_ptr = fold_edits.as_ptr();
-        // Combine any edits that overlap due to the expansion.
+        // Combine any edits that overlap due to the expansion. An elastic row
+        // edit can contain the edits after it, so the ends only ever grow.
         let mut fold_edits = fold_edits.into_iter();
         let mut first_edit = fold_edits.next().unwrap();
         // This code relies on reusing allocations from the Vec<_> - at the time of writing .flatten() prevents them.
@@ -159,8 +228,8 @@ impl TabMap {
         let mut v: Vec<_> = fold_edits
             .scan(&mut first_edit, |state, edit| {
                 if state.old.end >= edit.old.start {
-                    state.old.end = edit.old.end;
-                    state.new.end = edit.new.end;
+                    state.old.end = state.old.end.max(edit.old.end);
+                    state.new.end = state.new.end.max(edit.new.end);
                     Some(None) // Skip this edit, it's merged
                 } else {
                     let new_state = edit;
@@ -200,6 +269,8 @@ pub struct TabSnapshot {
     /// The maximum column up to which a tab can expand.
     /// Any tab after this column will not expand.
     pub max_expansion_column: u32,
+    /// The column widths used when expanding tabs elastically, if enabled.
+    elastic_tabstops: Option<ElasticTabstops>,
     pub version: usize,
 }
 
@@ -317,7 +388,6 @@ impl TabSnapshot {
             max_expansion_column: self.max_expansion_column,
             output_position: range.start.0,
             max_output_position: range.end.0,
-            tab_size: self.tab_size,
             chunk: Chunk {
                 text: unsafe { std::str::from_utf8_unchecked(&SPACES[..to_next_stop as usize]) },
                 is_tab: true,
@@ -365,7 +435,7 @@ impl TabSnapshot {
     pub fn fold_point_to_tab_point(&self, input: FoldPoint) -> TabPoint {
         let chunks = self.fold_snapshot.chunks_at(FoldPoint::new(input.row(), 0));
         let tab_cursor = TabStopCursor::new(chunks);
-        let expanded = self.expand_tabs(tab_cursor, input.column());
+        let expanded = self.expand_tabs(tab_cursor, input.row(), input.column());
         TabPoint::new(input.row(), expanded)
     }
 
@@ -383,7 +453,7 @@ impl TabSnapshot {
         let tab_cursor = TabStopCursor::new(chunks);
         let expanded = output.column();
         let (collapsed, expanded_char_column, to_next_stop) =
-            self.collapse_tabs(tab_cursor, expanded, bias);
+            self.collapse_tabs(tab_cursor, output.row(), expanded, bias);
 
         (
             FoldPoint::new(output.row(), collapsed),
@@ -413,13 +483,35 @@ impl TabSnapshot {
             .to_buffer_point(inlay_point)
     }
 
+    /// Returns the column at which each tab on the given row ends when
+    /// elastic tabstops are enabled, or an empty slice otherwise.
+    fn tab_stops_for_row(&self, row: u32) -> &[u32] {
+        match &self.elastic_tabstops {
+            Some(elastic_tabstops) => elastic_tabstops.stops_for_row(row),
+            None => &[],
+        }
+    }
+
+    /// Returns the number of columns a tab at the given character column
+    /// expands to, falling back to fixed tabstops past the row's last stop.
+    fn tab_len(&self, stops: &[u32], column: u32) -> u32 {
+        let ix = stops.partition_point(|&stop| stop <= column);
+        match stops.get(ix) {
+            Some(stop) => stop - column,
+            None => {
+                let tab_size = self.tab_size.get();
+                tab_size - column % tab_size
+            }
+        }
+    }
+
     #[ztracing::instrument(skip_all)]
-    fn expand_tabs<'a>(&self, mut cursor: TabStopCursor<'a>, column: u32) -> u32 {
+    fn expand_tabs<'a>(&self, mut cursor: TabStopCursor<'a>, row: u32, column: u32) -> u32 {
         // we only ever act on a single row at a time
         // so the main difference is that other layers build a transform sumtree, and can then just run through that
         // we cant quite do this here, as we need to work with the previous layer chunk to understand the tabs of the corresponding row
         // we can still do forward searches for this though, we search for a row, then traverse the column up to where we need to be
-        let tab_size = self.tab_size.get();
+        let stops = self.tab_stops_for_row(row);
 
         let end_column = column.min(self.max_expansion_column);
         let mut seek_target = end_column;
@@ -428,7 +520,7 @@ impl TabSnapshot {
 
         while let Some(tab_stop) = cursor.seek_forward(seek_target) {
             let expanded_chars_old = tab_stop.char_offset + expanded_tab_len - tab_count;
-            let tab_len = tab_size - ((expanded_chars_old - 1) % tab_size);
+            let tab_len = self.tab_len(stops, expanded_chars_old - 1);
             tab_count += 1;
             expanded_tab_len += tab_len;
 
@@ -452,10 +544,11 @@ impl TabSnapshot {
     fn collapse_tabs<'a>(
         &self,
         mut cursor: TabStopCursor<'a>,
+        row: u32,
         column: u32,
         bias: Bias,
     ) -> (u32, u32, u32) {
-        let tab_size = self.tab_size.get();
+        let stops = self.tab_stops_for_row(row);
         let mut collapsed_column = column;
         let mut seek_target = column.min(self.max_expansion_column);
         let mut tab_count = 0;
@@ -464,7 +557,7 @@ impl TabSnapshot {
         while let Some(tab_stop) = cursor.seek_forward(seek_target) {
             // Calculate how much we want to expand this tab stop (into spaces)
             let expanded_chars_old = tab_stop.char_offset + expanded_tab_len - tab_count;
-            let tab_len = tab_size - ((expanded_chars_old - 1) % tab_size);
+            let tab_len = self.tab_len(stops, expanded_chars_old - 1);
             // Increment tab count
             tab_count += 1;
             // The count of how many spaces we've added to this line in place of tab bytes
@@ -605,7 +698,6 @@ pub struct TabChunks<'a> {
     snapshot: &'a TabSnapshot,
     max_expansion_column: u32,
     max_output_position: Point,
-    tab_size: NonZeroU32,
     // region: iteration state
     fold_chunks: FoldChunks<'a>,
     chunk: Chunk<'a>,
@@ -674,12 +766,14 @@ impl<'a> Iterator for TabChunks<'a> {
             self.chunk.chars >>= 1;
             self.chunk.newlines >>= 1;
 
-            let tab_size = if self.input_column < self.max_expansion_column {
-                self.tab_size.get()
+            let mut len = if self.input_column < self.max_expansion_column {
+                self.snapshot.tab_len(
+                    self.snapshot.tab_stops_for_row(self.output_position.row),
+                    self.column,
+                )
             } else {
                 1
             };
-            let mut len = tab_size - self.column % tab_size;
             let next_output_position = cmp::min(
                 self.output_position + Point::new(0, len),
                 self.max_output_position,
@@ -1242,6 +1336,121 @@ mod tests {
         assert_eq!(tab_snapshot.text(), input);
     }
 
+    #[gpui::test]
+    fn test_elastic_tabstops(cx: &mut gpui::App) {
+        let tab_size = NonZeroU32::new(4).unwrap();
+        let buffer = MultiBuffer::build_simple("a\tb\nlonger\tc\n\nx\ty", cx);
+        let subscription = buffer.update(cx, |buffer, _| buffer.subscribe());
+        let buffer_snapshot = buffer.read(cx).snapshot(cx);
+        let (mut inlay_map, inlay_snapshot) = InlayMap::new(buffer_snapshot);
+        let (mut fold_map, fold_snapshot) = FoldMap::new(inlay_snapshot);
+        let (mut tab_map, _) = TabMap::new(fold_snapshot.clone(), tab_size);
+        tab_map.set_elastic_tabstops(true);
+        let (tab_snapshot, _) = tab_map.sync(fold_snapshot, Vec::new(), tab_size);
+        assert_eq!(tab_snapshot.text(), "a       b\nlonger  c\n\nx   y");
+
+        let fold_text = tab_snapshot.fold_snapshot.text();
+        for (row, line) in fold_text.split('\n').enumerate() {
+            for (column, _) in line.char_indices() {
+                let fold_point = FoldPoint::new(row as u32, column as u32);
+                let tab_point = tab_snapshot.fold_point_to_tab_point(fold_point);
+                assert_eq!(
+                    tab_snapshot
+                        .tab_point_to_fold_point(tab_point, Bias::Left)
+                        .0,
+                    fold_point,
+                    "round trip of {fold_point:?}"
+                );
+            }
+        }
+
+        // Shortening the widest cell of a column narrows it on the other lines
+        // of the block, which are reported as edited.
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit([(Point::new(1, 1)..Point::new(1, 6), "")], None, cx)
+        });
+        let buffer_snapshot = buffer.read(cx).snapshot(cx);
+        let (inlay_snapshot, inlay_edits) =
+            inlay_map.sync(buffer_snapshot, subscription.consume().into_inner());
+        let (fold_snapshot, fold_edits) = fold_map.read(inlay_snapshot, inlay_edits);
+        let (tab_snapshot, tab_edits) = tab_map.sync(fold_snapshot, fold_edits, tab_size);
+        assert_eq!(tab_snapshot.text(), "a   b\nl   c\n\nx   y");
+        assert_eq!(
+            tab_edits,
+            [
+                TabEdit {
+                    old: TabPoint::new(0, 0)..TabPoint::new(0, 9),
+                    new: TabPoint::new(0, 0)..TabPoint::new(0, 5),
+                },
+                TabEdit {
+                    old: TabPoint::new(1, 1)..TabPoint::new(1, 8),
+                    new: TabPoint::new(1, 1)..TabPoint::new(1, 4),
+                },
+            ]
+        );
+
+        tab_map.set_elastic_tabstops(false);
+        let (tab_snapshot, _) =
+            tab_map.sync(tab_snapshot.fold_snapshot.clone(), Vec::new(), tab_size);
+        assert_eq!(tab_snapshot.text(), "a   b\nl   c\n\nx   y");
+    }
+
+    #[gpui::test(iterations = 100)]
+    fn test_random_elastic_tabstops(cx: &mut gpui::App, mut rng: StdRng) {
+        let tab_size = NonZeroU32::new(rng.random_range(1..=4)).unwrap();
+        let random_text = |rng: &mut StdRng| {
+            let len = rng.random_range(0..20);
+            (0..len)
+                .map(|_| ['a', 'b', '\t', '\t', '\n'][rng.random_range(0..5)])
+                .collect::<String>()
+        };
+        let buffer = MultiBuffer::build_simple(&random_text(&mut rng), cx);
+        let subscription = buffer.update(cx, |buffer, _| buffer.subscribe());
+        let buffer_snapshot = buffer.read(cx).snapshot(cx);
+        let (mut inlay_map, inlay_snapshot) = InlayMap::new(buffer_snapshot);
+        let (mut fold_map, fold_snapshot) = FoldMap::new(inlay_snapshot);
+        let (mut tab_map, _) = TabMap::new(fold_snapshot.clone(), tab_size);
+        tab_map.set_elastic_tabstops(true);
+        let (mut tab_snapshot, _) = tab_map.sync(fold_snapshot, Vec::new(), tab_size);
+
+        for _ in 0..5 {
+            buffer.update(cx, |buffer, cx| {
+                let snapshot = buffer.snapshot(cx);
+                let end = rng.random_range(0..=snapshot.len().0);
+                let start = rng.random_range(0..=end);
+                let range = MultiBufferOffset(start)..MultiBufferOffset(end);
+                buffer.edit([(range, random_text(&mut rng))], None, cx);
+            });
+            let buffer_snapshot = buffer.read(cx).snapshot(cx);
+            log::info!("Buffer text: {:?}", buffer_snapshot.text());
+            let (inlay_snapshot, inlay_edits) =
+                inlay_map.sync(buffer_snapshot, subscription.consume().into_inner());
+            let (fold_snapshot, fold_edits) = fold_map.read(inlay_snapshot, inlay_edits);
+            let (new_tab_snapshot, tab_edits) =
+                tab_map.sync(fold_snapshot.clone(), fold_edits, tab_size);
+
+            let (mut expected_tab_map, _) = TabMap::new(fold_snapshot.clone(), tab_size);
+            expected_tab_map.set_elastic_tabstops(true);
+            let (expected_snapshot, _) = expected_tab_map.sync(fold_snapshot, Vec::new(), tab_size);
+            assert_eq!(new_tab_snapshot.text(), expected_snapshot.text());
+
+            let mut text = text::Rope::from(tab_snapshot.text().as_str());
+            let new_text = text::Rope::from(new_tab_snapshot.text().as_str());
+            for edit in tab_edits.iter().rev() {
+                let old_range =
+                    text.point_to_offset(edit.old.start.0)..text.point_to_offset(edit.old.end.0);
+                let new_range = new_text.point_to_offset(edit.new.start.0)
+                    ..new_text.point_to_offset(edit.new.end.0);
+                text.replace(
+                    old_range,
+                    &new_text.chunks_in_range(new_range).collect::<String>(),
+                );
+            }
+            assert_eq!(text.to_string(), new_tab_snapshot.text(), "{tab_edits:?}");
+            tab_snapshot = new_tab_snapshot;
+        }
+    }
+
     #[gpui::test]
     fn test_marking_tabs(cx: &mut gpui::App) {
         let input = "\t \thello";
@@ -65,8 +65,10 @@ mod completions;
 mod config;
 mod diagnostics;
 mod edit_prediction;
+mod file_view_state;
 mod input;
 mod markdown_actions;
+mod narrow;
 mod navigation;
 mod rewrap;
 mod selection;
@@ -221,6 +223,7 @@ use project::{
         LspFormatTarget, OpenLspBufferHandle,
     },
     project_settings::{DiagnosticSeverity, GoToDiagnosticSeverityFilter, ProjectSettings},
+    review_comment_store::{ReviewCommentStore, ReviewCommentStoreEvent},
 };
 use rand::seq::SliceRandom;
 use regex::Regex;
@@ -1119,6 +1122,7 @@ pub struct Editor {
     stored_review_comments: Vec<(DiffHunkKey, Vec<StoredReviewComment>)>,
     /// Counter for generating unique comment IDs.
     next_review_comment_id: usize,
+    review_comment_store: Option<Entity<ReviewCommentStore>>,
     hovered_diff_hunk_row: Option<DisplayRow>,
     pull_diagnostics_task: Task<()>,
     in_project_search: bool,
@@ -1135,6 +1139,7 @@ pub struct Editor {
     _scroll_cursor_center_top_bottom_task: Task<()>,
     serialize_selections: Task<()>,
     serialize_folds: Task<()>,
+    serialize_file_view_state: Task<()>,
     minimap: Option<Entity<Self>>,
     pub change_list: ChangeList,
     inline_value_cache: InlineValueCache,
@@ -1152,6 +1157,7 @@ pub struct Editor {
     folding_newlines: Task<()>,
     select_next_is_case_sensitive: Option<bool>,
     pub lookup_key: Option<Box<dyn Any + Send + Sync>>,
+    narrowed_region: Option<narrow::NarrowedRegion>,
     on_local_selections_changed:
         Option<Box<dyn Fn(Point, &mut Window, &mut Context<Self>) + 'static>>,
     suppress_selection_callback: bool,
@@ -2243,6 +2249,13 @@ impl Editor {
             _ => None,
         };
 
+        let review_comment_store = match (&mode, project.as_ref()) {
+            (EditorMode::Full { .. }, Some(project)) => {
+                Some(project.read(cx).review_comment_store())
+            }
+            _ => None,
+        };
+
         let breakpoint_store = match (&mode, project.as_ref()) {
             (EditorMode::Full { .. }, Some(project)) => Some(project.read(cx).breakpoint_store()),
             _ => None,
@@ -2424,6 +2437,7 @@ impl Editor {
             diff_review_overlays: Vec::new(),
             stored_review_comments: Vec::new(),
             next_review_comment_id: 0,
+            review_comment_store,
             hovered_diff_hunk_row: None,
             _subscriptions: (!is_minimap)
                 .then(|| {
@@ -2462,6 +2476,7 @@ impl Editor {
             toggle_fold_multiple_buffers: Task::ready(()),
             serialize_selections: Task::ready(()),
             serialize_folds: Task::ready(()),
+            serialize_file_view_state: Task::ready(()),
             text_style_refinement: None,
             load_diff_task: None,
             diff_hunk_delegate: None,
@@ -2471,6 +2486,7 @@ impl Editor {
             selection_drag_state: SelectionDragState::None,
             folding_newlines: Task::ready(()),
             lookup_key: None,
+            narrowed_region: None,
             select_next_is_case_sensitive: None,
             on_local_selections_changed: None,
             suppress_selection_callback: false,
@@ -2516,6 +2532,15 @@ impl Editor {
                     cx.notify();
                 }));
         }
+        if let Some(review_comments) = editor.review_comment_store.clone() {
+            editor._subscriptions.push(cx.subscribe(
+                &review_comments,
+                |editor, _, _: &ReviewCommentStoreEvent, cx| {
+                    editor.sync_review_comments_from_store(cx);
+                },
+            ));
+            editor.sync_review_comments_from_store(cx);
+        }
         editor._subscriptions.extend(project_subscriptions);
 
         editor._subscriptions.push(cx.subscribe_in(
@@ -4283,6 +4308,7 @@ impl Editor {
             });
 
         let run_to_cursor = window.is_action_available(&RunToCursor, cx);
+        let jump_to_cursor = window.is_action_available(&JumpToCursor, cx);
 
         let toggle_state_entry: Option<(&str, Box<dyn Action>)> =
             breakpoint.as_ref().map(|bp| match bp.1.state {
@@ -4340,8 +4366,33 @@ impl Editor {
                             window.dispatch_action(Box::new(RunToCursor), cx);
                         },
                     )
-                    .separator()
                 })
+                .when(jump_to_cursor, |this| {
+                    let weak_editor = weak_editor.clone();
+                    this.entry(
+                        "Jump to Cursor",
+                        Some(JumpToCursor.boxed_clone()),
+                        move |window, cx| {
+                            weak_editor
+                                .update(cx, |editor, cx| {
+                                    editor.change_selections(
+                                        SelectionEffects::no_scroll(),
+                                        window,
+                                        cx,
+                                        |s| {
+                                            s.select_ranges(
+                                                [Point::new(row, 0)..Point::new(row, 0)],
+                                            )
+                                        },
+                                    );
+                                })
+                                .ok();
+
+                            window.dispatch_action(Box::new(JumpToCursor), cx);
+                        },
+                    )
+                })
+                .when(run_to_cursor || jump_to_cursor, |this| this.separator())
                 .when_some(toggle_state_entry, |this, (msg, action)| {
                     this.entry(msg, Some(action), {
                         let weak_editor = weak_editor.clone();
@@ -9753,6 +9804,7 @@ impl Editor {
                 self.colorize_brackets(false, cx);
                 self.refresh_selected_text_highlights(&self.display_snapshot(cx), true, window, cx);
                 self.semantic_token_state.invalidate_buffer(&buffer_id);
+                self.sync_review_comments_from_store(cx);
                 cx.emit(EditorEvent::BufferRangesUpdated {
                     buffer: buffer.clone(),
                     ranges: ranges.clone(),
@@ -43979,3 +43979,81 @@ async fn assert_range_format_merge(
     );
     assert!(!cx.read(|cx| editor.is_dirty(cx)));
 }
+
+#[gpui::test]
+async fn test_narrow_to_selection_and_widen(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/a"),
+        json!({ "main.rs": "one\ntwo\nthree\nfour\nfive\n" }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/a").as_ref()], cx).await;
+    let window = cx.add_window(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+    let cx = &mut VisualTestContext::from_window(*window, cx);
+    let workspace = window
+        .read_with(cx, |mw, _| mw.workspace().clone())
+        .unwrap();
+    let worktree_id = workspace.update_in(cx, |workspace, _, cx| {
+        workspace.project().update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        })
+    });
+    let editor = workspace
+        .update_in(cx, |workspace, window, cx| {
+            workspace.open_path((worktree_id, rel_path("main.rs")), None, true, window, cx)
+        })
+        .await
+        .unwrap()
+        .downcast::<Editor>()
+        .unwrap();
+
+    editor.update_in(cx, |editor, window, cx| {
+        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_ranges([Point::new(1, 1)..Point::new(2, 2)])
+        });
+        editor.narrow_to_selection(&NarrowToSelection, window, cx);
+    });
+    cx.run_until_parked();
+
+    let narrowed_editor = workspace.read_with(cx, |workspace, cx| {
+        workspace.active_item_as::<Editor>(cx).unwrap()
+    });
+    assert_ne!(narrowed_editor, editor);
+    narrowed_editor.update_in(cx, |editor, window, cx| {
+        assert!(editor.is_narrowed());
+        assert_eq!(editor.text(cx), "two\nthree");
+
+        // Edits are confined to the region and made to the shared buffer.
+        editor.select_all(&SelectAll, window, cx);
+        editor.handle_input("2\n3", window, cx);
+        assert_eq!(editor.text(cx), "2\n3");
+    });
+    assert_eq!(
+        editor.read_with(cx, |editor, cx| editor.text(cx)),
+        "one\n2\n3\nfour\nfive\n"
+    );
+
+    narrowed_editor.update_in(cx, |editor, window, cx| {
+        editor.widen(&Widen, window, cx);
+    });
+    cx.run_until_parked();
+
+    workspace.read_with(cx, |workspace, cx| {
+        assert_eq!(workspace.active_item_as::<Editor>(cx).unwrap(), editor);
+        assert_eq!(workspace.active_pane().read(cx).items_len(), 1);
+    });
+    editor.update(cx, |editor, cx| {
+        assert_eq!(
+            editor
+                .selections
+                .all::<Point>(&editor.display_snapshot(cx))
+                .into_iter()
+                .map(|selection| selection.range())
+                .collect::<Vec<_>>(),
+            [Point::new(2, 1)..Point::new(2, 1)]
+        );
+    });
+}
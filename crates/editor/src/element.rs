@@ -395,7 +395,9 @@ impl EditorElement {
         register_action(editor, window, Editor::select_around_delimiters);
         register_action(editor, window, Editor::undo_selection);
         register_action(editor, window, Editor::redo_selection);
-        if editor.read(cx).buffer_kind(cx) == ItemBufferKind::Multibuffer {
+        if editor.read(cx).buffer_kind(cx) == ItemBufferKind::Multibuffer
+            && !editor.read(cx).is_narrowed()
+        {
             register_action(editor, window, Editor::expand_excerpts);
             register_action(editor, window, Editor::expand_excerpts_up);
             register_action(editor, window, Editor::expand_excerpts_down);
@@ -562,6 +564,9 @@ impl EditorElement {
         register_action(editor, window, Editor::open_active_item_in_terminal);
         register_action(editor, window, Editor::spawn_nearest_task);
         register_action(editor, window, Editor::open_selections_in_multibuffer);
+        register_action(editor, window, Editor::narrow_to_selection);
+        register_action(editor, window, Editor::narrow_to_enclosing_symbol);
+        register_action(editor, window, Editor::widen);
         register_action(editor, window, Editor::toggle_bookmark);
         register_action(editor, window, Editor::toggle_bookmark_with_label);
         register_action(editor, window, Editor::edit_bookmark);
@@ -0,0 +1,328 @@
+use super::*;
+use crate::persistence::{SerializedFileSelection, SerializedFileViewState};
+use collections::FxHasher;
+use std::hash::Hasher as _;
+
+/// How many rows above and below a stored row are searched for a line with the
+/// same text, when restoring the view state of a file that changed on disk.
+const MAX_REANCHOR_DISTANCE: u32 = 1000;
+
+impl Editor {
+    /// Persists the cursor and scroll position of the file open in this editor
+    /// by its path, so they are restored when the file is opened again after
+    /// its tab was closed.
+    pub(super) fn serialize_file_view_state(&mut self, cx: &mut Context<Self>) {
+        if !self.mode.is_full()
+            || WorkspaceSettings::get(None, cx).restore_on_startup
+                == RestoreOnStartupBehavior::EmptyTab
+        {
+            return;
+        }
+        let Some(workspace_id) = self.workspace_serialization_id(cx) else {
+            return;
+        };
+        let Some(file_path) = self.buffer().read(cx).as_singleton().and_then(|buffer| {
+            project::File::from_dyn(buffer.read(cx).file())
+                .map(|file| Arc::<Path>::from(file.abs_path(cx)))
+        }) else {
+            return;
+        };
+
+        let display_snapshot = self
+            .display_map
+            .update(cx, |display_map, cx| display_map.snapshot(cx));
+        let Some(buffer_snapshot) = display_snapshot.buffer_snapshot().as_singleton() else {
+            return;
+        };
+        let line_at = |row: u32| -> String {
+            buffer_snapshot
+                .text_for_range(Point::new(row, 0)..Point::new(row, buffer_snapshot.line_len(row)))
+                .collect()
+        };
+
+        let scroll_anchor = self.scroll_manager.native_anchor(&display_snapshot, cx);
+        let scroll_top_row = scroll_anchor
+            .anchor
+            .text_anchor_in(buffer_snapshot)
+            .to_point(buffer_snapshot)
+            .row;
+        let selections = self
+            .selections
+            .disjoint_anchors_arc()
+            .iter()
+            .map(|selection| {
+                let start = selection
+                    .start
+                    .text_anchor_in(buffer_snapshot)
+                    .to_point(buffer_snapshot);
+                let end = selection
+                    .end
+                    .text_anchor_in(buffer_snapshot)
+                    .to_point(buffer_snapshot);
+                SerializedFileSelection {
+                    start_row: start.row,
+                    start_column: start.column,
+                    end_row: end.row,
+                    end_column: end.column,
+                    start_line: line_at(start.row),
+                    end_line: line_at(end.row),
+                }
+            })
+            .collect();
+        let mut state = SerializedFileViewState {
+            content_fingerprint: String::new(),
+            scroll_top_row,
+            scroll_top_line: line_at(scroll_top_row),
+            scroll_horizontal_offset: scroll_anchor.offset.x,
+            scroll_vertical_offset: scroll_anchor.offset.y,
+            selections,
+        };
+        let text = buffer_snapshot.as_rope().clone();
+
+        let background_executor = cx.background_executor().clone();
+        let db = EditorDb::global(cx);
+        self.serialize_file_view_state = cx.background_spawn(async move {
+            background_executor.timer(SERIALIZATION_THROTTLE_TIME).await;
+            state.content_fingerprint = content_fingerprint(&text);
+            db.save_file_view_state(workspace_id, file_path, state)
+                .await
+                .with_context(|| {
+                    format!("persisting file view state for workspace {workspace_id:?}")
+                })
+                .log_err();
+        });
+    }
+
+    /// Restores the cursor and scroll position last persisted for the file at
+    /// `file_path`, unless the editor has already been moved away from the
+    /// start of the file. If the file changed since the state was saved, each
+    /// position is moved to the nearest row that still has the same text.
+    pub(super) fn restore_file_view_state(
+        &mut self,
+        workspace_id: WorkspaceId,
+        file_path: PathBuf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.mode.is_full()
+            || WorkspaceSettings::get(None, cx).restore_on_startup
+                == RestoreOnStartupBehavior::EmptyTab
+            || !self.has_initial_view_state(cx)
+        {
+            return;
+        }
+        let Some(buffer) = self.buffer().read(cx).as_singleton() else {
+            return;
+        };
+
+        let db = EditorDb::global(cx);
+        let Some(stored_state) = db.get_file_view_state(workspace_id, &file_path).log_err() else {
+            return;
+        };
+        let Some((
+            stored_fingerprint,
+            scroll_top_row,
+            scroll_top_line,
+            scroll_horizontal_offset,
+            scroll_vertical_offset,
+        )) = stored_state
+        else {
+            return;
+        };
+        let Some(stored_selections) = db.get_file_selections(workspace_id, &file_path).log_err()
+        else {
+            return;
+        };
+
+        let text = buffer.read(cx).as_rope().clone();
+        let fingerprint = cx.background_spawn(async move { content_fingerprint(&text) });
+        cx.spawn_in(window, async move |editor, cx| {
+            let unchanged = fingerprint.await == stored_fingerprint;
+            editor
+                .update_in(cx, |editor, window, cx| {
+                    // The cursor may have been placed while the fingerprint was computed,
+                    // e.g. when the file was opened to navigate to a definition.
+                    if !editor.has_initial_view_state(cx) {
+                        return;
+                    }
+
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    let max_row = buffer_snapshot.max_point().row;
+                    let line_at = |row: u32| -> String {
+                        buffer_snapshot
+                            .text_for_range(
+                                Point::new(row, 0)..Point::new(row, buffer_snapshot.line_len(row)),
+                            )
+                            .collect()
+                    };
+                    let reanchor = |row: u32, line: &str| {
+                        if unchanged {
+                            row.min(max_row)
+                        } else {
+                            reanchor_row(row, line, max_row, &line_at)
+                        }
+                    };
+
+                    let selections = stored_selections
+                        .into_iter()
+                        .map(
+                            |(
+                                start_row,
+                                start_column,
+                                end_row,
+                                end_column,
+                                start_line,
+                                end_line,
+                            )| {
+                                let new_start_row = reanchor(start_row, &start_line);
+                                // Look for the end where it would be if it moved with the start.
+                                let shifted_end_row = end_row
+                                    .saturating_add(new_start_row)
+                                    .saturating_sub(start_row);
+                                let new_end_row = reanchor(shifted_end_row, &end_line);
+                                let start = buffer_snapshot.clip_point(
+                                    Point::new(new_start_row, start_column),
+                                    Bias::Left,
+                                );
+                                let end = buffer_snapshot
+                                    .clip_point(Point::new(new_end_row, end_column), Bias::Left);
+                                start..end.max(start)
+                            },
+                        )
+                        .collect::<Vec<_>>();
+                    if !selections.is_empty() {
+                        // skip adding the initial selection to selection history
+                        editor.selection_history.mode = SelectionHistoryMode::Skipping;
+                        editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                            s.select_ranges(selections);
+                        });
+                        editor.selection_history.mode = SelectionHistoryMode::Normal;
+                    }
+
+                    let top_row = reanchor(scroll_top_row, &scroll_top_line);
+                    let top_anchor = editor
+                        .buffer()
+                        .read(cx)
+                        .snapshot(cx)
+                        .anchor_before(Point::new(top_row, 0));
+                    editor.set_scroll_anchor(
+                        ScrollAnchor {
+                            offset: gpui::Point::new(
+                                scroll_horizontal_offset,
+                                scroll_vertical_offset,
+                            ),
+                            anchor: top_anchor,
+                        },
+                        window,
+                        cx,
+                    );
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    /// Whether the editor still has a single cursor at the start of the file
+    /// and is scrolled to the top, as it is when a file is first opened.
+    fn has_initial_view_state(&mut self, cx: &mut Context<Self>) -> bool {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.disjoint_anchors_arc();
+        let cursor_at_start = match selections.as_ref() {
+            [selection] => {
+                selection.start.to_offset(&snapshot) == MultiBufferOffset(0)
+                    && selection.end.to_offset(&snapshot) == MultiBufferOffset(0)
+            }
+            _ => false,
+        };
+        cursor_at_start && self.scroll_position(cx) == gpui::Point::default()
+    }
+}
+
+/// A hash of the text of a file, used to tell whether stored rows still point
+/// at the same text. The bytes are hashed one by one so that the result does
+/// not depend on how the text is split into chunks.
+fn content_fingerprint(text: &Rope) -> String {
+    let mut hasher = FxHasher::default();
+    for chunk in text.chunks() {
+        for byte in chunk.bytes() {
+            hasher.write_u8(byte);
+        }
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+/// Returns the row nearest to `row` whose text is `line`, or `row` clamped to
+/// `max_row` if there is none within [`MAX_REANCHOR_DISTANCE`] rows. Blank
+/// lines are too common to identify a position, so they are never searched for.
+fn reanchor_row(row: u32, line: &str, max_row: u32, line_at: &dyn Fn(u32) -> String) -> u32 {
+    if !line.trim().is_empty() {
+        for distance in 0..=MAX_REANCHOR_DISTANCE {
+            let below = row.checked_add(distance).filter(|row| *row <= max_row);
+            let above = row
+                .checked_sub(distance)
+                .filter(|row| distance > 0 && *row <= max_row);
+            for candidate in below.into_iter().chain(above) {
+                if line_at(candidate) == line {
+                    return candidate;
+                }
+            }
+            if distance >= row && row.saturating_add(distance) >= max_row {
+                break;
+            }
+        }
+    }
+    row.min(max_row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reanchor_row() {
+        let lines = [
+            "fn a() {",
+            "",
+            "    one();",
+            "}",
+            "",
+            "fn b() {",
+            "    one();",
+            "}",
+        ];
+        let line_at = |row: u32| lines[row as usize].to_string();
+        let max_row = lines.len() as u32 - 1;
+
+        // Unchanged rows are kept.
+        assert_eq!(reanchor_row(5, "fn b() {", max_row, &line_at), 5);
+        // Moved rows are found in either direction.
+        assert_eq!(reanchor_row(2, "fn b() {", max_row, &line_at), 5);
+        assert_eq!(reanchor_row(7, "fn a() {", max_row, &line_at), 0);
+        // The nearest of several matching rows is used.
+        assert_eq!(reanchor_row(5, "    one();", max_row, &line_at), 6);
+        assert_eq!(reanchor_row(3, "    one();", max_row, &line_at), 2);
+        // Rows past the end of the file are searched from the end.
+        assert_eq!(reanchor_row(20, "}", max_row, &line_at), 7);
+        // Blank and deleted lines keep their row, clamped to the file.
+        assert_eq!(reanchor_row(3, "", max_row, &line_at), 3);
+        assert_eq!(reanchor_row(6, "fn c() {", max_row, &line_at), 6);
+        assert_eq!(reanchor_row(20, "fn c() {", max_row, &line_at), max_row);
+    }
+
+    #[test]
+    fn test_content_fingerprint() {
+        let text = "fn main() {\n    println!(\"hello\");\n}\n".repeat(100);
+        let mut chunked = Rope::new();
+        for line in text.split_inclusive('\n') {
+            chunked.push(line);
+        }
+        assert_eq!(
+            content_fingerprint(&Rope::from(text.as_str())),
+            content_fingerprint(&chunked)
+        );
+        assert_ne!(
+            content_fingerprint(&Rope::from(text.as_str())),
+            content_fingerprint(&Rope::from("fn main() {}\n"))
+        );
+    }
+}
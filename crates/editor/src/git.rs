@@ -6,7 +6,7 @@ use ::git::{
     status::FileStatus,
 };
 use buffer_diff::{BufferDiff, DiffHunkStatus, DiffHunkStatusKind};
-use project::git_store::Repository;
+use project::{git_store::Repository, review_comment_store::ReviewCommentId};
 
 #[derive(Clone)]
 pub struct ResolvedDiffHunk {
@@ -323,6 +323,9 @@ pub(super) struct StoredReviewComment {
     pub(super) range: Range<Anchor>,
     /// Whether this comment is currently being edited inline.
     pub(super) is_editing: bool,
+    /// The comment in the project's review comment store, for comments on
+    /// files of a git repository.
+    pub(super) store_id: Option<ReviewCommentId>,
 }
 
 /// Represents an active diff review overlay that appears when clicking the "Add Review" button.
@@ -367,6 +370,7 @@ impl StoredReviewComment {
             comment,
             range: anchor_range,
             is_editing: false,
+            store_id: None,
         }
     }
 }
@@ -907,27 +911,134 @@ impl Editor {
         let id = self.next_review_comment_id;
         self.next_review_comment_id += 1;
 
-        let stored_comment = StoredReviewComment::new(id, comment, anchor_range);
+        let store_id = self.add_comment_to_store(&anchor_range, &comment, cx);
+        let mut stored_comment = StoredReviewComment::new(id, comment, anchor_range);
+        stored_comment.store_id = store_id;
 
         let snapshot = self.buffer.read(cx).snapshot(cx);
-        let key_point = hunk_key.hunk_start_anchor.to_point(&snapshot);
+        self.insert_review_comment(hunk_key, stored_comment, &snapshot);
+
+        cx.emit(EditorEvent::ReviewCommentsChanged {
+            total_count: self.total_review_comment_count(),
+        });
+        cx.notify();
+        id
+    }
+
+    fn insert_review_comment(
+        &mut self,
+        hunk_key: DiffHunkKey,
+        comment: StoredReviewComment,
+        snapshot: &MultiBufferSnapshot,
+    ) {
+        let key_point = hunk_key.hunk_start_anchor.to_point(snapshot);
 
         // Find existing entry for this hunk or add a new one
         if let Some((_, comments)) = self.stored_review_comments.iter_mut().find(|(k, _)| {
-            k.file_path == hunk_key.file_path
-                && k.hunk_start_anchor.to_point(&snapshot) == key_point
+            k.file_path == hunk_key.file_path && k.hunk_start_anchor.to_point(snapshot) == key_point
         }) {
-            comments.push(stored_comment);
+            comments.push(comment);
         } else {
-            self.stored_review_comments
-                .push((hunk_key, vec![stored_comment]));
+            self.stored_review_comments.push((hunk_key, vec![comment]));
         }
+    }
 
-        cx.emit(EditorEvent::ReviewCommentsChanged {
-            total_count: self.total_review_comment_count(),
+    /// Saves a new comment in the project's review comment store, so that it
+    /// is kept for its branch and shared with collaborators.
+    fn add_comment_to_store(
+        &self,
+        range: &Range<Anchor>,
+        body: &str,
+        cx: &mut Context<Self>,
+    ) -> Option<ReviewCommentId> {
+        let store = self.review_comment_store.clone()?;
+        let (buffer, buffer_range) = {
+            let multi_buffer = self.buffer.read(cx);
+            let snapshot = multi_buffer.snapshot(cx);
+            let (start, buffer_snapshot) = snapshot.anchor_to_buffer_anchor(range.start)?;
+            let (end, _) = snapshot.anchor_to_buffer_anchor(range.end)?;
+            (
+                multi_buffer.buffer(buffer_snapshot.remote_id())?,
+                start..end,
+            )
+        };
+        store.update(cx, |store, cx| {
+            store.add_comment(&buffer, buffer_range, body.to_string(), cx)
+        })
+    }
+
+    /// Shows the comments of the project's review comment store on the
+    /// buffers of this editor, keeping the comments that only live here.
+    pub(super) fn sync_review_comments_from_store(&mut self, cx: &mut Context<Self>) {
+        let Some(store) = self.review_comment_store.clone() else {
+            return;
+        };
+        let buffers = self.buffer.read(cx).all_buffers();
+        let store_comments = store.update(cx, |store, cx| {
+            buffers
+                .iter()
+                .flat_map(|buffer| store.comments_for_buffer(buffer, cx))
+                .collect::<Vec<_>>()
         });
-        cx.notify();
-        id
+
+        let mut changed = false;
+        for (_, comments) in &mut self.stored_review_comments {
+            comments.retain_mut(|comment| {
+                let Some(store_id) = comment.store_id else {
+                    return true;
+                };
+                let Some(store_comment) = store_comments.iter().find(|c| c.id == store_id) else {
+                    changed = true;
+                    return false;
+                };
+                if !comment.is_editing && comment.comment != store_comment.body {
+                    comment.comment = store_comment.body.clone();
+                    changed = true;
+                }
+                true
+            });
+        }
+        self.stored_review_comments
+            .retain(|(_, comments)| !comments.is_empty());
+
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        for store_comment in store_comments {
+            let is_shown = self.stored_review_comments.iter().any(|(_, comments)| {
+                comments
+                    .iter()
+                    .any(|comment| comment.store_id == Some(store_comment.id))
+            });
+            if is_shown {
+                continue;
+            }
+            let Some(range) = snapshot.buffer_anchor_range_to_anchor_range(store_comment.range)
+            else {
+                continue;
+            };
+            let start_point = range.start.to_point(&snapshot);
+            let file_path = snapshot
+                .file_at(start_point)
+                .map(|file: &Arc<dyn language::File>| file.path().clone())
+                .unwrap_or_else(|| Arc::from(util::rel_path::RelPath::empty()));
+            let hunk_key = DiffHunkKey {
+                file_path,
+                hunk_start_anchor: snapshot.anchor_before(start_point),
+            };
+
+            let id = self.next_review_comment_id;
+            self.next_review_comment_id += 1;
+            let mut comment = StoredReviewComment::new(id, store_comment.body, range);
+            comment.store_id = Some(store_comment.id);
+            self.insert_review_comment(hunk_key, comment, &snapshot);
+            changed = true;
+        }
+
+        if changed {
+            cx.emit(EditorEvent::ReviewCommentsChanged {
+                total_count: self.total_review_comment_count(),
+            });
+            cx.notify();
+        }
     }
 
     pub(super) fn blame_hover(
@@ -1310,7 +1421,12 @@ impl Editor {
     pub(super) fn remove_review_comment(&mut self, id: usize, cx: &mut Context<Self>) -> bool {
         for (_, comments) in self.stored_review_comments.iter_mut() {
             if let Some(index) = comments.iter().position(|c| c.id == id) {
-                comments.remove(index);
+                let comment = comments.remove(index);
+                if let Some((store, store_id)) =
+                    self.review_comment_store.clone().zip(comment.store_id)
+                {
+                    store.update(cx, |store, cx| store.remove_comment(store_id, cx));
+                }
                 cx.emit(EditorEvent::ReviewCommentsChanged {
                     total_count: self.total_review_comment_count(),
                 });
@@ -1330,6 +1446,13 @@ impl Editor {
     ) -> bool {
         for (_, comments) in self.stored_review_comments.iter_mut() {
             if let Some(comment) = comments.iter_mut().find(|c| c.id == id) {
+                if let Some((store, store_id)) =
+                    self.review_comment_store.clone().zip(comment.store_id)
+                {
+                    store.update(cx, |store, cx| {
+                        store.update_comment(store_id, new_comment.clone(), cx)
+                    });
+                }
                 comment.comment = new_comment;
                 comment.is_editing = false;
                 cx.emit(EditorEvent::ReviewCommentsChanged {
@@ -2961,6 +3084,15 @@ impl Editor {
         // Dismiss all overlays when taking comments (e.g., when sending to agent)
         self.dismiss_all_diff_review_overlays(cx);
         let comments = std::mem::take(&mut self.stored_review_comments);
+        if let Some(store) = self.review_comment_store.clone() {
+            store.update(cx, |store, cx| {
+                for comment in comments.iter().flat_map(|(_, comments)| comments) {
+                    if let Some(store_id) = comment.store_id {
+                        store.remove_comment(store_id, cx);
+                    }
+                }
+            });
+        }
         // Reset the ID counter since all comments have been taken
         self.next_review_comment_id = 0;
         cx.emit(EditorEvent::ReviewCommentsChanged { total_count: 0 });
@@ -1152,6 +1152,15 @@ impl Item for Editor {
                 self.load_folds_from_db(workspace_id, file_path, window, cx);
             }
         }
+
+        // Restore the cursor and scroll position the file had when it was last open.
+        if let Some(workspace_id) = workspace.database_id()
+            && let Some(file_path) = self.buffer().read(cx).as_singleton().and_then(|buffer| {
+                project::File::from_dyn(buffer.read(cx).file()).map(|file| file.abs_path(cx))
+            })
+        {
+            self.restore_file_view_state(workspace_id, file_path, window, cx);
+        }
     }
 
     fn pane_changed(&mut self, new_pane_id: EntityId, cx: &mut Context<Self>) {
@@ -1695,6 +1704,21 @@ impl SearchHitContext {
     }
 }
 
+/// Computes the replacement for a structural search hit, which depends on the
+/// syntax tree of the buffer containing it.
+fn structural_replacement(
+    snapshot: &MultiBufferSnapshot,
+    query: &SearchQuery,
+    hit: &Range<Anchor>,
+) -> Option<Arc<str>> {
+    let (buffer, range) =
+        snapshot.range_to_buffer_range(hit.start.to_point(snapshot)..hit.end.to_point(snapshot))?;
+    let range = buffer.point_to_offset(range.start)..buffer.point_to_offset(range.end);
+    query
+        .structural_replacement_for(buffer, range)
+        .map(Arc::from)
+}
+
 impl SearchableItem for Editor {
     type Match = Range<Anchor>;
 
@@ -1786,6 +1810,7 @@ impl SearchableItem for Editor {
                 replacement: false,
                 selection: false,
                 select_all: true,
+                syntax_scope: true,
                 find_in_results: true,
             }
         } else {
@@ -1796,6 +1821,7 @@ impl SearchableItem for Editor {
                 replacement: true,
                 selection: true,
                 select_all: true,
+                syntax_scope: true,
                 find_in_results: false,
             }
         }
@@ -1880,7 +1906,10 @@ impl SearchableItem for Editor {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
-        let replacement = if query.replacement_requires_context() {
+        let replacement = if query.is_structural() {
+            let snapshot = self.buffer.read(cx).snapshot(cx);
+            structural_replacement(&snapshot, query, identifier)
+        } else if query.replacement_requires_context() {
             let snapshot = self.buffer.read(cx).snapshot(cx);
             let mut context = SearchHitContext::default();
             let (line, hit) = context.for_hit(&snapshot, identifier);
@@ -1910,7 +1939,14 @@ impl SearchableItem for Editor {
 
         // A regex might have replacement variables so we cannot apply
         // the same replacement to all matches
-        if query.replacement_requires_context() {
+        if query.is_structural() {
+            edits = matches
+                .filter_map(|m| {
+                    structural_replacement(&snapshot, query, m)
+                        .map(|replacement| (m.clone(), replacement))
+                })
+                .collect();
+        } else if query.replacement_requires_context() {
             let mut context = SearchHitContext::default();
             edits = matches
                 .filter_map(|m| {
@@ -1,7 +1,7 @@
 use crate::{
     Copy, CopyAndTrim, CopyPermalinkToLine, Cut, DisplayPoint, DisplaySnapshot, Editor,
     EvaluateSelectedText, FindAllReferences, GoToDeclaration, GoToDefinition, GoToImplementation,
-    GoToTypeDefinition, Paste, Rename, RevealInFileManager, RunToCursor, SelectMode,
+    GoToTypeDefinition, JumpToCursor, Paste, Rename, RevealInFileManager, RunToCursor, SelectMode,
     SelectionEffects, SelectionExt, ToDisplayPoint, ToggleCodeActions,
     actions::{Format, FormatSelections},
     selections_collection::SelectionsCollection,
@@ -219,6 +219,7 @@ pub fn deploy_context_menu(
 
         let evaluate_selection = window.is_action_available(&EvaluateSelectedText, cx);
         let run_to_cursor = window.is_action_available(&RunToCursor, cx);
+        let jump_to_cursor = window.is_action_available(&JumpToCursor, cx);
         let format_selections = window.is_action_available(&FormatSelections, cx);
         let disable_ai = DisableAiSettings::is_ai_disabled_for_buffer(
             editor.buffer.read(cx).as_singleton().as_ref(),
@@ -249,11 +250,14 @@ pub fn deploy_context_menu(
                 .when(run_to_cursor, |builder| {
                     builder.action("Run to Cursor", Box::new(RunToCursor))
                 })
+                .when(jump_to_cursor, |builder| {
+                    builder.action("Jump to Cursor", Box::new(JumpToCursor))
+                })
                 .when(evaluate_selection && has_selections, |builder| {
                     builder.action("Evaluate Selection", Box::new(EvaluateSelectedText))
                 })
                 .when(
-                    run_to_cursor || (evaluate_selection && has_selections),
+                    run_to_cursor || jump_to_cursor || (evaluate_selection && has_selections),
                     |builder| builder.separator(),
                 )
                 .action("Go to Definition", Box::new(GoToDefinition::default()))
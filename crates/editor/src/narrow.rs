@@ -0,0 +1,189 @@
+use super::*;
+
+/// The region of a buffer that a narrowed editor is restricted to.
+///
+/// A narrowed editor shows a single excerpt of its buffer, so edits, search,
+/// select-all and go-to-line stay within that excerpt, while the changes are
+/// made to the same underlying buffer as every other editor for it.
+pub(crate) struct NarrowedRegion {
+    /// The editor that was narrowed, which is revealed again when widening.
+    source: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+}
+
+impl Editor {
+    pub fn is_narrowed(&self) -> bool {
+        self.narrowed_region.is_some()
+    }
+
+    pub fn narrow_to_selection(
+        &mut self,
+        _: &NarrowToSelection,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let selection = self.selections.newest::<Point>(&self.display_snapshot(cx));
+        if selection.is_empty() {
+            return;
+        }
+        self.narrow_to_range(selection.range(), window, cx);
+    }
+
+    pub fn narrow_to_enclosing_symbol(
+        &mut self,
+        _: &NarrowToEnclosingSymbol,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let cursor = self.selections.newest_anchor().head();
+        self.select_enclosing_symbol(&SelectEnclosingSymbol, window, cx);
+        let selection = self.selections.newest::<Point>(&self.display_snapshot(cx));
+        if selection.is_empty() {
+            return;
+        }
+        let range = selection.range();
+        // Leave the cursor where it was in this editor, the symbol is only
+        // selected to find its range.
+        self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+            s.select_anchor_ranges([cursor..cursor])
+        });
+        self.narrow_to_range(range, window, cx);
+    }
+
+    /// Opens the lines touched by `range` in a new editor, next to this one.
+    fn narrow_to_range(
+        &mut self,
+        range: Range<Point>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let multi_buffer = self.buffer.read(cx);
+        let snapshot = multi_buffer.snapshot(cx);
+        let Some((buffer_snapshot, buffer_range)) = snapshot.range_to_buffer_range(range) else {
+            return;
+        };
+        let Some(buffer) = multi_buffer.buffer(buffer_snapshot.remote_id()) else {
+            return;
+        };
+        let capability = multi_buffer.capability();
+        let file_name = buffer_snapshot
+            .file()
+            .map(|file| file.file_name(cx).to_string())
+            .unwrap_or_else(|| multi_buffer.title(cx).to_string());
+        let title = format!(
+            "{file_name} (lines {}–{})",
+            buffer_range.start.row + 1,
+            buffer_range.end.row + 1
+        );
+        let source = cx.weak_entity();
+        let project = self.project.clone();
+
+        let narrowed_buffer = cx.new(|cx| {
+            let mut multi_buffer = MultiBuffer::new(capability);
+            multi_buffer.set_excerpts_for_path(
+                PathKey::for_buffer(&buffer, cx),
+                buffer.clone(),
+                [buffer_range.clone()],
+                0,
+                cx,
+            );
+            multi_buffer.with_title(title)
+        });
+
+        let narrowed_editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(narrowed_buffer, project, window, cx);
+            editor.narrowed_region = Some(NarrowedRegion {
+                source,
+                buffer: buffer.clone(),
+            });
+            // Expanding the excerpt would widen the region.
+            editor.disable_expand_excerpt_buttons(cx);
+            editor
+        });
+        narrowed_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer.read(cx).snapshot(cx);
+            if let Some(start) = snapshot
+                .anchor_in_buffer(buffer.read(cx).snapshot().anchor_before(buffer_range.start))
+            {
+                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.select_anchor_ranges([start..start])
+                });
+            }
+        });
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item_to_active_pane(Box::new(narrowed_editor), None, true, window, cx);
+        });
+    }
+
+    /// Closes a narrowed editor and reveals the editor it was narrowed from,
+    /// keeping the selections.
+    pub fn widen(&mut self, _: &Widen, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(narrowed_region) = self.narrowed_region.as_ref() else {
+            cx.propagate();
+            return;
+        };
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let source = narrowed_region.source.upgrade();
+        let buffer = narrowed_region.buffer.clone();
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let selections = self
+            .selections
+            .disjoint_anchors_arc()
+            .iter()
+            .map(|selection| {
+                selection.start.text_anchor_in(&buffer_snapshot)
+                    ..selection.end.text_anchor_in(&buffer_snapshot)
+            })
+            .collect::<Vec<_>>();
+        let narrowed_item_id = cx.entity_id();
+
+        cx.spawn_in(window, async move |_, cx| {
+            workspace.update_in(cx, |workspace, window, cx| {
+                let pane = workspace
+                    .pane_for_item_id(narrowed_item_id)
+                    .unwrap_or_else(|| workspace.active_pane().clone());
+                let source = match source {
+                    Some(source) if workspace.activate_item(&source, true, true, window, cx) => {
+                        source
+                    }
+                    _ => workspace.open_project_item::<Editor>(
+                        pane.clone(),
+                        buffer,
+                        true,
+                        true,
+                        false,
+                        false,
+                        window,
+                        cx,
+                    ),
+                };
+                source.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer.read(cx).snapshot(cx);
+                    let selections = selections
+                        .into_iter()
+                        .filter_map(|range| snapshot.anchor_range_in_buffer(range))
+                        .collect::<Vec<_>>();
+                    if !selections.is_empty() {
+                        editor.change_selections(
+                            SelectionEffects::scroll(Autoscroll::center()),
+                            window,
+                            cx,
+                            |s| s.select_anchor_ranges(selections),
+                        );
+                    }
+                });
+                pane.update(cx, |pane, cx| {
+                    pane.close_item_by_id(narrowed_item_id, workspace::SaveIntent::Skip, window, cx)
+                })
+                .detach_and_log_err(cx);
+            })
+        })
+        .detach();
+    }
+}
@@ -25,6 +25,32 @@ pub(crate) struct SerializedEditor {
     pub(crate) mtime: Option<MTime>,
 }
 
+/// The cursor and scroll state of a file, stored by path so that it is
+/// restored when the file is opened again after its tab was closed.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub(crate) struct SerializedFileViewState {
+    /// A hash of the file's contents when the state was saved, used to detect
+    /// whether the stored rows still point at the same text.
+    pub(crate) content_fingerprint: String,
+    pub(crate) scroll_top_row: u32,
+    pub(crate) scroll_top_line: String,
+    pub(crate) scroll_horizontal_offset: f64,
+    pub(crate) scroll_vertical_offset: f64,
+    pub(crate) selections: Vec<SerializedFileSelection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub(crate) struct SerializedFileSelection {
+    pub(crate) start_row: u32,
+    pub(crate) start_column: u32,
+    pub(crate) end_row: u32,
+    pub(crate) end_column: u32,
+    /// The text of the row containing the start of the selection.
+    pub(crate) start_line: String,
+    /// The text of the row containing the end of the selection.
+    pub(crate) end_line: String,
+}
+
 impl StaticColumnCount for SerializedEditor {
     fn column_count() -> usize {
         6
@@ -126,6 +152,27 @@ impl Domain for EditorDb {
     //   start_fingerprint: Option<String>,
     //   end_fingerprint: Option<String>,
     // )
+    //
+    // file_view_states(
+    //   workspace_id: usize,
+    //   path: String,
+    //   content_fingerprint: String,
+    //   scroll_top_row: u32,
+    //   scroll_top_line: String,
+    //   scroll_horizontal_offset: f64,
+    //   scroll_vertical_offset: f64,
+    // )
+    //
+    // file_selections(
+    //   workspace_id: usize,
+    //   path: String,
+    //   start_row: u32,
+    //   start_column: u32,
+    //   end_row: u32,
+    //   end_column: u32,
+    //   start_line: String,
+    //   end_line: String,
+    // )
 
     const MIGRATIONS: &[&str] = &[
         sql! (
@@ -223,6 +270,38 @@ impl Domain for EditorDb {
                 PRIMARY KEY(workspace_id, path, start)
             );
         ),
+        // File-level cursor and scroll persistence, stored by path like file_folds.
+        // The text of each stored row is kept so that positions can be re-anchored
+        // when the file changed on disk since it was last open.
+        sql! (
+            CREATE TABLE file_view_states (
+                workspace_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                content_fingerprint TEXT NOT NULL,
+                scroll_top_row INTEGER NOT NULL,
+                scroll_top_line TEXT NOT NULL,
+                scroll_horizontal_offset REAL NOT NULL,
+                scroll_vertical_offset REAL NOT NULL,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                PRIMARY KEY(workspace_id, path)
+            );
+
+            CREATE TABLE file_selections (
+                workspace_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                start_row INTEGER NOT NULL,
+                start_column INTEGER NOT NULL,
+                end_row INTEGER NOT NULL,
+                end_column INTEGER NOT NULL,
+                start_line TEXT NOT NULL,
+                end_line TEXT NOT NULL,
+                FOREIGN KEY(workspace_id, path) REFERENCES file_view_states(workspace_id, path)
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE
+            );
+        ),
     ];
 }
 
@@ -319,6 +398,29 @@ impl EditorDb {
         }
     }
 
+    query! {
+        pub fn get_file_view_state(
+            workspace_id: WorkspaceId,
+            path: &Path
+        ) -> Result<Option<(String, u32, String, f64, f64)>> {
+            SELECT content_fingerprint, scroll_top_row, scroll_top_line, scroll_horizontal_offset, scroll_vertical_offset
+            FROM file_view_states
+            WHERE workspace_id = ?1 AND path = ?2
+        }
+    }
+
+    query! {
+        pub fn get_file_selections(
+            workspace_id: WorkspaceId,
+            path: &Path
+        ) -> Result<Vec<(u32, u32, u32, u32, String, String)>> {
+            SELECT start_row, start_column, end_row, end_column, start_line, end_line
+            FROM file_selections
+            WHERE workspace_id = ?1 AND path = ?2
+            ORDER BY start_row, start_column
+        }
+    }
+
     pub async fn save_editor_selections(
         &self,
         editor_id: ItemId,
@@ -396,6 +498,54 @@ VALUES {placeholders};
         .await
     }
 
+    pub async fn save_file_view_state(
+        &self,
+        workspace_id: WorkspaceId,
+        path: Arc<Path>,
+        state: SerializedFileViewState,
+    ) -> Result<()> {
+        log::debug!("Saving view state for file {path:?} in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            // Deleting the view state also deletes its selections
+            conn.exec_bound(sql!(
+                DELETE FROM file_view_states WHERE workspace_id = ?1 AND path = ?2;
+            ))?((workspace_id, path.as_ref()))?;
+
+            conn.exec_bound(sql!(
+                INSERT INTO file_view_states
+                    (workspace_id, path, content_fingerprint, scroll_top_row, scroll_top_line, scroll_horizontal_offset, scroll_vertical_offset)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7);
+            ))?((
+                workspace_id,
+                path.as_ref(),
+                state.content_fingerprint,
+                state.scroll_top_row,
+                state.scroll_top_line,
+                state.scroll_horizontal_offset,
+                state.scroll_vertical_offset,
+            ))?;
+
+            for selection in state.selections {
+                conn.exec_bound(sql!(
+                    INSERT INTO file_selections
+                        (workspace_id, path, start_row, start_column, end_row, end_column, start_line, end_line)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);
+                ))?((
+                    workspace_id,
+                    path.as_ref(),
+                    selection.start_row,
+                    selection.start_column,
+                    selection.end_row,
+                    selection.end_column,
+                    selection.start_line,
+                    selection.end_line,
+                ))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
     pub async fn delete_file_folds(
         &self,
         workspace_id: WorkspaceId,
@@ -614,4 +764,102 @@ mod tests {
         assert_eq!(retrieved_a[0].0, 10); // file_a's fold
         assert_eq!(retrieved_b[0].0, 30); // file_b's fold
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_file_view_state(cx: &mut gpui::TestAppContext) {
+        let db = cx.update(|cx| workspace::WorkspaceDb::global(cx));
+        let workspace_id = db.next_id().await.unwrap();
+        let editor_db = cx.update(|cx| EditorDb::global(cx));
+        let file_path: Arc<Path> = Arc::from(Path::new("/tmp/test_file_view_state.rs"));
+
+        assert_eq!(
+            editor_db
+                .get_file_view_state(workspace_id, &file_path)
+                .unwrap(),
+            None
+        );
+
+        let state = SerializedFileViewState {
+            content_fingerprint: "0123456789abcdef".to_string(),
+            scroll_top_row: 40,
+            scroll_top_line: "fn main() {".to_string(),
+            scroll_horizontal_offset: 2.0,
+            scroll_vertical_offset: 0.5,
+            selections: vec![
+                SerializedFileSelection {
+                    start_row: 42,
+                    start_column: 4,
+                    end_row: 42,
+                    end_column: 8,
+                    start_line: "    let x = 1;".to_string(),
+                    end_line: "    let x = 1;".to_string(),
+                },
+                SerializedFileSelection {
+                    start_row: 50,
+                    start_column: 0,
+                    end_row: 51,
+                    end_column: 1,
+                    start_line: "impl Foo {".to_string(),
+                    end_line: "}".to_string(),
+                },
+            ],
+        };
+        editor_db
+            .save_file_view_state(workspace_id, file_path.clone(), state)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            editor_db
+                .get_file_view_state(workspace_id, &file_path)
+                .unwrap(),
+            Some((
+                "0123456789abcdef".to_string(),
+                40,
+                "fn main() {".to_string(),
+                2.0,
+                0.5
+            ))
+        );
+        assert_eq!(
+            editor_db
+                .get_file_selections(workspace_id, &file_path)
+                .unwrap(),
+            vec![
+                (
+                    42,
+                    4,
+                    42,
+                    8,
+                    "    let x = 1;".to_string(),
+                    "    let x = 1;".to_string()
+                ),
+                (50, 0, 51, 1, "impl Foo {".to_string(), "}".to_string()),
+            ]
+        );
+
+        // Saving again replaces the previous state and all of its selections
+        let state = SerializedFileViewState {
+            content_fingerprint: "fedcba9876543210".to_string(),
+            selections: vec![SerializedFileSelection::default()],
+            ..SerializedFileViewState::default()
+        };
+        editor_db
+            .save_file_view_state(workspace_id, file_path.clone(), state)
+            .await
+            .unwrap();
+
+        let retrieved = editor_db
+            .get_file_view_state(workspace_id, &file_path)
+            .unwrap()
+            .unwrap();
+        assert_eq!(retrieved.0, "fedcba9876543210");
+        assert_eq!(retrieved.1, 0);
+        assert_eq!(
+            editor_db
+                .get_file_selections(workspace_id, &file_path)
+                .unwrap(),
+            vec![(0, 0, 0, 0, String::new(), String::new())]
+        );
+    }
 }
@@ -736,7 +736,7 @@ impl Editor {
             scroll_position
         };
         let scroll_beyond_last_line = self.scroll_beyond_last_line(cx);
-        self.scroll_manager.set_scroll_position(
+        let was_scrolled = self.scroll_manager.set_scroll_position(
             adjusted_position,
             &display_map,
             scroll_beyond_last_line,
@@ -745,7 +745,11 @@ impl Editor {
             workspace_id,
             window,
             cx,
-        )
+        );
+        if local && was_scrolled.0 {
+            self.serialize_file_view_state(cx);
+        }
+        was_scrolled
     }
 
     pub fn scroll_position(&self, cx: &mut Context<Self>) -> gpui::Point<ScrollOffset> {
@@ -766,7 +770,7 @@ impl Editor {
             .anchor
             .to_point(&self.buffer().read(cx).snapshot(cx))
             .row;
-        self.scroll_manager.set_anchor(
+        let was_scrolled = self.scroll_manager.set_anchor(
             scroll_anchor,
             &display_map,
             top_row,
@@ -776,6 +780,9 @@ impl Editor {
             window,
             cx,
         );
+        if was_scrolled.0 {
+            self.serialize_file_view_state(cx);
+        }
     }
 
     pub(crate) fn set_scroll_anchor_remote(
@@ -1736,6 +1736,7 @@ impl Editor {
                         .log_err();
                 });
             }
+            self.serialize_file_view_state(cx);
         }
 
         cx.notify();
@@ -12,6 +12,12 @@
 //!          --instruction "Fix the bug described in..." --timeout 600
 //! ```
 //!
+//! ## Offline runs
+//!
+//! With `--cassette-dir`, model completions are recorded to that directory
+//! (`--cassette-mode record`) and can later be replayed from it
+//! (`--cassette-mode replay`) without network access or an API key.
+//!
 //! ## Output
 //!
 //! Writes to `--output-dir` (default `/logs/agent/`):
@@ -48,6 +54,7 @@ use feature_flags::FeatureFlagAppExt as _;
 
 use futures::{FutureExt, select_biased};
 use gpui::{AppContext as _, AsyncApp, Entity, UpdateGlobal};
+use language_model::cassette::{Cassette, CassetteLanguageModelProvider, CassetteMode};
 use language_model::{
     ANTHROPIC_PROVIDER_ID, LanguageModel, LanguageModelId, LanguageModelProviderId,
     LanguageModelRegistry, SelectedModel,
@@ -105,6 +112,16 @@ struct Args {
     /// Enable or disable extended thinking. Defaults to model auto-detection if omitted.
     #[arg(long)]
     thinking: Option<bool>,
+
+    /// Directory of recorded model completions. When set, completions are
+    /// recorded to or replayed from it according to `--cassette-mode`.
+    #[arg(long)]
+    cassette_dir: Option<PathBuf>,
+
+    /// How to use `--cassette-dir`: `record`, `replay` or `replay-or-record`.
+    /// Replaying needs no network access or API key.
+    #[arg(long, default_value = "replay")]
+    cassette_mode: CassetteMode,
 }
 
 enum AgentOutcome {
@@ -225,6 +242,14 @@ fn main() {
         let timeout = args.timeout;
         let thinking_override = args.thinking;
         let reasoning_effort = args.reasoning_effort.clone();
+        // The workdir differs between runs (e.g. a fresh checkout in a temporary
+        // directory), so it's masked before requests are matched to recordings.
+        let cassette = args.cassette_dir.clone().map(|dir| {
+            Arc::new(
+                Cassette::new(dir, args.cassette_mode)
+                    .with_substitution(workdir.to_string_lossy(), "$WORKDIR"),
+            )
+        });
 
         cx.spawn(async move |cx| {
             // Each settings change below is applied in its own `cx.update` call (rather than
@@ -251,6 +276,22 @@ fn main() {
                 }
             }
 
+            if let Some(cassette) = &cassette {
+                cx.update(|cx| {
+                    LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
+                        for provider in registry.providers() {
+                            registry.register_provider(
+                                Arc::new(CassetteLanguageModelProvider::new(
+                                    provider,
+                                    cassette.clone(),
+                                )),
+                                cx,
+                            );
+                        }
+                    })
+                });
+            }
+
             let auth_tasks = cx.update(|cx| {
                 LanguageModelRegistry::global(cx).update(cx, |registry, cx| {
                     registry
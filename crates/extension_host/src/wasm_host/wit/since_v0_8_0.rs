@@ -313,6 +313,8 @@ impl TryFrom<DebugScenario> for extension::DebugScenario {
             adapter: value.adapter.into(),
             label: value.label.into(),
             build: value.build.map(Into::into),
+            pre_launch_task: None,
+            post_debug_task: None,
             config: serde_json::Value::from_str(&value.config)?,
             tcp_connection: value.tcp_connection.map(Into::into),
         })
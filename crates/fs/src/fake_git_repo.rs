@@ -14,8 +14,8 @@ use git::{
         AskPassDelegate, Branch, CommitData, CommitDataReader, CommitDetails, CommitOptions,
         CreateWorktreeTarget, FetchOptions, FileHistoryChangedFileSets, GRAPH_CHUNK_SIZE,
         GitRepository, GitRepositoryCheckpoint, InitialGraphCommitData, LogOrder, LogSource,
-        PushOptions, RefEdit, Remote, RepoPath, ResetMode, SearchCommitArgs, Worktree,
-        commit_hash_search_query,
+        PushOptions, RefEdit, Remote, RepoPath, ResetMode, SearchCommitArgs, SearchCommitTarget,
+        Worktree, commit_hash_search_query,
     },
     stash::GitStash,
     status::{
@@ -62,6 +62,9 @@ pub struct FakeGitRepositoryState {
     pub unmerged_paths: HashMap<RepoPath, UnmergedStatus>,
     pub head_contents: HashMap<RepoPath, Vec<u8>>,
     pub index_contents: HashMap<RepoPath, Vec<u8>>,
+    /// The base, ours and theirs versions (index stages 1 to 3) of
+    /// conflicted paths.
+    pub merge_stage_contents: HashMap<RepoPath, [Option<Vec<u8>>; 3]>,
     // everything in commit contents is in oids
     pub merge_base_contents: HashMap<RepoPath, Oid>,
     pub oids: HashMap<Oid, Vec<u8>>,
@@ -89,6 +92,7 @@ impl FakeGitRepositoryState {
             event_emitter,
             head_contents: Default::default(),
             index_contents: Default::default(),
+            merge_stage_contents: Default::default(),
             unmerged_paths: Default::default(),
             blames: Default::default(),
             blames_at_revision: Default::default(),
@@ -269,6 +273,13 @@ impl GitRepository for FakeGitRepository {
             Ok(revisions
                 .into_iter()
                 .map(|rev| {
+                    if let Some((stage, path)) =
+                        rev.strip_prefix(':').and_then(|rev| rev.split_once(':'))
+                        && let Ok(stage @ 1..=3) = stage.parse::<usize>()
+                    {
+                        let repo_path = RepoPath::new(path).ok()?;
+                        return state.merge_stage_contents.get(&repo_path)?[stage - 1].clone();
+                    }
                     let (prefix, path) = rev.split_once(':')?;
                     let repo_path = RepoPath::new(path).ok()?;
                     match prefix {
@@ -1033,6 +1044,9 @@ impl GitRepository for FakeGitRepository {
             let contents = join_all(contents).await;
             self.with_state_async(true, move |state| {
                 for (path, content) in contents {
+                    // Staging a conflicted path resolves its conflict.
+                    state.unmerged_paths.remove(&path);
+                    state.merge_stage_contents.remove(&path);
                     if let Some(content) = content {
                         state.index_contents.insert(path, content);
                     } else {
@@ -1567,6 +1581,11 @@ impl GitRepository for FakeGitRepository {
         request_tx: Sender<Oid>,
     ) -> BoxFuture<'_, Result<()>> {
         async move {
+            // Fake commits don't carry any file contents to search through.
+            if search_args.target != SearchCommitTarget::Message {
+                return Ok(());
+            }
+
             let hash_query = commit_hash_search_query(search_args.query.as_str())
                 .map(|query| query.to_ascii_lowercase());
             let message_query = if search_args.case_sensitive {
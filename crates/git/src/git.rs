@@ -130,6 +130,20 @@ actions!(
         OpenModifiedFiles,
         /// Opens the current file in a solo diff view.
         OpenFileDiff,
+        /// Opens the current conflicted file in the three-way merge editor.
+        OpenMergeEditor,
+        /// Moves to the next unresolved conflict in the merge editor.
+        NextConflict,
+        /// Moves to the previous unresolved conflict in the merge editor.
+        PreviousConflict,
+        /// Accepts the current branch's side of the conflict at the cursor.
+        AcceptOurs,
+        /// Accepts the incoming branch's side of the conflict at the cursor.
+        AcceptTheirs,
+        /// Accepts both sides of the conflict at the cursor.
+        AcceptBoth,
+        /// Saves the merge result and stages the file to mark its conflicts as resolved.
+        MarkResolved,
         /// Clones a repository.
         Clone,
         ViewCommit,
@@ -139,6 +153,14 @@ actions!(
         AddToGitInfoExclude,
         /// Copies the current branch name to the clipboard.
         CopyBranchName,
+        /// Exports the review comments on the current branch as Markdown.
+        ExportReviewCommentsAsMarkdown,
+        /// Exports the review comments on the current branch as the payload of
+        /// a GitHub pull request review.
+        ExportReviewCommentsForGitHub,
+        /// Imports review comments on the current branch from a Markdown or
+        /// GitHub review file.
+        ImportReviewComments,
     ]
 );
 
@@ -727,6 +727,21 @@ impl LogSource {
 pub struct SearchCommitArgs {
     pub query: SharedString,
     pub case_sensitive: bool,
+    pub target: SearchCommitTarget,
+    /// Restricts the search to commits touching paths matching these pathspecs.
+    pub pathspecs: Vec<SharedString>,
+}
+
+/// What a commit search query is matched against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchCommitTarget {
+    /// The commit message, or a prefix of the commit hash.
+    #[default]
+    Message,
+    /// Changes in the number of occurrences of the query, like `git log -S`.
+    Pickaxe,
+    /// Added or removed lines matching the query as a regex, like `git log -G`.
+    PickaxeRegex,
 }
 
 pub fn commit_hash_search_query(query: &str) -> Option<&str> {
@@ -3378,21 +3393,40 @@ impl GitRepository for RealGitRepository {
         async move {
             let log_source_args = log_source.get_args();
             let mut args = vec!["log", SEARCH_COMMIT_FORMAT];
-            let hash_query = commit_hash_search_query(search_args.query.as_str())
+            let hash_query = (search_args.target == SearchCommitTarget::Message)
+                .then(|| commit_hash_search_query(search_args.query.as_str()))
+                .flatten()
                 .map(|query| query.to_ascii_lowercase());
 
             if hash_query.is_none() {
-                args.push("--fixed-strings");
-
                 if !search_args.case_sensitive {
                     args.push("--regexp-ignore-case");
                 }
 
-                args.push("--grep");
+                match search_args.target {
+                    SearchCommitTarget::Message => {
+                        args.push("--fixed-strings");
+                        args.push("--grep");
+                    }
+                    SearchCommitTarget::Pickaxe => args.push("-S"),
+                    SearchCommitTarget::PickaxeRegex => args.push("-G"),
+                }
                 args.push(search_args.query.as_str());
             }
 
             args.extend(log_source_args.iter().map(|arg| arg.as_ref()));
+            if !search_args.pathspecs.is_empty() {
+                // Path log sources already end with a pathspec separator.
+                if !matches!(log_source, LogSource::Path(_)) {
+                    args.push("--");
+                }
+                args.extend(
+                    search_args
+                        .pathspecs
+                        .iter()
+                        .map(|pathspec| pathspec.as_str()),
+                );
+            }
             let mut command = git.build_command(&args);
             command.stdout(Stdio::piped());
             command.stderr(Stdio::null());
@@ -6533,4 +6567,73 @@ mod tests {
             "/Users/user/My Projects/upstream.git"
         );
     }
+
+    #[gpui::test]
+    async fn test_search_commits_with_pickaxe(cx: &mut TestAppContext) {
+        disable_git_global_config();
+        cx.executor().allow_parking();
+
+        let repo_dir = tempfile::tempdir().expect("failed to create temporary repository");
+        git_init_repo(repo_dir.path());
+        fs::create_dir_all(repo_dir.path().join("src")).unwrap();
+        fs::write(repo_dir.path().join("src/lib.rs"), "fn old_name() {}\n").unwrap();
+        fs::write(repo_dir.path().join("notes.txt"), "old_name\n").unwrap();
+        git_command(repo_dir.path(), ["add", "."]);
+        git_command(repo_dir.path(), ["commit", "-m", "add old_name"]);
+        fs::write(repo_dir.path().join("src/lib.rs"), "fn new_name() {}\n").unwrap();
+        git_command(repo_dir.path(), ["commit", "-am", "rename"]);
+        fs::write(repo_dir.path().join("src/lib.rs"), "fn new_name() { }\n").unwrap();
+        git_command(repo_dir.path(), ["commit", "-am", "reformat"]);
+
+        let repository = RealGitRepository::new(
+            &repo_dir.path().join(".git"),
+            None,
+            Some("git".into()),
+            cx.executor(),
+        )
+        .expect("failed to open repository");
+        let subjects_of_matches = async |target, query: &str, pathspecs: &[&str]| {
+            let (request_tx, request_rx) = async_channel::unbounded();
+            repository
+                .search_commits(
+                    LogSource::Branch("HEAD".into()),
+                    SearchCommitArgs {
+                        query: query.to_string().into(),
+                        case_sensitive: true,
+                        target,
+                        pathspecs: pathspecs
+                            .iter()
+                            .map(|pathspec| pathspec.to_string().into())
+                            .collect(),
+                    },
+                    request_tx,
+                )
+                .await
+                .expect("failed to search commits");
+            let mut subjects = Vec::new();
+            while let Ok(oid) = request_rx.try_recv() {
+                let details = repository
+                    .show(oid.to_string())
+                    .await
+                    .expect("failed to show commit");
+                subjects.push(details.message.trim().to_string());
+            }
+            subjects
+        };
+
+        // `-S` only matches commits changing the number of occurrences.
+        assert_eq!(
+            subjects_of_matches(SearchCommitTarget::Pickaxe, "old_name", &[]).await,
+            ["rename", "add old_name"]
+        );
+        assert_eq!(
+            subjects_of_matches(SearchCommitTarget::Pickaxe, "old_name", &["*.txt"]).await,
+            ["add old_name"]
+        );
+        // `-G` also matches commits that merely touch a matching line.
+        assert_eq!(
+            subjects_of_matches(SearchCommitTarget::PickaxeRegex, "new_[a-z]+", &["src"]).await,
+            ["reformat", "rename"]
+        );
+    }
 }
@@ -393,6 +393,13 @@ fn render_conflict_buttons(
                     }
                 }),
         )
+        .child(
+            Button::new("merge-editor", "Open Merge Editor")
+                .label_size(LabelSize::Small)
+                .on_click(|_, window, cx| {
+                    window.dispatch_action(Box::new(git::OpenMergeEditor), cx);
+                }),
+        )
         .when(is_ai_enabled, |this| {
             this.child(Divider::vertical()).child(
                 Button::new("resolve-with-agent", "Resolve with Agent")
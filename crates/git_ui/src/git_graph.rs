@@ -11,7 +11,9 @@ use file_icons::FileIcons;
 use git::{
     BuildCommitPermalinkParams, GitHostingProviderRegistry, GitRemote, Oid, ParsedGitRemote,
     parse_git_remote_url,
-    repository::{InitialGraphCommitData, LogOrder, LogSource, RepoPath, SearchCommitArgs},
+    repository::{
+        InitialGraphCommitData, LogOrder, LogSource, RepoPath, SearchCommitArgs, SearchCommitTarget,
+    },
     status::{FileStatus, StatusCode, TrackedStatus},
 };
 use gpui::{
@@ -2035,6 +2037,8 @@ impl GitGraph {
                 SearchCommitArgs {
                     query: query.clone(),
                     case_sensitive: self.search_state.case_sensitive,
+                    target: SearchCommitTarget::Message,
+                    pathspecs: Vec::new(),
                 },
                 request_tx,
                 cx,
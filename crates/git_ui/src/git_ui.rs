@@ -46,11 +46,14 @@ pub mod git_panel;
 mod git_panel_settings;
 pub mod git_picker;
 mod git_runtime_diagnostics;
+pub mod history_search;
+pub mod merge_editor;
 pub mod multi_diff_view;
 pub mod picker_prompt;
 pub mod project_diff;
 pub(crate) mod remote_output;
 pub mod repository_selector;
+mod review_comments;
 pub mod solo_diff_view;
 pub mod staged_diff;
 pub mod stash_picker;
@@ -113,6 +116,8 @@ pub fn init(cx: &mut App) {
         git_panel::register(workspace);
         repository_selector::register(workspace);
         git_picker::register(workspace);
+        history_search::register(workspace);
+        review_comments::register(workspace, cx);
 
         workspace.register_action(
             |workspace, action: &zed_actions::CreateWorktree, window, cx| {
@@ -328,6 +333,7 @@ pub fn init(cx: &mut App) {
                 GitCloneModal::show(panel, window, cx)
             });
         });
+        merge_editor::MergeEditor::register(workspace, cx);
         workspace.register_action(|workspace, _: &git::OpenModifiedFiles, window, cx| {
             open_modified_files(workspace, window, cx);
         });
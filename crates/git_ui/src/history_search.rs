@@ -0,0 +1,680 @@
+use std::{
+    any::{Any, TypeId},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use editor::{Editor, EditorEvent, MultiBuffer, multibuffer_context_lines};
+use git::repository::{CommitDetails, LogSource, SearchCommitArgs, SearchCommitTarget};
+use gpui::{
+    AnyElement, App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, Font,
+    ScrollHandle, SharedString, Task, WeakEntity, Window,
+};
+use language::{Capability, HighlightedText, OffsetRangeExt as _};
+use multi_buffer::PathKey;
+use project::{
+    Project, ProjectPath,
+    git_store::{CommitDiff, Repository},
+    search::SearchQuery,
+};
+use time::OffsetDateTime;
+use ui::{Tooltip, prelude::*};
+use util::{ResultExt as _, paths::PathMatcher, paths::PathStyle};
+use workspace::{
+    Item, ItemNavHistory, ToolbarItemLocation, Workspace, item::ItemEvent,
+    searchable::SearchableItemHandle,
+};
+use zed_actions::git::SearchHistory;
+
+use crate::commit_view::{CommitView, GitBlob, build_buffer, build_buffer_diff};
+
+/// The number of matching commits after which a history search stops, as
+/// pickaxe searches over long histories can match a lot of commits.
+const MAX_COMMITS: usize = 100;
+
+pub fn register(workspace: &mut Workspace) {
+    workspace.register_action(|workspace, action: &SearchHistory, window, cx| {
+        HistorySearchView::deploy(workspace, action.query.clone(), window, cx);
+    });
+}
+
+enum SearchStatus {
+    Idle,
+    Searching,
+    Finished { limit_reached: bool },
+    Error(SharedString),
+}
+
+/// Searches the history of the active repository for commits that added or
+/// removed some text, showing the matching diff hunks of each commit.
+pub struct HistorySearchView {
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    repository: Option<Entity<Repository>>,
+    query_editor: Entity<Editor>,
+    pathspecs_editor: Entity<Editor>,
+    target: SearchCommitTarget,
+    case_sensitive: bool,
+    multibuffer: Entity<MultiBuffer>,
+    results_editor: Entity<Editor>,
+    commits: Vec<CommitDetails>,
+    commits_scroll_handle: ScrollHandle,
+    status: SearchStatus,
+    _search_task: Option<Task<()>>,
+}
+
+impl HistorySearchView {
+    fn deploy(
+        workspace: &mut Workspace,
+        query: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<Self>());
+        let view = if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            existing
+        } else {
+            let project = workspace.project().clone();
+            let workspace_handle = cx.weak_entity();
+            let view = cx.new(|cx| Self::new(project, workspace_handle, window, cx));
+            workspace.add_item_to_active_pane(Box::new(view.clone()), None, true, window, cx);
+            view
+        };
+
+        view.update(cx, |view, cx| {
+            if let Some(query) = query.filter(|query| !query.is_empty()) {
+                view.query_editor.update(cx, |editor, cx| {
+                    editor.set_text(query, window, cx);
+                });
+                view.search(window, cx);
+            }
+            window.focus(&view.query_editor.focus_handle(cx), cx);
+        });
+    }
+
+    fn new(
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Search for added or removed text…", window, cx);
+            editor
+        });
+        let pathspecs_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Paths: e.g. src/*.rs, docs", window, cx);
+            editor
+        });
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::new(Capability::ReadOnly);
+            multibuffer.set_all_diff_hunks_expanded(cx);
+            multibuffer
+        });
+        let results_editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), window, cx);
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor
+        });
+
+        Self {
+            project,
+            workspace,
+            repository: None,
+            query_editor,
+            pathspecs_editor,
+            target: SearchCommitTarget::Pickaxe,
+            case_sensitive: false,
+            multibuffer,
+            results_editor,
+            commits: Vec::new(),
+            commits_scroll_handle: ScrollHandle::new(),
+            status: SearchStatus::Idle,
+            _search_task: None,
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        self.search(window, cx);
+    }
+
+    fn search(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        if query.is_empty() {
+            return;
+        }
+
+        let Some(repository) = self.project.read(cx).active_repository(cx) else {
+            self.status = SearchStatus::Error("No git repository in this project".into());
+            cx.notify();
+            return;
+        };
+
+        // The same query highlights the relevant hunks of each matching commit.
+        let matcher = match self.target {
+            SearchCommitTarget::PickaxeRegex => SearchQuery::regex(
+                &query,
+                false,
+                self.case_sensitive,
+                false,
+                false,
+                PathMatcher::default(),
+                PathMatcher::default(),
+                false,
+                None,
+            ),
+            SearchCommitTarget::Message | SearchCommitTarget::Pickaxe => SearchQuery::text(
+                &query,
+                false,
+                self.case_sensitive,
+                false,
+                PathMatcher::default(),
+                PathMatcher::default(),
+                false,
+                None,
+            ),
+        };
+        let matcher = match matcher {
+            Ok(matcher) => Arc::new(matcher),
+            Err(error) => {
+                self.status = SearchStatus::Error(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+
+        let pathspecs = self
+            .pathspecs_editor
+            .read(cx)
+            .text(cx)
+            .split(',')
+            .map(str::trim)
+            .filter(|pathspec| !pathspec.is_empty())
+            .map(|pathspec| SharedString::from(pathspec.to_string()))
+            .collect();
+
+        self.commits.clear();
+        self.multibuffer
+            .update(cx, |multibuffer, cx| multibuffer.clear(cx));
+        self.repository = Some(repository.clone());
+        self.status = SearchStatus::Searching;
+
+        let (request_tx, request_rx) = async_channel::unbounded();
+        repository.update(cx, |repository, cx| {
+            repository.search_commits(
+                LogSource::Branch("HEAD".into()),
+                SearchCommitArgs {
+                    query: query.into(),
+                    case_sensitive: self.case_sensitive,
+                    target: self.target,
+                    pathspecs,
+                },
+                request_tx,
+                cx,
+            );
+        });
+
+        self._search_task = Some(cx.spawn_in(window, async move |this, cx| {
+            let mut limit_reached = false;
+            let mut commit_count = 0;
+            while let Ok(oid) = request_rx.recv().await {
+                if commit_count == MAX_COMMITS {
+                    limit_reached = true;
+                    break;
+                }
+
+                let sha = oid.to_string();
+                let (details, diff) = repository.update(cx, |repository, _| {
+                    (
+                        repository.show(sha.clone()),
+                        repository.load_commit_diff(sha.clone()),
+                    )
+                });
+                let (details, diff) = futures::join!(details, diff);
+                let (Some(details), Some(diff)) = (
+                    details.log_err().and_then(|details| details.log_err()),
+                    diff.log_err().and_then(|diff| diff.log_err()),
+                ) else {
+                    continue;
+                };
+
+                if this
+                    .update(cx, |this, cx| {
+                        this.commits.push(details);
+                        cx.notify();
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+                Self::add_commit_hunks(
+                    this.clone(),
+                    commit_count,
+                    sha,
+                    diff,
+                    matcher.clone(),
+                    repository.clone(),
+                    cx,
+                )
+                .await
+                .log_err();
+                commit_count += 1;
+            }
+
+            this.update(cx, |this, cx| {
+                this.status = SearchStatus::Finished { limit_reached };
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    /// Adds the hunks of a commit that add or remove text matching the
+    /// query to the results, with the commit's position in the search as
+    /// their sort order.
+    async fn add_commit_hunks(
+        this: WeakEntity<Self>,
+        commit_ix: usize,
+        sha: String,
+        diff: CommitDiff,
+        matcher: Arc<SearchQuery>,
+        repository: Entity<Repository>,
+        cx: &mut AsyncWindowContext,
+    ) -> Result<()> {
+        let (project, multibuffer) = this.read_with(cx, |this, _| {
+            (this.project.clone(), this.multibuffer.clone())
+        })?;
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        let short_sha = sha.get(0..git::SHORT_SHA_LENGTH).unwrap_or(&sha);
+
+        for file in diff.files {
+            if file.is_binary {
+                continue;
+            }
+
+            let Some(worktree_id) = repository.read_with(cx, |repository, cx| {
+                crate::commit_view::worktree_id_for_repo_path(
+                    repository,
+                    project.read(cx),
+                    &file.path,
+                    cx,
+                )
+            }) else {
+                continue;
+            };
+            let file_name = file
+                .path
+                .file_name()
+                .map(|name| name.to_string())
+                .unwrap_or_else(|| file.path.display(PathStyle::local()).to_string());
+            let blob = Arc::new(GitBlob {
+                path: file.path.clone(),
+                worktree_id,
+                is_deleted: file.new_text.is_none(),
+                is_binary: false,
+                display_name: format!("{short_sha} - {file_name}"),
+            }) as Arc<dyn language::File>;
+
+            let buffer = build_buffer(
+                file.new_text.unwrap_or_default(),
+                blob,
+                &language_registry,
+                cx,
+            )
+            .await?;
+            let buffer_diff =
+                build_buffer_diff(file.old_text, &buffer, &language_registry, cx).await?;
+
+            let ranges = cx.update(|_, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                let diff_snapshot = buffer_diff.read(cx).snapshot(cx);
+                let base_text = diff_snapshot.base_text();
+                diff_snapshot
+                    .hunks(&snapshot)
+                    .filter(|hunk| {
+                        let added = snapshot
+                            .text_for_range(hunk.buffer_range.clone())
+                            .collect::<String>();
+                        let removed = base_text
+                            .text_for_range(hunk.diff_base_byte_range.clone())
+                            .collect::<String>();
+                        !matcher.search_str(&added).is_empty()
+                            || !matcher.search_str(&removed).is_empty()
+                    })
+                    .map(|hunk| hunk.buffer_range.to_point(&snapshot))
+                    .collect::<Vec<_>>()
+            })?;
+            if ranges.is_empty() {
+                continue;
+            }
+
+            cx.update(|_, cx| {
+                let path = buffer.read(cx).file()?.path().clone();
+                let path_key = PathKey::with_sort_prefix(commit_ix as u64, path);
+                multibuffer.update(cx, |multibuffer, cx| {
+                    multibuffer.set_excerpts_for_path(
+                        path_key,
+                        buffer,
+                        ranges,
+                        multibuffer_context_lines(cx),
+                        cx,
+                    );
+                    multibuffer.add_diff(buffer_diff, cx);
+                });
+                Some(())
+            })?;
+        }
+
+        Ok(())
+    }
+
+    fn toggle_regex(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.target = match self.target {
+            SearchCommitTarget::PickaxeRegex => SearchCommitTarget::Pickaxe,
+            SearchCommitTarget::Message | SearchCommitTarget::Pickaxe => {
+                SearchCommitTarget::PickaxeRegex
+            }
+        };
+        self.search(window, cx);
+        cx.notify();
+    }
+
+    fn toggle_case_sensitive(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.case_sensitive = !self.case_sensitive;
+        self.search(window, cx);
+        cx.notify();
+    }
+
+    fn open_commit(&mut self, sha: SharedString, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(repository) = self.repository.as_ref() else {
+            return;
+        };
+        CommitView::open(
+            sha.to_string(),
+            repository.downgrade(),
+            self.workspace.clone(),
+            None,
+            None,
+            window,
+            cx,
+        );
+    }
+
+    fn title(&self, cx: &App) -> SharedString {
+        let query = self.query_editor.read(cx).text(cx);
+        if query.is_empty() {
+            "History Search".into()
+        } else {
+            format!("History: {query}").into()
+        }
+    }
+
+    fn render_search_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = cx.theme().colors();
+        let input = |editor: &Entity<Editor>| {
+            h_flex()
+                .h_8()
+                .flex_1()
+                .min_w_0()
+                .px_1p5()
+                .gap_1()
+                .border_1()
+                .border_color(colors.border_variant)
+                .rounded_md()
+                .bg(colors.toolbar_background)
+                .child(editor.clone())
+        };
+        let status = match &self.status {
+            SearchStatus::Idle => None,
+            SearchStatus::Searching => Some(("Searching…".into(), Color::Muted)),
+            SearchStatus::Finished { limit_reached } => {
+                let count = self.commits.len();
+                let label = match (count, limit_reached) {
+                    (_, true) => format!("First {count} commits"),
+                    (1, false) => "1 commit".to_string(),
+                    (count, false) => format!("{count} commits"),
+                };
+                Some((SharedString::from(label), Color::Muted))
+            }
+            SearchStatus::Error(error) => Some((error.clone(), Color::Error)),
+        };
+
+        h_flex()
+            .w_full()
+            .p_1p5()
+            .gap_1p5()
+            .border_b_1()
+            .border_color(colors.border_variant)
+            .child(
+                input(&self.query_editor)
+                    .on_action(cx.listener(Self::confirm))
+                    .child(
+                        IconButton::new("history-search-case-sensitive", IconName::CaseSensitive)
+                            .shape(ui::IconButtonShape::Square)
+                            .toggle_state(self.case_sensitive)
+                            .tooltip(Tooltip::text("Match Case Sensitivity"))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_case_sensitive(window, cx);
+                            })),
+                    )
+                    .child(
+                        IconButton::new("history-search-regex", IconName::Regex)
+                            .shape(ui::IconButtonShape::Square)
+                            .toggle_state(self.target == SearchCommitTarget::PickaxeRegex)
+                            .tooltip(Tooltip::text("Match Changed Lines by Regular Expression"))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_regex(window, cx);
+                            })),
+                    ),
+            )
+            .child(input(&self.pathspecs_editor).on_action(cx.listener(Self::confirm)))
+            .when_some(status, |this, (label, color)| {
+                this.child(Label::new(label).size(LabelSize::Small).color(color))
+            })
+    }
+
+    fn render_commit(
+        &self,
+        ix: usize,
+        commit: &CommitDetails,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let sha = commit.sha.clone();
+        let short_sha = SharedString::from(
+            sha.get(0..git::SHORT_SHA_LENGTH)
+                .unwrap_or(&sha)
+                .to_string(),
+        );
+        let subject = commit
+            .message
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let commit_time = OffsetDateTime::from_unix_timestamp(commit.commit_timestamp)
+            .unwrap_or_else(|_| OffsetDateTime::now_utc());
+        let local_offset = time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC);
+        let relative_time = time_format::format_localized_timestamp(
+            commit_time,
+            OffsetDateTime::now_utc(),
+            local_offset,
+            time_format::TimestampFormat::Relative,
+        );
+
+        h_flex()
+            .id(("history-search-commit", ix))
+            .w_full()
+            .px_2()
+            .py_0p5()
+            .gap_2()
+            .rounded_sm()
+            .cursor_pointer()
+            .hover(|this| this.bg(cx.theme().colors().element_hover))
+            .tooltip(Tooltip::text("Open Commit"))
+            .on_click(cx.listener(move |this, _, window, cx| {
+                this.open_commit(sha.clone(), window, cx);
+            }))
+            .child(
+                Label::new(short_sha)
+                    .size(LabelSize::Small)
+                    .color(Color::Accent)
+                    .buffer_font(cx),
+            )
+            .child(
+                div().flex_1().min_w_0().child(
+                    Label::new(subject)
+                        .size(LabelSize::Small)
+                        .single_line()
+                        .truncate(),
+                ),
+            )
+            .child(
+                Label::new(commit.author_name.clone())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(relative_time)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .into_any_element()
+    }
+}
+
+impl EventEmitter<EditorEvent> for HistorySearchView {}
+
+impl Focusable for HistorySearchView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.query_editor.focus_handle(cx)
+    }
+}
+
+impl Item for HistorySearchView {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _detail: usize, cx: &App) -> SharedString {
+        self.title(cx)
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Git History Search Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.results_editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<gpui::AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.results_editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>, _: &App) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.results_editor.clone()))
+    }
+
+    fn active_project_path(&self, cx: &App) -> Option<ProjectPath> {
+        self.results_editor.read(cx).active_project_path(cx)
+    }
+
+    fn set_nav_history(
+        &mut self,
+        nav_history: ItemNavHistory,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.results_editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn navigate(
+        &mut self,
+        data: Arc<dyn Any + Send>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        self.results_editor
+            .update(cx, |editor, cx| editor.navigate(data, window, cx))
+    }
+
+    fn breadcrumb_location(&self, _: &App) -> ToolbarItemLocation {
+        ToolbarItemLocation::PrimaryLeft
+    }
+
+    fn breadcrumbs(&self, cx: &App) -> Option<(Vec<HighlightedText>, Option<Font>)> {
+        self.results_editor.breadcrumbs(cx)
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.results_editor.update(cx, |editor, cx| {
+            editor.added_to_workspace(workspace, window, cx)
+        });
+    }
+}
+
+impl Render for HistorySearchView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let commits = self
+            .commits
+            .iter()
+            .enumerate()
+            .map(|(ix, commit)| self.render_commit(ix, commit, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .key_context("HistorySearch")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_search_bar(cx))
+            .when(!commits.is_empty(), |this| {
+                this.child(
+                    v_flex()
+                        .id("history-search-commits")
+                        .max_h(rems(12.))
+                        .p_1()
+                        .overflow_y_scroll()
+                        .track_scroll(&self.commits_scroll_handle)
+                        .border_b_1()
+                        .border_color(cx.theme().colors().border_variant)
+                        .children(commits),
+                )
+            })
+            .child(div().flex_1().min_h_0().child(self.results_editor.clone()))
+    }
+}
@@ -0,0 +1,1017 @@
+//! A three-way merge editor for resolving conflicts in a single file.
+//!
+//! The base, ours and theirs versions of the file are loaded from the index
+//! and shown side by side, each side diffed against the base, above an
+//! editable result pane containing the file with its conflict markers.
+
+use crate::conflict_view::resolve_conflict;
+use anyhow::{Context as _, Result};
+use buffer_diff::{BufferDiff, DiffBaseKind};
+use collections::HashSet;
+use editor::{
+    Editor, EditorEvent, MultiBuffer, SelectionEffects,
+    display_map::{BlockPlacement, BlockProperties, BlockStyle, CustomBlockId},
+    scroll::Autoscroll,
+};
+use git::{
+    AcceptBoth, AcceptOurs, AcceptTheirs, MarkResolved, NextConflict, OpenMergeEditor,
+    PreviousConflict, repository::RepoPath,
+};
+use gpui::{
+    AnyElement, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle, Focusable,
+    PromptLevel, Render, Subscription, Task, WeakEntity, Window,
+};
+use language::{Buffer, Capability, ToOffset as _};
+use project::{
+    ConflictRegion, ConflictSet, ConflictSetUpdate, Project, ProjectPath,
+    git_store::{MergeStages, Repository},
+};
+use std::{ops::Range, sync::Arc};
+use ui::{Divider, Tooltip, prelude::*};
+use util::paths::PathStyle;
+use workspace::{
+    Item, ItemHandle as _, Toast, Workspace,
+    item::{ItemEvent, SaveOptions},
+    notifications::{NotificationId, NotifyTaskExt},
+};
+
+/// One side of a conflict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Ours,
+    Theirs,
+}
+
+impl Side {
+    fn range(self, conflict: &ConflictRegion) -> Range<language::Anchor> {
+        match self {
+            Side::Ours => conflict.ours.clone(),
+            Side::Theirs => conflict.theirs.clone(),
+        }
+    }
+
+    fn branch_name(self, conflict: &ConflictRegion) -> SharedString {
+        match self {
+            Side::Ours => conflict.ours_branch_name.clone(),
+            Side::Theirs => conflict.theirs_branch_name.clone(),
+        }
+    }
+}
+
+/// A read-only pane showing one side of the merge, diffed against the base.
+struct SidePane {
+    side: Side,
+    buffer: Entity<Buffer>,
+    editor: Entity<Editor>,
+    block_ids: HashSet<CustomBlockId>,
+    /// Where each unresolved conflict's text for this side starts, if it
+    /// could be found.
+    conflict_anchors: Vec<Option<editor::Anchor>>,
+    _recalculate_diff: Task<()>,
+}
+
+pub struct MergeEditor {
+    project: Entity<Project>,
+    workspace: WeakEntity<Workspace>,
+    repository: Entity<Repository>,
+    repo_path: RepoPath,
+    buffer: Entity<Buffer>,
+    conflict_set: Entity<ConflictSet>,
+    base_editor: Entity<Editor>,
+    ours: SidePane,
+    theirs: SidePane,
+    result_editor: Entity<Editor>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl MergeEditor {
+    pub(crate) fn register(workspace: &mut Workspace, _: &mut Context<Workspace>) {
+        workspace.register_action(|workspace, _: &OpenMergeEditor, window, cx| {
+            Self::deploy(workspace, window, cx);
+        });
+    }
+
+    fn deploy(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+        let Some(project_path) = workspace
+            .active_item(cx)
+            .and_then(|item| item.project_path(cx))
+        else {
+            return;
+        };
+        let conflicted = workspace
+            .project()
+            .read(cx)
+            .git_store()
+            .read(cx)
+            .repository_and_path_for_project_path(&project_path, cx)
+            .filter(|(repository, repo_path)| {
+                repository.read(cx).snapshot().has_conflict(repo_path)
+            });
+        let Some((repository, repo_path)) = conflicted else {
+            struct NoConflicts;
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<NoConflicts>(),
+                    "The active file has no merge conflicts",
+                )
+                .autohide(),
+                cx,
+            );
+            return;
+        };
+
+        let workspace_handle = workspace.weak_handle();
+        Self::open_or_focus(
+            project_path,
+            repository,
+            repo_path,
+            workspace_handle.clone(),
+            window,
+            cx,
+        )
+        .detach_and_notify_err(workspace_handle, window, cx);
+    }
+
+    pub fn open_or_focus(
+        project_path: ProjectPath,
+        repository: Entity<Repository>,
+        repo_path: RepoPath,
+        workspace: WeakEntity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let Some(workspace_entity) = workspace.upgrade() else {
+            return Task::ready(Err(anyhow::anyhow!("workspace was dropped")));
+        };
+
+        let existing = workspace_entity
+            .read(cx)
+            .items_of_type::<MergeEditor>(cx)
+            .find(|item| {
+                let item = item.read(cx);
+                item.repository == repository && item.repo_path == repo_path
+            });
+        if let Some(existing) = existing {
+            workspace_entity.update(cx, |workspace, cx| {
+                workspace.activate_item(&existing, true, true, window, cx);
+            });
+            return Task::ready(Ok(existing));
+        }
+
+        let project = workspace_entity.read(cx).project().clone();
+        window.spawn(cx, async move |cx| {
+            let buffer = project
+                .update(cx, |project, cx| project.open_buffer(project_path, cx))
+                .await?;
+            let stages = repository
+                .update(cx, |repository, cx| {
+                    repository.load_merge_stages(repo_path.clone(), cx)
+                })
+                .await
+                .context("loading the conflicting versions of the file")?;
+            let conflict_set = project
+                .update(cx, |project, cx| {
+                    project.git_store().update(cx, |git_store, cx| {
+                        git_store.open_conflict_set(buffer.clone(), cx)
+                    })
+                })
+                .await;
+
+            workspace_entity.update_in(cx, |workspace, window, cx| {
+                let merge_editor = cx.new(|cx| {
+                    Self::new(
+                        project,
+                        workspace.weak_handle(),
+                        repository,
+                        repo_path,
+                        buffer,
+                        stages,
+                        conflict_set,
+                        window,
+                        cx,
+                    )
+                });
+                workspace.add_item_to_active_pane(
+                    Box::new(merge_editor.clone()),
+                    None,
+                    true,
+                    window,
+                    cx,
+                );
+                merge_editor
+            })
+        })
+    }
+
+    fn new(
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        repository: Entity<Repository>,
+        repo_path: RepoPath,
+        buffer: Entity<Buffer>,
+        stages: MergeStages,
+        conflict_set: Entity<ConflictSet>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let base_buffer = stage_buffer(stages.base.unwrap_or_default(), &buffer, cx);
+        let base_editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(base_buffer.clone(), None, window, cx);
+            editor.set_read_only(true);
+            editor
+        });
+        let ours = SidePane::new(
+            Side::Ours,
+            stages.ours.unwrap_or_default(),
+            &buffer,
+            &base_buffer,
+            window,
+            cx,
+        );
+        let theirs = SidePane::new(
+            Side::Theirs,
+            stages.theirs.unwrap_or_default(),
+            &buffer,
+            &base_buffer,
+            window,
+            cx,
+        );
+        let result_editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), Some(project.clone()), window, cx);
+            editor.set_should_serialize(false, cx);
+            editor
+        });
+
+        let subscriptions = vec![
+            cx.subscribe(&conflict_set, |this, _, _: &ConflictSetUpdate, cx| {
+                this.update_side_blocks(cx);
+            }),
+            cx.subscribe(&result_editor, |_, _, event: &EditorEvent, cx| {
+                if let EditorEvent::SelectionsChanged { .. } = event {
+                    cx.notify();
+                }
+                cx.emit(event.clone());
+            }),
+        ];
+
+        let mut this = Self {
+            project,
+            workspace,
+            repository,
+            repo_path,
+            buffer,
+            conflict_set,
+            base_editor,
+            ours,
+            theirs,
+            result_editor,
+            _subscriptions: subscriptions,
+        };
+        this.update_side_blocks(cx);
+        this
+    }
+
+    fn conflicts(&self, cx: &App) -> Arc<[ConflictRegion]> {
+        self.conflict_set.read(cx).snapshot().conflicts
+    }
+
+    fn cursor_offset(&self, cx: &App) -> Option<usize> {
+        let editor = self.result_editor.read(cx);
+        let head = editor.selections.newest_anchor().head();
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let (anchor, buffer) = snapshot.anchor_to_buffer_anchor(head)?;
+        Some(anchor.to_offset(buffer))
+    }
+
+    /// Returns the index of the conflict containing the cursor, or of the
+    /// first conflict after it.
+    fn current_conflict_ix(&self, cx: &App) -> Option<usize> {
+        let conflicts = self.conflicts(cx);
+        let cursor = self.cursor_offset(cx).unwrap_or_default();
+        let buffer = self.buffer.read(cx).snapshot();
+        conflicts
+            .iter()
+            .position(|conflict| conflict.range.end.to_offset(&buffer) >= cursor)
+    }
+
+    /// Places an accept button above each conflict in the side panes, at the
+    /// position of that side's text for the conflict.
+    fn update_side_blocks(&mut self, cx: &mut Context<Self>) {
+        let conflicts = self.conflicts(cx);
+        let result_editor = self.result_editor.downgrade();
+        let result_buffer = self.buffer.read(cx).snapshot();
+        for pane in [&mut self.ours, &mut self.theirs] {
+            let sections = conflicts
+                .iter()
+                .map(|conflict| {
+                    result_buffer
+                        .text_for_range(pane.side.range(conflict))
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+            let side_text = pane.buffer.read(cx).text();
+            let offsets = locate_sections(&side_text, sections.iter().map(String::as_str));
+            let side_buffer = pane.buffer.read(cx).snapshot();
+
+            let side = pane.side;
+            let old_block_ids = std::mem::take(&mut pane.block_ids);
+            let (anchors, block_ids) = pane.editor.update(cx, |editor, cx| {
+                editor.remove_blocks(old_block_ids, None, cx);
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let anchors = offsets
+                    .iter()
+                    .map(|offset| snapshot.anchor_in_excerpt(side_buffer.anchor_before((*offset)?)))
+                    .collect::<Vec<_>>();
+                let blocks = conflicts
+                    .iter()
+                    .zip(&anchors)
+                    .filter_map(|(conflict, anchor)| {
+                        let conflict = conflict.clone();
+                        let result_editor = result_editor.clone();
+                        Some(BlockProperties {
+                            placement: BlockPlacement::Above((*anchor)?),
+                            height: Some(1),
+                            style: BlockStyle::Sticky,
+                            render: Arc::new(move |cx| {
+                                render_accept_button(side, &conflict, result_editor.clone(), cx)
+                            }),
+                            priority: 0,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let block_ids = editor.insert_blocks(blocks, None, cx);
+                (anchors, block_ids)
+            });
+            pane.conflict_anchors = anchors;
+            pane.block_ids = block_ids.into_iter().collect();
+        }
+        cx.notify();
+    }
+
+    fn go_to_conflict(&mut self, next: bool, window: &mut Window, cx: &mut Context<Self>) {
+        let conflicts = self.conflicts(cx);
+        if conflicts.is_empty() {
+            return;
+        }
+        let buffer = self.buffer.read(cx).snapshot();
+        let cursor = self.cursor_offset(cx).unwrap_or_default();
+        let ix = if next {
+            conflicts
+                .iter()
+                .position(|conflict| conflict.range.start.to_offset(&buffer) > cursor)
+                .unwrap_or(0)
+        } else {
+            conflicts
+                .iter()
+                .rposition(|conflict| conflict.range.start.to_offset(&buffer) < cursor)
+                .unwrap_or(conflicts.len() - 1)
+        };
+        let conflict = &conflicts[ix];
+
+        self.result_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let Some(start) = snapshot.anchor_in_excerpt(conflict.range.start) else {
+                return;
+            };
+            editor.change_selections(
+                SelectionEffects::scroll(Autoscroll::center()),
+                window,
+                cx,
+                |selections| selections.select_anchor_ranges([start..start]),
+            );
+        });
+        for pane in [&self.ours, &self.theirs] {
+            let Some(Some(anchor)) = pane.conflict_anchors.get(ix).copied() else {
+                continue;
+            };
+            pane.editor.update(cx, |editor, cx| {
+                editor.change_selections(
+                    SelectionEffects::scroll(Autoscroll::center()),
+                    window,
+                    cx,
+                    |selections| selections.select_anchor_ranges([anchor..anchor]),
+                );
+            });
+        }
+        cx.notify();
+    }
+
+    fn next_conflict(&mut self, _: &NextConflict, window: &mut Window, cx: &mut Context<Self>) {
+        self.go_to_conflict(true, window, cx);
+    }
+
+    fn previous_conflict(
+        &mut self,
+        _: &PreviousConflict,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_conflict(false, window, cx);
+    }
+
+    fn accept(&mut self, sides: &[Side], window: &mut Window, cx: &mut Context<Self>) {
+        let Some(conflict) = self
+            .current_conflict_ix(cx)
+            .and_then(|ix| self.conflicts(cx).get(ix).cloned())
+        else {
+            return;
+        };
+        let ranges = sides.iter().map(|side| side.range(&conflict)).collect();
+        resolve_conflict(self.result_editor.downgrade(), conflict, ranges, window, cx).detach();
+    }
+
+    fn accept_ours(&mut self, _: &AcceptOurs, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Ours], window, cx);
+    }
+
+    fn accept_theirs(&mut self, _: &AcceptTheirs, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Theirs], window, cx);
+    }
+
+    fn accept_both(&mut self, _: &AcceptBoth, window: &mut Window, cx: &mut Context<Self>) {
+        self.accept(&[Side::Ours, Side::Theirs], window, cx);
+    }
+
+    /// Saves the result and stages it, asking for confirmation first if the
+    /// file still contains conflict markers.
+    fn mark_resolved(&mut self, _: &MarkResolved, window: &mut Window, cx: &mut Context<Self>) {
+        let remaining = self.conflicts(cx).len();
+        let confirmation = (remaining > 0).then(|| {
+            window.prompt(
+                PromptLevel::Warning,
+                &format!(
+                    "The file still contains {remaining} unresolved conflict{}. Mark it as resolved anyway?",
+                    if remaining == 1 { "" } else { "s" }
+                ),
+                None,
+                &["Mark Resolved", "Cancel"],
+                cx,
+            )
+        });
+
+        let project = self.project.clone();
+        let buffer = self.buffer.clone();
+        let repository = self.repository.clone();
+        let repo_path = self.repo_path.clone();
+        let task = cx.spawn(async move |_, cx| {
+            if let Some(confirmation) = confirmation
+                && confirmation.await? != 0
+            {
+                return Ok(());
+            }
+            if buffer.read_with(cx, |buffer, _| buffer.is_dirty()) {
+                project
+                    .update(cx, |project, cx| project.save_buffer(buffer, cx))
+                    .await
+                    .context("failed to save the merge result")?;
+            }
+            repository
+                .update(cx, |repository, cx| {
+                    repository.stage_entries(vec![repo_path], cx)
+                })
+                .await
+                .context("failed to stage the merge result")
+        });
+        task.detach_and_notify_err(self.workspace.clone(), window, cx);
+    }
+
+    fn render_pane(
+        &self,
+        title: impl Into<SharedString>,
+        color: Color,
+        editor: &Entity<Editor>,
+        cx: &Context<Self>,
+    ) -> impl IntoElement {
+        v_flex()
+            .flex_1()
+            .min_w_0()
+            .h_full()
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(Label::new(title).size(LabelSize::Small).color(color)),
+            )
+            .child(div().flex_1().min_h_0().child(editor.clone()))
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let conflicts = self.conflicts(cx);
+        let focus_handle = self.result_editor.focus_handle(cx);
+        let current = self
+            .current_conflict_ix(cx)
+            .and_then(|ix| conflicts.get(ix));
+        let status = match conflicts.len() {
+            0 => "No conflicts remaining".to_string(),
+            1 => "1 conflict remaining".to_string(),
+            n => format!("{n} conflicts remaining"),
+        };
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1p5()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(status).color(Color::Muted))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new("previous-conflict", IconName::ArrowUp)
+                            .icon_size(IconSize::Small)
+                            .disabled(conflicts.is_empty())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Go to Previous Conflict",
+                                &PreviousConflict,
+                                &focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.go_to_conflict(false, window, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new("next-conflict", IconName::ArrowDown)
+                            .icon_size(IconSize::Small)
+                            .disabled(conflicts.is_empty())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Go to Next Conflict",
+                                &NextConflict,
+                                &focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.go_to_conflict(true, window, cx)
+                            })),
+                    )
+                    .child(Divider::vertical())
+                    .child(
+                        Button::new(
+                            "accept-ours",
+                            format!(
+                                "Accept {}",
+                                current.map_or("Ours".into(), |c| c.ours_branch_name.clone())
+                            ),
+                        )
+                        .disabled(current.is_none())
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Accept Ours",
+                            &AcceptOurs,
+                            &focus_handle,
+                        ))
+                        .on_click(
+                            cx.listener(|this, _, window, cx| {
+                                this.accept(&[Side::Ours], window, cx)
+                            }),
+                        ),
+                    )
+                    .child(
+                        Button::new(
+                            "accept-theirs",
+                            format!(
+                                "Accept {}",
+                                current.map_or("Theirs".into(), |c| c.theirs_branch_name.clone())
+                            ),
+                        )
+                        .disabled(current.is_none())
+                        .tooltip(Tooltip::for_action_title_in(
+                            "Accept Theirs",
+                            &AcceptTheirs,
+                            &focus_handle,
+                        ))
+                        .on_click(cx.listener(|this, _, window, cx| {
+                            this.accept(&[Side::Theirs], window, cx)
+                        })),
+                    )
+                    .child(
+                        Button::new("accept-both", "Accept Both")
+                            .disabled(current.is_none())
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Accept Both",
+                                &AcceptBoth,
+                                &focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.accept(&[Side::Ours, Side::Theirs], window, cx)
+                            })),
+                    )
+                    .child(Divider::vertical())
+                    .child(
+                        Button::new("mark-resolved", "Mark Resolved")
+                            .style(ButtonStyle::Filled)
+                            .tooltip(Tooltip::for_action_title_in(
+                                "Save and Stage the File",
+                                &MarkResolved,
+                                &focus_handle,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.mark_resolved(&MarkResolved, window, cx)
+                            })),
+                    ),
+            )
+    }
+}
+
+impl SidePane {
+    fn new(
+        side: Side,
+        text: String,
+        result_buffer: &Entity<Buffer>,
+        base_buffer: &Entity<Buffer>,
+        window: &mut Window,
+        cx: &mut Context<MergeEditor>,
+    ) -> Self {
+        let buffer = stage_buffer(text, result_buffer, cx);
+        let diff = cx.new(|cx| {
+            BufferDiff::new_with_base_text_buffer(
+                &buffer.read(cx).text_snapshot(),
+                base_buffer.clone(),
+                DiffBaseKind::Custom,
+                cx,
+            )
+        });
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer.set_all_diff_hunks_expanded(cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, None, window, cx);
+            editor.set_read_only(true);
+            editor
+        });
+
+        // Neither side changes while the merge editor is open, so the diff
+        // against the base only needs to be computed once.
+        let recalculate_diff = cx.spawn({
+            let buffer = buffer.clone();
+            let base_buffer = base_buffer.clone();
+            async move |_, cx| {
+                let buffer_snapshot = buffer.read_with(cx, |buffer, _| buffer.text_snapshot());
+                let base_snapshot = base_buffer.read_with(cx, |buffer, _| buffer.snapshot());
+                let base_text = Arc::<str>::from(base_snapshot.text());
+                let update = diff
+                    .update(cx, |diff, cx| {
+                        diff.update_diff(buffer_snapshot, &base_snapshot, Some(base_text), cx)
+                    })
+                    .await;
+                diff.update(cx, |diff, cx| diff.set_snapshot(update, cx));
+            }
+        });
+
+        Self {
+            side,
+            buffer,
+            editor,
+            block_ids: HashSet::default(),
+            conflict_anchors: Vec::new(),
+            _recalculate_diff: recalculate_diff,
+        }
+    }
+}
+
+/// Creates a read-only buffer for one version of the conflicted file, using
+/// the language of the file itself.
+fn stage_buffer(text: String, result_buffer: &Entity<Buffer>, cx: &mut App) -> Entity<Buffer> {
+    let language = result_buffer.read(cx).language().cloned();
+    let language_registry = result_buffer.read(cx).language_registry();
+    cx.new(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        if let Some(language_registry) = language_registry {
+            buffer.set_language_registry(language_registry);
+        }
+        buffer.set_language(language, cx);
+        buffer.set_capability(Capability::ReadOnly, cx);
+        buffer
+    })
+}
+
+fn render_accept_button(
+    side: Side,
+    conflict: &ConflictRegion,
+    result_editor: WeakEntity<Editor>,
+    cx: &mut editor::display_map::BlockContext,
+) -> AnyElement {
+    h_flex()
+        .id(cx.block_id)
+        .h(cx.line_height)
+        .ml(cx.margins.gutter.width)
+        .bg(cx.theme().colors().editor_background)
+        .child(
+            Button::new("accept", format!("Accept {}", side.branch_name(conflict)))
+                .label_size(LabelSize::Small)
+                .on_click({
+                    let conflict = conflict.clone();
+                    move |_, window, cx| {
+                        let range = side.range(&conflict);
+                        resolve_conflict(
+                            result_editor.clone(),
+                            conflict.clone(),
+                            vec![range],
+                            window,
+                            cx,
+                        )
+                        .detach()
+                    }
+                }),
+        )
+        .into_any()
+}
+
+/// Finds where each of `sections` occurs in `text`, searching for each one
+/// after the end of the previous match so that sections are matched in order.
+///
+/// Empty sections, such as the side of a conflict that deleted the lines,
+/// have no position of their own and are reported as `None`.
+fn locate_sections<'a>(
+    text: &str,
+    sections: impl IntoIterator<Item = &'a str>,
+) -> Vec<Option<usize>> {
+    let mut search_start = 0;
+    sections
+        .into_iter()
+        .map(|section| {
+            if section.is_empty() {
+                return None;
+            }
+            let offset = search_start + text.get(search_start..)?.find(section)?;
+            search_start = offset + section.len();
+            Some(offset)
+        })
+        .collect()
+}
+
+impl EventEmitter<EditorEvent> for MergeEditor {}
+
+impl Focusable for MergeEditor {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.result_editor.focus_handle(cx)
+    }
+}
+
+impl Item for MergeEditor {
+    type Event = EditorEvent;
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::GitMergeConflict).color(Color::Muted))
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        let file_name = self
+            .repo_path
+            .file_name()
+            .map(str::to_string)
+            .unwrap_or_else(|| {
+                self.repo_path
+                    .as_ref()
+                    .display(PathStyle::local())
+                    .into_owned()
+            });
+        format!("Merge {file_name}").into()
+    }
+
+    fn tab_tooltip_text(&self, _cx: &App) -> Option<SharedString> {
+        Some(
+            self.repo_path
+                .as_ref()
+                .display(PathStyle::local())
+                .into_owned()
+                .into(),
+        )
+    }
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Merge Editor Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.result_editor.deactivated(window, cx);
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        self.result_editor.for_each_project_item(cx, f)
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).is_dirty(cx)
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.result_editor.read(cx).can_save(cx)
+    }
+
+    fn save(
+        &mut self,
+        options: SaveOptions,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.result_editor.save(options, project, window, cx)
+    }
+}
+
+impl Render for MergeEditor {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let conflicts = self.conflicts(cx);
+        let (ours_name, theirs_name) = conflicts.first().map_or(
+            ("Ours".into(), "Theirs".into()),
+            |conflict: &ConflictRegion| {
+                (
+                    conflict.ours_branch_name.clone(),
+                    conflict.theirs_branch_name.clone(),
+                )
+            },
+        );
+
+        v_flex()
+            .key_context("MergeEditor")
+            .on_action(cx.listener(Self::next_conflict))
+            .on_action(cx.listener(Self::previous_conflict))
+            .on_action(cx.listener(Self::accept_ours))
+            .on_action(cx.listener(Self::accept_theirs))
+            .on_action(cx.listener(Self::accept_both))
+            .on_action(cx.listener(Self::mark_resolved))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(cx))
+            .child(
+                h_flex()
+                    .h(relative(0.45))
+                    .w_full()
+                    .child(self.render_pane(
+                        format!("Ours ({ours_name})"),
+                        Color::Created,
+                        &self.ours.editor,
+                        cx,
+                    ))
+                    .child(Divider::vertical())
+                    .child(self.render_pane("Base", Color::Muted, &self.base_editor, cx))
+                    .child(Divider::vertical())
+                    .child(self.render_pane(
+                        format!("Theirs ({theirs_name})"),
+                        Color::Modified,
+                        &self.theirs.editor,
+                        cx,
+                    )),
+            )
+            .child(Divider::horizontal())
+            .child(self.render_pane("Result", Color::Default, &self.result_editor, cx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::{
+        repository::repo_path,
+        status::{UnmergedStatus, UnmergedStatusCode},
+    };
+    use gpui::TestAppContext;
+    use project::{FakeFs, Fs as _};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::{path, rel_path::rel_path};
+    use workspace::MultiWorkspace;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let store = SettingsStore::test(cx);
+            cx.set_global(store);
+            theme_settings::init(theme::LoadThemes::JustBase, cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_accept_and_mark_resolved(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\nend\n",
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/project/.git"));
+        fs.with_git_state(dot_git, true, |state| {
+            state.unmerged_paths.insert(
+                repo_path("a.txt"),
+                UnmergedStatus {
+                    first_head: UnmergedStatusCode::Updated,
+                    second_head: UnmergedStatusCode::Updated,
+                },
+            );
+            state.merge_stage_contents.insert(
+                repo_path("a.txt"),
+                [
+                    Some(b"base\nend\n".to_vec()),
+                    Some(b"ours\nend\n".to_vec()),
+                    Some(b"theirs\nend\n".to_vec()),
+                ],
+            );
+        })
+        .unwrap();
+
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        cx.run_until_parked();
+
+        let project_path = ProjectPath {
+            worktree_id: project.read_with(cx, |project, cx| {
+                project.worktrees(cx).next().unwrap().read(cx).id()
+            }),
+            path: rel_path("a.txt").into(),
+        };
+        let (repository, repo_path) = project.read_with(cx, |project, cx| {
+            project
+                .git_store()
+                .read(cx)
+                .repository_and_path_for_project_path(&project_path, cx)
+                .unwrap()
+        });
+        let merge_editor = cx
+            .update(|window, cx| {
+                MergeEditor::open_or_focus(
+                    project_path,
+                    repository,
+                    repo_path,
+                    workspace.downgrade(),
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(merge_editor.base_editor.read(cx).text(cx), "base\nend\n");
+            assert_eq!(merge_editor.ours.buffer.read(cx).text(), "ours\nend\n");
+            assert_eq!(merge_editor.theirs.buffer.read(cx).text(), "theirs\nend\n");
+            assert_eq!(merge_editor.conflicts(cx).len(), 1);
+        });
+
+        merge_editor.update_in(cx, |merge_editor, window, cx| {
+            merge_editor.accept_ours(&AcceptOurs, window, cx);
+        });
+        cx.run_until_parked();
+        merge_editor.read_with(cx, |merge_editor, cx| {
+            assert_eq!(merge_editor.buffer.read(cx).text(), "ours\nend\n");
+            assert!(merge_editor.conflicts(cx).is_empty());
+        });
+
+        merge_editor.update_in(cx, |merge_editor, window, cx| {
+            merge_editor.mark_resolved(&MarkResolved, window, cx);
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(Path::new(path!("/project/a.txt"))).await.unwrap(),
+            "ours\nend\n"
+        );
+        fs.with_git_state(dot_git, false, |state| {
+            assert_eq!(
+                state
+                    .index_contents
+                    .get(&repo_path("a.txt"))
+                    .map(Vec::as_slice),
+                Some(b"ours\nend\n".as_slice())
+            );
+            assert!(!state.unmerged_paths.contains_key(&repo_path("a.txt")));
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_locate_sections() {
+        let text = "a\nb\nconflict\nc\nconflict\nd\n";
+        assert_eq!(
+            locate_sections(text, ["conflict\n", "", "conflict\n", "missing"]),
+            [Some(4), None, Some(15), None]
+        );
+        assert_eq!(locate_sections(text, ["d\n", "a\n"]), [Some(24), None]);
+    }
+}
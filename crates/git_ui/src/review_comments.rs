@@ -0,0 +1,393 @@
+//! Saving review comments per repository and branch, and exchanging them with
+//! other tools through Markdown and GitHub review files.
+
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use db::{
+    query,
+    sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+    sqlez_macros::sql,
+};
+use git::{
+    ExportReviewCommentsAsMarkdown, ExportReviewCommentsForGitHub, ImportReviewComments,
+    repository::RepoPath,
+};
+use gpui::{App, Context, Entity, PathPromptOptions, Task, TaskExt as _, Window};
+use project::{
+    DirectoryLister,
+    git_store::GitStoreEvent,
+    review_comment_store::{
+        LineAnchor, ReviewComment, ReviewCommentKey, ReviewCommentStore, ReviewCommentStoreEvent,
+        export,
+    },
+};
+use workspace::{
+    Toast, Workspace,
+    notifications::{DetachAndPromptErr as _, NotificationId},
+};
+
+pub(crate) fn register(workspace: &mut Workspace, cx: &mut Context<Workspace>) {
+    workspace.register_action(
+        |workspace, _: &ExportReviewCommentsAsMarkdown, window, cx| {
+            export_review_comments(workspace, ExportFormat::Markdown, window, cx);
+        },
+    );
+    workspace.register_action(|workspace, _: &ExportReviewCommentsForGitHub, window, cx| {
+        export_review_comments(workspace, ExportFormat::GitHub, window, cx);
+    });
+    workspace.register_action(import_review_comments);
+
+    // Collaborators see the comments of the host, who saves them.
+    let project = workspace.project().read(cx);
+    if project.is_via_collab() {
+        return;
+    }
+    let git_store = project.git_store().clone();
+    let review_comment_store = project.review_comment_store();
+    cx.subscribe(&git_store, |workspace, _, event: &GitStoreEvent, cx| {
+        if let GitStoreEvent::RepositoryAdded = event {
+            load_saved_comments(&workspace.project().read(cx).review_comment_store(), cx);
+        }
+    })
+    .detach();
+    cx.subscribe(
+        &review_comment_store,
+        |_, store, event: &ReviewCommentStoreEvent, cx| match event {
+            ReviewCommentStoreEvent::HeadChanged(_) => load_saved_comments(&store, cx),
+            ReviewCommentStoreEvent::CommentsChanged(key) => save_comments(&store, key, cx),
+            ReviewCommentStoreEvent::CommentsLoaded(_) => {}
+        },
+    )
+    .detach();
+    load_saved_comments(&review_comment_store, cx);
+}
+
+fn load_saved_comments(store: &Entity<ReviewCommentStore>, cx: &mut App) {
+    let keys = store.update(cx, |store, cx| {
+        store
+            .repository_keys(cx)
+            .into_iter()
+            .filter(|key| store.mark_loaded(key.clone()))
+            .collect::<Vec<_>>()
+    });
+    let db = ReviewCommentsDb::global(cx);
+    for key in keys {
+        let comments = db
+            .review_comments(&key.repository, branch_column(&key))
+            .and_then(|rows| rows.into_iter().map(comment_from_row).collect());
+        match comments {
+            Ok(comments) => store.update(cx, |store, cx| store.load_comments(key, comments, cx)),
+            Err(error) => log::error!(
+                "Failed to load review comments for {}: {error:#}",
+                key.repository.display()
+            ),
+        }
+    }
+}
+
+fn save_comments(store: &Entity<ReviewCommentStore>, key: &ReviewCommentKey, cx: &mut App) {
+    let rows = store
+        .read(cx)
+        .comments(key, cx)
+        .into_iter()
+        .map(|comment| {
+            (
+                comment.path.as_unix_str().to_string(),
+                comment.lines.start_row,
+                comment.lines.end_row,
+                comment.lines.text,
+                comment.body,
+            )
+        })
+        .collect();
+    let db = ReviewCommentsDb::global(cx);
+    let repository = key.repository.clone();
+    let branch = branch_column(key).to_string();
+    db::write_and_log(cx, move || async move {
+        db.save_review_comments(repository, branch, rows).await
+    });
+}
+
+/// Comments made while the HEAD is detached are kept under an empty branch
+/// name.
+fn branch_column(key: &ReviewCommentKey) -> &str {
+    key.branch.as_deref().unwrap_or_default()
+}
+
+type ReviewCommentRow = (String, u32, u32, String, String);
+
+fn comment_from_row(row: ReviewCommentRow) -> Result<ReviewComment> {
+    let (path, start_row, end_row, text, body) = row;
+    Ok(ReviewComment::new(
+        RepoPath::new(&path)?,
+        LineAnchor {
+            start_row,
+            end_row,
+            text,
+        },
+        body,
+    ))
+}
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Markdown,
+    GitHub,
+}
+
+fn export_review_comments(
+    workspace: &mut Workspace,
+    format: ExportFormat,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let project = workspace.project().read(cx);
+    let store = project.review_comment_store().read(cx);
+    let key = store.active_key(cx);
+    let comments = key
+        .as_ref()
+        .map(|key| store.comments(key, cx))
+        .unwrap_or_default();
+    let head_sha = project.active_repository(cx).and_then(|repository| {
+        let head_commit = repository.read(cx).head_commit.as_ref()?;
+        Some(head_commit.sha.clone())
+    });
+    let Some(key) = key else {
+        show_toast(
+            workspace,
+            "No repository to export review comments from",
+            cx,
+        );
+        return;
+    };
+    if comments.is_empty() {
+        show_toast(workspace, "There are no review comments on this branch", cx);
+        return;
+    }
+
+    let (contents, file_name) = match format {
+        ExportFormat::Markdown => (
+            Ok(export::to_markdown(key.branch.as_deref(), &comments)),
+            "review.md",
+        ),
+        ExportFormat::GitHub => (
+            export::to_github_review(head_sha.as_deref(), &comments),
+            "review.json",
+        ),
+    };
+
+    let fs = workspace.app_state().fs.clone();
+    let path = workspace.prompt_for_new_path(
+        DirectoryLister::Local(workspace.project().clone(), fs.clone()),
+        Some(file_name.to_string()),
+        window,
+        cx,
+    );
+    let comment_count = comments.len();
+    cx.spawn_in(window, async move |workspace, cx| {
+        let contents = contents?;
+        let Some(path) = path.await?.and_then(|mut paths| paths.pop()) else {
+            return Ok(());
+        };
+        fs.atomic_write(path.clone(), contents).await?;
+        workspace.update(cx, |workspace, cx| {
+            show_toast(
+                workspace,
+                format!(
+                    "Exported {comment_count} review comment{} to {}",
+                    if comment_count == 1 { "" } else { "s" },
+                    path.display()
+                ),
+                cx,
+            );
+        })
+    })
+    .detach_and_prompt_err("Failed to export review comments", window, cx, |_, _, _| {
+        None
+    });
+}
+
+fn import_review_comments(
+    workspace: &mut Workspace,
+    _: &ImportReviewComments,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let store = workspace.project().read(cx).review_comment_store();
+    let Some(key) = store.read(cx).active_key(cx) else {
+        show_toast(
+            workspace,
+            "No repository to import review comments into",
+            cx,
+        );
+        return;
+    };
+
+    let fs = workspace.app_state().fs.clone();
+    let paths = workspace.prompt_for_open_path(
+        PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import".into()),
+        },
+        DirectoryLister::Local(workspace.project().clone(), fs.clone()),
+        window,
+        cx,
+    );
+    let task: Task<Result<()>> = cx.spawn_in(window, async move |workspace, cx| {
+        let Some(path) = paths.await?.and_then(|mut paths| paths.pop()) else {
+            return Ok(());
+        };
+        let contents = fs.load(&path).await?;
+        let comments = parse_review_file(&path, &contents)
+            .with_context(|| format!("reading {}", path.display()))?;
+        let comment_count = comments.len();
+        store.update(cx, |store, cx| store.import_comments(key, comments, cx));
+        workspace.update(cx, |workspace, cx| {
+            show_toast(
+                workspace,
+                format!(
+                    "Imported {comment_count} review comment{}",
+                    if comment_count == 1 { "" } else { "s" }
+                ),
+                cx,
+            );
+        })
+    });
+    task.detach_and_prompt_err("Failed to import review comments", window, cx, |_, _, _| {
+        None
+    });
+}
+
+/// GitHub review payloads are JSON; anything else is read as Markdown.
+fn parse_review_file(path: &Path, contents: &str) -> Result<Vec<ReviewComment>> {
+    if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        export::from_github_review(contents)
+    } else {
+        export::from_markdown(contents)
+    }
+}
+
+fn show_toast(workspace: &mut Workspace, message: impl Into<String>, cx: &mut Context<Workspace>) {
+    struct ReviewCommentsToast;
+    workspace.show_toast(
+        Toast::new(
+            NotificationId::unique::<ReviewCommentsToast>(),
+            message.into(),
+        ),
+        cx,
+    );
+}
+
+pub struct ReviewCommentsDb(ThreadSafeConnection);
+
+impl Domain for ReviewCommentsDb {
+    const NAME: &str = stringify!(ReviewCommentsDb);
+
+    const MIGRATIONS: &[&str] = &[sql!(
+        CREATE TABLE review_comments(
+            repository_path TEXT NOT NULL,
+            branch TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            start_row INTEGER NOT NULL,
+            end_row INTEGER NOT NULL,
+            line_text TEXT NOT NULL,
+            body TEXT NOT NULL,
+            PRIMARY KEY(repository_path, branch, position)
+        ) STRICT;
+    )];
+}
+
+db::static_connection!(ReviewCommentsDb, []);
+
+impl ReviewCommentsDb {
+    query! {
+        fn review_comments(repository_path: &Path, branch: &str) -> Result<Vec<ReviewCommentRow>> {
+            SELECT path, start_row, end_row, line_text, body
+            FROM review_comments
+            WHERE repository_path = ? AND branch = ?
+            ORDER BY position
+        }
+    }
+
+    async fn save_review_comments(
+        &self,
+        repository_path: Arc<Path>,
+        branch: String,
+        comments: Vec<ReviewCommentRow>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM review_comments WHERE repository_path = ?1 AND branch = ?2;
+            ))?((repository_path.as_ref(), branch.as_str()))?;
+
+            for (position, (path, start_row, end_row, line_text, body)) in
+                comments.into_iter().enumerate()
+            {
+                conn.exec_bound(sql!(
+                    INSERT INTO review_comments
+                        (repository_path, branch, position, path, start_row, end_row, line_text, body)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8);
+                ))?((
+                    repository_path.as_ref(),
+                    branch.as_str(),
+                    position,
+                    path,
+                    start_row,
+                    end_row,
+                    line_text,
+                    body,
+                ))?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_review_comments_round_trip_through_db() {
+        let db = ReviewCommentsDb::open_test_db("test_review_comments_round_trip_through_db").await;
+        let repository: Arc<Path> = Path::new("/repo").into();
+        let rows = vec![
+            (
+                "src/main.rs".to_string(),
+                4,
+                5,
+                "fn main() {\n}".to_string(),
+                "First".to_string(),
+            ),
+            (
+                "README.md".to_string(),
+                0,
+                0,
+                String::new(),
+                "Second".to_string(),
+            ),
+        ];
+
+        db.save_review_comments(repository.clone(), "feature".into(), rows.clone())
+            .await
+            .unwrap();
+        assert_eq!(db.review_comments(&repository, "feature").unwrap(), rows);
+        assert_eq!(db.review_comments(&repository, "main").unwrap(), vec![]);
+
+        db.save_review_comments(repository.clone(), "feature".into(), rows[1..].to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            db.review_comments(&repository, "feature").unwrap(),
+            rows[1..]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::ops::Range;
+
+use anyhow::{Result, anyhow};
+
+/// How many possible starts of a match are searched per read.
+const SEARCH_CHUNK_LEN: usize = 1024 * 1024;
+
+/// A sequence of bytes to search for, where `None` matches any byte.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BytePattern(Vec<Option<u8>>);
+
+impl BytePattern {
+    /// Parses a pattern made of hex byte pairs, `??` wildcards and quoted
+    /// ASCII strings, optionally separated by whitespace, such as
+    /// `7f 45 4c 46`, `de??beef` or `"PK" 03 04`.
+    pub fn parse(query: &str) -> Result<Self> {
+        let mut bytes = Vec::new();
+        let mut chars = query.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_whitespace() => {}
+                '"' => {
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '"' {
+                            closed = true;
+                            break;
+                        }
+                        let mut buf = [0; 4];
+                        bytes.extend(c.encode_utf8(&mut buf).bytes().map(Some));
+                    }
+                    if !closed {
+                        return Err(anyhow!("unterminated string in byte pattern"));
+                    }
+                }
+                '?' => {
+                    if chars.next() != Some('?') {
+                        return Err(anyhow!("wildcards must cover a whole byte, as in `??`"));
+                    }
+                    bytes.push(None);
+                }
+                high => {
+                    let low = chars
+                        .next()
+                        .ok_or_else(|| anyhow!("incomplete byte `{high}` in byte pattern"))?;
+                    let byte = high
+                        .to_digit(16)
+                        .zip(low.to_digit(16))
+                        .map(|(high, low)| (high * 16 + low) as u8)
+                        .ok_or_else(|| anyhow!("invalid byte `{high}{low}` in byte pattern"))?;
+                    bytes.push(Some(byte));
+                }
+            }
+        }
+        if bytes.is_empty() {
+            return Err(anyhow!("empty byte pattern"));
+        }
+        Ok(Self(bytes))
+    }
+
+    fn matches_at(&self, haystack: &[u8], offset: usize) -> bool {
+        haystack
+            .get(offset..offset + self.0.len())
+            .is_some_and(|window| {
+                window
+                    .iter()
+                    .zip(&self.0)
+                    .all(|(byte, expected)| expected.is_none_or(|expected| *byte == expected))
+            })
+    }
+
+    /// Finds the first match starting at or after `offset`, wrapping around
+    /// to the start of `haystack`.
+    pub fn find_next(&self, haystack: &[u8], offset: usize) -> Option<Range<usize>> {
+        self.find_next_in(haystack.len(), offset, |range| Ok(haystack[range].to_vec()))
+            .ok()
+            .flatten()
+    }
+
+    /// Finds the last match starting before `offset`, wrapping around to the
+    /// end of `haystack`.
+    pub fn find_previous(&self, haystack: &[u8], offset: usize) -> Option<Range<usize>> {
+        self.find_previous_in(haystack.len(), offset, |range| Ok(haystack[range].to_vec()))
+            .ok()
+            .flatten()
+    }
+
+    /// Like [`Self::find_next`], for `len` bytes that are read a chunk at a
+    /// time with `read`.
+    pub fn find_next_in(
+        &self,
+        len: usize,
+        offset: usize,
+        mut read: impl FnMut(Range<usize>) -> Result<Vec<u8>>,
+    ) -> Result<Option<Range<usize>>> {
+        let Some(last_start) = len.checked_sub(self.0.len()) else {
+            return Ok(None);
+        };
+        let start = offset.min(last_start + 1);
+        if let Some(found) = self.search(start..last_start + 1, false, &mut read)? {
+            return Ok(Some(found));
+        }
+        self.search(0..start, false, &mut read)
+    }
+
+    /// Like [`Self::find_previous`], for `len` bytes that are read a chunk at
+    /// a time with `read`.
+    pub fn find_previous_in(
+        &self,
+        len: usize,
+        offset: usize,
+        mut read: impl FnMut(Range<usize>) -> Result<Vec<u8>>,
+    ) -> Result<Option<Range<usize>>> {
+        let Some(last_start) = len.checked_sub(self.0.len()) else {
+            return Ok(None);
+        };
+        let end = offset.min(last_start + 1);
+        if let Some(found) = self.search(0..end, true, &mut read)? {
+            return Ok(Some(found));
+        }
+        self.search(end..last_start + 1, true, &mut read)
+    }
+
+    /// Finds the first match starting in `starts`, or the last one if
+    /// `reversed`.
+    fn search(
+        &self,
+        starts: Range<usize>,
+        reversed: bool,
+        read: &mut impl FnMut(Range<usize>) -> Result<Vec<u8>>,
+    ) -> Result<Option<Range<usize>>> {
+        let chunk_count = starts.len().div_ceil(SEARCH_CHUNK_LEN);
+        let chunks: Box<dyn Iterator<Item = usize>> = if reversed {
+            Box::new((0..chunk_count).rev())
+        } else {
+            Box::new(0..chunk_count)
+        };
+        for chunk in chunks {
+            let chunk_start = starts.start + chunk * SEARCH_CHUNK_LEN;
+            let chunk_len = SEARCH_CHUNK_LEN.min(starts.end - chunk_start);
+            let haystack = read(chunk_start..chunk_start + chunk_len + self.0.len() - 1)?;
+            let found = if reversed {
+                (0..chunk_len)
+                    .rev()
+                    .find(|&start| self.matches_at(&haystack, start))
+            } else {
+                (0..chunk_len).find(|&start| self.matches_at(&haystack, start))
+            };
+            if let Some(start) = found {
+                let start = chunk_start + start;
+                return Ok(Some(start..start + self.0.len()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            BytePattern::parse("7f 45 4C46").unwrap(),
+            BytePattern(vec![Some(0x7f), Some(0x45), Some(0x4c), Some(0x46)])
+        );
+        assert_eq!(
+            BytePattern::parse("\"PK\" ?? 04").unwrap(),
+            BytePattern(vec![Some(b'P'), Some(b'K'), None, Some(0x04)])
+        );
+        assert!(BytePattern::parse("").is_err());
+        assert!(BytePattern::parse("abc").is_err());
+        assert!(BytePattern::parse("zz").is_err());
+        assert!(BytePattern::parse("?1").is_err());
+        assert!(BytePattern::parse("\"PK").is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let haystack = [0xde, 0xad, 0x00, 0xde, 0xbe, 0xef, 0xde, 0xad];
+        let pattern = BytePattern::parse("de ??").unwrap();
+
+        assert_eq!(pattern.find_next(&haystack, 0), Some(0..2));
+        assert_eq!(pattern.find_next(&haystack, 1), Some(3..5));
+        assert_eq!(pattern.find_next(&haystack, 4), Some(6..8));
+        assert_eq!(pattern.find_next(&haystack, 7), Some(0..2));
+        assert_eq!(pattern.find_previous(&haystack, 6), Some(3..5));
+        assert_eq!(pattern.find_previous(&haystack, 0), Some(6..8));
+
+        let pattern = BytePattern::parse("be ef").unwrap();
+        assert_eq!(pattern.find_next(&haystack, 4), Some(4..6));
+        assert_eq!(pattern.find_next(&haystack, 5), Some(4..6));
+        assert_eq!(pattern.find_previous(&haystack, 4), Some(4..6));
+        assert_eq!(
+            BytePattern::parse("ff").unwrap().find_next(&haystack, 0),
+            None
+        );
+        assert_eq!(pattern.find_next(&[0xbe], 0), None);
+
+        // Matches across the chunks that are read at once are found too.
+        let mut haystack = vec![0; 2 * SEARCH_CHUNK_LEN + 4];
+        haystack[SEARCH_CHUNK_LEN - 1] = 0xbe;
+        haystack[SEARCH_CHUNK_LEN] = 0xef;
+        let expected = Some(SEARCH_CHUNK_LEN - 1..SEARCH_CHUNK_LEN + 1);
+        assert_eq!(pattern.find_next(&haystack, 1), expected);
+        assert_eq!(pattern.find_previous(&haystack, haystack.len()), expected);
+    }
+}
@@ -0,0 +1,127 @@
+/// The byte order used to interpret multi-byte values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+impl Endianness {
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Little => Self::Big,
+            Self::Big => Self::Little,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Little => "Little Endian",
+            Self::Big => "Big Endian",
+        }
+    }
+}
+
+/// A value decoded from the bytes at the cursor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InspectedValue {
+    pub label: &'static str,
+    /// The decoded value, or `None` if there are too few bytes left.
+    pub value: Option<String>,
+}
+
+fn read<const N: usize>(bytes: &[u8], endianness: Endianness) -> Option<[u8; N]> {
+    let mut array: [u8; N] = bytes.get(..N)?.try_into().ok()?;
+    if endianness == Endianness::Big {
+        array.reverse();
+    }
+    Some(array)
+}
+
+/// Interprets the bytes at the start of `bytes` as each of the common
+/// integer and floating-point types.
+pub fn inspect(bytes: &[u8], endianness: Endianness) -> Vec<InspectedValue> {
+    macro_rules! value {
+        ($label:literal, $ty:ty, $len:literal) => {
+            InspectedValue {
+                label: $label,
+                value: read::<$len>(bytes, endianness)
+                    .map(|array| <$ty>::from_le_bytes(array).to_string()),
+            }
+        };
+    }
+
+    vec![
+        InspectedValue {
+            label: "binary",
+            value: bytes.first().map(|byte| format!("{byte:08b}")),
+        },
+        value!("u8", u8, 1),
+        value!("i8", i8, 1),
+        value!("u16", u16, 2),
+        value!("i16", i16, 2),
+        value!("u32", u32, 4),
+        value!("i32", i32, 4),
+        value!("u64", u64, 8),
+        value!("i64", i64, 8),
+        value!("f32", f32, 4),
+        value!("f64", f64, 8),
+        InspectedValue {
+            label: "UTF-8",
+            value: bytes.first().map(|_| {
+                let len = bytes.len().min(4);
+                match std::str::from_utf8(&bytes[..len]) {
+                    Ok(text) => text.chars().next(),
+                    Err(error) => std::str::from_utf8(&bytes[..error.valid_up_to()])
+                        .ok()
+                        .and_then(|text| text.chars().next()),
+                }
+                .map_or_else(|| "invalid".to_string(), |c| format!("{c:?}"))
+            }),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(values: &[InspectedValue], label: &str) -> Option<String> {
+        values
+            .iter()
+            .find(|value| value.label == label)
+            .and_then(|value| value.value.clone())
+    }
+
+    #[test]
+    fn test_inspect() {
+        let bytes = [0xfe, 0xff, 0x00, 0x00, 0x00, 0x00, 0xf0, 0x3f];
+
+        let values = inspect(&bytes, Endianness::Little);
+        assert_eq!(value(&values, "binary").as_deref(), Some("11111110"));
+        assert_eq!(value(&values, "u8").as_deref(), Some("254"));
+        assert_eq!(value(&values, "i8").as_deref(), Some("-2"));
+        assert_eq!(value(&values, "u16").as_deref(), Some("65534"));
+        assert_eq!(value(&values, "i16").as_deref(), Some("-2"));
+        assert_eq!(value(&values, "u32").as_deref(), Some("65534"));
+        assert_eq!(value(&values, "f64").as_deref(), Some("1.0000000000145515"));
+        assert_eq!(value(&values, "UTF-8").as_deref(), Some("invalid"));
+
+        let values = inspect(&bytes, Endianness::Big);
+        assert_eq!(value(&values, "u16").as_deref(), Some("65279"));
+        assert_eq!(value(&values, "i32").as_deref(), Some("-16842752"));
+
+        let values = inspect(&bytes[6..], Endianness::Little);
+        assert_eq!(value(&values, "u16").as_deref(), Some("16368"));
+        assert_eq!(value(&values, "u32"), None);
+        assert_eq!(value(&values, "f64"), None);
+
+        let values = inspect("é!".as_bytes(), Endianness::Little);
+        assert_eq!(value(&values, "UTF-8").as_deref(), Some("'é'"));
+        assert!(
+            inspect(&[], Endianness::Big)
+                .iter()
+                .all(|value| value.value.is_none())
+        );
+    }
+}
@@ -0,0 +1,811 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{self, Write as _},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Task};
+use project::{Project, ProjectEntryId, ProjectPath};
+use util::ResultExt as _;
+
+use crate::byte_pattern::BytePattern;
+
+/// How many bytes of a file are read at once.
+const PAGE_LEN: usize = 64 * 1024;
+/// How many pages are kept in memory, which bounds the memory used for a
+/// file however large it is.
+const MAX_CACHED_PAGES: usize = 256;
+/// How long runs of typed bytes may grow before new bytes start a new piece.
+const MAX_MERGED_PIECE_LEN: usize = 4 * 1024;
+
+pub enum HexBufferEvent {
+    /// The bytes were edited, undone, redone or reloaded.
+    Edited,
+    /// The bytes were written to disk.
+    Saved,
+}
+
+/// A file on disk whose bytes are read a page at a time when they are needed.
+struct SourceFile {
+    /// Identifies the pages of this file in the page cache.
+    id: usize,
+    file: File,
+    len: usize,
+}
+
+impl SourceFile {
+    fn open(id: usize, abs_path: &Path) -> Result<Self> {
+        let file = File::open(abs_path).with_context(|| format!("opening {abs_path:?}"))?;
+        let len = file
+            .metadata()
+            .with_context(|| format!("reading metadata of {abs_path:?}"))?
+            .len();
+        Ok(Self {
+            id,
+            file,
+            len: usize::try_from(len)?,
+        })
+    }
+
+    /// Reads the given byte range, returning fewer bytes than requested if
+    /// the file has been truncated since it was opened.
+    fn read(&self, range: Range<usize>) -> io::Result<Vec<u8>> {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let mut buffer = vec![0; range.len()];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match read_at(
+                &self.file,
+                &mut buffer[filled..],
+                (range.start + filled) as u64,
+            ) {
+                Ok(0) => break,
+                Ok(read_len) => filled += read_len,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        buffer.truncate(filled);
+        Ok(buffer)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buffer: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buffer, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buffer: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buffer, offset)
+}
+
+/// A run of consecutive bytes of a [`HexBuffer`].
+#[derive(Clone)]
+enum Piece {
+    /// Bytes of a file that were not edited since it was opened or saved.
+    File {
+        file: Arc<SourceFile>,
+        range: Range<usize>,
+    },
+    /// Bytes written by edits.
+    Memory {
+        bytes: Arc<[u8]>,
+        range: Range<usize>,
+    },
+}
+
+impl Piece {
+    fn memory(bytes: &[u8]) -> Self {
+        Self::Memory {
+            bytes: bytes.into(),
+            range: 0..bytes.len(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Self::File { range, .. } | Self::Memory { range, .. } => range.len(),
+        }
+    }
+
+    /// The part of this piece at `range`, relative to its start.
+    fn slice(&self, range: Range<usize>) -> Self {
+        match self {
+            Self::File { file, range: own } => Self::File {
+                file: file.clone(),
+                range: own.start + range.start..own.start + range.end,
+            },
+            Self::Memory { bytes, range: own } => Self::Memory {
+                bytes: bytes.clone(),
+                range: own.start + range.start..own.start + range.end,
+            },
+        }
+    }
+
+    /// Joins `next` to this piece if it continues it, copying short runs of
+    /// typed bytes so that typing doesn't leave a piece per byte.
+    fn merge(&mut self, next: &Self) -> bool {
+        match (self, next) {
+            (
+                Self::File { file, range },
+                Self::File {
+                    file: next_file,
+                    range: next_range,
+                },
+            ) if Arc::ptr_eq(file, next_file) && range.end == next_range.start => {
+                range.end = next_range.end;
+                true
+            }
+            (
+                Self::Memory { bytes, range },
+                Self::Memory {
+                    bytes: next_bytes,
+                    range: next_range,
+                },
+            ) => {
+                if Arc::ptr_eq(bytes, next_bytes) && range.end == next_range.start {
+                    range.end = next_range.end;
+                } else if range.len() + next_range.len() <= MAX_MERGED_PIECE_LEN {
+                    let merged = bytes[range.clone()]
+                        .iter()
+                        .chain(&next_bytes[next_range.clone()])
+                        .copied()
+                        .collect::<Arc<[u8]>>();
+                    *range = 0..merged.len();
+                    *bytes = merged;
+                } else {
+                    return false;
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn pieces_len(pieces: &[Piece]) -> usize {
+    pieces.iter().map(Piece::len).sum()
+}
+
+/// The bytes of a buffer at some point in time, which are read straight from
+/// disk, so that they can be searched or saved on a background thread.
+#[derive(Clone)]
+struct BytesSnapshot {
+    pieces: Vec<Piece>,
+    len: usize,
+}
+
+impl BytesSnapshot {
+    fn read(&self, range: Range<usize>) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(range.len());
+        let mut offset = 0;
+        for piece in &self.pieces {
+            let piece_range = offset..offset + piece.len();
+            offset = piece_range.end;
+            if piece_range.end <= range.start {
+                continue;
+            }
+            if piece_range.start >= range.end {
+                break;
+            }
+            let start = range.start.max(piece_range.start) - piece_range.start;
+            let end = range.end.min(piece_range.end) - piece_range.start;
+            match piece.slice(start..end) {
+                Piece::File { file, range } => bytes.extend(file.read(range)?),
+                Piece::Memory {
+                    bytes: memory,
+                    range,
+                } => bytes.extend_from_slice(&memory[range]),
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Writes the bytes next to `abs_path` and then moves them over it, so
+    /// that the pieces of the current file stay readable while writing.
+    fn write_to(&self, abs_path: &Path) -> Result<()> {
+        let file_name = abs_path
+            .file_name()
+            .with_context(|| format!("no file name in {abs_path:?}"))?;
+        let mut temp_name = file_name.to_owned();
+        temp_name.push(".hex-editor-save");
+        let temp_path = abs_path.with_file_name(temp_name);
+
+        let mut temp_file =
+            File::create(&temp_path).with_context(|| format!("creating {temp_path:?}"))?;
+        let written = (|| {
+            for page_start in (0..self.len).step_by(PAGE_LEN) {
+                temp_file.write_all(&self.read(page_start..page_start + PAGE_LEN)?)?;
+            }
+            temp_file.sync_all()?;
+            if let Ok(metadata) = std::fs::metadata(abs_path) {
+                std::fs::set_permissions(&temp_path, metadata.permissions())?;
+            }
+            std::fs::rename(&temp_path, abs_path)
+        })();
+        if written.is_err() {
+            std::fs::remove_file(&temp_path).log_err();
+        }
+        written.with_context(|| format!("writing {abs_path:?}"))
+    }
+}
+
+/// A single replacement of the pieces `old` with `new` at `offset`.
+#[derive(Clone)]
+struct ByteEdit {
+    offset: usize,
+    old: Vec<Piece>,
+    new: Vec<Piece>,
+}
+
+/// Edits that are undone and redone together.
+#[derive(Clone, Default)]
+struct Transaction {
+    edits: Vec<ByteEdit>,
+}
+
+/// The bytes of a file opened in the hex editor, with their undo history.
+///
+/// The bytes are a list of pieces, which are either ranges of the file on
+/// disk or bytes written by edits, so opening and editing a file doesn't
+/// depend on its size. The pages of the file are read in the background when
+/// they are first shown, and only a bounded number of them are kept.
+pub struct HexBuffer {
+    project_path: Option<ProjectPath>,
+    entry_id: Option<ProjectEntryId>,
+    abs_path: Option<PathBuf>,
+    pieces: Vec<Piece>,
+    len: usize,
+    /// Incremented on every change to the pieces.
+    version: usize,
+    next_source_id: usize,
+    pages: HashMap<(usize, usize), Arc<[u8]>>,
+    /// The cached pages, from the least to the most recently loaded.
+    page_order: VecDeque<(usize, usize)>,
+    loading_pages: HashSet<(usize, usize)>,
+    undo_stack: Vec<Transaction>,
+    redo_stack: Vec<Transaction>,
+    /// Whether the next edit may be grouped with the last transaction.
+    last_transaction_open: bool,
+    /// The length of the undo stack when the bytes were last loaded or saved,
+    /// or `None` if that state can no longer be reached by undoing.
+    saved_undo_depth: Option<usize>,
+}
+
+impl EventEmitter<HexBufferEvent> for HexBuffer {}
+
+impl HexBuffer {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        Self {
+            project_path: None,
+            entry_id: None,
+            abs_path: None,
+            pieces: if bytes.is_empty() {
+                Vec::new()
+            } else {
+                vec![Piece::memory(&bytes)]
+            },
+            len,
+            version: 0,
+            next_source_id: 0,
+            pages: HashMap::default(),
+            page_order: VecDeque::new(),
+            loading_pages: HashSet::default(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_transaction_open: false,
+            saved_undo_depth: Some(0),
+        }
+    }
+
+    /// Opens the given file, whose bytes are read as they are shown. Only
+    /// files in local projects can be opened, as the bytes are read directly
+    /// from the file system.
+    pub fn open(
+        project: Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let project = project.read(cx);
+        if !project.is_local() {
+            return Task::ready(Err(anyhow::anyhow!(
+                "the hex editor only supports local projects"
+            )));
+        }
+        let Some(abs_path) = project.absolute_path(&project_path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "no absolute path for {:?}",
+                project_path.path
+            )));
+        };
+        let entry_id = project
+            .entry_for_path(&project_path, cx)
+            .map(|entry| entry.id);
+
+        cx.spawn(async move |cx| {
+            let source = cx
+                .background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move { SourceFile::open(0, &abs_path) }
+                })
+                .await?;
+            Ok(cx.new(|_| {
+                let mut this = Self {
+                    project_path: Some(project_path),
+                    entry_id,
+                    abs_path: Some(abs_path),
+                    ..Self::new(Vec::new())
+                };
+                this.set_source(source);
+                this
+            }))
+        })
+    }
+
+    pub fn abs_path(&self) -> Option<&Path> {
+        self.abs_path.as_deref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.saved_undo_depth != Some(self.undo_stack.len())
+    }
+
+    /// Returns the bytes in `range`, or `None` while some of them are still
+    /// being read from disk, in which case the buffer notifies once they are.
+    pub fn bytes(&mut self, range: Range<usize>, cx: &mut Context<Self>) -> Option<Vec<u8>> {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let mut bytes = Vec::with_capacity(range.len());
+        let mut missing_pages = Vec::new();
+        let mut offset = 0;
+        for piece in &self.pieces {
+            let piece_range = offset..offset + piece.len();
+            offset = piece_range.end;
+            if piece_range.end <= range.start {
+                continue;
+            }
+            if piece_range.start >= range.end {
+                break;
+            }
+            let start = range.start.max(piece_range.start) - piece_range.start;
+            let end = range.end.min(piece_range.end) - piece_range.start;
+            match piece.slice(start..end) {
+                Piece::Memory {
+                    bytes: memory,
+                    range,
+                } => bytes.extend_from_slice(&memory[range]),
+                Piece::File { file, range } => {
+                    for page in range.start / PAGE_LEN..range.end.div_ceil(PAGE_LEN) {
+                        let page_start = page * PAGE_LEN;
+                        match self.pages.get(&(file.id, page)) {
+                            Some(page_bytes) if missing_pages.is_empty() => {
+                                let start = range.start.max(page_start) - page_start;
+                                let end = (range.end.min(page_start + PAGE_LEN) - page_start)
+                                    .min(page_bytes.len());
+                                bytes.extend_from_slice(&page_bytes[start.min(end)..end]);
+                            }
+                            Some(_) => {}
+                            None => missing_pages.push((file.clone(), page)),
+                        }
+                    }
+                }
+            }
+        }
+
+        if missing_pages.is_empty() {
+            return Some(bytes);
+        }
+        for (file, page) in missing_pages {
+            self.load_page(file, page, cx);
+        }
+        None
+    }
+
+    fn load_page(&mut self, file: Arc<SourceFile>, page: usize, cx: &mut Context<Self>) {
+        let key = (file.id, page);
+        if !self.loading_pages.insert(key) {
+            return;
+        }
+        let read = cx.background_spawn(async move {
+            let page_start = page * PAGE_LEN;
+            file.read(page_start..page_start + PAGE_LEN)
+        });
+        cx.spawn(async move |this, cx| {
+            let page_bytes = read.await;
+            this.update(cx, |this, cx| {
+                this.loading_pages.remove(&key);
+                let Some(page_bytes) = page_bytes.log_err() else {
+                    return;
+                };
+                this.pages.insert(key, page_bytes.into());
+                this.page_order.push_back(key);
+                while this.page_order.len() > MAX_CACHED_PAGES {
+                    if let Some(evicted) = this.page_order.pop_front() {
+                        this.pages.remove(&evicted);
+                    }
+                }
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
+    /// Replaces all bytes with the ones of `source`, dropping the pages that
+    /// were read before.
+    fn set_source(&mut self, mut source: SourceFile) {
+        source.id = self.next_source_id;
+        self.next_source_id += 1;
+        self.len = source.len;
+        self.pieces = if source.len == 0 {
+            Vec::new()
+        } else {
+            vec![Piece::File {
+                range: 0..source.len,
+                file: Arc::new(source),
+            }]
+        };
+        self.version += 1;
+        self.pages.clear();
+        self.page_order.clear();
+    }
+
+    fn snapshot(&self) -> BytesSnapshot {
+        BytesSnapshot {
+            pieces: self.pieces.clone(),
+            len: self.len,
+        }
+    }
+
+    /// Replaces the bytes in `range` with the `new` pieces, returning the
+    /// pieces that were replaced.
+    fn replace(&mut self, range: Range<usize>, new: Vec<Piece>) -> Vec<Piece> {
+        let new_len = pieces_len(&new);
+        let mut pieces = Vec::with_capacity(self.pieces.len() + new.len() + 1);
+        let mut replaced = Vec::new();
+        let mut new = Some(new);
+        let mut offset = 0;
+        for piece in std::mem::take(&mut self.pieces) {
+            let piece_range = offset..offset + piece.len();
+            offset = piece_range.end;
+            if piece_range.end <= range.start {
+                pieces.push(piece);
+                continue;
+            }
+            if piece_range.start < range.start {
+                pieces.push(piece.slice(0..range.start - piece_range.start));
+            }
+            if let Some(new) = new.take() {
+                pieces.extend(new);
+            }
+            let start = range.start.max(piece_range.start);
+            let end = range.end.min(piece_range.end);
+            if start < end {
+                replaced.push(piece.slice(start - piece_range.start..end - piece_range.start));
+            }
+            if piece_range.end > range.end {
+                let start = range.end.max(piece_range.start) - piece_range.start;
+                pieces.push(piece.slice(start..piece.len()));
+            }
+        }
+        pieces.extend(new.into_iter().flatten());
+
+        self.pieces = Vec::with_capacity(pieces.len());
+        for piece in pieces {
+            if piece.len() == 0 {
+                continue;
+            }
+            if let Some(last) = self.pieces.last_mut()
+                && last.merge(&piece)
+            {
+                continue;
+            }
+            self.pieces.push(piece);
+        }
+        self.len = self.len - range.len() + new_len;
+        self.version += 1;
+        replaced
+    }
+
+    /// Replaces the bytes in `range` with `new_bytes`.
+    ///
+    /// The edit is undone together with the previous one if that transaction
+    /// is still open and this edit touches the bytes it changed, so typing
+    /// both digits of a byte or a run of bytes is a single undo step.
+    pub fn edit(&mut self, range: Range<usize>, new_bytes: &[u8], cx: &mut Context<Self>) {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let new = if new_bytes.is_empty() {
+            Vec::new()
+        } else {
+            vec![Piece::memory(new_bytes)]
+        };
+        let old = self.replace(range.clone(), new.clone());
+        let edit = ByteEdit {
+            offset: range.start,
+            old,
+            new,
+        };
+
+        let extends_last_transaction = self.last_transaction_open
+            && self
+                .undo_stack
+                .last()
+                .and_then(|transaction| transaction.edits.last())
+                .is_some_and(|last| {
+                    let last_range = last.offset..last.offset + pieces_len(&last.new);
+                    range.start >= last_range.start && range.start <= last_range.end
+                });
+        if extends_last_transaction {
+            if let Some(transaction) = self.undo_stack.last_mut() {
+                transaction.edits.push(edit);
+            }
+            if self.saved_undo_depth == Some(self.undo_stack.len()) {
+                self.saved_undo_depth = None;
+            }
+        } else {
+            if self
+                .saved_undo_depth
+                .is_some_and(|depth| depth > self.undo_stack.len())
+            {
+                self.saved_undo_depth = None;
+            }
+            self.undo_stack.push(Transaction { edits: vec![edit] });
+        }
+        self.redo_stack.clear();
+        self.last_transaction_open = true;
+        cx.emit(HexBufferEvent::Edited);
+        cx.notify();
+    }
+
+    /// Prevents the next edit from being grouped with the previous ones.
+    pub fn finalize_last_transaction(&mut self) {
+        self.last_transaction_open = false;
+    }
+
+    /// Reverts the last transaction, returning the range of bytes it restored.
+    pub fn undo(&mut self, cx: &mut Context<Self>) -> Option<Range<usize>> {
+        let transaction = self.undo_stack.pop()?;
+        let mut restored = None;
+        for edit in transaction.edits.iter().rev() {
+            self.replace(
+                edit.offset..edit.offset + pieces_len(&edit.new),
+                edit.old.clone(),
+            );
+            restored = Some(edit.offset..edit.offset + pieces_len(&edit.old));
+        }
+        self.redo_stack.push(transaction);
+        self.last_transaction_open = false;
+        cx.emit(HexBufferEvent::Edited);
+        cx.notify();
+        restored
+    }
+
+    /// Reapplies the last undone transaction, returning the range of bytes
+    /// it wrote last.
+    pub fn redo(&mut self, cx: &mut Context<Self>) -> Option<Range<usize>> {
+        let transaction = self.redo_stack.pop()?;
+        let mut written = None;
+        for edit in &transaction.edits {
+            self.replace(
+                edit.offset..edit.offset + pieces_len(&edit.old),
+                edit.new.clone(),
+            );
+            written = Some(edit.offset..edit.offset + pieces_len(&edit.new));
+        }
+        self.undo_stack.push(transaction);
+        self.last_transaction_open = false;
+        cx.emit(HexBufferEvent::Edited);
+        cx.notify();
+        written
+    }
+
+    /// Finds the nearest match of `pattern` from `offset` on a background
+    /// thread, wrapping around the end or the start of the bytes.
+    pub fn find(
+        &self,
+        pattern: BytePattern,
+        offset: usize,
+        forward: bool,
+        cx: &App,
+    ) -> Task<Result<Option<Range<usize>>>> {
+        let snapshot = self.snapshot();
+        cx.background_spawn(async move {
+            let read = |range: Range<usize>| snapshot.read(range);
+            let found = if forward {
+                pattern.find_next_in(snapshot.len, offset, read)?
+            } else {
+                pattern.find_previous_in(snapshot.len, offset, read)?
+            };
+            Ok(found)
+        })
+    }
+
+    /// Writes the bytes to disk a page at a time, after which they are read
+    /// from the saved file.
+    pub fn save(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(abs_path) = self.abs_path.clone() else {
+            return Task::ready(Err(anyhow::anyhow!("buffer has no file to save to")));
+        };
+        let snapshot = self.snapshot();
+        let version = self.version;
+        let undo_depth = self.undo_stack.len();
+        cx.spawn(async move |this, cx| {
+            let source = cx
+                .background_spawn(async move {
+                    snapshot.write_to(&abs_path)?;
+                    SourceFile::open(0, &abs_path)
+                })
+                .await?;
+            this.update(cx, |this, cx| {
+                // Pieces of edits made while saving may still refer to the
+                // bytes of the previous file, which stay readable.
+                if this.version == version {
+                    this.set_source(source);
+                }
+                this.saved_undo_depth = Some(undo_depth);
+                this.last_transaction_open = false;
+                cx.emit(HexBufferEvent::Saved);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Discards the history and reads the bytes from disk again.
+    pub fn reload(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(abs_path) = self.abs_path.clone() else {
+            return Task::ready(Ok(()));
+        };
+        cx.spawn(async move |this, cx| {
+            let source = cx
+                .background_spawn(async move { SourceFile::open(0, &abs_path) })
+                .await?;
+            this.update(cx, |this, cx| {
+                this.set_source(source);
+                this.undo_stack.clear();
+                this.redo_stack.clear();
+                this.last_transaction_open = false;
+                this.saved_undo_depth = Some(0);
+                cx.emit(HexBufferEvent::Edited);
+                cx.notify();
+            })
+        })
+    }
+}
+
+impl project::ProjectItem for HexBuffer {
+    /// Files are only opened in the hex editor on request, so the hex buffer
+    /// never claims a path when the workspace opens it.
+    fn try_open(
+        _: &Entity<Project>,
+        _: &ProjectPath,
+        _: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        None
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        self.project_path.clone()
+    }
+
+    fn is_dirty(&self) -> bool {
+        HexBuffer::is_dirty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    fn all_bytes(buffer: &mut HexBuffer, cx: &mut Context<HexBuffer>) -> Vec<u8> {
+        buffer.bytes(0..buffer.len(), cx).unwrap()
+    }
+
+    #[gpui::test]
+    fn test_edit_undo_redo(cx: &mut TestAppContext) {
+        let buffer = cx.new(|_| HexBuffer::new(vec![0x00, 0x11, 0x22, 0x33]));
+
+        buffer.update(cx, |buffer, cx| {
+            // Both digits of a byte are a single undo step.
+            buffer.edit(1..2, &[0xa1], cx);
+            buffer.edit(1..2, &[0xab], cx);
+            buffer.finalize_last_transaction();
+            buffer.edit(4..4, &[0xff], cx);
+            assert_eq!(all_bytes(buffer, cx), [0x00, 0xab, 0x22, 0x33, 0xff]);
+            assert!(buffer.is_dirty());
+
+            assert_eq!(buffer.undo(cx), Some(4..4));
+            assert_eq!(all_bytes(buffer, cx), [0x00, 0xab, 0x22, 0x33]);
+            assert_eq!(buffer.undo(cx), Some(1..2));
+            assert_eq!(all_bytes(buffer, cx), [0x00, 0x11, 0x22, 0x33]);
+            assert!(!buffer.is_dirty());
+            assert_eq!(buffer.undo(cx), None);
+
+            assert_eq!(buffer.redo(cx), Some(1..2));
+            assert_eq!(all_bytes(buffer, cx), [0x00, 0xab, 0x22, 0x33]);
+
+            // Editing after undoing drops the redo history.
+            buffer.edit(0..1, &[], cx);
+            assert_eq!(all_bytes(buffer, cx), [0xab, 0x22, 0x33]);
+            assert_eq!(buffer.redo(cx), None);
+        });
+    }
+
+    #[gpui::test]
+    fn test_edits_split_and_merge_pieces(cx: &mut TestAppContext) {
+        let buffer = cx.new(|_| HexBuffer::new((0..=9).collect()));
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(3..5, &[0xaa, 0xbb, 0xcc], cx);
+            buffer.finalize_last_transaction();
+            buffer.edit(0..1, &[], cx);
+            buffer.finalize_last_transaction();
+            buffer.edit(10..10, &[0xee], cx);
+            assert_eq!(
+                all_bytes(buffer, cx),
+                [1, 2, 0xaa, 0xbb, 0xcc, 5, 6, 7, 8, 9, 0xee]
+            );
+            assert_eq!(buffer.len(), 11);
+            assert_eq!(buffer.bytes(4..7, cx), Some(vec![0xcc, 5, 6]));
+            assert_eq!(buffer.bytes(9..20, cx), Some(vec![9, 0xee]));
+            // Short runs of edited bytes are kept in a single piece.
+            assert_eq!(buffer.pieces.len(), 1);
+
+            while buffer.undo(cx).is_some() {}
+            assert_eq!(all_bytes(buffer, cx), (0..=9).collect::<Vec<u8>>());
+            while buffer.redo(cx).is_some() {}
+            assert_eq!(
+                all_bytes(buffer, cx),
+                [1, 2, 0xaa, 0xbb, 0xcc, 5, 6, 7, 8, 9, 0xee]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_find_wraps_around(cx: &mut TestAppContext) {
+        let buffer = cx.new(|_| HexBuffer::new(vec![0xde, 0xad, 0x00, 0xbe, 0xef, 0xde, 0xad]));
+        let pattern = BytePattern::parse("de ad").unwrap();
+
+        let find = buffer.read_with(cx, |buffer, cx| buffer.find(pattern.clone(), 1, true, cx));
+        assert_eq!(find.await.unwrap(), Some(5..7));
+        let find = buffer.read_with(cx, |buffer, cx| buffer.find(pattern.clone(), 6, true, cx));
+        assert_eq!(find.await.unwrap(), Some(0..2));
+        let find = buffer.read_with(cx, |buffer, cx| buffer.find(pattern, 0, false, cx));
+        assert_eq!(find.await.unwrap(), Some(5..7));
+    }
+
+    #[gpui::test]
+    fn test_dirty_after_undoing_past_save(cx: &mut TestAppContext) {
+        let buffer = cx.new(|_| HexBuffer::new(vec![0x00, 0x11]));
+
+        buffer.update(cx, |buffer, cx| {
+            buffer.edit(0..1, &[0x01], cx);
+            buffer.finalize_last_transaction();
+            buffer.saved_undo_depth = Some(buffer.undo_stack.len());
+            assert!(!buffer.is_dirty());
+
+            buffer.undo(cx);
+            assert!(buffer.is_dirty());
+            buffer.edit(1..2, &[0x02], cx);
+            assert!(buffer.is_dirty());
+            buffer.undo(cx);
+            assert!(buffer.is_dirty());
+        });
+    }
+}
@@ -0,0 +1,1008 @@
+mod byte_pattern;
+mod data_inspector;
+mod hex_buffer;
+
+use std::ops::Range;
+
+use editor::{Editor, actions::SelectAll, items::entry_git_aware_label_color};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, KeyDownEvent, MouseButton, ParentElement, Pixels, Render, ScrollStrategy,
+    SharedString, Subscription, Task, UniformListScrollHandle, Window, actions, uniform_list,
+};
+use project::Project;
+use settings::Settings;
+use theme_settings::ThemeSettings;
+use ui::{Tooltip, prelude::*};
+use util::{ResultExt as _, paths::PathExt};
+use workspace::{
+    DetachAndPromptErr, ItemSettings, Workspace,
+    invalid_item_view::InvalidItemView,
+    item::{Item, ItemEvent, SaveOptions, TabContentParams},
+};
+use zed_actions::hex_editor::OpenActiveFile;
+
+pub use crate::byte_pattern::BytePattern;
+pub use crate::data_inspector::{Endianness, InspectedValue, inspect};
+pub use crate::hex_buffer::{HexBuffer, HexBufferEvent};
+
+/// The number of bytes shown on each row.
+pub const BYTES_PER_ROW: usize = 16;
+/// How many bytes at the cursor the data inspector decodes.
+const INSPECTED_LEN: usize = 8;
+
+actions!(
+    hex_editor,
+    [
+        /// Moves the cursor to the previous byte.
+        MoveLeft,
+        /// Moves the cursor to the next byte.
+        MoveRight,
+        /// Moves the cursor to the previous row.
+        MoveUp,
+        /// Moves the cursor to the next row.
+        MoveDown,
+        /// Moves the cursor to the first byte of its row.
+        MoveToRowStart,
+        /// Moves the cursor to the last byte of its row.
+        MoveToRowEnd,
+        /// Moves the cursor to the first byte of the file.
+        MoveToStart,
+        /// Moves the cursor past the last byte of the file.
+        MoveToEnd,
+        /// Moves the cursor up by a page.
+        PageUp,
+        /// Moves the cursor down by a page.
+        PageDown,
+        /// Deletes the byte before the cursor in insert mode, or moves the
+        /// cursor back in overwrite mode.
+        Backspace,
+        /// Deletes the byte at the cursor.
+        Delete,
+        /// Undoes the last edit.
+        Undo,
+        /// Redoes the last undone edit.
+        Redo,
+        /// Switches between overwriting and inserting bytes.
+        ToggleInsertMode,
+        /// Moves the cursor between the hex and text columns.
+        SwitchColumn,
+        /// Switches the byte order used by the data inspector.
+        ToggleEndianness,
+        /// Prompts for an offset to move the cursor to.
+        GoToOffset,
+        /// Prompts for a byte pattern to search for.
+        FindBytes,
+        /// Moves to the next match of the last byte pattern.
+        FindNext,
+        /// Moves to the previous match of the last byte pattern.
+        FindPrevious,
+    ]
+);
+
+/// The column that typed characters are written to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ByteColumn {
+    Hex,
+    Text,
+}
+
+/// What the query bar is currently prompting for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum QueryMode {
+    GoToOffset,
+    FindBytes,
+}
+
+/// An editor that shows the bytes of a file as offset, hex and text columns.
+///
+/// Only the visible rows are laid out, and only their bytes are read from
+/// disk, so the view stays responsive for files of any size.
+pub struct HexEditor {
+    buffer: Entity<HexBuffer>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    /// The offset of the byte at the cursor, which may be one past the last
+    /// byte so that bytes can be appended.
+    cursor: usize,
+    /// Whether the next hex digit is written to the low half of the byte.
+    low_nibble: bool,
+    column: ByteColumn,
+    insert_mode: bool,
+    endianness: Endianness,
+    visible_row_count: usize,
+    query_editor: Entity<Editor>,
+    query_mode: Option<QueryMode>,
+    query_error: Option<SharedString>,
+    last_pattern: Option<BytePattern>,
+    highlighted_match: Option<Range<usize>>,
+    pending_search: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl HexEditor {
+    pub fn new(
+        buffer: Entity<HexBuffer>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let query_editor = cx.new(|cx| Editor::single_line(window, cx));
+        let subscriptions = vec![
+            cx.subscribe(&buffer, Self::handle_buffer_event),
+            cx.observe(&buffer, |_, _, cx| cx.notify()),
+        ];
+
+        Self {
+            buffer,
+            project,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            cursor: 0,
+            low_nibble: false,
+            column: ByteColumn::Hex,
+            insert_mode: false,
+            endianness: Endianness::default(),
+            visible_row_count: 0,
+            query_editor,
+            query_mode: None,
+            query_error: None,
+            last_pattern: None,
+            highlighted_match: None,
+            pending_search: Task::ready(()),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    pub fn buffer(&self) -> &Entity<HexBuffer> {
+        &self.buffer
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn handle_buffer_event(
+        &mut self,
+        _: Entity<HexBuffer>,
+        event: &HexBufferEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            HexBufferEvent::Edited => {
+                self.cursor = self.cursor.min(self.buffer.read(cx).len());
+                self.highlighted_match = None;
+                cx.emit(ItemEvent::Edit);
+                cx.emit(ItemEvent::UpdateTab);
+            }
+            HexBufferEvent::Saved => cx.emit(ItemEvent::UpdateTab),
+        }
+        cx.notify();
+    }
+
+    fn line_height(&self, window: &Window, cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        let font_size = settings.buffer_font_size(cx);
+        (font_size * settings.line_height())
+            .round()
+            .max(window.line_height())
+    }
+
+    /// Moves the cursor to `offset`, scrolling it into view.
+    pub fn move_cursor_to(&mut self, offset: usize, cx: &mut Context<Self>) {
+        self.cursor = offset.min(self.buffer.read(cx).len());
+        self.low_nibble = false;
+        self.buffer
+            .update(cx, |buffer, _| buffer.finalize_last_transaction());
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    fn move_left(&mut self, _: &MoveLeft, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor.saturating_sub(1), cx);
+    }
+
+    fn move_right(&mut self, _: &MoveRight, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + 1, cx);
+    }
+
+    fn move_up(&mut self, _: &MoveUp, _: &mut Window, cx: &mut Context<Self>) {
+        if self.cursor >= BYTES_PER_ROW {
+            self.move_cursor_to(self.cursor - BYTES_PER_ROW, cx);
+        }
+    }
+
+    fn move_down(&mut self, _: &MoveDown, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor + BYTES_PER_ROW, cx);
+    }
+
+    fn move_to_row_start(&mut self, _: &MoveToRowStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(self.cursor - self.cursor % BYTES_PER_ROW, cx);
+    }
+
+    fn move_to_row_end(&mut self, _: &MoveToRowEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(
+            self.cursor - self.cursor % BYTES_PER_ROW + BYTES_PER_ROW - 1,
+            cx,
+        );
+    }
+
+    fn move_to_start(&mut self, _: &MoveToStart, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(0, cx);
+    }
+
+    fn move_to_end(&mut self, _: &MoveToEnd, _: &mut Window, cx: &mut Context<Self>) {
+        self.move_cursor_to(usize::MAX, cx);
+    }
+
+    fn page_up(&mut self, _: &PageUp, _: &mut Window, cx: &mut Context<Self>) {
+        let page = self.visible_row_count.max(1) * BYTES_PER_ROW;
+        self.move_cursor_to(self.cursor.saturating_sub(page), cx);
+    }
+
+    fn page_down(&mut self, _: &PageDown, _: &mut Window, cx: &mut Context<Self>) {
+        let page = self.visible_row_count.max(1) * BYTES_PER_ROW;
+        self.move_cursor_to(self.cursor.saturating_add(page), cx);
+    }
+
+    fn backspace(&mut self, _: &Backspace, _: &mut Window, cx: &mut Context<Self>) {
+        if self.insert_mode && self.cursor > 0 {
+            let offset = self.cursor - 1;
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(offset..offset + 1, &[], cx));
+            self.move_cursor_to(offset, cx);
+        } else if self.low_nibble {
+            self.low_nibble = false;
+            cx.notify();
+        } else {
+            self.move_cursor_to(self.cursor.saturating_sub(1), cx);
+        }
+    }
+
+    fn delete(&mut self, _: &Delete, _: &mut Window, cx: &mut Context<Self>) {
+        let offset = self.cursor;
+        if offset < self.buffer.read(cx).len() {
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(offset..offset + 1, &[], cx));
+            self.move_cursor_to(offset, cx);
+        }
+    }
+
+    fn undo(&mut self, _: &Undo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.buffer.update(cx, |buffer, cx| buffer.undo(cx)) {
+            self.move_cursor_to(range.start, cx);
+        }
+    }
+
+    fn redo(&mut self, _: &Redo, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.buffer.update(cx, |buffer, cx| buffer.redo(cx)) {
+            self.move_cursor_to(range.start, cx);
+        }
+    }
+
+    fn toggle_insert_mode(&mut self, _: &ToggleInsertMode, _: &mut Window, cx: &mut Context<Self>) {
+        self.insert_mode = !self.insert_mode;
+        self.low_nibble = false;
+        cx.notify();
+    }
+
+    fn switch_column(&mut self, _: &SwitchColumn, _: &mut Window, cx: &mut Context<Self>) {
+        self.column = match self.column {
+            ByteColumn::Hex => ByteColumn::Text,
+            ByteColumn::Text => ByteColumn::Hex,
+        };
+        self.low_nibble = false;
+        cx.notify();
+    }
+
+    fn toggle_endianness(&mut self, _: &ToggleEndianness, _: &mut Window, cx: &mut Context<Self>) {
+        self.endianness = self.endianness.toggle();
+        cx.notify();
+    }
+
+    fn key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let modifiers = &event.keystroke.modifiers;
+        if modifiers.control || modifiers.alt || modifiers.platform || modifiers.function {
+            return;
+        }
+        let Some(key_char) = event.keystroke.key_char.as_deref() else {
+            return;
+        };
+        let mut chars = key_char.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return;
+        };
+
+        match self.column {
+            ByteColumn::Hex => {
+                let Some(digit) = c.to_digit(16) else {
+                    return;
+                };
+                self.type_hex_digit(digit as u8, cx);
+            }
+            ByteColumn::Text => {
+                if !c.is_ascii() || c.is_ascii_control() {
+                    return;
+                }
+                self.type_byte(c as u8, cx);
+            }
+        }
+        cx.stop_propagation();
+    }
+
+    /// Writes a hex digit to the high or low half of the byte at the cursor.
+    fn type_hex_digit(&mut self, digit: u8, cx: &mut Context<Self>) {
+        let offset = self.cursor;
+        let Some(existing) = self
+            .buffer
+            .update(cx, |buffer, cx| buffer.bytes(offset..offset + 1, cx))
+        else {
+            return;
+        };
+        let existing = existing.first().copied();
+        let low_nibble = self.low_nibble;
+        let insert = self.insert_mode || existing.is_none();
+        self.buffer
+            .update(cx, |buffer, cx| match (low_nibble, existing) {
+                (true, Some(byte)) => buffer.edit(offset..offset + 1, &[(byte & 0xf0) | digit], cx),
+                _ if insert => buffer.edit(offset..offset, &[digit << 4], cx),
+                (_, byte) => buffer.edit(
+                    offset..offset + 1,
+                    &[(digit << 4) | (byte.unwrap_or_default() & 0x0f)],
+                    cx,
+                ),
+            });
+
+        if low_nibble {
+            self.cursor = offset + 1;
+            self.low_nibble = false;
+        } else {
+            self.cursor = offset;
+            self.low_nibble = true;
+        }
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    /// Writes a byte at the cursor, typed into the text column.
+    fn type_byte(&mut self, byte: u8, cx: &mut Context<Self>) {
+        let offset = self.cursor;
+        let end = if self.insert_mode {
+            offset
+        } else {
+            (offset + 1).min(self.buffer.read(cx).len())
+        };
+        self.buffer
+            .update(cx, |buffer, cx| buffer.edit(offset..end, &[byte], cx));
+        self.cursor = offset + 1;
+        self.low_nibble = false;
+        self.scroll_handle
+            .scroll_to_item(self.cursor / BYTES_PER_ROW, ScrollStrategy::Nearest);
+        cx.notify();
+    }
+
+    fn handle_byte_click(
+        &mut self,
+        offset: usize,
+        column: ByteColumn,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.column = column;
+        self.move_cursor_to(offset, cx);
+        self.focus_handle.focus(window, cx);
+    }
+
+    fn go_to_offset(&mut self, _: &GoToOffset, window: &mut Window, cx: &mut Context<Self>) {
+        self.open_query_bar(QueryMode::GoToOffset, "Go to offset, in hex", window, cx);
+    }
+
+    fn find_bytes(&mut self, _: &FindBytes, window: &mut Window, cx: &mut Context<Self>) {
+        self.open_query_bar(
+            QueryMode::FindBytes,
+            "Find bytes, as in 7f 45 4c 46, de ?? be ef or \"PK\"",
+            window,
+            cx,
+        );
+    }
+
+    fn open_query_bar(
+        &mut self,
+        mode: QueryMode,
+        placeholder: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.query_mode = Some(mode);
+        self.query_error = None;
+        self.query_editor.update(cx, |editor, cx| {
+            editor.set_placeholder_text(placeholder, window, cx);
+            editor.select_all(&SelectAll, window, cx);
+        });
+        self.query_editor.focus_handle(cx).focus(window, cx);
+        cx.notify();
+    }
+
+    fn confirm_query(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(mode) = self.query_mode else {
+            return;
+        };
+        let query = self.query_editor.read(cx).text(cx);
+        let result = match mode {
+            QueryMode::GoToOffset => parse_offset(&query)
+                .map(|offset| self.move_cursor_to(offset, cx))
+                .ok_or_else(|| SharedString::from(format!("Invalid offset: {}", query.trim()))),
+            QueryMode::FindBytes => match BytePattern::parse(&query) {
+                Ok(pattern) => {
+                    self.last_pattern = Some(pattern.clone());
+                    self.find(pattern, self.cursor, true, window, cx);
+                    return;
+                }
+                Err(error) => Err(error.to_string().into()),
+            },
+        };
+
+        match result {
+            Ok(()) => self.cancel_query(&menu::Cancel, window, cx),
+            Err(error) => {
+                self.query_error = Some(error);
+                cx.notify();
+            }
+        }
+    }
+
+    fn cancel_query(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        self.query_mode = None;
+        self.query_error = None;
+        self.focus_handle.focus(window, cx);
+        cx.notify();
+    }
+
+    fn find_next(&mut self, _: &FindNext, window: &mut Window, cx: &mut Context<Self>) {
+        match self.last_pattern.clone() {
+            Some(pattern) => self.find(pattern, self.cursor + 1, true, window, cx),
+            None => self.find_bytes(&FindBytes, window, cx),
+        }
+    }
+
+    fn find_previous(&mut self, _: &FindPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        match self.last_pattern.clone() {
+            Some(pattern) => self.find(pattern, self.cursor, false, window, cx),
+            None => self.find_bytes(&FindBytes, window, cx),
+        }
+    }
+
+    /// Searches for the nearest match of `pattern` from `offset` in the
+    /// background, then moves the cursor to it and highlights it. The query
+    /// bar is closed once a match is found, or shows that there is none.
+    fn find(
+        &mut self,
+        pattern: BytePattern,
+        offset: usize,
+        forward: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let search = self.buffer.read(cx).find(pattern, offset, forward, cx);
+        self.pending_search = cx.spawn_in(window, async move |this, cx| {
+            let found = search.await.log_err().flatten();
+            this.update_in(cx, |this, window, cx| match found {
+                Some(range) => {
+                    this.move_cursor_to(range.start, cx);
+                    this.highlighted_match = Some(range);
+                    if this.query_mode == Some(QueryMode::FindBytes) {
+                        this.cancel_query(&menu::Cancel, window, cx);
+                    }
+                }
+                None => {
+                    this.highlighted_match = None;
+                    if this.query_mode == Some(QueryMode::FindBytes) {
+                        this.query_error = Some("No matches".into());
+                    }
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+    }
+
+    fn offset_width(&self, cx: &App) -> usize {
+        let len = self.buffer.read(cx).len();
+        let digits = (usize::BITS - len.leading_zeros()).div_ceil(4) as usize;
+        digits.max(8)
+    }
+
+    fn render_status(&self, cx: &Context<Self>) -> impl IntoElement {
+        let buffer = self.buffer.read(cx);
+        let size = util::size::format_file_size(buffer.len() as u64, false);
+        let mode = if self.insert_mode {
+            "Insert"
+        } else {
+            "Overwrite"
+        };
+        let offset_width = self.offset_width(cx);
+
+        h_flex()
+            .flex_none()
+            .px_2()
+            .py_1()
+            .gap_3()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(format!(
+                    "Offset {:0offset_width$X} ({}) · {size}",
+                    self.cursor, self.cursor
+                ))
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+            )
+            .child(div().flex_1())
+            .child(
+                Button::new("toggle-insert-mode", mode)
+                    .label_size(LabelSize::Small)
+                    .tooltip(Tooltip::for_action_title(
+                        "Toggle Insert Mode",
+                        &ToggleInsertMode,
+                    ))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_insert_mode(&ToggleInsertMode, window, cx)
+                    })),
+            )
+    }
+
+    fn render_query_bar(&self, mode: QueryMode, cx: &Context<Self>) -> impl IntoElement {
+        let label = match mode {
+            QueryMode::GoToOffset => "Go to Offset",
+            QueryMode::FindBytes => "Find Bytes",
+        };
+
+        h_flex()
+            .key_context("HexEditorQuery")
+            .on_action(cx.listener(Self::confirm_query))
+            .on_action(cx.listener(Self::cancel_query))
+            .flex_none()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(label).size(LabelSize::Small))
+            .child(
+                div()
+                    .flex_1()
+                    .px_1()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(self.query_editor.clone()),
+            )
+            .when_some(self.query_error.clone(), |this, error| {
+                this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            })
+    }
+
+    fn render_rows(
+        &mut self,
+        rows: Range<usize>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        let colors = cx.theme().colors();
+        let cursor_background = cx.theme().players().local().selection;
+        let offset_width = self.offset_width(cx);
+        let buffer_len = self.buffer.read(cx).len();
+        let rows_range = rows.start * BYTES_PER_ROW..rows.end * BYTES_PER_ROW;
+        // While the bytes are read from disk, their cells show placeholders.
+        let bytes = self
+            .buffer
+            .update(cx, |buffer, cx| buffer.bytes(rows_range.clone(), cx));
+        let is_cursor =
+            |offset: usize, column: ByteColumn| offset == self.cursor && column == self.column;
+        let cell_background = |offset: usize, column: ByteColumn| {
+            if is_cursor(offset, column) {
+                Some(cursor_background)
+            } else if offset == self.cursor {
+                Some(colors.editor_active_line_background)
+            } else if self
+                .highlighted_match
+                .as_ref()
+                .is_some_and(|range| range.contains(&offset))
+            {
+                Some(colors.search_match_background)
+            } else {
+                None
+            }
+        };
+
+        rows.map(|row| {
+            let row_start = row * BYTES_PER_ROW;
+            let row_bytes = bytes.as_ref().map(|bytes| {
+                let start = (row_start - rows_range.start).min(bytes.len());
+                &bytes[start..(start + BYTES_PER_ROW).min(bytes.len())]
+            });
+            let row_len = (row_start + BYTES_PER_ROW)
+                .min(buffer_len)
+                .saturating_sub(row_start);
+
+            let hex_cells = (0..BYTES_PER_ROW).map(|ix| {
+                let offset = row_start + ix;
+                let text = match row_bytes {
+                    Some(row_bytes) => row_bytes
+                        .get(ix)
+                        .map_or_else(|| "  ".to_string(), |byte| format!("{byte:02X}")),
+                    None if ix < row_len => "··".to_string(),
+                    None => "  ".to_string(),
+                };
+                div()
+                    .id(("hex", offset))
+                    .when(ix == BYTES_PER_ROW / 2, |this| this.ml_2())
+                    .px_0p5()
+                    .when_some(cell_background(offset, ByteColumn::Hex), |this, color| {
+                        this.bg(color)
+                    })
+                    .when(
+                        is_cursor(offset, ByteColumn::Hex) && self.low_nibble,
+                        |this| this.underline(),
+                    )
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, window, cx| {
+                            this.handle_byte_click(offset, ByteColumn::Hex, window, cx)
+                        }),
+                    )
+                    .child(text)
+            });
+            let text_cells = (0..BYTES_PER_ROW).map(|ix| {
+                let offset = row_start + ix;
+                let text = match row_bytes {
+                    Some(row_bytes) => row_bytes.get(ix).map_or(' ', |&byte| {
+                        if byte.is_ascii_graphic() || byte == b' ' {
+                            byte as char
+                        } else {
+                            '.'
+                        }
+                    }),
+                    None if ix < row_len => '·',
+                    None => ' ',
+                };
+                div()
+                    .id(("text", offset))
+                    .when_some(cell_background(offset, ByteColumn::Text), |this, color| {
+                        this.bg(color)
+                    })
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, window, cx| {
+                            this.handle_byte_click(offset, ByteColumn::Text, window, cx)
+                        }),
+                    )
+                    .child(text.to_string())
+            });
+
+            h_flex()
+                .px_2()
+                .gap_4()
+                .child(
+                    div()
+                        .text_color(colors.editor_line_number)
+                        .child(format!("{row_start:0offset_width$X}")),
+                )
+                .child(h_flex().children(hex_cells))
+                .child(h_flex().children(text_cells))
+                .into_any_element()
+        })
+        .collect()
+    }
+
+    fn render_inspector(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let cursor = self.cursor;
+        let inspected = self
+            .buffer
+            .update(cx, |buffer, cx| {
+                buffer.bytes(cursor..cursor + INSPECTED_LEN, cx)
+            })
+            .unwrap_or_default();
+
+        v_flex()
+            .flex_none()
+            .w(rems(18.))
+            .p_2()
+            .gap_1()
+            .border_l_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(Label::new("Data Inspector").size(LabelSize::Small))
+                    .child(
+                        Button::new("toggle-endianness", self.endianness.label())
+                            .label_size(LabelSize::Small)
+                            .tooltip(Tooltip::for_action_title(
+                                "Toggle Endianness",
+                                &ToggleEndianness,
+                            ))
+                            .on_click(cx.listener(|this, _, window, cx| {
+                                this.toggle_endianness(&ToggleEndianness, window, cx)
+                            })),
+                    ),
+            )
+            .children(
+                inspect(&inspected, self.endianness)
+                    .into_iter()
+                    .map(|value| {
+                        h_flex()
+                            .gap_2()
+                            .justify_between()
+                            .child(
+                                Label::new(value.label)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                            .child(
+                                Label::new(value.value.unwrap_or_else(|| "—".to_string()))
+                                    .size(LabelSize::Small)
+                                    .buffer_font(cx),
+                            )
+                    }),
+            )
+    }
+}
+
+/// Parses an offset typed into the query bar, in hex with an optional `0x`
+/// prefix to match the offset column.
+fn parse_offset(query: &str) -> Option<usize> {
+    let query = query.trim();
+    let digits = query
+        .strip_prefix("0x")
+        .or_else(|| query.strip_prefix("0X"))
+        .unwrap_or(query);
+    usize::from_str_radix(digits, 16).ok()
+}
+
+impl EventEmitter<ItemEvent> for HexEditor {}
+
+impl Focusable for HexEditor {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for HexEditor {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let line_height = self.line_height(window, cx);
+        self.visible_row_count = (window.viewport_size().height / line_height).floor() as usize;
+        let row_count = self.buffer.read(cx).len() / BYTES_PER_ROW + 1;
+
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_status(cx))
+            .when_some(self.query_mode, |this, mode| {
+                this.child(self.render_query_bar(mode, cx))
+            })
+            .child(
+                h_flex()
+                    .flex_1()
+                    .min_h_0()
+                    .child(
+                        div()
+                            .id("hex-editor-bytes")
+                            .key_context("HexEditor")
+                            .track_focus(&self.focus_handle)
+                            .on_action(cx.listener(Self::move_left))
+                            .on_action(cx.listener(Self::move_right))
+                            .on_action(cx.listener(Self::move_up))
+                            .on_action(cx.listener(Self::move_down))
+                            .on_action(cx.listener(Self::move_to_row_start))
+                            .on_action(cx.listener(Self::move_to_row_end))
+                            .on_action(cx.listener(Self::move_to_start))
+                            .on_action(cx.listener(Self::move_to_end))
+                            .on_action(cx.listener(Self::page_up))
+                            .on_action(cx.listener(Self::page_down))
+                            .on_action(cx.listener(Self::backspace))
+                            .on_action(cx.listener(Self::delete))
+                            .on_action(cx.listener(Self::undo))
+                            .on_action(cx.listener(Self::redo))
+                            .on_action(cx.listener(Self::toggle_insert_mode))
+                            .on_action(cx.listener(Self::switch_column))
+                            .on_action(cx.listener(Self::toggle_endianness))
+                            .on_action(cx.listener(Self::go_to_offset))
+                            .on_action(cx.listener(Self::find_bytes))
+                            .on_action(cx.listener(Self::find_next))
+                            .on_action(cx.listener(Self::find_previous))
+                            .on_key_down(cx.listener(Self::key_down))
+                            .flex_1()
+                            .h_full()
+                            .font(font)
+                            .text_size(font_size)
+                            .line_height(line_height)
+                            .text_color(cx.theme().colors().editor_foreground)
+                            .child(
+                                uniform_list(
+                                    "hex-editor-rows",
+                                    row_count,
+                                    cx.processor(Self::render_rows),
+                                )
+                                .size_full()
+                                .track_scroll(&self.scroll_handle),
+                            ),
+                    )
+                    .child(self.render_inspector(cx)),
+            )
+    }
+}
+
+impl Item for HexEditor {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.buffer.entity_id(), self.buffer.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.buffer.read(cx).abs_path()?;
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        let project_path = project::ProjectItem::project_path(self.buffer.read(cx), cx);
+
+        let label_color = if let Some(project_path) = project_path
+            && ItemSettings::get_global(cx).git_status
+        {
+            let git_status = self
+                .project
+                .read(cx)
+                .git_store()
+                .read(cx)
+                .display_status_for_project_path(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.buffer
+            .read(cx)
+            .abs_path()
+            .and_then(|abs_path| abs_path.file_name())
+            .map(|file_name| file_name.to_string_lossy().into_owned().into())
+            .unwrap_or_else(|| "untitled".into())
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.buffer.read(cx).abs_path()?;
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        self.buffer.read(cx).is_dirty()
+    }
+
+    fn can_save(&self, cx: &App) -> bool {
+        self.buffer.read(cx).abs_path().is_some()
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.buffer.update(cx, |buffer, cx| buffer.save(cx))
+    }
+
+    fn reload(
+        &mut self,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<anyhow::Result<()>> {
+        self.buffer.update(cx, |buffer, cx| buffer.reload(cx))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenActiveFile, window, cx| {
+            open_active_file(workspace, window, cx);
+        });
+    })
+    .detach();
+}
+
+/// Opens the file of the active item in a hex editor, which also works for
+/// files that failed to open as text.
+fn open_active_file(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let project = workspace.project().clone();
+    let Some(item) = workspace.active_item(cx) else {
+        return;
+    };
+    let project_path = item.project_path(cx).or_else(|| {
+        let invalid_item = item.downcast::<InvalidItemView>()?;
+        let abs_path = invalid_item.read(cx).abs_path.clone();
+        project.read(cx).find_project_path(&abs_path, cx)
+    });
+    let Some(project_path) = project_path else {
+        return;
+    };
+
+    let existing = workspace.items_of_type::<HexEditor>(cx).find(|editor| {
+        project::ProjectItem::project_path(editor.read(cx).buffer.read(cx), cx).as_ref()
+            == Some(&project_path)
+    });
+    if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let open = HexBuffer::open(project.clone(), project_path, cx);
+    cx.spawn_in(window, async move |workspace, cx| {
+        let buffer = open.await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            let editor = cx.new(|cx| HexEditor::new(buffer, project, window, cx));
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+        })
+    })
+    .detach_and_prompt_err(
+        "Failed to open file in hex editor",
+        window,
+        cx,
+        |_, _, _| None,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("1f"), Some(0x1f));
+        assert_eq!(parse_offset(" 0x10 "), Some(0x10));
+        assert_eq!(parse_offset("0XFF"), Some(0xff));
+        assert_eq!(parse_offset("zz"), None);
+        assert_eq!(parse_offset(""), None);
+    }
+}
@@ -0,0 +1,644 @@
+mod keyboard_macros_settings;
+mod save_macro_modal;
+
+use std::{cell::Cell, collections::BTreeSet, rc::Rc, sync::Arc};
+
+use anyhow::{Context as _, Result, anyhow};
+use editor::{Anchor, Editor, SelectionEffects};
+use gpui::{
+    Action, App, AsyncWindowContext, Context, Entity, Focusable as _, Global, Keystroke,
+    KeystrokeEvent, Window, actions,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use settings::{KeyboardMacroStep, Settings as _};
+use ui::SharedString;
+use workspace::{Toast, Workspace, notifications::NotificationId};
+
+pub use crate::keyboard_macros_settings::*;
+use crate::save_macro_modal::SaveMacroModal;
+
+actions!(
+    keyboard_macros,
+    [
+        /// Starts recording a keyboard macro. Only keyboard input is
+        /// recorded, not actions run from the command palette or the mouse.
+        StartRecording,
+        /// Stops recording the current keyboard macro.
+        StopRecording,
+        /// Starts recording a keyboard macro, or stops the current recording.
+        ToggleRecording,
+        /// Saves the last recorded keyboard macro to the `keyboard_macros` setting.
+        SaveLastMacro,
+    ]
+);
+
+/// Plays back a keyboard macro.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, JsonSchema, Action)]
+#[action(namespace = keyboard_macros)]
+#[serde(deny_unknown_fields)]
+pub struct Play {
+    /// The name of a macro from the `keyboard_macros` setting. When omitted,
+    /// the last recorded macro is played.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// How many times to play the macro.
+    #[serde(default)]
+    pub count: Option<usize>,
+}
+
+/// Plays back a keyboard macro once for every line touched by the
+/// selections in the active editor, starting at the beginning of the line.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, JsonSchema, Action)]
+#[action(namespace = keyboard_macros)]
+#[serde(deny_unknown_fields)]
+pub struct PlayOnEachLine {
+    /// The name of a macro from the `keyboard_macros` setting. When omitted,
+    /// the last recorded macro is played.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Plays back a keyboard macro once for every selection in the active
+/// editor, with only that selection active.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, JsonSchema, Action)]
+#[action(namespace = keyboard_macros)]
+#[serde(deny_unknown_fields)]
+pub struct PlayOnEachSelection {
+    /// The name of a macro from the `keyboard_macros` setting. When omitted,
+    /// the last recorded macro is played.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// Playback stops after this many steps, in case a macro never terminates.
+const MAX_PLAYBACK_STEPS: usize = 10_000;
+
+/// A recorded unit of input.
+///
+/// Keystrokes that resolve to an action are recorded as that action, so
+/// that multi-keystroke bindings replay correctly. Everything else, most
+/// notably text input, is recorded as the keystroke itself.
+#[derive(Debug)]
+pub enum MacroStep {
+    Action(Box<dyn Action>),
+    Keystroke(Keystroke),
+}
+
+impl Clone for MacroStep {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Action(action) => Self::Action(action.boxed_clone()),
+            Self::Keystroke(keystroke) => Self::Keystroke(keystroke.clone()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct KeyboardMacros {
+    recording: Option<Vec<MacroStep>>,
+    last_macro: Option<Arc<[MacroStep]>>,
+    is_playing: Rc<Cell<bool>>,
+}
+
+impl Global for KeyboardMacros {}
+
+/// Clears the playing flag when playback finishes or is dropped.
+struct PlaybackGuard(Rc<Cell<bool>>);
+
+impl Drop for PlaybackGuard {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl KeyboardMacros {
+    /// Records a keystroke and the action it resolved to, if any.
+    ///
+    /// Macros are recorded from keystrokes because that's the only input
+    /// gpui reports globally. Actions dispatched directly, such as from the
+    /// command palette, menus or mouse handlers, never reach this and are
+    /// left out of the recording.
+    fn observe_keystroke(&mut self, event: &KeystrokeEvent) {
+        if self.is_playing.get() {
+            return;
+        }
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+        match &event.action {
+            Some(action) if is_macro_action(action.as_ref()) => {}
+            Some(action) => recording.push(MacroStep::Action(action.boxed_clone())),
+            None => recording.push(MacroStep::Keystroke(event.keystroke.clone())),
+        }
+    }
+
+    fn start_playback(&self) -> Option<PlaybackGuard> {
+        if self.is_playing.replace(true) {
+            return None;
+        }
+        Some(PlaybackGuard(self.is_playing.clone()))
+    }
+}
+
+fn is_macro_action(action: &dyn Action) -> bool {
+    action.name().starts_with("keyboard_macros::")
+}
+
+pub fn init(cx: &mut App) {
+    cx.set_global(KeyboardMacros::default());
+
+    cx.observe_keystrokes(|event, _, cx| {
+        cx.global_mut::<KeyboardMacros>().observe_keystroke(event);
+    })
+    .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace
+            .register_action(|workspace, _: &StartRecording, _, cx| {
+                start_recording(workspace, cx);
+            })
+            .register_action(|workspace, _: &StopRecording, _, cx| {
+                stop_recording(workspace, cx);
+            })
+            .register_action(|workspace, _: &ToggleRecording, _, cx| {
+                if is_recording(cx) {
+                    stop_recording(workspace, cx);
+                } else {
+                    start_recording(workspace, cx);
+                }
+            })
+            .register_action(|workspace, action: &Play, window, cx| {
+                play(workspace, action, window, cx);
+            })
+            .register_action(|workspace, action: &PlayOnEachLine, window, cx| {
+                play_on_each(workspace, action.name.as_deref(), Target::Lines, window, cx);
+            })
+            .register_action(|workspace, action: &PlayOnEachSelection, window, cx| {
+                play_on_each(
+                    workspace,
+                    action.name.as_deref(),
+                    Target::Selections,
+                    window,
+                    cx,
+                );
+            })
+            .register_action(|workspace, _: &SaveLastMacro, window, cx| {
+                save_last_macro(workspace, window, cx);
+            });
+    })
+    .detach();
+}
+
+pub fn is_recording(cx: &App) -> bool {
+    cx.global::<KeyboardMacros>().recording.is_some()
+}
+
+struct RecordingToast;
+
+fn show_toast(
+    workspace: &mut Workspace,
+    message: impl Into<SharedString>,
+    cx: &mut Context<Workspace>,
+) {
+    let message = message.into();
+    workspace.dismiss_toast(&NotificationId::unique::<RecordingToast>(), cx);
+    workspace.show_toast(
+        Toast::new(NotificationId::unique::<KeyboardMacros>(), message).autohide(),
+        cx,
+    );
+}
+
+fn start_recording(workspace: &mut Workspace, cx: &mut Context<Workspace>) {
+    let macros = cx.global_mut::<KeyboardMacros>();
+    if macros.is_playing.get() {
+        return;
+    }
+    macros.recording = Some(Vec::new());
+    workspace.show_toast(
+        Toast::new(
+            NotificationId::unique::<RecordingToast>(),
+            "Recording keyboard macro…",
+        ),
+        cx,
+    );
+}
+
+fn stop_recording(workspace: &mut Workspace, cx: &mut Context<Workspace>) {
+    let macros = cx.global_mut::<KeyboardMacros>();
+    let Some(recording) = macros.recording.take() else {
+        return;
+    };
+    let message = if recording.is_empty() {
+        "Keyboard macro is empty".to_string()
+    } else {
+        let step_count = recording.len();
+        macros.last_macro = Some(recording.into());
+        format!(
+            "Recorded keyboard macro with {step_count} step{}",
+            if step_count == 1 { "" } else { "s" }
+        )
+    };
+    show_toast(workspace, message, cx);
+}
+
+/// Returns the steps of the named macro, or of the last recorded one.
+fn resolve_macro(name: Option<&str>, cx: &App) -> Result<Arc<[MacroStep]>> {
+    let Some(name) = name else {
+        return cx
+            .global::<KeyboardMacros>()
+            .last_macro
+            .clone()
+            .context("No keyboard macro has been recorded");
+    };
+
+    let steps = KeyboardMacroSettings::get_global(cx)
+        .macros
+        .get(name)
+        .with_context(|| format!("No keyboard macro named {name:?}"))?;
+    steps
+        .iter()
+        .map(|step| match step {
+            KeyboardMacroStep::Action(action_name) => cx
+                .build_action(action_name, None)
+                .map(MacroStep::Action)
+                .map_err(|error| anyhow!("Invalid action in keyboard macro {name:?}: {error}")),
+            KeyboardMacroStep::Keystroke(keystroke) => Keystroke::parse(keystroke)
+                .map(|keystroke| {
+                    MacroStep::Keystroke(
+                        cx.keyboard_mapper()
+                            .map_key_equivalent(keystroke, false)
+                            .inner()
+                            .clone(),
+                    )
+                })
+                .map_err(|error| anyhow!("Invalid keystroke in keyboard macro {name:?}: {error}")),
+        })
+        .collect()
+}
+
+/// Dispatches the given steps one at a time to the focused element.
+async fn run_steps(steps: &[MacroStep], cx: &mut AsyncWindowContext) -> Result<()> {
+    for step in steps {
+        let focused = cx.update(|window, cx| {
+            let focused = window.focused(cx);
+            match step {
+                MacroStep::Action(action) if is_macro_action(action.as_ref()) => {}
+                MacroStep::Action(action) => window.dispatch_action(action.boxed_clone(), cx),
+                MacroStep::Keystroke(keystroke) => {
+                    window.dispatch_keystroke(keystroke.clone(), cx);
+                }
+            }
+            focused
+        })?;
+        // Give focus changes a chance to settle before the next step, so that
+        // keystrokes reach the newly focused element.
+        cx.update(|window, cx| {
+            if window.focused(cx) != focused {
+                window.draw(cx).clear(cx);
+            }
+        })?;
+        futures_lite::future::yield_now().await;
+    }
+    Ok(())
+}
+
+fn play(
+    workspace: &mut Workspace,
+    action: &Play,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let count = action.count.unwrap_or(1).max(1);
+    let steps = match resolve_macro(action.name.as_deref(), cx) {
+        Ok(steps) => steps,
+        Err(error) => {
+            show_toast(workspace, error.to_string(), cx);
+            return;
+        }
+    };
+    if is_recording(cx) && action.name.is_none() {
+        show_toast(
+            workspace,
+            "Can't replay a keyboard macro while recording it",
+            cx,
+        );
+        return;
+    }
+    if steps.len().saturating_mul(count) > MAX_PLAYBACK_STEPS {
+        show_toast(
+            workspace,
+            format!("Keyboard macros are limited to {MAX_PLAYBACK_STEPS} steps per playback"),
+            cx,
+        );
+        return;
+    }
+    let Some(guard) = cx.global::<KeyboardMacros>().start_playback() else {
+        return;
+    };
+
+    cx.spawn_in(window, async move |_, cx| {
+        let _guard = guard;
+        for _ in 0..count {
+            run_steps(&steps, cx).await?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+#[derive(Clone, Copy)]
+enum Target {
+    Lines,
+    Selections,
+}
+
+fn play_on_each(
+    workspace: &mut Workspace,
+    name: Option<&str>,
+    target: Target,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let steps = match resolve_macro(name, cx) {
+        Ok(steps) => steps,
+        Err(error) => {
+            show_toast(workspace, error.to_string(), cx);
+            return;
+        }
+    };
+    let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+        show_toast(workspace, "Open an editor to play a keyboard macro on", cx);
+        return;
+    };
+    let ranges = target_ranges(&editor, target, cx);
+    if steps.len().saturating_mul(ranges.len()) > MAX_PLAYBACK_STEPS {
+        show_toast(
+            workspace,
+            format!("Keyboard macros are limited to {MAX_PLAYBACK_STEPS} steps per playback"),
+            cx,
+        );
+        return;
+    }
+    let Some(guard) = cx.global::<KeyboardMacros>().start_playback() else {
+        return;
+    };
+
+    cx.spawn_in(window, async move |_, cx| {
+        let _guard = guard;
+        for range in ranges {
+            cx.update(|window, cx| {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+                        s.select_anchor_ranges([range])
+                    });
+                });
+                window.focus(&editor.focus_handle(cx), cx);
+            })?;
+            run_steps(&steps, cx).await?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Returns the ranges to select before each playback. Anchors are used so
+/// that edits made by one playback don't shift the ranges of the next.
+fn target_ranges(
+    editor: &Entity<Editor>,
+    target: Target,
+    cx: &mut App,
+) -> Vec<std::ops::Range<Anchor>> {
+    editor.update(cx, |editor, cx| {
+        let display_snapshot = editor.display_snapshot(cx);
+        let snapshot = display_snapshot.buffer_snapshot();
+        let selections = editor.selections.all::<text::Point>(&display_snapshot);
+        match target {
+            Target::Lines => {
+                let rows = selections
+                    .iter()
+                    .flat_map(|selection| {
+                        let start = selection.start.row;
+                        let end = if selection.end.row > start && selection.end.column == 0 {
+                            selection.end.row - 1
+                        } else {
+                            selection.end.row
+                        };
+                        start..=end
+                    })
+                    .collect::<BTreeSet<_>>();
+                rows.into_iter()
+                    .map(|row| {
+                        let anchor = snapshot.anchor_before(text::Point::new(row, 0));
+                        anchor..anchor
+                    })
+                    .collect()
+            }
+            Target::Selections => selections
+                .iter()
+                .map(|selection| {
+                    snapshot.anchor_before(selection.start)..snapshot.anchor_after(selection.end)
+                })
+                .collect(),
+        }
+    })
+}
+
+fn save_last_macro(workspace: &mut Workspace, window: &mut Window, cx: &mut Context<Workspace>) {
+    let Some(steps) = cx.global::<KeyboardMacros>().last_macro.clone() else {
+        show_toast(workspace, "No keyboard macro has been recorded", cx);
+        return;
+    };
+    let (steps, skipped_step_count) = steps_for_settings(&steps, window, cx);
+    let fs = workspace.app_state().fs.clone();
+    workspace.toggle_modal(window, cx, move |window, cx| {
+        SaveMacroModal::new(steps, skipped_step_count, fs, window, cx)
+    });
+}
+
+/// Converts recorded steps into their settings representation.
+///
+/// Actions that can't be rebuilt from their name alone are saved as the
+/// keystrokes bound to them, and skipped if they have no binding.
+fn steps_for_settings(
+    steps: &[MacroStep],
+    window: &Window,
+    cx: &App,
+) -> (Vec<KeyboardMacroStep>, usize) {
+    let mut saved_steps = Vec::new();
+    let mut skipped_step_count = 0;
+    for step in steps {
+        match step {
+            MacroStep::Action(action) => {
+                let rebuilds_from_name = cx
+                    .build_action(action.name(), None)
+                    .is_ok_and(|built| built.partial_eq(action.as_ref()));
+                if rebuilds_from_name {
+                    saved_steps.push(KeyboardMacroStep::Action(action.name().to_string()));
+                } else if let Some(binding) =
+                    window.highest_precedence_binding_for_action(action.as_ref())
+                {
+                    saved_steps.extend(
+                        binding
+                            .keystrokes()
+                            .iter()
+                            .map(|keystroke| KeyboardMacroStep::Keystroke(keystroke.unparse())),
+                    );
+                } else {
+                    log::warn!("skipping {action:?} when saving keyboard macro");
+                    skipped_step_count += 1;
+                }
+            }
+            MacroStep::Keystroke(keystroke) => {
+                saved_steps.push(KeyboardMacroStep::Keystroke(keystroke.unparse()));
+            }
+        }
+    }
+    (saved_steps, skipped_step_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collections::HashMap;
+    use editor::actions::Newline;
+    use gpui::{KeyBinding, TestAppContext, VisualTestContext};
+    use project::{FakeFs, Project};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::{path, rel_path::rel_path};
+    use workspace::{AppState, MultiWorkspace};
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+            crate::init(cx);
+            cx.bind_keys([KeyBinding::new("enter", Newline, Some("Editor"))]);
+        });
+    }
+
+    async fn open_editor(
+        text: &str,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Editor>, &mut VisualTestContext) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({ "a.txt": text }))
+            .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let (multi_workspace, cx) =
+            cx.add_window_view(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = multi_workspace.read_with(cx, |mw, _| mw.workspace().clone());
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let editor = workspace
+            .update_in(cx, |workspace, window, cx| {
+                workspace.open_path((worktree_id, rel_path("a.txt")), None, true, window, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        (editor, cx)
+    }
+
+    #[gpui::test]
+    async fn test_record_and_play(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (editor, cx) = open_editor("", cx).await;
+
+        cx.dispatch_action(StartRecording);
+        cx.simulate_keystrokes("a b enter");
+        cx.dispatch_action(StopRecording);
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "ab\n");
+
+        cx.dispatch_action(Play {
+            name: None,
+            count: Some(2),
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            editor.read_with(cx, |editor, cx| editor.text(cx)),
+            "ab\nab\nab\n"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_actions_without_keystrokes_are_not_recorded(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (editor, cx) = open_editor("", cx).await;
+
+        cx.dispatch_action(StartRecording);
+        cx.simulate_keystrokes("a");
+        // Dispatched the way the command palette and mouse handlers do it.
+        cx.dispatch_action(Newline);
+        cx.simulate_keystrokes("b");
+        cx.dispatch_action(StopRecording);
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "a\nb");
+
+        cx.dispatch_action(Play {
+            name: None,
+            count: None,
+        });
+        cx.run_until_parked();
+        assert_eq!(editor.read_with(cx, |editor, cx| editor.text(cx)), "a\nbab");
+    }
+
+    #[gpui::test]
+    async fn test_play_on_each_line(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (editor, cx) = open_editor("x\ny\nz", cx).await;
+
+        cx.dispatch_action(StartRecording);
+        cx.simulate_keystrokes("- space");
+        cx.dispatch_action(StopRecording);
+        assert_eq!(
+            editor.read_with(cx, |editor, cx| editor.text(cx)),
+            "- x\ny\nz"
+        );
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+                s.select_ranges([text::Point::new(1, 0)..text::Point::new(2, 1)])
+            });
+        });
+        cx.dispatch_action(PlayOnEachLine { name: None });
+        cx.run_until_parked();
+        assert_eq!(
+            editor.read_with(cx, |editor, cx| editor.text(cx)),
+            "- x\n- y\n- z"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_play_named_macro(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (editor, cx) = open_editor("", cx).await;
+
+        cx.update(|_, cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.keyboard_macros = Some(HashMap::from_iter([(
+                        "greet".to_string(),
+                        vec![
+                            KeyboardMacroStep::Keystroke("h".into()),
+                            KeyboardMacroStep::Keystroke("i".into()),
+                            KeyboardMacroStep::Action("editor::Newline".into()),
+                        ],
+                    )]));
+                });
+            });
+        });
+
+        cx.dispatch_action(Play {
+            name: Some("greet".into()),
+            count: Some(2),
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            editor.read_with(cx, |editor, cx| editor.text(cx)),
+            "hi\nhi\n"
+        );
+    }
+}
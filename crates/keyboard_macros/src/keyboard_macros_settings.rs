@@ -0,0 +1,16 @@
+use collections::HashMap;
+use settings::{KeyboardMacroStep, RegisterSetting, Settings};
+
+/// The named keyboard macros saved in the settings.
+#[derive(Clone, Debug, Default, RegisterSetting)]
+pub struct KeyboardMacroSettings {
+    pub macros: HashMap<String, Vec<KeyboardMacroStep>>,
+}
+
+impl Settings for KeyboardMacroSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        Self {
+            macros: content.keyboard_macros.clone().unwrap(),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use editor::Editor;
+use fs::Fs;
+use gpui::{DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Subscription};
+use settings::{KeyboardMacroStep, update_settings_file};
+use ui::prelude::*;
+use workspace::ModalView;
+
+/// Prompts for a name under which the last recorded macro is saved to the
+/// `keyboard_macros` setting.
+pub struct SaveMacroModal {
+    name_editor: Entity<Editor>,
+    steps: Vec<KeyboardMacroStep>,
+    skipped_step_count: usize,
+    fs: Arc<dyn Fs>,
+    _subscription: Subscription,
+}
+
+impl ModalView for SaveMacroModal {}
+
+impl Focusable for SaveMacroModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.name_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SaveMacroModal {}
+
+impl SaveMacroModal {
+    pub fn new(
+        steps: Vec<KeyboardMacroStep>,
+        skipped_step_count: usize,
+        fs: Arc<dyn Fs>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let name_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Macro name", window, cx);
+            editor
+        });
+        let _subscription = cx.subscribe_in(&name_editor, window, |_, _, event, window, cx| {
+            if let editor::EditorEvent::Blurred = event
+                && window.is_window_active()
+            {
+                cx.emit(DismissEvent);
+            }
+        });
+        Self {
+            name_editor,
+            steps,
+            skipped_step_count,
+            fs,
+            _subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let name = self.name_editor.read(cx).text(cx).trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let steps = self.steps.clone();
+        update_settings_file(self.fs.clone(), cx, move |settings, _| {
+            settings
+                .keyboard_macros
+                .get_or_insert_default()
+                .insert(name, steps);
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for SaveMacroModal {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let help_text: SharedString = if self.skipped_step_count > 0 {
+            format!(
+                "{} step{} without a key binding can't be saved and will be left out",
+                self.skipped_step_count,
+                if self.skipped_step_count == 1 {
+                    ""
+                } else {
+                    "s"
+                }
+            )
+            .into()
+        } else {
+            format!("Save a macro with {} steps", self.steps.len()).into()
+        };
+
+        v_flex()
+            .w(rems(24.))
+            .elevation_2(cx)
+            .key_context("SaveMacroModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .child(
+                div()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .px_2()
+                    .py_1()
+                    .child(self.name_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(help_text).color(Color::Muted)),
+            )
+    }
+}
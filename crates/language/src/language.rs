@@ -1375,6 +1375,13 @@ impl Debug for Language {
     }
 }
 
+/// Parses a standalone snippet of text with the language's grammar, ignoring
+/// injections. Returns `None` if the language has no grammar.
+pub fn parse_snippet(language: &Language, text: &str) -> Option<Tree> {
+    let grammar = language.grammar()?;
+    Some(parse_text(grammar, &Rope::from(text), None))
+}
+
 pub(crate) fn parse_text(grammar: &Grammar, text: &Rope, old_tree: Option<Tree>) -> Tree {
     with_parser(|parser| {
         parser
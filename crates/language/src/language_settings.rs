@@ -63,6 +63,9 @@ pub struct LanguageSettings {
     /// Whether to indent lines using tab characters, as opposed to multiple
     /// spaces.
     pub hard_tabs: bool,
+    /// Whether tabs should expand to align with the tabs on neighboring
+    /// lines, rather than to the next multiple of the tab size.
+    pub elastic_tabstops: bool,
     /// How to soft-wrap long lines of text.
     pub soft_wrap: settings::SoftWrap,
     /// The column at which to soft-wrap lines, for buffers where soft-wrap
@@ -789,6 +792,7 @@ impl settings::Settings for AllLanguageSettings {
             LanguageSettings {
                 tab_size: settings.tab_size.unwrap(),
                 hard_tabs: settings.hard_tabs.unwrap(),
+                elastic_tabstops: settings.elastic_tabstops.unwrap(),
                 soft_wrap: settings.soft_wrap.unwrap(),
                 preferred_line_length: settings.preferred_line_length.unwrap(),
                 show_wrap_guides: settings.show_wrap_guides.unwrap(),
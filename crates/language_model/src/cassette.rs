@@ -0,0 +1,597 @@
+//! Record and replay of language model completions.
+//!
+//! A [`Cassette`] wraps a model (or a whole provider) and stores every
+//! completion it streams in a directory, one JSON file per request. Replaying
+//! the cassette serves the same events again without contacting the provider,
+//! so agent flows that were recorded once against a live model can run
+//! deterministically and offline, e.g. in CI.
+//!
+//! Requests are keyed by a hash of the model and the normalized request:
+//! per-run identifiers (`thread_id`, `prompt_id`) are dropped, and
+//! [`Cassette::with_substitution`] replaces run-specific text such as
+//! temporary directory paths before hashing.
+
+use crate::{
+    AuthenticateError, CompactionResult, DisabledReason, FastModeConfirmation, IconOrSvg,
+    LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
+    LanguageModelCostInfo, LanguageModelEffortLevel, LanguageModelId, LanguageModelName,
+    LanguageModelProvider, LanguageModelProviderId, LanguageModelProviderName,
+    LanguageModelProviderState, LanguageModelRequest, LanguageModelToolChoice,
+    LanguageModelToolSchemaFormat, ModelPricing, ProviderSettingsView,
+};
+use anyhow::{Context as _, Result, anyhow};
+use futures::{FutureExt, StreamExt, future::BoxFuture, stream::BoxStream};
+use gpui::{App, AsyncApp, Entity, SharedString, Task};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Always call the wrapped model and overwrite the stored responses.
+    Record,
+    /// Only serve stored responses; a request without one fails.
+    #[default]
+    Replay,
+    /// Serve stored responses, recording the ones that are missing.
+    ReplayOrRecord,
+}
+
+impl FromStr for CassetteMode {
+    type Err = anyhow::Error;
+
+    fn from_str(mode: &str) -> Result<Self> {
+        match mode {
+            "record" => Ok(Self::Record),
+            "replay" => Ok(Self::Replay),
+            "replay-or-record" => Ok(Self::ReplayOrRecord),
+            _ => Err(anyhow!(
+                "invalid cassette mode `{mode}`, expected `record`, `replay` or `replay-or-record`"
+            )),
+        }
+    }
+}
+
+/// A directory of recorded completions.
+pub struct Cassette {
+    dir: PathBuf,
+    mode: CassetteMode,
+    substitutions: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CassetteEntry {
+    model: String,
+    /// The normalized request, kept so a cassette miss can be diagnosed by
+    /// diffing it against the request that was made.
+    request: serde_json::Value,
+    events: Vec<CassetteEvent>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CassetteEvent {
+    Event(LanguageModelCompletionEvent),
+    /// Errors are replayed as [`LanguageModelCompletionError::Other`] with the
+    /// recorded message.
+    Error(String),
+}
+
+impl Cassette {
+    pub fn new(dir: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+            substitutions: Vec::new(),
+        }
+    }
+
+    /// Replaces `text` with `placeholder` in requests before they're keyed,
+    /// so that requests mentioning e.g. a per-run temporary directory match
+    /// across runs.
+    pub fn with_substitution(
+        mut self,
+        text: impl Into<String>,
+        placeholder: impl Into<String>,
+    ) -> Self {
+        self.substitutions.push((text.into(), placeholder.into()));
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn wrap_model(self: &Arc<Self>, model: Arc<dyn LanguageModel>) -> Arc<dyn LanguageModel> {
+        Arc::new(CassetteLanguageModel {
+            model,
+            cassette: self.clone(),
+        })
+    }
+
+    /// Returns the normalized request and the key it's stored under.
+    fn normalize(
+        &self,
+        model: &dyn LanguageModel,
+        request: &LanguageModelRequest,
+    ) -> Result<(serde_json::Value, String)> {
+        let mut request = request.clone();
+        request.thread_id = None;
+        request.prompt_id = None;
+
+        let mut json = serde_json::to_string(&request)?;
+        for (text, placeholder) in &self.substitutions {
+            // Match the text as it appears inside JSON strings.
+            let text = serde_json::to_string(text)?;
+            let placeholder = serde_json::to_string(placeholder)?;
+            json = json.replace(text.trim_matches('"'), placeholder.trim_matches('"'));
+        }
+
+        let mut hasher = Sha256::new();
+        hasher.update(model_key(model).as_bytes());
+        hasher.update(b"\n");
+        hasher.update(json.as_bytes());
+        let key = format!("{:x}", hasher.finalize());
+        Ok((serde_json::from_str(&json)?, key))
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    fn load(&self, key: &str) -> Result<Option<CassetteEntry>> {
+        let path = self.entry_path(key);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).with_context(|| format!("reading {}", path.display()));
+            }
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .with_context(|| format!("parsing {}", path.display()))
+    }
+
+    fn save(&self, key: &str, entry: &CassetteEntry) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("creating {}", self.dir.display()))?;
+        let path = self.entry_path(key);
+        std::fs::write(&path, serde_json::to_string_pretty(entry)?)
+            .with_context(|| format!("writing {}", path.display()))
+    }
+}
+
+fn model_key(model: &dyn LanguageModel) -> String {
+    format!("{}/{}", model.provider_id().0, model.id().0)
+}
+
+/// A model whose completions are recorded to, or replayed from, a
+/// [`Cassette`]. Everything other than streamed completions is delegated to
+/// the wrapped model.
+pub struct CassetteLanguageModel {
+    model: Arc<dyn LanguageModel>,
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteLanguageModel {
+    fn record(
+        &self,
+        request: LanguageModelRequest,
+        normalized_request: serde_json::Value,
+        key: String,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let cassette = self.cassette.clone();
+        let model = model_key(self.model.as_ref());
+        let future = self.model.stream_completion(request, cx);
+        async move {
+            let events = future.await?;
+            let recorded = Arc::new(Mutex::new(Vec::new()));
+            let save = futures::stream::once({
+                let recorded = recorded.clone();
+                async move {
+                    let entry = CassetteEntry {
+                        model,
+                        request: normalized_request,
+                        events: std::mem::take(&mut *recorded.lock()),
+                    };
+                    if let Err(error) = cassette.save(&key, &entry) {
+                        log::error!("failed to record completion: {error:#}");
+                    }
+                }
+            });
+            // The entry is only written once the stream completes, so an
+            // abandoned completion isn't recorded as a truncated one.
+            Ok(events
+                .inspect(move |event| {
+                    recorded.lock().push(match event {
+                        Ok(event) => CassetteEvent::Event(event.clone()),
+                        Err(error) => CassetteEvent::Error(error.to_string()),
+                    })
+                })
+                .chain(save.filter_map(|()| async { None }))
+                .boxed())
+        }
+        .boxed()
+    }
+}
+
+impl LanguageModel for CassetteLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.model.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.model.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.model.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.model.provider_name()
+    }
+
+    fn upstream_provider_id(&self) -> LanguageModelProviderId {
+        self.model.upstream_provider_id()
+    }
+
+    fn upstream_provider_name(&self) -> LanguageModelProviderName {
+        self.model.upstream_provider_name()
+    }
+
+    fn is_latest(&self) -> bool {
+        self.model.is_latest()
+    }
+
+    fn is_disabled(&self) -> Option<DisabledReason> {
+        self.model.is_disabled()
+    }
+
+    fn requires_data_retention(&self) -> bool {
+        self.model.requires_data_retention()
+    }
+
+    fn refusal_fallback_model_id(&self) -> Option<&'static str> {
+        self.model.refusal_fallback_model_id()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.model.telemetry_id()
+    }
+
+    fn api_key(&self, cx: &App) -> Option<String> {
+        self.model.api_key(cx)
+    }
+
+    fn model_cost_info(&self) -> Option<LanguageModelCostInfo> {
+        self.model.model_cost_info()
+    }
+
+    fn pricing(&self) -> Option<ModelPricing> {
+        self.model.pricing()
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.model.supports_thinking()
+    }
+
+    fn supports_disabling_thinking(&self) -> bool {
+        self.model.supports_disabling_thinking()
+    }
+
+    fn supports_fast_mode(&self) -> bool {
+        self.model.supports_fast_mode()
+    }
+
+    fn supported_effort_levels(&self) -> Vec<LanguageModelEffortLevel> {
+        self.model.supported_effort_levels()
+    }
+
+    fn default_effort_level(&self) -> Option<LanguageModelEffortLevel> {
+        self.model.default_effort_level()
+    }
+
+    fn supports_server_side_compaction(&self) -> bool {
+        self.model.supports_server_side_compaction()
+    }
+
+    fn supports_explicit_compaction(&self) -> bool {
+        self.model.supports_explicit_compaction()
+    }
+
+    fn minimum_explicit_compaction_input_tokens(&self) -> Option<u64> {
+        self.model.minimum_explicit_compaction_input_tokens()
+    }
+
+    fn compact(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<'static, Result<CompactionResult, LanguageModelCompletionError>> {
+        self.model.compact(request, cx)
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools()
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.model.supports_tool_choice(choice)
+    }
+
+    fn supports_streaming_tools(&self) -> bool {
+        self.model.supports_streaming_tools()
+    }
+
+    fn supports_split_token_display(&self) -> bool {
+        self.model.supports_split_token_display()
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.model.tool_input_format()
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count()
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let (normalized_request, key) = match self.cassette.normalize(self.model.as_ref(), &request)
+        {
+            Ok(normalized) => normalized,
+            Err(error) => return async move { Err(error.into()) }.boxed(),
+        };
+
+        let entry = match self.cassette.mode {
+            CassetteMode::Record => None,
+            CassetteMode::Replay | CassetteMode::ReplayOrRecord => match self.cassette.load(&key) {
+                Ok(entry) => entry,
+                Err(error) => return async move { Err(error.into()) }.boxed(),
+            },
+        };
+
+        match (entry, self.cassette.mode) {
+            (Some(entry), _) => {
+                let events = entry.events.into_iter().map(|event| match event {
+                    CassetteEvent::Event(event) => Ok(event),
+                    CassetteEvent::Error(message) => {
+                        Err(LanguageModelCompletionError::Other(anyhow!(message)))
+                    }
+                });
+                async move { Ok(futures::stream::iter(events).boxed()) }.boxed()
+            }
+            (None, CassetteMode::Replay) => {
+                let path = self.cassette.entry_path(&key);
+                let model = model_key(self.model.as_ref());
+                async move {
+                    Err(LanguageModelCompletionError::Other(anyhow!(
+                        "no recorded completion for this request to {model} (expected {}); \
+                         record it again with a live model",
+                        path.display()
+                    )))
+                }
+                .boxed()
+            }
+            (None, CassetteMode::Record | CassetteMode::ReplayOrRecord) => {
+                self.record(request, normalized_request, key, cx)
+            }
+        }
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    fn as_fake(&self) -> &crate::fake_provider::FakeLanguageModel {
+        self.model.as_fake()
+    }
+}
+
+/// A provider whose models record to, or replay from, a [`Cassette`].
+///
+/// Registering it in place of the provider it wraps routes every completion
+/// for that provider through the cassette. In [`CassetteMode::Replay`] the
+/// provider reports itself as authenticated, so no credentials are needed.
+pub struct CassetteLanguageModelProvider {
+    provider: Arc<dyn LanguageModelProvider>,
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteLanguageModelProvider {
+    pub fn new(provider: Arc<dyn LanguageModelProvider>, cassette: Arc<Cassette>) -> Self {
+        Self { provider, cassette }
+    }
+
+    fn wrap_models(&self, models: Vec<Arc<dyn LanguageModel>>) -> Vec<Arc<dyn LanguageModel>> {
+        models
+            .into_iter()
+            .map(|model| self.cassette.wrap_model(model))
+            .collect()
+    }
+}
+
+impl LanguageModelProviderState for CassetteLanguageModelProvider {
+    type ObservableEntity = ();
+
+    /// The registry stays subscribed to the wrapped provider, whose id this
+    /// provider shares.
+    fn observable_entity(&self) -> Option<Entity<Self::ObservableEntity>> {
+        None
+    }
+}
+
+impl LanguageModelProvider for CassetteLanguageModelProvider {
+    fn id(&self) -> LanguageModelProviderId {
+        self.provider.id()
+    }
+
+    fn name(&self) -> LanguageModelProviderName {
+        self.provider.name()
+    }
+
+    fn icon(&self) -> IconOrSvg {
+        self.provider.icon()
+    }
+
+    fn default_model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.provider
+            .default_model(cx)
+            .map(|model| self.cassette.wrap_model(model))
+    }
+
+    fn default_fast_model(&self, cx: &App) -> Option<Arc<dyn LanguageModel>> {
+        self.provider
+            .default_fast_model(cx)
+            .map(|model| self.cassette.wrap_model(model))
+    }
+
+    fn provided_models(&self, cx: &App) -> Vec<Arc<dyn LanguageModel>> {
+        self.wrap_models(self.provider.provided_models(cx))
+    }
+
+    fn recommended_models(&self, cx: &App) -> Vec<Arc<dyn LanguageModel>> {
+        self.wrap_models(self.provider.recommended_models(cx))
+    }
+
+    fn is_authenticated(&self, cx: &App) -> bool {
+        self.cassette.mode == CassetteMode::Replay || self.provider.is_authenticated(cx)
+    }
+
+    fn authenticate(&self, cx: &mut App) -> Task<Result<(), AuthenticateError>> {
+        if self.cassette.mode == CassetteMode::Replay {
+            Task::ready(Ok(()))
+        } else {
+            self.provider.authenticate(cx)
+        }
+    }
+
+    fn settings_view(&self, cx: &mut App) -> Option<ProviderSettingsView> {
+        self.provider.settings_view(cx)
+    }
+
+    fn set_api_key(&self, key: Option<String>, cx: &mut App) -> Task<Result<()>> {
+        self.provider.set_api_key(key, cx)
+    }
+
+    fn authentication_error_message(&self) -> SharedString {
+        self.provider.authentication_error_message()
+    }
+
+    fn missing_credentials_error_message(&self) -> SharedString {
+        self.provider.missing_credentials_error_message()
+    }
+
+    fn fast_mode_confirmation(&self, cx: &App) -> Option<FastModeConfirmation> {
+        self.provider.fast_mode_confirmation(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        LanguageModelRequestMessage, MessageContent, Role, fake_provider::FakeLanguageModel,
+    };
+    use gpui::TestAppContext;
+
+    fn request(text: &str, thread_id: &str) -> LanguageModelRequest {
+        LanguageModelRequest {
+            thread_id: Some(thread_id.to_string()),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![MessageContent::Text(text.to_string())],
+                cache: false,
+                reasoning_details: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    async fn collect(
+        events: BoxFuture<
+            'static,
+            Result<
+                BoxStream<
+                    'static,
+                    Result<LanguageModelCompletionEvent, LanguageModelCompletionError>,
+                >,
+                LanguageModelCompletionError,
+            >,
+        >,
+    ) -> Result<Vec<String>, LanguageModelCompletionError> {
+        Ok(events
+            .await?
+            .map(|event| match event {
+                Ok(LanguageModelCompletionEvent::Text(text)) => text,
+                Ok(event) => format!("{event:?}"),
+                Err(error) => format!("error: {error}"),
+            })
+            .collect()
+            .await)
+    }
+
+    #[gpui::test]
+    async fn test_record_then_replay(cx: &mut TestAppContext) {
+        let dir = tempfile::tempdir().unwrap();
+        let cx = cx.to_async();
+        let fake = Arc::new(FakeLanguageModel::default());
+
+        let recorder = Arc::new(
+            Cassette::new(dir.path(), CassetteMode::Record)
+                .with_substitution("/tmp/run-1", "$ROOT"),
+        )
+        .wrap_model(fake.clone());
+        let recording =
+            recorder.stream_completion(request("read /tmp/run-1/a.rs", "thread-1"), &cx);
+        fake.send_last_completion_stream_text_chunk("Hello");
+        fake.send_last_completion_stream_text_chunk(", world");
+        fake.end_last_completion_stream();
+        assert_eq!(collect(recording).await.unwrap(), ["Hello", ", world"]);
+
+        fake.forbid_requests();
+        let player = Arc::new(
+            Cassette::new(dir.path(), CassetteMode::Replay)
+                .with_substitution("/tmp/run-2", "$ROOT"),
+        )
+        .wrap_model(fake.clone());
+        let replay = player.stream_completion(request("read /tmp/run-2/a.rs", "thread-2"), &cx);
+        assert_eq!(collect(replay).await.unwrap(), ["Hello", ", world"]);
+
+        let miss = player.stream_completion(request("something else", "thread-2"), &cx);
+        let error = collect(miss).await.unwrap_err();
+        assert!(error.to_string().contains("no recorded completion"));
+    }
+}
@@ -1,4 +1,6 @@
 mod api_key;
+pub mod cassette;
+mod pricing;
 mod registry;
 mod request;
 
@@ -18,6 +20,7 @@ use std::sync::Arc;
 pub type CreateProviderSettingsView = Arc<dyn Fn(&mut Window, &mut App) -> AnyView + 'static>;
 
 pub use crate::api_key::{ApiKey, ApiKeyState};
+pub use crate::pricing::ModelPricing;
 pub use crate::registry::*;
 pub use crate::request::{LanguageModelImageExt, gpui_size_to_image_size, image_size_to_gpui};
 pub use env_var::{EnvVar, env_var};
@@ -107,6 +110,12 @@ pub trait LanguageModel: Send + Sync {
         None
     }
 
+    /// Per-token pricing used to estimate the cost of requests to this model,
+    /// if known.
+    fn pricing(&self) -> Option<ModelPricing> {
+        ModelPricing::for_model_id(&self.id().0)
+    }
+
     /// Whether this model supports thinking.
     fn supports_thinking(&self) -> bool {
         false
@@ -0,0 +1,165 @@
+use crate::TokenUsage;
+
+/// Published per-token prices for a model, in US dollars per million tokens.
+///
+/// Used to estimate what a conversation costs. Prices change and providers
+/// bill in different ways (plans, batch discounts, long-context tiers), so the
+/// result is an estimate, not a bill.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelPricing {
+    pub input_per_1m: f64,
+    pub output_per_1m: f64,
+    /// The price of writing input tokens to the provider's prompt cache.
+    pub cache_write_per_1m: f64,
+    /// The price of input tokens read from the provider's prompt cache.
+    pub cache_read_per_1m: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input_per_1m: input,
+            output_per_1m: output,
+            cache_write_per_1m: cache_write,
+            cache_read_per_1m: cache_read,
+        }
+    }
+
+    /// The estimated cost of `usage`, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_1m
+            + usage.output_tokens as f64 * self.output_per_1m
+            + usage.cache_creation_input_tokens as f64 * self.cache_write_per_1m
+            + usage.cache_read_input_tokens as f64 * self.cache_read_per_1m)
+            / 1_000_000.0
+    }
+
+    /// Looks up the published pricing for a model by its id. Dated snapshots
+    /// (`claude-sonnet-4-5-20250929`) and `-latest` aliases are priced like the
+    /// model they point to.
+    pub fn for_model_id(model_id: &str) -> Option<Self> {
+        let base_id = strip_snapshot_suffix(model_id);
+        PRICING_TABLE
+            .iter()
+            .find(|(id, _)| *id == base_id)
+            .map(|(_, pricing)| *pricing)
+    }
+}
+
+fn strip_snapshot_suffix(model_id: &str) -> &str {
+    if let Some(base_id) = model_id.strip_suffix("-latest") {
+        return base_id;
+    }
+    match model_id.rsplit_once('-') {
+        Some((base_id, date)) if date.len() == 8 && date.bytes().all(|b| b.is_ascii_digit()) => {
+            base_id
+        }
+        _ => model_id,
+    }
+}
+
+const PRICING_TABLE: &[(&str, ModelPricing)] = &[
+    // Anthropic
+    (
+        "claude-mythos-5",
+        ModelPricing::new(25.0, 125.0, 31.25, 2.5),
+    ),
+    (
+        "claude-mythos-preview",
+        ModelPricing::new(25.0, 125.0, 31.25, 2.5),
+    ),
+    ("claude-fable-5", ModelPricing::new(25.0, 125.0, 31.25, 2.5)),
+    ("claude-opus-5", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4-8", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4-7", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4-6", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4-5", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("claude-opus-4-1", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-opus-4-0", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-opus-4", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("claude-sonnet-5", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-sonnet-4-6", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-sonnet-4-5", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-sonnet-4-0", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-sonnet-4", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-7-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-3-5-sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("claude-haiku-4-5", ModelPricing::new(1.0, 5.0, 1.25, 0.1)),
+    ("claude-3-5-haiku", ModelPricing::new(0.8, 4.0, 1.0, 0.08)),
+    // OpenAI
+    ("gpt-4", ModelPricing::new(30.0, 60.0, 30.0, 30.0)),
+    ("gpt-4o", ModelPricing::new(2.5, 10.0, 2.5, 1.25)),
+    ("gpt-4o-mini", ModelPricing::new(0.15, 0.6, 0.15, 0.075)),
+    ("gpt-4.1", ModelPricing::new(2.0, 8.0, 2.0, 0.5)),
+    ("gpt-4.1-mini", ModelPricing::new(0.4, 1.6, 0.4, 0.1)),
+    ("gpt-4.1-nano", ModelPricing::new(0.1, 0.4, 0.1, 0.025)),
+    ("o3", ModelPricing::new(2.0, 8.0, 2.0, 0.5)),
+    ("o4-mini", ModelPricing::new(1.1, 4.4, 1.1, 0.275)),
+    ("gpt-5", ModelPricing::new(1.25, 10.0, 1.25, 0.125)),
+    ("gpt-5.1", ModelPricing::new(1.25, 10.0, 1.25, 0.125)),
+    ("gpt-5-mini", ModelPricing::new(0.25, 2.0, 0.25, 0.025)),
+    ("gpt-5-nano", ModelPricing::new(0.05, 0.4, 0.05, 0.005)),
+    // Google
+    ("gemini-2.5-pro", ModelPricing::new(1.25, 10.0, 1.25, 0.31)),
+    ("gemini-2.5-flash", ModelPricing::new(0.3, 2.5, 0.3, 0.075)),
+    (
+        "gemini-2.5-flash-lite",
+        ModelPricing::new(0.1, 0.4, 0.1, 0.025),
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_lookup_ignores_snapshot_suffixes() {
+        let sonnet = ModelPricing::for_model_id("claude-sonnet-4-5").unwrap();
+        assert_eq!(
+            ModelPricing::for_model_id("claude-sonnet-4-5-20250929"),
+            Some(sonnet)
+        );
+        assert_eq!(
+            ModelPricing::for_model_id("claude-sonnet-4-5-latest"),
+            Some(sonnet)
+        );
+        assert_eq!(
+            ModelPricing::for_model_id("gpt-5-mini"),
+            Some(ModelPricing::new(0.25, 2.0, 0.25, 0.025))
+        );
+        assert_eq!(ModelPricing::for_model_id("llama3.2"), None);
+    }
+
+    #[test]
+    fn test_pricing_covers_current_anthropic_models() {
+        for model_id in [
+            "claude-fable-5",
+            "claude-mythos-5",
+            "claude-mythos-preview",
+            "claude-opus-5",
+            "claude-opus-4-8",
+            "claude-opus-4-7",
+            "claude-opus-4-6",
+            "claude-sonnet-5",
+            "claude-sonnet-4-6",
+        ] {
+            assert!(
+                ModelPricing::for_model_id(model_id).is_some(),
+                "no pricing for {model_id}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_cost_counts_cached_tokens_separately() {
+        let pricing = ModelPricing::new(3.0, 15.0, 3.75, 0.3);
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 200_000,
+            cache_read_input_tokens: 2_000_000,
+        };
+        let cost = pricing.cost(&usage);
+        assert!((cost - (3.0 + 1.5 + 0.75 + 0.6)).abs() < 1e-9);
+    }
+}
@@ -888,6 +888,7 @@ impl SearchableItem for LspLogView {
             replacement: false,
             selection: false,
             select_all: true,
+            syntax_scope: false,
         }
     }
     fn active_match_index(
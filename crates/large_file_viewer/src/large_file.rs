@@ -0,0 +1,573 @@
+use std::{
+    fs::File,
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Subscription, Task};
+use project::{Project, ProjectEntryId, ProjectPath};
+use settings::Settings as _;
+
+use crate::{
+    LargeFileSettings,
+    line_index::{LONG_LINE_LEN, LineIndex},
+};
+
+/// How many bytes are scanned for line starts before the index is published.
+const INDEX_CHUNK_LEN: usize = 64 * 1024 * 1024;
+/// How many bytes are read from the file at a time.
+const READ_CHUNK_LEN: usize = 64 * 1024;
+
+pub enum LargeFileEvent {
+    /// More of the file has been indexed.
+    Indexed,
+    /// The file changed on disk and has been opened again.
+    Reloaded,
+}
+
+/// A read-only file that is too large to open as a buffer.
+///
+/// Lines are resolved through a sparse [`LineIndex`] that is built in the
+/// background, so nothing beyond the visible rows is ever read or decoded.
+pub struct LargeFile {
+    project_path: ProjectPath,
+    entry_id: Option<ProjectEntryId>,
+    abs_path: PathBuf,
+    text: Arc<FileText>,
+    line_index: Arc<LineIndex>,
+    _index_task: Task<Result<()>>,
+    _subscription: Subscription,
+}
+
+impl EventEmitter<LargeFileEvent> for LargeFile {}
+
+/// Returns whether the given path should open in the large-file view.
+///
+/// Only files on the local file system can be read on demand, so remote
+/// projects keep opening every file as a buffer.
+pub fn is_large_file(project: &Entity<Project>, path: &ProjectPath, cx: &App) -> bool {
+    let settings = LargeFileSettings::get_global(cx);
+    if !settings.enabled || !project.read(cx).is_local() {
+        return false;
+    }
+    project
+        .read(cx)
+        .entry_for_path(path, cx)
+        .is_some_and(|entry| entry.is_file() && entry.size >= settings.threshold)
+}
+
+impl LargeFile {
+    pub fn open(
+        project: Entity<Project>,
+        project_path: ProjectPath,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let Some(abs_path) = project.read(cx).absolute_path(&project_path, cx) else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "no absolute path for {:?}",
+                project_path.path
+            )));
+        };
+        let entry_id = project
+            .read(cx)
+            .entry_for_path(&project_path, cx)
+            .map(|entry| entry.id);
+
+        cx.spawn(async move |cx| {
+            let text = cx
+                .background_spawn({
+                    let abs_path = abs_path.clone();
+                    async move { FileText::open(&abs_path) }
+                })
+                .await?;
+            Ok(cx.new(|cx| {
+                let _subscription = cx.subscribe(&project, Self::handle_project_event);
+                let mut this = Self {
+                    project_path,
+                    entry_id,
+                    abs_path,
+                    text: Arc::new(text),
+                    line_index: Arc::default(),
+                    _index_task: Task::ready(Ok(())),
+                    _subscription,
+                };
+                this.start_indexing(cx);
+                this
+            }))
+        })
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    pub fn text(&self) -> &Arc<FileText> {
+        &self.text
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn is_indexed(&self) -> bool {
+        self.line_index.is_complete(self.text.len())
+    }
+
+    /// The number of rows known so far. This grows while the file is indexed.
+    pub fn row_count(&self) -> usize {
+        self.line_index.line_count()
+    }
+
+    /// The text and line index as they are now, to read lines from on a
+    /// background thread.
+    pub fn snapshot(&self) -> LargeFileSnapshot {
+        LargeFileSnapshot {
+            text: self.text.clone(),
+            line_index: self.line_index.clone(),
+        }
+    }
+
+    fn start_indexing(&mut self, cx: &mut Context<Self>) {
+        let text = self.text.clone();
+        self.line_index = Arc::default();
+        self._index_task = cx.spawn(async move |this, cx| {
+            let mut index = LineIndex::default();
+            loop {
+                let (next_index, is_complete) = cx
+                    .background_spawn({
+                        let text = text.clone();
+                        async move {
+                            let end = text
+                                .len()
+                                .min(index.scanned_len().saturating_add(INDEX_CHUNK_LEN));
+                            while index.scanned_len() < end {
+                                let start = index.scanned_len();
+                                let chunk = text
+                                    .read(start..end.min(start.saturating_add(READ_CHUNK_LEN)))?;
+                                // The file has been truncated, and will be
+                                // opened again once the worktree notices.
+                                if chunk.is_empty() {
+                                    return anyhow::Ok((index, true));
+                                }
+                                index.scan(&chunk);
+                            }
+                            let is_complete = index.is_complete(text.len());
+                            anyhow::Ok((index, is_complete))
+                        }
+                    })
+                    .await?;
+                index = next_index;
+                this.update(cx, |this, cx| {
+                    this.line_index = Arc::new(index.clone());
+                    cx.emit(LargeFileEvent::Indexed);
+                    cx.notify();
+                })?;
+                if is_complete {
+                    return Ok(());
+                }
+            }
+        });
+    }
+
+    fn handle_project_event(
+        &mut self,
+        _: Entity<Project>,
+        event: &project::Event,
+        cx: &mut Context<Self>,
+    ) {
+        let project::Event::WorktreeUpdatedEntries(worktree_id, changes) = event else {
+            return;
+        };
+        if *worktree_id != self.project_path.worktree_id
+            || !changes
+                .iter()
+                .any(|(path, _, _)| *path == self.project_path.path)
+        {
+            return;
+        }
+
+        let abs_path = self.abs_path.clone();
+        self._index_task = cx.spawn(async move |this, cx| {
+            let text = cx
+                .background_spawn(async move { FileText::open(&abs_path) })
+                .await?;
+            this.update(cx, |this, cx| {
+                this.text = Arc::new(text);
+                this.start_indexing(cx);
+                cx.emit(LargeFileEvent::Reloaded);
+            })
+        });
+    }
+}
+
+/// The text and line index of a [`LargeFile`] at some point in time.
+///
+/// Reading lines can take a while, so it is done on a background thread. No
+/// more than [`LONG_LINE_LEN`] bytes are scanned for the end of any line, as
+/// the index records where longer lines end.
+#[derive(Clone)]
+pub struct LargeFileSnapshot {
+    text: Arc<FileText>,
+    line_index: Arc<LineIndex>,
+}
+
+impl LargeFileSnapshot {
+    /// Reads the given rows, cutting each line off after `max_line_len`
+    /// bytes. Rows that haven't been indexed yet are left out.
+    pub fn lines(&self, rows: Range<usize>, max_line_len: usize) -> Result<Vec<Line>> {
+        let rows = rows.start..rows.end.min(self.line_index.line_count());
+        let Some((mut row, offset)) = self.line_index.checkpoint_for_row(rows.start) else {
+            return Ok(Vec::new());
+        };
+
+        let mut reader = LineReader::new(&self.text, &self.line_index, offset);
+        let mut lines = Vec::with_capacity(rows.len());
+        while row < rows.end {
+            let max_line_len = if row < rows.start { 0 } else { max_line_len };
+            let (range, bytes) = reader.next_line(max_line_len)?;
+            if row >= rows.start {
+                lines.push(Line { row, range, bytes });
+            }
+            row += 1;
+        }
+        Ok(lines)
+    }
+
+    /// Reads the text of the given rows joined by newlines, stopping once
+    /// `max_len` bytes have been read.
+    pub fn text_for_rows(&self, rows: Range<usize>, max_len: usize) -> Result<String> {
+        let rows = rows.start..rows.end.min(self.line_index.line_count());
+        let Some((mut row, offset)) = self.line_index.checkpoint_for_row(rows.start) else {
+            return Ok(String::new());
+        };
+
+        let mut reader = LineReader::new(&self.text, &self.line_index, offset);
+        let mut text = Vec::new();
+        while row < rows.end && text.len() < max_len {
+            if row < rows.start {
+                reader.next_line(0)?;
+            } else {
+                if row > rows.start {
+                    text.push(b'\n');
+                }
+                let (_, bytes) = reader.next_line(max_len.saturating_sub(text.len()))?;
+                text.extend_from_slice(&bytes);
+            }
+            row += 1;
+        }
+        text.truncate(max_len);
+        Ok(String::from_utf8_lossy(&text).into_owned())
+    }
+
+    /// Returns the row containing the given byte offset, which is clamped to
+    /// the indexed part of the file.
+    pub fn row_for_offset(&self, offset: usize) -> Result<usize> {
+        let offset = offset.min(self.line_index.scanned_len());
+        let (mut row, start) = self.line_index.checkpoint_for_offset(offset);
+        let mut reader = LineReader::new(&self.text, &self.line_index, start);
+        loop {
+            let line_start = reader.offset();
+            reader.next_line(0)?;
+            // The offset is on this line or its line ending, or there are no
+            // more lines to read.
+            if reader.offset() > offset || reader.offset() == line_start {
+                return Ok(row);
+            }
+            row += 1;
+        }
+    }
+}
+
+/// A line read from a [`LargeFile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    pub row: usize,
+    /// The byte range of the whole line, excluding its line ending.
+    pub range: Range<usize>,
+    /// The start of the line, up to the requested length.
+    pub bytes: Vec<u8>,
+}
+
+/// An open file that is read with positional reads.
+///
+/// The file isn't memory-mapped, since another process truncating a mapped
+/// file makes reading the mapping past the new end crash the process. Reads
+/// past the end of a truncated file just come up short.
+pub struct FileText {
+    file: File,
+    len: usize,
+}
+
+impl FileText {
+    fn open(abs_path: &Path) -> Result<Self> {
+        let file =
+            File::open(abs_path).with_context(|| format!("opening large file {abs_path:?}"))?;
+        let len = file
+            .metadata()
+            .with_context(|| format!("reading metadata of large file {abs_path:?}"))?
+            .len();
+        Ok(Self {
+            file,
+            len: usize::try_from(len)?,
+        })
+    }
+
+    /// The length of the file when it was opened.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Reads the given byte range, returning fewer bytes than requested if
+    /// the file has been truncated since it was opened.
+    pub fn read(&self, range: Range<usize>) -> io::Result<Vec<u8>> {
+        let range = range.start.min(self.len)..range.end.min(self.len);
+        let mut buffer = vec![0; range.len()];
+        let mut filled = 0;
+        while filled < buffer.len() {
+            match read_at(
+                &self.file,
+                &mut buffer[filled..],
+                (range.start + filled) as u64,
+            ) {
+                Ok(0) => break,
+                Ok(read_len) => filled += read_len,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        buffer.truncate(filled);
+        Ok(buffer)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buffer: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buffer, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buffer: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buffer, offset)
+}
+
+/// Reads consecutive lines of a [`FileText`] in chunks.
+struct LineReader<'a> {
+    text: &'a FileText,
+    line_index: &'a LineIndex,
+    chunk: Vec<u8>,
+    chunk_start: usize,
+    /// The position within `chunk` of the next line's start.
+    position: usize,
+    /// Whether a line was cut off before its end was found, after which
+    /// every line is empty.
+    gave_up: bool,
+}
+
+impl<'a> LineReader<'a> {
+    /// Creates a reader whose first line starts at `offset`.
+    fn new(text: &'a FileText, line_index: &'a LineIndex, offset: usize) -> Self {
+        Self {
+            text,
+            line_index,
+            chunk: Vec::new(),
+            chunk_start: offset,
+            position: 0,
+            gave_up: false,
+        }
+    }
+
+    /// The offset where the next line starts.
+    fn offset(&self) -> usize {
+        self.chunk_start + self.position
+    }
+
+    /// Reads the next line, returning its range without the line ending and
+    /// at most `max_len` of its bytes. Past the end of the file, every line is
+    /// empty.
+    ///
+    /// Lines that the index knows to be long are skipped over. Other lines
+    /// are scanned for at most [`LONG_LINE_LEN`] bytes, as a longer one can
+    /// only be the last line the index has reached, and is cut off there.
+    fn next_line(&mut self, max_len: usize) -> io::Result<(Range<usize>, Vec<u8>)> {
+        let start = self.offset();
+        if self.gave_up {
+            return Ok((start..start, Vec::new()));
+        }
+        if let Some(newline) = self.line_index.long_line_end(start) {
+            let ends_with_carriage_return = self.text.read(newline - 1..newline)? == b"\r";
+            let end = if ends_with_carriage_return {
+                newline - 1
+            } else {
+                newline
+            };
+            let bytes = self
+                .text
+                .read(start..end.min(start.saturating_add(max_len)))?;
+            self.chunk.clear();
+            self.chunk_start = newline + 1;
+            self.position = 0;
+            return Ok((start..end, bytes));
+        }
+
+        let mut bytes = Vec::new();
+        let mut ends_with_carriage_return = false;
+        let end = loop {
+            if self.position == self.chunk.len() {
+                if self.chunk_start + self.chunk.len() - start >= LONG_LINE_LEN {
+                    self.gave_up = true;
+                    self.chunk_start += self.chunk.len();
+                    self.chunk.clear();
+                    self.position = 0;
+                    break self.chunk_start;
+                }
+                self.chunk_start += self.chunk.len();
+                self.chunk = self
+                    .text
+                    .read(self.chunk_start..self.chunk_start.saturating_add(READ_CHUNK_LEN))?;
+                self.position = 0;
+                if self.chunk.is_empty() {
+                    break self.chunk_start;
+                }
+            }
+
+            let rest = &self.chunk[self.position..];
+            let newline_ix = memchr::memchr(b'\n', rest);
+            let line = &rest[..newline_ix.unwrap_or(rest.len())];
+            let take_len = line.len().min(max_len.saturating_sub(bytes.len()));
+            bytes.extend_from_slice(&line[..take_len]);
+            if let Some(&last) = line.last() {
+                ends_with_carriage_return = last == b'\r';
+            }
+            match newline_ix {
+                Some(newline_ix) => {
+                    self.position += newline_ix + 1;
+                    break self.chunk_start + self.position - 1;
+                }
+                None => self.position = self.chunk.len(),
+            }
+        };
+
+        let end = if ends_with_carriage_return && end > start {
+            end - 1
+        } else {
+            end
+        };
+        bytes.truncate(end - start);
+        Ok((start..end, bytes))
+    }
+}
+
+impl project::ProjectItem for LargeFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        if is_large_file(project, path, cx) {
+            Some(Self::open(project.clone(), path.clone(), cx))
+        } else {
+            None
+        }
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs::OpenOptions;
+    use util::test::TempTree;
+
+    fn read_lines(text: &FileText, offset: usize, count: usize) -> Vec<(Range<usize>, String)> {
+        let index = LineIndex::default();
+        let mut reader = LineReader::new(text, &index, offset);
+        (0..count)
+            .map(|_| {
+                let (range, bytes) = reader.next_line(usize::MAX).unwrap();
+                (range, String::from_utf8(bytes).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_line_reader() {
+        let tree = TempTree::new(json!({ "large.log": "first\r\nsecond\n\nlast" }));
+        let text = FileText::open(&tree.path().join("large.log")).unwrap();
+
+        assert_eq!(
+            read_lines(&text, 0, 5),
+            vec![
+                (0..5, "first".to_string()),
+                (7..13, "second".to_string()),
+                (14..14, String::new()),
+                (15..19, "last".to_string()),
+                (19..19, String::new()),
+            ]
+        );
+        assert_eq!(
+            LineReader::new(&text, &LineIndex::default(), 7)
+                .next_line(3)
+                .unwrap(),
+            (7..13, b"sec".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_line_reader_across_chunks() {
+        let long_line = "a".repeat(READ_CHUNK_LEN + 10);
+        let tree = TempTree::new(json!({ "large.log": format!("{long_line}\r\nb") }));
+        let text = FileText::open(&tree.path().join("large.log")).unwrap();
+
+        let index = LineIndex::default();
+        let mut reader = LineReader::new(&text, &index, 0);
+        assert_eq!(
+            reader.next_line(4).unwrap(),
+            (0..long_line.len(), b"aaaa".to_vec())
+        );
+        let offset = long_line.len() + 2;
+        assert_eq!(
+            reader.next_line(usize::MAX).unwrap(),
+            (offset..offset + 1, b"b".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_read_truncated_file() {
+        let tree = TempTree::new(json!({ "large.log": "first\r\nsecond\n\nlast" }));
+        let path = tree.path().join("large.log");
+        let text = FileText::open(&path).unwrap();
+
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(4)
+            .unwrap();
+        assert_eq!(text.len(), 19);
+        assert_eq!(text.read(0..text.len()).unwrap(), b"firs");
+        assert_eq!(text.read(10..19).unwrap(), b"");
+        assert_eq!(
+            read_lines(&text, 0, 2),
+            vec![(0..4, "firs".to_string()), (4..4, String::new())]
+        );
+    }
+}
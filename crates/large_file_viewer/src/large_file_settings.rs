@@ -0,0 +1,24 @@
+use settings::{RegisterSetting, Settings};
+
+/// The settings for opening large files.
+#[derive(Clone, Debug, Default, RegisterSetting)]
+pub struct LargeFileSettings {
+    /// Whether large files open in the large-file view.
+    ///
+    /// Default: true
+    pub enabled: bool,
+    /// The file size, in bytes, above which the large-file view is used.
+    ///
+    /// Default: 256 MB
+    pub threshold: u64,
+}
+
+impl Settings for LargeFileSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let large_file = content.large_file.clone().unwrap();
+        Self {
+            enabled: large_file.enabled.unwrap(),
+            threshold: large_file.threshold_mb.unwrap().saturating_mul(1024 * 1024),
+        }
+    }
+}
@@ -0,0 +1,1115 @@
+mod large_file;
+mod large_file_settings;
+mod line_index;
+
+use std::{
+    ops::{Range, RangeInclusive},
+    path::Path,
+    sync::Arc,
+};
+
+use anyhow::Result;
+use editor::{
+    Editor, EditorSettings,
+    actions::{Copy, SelectAll},
+    items::entry_git_aware_label_color,
+};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, ClipboardItem, Context, Entity, EventEmitter, FocusHandle, Focusable, Font,
+    HighlightStyle, InteractiveElement, IntoElement, MouseButton, MouseDownEvent, MouseMoveEvent,
+    ParentElement, Pixels, Render, ScrollWheelEvent, SharedString, StyledText, Subscription, Task,
+    Window, px,
+};
+use project::{Project, search::SearchQuery};
+use settings::Settings;
+use theme_settings::ThemeSettings;
+use ui::prelude::*;
+use util::paths::PathExt;
+use workspace::{
+    ItemSettings, Pane, ToolbarItemLocation, WorkspaceId,
+    invalid_item_view::InvalidItemView,
+    item::{HighlightedText, Item, ItemEvent, ProjectItem, TabContentParams},
+    searchable::{
+        Direction, SearchEvent, SearchOptions, SearchToken, SearchableItem, SearchableItemHandle,
+    },
+};
+
+pub use crate::large_file::*;
+pub use crate::large_file_settings::*;
+
+/// Lines longer than this are cut off when rendered.
+const MAX_RENDERED_LINE_LEN: usize = 4096;
+/// Searches run over chunks of roughly this size, split on line boundaries.
+const SEARCH_CHUNK_LEN: usize = 4 * 1024 * 1024;
+/// Searching stops once this many matches have been found.
+const MAX_SEARCH_MATCHES: usize = 100_000;
+/// At most this many rows are copied to the clipboard at once.
+const MAX_COPIED_ROWS: usize = 100_000;
+/// At most this many bytes are copied to the clipboard at once.
+const MAX_COPIED_LEN: usize = 16 * 1024 * 1024;
+
+/// A position to scroll to once enough of the file has been indexed.
+#[derive(Clone, Copy, Debug)]
+enum PendingReveal {
+    Row(usize),
+    Offset(usize),
+}
+
+/// Whole rows selected with the mouse or revealed by go-to-line and search.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RowSelection {
+    anchor: usize,
+    head: usize,
+}
+
+impl RowSelection {
+    fn row(row: usize) -> Self {
+        Self {
+            anchor: row,
+            head: row,
+        }
+    }
+
+    fn rows(&self) -> RangeInclusive<usize> {
+        self.anchor.min(self.head)..=self.anchor.max(self.head)
+    }
+}
+
+/// A read-only view of a [`LargeFile`].
+///
+/// Only the rows in the viewport are read and laid out, and the view keeps
+/// its scroll position as a row number rather than in pixels, so it works the
+/// same for files with billions of lines.
+pub struct LargeFileView {
+    large_file: Entity<LargeFile>,
+    project: Entity<Project>,
+    focus_handle: FocusHandle,
+    go_to_line_editor: Entity<Editor>,
+    top_row: usize,
+    visible_row_count: usize,
+    scroll_remainder: Pixels,
+    /// The lines in the viewport, read again in the background only when it
+    /// changes.
+    visible_lines: Vec<Line>,
+    /// The first row, row count and number of indexed rows in the viewport
+    /// that `visible_lines` is being or was read for.
+    visible_lines_key: Option<(usize, usize, usize)>,
+    read_visible_lines: Task<()>,
+    selection: Option<RowSelection>,
+    pending_reveal: Option<PendingReveal>,
+    reveal_offset: Task<()>,
+    search_matches: Vec<Range<usize>>,
+    active_match_index: Option<usize>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LargeFileView {
+    pub fn new(
+        large_file: Entity<LargeFile>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let go_to_line_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Go to line…", window, cx);
+            editor
+        });
+        let subscriptions = vec![cx.subscribe(&large_file, Self::handle_large_file_event)];
+
+        Self {
+            large_file,
+            project,
+            focus_handle: cx.focus_handle(),
+            go_to_line_editor,
+            top_row: 0,
+            visible_row_count: 0,
+            scroll_remainder: px(0.),
+            visible_lines: Vec::new(),
+            visible_lines_key: None,
+            read_visible_lines: Task::ready(()),
+            selection: None,
+            pending_reveal: None,
+            reveal_offset: Task::ready(()),
+            search_matches: Vec::new(),
+            active_match_index: None,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn handle_large_file_event(
+        &mut self,
+        _: Entity<LargeFile>,
+        event: &LargeFileEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            LargeFileEvent::Indexed => {
+                if let Some(pending_reveal) = self.pending_reveal.take() {
+                    self.reveal(pending_reveal, cx);
+                }
+            }
+            LargeFileEvent::Reloaded => {
+                self.search_matches.clear();
+                self.active_match_index = None;
+                self.selection = None;
+                self.visible_lines_key = None;
+                self.top_row = 0;
+                cx.emit(SearchEvent::MatchesInvalidated);
+            }
+        }
+        cx.notify();
+    }
+
+    fn line_height(&self, window: &Window, cx: &App) -> Pixels {
+        let settings = ThemeSettings::get_global(cx);
+        let font_size = settings.buffer_font_size(cx);
+        (font_size * settings.line_height())
+            .round()
+            .max(window.line_height())
+    }
+
+    fn scroll_to_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        let max_row = self.large_file.read(cx).row_count().saturating_sub(1);
+        self.top_row = row.min(max_row);
+        cx.notify();
+    }
+
+    /// Scrolls the given position into the middle of the viewport and
+    /// highlights its row, waiting for the line index to reach it if needed.
+    fn reveal(&mut self, reveal: PendingReveal, cx: &mut Context<Self>) {
+        let large_file = self.large_file.read(cx);
+        match reveal {
+            PendingReveal::Row(row) if row < large_file.row_count() => self.reveal_row(row, cx),
+            PendingReveal::Row(_) if large_file.is_indexed() => {
+                self.reveal_row(large_file.row_count().saturating_sub(1), cx)
+            }
+            PendingReveal::Offset(offset) if offset < large_file.line_index().scanned_len() => {
+                let snapshot = large_file.snapshot();
+                self.reveal_offset = cx.spawn(async move |this, cx| {
+                    let row = cx
+                        .background_spawn(async move { snapshot.row_for_offset(offset) })
+                        .await;
+                    this.update(cx, |this, cx| match row {
+                        Ok(row) => this.reveal_row(row, cx),
+                        Err(error) => log::error!("failed to read large file: {error:#}"),
+                    })
+                    .ok();
+                });
+            }
+            _ => self.pending_reveal = Some(reveal),
+        }
+    }
+
+    fn reveal_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        self.selection = Some(RowSelection::row(row));
+        self.scroll_to_row(row.saturating_sub(self.visible_row_count / 2), cx);
+    }
+
+    fn handle_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let line_height = self.line_height(window, cx);
+        self.scroll_remainder -= event.delta.pixel_delta(line_height).y;
+        let rows = (self.scroll_remainder / line_height).trunc();
+        if rows != 0. {
+            self.scroll_remainder -= line_height * rows;
+            let top_row = if rows > 0. {
+                self.top_row.saturating_add(rows as usize)
+            } else {
+                self.top_row.saturating_sub(-rows as usize)
+            };
+            self.scroll_to_row(top_row, cx);
+        }
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_to_row(self.top_row + 1, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.scroll_to_row(self.top_row.saturating_sub(1), cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        self.scroll_to_row(0, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        let row_count = self.large_file.read(cx).row_count();
+        self.scroll_to_row(row_count.saturating_sub(self.visible_row_count), cx);
+    }
+
+    fn select_row(&mut self, row: usize, extend: bool, cx: &mut Context<Self>) {
+        self.selection = match self.selection {
+            Some(selection) if extend => Some(RowSelection {
+                anchor: selection.anchor,
+                head: row,
+            }),
+            _ => Some(RowSelection::row(row)),
+        };
+        cx.notify();
+    }
+
+    fn handle_row_mouse_down(
+        &mut self,
+        row: usize,
+        event: &MouseDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.focus_handle.focus(window, cx);
+        self.select_row(row, event.modifiers.shift, cx);
+    }
+
+    fn handle_row_mouse_move(
+        &mut self,
+        row: usize,
+        event: &MouseMoveEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if event.dragging()
+            && self
+                .selection
+                .is_some_and(|selection| selection.head != row)
+        {
+            self.select_row(row, true, cx);
+        }
+    }
+
+    /// Copies the text of the selected rows, joined by newlines, once it has
+    /// been read in the background.
+    fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(rows) = self.selection.map(|selection| selection.rows()) else {
+            return;
+        };
+        let rows = *rows.start()..(*rows.end() + 1).min(rows.start() + MAX_COPIED_ROWS);
+        let snapshot = self.large_file.read(cx).snapshot();
+        let text = cx.background_spawn(async move { snapshot.text_for_rows(rows, MAX_COPIED_LEN) });
+        cx.spawn(async move |_, cx| match text.await {
+            Ok(text) => cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string(text))),
+            Err(error) => log::error!("failed to copy from large file: {error:#}"),
+        })
+        .detach();
+    }
+
+    fn toggle_go_to_line(
+        &mut self,
+        _: &editor::actions::ToggleGoToLine,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.go_to_line_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        self.go_to_line_editor.focus_handle(cx).focus(window, cx);
+    }
+
+    fn confirm_go_to_line(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let query = self.go_to_line_editor.read(cx).text(cx);
+        let row = query
+            .trim()
+            .split(':')
+            .next()
+            .and_then(|row| row.trim().parse::<usize>().ok());
+        if let Some(row) = row {
+            self.reveal(PendingReveal::Row(row.saturating_sub(1)), cx);
+        }
+        self.cancel_go_to_line(&menu::Cancel, window, cx);
+    }
+
+    fn cancel_go_to_line(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        self.go_to_line_editor.update(cx, |editor, cx| {
+            editor.set_text("", window, cx);
+        });
+        self.focus_handle.focus(window, cx);
+    }
+
+    fn render_status(&self, cx: &Context<Self>) -> impl IntoElement {
+        let large_file = self.large_file.read(cx);
+        let size = util::size::format_file_size(large_file.len() as u64, false);
+        let lines = if large_file.is_indexed() {
+            format!("{} lines", large_file.row_count())
+        } else {
+            let progress = large_file.line_index().scanned_len() * 100 / large_file.len().max(1);
+            format!("Indexing lines… {progress}%")
+        };
+
+        h_flex()
+            .flex_none()
+            .px_2()
+            .py_1()
+            .gap_3()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(format!("{size} · {lines}"))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                Label::new(
+                    "Read-only. Syntax highlighting, language servers and git are disabled for large files.",
+                )
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+                .truncate(),
+            )
+            .child(div().flex_1())
+            .child(
+                div()
+                    .key_context("LargeFileGoToLine")
+                    .w(rems(10.))
+                    .px_1()
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .on_action(cx.listener(Self::confirm_go_to_line))
+                    .on_action(cx.listener(Self::cancel_go_to_line))
+                    .child(self.go_to_line_editor.clone()),
+            )
+    }
+
+    /// Starts reading the lines in the viewport on a background thread if it
+    /// has changed since they were last read. The previous lines are shown
+    /// until then.
+    fn update_visible_lines(&mut self, cx: &mut Context<Self>) {
+        let large_file = self.large_file.read(cx);
+        let rows = self.top_row..self.top_row + self.visible_row_count;
+        let key = (rows.start, rows.len(), large_file.row_count().min(rows.end));
+        if self.visible_lines_key == Some(key) {
+            return;
+        }
+        self.visible_lines_key = Some(key);
+        let snapshot = large_file.snapshot();
+        let lines = cx.background_spawn(async move { snapshot.lines(rows, MAX_RENDERED_LINE_LEN) });
+        self.read_visible_lines = cx.spawn(async move |this, cx| {
+            let lines = lines.await.unwrap_or_else(|error| {
+                log::error!("failed to read large file: {error:#}");
+                Vec::new()
+            });
+            this.update(cx, |this, cx| {
+                if this.visible_lines_key == Some(key) {
+                    this.visible_lines = lines;
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+    }
+
+    fn render_rows(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_height = self.line_height(window, cx);
+        self.visible_row_count = (window.viewport_size().height / line_height).ceil() as usize;
+        self.update_visible_lines(cx);
+
+        let settings = ThemeSettings::get_global(cx);
+        let font = settings.buffer_font.clone();
+        let font_size = settings.buffer_font_size(cx);
+        let colors = cx.theme().colors();
+        let selection_background = cx.theme().players().local().selection;
+        let gutter_width = self
+            .large_file
+            .read(cx)
+            .row_count()
+            .to_string()
+            .len()
+            .max(3);
+        let selected_rows = self.selection.map(|selection| selection.rows());
+        let is_single_row_selected = selected_rows
+            .as_ref()
+            .is_some_and(|rows| rows.start() == rows.end());
+
+        v_flex()
+            .id("large-file-rows")
+            .size_full()
+            .overflow_hidden()
+            .font(font)
+            .text_size(font_size)
+            .line_height(line_height)
+            .on_scroll_wheel(cx.listener(Self::handle_scroll_wheel))
+            .children(self.visible_lines.iter().map(|line| {
+                let row = line.row;
+                let text_range = line.range.start..line.range.start + line.bytes.len();
+                let text = String::from_utf8_lossy(&line.bytes);
+                let highlights = if text.len() == text_range.len() {
+                    self.match_highlights(&text, text_range, colors)
+                } else {
+                    Vec::new()
+                };
+                let mut text = text.into_owned();
+                if line.bytes.len() < line.range.len() {
+                    text.push('…');
+                }
+                let is_selected = selected_rows
+                    .as_ref()
+                    .is_some_and(|rows| rows.contains(&row));
+
+                h_flex()
+                    .id(("large-file-row", row))
+                    .h(line_height)
+                    .flex_none()
+                    .when(is_selected, |this| {
+                        this.bg(if is_single_row_selected {
+                            colors.editor_active_line_background
+                        } else {
+                            selection_background
+                        })
+                    })
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, event, window, cx| {
+                            this.handle_row_mouse_down(row, event, window, cx)
+                        }),
+                    )
+                    .on_mouse_move(cx.listener(move |this, event, window, cx| {
+                        this.handle_row_mouse_move(row, event, window, cx)
+                    }))
+                    .child(
+                        div()
+                            .flex_none()
+                            .pl_2()
+                            .pr_3()
+                            .text_color(if is_selected {
+                                colors.editor_active_line_number
+                            } else {
+                                colors.editor_line_number
+                            })
+                            .child(format!("{:>gutter_width$}", row + 1)),
+                    )
+                    .child(
+                        div()
+                            .whitespace_nowrap()
+                            .text_color(colors.editor_foreground)
+                            .child(StyledText::new(text).with_highlights(highlights)),
+                    )
+            }))
+    }
+
+    /// Returns the search match highlights that intersect the given line.
+    fn match_highlights(
+        &self,
+        line: &str,
+        line_range: Range<usize>,
+        colors: &theme::ThemeColors,
+    ) -> Vec<(Range<usize>, HighlightStyle)> {
+        let first_match = self
+            .search_matches
+            .partition_point(|search_match| search_match.end <= line_range.start);
+        self.search_matches[first_match..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, search_match)| search_match.start < line_range.end)
+            .filter_map(|(ix, search_match)| {
+                let start = search_match.start.max(line_range.start) - line_range.start;
+                let end = search_match.end.min(line_range.end) - line_range.start;
+                if start == end || !line.is_char_boundary(start) || !line.is_char_boundary(end) {
+                    return None;
+                }
+                let background = if self.active_match_index == Some(first_match + ix) {
+                    colors.search_active_match_background
+                } else {
+                    colors.search_match_background
+                };
+                Some((
+                    start..end,
+                    HighlightStyle {
+                        background_color: Some(background),
+                        ..Default::default()
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Searches the given file in chunks that end on line boundaries.
+///
+/// A line longer than a whole chunk is split between chunks, so a match
+/// spanning the split is missed.
+fn search_text(query: &SearchQuery, text: &FileText) -> Result<Vec<Range<usize>>> {
+    let mut matches = Vec::new();
+    let mut chunk_start = 0;
+    while chunk_start < text.len() && matches.len() < MAX_SEARCH_MATCHES {
+        let mut chunk = text.read(chunk_start..chunk_start.saturating_add(SEARCH_CHUNK_LEN))?;
+        if chunk.is_empty() {
+            break;
+        }
+        if chunk_start + chunk.len() < text.len()
+            && let Some(newline_ix) = memchr::memrchr(b'\n', &chunk)
+        {
+            chunk.truncate(newline_ix + 1);
+        }
+        search_chunk(query, &chunk, chunk_start, &mut matches);
+        chunk_start += chunk.len();
+    }
+    matches.truncate(MAX_SEARCH_MATCHES);
+    Ok(matches)
+}
+
+/// Searches a chunk of text that starts at `chunk_start`, skipping over byte
+/// sequences that are not valid UTF-8.
+fn search_chunk(
+    query: &SearchQuery,
+    chunk: &[u8],
+    chunk_start: usize,
+    matches: &mut Vec<Range<usize>>,
+) {
+    let mut offset = 0;
+    while offset < chunk.len() {
+        let (valid_len, invalid_len) = match std::str::from_utf8(&chunk[offset..]) {
+            Ok(valid) => (valid.len(), 0),
+            Err(error) => (
+                error.valid_up_to(),
+                error
+                    .error_len()
+                    .unwrap_or(chunk.len() - offset - error.valid_up_to()),
+            ),
+        };
+        let valid = std::str::from_utf8(&chunk[offset..offset + valid_len]).unwrap_or_default();
+        let match_offset = chunk_start + offset;
+        matches.extend(
+            query
+                .search_str(valid)
+                .into_iter()
+                .map(|range| match_offset + range.start..match_offset + range.end),
+        );
+        offset += valid_len + invalid_len;
+    }
+}
+
+impl EventEmitter<ItemEvent> for LargeFileView {}
+impl EventEmitter<SearchEvent> for LargeFileView {}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .track_focus(&self.focus_handle)
+            .key_context("LargeFileView")
+            .on_action(cx.listener(Self::toggle_go_to_line))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::copy))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_status(cx))
+            .child(self.render_rows(window, cx))
+    }
+}
+
+impl Item for LargeFileView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.large_file.entity_id(), self.large_file.read(cx))
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let abs_path = self.large_file.read(cx).abs_path();
+        Some(abs_path.compact().to_string_lossy().into_owned().into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        let project_path = project::ProjectItem::project_path(self.large_file.read(cx), cx);
+
+        let label_color = if let Some(project_path) = project_path
+            && ItemSettings::get_global(cx).git_status
+        {
+            let git_status = self
+                .project
+                .read(cx)
+                .git_store()
+                .read(cx)
+                .display_status_for_project_path(&project_path, cx)
+                .map(|status| status.summary())
+                .unwrap_or_default();
+
+            self.project
+                .read(cx)
+                .entry_for_path(&project_path, cx)
+                .map(|entry| {
+                    entry_git_aware_label_color(git_status, entry.is_ignored, params.selected)
+                })
+                .unwrap_or_else(|| params.text_color())
+        } else {
+            params.text_color()
+        };
+
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(label_color)
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.large_file
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned().into())
+            .unwrap_or_default()
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let path = self.large_file.read(cx).abs_path();
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(path, cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn breadcrumb_location(&self, cx: &App) -> ToolbarItemLocation {
+        if EditorSettings::get_global(cx).toolbar.breadcrumbs {
+            ToolbarItemLocation::PrimaryLeft
+        } else {
+            ToolbarItemLocation::Hidden
+        }
+    }
+
+    fn breadcrumbs(&self, cx: &App) -> Option<(Vec<HighlightedText>, Option<Font>)> {
+        let text = self
+            .large_file
+            .read(cx)
+            .abs_path()
+            .compact()
+            .to_string_lossy()
+            .into_owned();
+        let font = ThemeSettings::get_global(cx).buffer_font.clone();
+        Some((
+            vec![HighlightedText {
+                text: text.into(),
+                highlights: vec![],
+            }],
+            Some(font),
+        ))
+    }
+
+    fn can_split(&self) -> bool {
+        true
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<WorkspaceId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Option<Entity<Self>>>
+    where
+        Self: Sized,
+    {
+        let large_file = self.large_file.clone();
+        let project = self.project.clone();
+        let top_row = self.top_row;
+        Task::ready(Some(cx.new(|cx| {
+            let mut view = Self::new(large_file, project, window, cx);
+            view.top_row = top_row;
+            view
+        })))
+    }
+
+    fn as_searchable(
+        &self,
+        handle: &Entity<Self>,
+        _: &App,
+    ) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(handle.clone()))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl SearchableItem for LargeFileView {
+    type Match = Range<usize>;
+
+    fn supported_options(&self) -> SearchOptions {
+        SearchOptions {
+            case: true,
+            word: true,
+            regex: true,
+            replacement: false,
+            selection: false,
+            select_all: false,
+            syntax_scope: false,
+            find_in_results: false,
+        }
+    }
+
+    fn clear_matches(&mut self, _: &mut Window, cx: &mut Context<Self>) {
+        self.search_matches.clear();
+        self.active_match_index = None;
+        cx.notify();
+    }
+
+    fn update_matches(
+        &mut self,
+        matches: &[Self::Match],
+        active_match_index: Option<usize>,
+        _token: SearchToken,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.search_matches = matches.to_vec();
+        self.active_match_index = active_match_index;
+        cx.notify();
+    }
+
+    fn query_suggestion(
+        &mut self,
+        _seed_query_override: Option<settings::SeedQuerySetting>,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) -> String {
+        String::new()
+    }
+
+    fn activate_match(
+        &mut self,
+        index: usize,
+        matches: &[Self::Match],
+        _token: SearchToken,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(search_match) = matches.get(index) else {
+            return;
+        };
+        self.active_match_index = Some(index);
+        self.reveal(PendingReveal::Offset(search_match.start), cx);
+    }
+
+    fn select_matches(
+        &mut self,
+        _: &[Self::Match],
+        _token: SearchToken,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+        // Selections cover whole rows, so matches can't be selected.
+    }
+
+    fn replace(
+        &mut self,
+        _: &Self::Match,
+        _: &SearchQuery,
+        _token: SearchToken,
+        _: &mut Window,
+        _: &mut Context<Self>,
+    ) {
+        // Large files are read-only, so replacement is a no-op.
+    }
+
+    fn find_matches(
+        &mut self,
+        query: Arc<SearchQuery>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<Self::Match>> {
+        let text = self.large_file.read(cx).text().clone();
+        cx.background_spawn(async move {
+            search_text(&query, &text).unwrap_or_else(|error| {
+                log::error!("failed to search large file: {error:#}");
+                Vec::new()
+            })
+        })
+    }
+
+    fn active_match_index(
+        &mut self,
+        direction: Direction,
+        matches: &[Self::Match],
+        _token: SearchToken,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<usize> {
+        if matches.is_empty() {
+            return None;
+        }
+        let row = self
+            .selection
+            .map_or(self.top_row, |selection| selection.head);
+        // Rows outside the viewport aren't read here, so matches are counted
+        // from the closest indexed row before them instead.
+        let anchor = self
+            .visible_lines
+            .iter()
+            .find(|line| line.row == row)
+            .map(|line| line.range.start)
+            .or_else(|| {
+                let line_index = self.large_file.read(cx).line_index();
+                line_index.checkpoint_for_row(row).map(|(_, offset)| offset)
+            })
+            .unwrap_or(0);
+        let next_match = matches.partition_point(|search_match| search_match.start < anchor);
+        match direction {
+            Direction::Next => Some(next_match % matches.len()),
+            Direction::Prev => Some(next_match.checked_sub(1).unwrap_or(matches.len() - 1)),
+        }
+    }
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFile;
+
+    fn for_project_item(
+        project: Entity<Project>,
+        _: Option<&Pane>,
+        item: Entity<Self::Item>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(item, project, window, cx)
+    }
+
+    fn for_broken_project_item(
+        abs_path: &Path,
+        is_local: bool,
+        e: &anyhow::Error,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<InvalidItemView>
+    where
+        Self: Sized,
+    {
+        Some(InvalidItemView::new(abs_path, is_local, e, window, cx))
+    }
+}
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<LargeFileView>(cx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::RealFs;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::ProjectPath;
+    use serde_json::json;
+    use util::{rel_path::rel_path, test::TempTree};
+    use workspace::AppState;
+
+    const ROW_COUNT: usize = 1000;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    async fn open_view(
+        tree: &TempTree,
+        cx: &mut TestAppContext,
+    ) -> (Entity<LargeFileView>, &mut VisualTestContext) {
+        let fs = Arc::new(RealFs::new(None, cx.executor()));
+        let project = Project::test(fs, [tree.path()], cx).await;
+        let worktree_id = project.read_with(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+        let project_path = ProjectPath {
+            worktree_id,
+            path: rel_path("large.log").into(),
+        };
+        let large_file = cx
+            .update(|cx| LargeFile::open(project.clone(), project_path, cx))
+            .await
+            .unwrap();
+        let (view, cx) =
+            cx.add_window_view(|window, cx| LargeFileView::new(large_file, project, window, cx));
+        cx.run_until_parked();
+        view.update_in(cx, |view, window, cx| view.focus_handle.focus(window, cx));
+        cx.run_until_parked();
+        (view, cx)
+    }
+
+    fn numbered_lines() -> TempTree {
+        let text = (1..=ROW_COUNT)
+            .map(|row| format!("line {row}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        TempTree::new(json!({ "large.log": text }))
+    }
+
+    #[gpui::test]
+    async fn test_scrolling(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let tree = numbered_lines();
+        let (view, cx) = open_view(&tree, cx).await;
+
+        view.read_with(cx, |view, cx| {
+            assert!(view.large_file.read(cx).is_indexed());
+            assert_eq!(view.large_file.read(cx).row_count(), ROW_COUNT);
+            assert_eq!(view.top_row, 0);
+        });
+
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::SelectNext);
+        cx.dispatch_action(menu::SelectNext);
+        view.read_with(cx, |view, _| assert_eq!(view.top_row, 3));
+
+        cx.dispatch_action(menu::SelectPrevious);
+        view.read_with(cx, |view, _| assert_eq!(view.top_row, 2));
+
+        cx.dispatch_action(menu::SelectLast);
+        view.read_with(cx, |view, _| {
+            assert_eq!(
+                view.top_row,
+                (ROW_COUNT - view.visible_row_count).min(ROW_COUNT - 1)
+            );
+        });
+
+        // Scrolling never goes past the last row.
+        for _ in 0..view.read_with(cx, |view, _| view.visible_row_count) + 1 {
+            cx.dispatch_action(menu::SelectNext);
+        }
+        view.read_with(cx, |view, _| assert_eq!(view.top_row, ROW_COUNT - 1));
+
+        cx.dispatch_action(menu::SelectFirst);
+        view.update(cx, |view, cx| {
+            assert_eq!(view.top_row, 0);
+            view.visible_row_count = 3;
+            view.update_visible_lines(cx);
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _| {
+            assert_eq!(
+                view.visible_lines
+                    .iter()
+                    .map(|line| (line.row, String::from_utf8_lossy(&line.bytes).into_owned()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (0, "line 1".to_string()),
+                    (1, "line 2".to_string()),
+                    (2, "line 3".to_string()),
+                ]
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_go_to_line(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let tree = numbered_lines();
+        let (view, cx) = open_view(&tree, cx).await;
+
+        view.update_in(cx, |view, window, cx| {
+            view.go_to_line_editor.update(cx, |editor, cx| {
+                editor.set_text("150:4", window, cx);
+            });
+            view.confirm_go_to_line(&menu::Confirm, window, cx);
+        });
+        view.read_with(cx, |view, cx| {
+            assert_eq!(
+                view.selection.map(|selection| selection.rows()),
+                Some(149..=149)
+            );
+            assert_eq!(view.top_row, 149 - view.visible_row_count / 2);
+            assert!(view.go_to_line_editor.read(cx).text(cx).is_empty());
+        });
+
+        // Rows past the end of the file go to the last row.
+        view.update_in(cx, |view, window, cx| {
+            view.go_to_line_editor.update(cx, |editor, cx| {
+                editor.set_text("5000", window, cx);
+            });
+            view.confirm_go_to_line(&menu::Confirm, window, cx);
+        });
+        view.read_with(cx, |view, _| {
+            assert_eq!(
+                view.selection.map(|selection| selection.rows()),
+                Some(ROW_COUNT - 1..=ROW_COUNT - 1)
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_copy(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.executor().allow_parking();
+        let tree = numbered_lines();
+        let (view, cx) = open_view(&tree, cx).await;
+
+        view.update(cx, |view, cx| view.select_row(4, false, cx));
+        cx.dispatch_action(Copy);
+        cx.run_until_parked();
+        assert_eq!(
+            cx.read_from_clipboard().and_then(|item| item.text()),
+            Some("line 5".to_string())
+        );
+
+        // Extending the selection upwards copies the rows in order.
+        view.update(cx, |view, cx| view.select_row(2, true, cx));
+        cx.dispatch_action(Copy);
+        cx.run_until_parked();
+        assert_eq!(
+            cx.read_from_clipboard().and_then(|item| item.text()),
+            Some("line 3\nline 4\nline 5".to_string())
+        );
+    }
+
+    #[test]
+    fn test_search_text_skips_invalid_utf8() {
+        let query = SearchQuery::text(
+            "needle",
+            false,
+            true,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .unwrap();
+        let mut text = b"a needle\n".to_vec();
+        text.extend_from_slice(&[0xff, 0xfe]);
+        text.extend_from_slice(b" needle\n");
+
+        let mut matches = Vec::new();
+        search_chunk(&query, &text, 0, &mut matches);
+        assert_eq!(matches, vec![2..8, 12..18]);
+        for range in matches {
+            assert_eq!(&text[range], b"needle");
+        }
+    }
+}
@@ -0,0 +1,194 @@
+/// Number of lines between two consecutive checkpoints in a [`LineIndex`].
+///
+/// Keeping only every Nth line start bounds the index to a few megabytes even
+/// for files with billions of lines, at the cost of scanning at most this many
+/// lines whenever a row is resolved.
+const LINE_INDEX_STRIDE: usize = 256;
+
+/// Lines at least this long are recorded in a [`LineIndex`], so that reading
+/// the lines after them skips over them instead of scanning them again.
+pub const LONG_LINE_LEN: usize = 256 * 1024;
+
+/// A sparse, incrementally built index of line start offsets.
+#[derive(Clone, Debug)]
+pub struct LineIndex {
+    /// Byte offset of the start of every `LINE_INDEX_STRIDE`th line.
+    checkpoints: Vec<usize>,
+    /// Number of bytes that have been scanned for newlines so far.
+    scanned_len: usize,
+    /// Number of line starts found so far.
+    line_count: usize,
+    /// The start offset of the line being scanned.
+    line_start: usize,
+    /// The start and newline offsets of the lines of at least
+    /// `LONG_LINE_LEN` bytes, in order.
+    long_lines: Vec<(usize, usize)>,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self {
+            checkpoints: vec![0],
+            scanned_len: 0,
+            line_count: 1,
+            line_start: 0,
+            long_lines: Vec::new(),
+        }
+    }
+}
+
+impl LineIndex {
+    /// Scans the next chunk of the text for line starts. Each chunk has to
+    /// start where the previous one ended.
+    pub fn scan(&mut self, chunk: &[u8]) {
+        for newline_ix in memchr::memchr_iter(b'\n', chunk) {
+            let newline = self.scanned_len + newline_ix;
+            if newline - self.line_start >= LONG_LINE_LEN {
+                self.long_lines.push((self.line_start, newline));
+            }
+            let line_start = newline + 1;
+            self.line_start = line_start;
+            if self.line_count % LINE_INDEX_STRIDE == 0 {
+                self.checkpoints.push(line_start);
+            }
+            self.line_count += 1;
+        }
+        self.scanned_len += chunk.len();
+    }
+
+    pub fn is_complete(&self, len: usize) -> bool {
+        self.scanned_len >= len
+    }
+
+    /// The number of lines found so far.
+    pub fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    pub fn scanned_len(&self) -> usize {
+        self.scanned_len
+    }
+
+    /// Returns the row and start offset of the closest checkpoint at or
+    /// before the given row, or `None` if the row hasn't been indexed yet.
+    pub fn checkpoint_for_row(&self, row: usize) -> Option<(usize, usize)> {
+        if row >= self.line_count {
+            return None;
+        }
+        let checkpoint_ix = row / LINE_INDEX_STRIDE;
+        Some((
+            checkpoint_ix * LINE_INDEX_STRIDE,
+            self.checkpoints[checkpoint_ix],
+        ))
+    }
+
+    /// Returns the offset of the newline ending the line that starts at
+    /// `line_start`, if that line is at least `LONG_LINE_LEN` bytes long.
+    pub fn long_line_end(&self, line_start: usize) -> Option<usize> {
+        let ix = self
+            .long_lines
+            .binary_search_by_key(&line_start, |(start, _)| *start)
+            .ok()?;
+        Some(self.long_lines[ix].1)
+    }
+
+    /// Returns the row and start offset of the closest checkpoint at or
+    /// before the given offset, which is clamped to the indexed part of the
+    /// text.
+    pub fn checkpoint_for_offset(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.scanned_len);
+        let checkpoint_ix = self
+            .checkpoints
+            .partition_point(|&checkpoint| checkpoint <= offset)
+            .saturating_sub(1);
+        (
+            checkpoint_ix * LINE_INDEX_STRIDE,
+            self.checkpoints[checkpoint_ix],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(text: &[u8], chunk_len: usize) -> LineIndex {
+        let mut index = LineIndex::default();
+        for chunk in text.chunks(chunk_len) {
+            index.scan(chunk);
+        }
+        index
+    }
+
+    #[test]
+    fn test_scan_in_chunks() {
+        let text = b"first\r\nsecond\n\nlast";
+        for chunk_len in [1, 3, 7, text.len()] {
+            let index = index(text, chunk_len);
+            assert_eq!(index.line_count(), 4);
+            assert_eq!(index.scanned_len(), text.len());
+            assert!(index.is_complete(text.len()));
+        }
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let text = (0..LINE_INDEX_STRIDE * 3 + 10)
+            .map(|row| format!("line {row}\n"))
+            .collect::<String>();
+        let index = index(text.as_bytes(), 7);
+        let line_start = |row: usize| text.find(&format!("line {row}\n")).unwrap();
+
+        assert_eq!(index.line_count(), LINE_INDEX_STRIDE * 3 + 11);
+        assert_eq!(index.checkpoint_for_row(0), Some((0, 0)));
+        assert_eq!(
+            index.checkpoint_for_row(LINE_INDEX_STRIDE - 1),
+            Some((0, 0))
+        );
+        assert_eq!(
+            index.checkpoint_for_row(2 * LINE_INDEX_STRIDE + 5),
+            Some((2 * LINE_INDEX_STRIDE, line_start(2 * LINE_INDEX_STRIDE)))
+        );
+        assert_eq!(index.checkpoint_for_row(index.line_count()), None);
+
+        assert_eq!(
+            index.checkpoint_for_offset(line_start(LINE_INDEX_STRIDE)),
+            (LINE_INDEX_STRIDE, line_start(LINE_INDEX_STRIDE))
+        );
+        assert_eq!(
+            index.checkpoint_for_offset(line_start(LINE_INDEX_STRIDE) - 1),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn test_partial_index() {
+        let text = b"a\nb\nc\nd\n";
+        let mut index = LineIndex::default();
+
+        index.scan(&text[..3]);
+        assert!(!index.is_complete(text.len()));
+        assert_eq!(index.line_count(), 2);
+        assert_eq!(index.checkpoint_for_row(2), None);
+        assert_eq!(index.checkpoint_for_offset(text.len()), (0, 0));
+
+        index.scan(&text[3..]);
+        assert!(index.is_complete(text.len()));
+        assert_eq!(index.line_count(), 5);
+        assert_eq!(index.checkpoint_for_row(3), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_long_lines() {
+        let long_line = "a".repeat(LONG_LINE_LEN);
+        let text = format!("short\n{long_line}\r\n{long_line}");
+        let index = index(text.as_bytes(), 1000);
+
+        let long_line_start = "short\n".len();
+        let newline = long_line_start + LONG_LINE_LEN + 1;
+        assert_eq!(index.long_line_end(0), None);
+        assert_eq!(index.long_line_end(long_line_start), Some(newline));
+        // The last line is still being scanned until a newline ends it.
+        assert_eq!(index.long_line_end(newline + 1), None);
+    }
+}
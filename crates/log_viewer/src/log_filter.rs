@@ -0,0 +1,332 @@
+use std::cmp::Ordering;
+
+use anyhow::{Context as _, Result, anyhow};
+use regex::Regex;
+
+use crate::log_format::{LogFormat, LogLevel, LogLine};
+
+/// How a field predicate compares a field's value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PredicateOperator {
+    Equal,
+    NotEqual,
+    /// The value matches a regular expression.
+    Matches,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl PredicateOperator {
+    /// Operators in the order they are looked for, so that `!=`, `<=` and
+    /// `>=` aren't read as `=`, `<` or `>`.
+    const ALL: [(&str, PredicateOperator); 7] = [
+        ("!=", PredicateOperator::NotEqual),
+        ("<=", PredicateOperator::LessOrEqual),
+        (">=", PredicateOperator::GreaterOrEqual),
+        ("=", PredicateOperator::Equal),
+        ("~", PredicateOperator::Matches),
+        ("<", PredicateOperator::Less),
+        (">", PredicateOperator::Greater),
+    ];
+}
+
+/// A condition on a named field of a log entry, like `status>=500`.
+#[derive(Clone, Debug)]
+pub struct FieldPredicate {
+    pub field: String,
+    pub operator: PredicateOperator,
+    pub value: String,
+    regex: Option<Regex>,
+}
+
+impl FieldPredicate {
+    /// Parses predicates separated by whitespace. Values containing spaces
+    /// can be written in double quotes, e.g. `msg~"connection (reset|refused)"`.
+    pub fn parse_all(query: &str) -> Result<Vec<Self>> {
+        split_quoted(query)?
+            .into_iter()
+            .map(|predicate| Self::parse(&predicate))
+            .collect()
+    }
+
+    fn parse(predicate: &str) -> Result<Self> {
+        let (index, symbol, operator) = predicate
+            .char_indices()
+            .find_map(|(index, _)| {
+                PredicateOperator::ALL
+                    .iter()
+                    .find(|(symbol, _)| predicate[index..].starts_with(symbol))
+                    .map(|(symbol, operator)| (index, *symbol, *operator))
+            })
+            .with_context(|| format!("expected a comparison in {predicate:?}"))?;
+        let field = predicate[..index].to_string();
+        if field.is_empty() {
+            return Err(anyhow!("expected a field name in {predicate:?}"));
+        }
+        let value = predicate[index + symbol.len()..].to_string();
+        let regex = if operator == PredicateOperator::Matches {
+            Some(Regex::new(&value).with_context(|| format!("invalid regex in {predicate:?}"))?)
+        } else {
+            None
+        };
+        Ok(Self {
+            field,
+            operator,
+            value,
+            regex,
+        })
+    }
+
+    pub fn matches(&self, line: &LogLine) -> bool {
+        let Some(actual) = line.field(&self.field) else {
+            return self.operator == PredicateOperator::NotEqual;
+        };
+        match self.operator {
+            PredicateOperator::Equal => actual == self.value,
+            PredicateOperator::NotEqual => actual != self.value,
+            PredicateOperator::Matches => self
+                .regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(actual)),
+            PredicateOperator::Less => compare_values(actual, &self.value).is_lt(),
+            PredicateOperator::LessOrEqual => compare_values(actual, &self.value).is_le(),
+            PredicateOperator::Greater => compare_values(actual, &self.value).is_gt(),
+            PredicateOperator::GreaterOrEqual => compare_values(actual, &self.value).is_ge(),
+        }
+    }
+}
+
+/// Compares values as numbers when both are numeric, and as text otherwise,
+/// which also orders ISO 8601 timestamps correctly.
+fn compare_values(left: &str, right: &str) -> Ordering {
+    match (left.parse::<f64>(), right.parse::<f64>()) {
+        (Ok(left), Ok(right)) => left.total_cmp(&right),
+        _ => left.cmp(right),
+    }
+}
+
+/// Splits text on whitespace, keeping double-quoted parts together and
+/// removing their quotes.
+fn split_quoted(text: &str) -> Result<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quoted = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_word = true;
+            }
+            '\\' if quoted => {
+                if let Some(escaped) = chars.next() {
+                    if escaped != '"' {
+                        word.push('\\');
+                    }
+                    word.push(escaped);
+                }
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quoted {
+        return Err(anyhow!("unterminated quote"));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Which log entries are shown.
+#[derive(Clone, Debug, Default)]
+pub struct LogFilter {
+    /// Entries less severe than this level are hidden.
+    pub level: Option<LogLevel>,
+    /// Entries are hidden unless one of their lines matches this pattern.
+    pub pattern: Option<Regex>,
+    /// Entries are hidden unless all of these predicates hold.
+    pub predicates: Vec<FieldPredicate>,
+}
+
+impl LogFilter {
+    pub fn is_empty(&self) -> bool {
+        self.level.is_none() && self.pattern.is_none() && self.predicates.is_empty()
+    }
+
+    /// Returns whether each of the given lines is shown.
+    ///
+    /// Lines that don't start an entry, like the lines of a stack trace, are
+    /// shown or hidden together with the entry above them. Entries without a
+    /// level are treated as informational.
+    pub fn visible_lines(&self, format: LogFormat, lines: &[&str]) -> Vec<bool> {
+        let mut visible = vec![true; lines.len()];
+        if self.is_empty() {
+            return visible;
+        }
+
+        let mut entry_start = 0;
+        let mut entry: Option<LogLine> = None;
+        for (row, line) in lines.iter().enumerate() {
+            let parsed = format.parse_line(line);
+            if parsed.is_some() || row == 0 {
+                if row > 0 {
+                    let shown = self.entry_matches(entry.as_ref(), &lines[entry_start..row]);
+                    visible[entry_start..row].fill(shown);
+                }
+                entry_start = row;
+                entry = parsed;
+            }
+        }
+        if !lines.is_empty() {
+            let shown = self.entry_matches(entry.as_ref(), &lines[entry_start..]);
+            visible[entry_start..].fill(shown);
+        }
+        visible
+    }
+
+    fn entry_matches(&self, entry: Option<&LogLine>, lines: &[&str]) -> bool {
+        if let Some(level) = self.level {
+            let entry_level = entry
+                .and_then(|entry| entry.level)
+                .unwrap_or(LogLevel::Info);
+            if entry_level > level {
+                return false;
+            }
+        }
+        if !self.predicates.is_empty() {
+            let Some(entry) = entry else {
+                return false;
+            };
+            if !self
+                .predicates
+                .iter()
+                .all(|predicate| predicate.matches(entry))
+            {
+                return false;
+            }
+        }
+        self.pattern
+            .as_ref()
+            .is_none_or(|pattern| lines.iter().any(|line| pattern.is_match(line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(fields: &[(&str, &str)]) -> LogLine {
+        LogLine {
+            fields: fields
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            ..LogLine::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_predicates() {
+        let predicates =
+            FieldPredicate::parse_all(r#"status>=500 user!=bot msg~"conn.* (reset|refused)""#)
+                .unwrap();
+        assert_eq!(
+            predicates
+                .iter()
+                .map(|predicate| (
+                    predicate.field.as_str(),
+                    predicate.operator,
+                    predicate.value.as_str()
+                ))
+                .collect::<Vec<_>>(),
+            [
+                ("status", PredicateOperator::GreaterOrEqual, "500"),
+                ("user", PredicateOperator::NotEqual, "bot"),
+                ("msg", PredicateOperator::Matches, "conn.* (reset|refused)"),
+            ]
+        );
+
+        assert!(FieldPredicate::parse_all("status").is_err());
+        assert!(FieldPredicate::parse_all("=500").is_err());
+        assert!(FieldPredicate::parse_all("msg~(").is_err());
+        assert!(FieldPredicate::parse_all("msg=\"open").is_err());
+        assert!(FieldPredicate::parse_all("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_predicate_matches() {
+        let entry = line(&[("status", "503"), ("path", "/api/users"), ("user", "ann")]);
+        let matches = |query: &str| {
+            FieldPredicate::parse_all(query)
+                .unwrap()
+                .iter()
+                .all(|predicate| predicate.matches(&entry))
+        };
+        assert!(matches("status>=500 status<600"));
+        assert!(!matches("status>503"));
+        // Numbers are compared by value rather than as text.
+        assert!(matches("status>60"));
+        assert!(matches("path~^/api/ user=ann"));
+        assert!(!matches("path=/api"));
+        // Missing fields only satisfy inequality.
+        assert!(matches("region!=eu"));
+        assert!(!matches("region=eu"));
+    }
+
+    #[test]
+    fn test_visible_lines() {
+        let lines = [
+            "2024-05-01T10:00:00+00:00 INFO  [app] started",
+            "2024-05-01T10:00:01+00:00 ERROR [app::db] query failed",
+            "    caused by: connection reset",
+            "2024-05-01T10:00:02+00:00 DEBUG [app::db] retrying",
+            "2024-05-01T10:00:03+00:00 WARN  [app::http] slow request",
+        ];
+
+        let filter = LogFilter {
+            level: Some(LogLevel::Warn),
+            ..LogFilter::default()
+        };
+        assert_eq!(
+            filter.visible_lines(LogFormat::Zlog, &lines),
+            [false, true, true, false, true]
+        );
+
+        // A match in a continuation line shows the whole entry.
+        let filter = LogFilter {
+            pattern: Some(Regex::new("connection reset").unwrap()),
+            ..LogFilter::default()
+        };
+        assert_eq!(
+            filter.visible_lines(LogFormat::Zlog, &lines),
+            [false, true, true, false, false]
+        );
+
+        let filter = LogFilter {
+            predicates: FieldPredicate::parse_all("scope~^app::db").unwrap(),
+            ..LogFilter::default()
+        };
+        assert_eq!(
+            filter.visible_lines(LogFormat::Zlog, &lines),
+            [false, true, true, true, false]
+        );
+
+        assert_eq!(
+            LogFilter::default().visible_lines(LogFormat::Zlog, &lines),
+            [true; 5]
+        );
+    }
+}
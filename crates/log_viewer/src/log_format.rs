@@ -0,0 +1,555 @@
+use std::{ops::Range, sync::LazyLock};
+
+use regex::Regex;
+use serde_json::Value;
+
+/// How many lines from the start of a file are used to detect its format.
+pub(crate) const DETECTION_SAMPLE_LEN: usize = 64;
+/// How far into a plain text line a level keyword is looked for, so that
+/// words in the message itself are not mistaken for the level.
+const PLAIN_LEVEL_SEARCH_LEN: usize = 96;
+
+const LEVEL_KEYS: &[&str] = &["level", "lvl", "severity", "log.level", "levelname"];
+const TIMESTAMP_KEYS: &[&str] = &["time", "timestamp", "ts", "@timestamp", "datetime"];
+
+static TIMESTAMP: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\[?(\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2}(?:[.,]\d+)?)?(?:Z|[+-]\d{2}:?\d{2})?)",
+    )
+    .unwrap()
+});
+static PLAIN_LEVEL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(fatal|critical|error|err|warning|warn|info|notice|debug|trace)\b").unwrap()
+});
+static ZLOG_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:\d{2})) (ERROR|WARN|INFO|DEBUG|TRACE) +\[([^\]]*)\] ?",
+    )
+    .unwrap()
+});
+static SYSLOG_RFC3164_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:<(\d{1,3})>)?([A-Z][a-z]{2} [ \d]\d \d{2}:\d{2}:\d{2}) (\S+) ([^\s:\[]+)(?:\[(\d+)\])?: ?",
+    )
+    .unwrap()
+});
+static SYSLOG_RFC5424_LINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^<(\d{1,3})>1 (\S+) (\S+) (\S+) (\S+) (\S+) ?").unwrap());
+
+/// The severity of a log line, from the most to the least severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "Error",
+            LogLevel::Warn => "Warn",
+            LogLevel::Info => "Info",
+            LogLevel::Debug => "Debug",
+            LogLevel::Trace => "Trace",
+        }
+    }
+
+    /// Parses the level names used by common logging libraries, ignoring case.
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "fatal" | "critical" | "crit" | "panic" | "emerg" | "emergency" | "alert" | "error"
+            | "err" => Some(LogLevel::Error),
+            "warning" | "warn" => Some(LogLevel::Warn),
+            "info" | "information" | "notice" => Some(LogLevel::Info),
+            "debug" | "dbg" => Some(LogLevel::Debug),
+            "trace" | "verbose" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+
+    /// Interprets the numeric levels written by bunyan and pino.
+    fn from_number(number: i64) -> Self {
+        match number {
+            50.. => LogLevel::Error,
+            40..50 => LogLevel::Warn,
+            30..40 => LogLevel::Info,
+            20..30 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+
+    /// Interprets the severity encoded in a syslog priority value.
+    fn from_syslog_priority(priority: u32) -> Self {
+        match priority % 8 {
+            0..=3 => LogLevel::Error,
+            4 => LogLevel::Warn,
+            5 | 6 => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
+}
+
+/// The way each line of a log file is structured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON object per line.
+    JsonLines,
+    /// Space separated `key=value` pairs.
+    Logfmt,
+    /// BSD (RFC 3164) or IETF (RFC 5424) syslog lines.
+    Syslog,
+    /// The format of Zed's own log.
+    Zlog,
+    /// Free-form text, where levels and timestamps are found heuristically.
+    Plain,
+}
+
+impl LogFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogFormat::JsonLines => "JSON Lines",
+            LogFormat::Logfmt => "logfmt",
+            LogFormat::Syslog => "Syslog",
+            LogFormat::Zlog => "Zed Log",
+            LogFormat::Plain => "Plain Text",
+        }
+    }
+
+    /// Picks the format that the most of the first non-blank lines are
+    /// written in, falling back to [`LogFormat::Plain`] when no structured
+    /// format matches at least half of them.
+    pub fn detect<'a>(lines: impl IntoIterator<Item = &'a str>) -> Self {
+        let sample = lines
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .take(DETECTION_SAMPLE_LEN)
+            .collect::<Vec<_>>();
+        if sample.is_empty() {
+            return LogFormat::Plain;
+        }
+
+        [
+            LogFormat::Zlog,
+            LogFormat::JsonLines,
+            LogFormat::Syslog,
+            LogFormat::Logfmt,
+        ]
+        .into_iter()
+        .map(|format| {
+            let count = sample
+                .iter()
+                .filter(|line| format.parse_line(line).is_some())
+                .count();
+            (format, count)
+        })
+        .filter(|(_, count)| count * 2 >= sample.len())
+        // Prefer the earlier, more specific format when counts are equal.
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map_or(LogFormat::Plain, |(format, _)| format)
+    }
+
+    /// Parses a line written in this format, returning `None` if the line
+    /// doesn't start a log entry, like the lines of a stack trace.
+    pub fn parse_line(self, line: &str) -> Option<LogLine> {
+        match self {
+            LogFormat::JsonLines => parse_json_line(line),
+            LogFormat::Logfmt => parse_logfmt_line(line),
+            LogFormat::Syslog => parse_syslog_line(line),
+            LogFormat::Zlog => parse_zlog_line(line),
+            LogFormat::Plain => parse_plain_line(line),
+        }
+    }
+}
+
+/// The parts of a line that start a log entry. Ranges are byte ranges within
+/// the line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogLine {
+    pub level: Option<LogLevel>,
+    pub level_range: Option<Range<usize>>,
+    pub timestamp_range: Option<Range<usize>>,
+    /// The named fields of the entry, which field predicates are tested against.
+    pub fields: Vec<(String, String)>,
+}
+
+impl LogLine {
+    pub fn field(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn parse_zlog_line(line: &str) -> Option<LogLine> {
+    let captures = ZLOG_LINE.captures(line)?;
+    let timestamp = captures.get(1)?;
+    let level = captures.get(2)?;
+    let scope = captures.get(3)?;
+    let message = &line[captures.get(0)?.end()..];
+    Some(LogLine {
+        level: LogLevel::parse(level.as_str()),
+        level_range: Some(level.range()),
+        timestamp_range: Some(timestamp.range()),
+        fields: vec![
+            ("scope".into(), scope.as_str().into()),
+            ("message".into(), message.into()),
+        ],
+    })
+}
+
+fn parse_syslog_line(line: &str) -> Option<LogLine> {
+    let (priority, timestamp, fields, message_start) =
+        if let Some(captures) = SYSLOG_RFC5424_LINE.captures(line) {
+            let fields = [("host", 3), ("app", 4), ("pid", 5), ("msgid", 6)]
+                .into_iter()
+                .filter_map(|(key, group)| {
+                    let value = captures.get(group)?.as_str();
+                    (value != "-").then(|| (key.to_string(), value.to_string()))
+                })
+                .collect::<Vec<_>>();
+            (
+                captures.get(1),
+                captures.get(2)?,
+                fields,
+                captures.get(0)?.end(),
+            )
+        } else {
+            let captures = SYSLOG_RFC3164_LINE.captures(line)?;
+            let fields = [("host", 3), ("app", 4), ("pid", 5)]
+                .into_iter()
+                .filter_map(|(key, group)| {
+                    Some((key.to_string(), captures.get(group)?.as_str().to_string()))
+                })
+                .collect::<Vec<_>>();
+            (
+                captures.get(1),
+                captures.get(2)?,
+                fields,
+                captures.get(0)?.end(),
+            )
+        };
+
+    let message = &line[message_start..];
+    let (level, level_range) = match priority.and_then(|p| p.as_str().parse::<u32>().ok()) {
+        Some(priority) => (
+            Some(LogLevel::from_syslog_priority(priority)),
+            priority_range(line),
+        ),
+        None => match find_plain_level(message) {
+            Some((level, range)) => (
+                Some(level),
+                Some(message_start + range.start..message_start + range.end),
+            ),
+            None => (None, None),
+        },
+    };
+
+    let mut fields = fields;
+    fields.push(("message".into(), message.into()));
+    Some(LogLine {
+        level,
+        level_range,
+        timestamp_range: Some(timestamp.range()),
+        fields,
+    })
+}
+
+/// The range of the `<N>` priority prefix of a syslog line.
+fn priority_range(line: &str) -> Option<Range<usize>> {
+    line.starts_with('<')
+        .then(|| line.find('>'))
+        .flatten()
+        .map(|end| 0..end + 1)
+}
+
+fn parse_json_line(line: &str) -> Option<LogLine> {
+    if !line.trim_start().starts_with('{') {
+        return None;
+    }
+    let object = serde_json::from_str::<serde_json::Map<String, Value>>(line).ok()?;
+
+    let mut log_line = LogLine::default();
+    for key in LEVEL_KEYS {
+        if let Some(value) = object.get(*key) {
+            log_line.level = match value {
+                Value::String(level) => LogLevel::parse(level),
+                Value::Number(number) => number.as_i64().map(LogLevel::from_number),
+                _ => None,
+            };
+            if log_line.level.is_some() {
+                log_line.level_range = json_value_range(line, key);
+                break;
+            }
+        }
+    }
+    log_line.timestamp_range = TIMESTAMP_KEYS
+        .iter()
+        .find(|key| object.contains_key(**key))
+        .and_then(|key| json_value_range(line, key));
+
+    for (key, value) in object {
+        flatten_json_field(key, value, &mut log_line.fields);
+    }
+    Some(log_line)
+}
+
+/// Adds a JSON field, naming the fields of nested objects with dotted paths.
+fn flatten_json_field(key: String, value: Value, fields: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => {
+            for (nested_key, nested_value) in object {
+                flatten_json_field(format!("{key}.{nested_key}"), nested_value, fields);
+            }
+        }
+        Value::String(value) => fields.push((key, value)),
+        value => fields.push((key, value.to_string())),
+    }
+}
+
+/// Finds where the value of a top-level `key` is written in a JSON line,
+/// excluding the quotes around string values.
+fn json_value_range(line: &str, key: &str) -> Option<Range<usize>> {
+    let quoted_key = format!("\"{key}\"");
+    let key_end = line.find(&quoted_key)? + quoted_key.len();
+    let after_key = &line[key_end..];
+    let value_start = key_end + after_key.find(|c: char| c != ':' && !c.is_whitespace())?;
+    let value = &line[value_start..];
+    if let Some(string) = value.strip_prefix('"') {
+        let len = quoted_string_len(string)?;
+        Some(value_start + 1..value_start + 1 + len)
+    } else {
+        let len = value.find([',', '}']).unwrap_or(value.len());
+        Some(value_start..value_start + value[..len].trim_end().len())
+    }
+}
+
+/// The length of a string up to its closing, unescaped quote.
+fn quoted_string_len(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(index),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn parse_logfmt_line(line: &str) -> Option<LogLine> {
+    let mut pairs = Vec::new();
+    let mut bare_words = 0;
+    let mut rest = line;
+    loop {
+        let trimmed = rest.trim_start();
+        if trimmed.is_empty() {
+            break;
+        }
+        let offset = line.len() - trimmed.len();
+        let key_len = trimmed
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(trimmed.len());
+        let key = &trimmed[..key_len];
+        let after_key = &trimmed[key_len..];
+        let Some(value) = after_key.strip_prefix('=') else {
+            bare_words += 1;
+            rest = after_key;
+            continue;
+        };
+        let value_start = offset + key_len + 1;
+        let (value_range, consumed) = if let Some(string) = value.strip_prefix('"') {
+            let len = quoted_string_len(string)?;
+            (value_start + 1..value_start + 1 + len, len + 2)
+        } else {
+            let len = value.find(char::is_whitespace).unwrap_or(value.len());
+            (value_start..value_start + len, len)
+        };
+        pairs.push((key, value_range));
+        rest = &value[consumed..];
+    }
+    if pairs.len() < 2 || bare_words > pairs.len() {
+        return None;
+    }
+
+    let mut log_line = LogLine::default();
+    for (key, range) in &pairs {
+        if log_line.level.is_none() && LEVEL_KEYS.contains(key) {
+            log_line.level = LogLevel::parse(&line[range.clone()]);
+            log_line.level_range = log_line.level.map(|_| range.clone());
+        } else if log_line.timestamp_range.is_none() && TIMESTAMP_KEYS.contains(key) {
+            log_line.timestamp_range = Some(range.clone());
+        }
+    }
+    log_line.fields = pairs
+        .into_iter()
+        .map(|(key, range)| (key.to_string(), line[range].replace("\\\"", "\"")))
+        .collect();
+    Some(log_line)
+}
+
+fn parse_plain_line(line: &str) -> Option<LogLine> {
+    let timestamp_range = TIMESTAMP
+        .captures(line)
+        .and_then(|captures| captures.get(1))
+        .map(|timestamp| timestamp.range());
+    let level = find_plain_level(line);
+    if timestamp_range.is_none() && level.is_none() {
+        return None;
+    }
+    Some(LogLine {
+        level: level.as_ref().map(|(level, _)| *level),
+        level_range: level.map(|(_, range)| range),
+        timestamp_range,
+        fields: Vec::new(),
+    })
+}
+
+/// Finds the first level keyword near the start of a free-form line.
+fn find_plain_level(line: &str) -> Option<(LogLevel, Range<usize>)> {
+    let mut search_len = line.len().min(PLAIN_LEVEL_SEARCH_LEN);
+    while !line.is_char_boundary(search_len) {
+        search_len -= 1;
+    }
+    let keyword = PLAIN_LEVEL.find(&line[..search_len])?;
+    Some((LogLevel::parse(keyword.as_str())?, keyword.range()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_format() {
+        let zlog = "2024-05-01T10:00:00+02:00 INFO  [zed] opened workspace\n\
+                    2024-05-01T10:00:01+02:00 ERROR [project::lsp_store:123] failed to start\n\
+                    stack backtrace:";
+        assert_eq!(LogFormat::detect(zlog.lines()), LogFormat::Zlog);
+
+        let json = r#"{"level":"info","msg":"started","ts":1714550400}
+{"level":"warn","msg":"slow request","duration_ms":1200}"#;
+        assert_eq!(LogFormat::detect(json.lines()), LogFormat::JsonLines);
+
+        let logfmt = "time=2024-05-01T10:00:00Z level=info msg=\"server started\" port=8080\n\
+                      time=2024-05-01T10:00:05Z level=error msg=\"connection refused\"";
+        assert_eq!(LogFormat::detect(logfmt.lines()), LogFormat::Logfmt);
+
+        let syslog = "<34>Oct 11 22:14:15 mymachine su[230]: 'su root' failed for lonvick\n\
+                      Oct 11 22:14:16 mymachine sshd[231]: Accepted publickey for root";
+        assert_eq!(LogFormat::detect(syslog.lines()), LogFormat::Syslog);
+
+        let plain = "starting up\nlistening on port 8080\nERROR: lost connection";
+        assert_eq!(LogFormat::detect(plain.lines()), LogFormat::Plain);
+        assert_eq!(LogFormat::detect("".lines()), LogFormat::Plain);
+    }
+
+    #[test]
+    fn test_parse_zlog_line() {
+        let line = "2024-05-01T10:00:01+02:00 WARN  [project::lsp_store:123] server is slow";
+        let parsed = LogFormat::Zlog.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Warn));
+        assert_eq!(&line[parsed.level_range.unwrap()], "WARN");
+        assert_eq!(
+            &line[parsed.timestamp_range.unwrap()],
+            "2024-05-01T10:00:01+02:00"
+        );
+        assert_eq!(parsed.field("scope"), Some("project::lsp_store:123"));
+        assert_eq!(parsed.field("message"), Some("server is slow"));
+        assert_eq!(LogFormat::Zlog.parse_line("  at main.rs:10"), None);
+    }
+
+    #[test]
+    fn test_parse_json_line() {
+        let line = r#"{"ts": "2024-05-01T10:00:00Z", "level": "ERROR", "msg": "boom", "http": {"status": 500}}"#;
+        let parsed = LogFormat::JsonLines.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Error));
+        assert_eq!(&line[parsed.level_range.unwrap()], "ERROR");
+        assert_eq!(
+            &line[parsed.timestamp_range.unwrap()],
+            "2024-05-01T10:00:00Z"
+        );
+        assert_eq!(parsed.field("msg"), Some("boom"));
+        assert_eq!(parsed.field("http.status"), Some("500"));
+
+        let line = r#"{"level":30,"time":1714550400000,"msg":"hi"}"#;
+        let parsed = LogFormat::JsonLines.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Info));
+        assert_eq!(&line[parsed.level_range.unwrap()], "30");
+        assert_eq!(&line[parsed.timestamp_range.unwrap()], "1714550400000");
+
+        assert_eq!(LogFormat::JsonLines.parse_line("not json"), None);
+    }
+
+    #[test]
+    fn test_parse_logfmt_line() {
+        let line = r#"ts=2024-05-01T10:00:00Z lvl=warn msg="disk \"/\" almost full" used=93"#;
+        let parsed = LogFormat::Logfmt.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Warn));
+        assert_eq!(&line[parsed.level_range.unwrap()], "warn");
+        assert_eq!(
+            &line[parsed.timestamp_range.unwrap()],
+            "2024-05-01T10:00:00Z"
+        );
+        assert_eq!(parsed.field("msg"), Some(r#"disk "/" almost full"#));
+        assert_eq!(parsed.field("used"), Some("93"));
+        assert_eq!(LogFormat::Logfmt.parse_line("just some words=here"), None);
+    }
+
+    #[test]
+    fn test_parse_syslog_line() {
+        let line = "<12>Oct 11 22:14:15 mymachine kernel: disk is getting full";
+        let parsed = LogFormat::Syslog.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Warn));
+        assert_eq!(&line[parsed.level_range.unwrap()], "<12>");
+        assert_eq!(&line[parsed.timestamp_range.unwrap()], "Oct 11 22:14:15");
+        assert_eq!(parsed.field("host"), Some("mymachine"));
+        assert_eq!(parsed.field("app"), Some("kernel"));
+        assert_eq!(parsed.field("pid"), None);
+
+        let line = "Oct  1 08:00:00 host app[42]: error: could not bind";
+        let parsed = LogFormat::Syslog.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Error));
+        assert_eq!(&line[parsed.level_range.unwrap()], "error");
+        assert_eq!(parsed.field("pid"), Some("42"));
+
+        let line =
+            "<165>1 2003-10-11T22:14:15.003Z host.example.com evntslog - ID47 An application event";
+        let parsed = LogFormat::Syslog.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Info));
+        assert_eq!(
+            &line[parsed.timestamp_range.unwrap()],
+            "2003-10-11T22:14:15.003Z"
+        );
+        assert_eq!(parsed.field("app"), Some("evntslog"));
+        assert_eq!(parsed.field("pid"), None);
+        assert_eq!(parsed.field("message"), Some("An application event"));
+    }
+
+    #[test]
+    fn test_parse_plain_line() {
+        let line = "[2024-05-01 10:00:00,123] WARNING something happened";
+        let parsed = LogFormat::Plain.parse_line(line).unwrap();
+        assert_eq!(parsed.level, Some(LogLevel::Warn));
+        assert_eq!(&line[parsed.level_range.unwrap()], "WARNING");
+        assert_eq!(
+            &line[parsed.timestamp_range.unwrap()],
+            "2024-05-01 10:00:00,123"
+        );
+        assert_eq!(
+            LogFormat::Plain.parse_line("    at Object.<anonymous>"),
+            None
+        );
+    }
+}
@@ -0,0 +1,793 @@
+use std::{any::TypeId, ops::Range, sync::Arc, time::Duration};
+
+use anyhow::{Context as _, Result};
+use editor::{
+    Anchor, Editor, EditorEvent, FoldPlaceholder, HighlightKey, MultiBufferOffset,
+    MultiBufferSnapshot, actions::MoveToEnd, display_map::Crease,
+};
+use file_icons::FileIcons;
+use gpui::{
+    AnyEntity, App, Context, Entity, EventEmitter, FocusHandle, Focusable, FontWeight,
+    HighlightStyle, InteractiveElement, IntoElement, ParentElement, Render, SharedString,
+    Subscription, Task, Window,
+};
+use language::{Buffer, Point};
+use multi_buffer::{MultiBufferRow, ToPoint as _};
+use project::Project;
+use regex::Regex;
+use ui::{Tooltip, prelude::*};
+use util::paths::PathExt;
+use workspace::{
+    DetachAndPromptErr, ItemSettings, Workspace,
+    item::{Item, ItemBufferKind, ItemEvent},
+    searchable::SearchableItemHandle,
+};
+
+use crate::{
+    OpenLogView, ToggleFollowTail,
+    log_filter::{FieldPredicate, LogFilter},
+    log_format::{DETECTION_SAMPLE_LEN, LogFormat, LogLevel},
+};
+
+/// How long to wait after the file changes before parsing it again, so that
+/// a quickly growing file isn't parsed on every write.
+const REFRESH_DEBOUNCE: Duration = Duration::from_millis(100);
+/// The highlight slot used for timestamps, after the one of each level.
+const TIMESTAMP_HIGHLIGHT: usize = LogLevel::ALL.len();
+
+/// Tags the folds that hide filtered-out lines.
+struct FilteredLines;
+
+/// A read-only view of a log file that colorizes levels and timestamps and
+/// hides the entries that don't match its filter.
+///
+/// The file is shown in a regular editor, so it is reloaded as it changes on
+/// disk, and appended lines are parsed and filtered as they arrive.
+pub struct LogView {
+    editor: Entity<Editor>,
+    buffer: Entity<Buffer>,
+    level: Option<LogLevel>,
+    pattern_editor: Entity<Editor>,
+    predicates_editor: Entity<Editor>,
+    filter_error: Option<SharedString>,
+    format: Option<LogFormat>,
+    follow_tail: bool,
+    line_count: usize,
+    visible_line_count: usize,
+    analyzed: Option<AnalyzedText>,
+    refresh_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// What the last analysis found out about the text before the file's last
+/// entry, so that lines appended to the file can be analyzed on their own.
+struct AnalyzedText {
+    /// The version of the buffer that was analyzed.
+    version: clock::Global,
+    resume: Resume,
+    /// The ranges highlighted in each slot, which the highlights of appended
+    /// lines are added to.
+    highlights: Vec<Vec<Range<Anchor>>>,
+}
+
+/// Where an analysis starts parsing, and what it needs to know about the
+/// lines before that point.
+#[derive(Clone, Copy, Debug)]
+struct Resume {
+    format: LogFormat,
+    row: usize,
+    offset: usize,
+    /// How many of the rows right before `row` are hidden.
+    hidden_rows_before: usize,
+    visible_line_count_before: usize,
+}
+
+/// The result of parsing and filtering the text of a log file, or the part of
+/// it after where the previous analysis left off.
+struct LogAnalysis {
+    format: LogFormat,
+    /// Where the analyzed text starts, or `None` if the whole text was
+    /// analyzed.
+    start: Option<Anchor>,
+    /// The ranges highlighted in each slot: one per level, then timestamps.
+    highlights: Vec<Vec<Range<Anchor>>>,
+    /// Where the folds hiding filtered-out lines have been computed from.
+    refold_start: Anchor,
+    /// The ranges of the folds hiding filtered-out lines, with the number of
+    /// lines each one hides.
+    hidden_ranges: Vec<(Range<Anchor>, usize)>,
+    line_count: usize,
+    visible_line_count: usize,
+    /// Where the next analysis can start, unless the format could still
+    /// change as lines are appended.
+    next_resume: Option<Resume>,
+}
+
+impl LogView {
+    pub fn new(
+        buffer: Entity<Buffer>,
+        project: Entity<Project>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::for_buffer(buffer.clone(), Some(project), window, cx);
+            editor.set_read_only(true);
+            editor.set_show_breakpoints(false, cx);
+            editor.set_show_code_actions(false, cx);
+            editor.set_show_runnables(false, cx);
+            editor
+        });
+        let pattern_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Filter by regex…", window, cx);
+            editor
+        });
+        let predicates_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Fields, e.g. status>=500 user!=bot", window, cx);
+            editor
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &editor,
+                window,
+                |this, _, event: &EditorEvent, window, cx| {
+                    if let EditorEvent::BufferEdited = event {
+                        this.refresh(true, window, cx);
+                    }
+                    cx.emit(event.clone());
+                },
+            ),
+            cx.subscribe_in(&pattern_editor, window, Self::handle_filter_event),
+            cx.subscribe_in(&predicates_editor, window, Self::handle_filter_event),
+        ];
+
+        let mut this = Self {
+            editor,
+            buffer,
+            level: None,
+            pattern_editor,
+            predicates_editor,
+            filter_error: None,
+            format: None,
+            follow_tail: false,
+            line_count: 0,
+            visible_line_count: 0,
+            analyzed: None,
+            refresh_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.refresh(false, window, cx);
+        this
+    }
+
+    pub fn editor(&self) -> &Entity<Editor> {
+        &self.editor
+    }
+
+    pub fn set_level(
+        &mut self,
+        level: Option<LogLevel>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.level = level;
+        self.refresh(false, window, cx);
+    }
+
+    fn handle_filter_event(
+        &mut self,
+        _: &Entity<Editor>,
+        event: &EditorEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let EditorEvent::BufferEdited = event {
+            self.refresh(false, window, cx);
+        }
+    }
+
+    fn toggle_follow_tail(
+        &mut self,
+        _: &ToggleFollowTail,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.follow_tail = !self.follow_tail;
+        if self.follow_tail {
+            self.editor
+                .update(cx, |editor, cx| editor.move_to_end(&MoveToEnd, window, cx));
+        }
+        cx.notify();
+    }
+
+    fn filter(&self, cx: &App) -> Result<LogFilter> {
+        let pattern = self.pattern_editor.read(cx).text(cx);
+        let pattern = if pattern.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&pattern).context("invalid regex")?)
+        };
+        let predicates = FieldPredicate::parse_all(&self.predicates_editor.read(cx).text(cx))?;
+        Ok(LogFilter {
+            level: self.level,
+            pattern,
+            predicates,
+        })
+    }
+
+    /// Parses and filters the file again in the background, then updates the
+    /// highlights and the folds hiding filtered-out lines.
+    ///
+    /// After the buffer is edited, only the text from the start of its last
+    /// entry onward is parsed again, as long as nothing before it changed.
+    fn refresh(&mut self, buffer_edited: bool, window: &mut Window, cx: &mut Context<Self>) {
+        if !buffer_edited {
+            self.analyzed = None;
+        }
+        let filter = match self.filter(cx) {
+            Ok(filter) => {
+                self.filter_error = None;
+                filter
+            }
+            Err(error) => {
+                self.filter_error = Some(error.to_string().into());
+                cx.notify();
+                return;
+            }
+        };
+        let buffer = self.buffer.read(cx);
+        let version = buffer.version();
+        let resume = self.analyzed.as_ref().and_then(|analyzed| {
+            buffer
+                .edits_since::<usize>(&analyzed.version)
+                .all(|edit| edit.old.start >= analyzed.resume.offset)
+                .then_some(analyzed.resume)
+        });
+        let snapshot = self.editor.read(cx).buffer().read(cx).snapshot(cx);
+
+        self.refresh_task = cx.spawn_in(window, async move |this, cx| {
+            if buffer_edited {
+                cx.background_executor().timer(REFRESH_DEBOUNCE).await;
+            }
+            let analysis = cx
+                .background_spawn(async move { analyze(&snapshot, &filter, resume) })
+                .await;
+            this.update_in(cx, |this, window, cx| {
+                this.apply_analysis(analysis, version, window, cx)
+            })
+            .ok();
+        });
+    }
+
+    fn apply_analysis(
+        &mut self,
+        analysis: LogAnalysis,
+        version: clock::Global,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.format = Some(analysis.format);
+        self.line_count = analysis.line_count;
+        self.visible_line_count = analysis.visible_line_count;
+
+        let snapshot = self.editor.read(cx).buffer().read(cx).snapshot(cx);
+        let highlights = match (analysis.start, self.analyzed.take()) {
+            (Some(start), Some(analyzed)) => {
+                let mut highlights = analyzed.highlights;
+                for (ranges, new_ranges) in highlights.iter_mut().zip(analysis.highlights) {
+                    // Drop the highlights of the lines that were parsed again.
+                    let kept_len =
+                        ranges.partition_point(|range| range.start.cmp(&start, &snapshot).is_lt());
+                    ranges.truncate(kept_len);
+                    ranges.extend(new_ranges);
+                }
+                highlights
+            }
+            _ => analysis.highlights,
+        };
+        let refold_start = analysis.refold_start.to_point(&snapshot);
+
+        let styles = (0..=TIMESTAMP_HIGHLIGHT)
+            .map(|slot| highlight_style(slot, cx))
+            .collect::<Vec<_>>();
+        let creases = analysis
+            .hidden_ranges
+            .into_iter()
+            .map(|(range, line_count)| Crease::simple(range, hidden_lines_placeholder(line_count)))
+            .collect::<Vec<_>>();
+        let follow_tail = self.follow_tail;
+        self.editor.update(cx, |editor, cx| {
+            for (slot, ranges) in highlights.iter().enumerate() {
+                let key = HighlightKey::LogViewer(slot);
+                if ranges.is_empty() {
+                    editor.clear_highlights(key, cx);
+                } else {
+                    editor.highlight_text(key, ranges.clone(), styles[slot], cx);
+                }
+            }
+
+            editor.remove_folds_with_type(
+                &[refold_start..snapshot.max_point()],
+                TypeId::of::<FilteredLines>(),
+                false,
+                cx,
+            );
+            editor.fold_creases(creases, false, window, cx);
+
+            if follow_tail {
+                editor.move_to_end(&MoveToEnd, window, cx);
+            }
+        });
+        self.analyzed = analysis.next_resume.map(|resume| AnalyzedText {
+            version,
+            resume,
+            highlights,
+        });
+        cx.notify();
+    }
+
+    fn render_filter_bar(&self, cx: &Context<Self>) -> impl IntoElement {
+        let level_button = |id: usize, label: &'static str, level: Option<LogLevel>| {
+            Button::new(("log-level", id), label)
+                .label_size(LabelSize::Small)
+                .toggle_state(self.level == level)
+                .on_click(cx.listener(move |this, _, window, cx| this.set_level(level, window, cx)))
+        };
+        let input = |editor: &Entity<Editor>, icon: IconName| {
+            h_flex()
+                .flex_1()
+                .min_w_32()
+                .gap_1()
+                .px_1()
+                .rounded_sm()
+                .border_1()
+                .border_color(cx.theme().colors().border)
+                .child(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+                .child(editor.clone())
+        };
+
+        let line_count = if self.visible_line_count == self.line_count {
+            format!("{} lines", self.line_count)
+        } else {
+            format!("{} of {} lines", self.visible_line_count, self.line_count)
+        };
+        let status = match self.format {
+            Some(format) => format!("{line_count} · {}", format.label()),
+            None => line_count,
+        };
+
+        h_flex()
+            .flex_none()
+            .px_2()
+            .py_1()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .gap_0p5()
+                    .child(level_button(0, "All", None))
+                    .children(
+                        LogLevel::ALL.into_iter().enumerate().map(|(index, level)| {
+                            level_button(index + 1, level.label(), Some(level))
+                        }),
+                    ),
+            )
+            .child(input(&self.pattern_editor, IconName::Regex))
+            .child(input(&self.predicates_editor, IconName::Filter))
+            .when_some(self.filter_error.clone(), |this, error| {
+                this.child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            })
+            .child(
+                Label::new(status)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                IconButton::new("follow-tail", IconName::ArrowDown)
+                    .icon_size(IconSize::Small)
+                    .toggle_state(self.follow_tail)
+                    .tooltip(Tooltip::for_action_title("Follow Tail", &ToggleFollowTail))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        this.toggle_follow_tail(&ToggleFollowTail, window, cx)
+                    })),
+            )
+    }
+}
+
+/// Parses and filters the text from the given resume point onward, or all of
+/// it if there is none.
+fn analyze(
+    snapshot: &MultiBufferSnapshot,
+    filter: &LogFilter,
+    resume: Option<Resume>,
+) -> LogAnalysis {
+    let (start_row, start_offset) = resume.map_or((0, 0), |resume| (resume.row, resume.offset));
+    let text = snapshot
+        .text_for_range(MultiBufferOffset(start_offset)..snapshot.len())
+        .collect::<String>();
+    let lines = text.split('\n').collect::<Vec<_>>();
+    let format = match resume {
+        Some(resume) => resume.format,
+        None => LogFormat::detect(lines.iter().copied()),
+    };
+
+    let mut highlights = vec![Vec::new(); TIMESTAMP_HIGHLIGHT + 1];
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut line_start = start_offset;
+    for line in &lines {
+        line_starts.push(line_start);
+        if let Some(parsed) = format.parse_line(line) {
+            let anchor_range = |range: Range<usize>| {
+                snapshot.anchor_after(MultiBufferOffset(line_start + range.start))
+                    ..snapshot.anchor_before(MultiBufferOffset(line_start + range.end))
+            };
+            if let Some((level, range)) = parsed.level.zip(parsed.level_range) {
+                highlights[level as usize].push(anchor_range(range));
+            }
+            if let Some(range) = parsed.timestamp_range {
+                highlights[TIMESTAMP_HIGHLIGHT].push(anchor_range(range));
+            }
+        }
+        line_start += line.len() + 1;
+    }
+
+    // Folds are computed again from the start of the hidden lines right before
+    // the analyzed text, so that they merge with hidden lines in it.
+    let hidden_rows_before = resume.map_or(0, |resume| resume.hidden_rows_before);
+    let visible = filter.visible_lines(format, &lines);
+    let refold_row = start_row - hidden_rows_before;
+    let refold_visible = std::iter::repeat_n(false, hidden_rows_before)
+        .chain(visible.iter().copied())
+        .collect::<Vec<_>>();
+    let max_row = snapshot.max_point().row;
+    let line_end = |row: u32| Point::new(row, snapshot.line_len(MultiBufferRow(row)));
+    let mut hidden_ranges = Vec::new();
+    let mut index = 0;
+    while index < refold_visible.len() {
+        if refold_visible[index] {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < refold_visible.len() && !refold_visible[index] {
+            index += 1;
+        }
+        let (start_row, end_row) = ((refold_row + start) as u32, (refold_row + index) as u32 - 1);
+        // Fold from the end of the line above, so that the placeholder follows
+        // the last visible line instead of taking up a line of its own.
+        let range = if start_row > 0 {
+            line_end(start_row - 1)..line_end(end_row)
+        } else if end_row < max_row {
+            Point::zero()..Point::new(end_row + 1, 0)
+        } else {
+            Point::zero()..line_end(max_row)
+        };
+        hidden_ranges.push((
+            snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end),
+            index - start,
+        ));
+    }
+    let refold_start = if refold_row > 0 {
+        line_end(refold_row as u32 - 1)
+    } else {
+        Point::zero()
+    };
+
+    let visible_line_count_before = resume.map_or(0, |resume| resume.visible_line_count_before);
+    let visible_line_count =
+        visible_line_count_before + visible.iter().filter(|visible| **visible).count();
+
+    // Appended lines can belong to the last entry and change whether it is
+    // shown, so the next analysis starts over from it. The format has to be
+    // detected again until enough lines have been seen.
+    let format_is_final = resume.is_some()
+        || lines.iter().filter(|line| !line.trim().is_empty()).count() >= DETECTION_SAMPLE_LEN;
+    let next_resume = format_is_final.then(|| {
+        let last_entry = if filter.is_empty() {
+            lines.len() - 1
+        } else {
+            lines
+                .iter()
+                .rposition(|line| format.parse_line(line).is_some())
+                .unwrap_or(0)
+        };
+        let hidden_rows = visible[..last_entry]
+            .iter()
+            .rev()
+            .take_while(|visible| !**visible)
+            .count();
+        Resume {
+            format,
+            row: start_row + last_entry,
+            offset: line_starts[last_entry],
+            hidden_rows_before: if hidden_rows == last_entry {
+                hidden_rows + hidden_rows_before
+            } else {
+                hidden_rows
+            },
+            visible_line_count_before: visible_line_count_before
+                + visible[..last_entry]
+                    .iter()
+                    .filter(|visible| **visible)
+                    .count(),
+        }
+    });
+
+    LogAnalysis {
+        format,
+        start: resume.map(|_| snapshot.anchor_before(MultiBufferOffset(start_offset))),
+        highlights,
+        refold_start: snapshot.anchor_before(refold_start),
+        hidden_ranges,
+        line_count: start_row + lines.len(),
+        visible_line_count,
+        next_resume,
+    }
+}
+
+fn highlight_style(slot: usize, cx: &App) -> HighlightStyle {
+    let status = cx.theme().status();
+    let colors = cx.theme().colors();
+    let (color, font_weight) = match LogLevel::ALL.get(slot) {
+        Some(LogLevel::Error) => (status.error, Some(FontWeight::BOLD)),
+        Some(LogLevel::Warn) => (status.warning, Some(FontWeight::BOLD)),
+        Some(LogLevel::Info) => (status.info, None),
+        Some(LogLevel::Debug) => (status.hint, None),
+        Some(LogLevel::Trace) | None => (colors.text_muted, None),
+    };
+    HighlightStyle {
+        color: Some(color),
+        font_weight,
+        ..HighlightStyle::default()
+    }
+}
+
+fn hidden_lines_placeholder(line_count: usize) -> FoldPlaceholder {
+    let label: SharedString = if line_count == 1 {
+        "⋯ 1 hidden line".into()
+    } else {
+        format!("⋯ {line_count} hidden lines").into()
+    };
+    FoldPlaceholder {
+        render: Arc::new(move |_, _, cx| {
+            div()
+                .ml_2()
+                .px_1()
+                .rounded_xs()
+                .bg(cx.theme().colors().ghost_element_hover)
+                .child(
+                    Label::new(label.clone())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .into_any_element()
+        }),
+        constrain_width: false,
+        merge_adjacent: false,
+        type_tag: Some(TypeId::of::<FilteredLines>()),
+        collapsed_text: None,
+    }
+}
+
+impl EventEmitter<EditorEvent> for LogView {}
+
+impl Focusable for LogView {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for LogView {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("LogView")
+            .on_action(cx.listener(Self::toggle_follow_tail))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_filter_bar(cx))
+            .child(div().flex_1().min_h_0().child(self.editor.clone()))
+    }
+}
+
+impl Item for LogView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: &mut dyn FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        let file = self.buffer.read(cx).file()?;
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(&file.full_path(cx), cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.buffer
+            .read(cx)
+            .file()
+            .map(|file| file.file_name(cx).to_string().into())
+            .unwrap_or_else(|| "untitled".into())
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        let file = self.buffer.read(cx).file()?;
+        Some(
+            file.full_path(cx)
+                .compact()
+                .to_string_lossy()
+                .into_owned()
+                .into(),
+        )
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Log View Opened")
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+
+    fn as_searchable(&self, _: &Entity<Self>, _: &App) -> Option<Box<dyn SearchableItemHandle>> {
+        Some(Box::new(self.editor.clone()))
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.buffer.entity_id(), self.buffer.read(cx))
+    }
+
+    fn buffer_kind(&self, _: &App) -> ItemBufferKind {
+        ItemBufferKind::Singleton
+    }
+}
+
+/// Opens the file of the active item in a log view, or activates the log view
+/// that already shows it.
+pub(crate) fn open_log_view(
+    workspace: &mut Workspace,
+    _: &OpenLogView,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(item) = workspace.active_item(cx) else {
+        return;
+    };
+    if item.downcast::<LogView>().is_some() {
+        return;
+    }
+    let Some(project_path) = item.project_path(cx) else {
+        return;
+    };
+
+    let existing = workspace.items_of_type::<LogView>(cx).find(|view| {
+        project::ProjectItem::project_path(view.read(cx).buffer.read(cx), cx).as_ref()
+            == Some(&project_path)
+    });
+    if let Some(existing) = existing {
+        workspace.activate_item(&existing, true, true, window, cx);
+        return;
+    }
+
+    let project = workspace.project().clone();
+    let open = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+    cx.spawn_in(window, async move |workspace, cx| {
+        let buffer = open.await?;
+        workspace.update_in(cx, |workspace, window, cx| {
+            let view = cx.new(|cx| LogView::new(buffer, project, window, cx));
+            workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+        })
+    })
+    .detach_and_prompt_err("Failed to open log view", window, cx, |_, _, _| None);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+    use workspace::AppState;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+
+    fn log_lines(rows: Range<usize>) -> String {
+        rows.map(|row| {
+            if row % 10 == 0 {
+                format!("2024-05-01T10:00:00+02:00 ERROR [app] failure {row}\n  at main.rs:{row}\n")
+            } else {
+                format!("2024-05-01T10:00:00+02:00 INFO  [app] request {row}\n")
+            }
+        })
+        .collect()
+    }
+
+    #[gpui::test]
+    async fn test_follow_appended_lines(cx: &mut TestAppContext) {
+        init_test(cx);
+        let initial_text = log_lines(0..80);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(path!("/dir"), json!({ "app.log": initial_text }))
+            .await;
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/app.log"), cx)
+            })
+            .await
+            .unwrap();
+        let (view, cx) =
+            cx.add_window_view(|window, cx| LogView::new(buffer, project.clone(), window, cx));
+        view.update_in(cx, |view, window, cx| {
+            view.set_level(Some(LogLevel::Error), window, cx);
+            view.toggle_follow_tail(&ToggleFollowTail, window, cx);
+        });
+        cx.run_until_parked();
+
+        // Each error is two lines, followed by nine info lines, and the text
+        // ends with an empty line.
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.format, Some(LogFormat::Zlog));
+            assert_eq!((view.visible_line_count, view.line_count), (16, 89));
+            assert_eq!(view.analyzed.as_ref().unwrap().resume.row, 87);
+        });
+
+        fs.insert_file(
+            path!("/dir/app.log"),
+            (initial_text + &log_lines(80..95)).into_bytes(),
+        )
+        .await;
+        cx.run_until_parked();
+        cx.executor().advance_clock(REFRESH_DEBOUNCE);
+        cx.run_until_parked();
+
+        view.update(cx, |view, cx| {
+            assert_eq!((view.visible_line_count, view.line_count), (20, 106));
+            assert_eq!(view.analyzed.as_ref().unwrap().resume.row, 104);
+
+            view.editor.update(cx, |editor, cx| {
+                let display_text = editor.display_text(cx);
+                assert!(display_text.contains("failure 90\n  at main.rs:90"));
+                assert!(!display_text.contains("request 91"));
+                let error_highlights = editor
+                    .text_highlights(HighlightKey::LogViewer(LogLevel::Error as usize), cx)
+                    .map_or(0, |(_, ranges)| ranges.len());
+                assert_eq!(error_highlights, 10);
+
+                // The cursor follows the appended lines.
+                let snapshot = editor.display_snapshot(cx);
+                let cursor = editor.selections.newest::<Point>(&snapshot).head();
+                assert_eq!(cursor, snapshot.buffer_snapshot().max_point());
+            });
+        });
+    }
+}
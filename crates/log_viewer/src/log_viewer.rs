@@ -0,0 +1,28 @@
+mod log_filter;
+mod log_format;
+mod log_view;
+
+use gpui::{App, actions};
+use workspace::Workspace;
+
+pub use crate::log_filter::{FieldPredicate, LogFilter, PredicateOperator};
+pub use crate::log_format::{LogFormat, LogLevel, LogLine};
+pub use crate::log_view::LogView;
+
+actions!(
+    log_viewer,
+    [
+        /// Opens the file of the active item in a log view, which highlights
+        /// levels and timestamps and can filter entries.
+        OpenLogView,
+        /// Keeps the log view scrolled to the end as lines are appended.
+        ToggleFollowTail,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(log_view::open_log_view);
+    })
+    .detach();
+}
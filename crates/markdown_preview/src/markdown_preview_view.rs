@@ -1780,6 +1780,7 @@ impl SearchableItem for MarkdownPreviewView {
             replacement: false,
             selection: false,
             select_all: false,
+            syntax_scope: false,
             find_in_results: false,
         }
     }
@@ -0,0 +1,360 @@
+//! A panel listing the ports listened on by processes on the remote host of a
+//! remote project, which forwards them to the local machine on request.
+
+mod ports_panel_settings;
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use gpui::{
+    AnyElement, App, AsyncWindowContext, Entity, EventEmitter, FocusHandle, Focusable, Pixels,
+    Subscription, WeakEntity, Window, actions, prelude::*,
+};
+use project::{
+    Fs, Project,
+    port_forward_store::{ListeningPort, PortForwardStore, PortForwardStoreEvent},
+};
+use settings::Settings;
+use ui::{ListHeader, ListItem, Tooltip, prelude::*};
+use workspace::{
+    Workspace,
+    dock::{DockPosition, Panel, PanelEvent},
+    notifications::{NotificationId, simple_message_notification::MessageNotification},
+};
+
+pub use crate::ports_panel_settings::PortsPanelSettings;
+
+const PORTS_PANEL_KEY: &str = "PortsPanel";
+
+actions!(
+    ports_panel,
+    [
+        /// Toggles focus on the ports panel.
+        ToggleFocus,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, window, cx| {
+            workspace.toggle_panel_focus::<PortsPanel>(window, cx);
+        });
+    })
+    .detach();
+}
+
+pub struct PortsPanel {
+    fs: Arc<dyn Fs>,
+    port_forward_store: Option<Entity<PortForwardStore>>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl PortsPanel {
+    pub async fn load(
+        workspace: WeakEntity<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> Result<Entity<Self>> {
+        workspace.update_in(&mut cx, |workspace, _, cx| {
+            let fs = workspace.app_state().fs.clone();
+            let project = workspace.project().clone();
+            let workspace = cx.weak_entity();
+            cx.new(|cx| Self::new(fs, project, workspace, cx))
+        })
+    }
+
+    fn new(
+        fs: Arc<dyn Fs>,
+        project: Entity<Project>,
+        workspace: WeakEntity<Workspace>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let port_forward_store = project.read(cx).port_forward_store();
+        let mut subscriptions = Vec::new();
+        if let Some(port_forward_store) = &port_forward_store {
+            subscriptions.push(cx.observe(port_forward_store, |_, _, cx| cx.notify()));
+            subscriptions.push(cx.subscribe(
+                port_forward_store,
+                move |_, port_forward_store, event, cx| match event {
+                    PortForwardStoreEvent::PortDetected(port) => {
+                        if PortsPanelSettings::get_global(cx).notify_on_new_ports {
+                            offer_to_forward(&workspace, port_forward_store, port, cx);
+                        }
+                    }
+                },
+            ));
+        }
+
+        Self {
+            fs,
+            port_forward_store,
+            focus_handle: cx.focus_handle(),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn render_forwards(
+        &self,
+        port_forward_store: &Entity<PortForwardStore>,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        port_forward_store
+            .read(cx)
+            .forwards()
+            .iter()
+            .map(|forward| {
+                let remote_port = forward.remote_port;
+                let local_port = forward.local_port;
+                ListItem::new(("forward", remote_port as usize))
+                    .child(port_label(
+                        remote_port,
+                        format!("localhost:{local_port}"),
+                        forward.process_name.clone(),
+                    ))
+                    .end_slot(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                IconButton::new(
+                                    ("open-in-browser", remote_port as usize),
+                                    IconName::ArrowUpRight,
+                                )
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Open in Browser"))
+                                .on_click(move |_, _, cx| open_in_browser(local_port, cx)),
+                            )
+                            .child(
+                                IconButton::new(
+                                    ("stop-forwarding", remote_port as usize),
+                                    IconName::Close,
+                                )
+                                .icon_size(IconSize::Small)
+                                .tooltip(Tooltip::text("Stop Forwarding"))
+                                .on_click({
+                                    let port_forward_store = port_forward_store.clone();
+                                    move |_, _, cx| {
+                                        port_forward_store.update(cx, |store, cx| {
+                                            store.stop_forwarding(remote_port, cx)
+                                        })
+                                    }
+                                }),
+                            ),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_listening_ports(
+        &self,
+        port_forward_store: &Entity<PortForwardStore>,
+        cx: &mut Context<Self>,
+    ) -> Vec<AnyElement> {
+        let store = port_forward_store.read(cx);
+        store
+            .listening_ports()
+            .iter()
+            .filter(|port| store.forward(port.port).is_none())
+            .map(|port| {
+                let remote_port = port.port;
+                ListItem::new(("listening-port", remote_port as usize))
+                    .child(port_label(
+                        remote_port,
+                        port.host.to_string(),
+                        port.process_name.clone(),
+                    ))
+                    .end_slot(
+                        IconButton::new(("forward", remote_port as usize), IconName::ArrowRight)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Forward Port"))
+                            .on_click({
+                                let port_forward_store = port_forward_store.clone();
+                                move |_, _, cx| {
+                                    port_forward_store
+                                        .update(cx, |store, cx| store.forward_port(remote_port, cx))
+                                        .detach_and_log_err(cx);
+                                }
+                            }),
+                    )
+                    .into_any_element()
+            })
+            .collect()
+    }
+}
+
+fn port_label(port: u16, address: String, process_name: Option<Arc<str>>) -> impl IntoElement {
+    h_flex()
+        .gap_2()
+        .child(Label::new(port.to_string()))
+        .child(
+            Label::new(address)
+                .size(LabelSize::Small)
+                .color(Color::Muted),
+        )
+        .when_some(process_name, |this, process_name| {
+            this.child(
+                Label::new(process_name)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .truncate(),
+            )
+        })
+}
+
+fn open_in_browser(local_port: u16, cx: &App) {
+    cx.open_url(&format!("http://localhost:{local_port}"));
+}
+
+/// Shows a notification offering to forward a port that started listening on
+/// the remote host.
+fn offer_to_forward(
+    workspace: &WeakEntity<Workspace>,
+    port_forward_store: Entity<PortForwardStore>,
+    port: &ListeningPort,
+    cx: &mut App,
+) {
+    struct PortDetectedNotification;
+
+    let remote_port = port.port;
+    let message = match &port.process_name {
+        Some(process_name) => {
+            format!("{process_name} is listening on port {remote_port} of the remote host.")
+        }
+        None => format!("A process is listening on port {remote_port} of the remote host."),
+    };
+    let notification_id =
+        NotificationId::composite::<PortDetectedNotification>(remote_port.to_string());
+    workspace
+        .update(cx, |workspace, cx| {
+            workspace.show_notification(notification_id, cx, |cx| {
+                cx.new(|cx| {
+                    MessageNotification::new(message, cx)
+                        .primary_message("Forward and Open in Browser")
+                        .primary_icon(IconName::ArrowUpRight)
+                        .primary_on_click({
+                            let port_forward_store = port_forward_store.clone();
+                            move |_, cx| {
+                                let forward = port_forward_store
+                                    .update(cx, |store, cx| store.forward_port(remote_port, cx));
+                                cx.spawn(async move |_, cx| {
+                                    let local_port = forward.await?;
+                                    cx.update(|cx| open_in_browser(local_port, cx));
+                                    anyhow::Ok(())
+                                })
+                                .detach_and_log_err(cx);
+                            }
+                        })
+                        .secondary_message("Forward Port")
+                        .secondary_on_click(move |_, cx| {
+                            port_forward_store
+                                .update(cx, |store, cx| store.forward_port(remote_port, cx))
+                                .detach_and_log_err(cx);
+                        })
+                })
+            })
+        })
+        .ok();
+}
+
+impl EventEmitter<PanelEvent> for PortsPanel {}
+
+impl Focusable for PortsPanel {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for PortsPanel {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let panel = v_flex()
+            .id("ports-panel")
+            .key_context("PortsPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .overflow_y_scroll()
+            .bg(cx.theme().colors().panel_background);
+        let Some(port_forward_store) = self.port_forward_store.clone() else {
+            return panel;
+        };
+
+        let forwards = self.render_forwards(&port_forward_store, cx);
+        let listening_ports = self.render_listening_ports(&port_forward_store, cx);
+        if forwards.is_empty() && listening_ports.is_empty() {
+            return panel.child(v_flex().size_full().items_center().justify_center().child(
+                Label::new("No ports are listening on the remote host.").color(Color::Muted),
+            ));
+        }
+
+        panel
+            .p_1()
+            .when(!forwards.is_empty(), |this| {
+                this.child(ListHeader::new("Forwarded Ports"))
+                    .children(forwards)
+            })
+            .when(!listening_ports.is_empty(), |this| {
+                this.child(ListHeader::new("Listening Ports"))
+                    .children(listening_ports)
+            })
+    }
+}
+
+impl Panel for PortsPanel {
+    fn persistent_name() -> &'static str {
+        "PortsPanel"
+    }
+
+    fn panel_key() -> &'static str {
+        PORTS_PANEL_KEY
+    }
+
+    fn position(&self, _window: &Window, cx: &App) -> DockPosition {
+        PortsPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(
+        &mut self,
+        position: DockPosition,
+        _window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        settings::update_settings_file(self.fs.clone(), cx, move |settings, _| {
+            settings.ports_panel.get_or_insert_default().dock = Some(position.into())
+        });
+    }
+
+    fn default_size(&self, _window: &Window, cx: &App) -> Pixels {
+        PortsPanelSettings::get_global(cx).default_width
+    }
+
+    fn icon(&self, _window: &Window, cx: &App) -> Option<IconName> {
+        PortsPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Server)
+    }
+
+    fn icon_tooltip(&self, _window: &Window, _cx: &App) -> Option<&'static str> {
+        Some("Ports Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn activation_priority(&self) -> u32 {
+        8
+    }
+
+    fn enabled(&self, _cx: &App) -> bool {
+        self.port_forward_store.is_some()
+    }
+
+    fn hide_button_setting(&self, _: &App) -> Option<workspace::HideStatusItem> {
+        Some(workspace::HideStatusItem::new(|settings| {
+            settings.ports_panel.get_or_insert_default().button = Some(false);
+        }))
+    }
+}
@@ -0,0 +1,24 @@
+use gpui::Pixels;
+use settings::{IntoGpui, RegisterSetting, Settings};
+use workspace::dock::DockPosition;
+
+#[derive(Debug, RegisterSetting)]
+pub struct PortsPanelSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+    pub notify_on_new_ports: bool,
+}
+
+impl Settings for PortsPanelSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let panel = content.ports_panel.as_ref().unwrap();
+
+        Self {
+            button: panel.button.unwrap(),
+            dock: panel.dock.unwrap().into(),
+            default_width: panel.default_width.unwrap().into_gpui(),
+            notify_on_new_ports: panel.notify_on_new_ports.unwrap(),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     ProjectPath,
     lsp_store::OpenLspBufferHandle,
+    search::SearchQuery,
     worktree_store::{WorktreeStore, WorktreeStoreEvent},
 };
 use anyhow::{Context as _, Result, anyhow};
@@ -46,8 +47,8 @@ pub struct BufferStore {
 
 #[derive(Default)]
 struct RemoteProjectSearchState {
-    // List of ongoing project search chunks from our remote host. Used by the side issuing a search RPC request.
-    chunks: HashMap<u64, async_channel::Sender<BufferId>>,
+    // List of ongoing project search chunks from our remote host, along with the query they are for. Used by the side issuing a search RPC request.
+    chunks: HashMap<u64, (async_channel::Sender<BufferId>, Arc<SearchQuery>)>,
     // Monotonously-increasing handle to hand out to remote host in order to identify the project search result chunk.
     next_id: u64,
     // Used by the side running the actual search for match candidates to potentially cancel the search prematurely.
@@ -1766,10 +1767,11 @@ impl BufferStore {
 
     pub(crate) fn register_project_search_result_handle(
         &mut self,
+        query: Arc<SearchQuery>,
     ) -> (u64, async_channel::Receiver<BufferId>) {
         let (tx, rx) = async_channel::unbounded();
         let handle = util::post_inc(&mut self.project_search.next_id);
-        let _old_entry = self.project_search.chunks.insert(handle, tx);
+        let _old_entry = self.project_search.chunks.insert(handle, (tx, query));
         debug_assert!(_old_entry.is_none());
         (handle, rx)
     }
@@ -1816,15 +1818,22 @@ impl BufferStore {
                 .into_iter()
                 .filter_map(|buffer_id| BufferId::new(buffer_id).ok())
                 .collect::<Vec<_>>(),
-            Variant::Done(_) => {
+            Variant::Done(done) => {
                 this.update(&mut cx, |this, _| {
-                    this.project_search.chunks.remove(&handle)
+                    if let Some((_, query)) = this.project_search.chunks.remove(&handle)
+                        && done.pattern_too_complex
+                    {
+                        query.set_exceeded_backtrack_limit();
+                    }
                 });
                 return Ok(proto::Ack {});
             }
         };
         let Some(sender) = this.read_with(&mut cx, |this, _| {
-            this.project_search.chunks.get(&handle).cloned()
+            this.project_search
+                .chunks
+                .get(&handle)
+                .map(|(sender, _)| sender.clone())
         }) else {
             return Ok(proto::Ack {});
         };
@@ -17,6 +17,7 @@ pub mod dap_store;
 pub mod locators;
 pub mod memory;
 pub mod session;
+pub mod shared_session;
 
 #[cfg(any(feature = "test-support", test))]
 pub mod test;
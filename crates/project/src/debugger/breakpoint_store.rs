@@ -213,7 +213,14 @@ impl BreakpointStore {
         message: TypedEnvelope<proto::BreakpointsForFile>,
         mut cx: AsyncApp,
     ) -> Result<()> {
+        // The host removed the last breakpoint of the file.
         if message.payload.breakpoints.is_empty() {
+            this.update(&mut cx, |this, cx| {
+                let path = Arc::<Path>::from(message.payload.path.as_ref());
+                if this.breakpoints.remove(&path).is_some() {
+                    cx.notify();
+                }
+            });
             return Ok(());
         }
 
@@ -276,6 +283,10 @@ impl BreakpointStore {
         message: TypedEnvelope<proto::ToggleBreakpoint>,
         mut cx: AsyncApp,
     ) -> Result<proto::Ack> {
+        let edit_action = BreakpointEditAction::from_proto(
+            message.payload.edit_kind(),
+            message.payload.edit_text.clone(),
+        );
         let path = this
             .update(&mut cx, |this, cx| {
                 this.worktree_store
@@ -301,7 +312,6 @@ impl BreakpointStore {
         .context("Anchor deserialization failed")?;
         let breakpoint =
             Breakpoint::from_proto(breakpoint).context("Could not deserialize breakpoint")?;
-
         this.update(&mut cx, |this, cx| {
             this.toggle_breakpoint(
                 buffer,
@@ -309,7 +319,7 @@ impl BreakpointStore {
                     position,
                     bp: breakpoint,
                 },
-                BreakpointEditAction::Toggle,
+                edit_action,
                 cx,
             );
         });
@@ -438,6 +448,7 @@ impl BreakpointStore {
             breakpoint_set.breakpoints = breakpoints;
         }
 
+        let (edit_kind, edit_text) = edit_action.to_proto();
         match edit_action {
             BreakpointEditAction::Toggle => {
                 let len_before = breakpoint_set.breakpoints.len();
@@ -581,6 +592,8 @@ impl BreakpointStore {
                     project_id: remote.upstream_project_id,
                     path: abs_path.to_string_lossy().into_owned(),
                     breakpoint: Some(breakpoint),
+                    edit_kind: edit_kind.into(),
+                    edit_text,
                 }))
                 .detach();
             }
@@ -934,6 +947,38 @@ pub enum BreakpointEditAction {
     EditHitCondition(BreakpointMessage),
 }
 
+impl BreakpointEditAction {
+    fn to_proto(&self) -> (proto::BreakpointEditKind, Option<String>) {
+        match self {
+            Self::Toggle => (proto::BreakpointEditKind::Toggle, None),
+            Self::InvertState => (proto::BreakpointEditKind::InvertState, None),
+            Self::EditLogMessage(message) => (
+                proto::BreakpointEditKind::EditLogMessage,
+                Some(message.to_string()),
+            ),
+            Self::EditCondition(condition) => (
+                proto::BreakpointEditKind::EditCondition,
+                Some(condition.to_string()),
+            ),
+            Self::EditHitCondition(hit_condition) => (
+                proto::BreakpointEditKind::EditHitCondition,
+                Some(hit_condition.to_string()),
+            ),
+        }
+    }
+
+    fn from_proto(kind: proto::BreakpointEditKind, text: Option<String>) -> Self {
+        let text = || BreakpointMessage::from(text.unwrap_or_default());
+        match kind {
+            proto::BreakpointEditKind::Toggle => Self::Toggle,
+            proto::BreakpointEditKind::InvertState => Self::InvertState,
+            proto::BreakpointEditKind::EditLogMessage => Self::EditLogMessage(text()),
+            proto::BreakpointEditKind::EditCondition => Self::EditCondition(text()),
+            proto::BreakpointEditKind::EditHitCondition => Self::EditHitCondition(text()),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BreakpointState {
     Enabled,
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use anyhow::{Context as _, Ok, Result};
 use base64::Engine;
@@ -1038,6 +1038,69 @@ impl DapCommand for RestartStackFrameCommand {
     }
 }
 
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct GotoTargetsCommand {
+    pub path: Arc<Path>,
+    pub line: u64,
+}
+
+impl LocalDapCommand for GotoTargetsCommand {
+    type Response = Vec<dap::GotoTarget>;
+    type DapRequest = dap::requests::GotoTargets;
+
+    fn is_supported(capabilities: &Capabilities) -> bool {
+        capabilities
+            .supports_goto_targets_request
+            .unwrap_or_default()
+    }
+
+    fn to_dap(&self) -> <Self::DapRequest as dap::requests::Request>::Arguments {
+        dap::GotoTargetsArguments {
+            source: super::session::client_source(&self.path),
+            line: self.line,
+            column: None,
+        }
+    }
+
+    fn response_from_dap(
+        &self,
+        message: <Self::DapRequest as dap::requests::Request>::Response,
+    ) -> Result<Self::Response> {
+        Ok(message.targets)
+    }
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub(crate) struct GotoCommand {
+    pub thread_id: i64,
+    pub target_id: u64,
+}
+
+impl LocalDapCommand for GotoCommand {
+    type Response = <dap::requests::Goto as dap::requests::Request>::Response;
+    type DapRequest = dap::requests::Goto;
+
+    fn is_supported(capabilities: &Capabilities) -> bool {
+        capabilities
+            .supports_goto_targets_request
+            .unwrap_or_default()
+    }
+
+    fn to_dap(&self) -> <Self::DapRequest as dap::requests::Request>::Arguments {
+        dap::GotoArguments {
+            thread_id: self.thread_id,
+            target_id: self.target_id,
+        }
+    }
+
+    fn response_from_dap(
+        &self,
+        _message: <Self::DapRequest as dap::requests::Request>::Response,
+    ) -> Result<Self::Response> {
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub(crate) struct ModulesCommand;
 
@@ -1792,6 +1855,45 @@ impl LocalDapCommand for SetDataBreakpointsCommand {
     }
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct SetInstructionBreakpointsCommand {
+    pub instruction_references: Vec<String>,
+}
+
+impl LocalDapCommand for SetInstructionBreakpointsCommand {
+    type Response = Vec<dap::Breakpoint>;
+    type DapRequest = dap::requests::SetInstructionBreakpoints;
+
+    fn is_supported(capabilities: &Capabilities) -> bool {
+        capabilities
+            .supports_instruction_breakpoints
+            .unwrap_or_default()
+    }
+
+    fn to_dap(&self) -> <Self::DapRequest as dap::requests::Request>::Arguments {
+        dap::SetInstructionBreakpointsArguments {
+            breakpoints: self
+                .instruction_references
+                .iter()
+                .map(|instruction_reference| dap::InstructionBreakpoint {
+                    instruction_reference: instruction_reference.clone(),
+                    offset: None,
+                    condition: None,
+                    hit_condition: None,
+                    mode: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn response_from_dap(
+        &self,
+        message: <Self::DapRequest as dap::requests::Request>::Response,
+    ) -> Result<Self::Response> {
+        Ok(message.breakpoints)
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq)]
 pub(super) enum SetExceptionBreakpoints {
     Plain {
@@ -1973,3 +2075,39 @@ impl LocalDapCommand for dap::WriteMemoryArguments {
         Ok(message)
     }
 }
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub(crate) struct DisassembleCommand {
+    pub(crate) memory_reference: String,
+    pub(crate) instruction_offset: i64,
+    pub(crate) instruction_count: u64,
+}
+
+impl LocalDapCommand for DisassembleCommand {
+    type Response = Arc<[dap::DisassembledInstruction]>;
+    type DapRequest = dap::requests::Disassemble;
+    const CACHEABLE: bool = true;
+
+    fn is_supported(capabilities: &Capabilities) -> bool {
+        capabilities
+            .supports_disassemble_request
+            .unwrap_or_default()
+    }
+
+    fn to_dap(&self) -> <Self::DapRequest as dap::requests::Request>::Arguments {
+        dap::DisassembleArguments {
+            memory_reference: self.memory_reference.clone(),
+            offset: None,
+            instruction_offset: Some(self.instruction_offset),
+            instruction_count: self.instruction_count,
+            resolve_symbols: Some(true),
+        }
+    }
+
+    fn response_from_dap(
+        &self,
+        message: <Self::DapRequest as dap::requests::Request>::Response,
+    ) -> Result<Self::Response> {
+        Ok(message.instructions.into())
+    }
+}
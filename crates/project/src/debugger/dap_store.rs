@@ -2,7 +2,8 @@ use super::{
     breakpoint_store::BreakpointStore,
     dap_command::EvaluateCommand,
     locators,
-    session::{self, Session, SessionStateEvent},
+    session::{self, OutputToken, Session, SessionEvent, SessionStateEvent, ThreadId},
+    shared_session::{self, CollabMode},
 };
 use remote::Interactive;
 
@@ -22,7 +23,7 @@ use dap::{
     },
     client::SessionId,
     inline_value::VariableLookupKind,
-    messages::Message,
+    messages::{Events, Message},
 };
 use fs::{Fs, RemoveOptions};
 use futures::{
@@ -30,7 +31,10 @@ use futures::{
     channel::mpsc::{self, UnboundedSender},
     future::{Shared, join_all},
 };
-use gpui::{App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Task, TaskExt};
+use gpui::{
+    App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Subscription, Task,
+    TaskExt,
+};
 use http_client::HttpClient;
 use language::{Buffer, LanguageToolchainStore};
 use node_runtime::NodeRuntime;
@@ -66,12 +70,23 @@ pub enum DapStoreEvent {
     },
     Notification(String),
     RemoteHasInitialized,
+    /// Asks the debug panel to start `scenario` on behalf of a caller without a
+    /// window, such as the agent. The new session is sent through `sender`.
+    SessionRequested {
+        scenario: DebugScenario,
+        task_context: SharedTaskContext,
+        worktree_id: Option<WorktreeId>,
+        sender: mpsc::Sender<Entity<Session>>,
+    },
+    /// The host of the shared project started a debug session, which this
+    /// guest can now follow.
+    HostSessionShared(Entity<Session>),
 }
 
 enum DapStoreMode {
     Local(LocalDapStore),
     Remote(RemoteDapStore),
-    Collab,
+    Collab(CollabDapStore),
 }
 
 pub struct LocalDapStore {
@@ -91,12 +106,29 @@ pub struct RemoteDapStore {
     http_client: Arc<dyn HttpClient>,
 }
 
+pub struct CollabDapStore {
+    upstream_client: AnyProtoClient,
+    upstream_project_id: u64,
+}
+
+/// A session of this host, which the guests of the project see while it is
+/// shared.
+struct HostedSession {
+    /// Whether the adapter is initialized, so the guests can be sent the
+    /// session.
+    ready: bool,
+    guests_can_control: bool,
+    sent_output: OutputToken,
+    _subscriptions: [Subscription; 2],
+}
+
 pub struct DapStore {
     mode: DapStoreMode,
     downstream_client: Option<(AnyProtoClient, u64)>,
     breakpoint_store: Entity<BreakpointStore>,
     worktree_store: Entity<WorktreeStore>,
     sessions: BTreeMap<SessionId, Entity<Session>>,
+    hosted_sessions: HashMap<SessionId, HostedSession>,
     next_session_id: u32,
     adapter_options: BTreeMap<DebugAdapterName, Arc<PersistedAdapterOptions>>,
 }
@@ -128,6 +160,11 @@ impl DapStore {
         client.add_entity_request_handler(Self::handle_run_debug_locator);
         client.add_entity_request_handler(Self::handle_get_debug_adapter_binary);
         client.add_entity_message_handler(Self::handle_log_to_debug_console);
+        client.add_entity_message_handler(Self::handle_share_debug_session);
+        client.add_entity_message_handler(Self::handle_update_debug_session);
+        client.add_entity_message_handler(Self::handle_unshare_debug_session);
+        client.add_entity_request_handler(Self::handle_inspect_debug_session);
+        client.add_entity_request_handler(Self::handle_control_debug_session);
     }
 
     #[expect(clippy::too_many_arguments)]
@@ -176,20 +213,19 @@ impl DapStore {
     }
 
     pub fn new_collab(
-        _project_id: u64,
-        _upstream_client: AnyProtoClient,
+        project_id: u64,
+        upstream_client: AnyProtoClient,
         breakpoint_store: Entity<BreakpointStore>,
         worktree_store: Entity<WorktreeStore>,
         fs: Arc<dyn Fs>,
         cx: &mut Context<Self>,
     ) -> Self {
-        Self::new(
-            DapStoreMode::Collab,
-            breakpoint_store,
-            worktree_store,
-            fs,
-            cx,
-        )
+        let mode = DapStoreMode::Collab(CollabDapStore {
+            upstream_client,
+            upstream_project_id: project_id,
+        });
+
+        Self::new(mode, breakpoint_store, worktree_store, fs, cx)
     }
 
     fn new(
@@ -236,6 +272,7 @@ impl DapStore {
             breakpoint_store,
             worktree_store,
             sessions: Default::default(),
+            hosted_sessions: Default::default(),
             adapter_options: Default::default(),
         }
     }
@@ -363,9 +400,9 @@ impl DapStore {
                     })
                 })
             }
-            DapStoreMode::Collab => {
-                Task::ready(Err(anyhow!("Debugging is not yet supported via collab")))
-            }
+            DapStoreMode::Collab(_) => Task::ready(Err(anyhow!(
+                "Only the host of a shared project can start debug sessions"
+            ))),
         }
     }
 
@@ -434,9 +471,9 @@ impl DapStore {
                     DebugRequest::from_proto(response)
                 })
             }
-            DapStoreMode::Collab => {
-                Task::ready(Err(anyhow!("Debugging is not yet supported via collab")))
-            }
+            DapStoreMode::Collab(_) => Task::ready(Err(anyhow!(
+                "Only the host of a shared project can start debug sessions"
+            ))),
         }
     }
 
@@ -471,7 +508,7 @@ impl DapStore {
                 Some(remote_dap_store.node_runtime.clone()),
                 Some(remote_dap_store.http_client.clone()),
             ),
-            DapStoreMode::Collab => (None, None, None),
+            DapStoreMode::Collab(_) => (None, None, None),
         };
         let session = Session::new(
             self.breakpoint_store.clone(),
@@ -487,6 +524,37 @@ impl DapStore {
             cx,
         );
 
+        if !matches!(self.mode, DapStoreMode::Collab(_)) {
+            self.hosted_sessions.insert(
+                session_id,
+                HostedSession {
+                    ready: false,
+                    guests_can_control: false,
+                    sent_output: OutputToken::default(),
+                    _subscriptions: [
+                        cx.observe(&session, move |this, session, cx| {
+                            this.send_session_output(session_id, &session, cx);
+                        }),
+                        cx.subscribe(&session, move |this, _, event: &SessionEvent, cx| {
+                            if let SessionEvent::CapabilitiesLoaded = event {
+                                this.share_session(session_id, cx);
+                            }
+                        }),
+                    ],
+                },
+            );
+        }
+        self.add_session(session_id, session.clone(), cx);
+
+        session
+    }
+
+    fn add_session(
+        &mut self,
+        session_id: SessionId,
+        session: Entity<Session>,
+        cx: &mut Context<Self>,
+    ) {
         self.sessions.insert(session_id, session.clone());
         cx.notify();
 
@@ -502,8 +570,6 @@ impl DapStore {
             }
         })
         .detach();
-
-        session
     }
 
     pub fn boot_session(
@@ -540,6 +606,31 @@ impl DapStore {
         })
     }
 
+    /// Starts `scenario` through the debug panel, so the session shows up
+    /// there just as if the user had started it. Fails if nothing handled the
+    /// request, e.g. because the project isn't open in a workspace.
+    pub fn request_session(
+        &mut self,
+        scenario: DebugScenario,
+        task_context: SharedTaskContext,
+        worktree_id: Option<WorktreeId>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Entity<Session>>> {
+        let (sender, mut receiver) = mpsc::channel(1);
+        cx.emit(DapStoreEvent::SessionRequested {
+            scenario,
+            task_context,
+            worktree_id,
+            sender,
+        });
+        cx.spawn(async move |_, _| {
+            receiver
+                .next()
+                .await
+                .context("no debug panel is available to start the session")
+        })
+    }
+
     pub fn session_by_id(
         &self,
         session_id: impl Borrow<SessionId>,
@@ -742,6 +833,17 @@ impl DapStore {
         let Some(session) = self.sessions.remove(&session_id) else {
             return Task::ready(Err(anyhow!("Could not find session: {:?}", session_id)));
         };
+        if let Some(hosted) = self.hosted_sessions.remove(&session_id)
+            && hosted.ready
+            && let Some((client, project_id)) = &self.downstream_client
+        {
+            client
+                .send(proto::UnshareDebugSession {
+                    project_id: *project_id,
+                    session_id: session_id.to_proto(),
+                })
+                .log_err();
+        }
 
         let shutdown_children = session
             .read(cx)
@@ -793,9 +895,143 @@ impl DapStore {
         &mut self,
         project_id: u64,
         downstream_client: AnyProtoClient,
-        _: &mut Context<Self>,
+        cx: &mut Context<Self>,
     ) {
         self.downstream_client = Some((downstream_client, project_id));
+        self.broadcast(cx);
+    }
+
+    /// Sends the sessions of this host to the guests, e.g. when one joins.
+    pub(crate) fn broadcast(&mut self, cx: &mut Context<Self>) {
+        let session_ids = self.hosted_sessions.keys().copied().collect::<Vec<_>>();
+        for session_id in session_ids {
+            self.share_session(session_id, cx);
+        }
+    }
+
+    /// Whether the guests of the shared project may step through and continue
+    /// a session of this host.
+    pub fn guests_can_control(&self, session_id: SessionId) -> bool {
+        self.hosted_sessions
+            .get(&session_id)
+            .is_some_and(|hosted| hosted.guests_can_control)
+    }
+
+    pub fn set_guests_can_control(
+        &mut self,
+        session_id: SessionId,
+        guests_can_control: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(hosted) = self.hosted_sessions.get_mut(&session_id) else {
+            return;
+        };
+        hosted.guests_can_control = guests_can_control;
+        self.share_session(session_id, cx);
+        cx.notify();
+    }
+
+    /// Sends a session of this host to the guests once its adapter is
+    /// initialized. Guests that already see the session only update it.
+    fn share_session(&mut self, session_id: SessionId, cx: &mut Context<Self>) {
+        let Some(session) = self.sessions.get(&session_id) else {
+            return;
+        };
+        let Some(hosted) = self.hosted_sessions.get_mut(&session_id) else {
+            return;
+        };
+        hosted.ready = true;
+        let Some((client, project_id)) = &self.downstream_client else {
+            return;
+        };
+
+        let session = session.read(cx);
+        let (all_threads_stopped, stopped_thread_ids) = session.stopped_threads();
+        let (output, output_token) = session.output(OutputToken::default());
+        let output = output
+            .filter_map(|event| serde_json::to_string(&Events::Output(event.clone())).log_err())
+            .collect();
+        hosted.sent_output = output_token;
+        client
+            .send(proto::ShareDebugSession {
+                project_id: *project_id,
+                session_id: session_id.to_proto(),
+                parent_session_id: session.parent_id(cx).map(SessionId::to_proto),
+                label: session.label().map(|label| label.to_string()),
+                adapter: session.adapter().to_string(),
+                capabilities: serde_json::to_string(session.capabilities()).unwrap_or_default(),
+                guests_can_control: hosted.guests_can_control,
+                all_threads_stopped,
+                stopped_thread_ids: stopped_thread_ids
+                    .into_iter()
+                    .map(|thread_id| thread_id.0)
+                    .collect(),
+                output,
+            })
+            .log_err();
+    }
+
+    /// Sends the console output of a session of this host that the guests
+    /// haven't seen yet.
+    fn send_session_output(
+        &mut self,
+        session_id: SessionId,
+        session: &Entity<Session>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((client, project_id)) = &self.downstream_client else {
+            return;
+        };
+        let Some(hosted) = self
+            .hosted_sessions
+            .get_mut(&session_id)
+            .filter(|hosted| hosted.ready)
+        else {
+            return;
+        };
+        let session = session.read(cx);
+        if !session.has_new_output(hosted.sent_output) {
+            return;
+        }
+
+        let (output, output_token) = session.output(hosted.sent_output);
+        hosted.sent_output = output_token;
+        for event in output {
+            if let Some(event) = serde_json::to_string(&Events::Output(event.clone())).log_err() {
+                client
+                    .send(proto::UpdateDebugSession {
+                        project_id: *project_id,
+                        session_id: session_id.to_proto(),
+                        event,
+                    })
+                    .log_err();
+            }
+        }
+    }
+
+    /// Sends an event of the debug adapter of a session of this host to the
+    /// guests.
+    pub(super) fn send_session_event(&self, session_id: SessionId, event: &Events) {
+        let Some((client, project_id)) = &self.downstream_client else {
+            return;
+        };
+        if !shared_session::is_shared_with_guests(event)
+            || !self
+                .hosted_sessions
+                .get(&session_id)
+                .is_some_and(|hosted| hosted.ready)
+        {
+            return;
+        }
+        if let Some(event) = serde_json::to_string(event).log_err() {
+            client
+                .send(proto::UpdateDebugSession {
+                    project_id: *project_id,
+                    session_id: session_id.to_proto(),
+                    event,
+                })
+                .log_err();
+        }
     }
 
     pub fn unshared(&mut self, cx: &mut Context<Self>) {
@@ -895,6 +1131,183 @@ impl DapStore {
         Ok(())
     }
 
+    async fn handle_share_debug_session(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ShareDebugSession>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let message = envelope.payload;
+        let session_id = SessionId::from_proto(message.session_id);
+        let capabilities: Capabilities = serde_json::from_str(&message.capabilities)?;
+        let output = message
+            .output
+            .iter()
+            .filter_map(|event| match serde_json::from_str(event).log_err()? {
+                Events::Output(event) => Some(event),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        this.update(&mut cx, |this, cx| {
+            if let Some(session) = this.sessions.get(&session_id) {
+                session.update(cx, |session, cx| {
+                    session.update_host_session(capabilities, message.guests_can_control, cx)
+                });
+                return Ok(());
+            }
+
+            let DapStoreMode::Collab(collab) = &this.mode else {
+                anyhow::bail!("Only guests of a shared project follow the host's debug sessions");
+            };
+            let mode = CollabMode::new(
+                session_id,
+                collab.upstream_client.clone(),
+                collab.upstream_project_id,
+                cx.background_executor().clone(),
+                message.guests_can_control,
+            );
+            let parent_session = message
+                .parent_session_id
+                .and_then(|parent_id| this.session_by_id(SessionId::from_proto(parent_id)));
+            if let Some(parent_session) = &parent_session {
+                parent_session.update(cx, |parent_session, _| {
+                    parent_session.add_child_session_id(session_id);
+                });
+            }
+            let session = Session::new(
+                this.breakpoint_store.clone(),
+                session_id,
+                parent_session,
+                message.label.map(SharedString::from),
+                DebugAdapterName(message.adapter.into()),
+                SharedTaskContext::default(),
+                SessionQuirks::default(),
+                None,
+                None,
+                None,
+                cx,
+            );
+            session.update(cx, |session, cx| {
+                session.join_host_session(
+                    mode,
+                    capabilities,
+                    message.all_threads_stopped,
+                    message
+                        .stopped_thread_ids
+                        .into_iter()
+                        .map(ThreadId)
+                        .collect(),
+                    output,
+                    cx,
+                )
+            });
+            // The ids of this guest's own sessions must not clash with the host's.
+            this.next_session_id = this.next_session_id.max(session_id.0 + 1);
+            this.add_session(session_id, session.clone(), cx);
+            cx.emit(DapStoreEvent::HostSessionShared(session));
+            Ok(())
+        })
+    }
+
+    async fn handle_update_debug_session(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UpdateDebugSession>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let session_id = SessionId::from_proto(envelope.payload.session_id);
+        let event: Events = serde_json::from_str(&envelope.payload.event)?;
+        if !matches!(event, Events::Output(_)) && !shared_session::is_shared_with_guests(&event) {
+            return Ok(());
+        }
+        this.update(&mut cx, |this, cx| {
+            let session = this
+                .session_by_id(session_id)
+                .context("Could not find the debug session of the host")?;
+            session.update(cx, |session, cx| {
+                session.handle_dap_event(Box::new(event), cx);
+                cx.notify();
+            });
+            Ok(())
+        })
+    }
+
+    async fn handle_unshare_debug_session(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UnshareDebugSession>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let session_id = SessionId::from_proto(envelope.payload.session_id);
+        this.update(&mut cx, |this, cx| {
+            if this.sessions.contains_key(&session_id) {
+                this.shutdown_session(session_id, cx).detach_and_log_err(cx);
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_inspect_debug_session(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::InspectDebugSession>,
+        cx: AsyncApp,
+    ) -> Result<proto::DebugSessionResponse> {
+        let message = envelope.payload;
+        shared_session::check_guest_request(&message.command, false, false)?;
+        Self::run_guest_request(
+            this,
+            message.session_id,
+            message.command,
+            message.arguments,
+            cx,
+        )
+        .await
+    }
+
+    async fn handle_control_debug_session(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ControlDebugSession>,
+        cx: AsyncApp,
+    ) -> Result<proto::DebugSessionResponse> {
+        let message = envelope.payload;
+        let session_id = SessionId::from_proto(message.session_id);
+        let guests_can_control = this.read_with(&cx, |this, _| this.guests_can_control(session_id));
+        shared_session::check_guest_request(&message.command, true, guests_can_control)?;
+        Self::run_guest_request(
+            this,
+            message.session_id,
+            message.command,
+            message.arguments,
+            cx,
+        )
+        .await
+    }
+
+    async fn run_guest_request(
+        this: Entity<Self>,
+        session_id: u64,
+        command: String,
+        arguments: String,
+        mut cx: AsyncApp,
+    ) -> Result<proto::DebugSessionResponse> {
+        let session_id = SessionId::from_proto(session_id);
+        let arguments = serde_json::from_str(&arguments)?;
+        let session = this
+            .read_with(&cx, |this, _| {
+                this.hosted_sessions
+                    .get(&session_id)
+                    .filter(|hosted| hosted.ready)?;
+                this.session_by_id(session_id)
+            })
+            .context("Could not find a shared debug session")?;
+        let body = session
+            .update(&mut cx, |session, cx| {
+                shared_session::run_guest_request(session, &command, arguments, cx)
+            })?
+            .await?;
+        Ok(proto::DebugSessionResponse {
+            body: body.map(|body| body.to_string()),
+        })
+    }
+
     pub fn sync_adapter_options(
         &mut self,
         session: &Entity<Session>,
@@ -104,6 +104,8 @@ impl DapLocator for CargoLocator {
         Some(DebugScenario {
             adapter: adapter.0.clone(),
             label: resolved_label.to_string().into(),
+            pre_launch_task: None,
+            post_debug_task: None,
             build: Some(BuildTaskDefinition::Template {
                 task_template,
                 locator_name: Some(self.name()),
@@ -185,6 +185,8 @@ impl DapLocator for GoLocator {
                     label: resolved_label.to_string().into(),
                     adapter: adapter.0.clone(),
                     build: None,
+                    pre_launch_task: None,
+                    post_debug_task: None,
                     config,
                     tcp_connection: None,
                 })
@@ -229,6 +231,8 @@ impl DapLocator for GoLocator {
                     label: resolved_label.to_string().into(),
                     adapter: adapter.0.clone(),
                     build: None,
+                    pre_launch_task: None,
+                    post_debug_task: None,
                     config,
                     tcp_connection: None,
                 })
@@ -85,6 +85,8 @@ impl DapLocator for PythonLocator {
             adapter: adapter.0.clone(),
             label: resolved_label.to_string().into(),
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             config,
             tcp_connection: None,
         })
@@ -10,8 +10,12 @@ use super::dap_command::{
     TerminateCommand, TerminateThreadsCommand, ThreadsCommand, VariablesCommand,
 };
 use super::dap_store::DapStore;
+use super::shared_session::CollabMode;
 use crate::debugger::breakpoint_store::BreakpointSessionState;
-use crate::debugger::dap_command::{DataBreakpointContext, ReadMemory};
+use crate::debugger::dap_command::{
+    DataBreakpointContext, DisassembleCommand, GotoCommand, GotoTargetsCommand, ReadMemory,
+    SetInstructionBreakpointsCommand,
+};
 use crate::debugger::memory::{self, Memory, MemoryIterator, MemoryPageBuilder, PageAddress};
 use anyhow::{Context as _, Result, anyhow, bail};
 use base64::Engine;
@@ -47,7 +51,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use smol::net::{TcpListener, TcpStream};
 use std::any::TypeId;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::net::{IpAddr, Ipv4Addr};
 use std::ops::RangeInclusive;
 use std::path::PathBuf;
@@ -157,6 +161,8 @@ pub enum SessionState {
     /// is used to run all the async tasks that are required to start the session
     Booting(Option<Task<Result<()>>>),
     Running(RunningMode),
+    /// Represents a guest's view of a session of the host of a shared project
+    Collab(CollabMode),
 }
 
 #[derive(Clone)]
@@ -177,7 +183,7 @@ pub struct SessionQuirks {
     pub prefer_thread_name: bool,
 }
 
-fn client_source(abs_path: &Path) -> dap::Source {
+pub(super) fn client_source(abs_path: &Path) -> dap::Source {
     dap::Source {
         name: abs_path
             .file_name()
@@ -446,6 +452,8 @@ impl RunningMode {
         let supports_exception_filters = capabilities
             .supports_exception_filter_options
             .unwrap_or_default();
+        let supports_instruction_breakpoints =
+            SetInstructionBreakpointsCommand::is_supported(capabilities);
         let this = self.clone();
         let worktree = self.worktree().clone();
         let mut filters = capabilities
@@ -494,6 +502,23 @@ impl RunningMode {
                     }
                 })?;
 
+                if supports_instruction_breakpoints {
+                    let instruction_references = session.read_with(cx, |session, _| {
+                        session
+                            .instruction_breakpoints
+                            .iter()
+                            .cloned()
+                            .collect::<Vec<_>>()
+                    })?;
+                    if !instruction_references.is_empty() {
+                        this.request(SetInstructionBreakpointsCommand {
+                            instruction_references,
+                        })
+                        .await
+                        .log_err();
+                    }
+                }
+
                 if should_send_exception_breakpoints {
                     _ = session.update(cx, |this, _| {
                         filters.retain(|filter| {
@@ -586,6 +611,7 @@ impl SessionState {
     {
         match self {
             SessionState::Running(debug_adapter_client) => debug_adapter_client.request(request),
+            SessionState::Collab(collab) => collab.request(request),
             SessionState::Booting(_) => Task::ready(Err(anyhow!(
                 "no adapter running to send request: {request:?}"
             ))),
@@ -597,6 +623,7 @@ impl SessionState {
         match self {
             SessionState::Booting(_) => false,
             SessionState::Running(running_mode) => running_mode.has_ever_stopped,
+            SessionState::Collab(_) => true,
         }
     }
 
@@ -708,6 +735,9 @@ pub struct Session {
     ignore_breakpoints: bool,
     exception_breakpoints: BTreeMap<String, (ExceptionBreakpointsFilter, IsEnabled)>,
     data_breakpoints: BTreeMap<String, DataBreakpointState>,
+    instruction_breakpoints: BTreeSet<String>,
+    frozen_threads: HashSet<ThreadId>,
+    disassembly: HashMap<DisassembleCommand, Arc<[dap::DisassembledInstruction]>>,
     background_tasks: Vec<Task<()>>,
     restart_task: Option<Task<()>>,
     task_context: SharedTaskContext,
@@ -882,6 +912,9 @@ impl Session {
                 ignore_breakpoints: false,
                 breakpoint_store,
                 data_breakpoints: Default::default(),
+                instruction_breakpoints: Default::default(),
+                frozen_threads: Default::default(),
+                disassembly: Default::default(),
                 exception_breakpoints: Default::default(),
                 label,
                 adapter,
@@ -902,7 +935,7 @@ impl Session {
 
     pub fn worktree(&self) -> Option<Entity<Worktree>> {
         match &self.state {
-            SessionState::Booting(_) => None,
+            SessionState::Booting(_) | SessionState::Collab(_) => None,
             SessionState::Running(local_mode) => local_mode.worktree.upgrade(),
         }
     }
@@ -917,6 +950,7 @@ impl Session {
         let (message_tx, mut message_rx) = futures::channel::mpsc::unbounded();
         let (initialized_tx, initialized_rx) = futures::channel::oneshot::channel();
 
+        let shared_with = dap_store.clone();
         let background_tasks = vec![cx.spawn(async move |this: WeakEntity<Session>, cx| {
             let mut initialized_tx = Some(initialized_tx);
             while let Some(message) = message_rx.next().await {
@@ -927,6 +961,11 @@ impl Session {
                         }
                     } else {
                         let Ok(_) = this.update(cx, |session, cx| {
+                            shared_with
+                                .read_with(cx, |store, _| {
+                                    store.send_session_event(session.id, &event)
+                                })
+                                .ok();
                             session.handle_dap_event(event, cx);
                         }) else {
                             break;
@@ -967,7 +1006,7 @@ impl Session {
                         task.take().unwrap().detach_and_log_err(cx);
                     }
                     SessionState::Booting(_) => {}
-                    SessionState::Running(_) => {
+                    SessionState::Running(_) | SessionState::Collab(_) => {
                         debug_panic!("Attempting to boot a session that is already running");
                     }
                 };
@@ -1064,7 +1103,7 @@ impl Session {
 
     pub fn binary(&self) -> Option<&DebugAdapterBinary> {
         match &self.state {
-            SessionState::Booting(_) => None,
+            SessionState::Booting(_) | SessionState::Collab(_) => None,
             SessionState::Running(running_mode) => Some(&running_mode.binary),
         }
     }
@@ -1086,7 +1125,7 @@ impl Session {
 
         cx.spawn(async move |this, cx| {
             while let Some(output) = rx.next().await {
-                this.update(cx, |this, _| {
+                this.update(cx, |this, cx| {
                     let event = dap::OutputEvent {
                         category: None,
                         output,
@@ -1099,6 +1138,7 @@ impl Session {
                         location_reference: None,
                     };
                     this.push_output(event);
+                    cx.notify();
                 })?;
             }
             anyhow::Ok(())
@@ -1112,6 +1152,7 @@ impl Session {
         match &self.state {
             SessionState::Booting(_) => false,
             SessionState::Running(running) => running.is_started,
+            SessionState::Collab(_) => true,
         }
     }
 
@@ -1119,17 +1160,86 @@ impl Session {
         matches!(self.state, SessionState::Booting(_))
     }
 
+    /// Whether this is a guest's view of a session of the host of a shared project.
+    pub fn is_via_collab(&self) -> bool {
+        matches!(self.state, SessionState::Collab(_))
+    }
+
+    /// Whether the user may step through and continue the debuggee, which the
+    /// host of a shared project has to allow its guests to do.
+    pub fn can_control(&self) -> bool {
+        match &self.state {
+            SessionState::Collab(collab) => collab.guests_can_control,
+            SessionState::Booting(_) | SessionState::Running(_) => true,
+        }
+    }
+
+    /// Makes this session show a session of the host of a shared project.
+    pub(super) fn join_host_session(
+        &mut self,
+        mode: CollabMode,
+        capabilities: Capabilities,
+        all_threads_stopped: bool,
+        stopped_thread_ids: Vec<ThreadId>,
+        output: Vec<OutputEvent>,
+        cx: &mut Context<Self>,
+    ) {
+        self.state = SessionState::Collab(mode);
+        self.capabilities = capabilities;
+        if all_threads_stopped {
+            self.active_snapshot.thread_states.stop_all_threads();
+        }
+        for thread_id in stopped_thread_ids {
+            self.active_snapshot.thread_states.stop_thread(thread_id);
+        }
+        for event in output {
+            self.push_output(event);
+        }
+        cx.emit(SessionEvent::CapabilitiesLoaded);
+        cx.notify();
+    }
+
+    pub(super) fn update_host_session(
+        &mut self,
+        capabilities: Capabilities,
+        guests_can_control: bool,
+        cx: &mut Context<Self>,
+    ) {
+        if let SessionState::Collab(collab) = &mut self.state {
+            collab.guests_can_control = guests_can_control;
+        }
+        self.capabilities = capabilities;
+        cx.emit(SessionEvent::CapabilitiesLoaded);
+        cx.notify();
+    }
+
+    /// Whether all threads of the debuggee are stopped, and which ones are
+    /// otherwise, for the guests of a shared project that join this session.
+    pub(super) fn stopped_threads(&self) -> (bool, Vec<ThreadId>) {
+        let thread_states = &self.active_snapshot.thread_states;
+        let stopped_thread_ids = thread_states
+            .known_thread_states
+            .iter()
+            .filter(|(_, status)| **status == ThreadStatus::Stopped)
+            .map(|(thread_id, _)| *thread_id)
+            .collect();
+        (
+            thread_states.global_state == Some(ThreadStatus::Stopped),
+            stopped_thread_ids,
+        )
+    }
+
     pub fn as_running_mut(&mut self) -> Option<&mut RunningMode> {
         match &mut self.state {
             SessionState::Running(local_mode) => Some(local_mode),
-            SessionState::Booting(_) => None,
+            SessionState::Booting(_) | SessionState::Collab(_) => None,
         }
     }
 
     pub fn as_running(&self) -> Option<&RunningMode> {
         match &self.state {
             SessionState::Running(local_mode) => Some(local_mode),
-            SessionState::Booting(_) => None,
+            SessionState::Booting(_) | SessionState::Collab(_) => None,
         }
     }
 
@@ -1326,6 +1436,9 @@ impl Session {
             SessionState::Booting(_) => {
                 Task::ready(Err(anyhow!("cannot initialize, still building")))
             }
+            SessionState::Collab(_) => {
+                Task::ready(Err(anyhow!("cannot initialize the session of the host")))
+            }
         }
     }
 
@@ -1362,8 +1475,52 @@ impl Session {
                 })
                 .detach();
             }
-            SessionState::Booting(_) => {}
+            SessionState::Booting(_) | SessionState::Collab(_) => {}
+        }
+    }
+
+    /// Moves the instruction pointer of a stopped thread to `row` in `path`
+    /// without executing the code in between, using the first goto target the
+    /// debug adapter reports for that line.
+    pub fn jump_to_position(
+        &mut self,
+        path: Arc<Path>,
+        row: u32,
+        thread_id: ThreadId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        if self.thread_status(thread_id) != ThreadStatus::Stopped {
+            return Task::ready(Err(anyhow!("Thread {} is not stopped", thread_id.0)));
         }
+
+        let targets = self.request(
+            GotoTargetsCommand {
+                path,
+                line: row as u64 + 1,
+            },
+            |_, result, _| result.log_err(),
+            cx,
+        );
+
+        cx.spawn(async move |this, cx| {
+            let target = targets
+                .await
+                .and_then(|targets| targets.into_iter().next())
+                .context("The debug adapter can't jump to this line")?;
+
+            this.update(cx, |this, cx| {
+                this.request(
+                    GotoCommand {
+                        thread_id: thread_id.0,
+                        target_id: target.id,
+                    },
+                    Self::empty_response,
+                    cx,
+                )
+            })?
+            .await
+            .context("Failed to jump to line")
+        })
     }
 
     pub fn has_new_output(&self, last_update: OutputToken) -> bool {
@@ -1556,6 +1713,7 @@ impl Session {
                     }
                     dap::ThreadEventReason::Exited => {
                         self.active_snapshot.thread_states.exit_thread(thread_id);
+                        self.frozen_threads.remove(&thread_id);
                     }
                     reason => {
                         log::error!("Unhandled thread event reason {:?}", reason);
@@ -1626,7 +1784,7 @@ impl Session {
                 }
                 self.exception_breakpoints
                     .retain(|k, _| recent_filters.contains_key(k));
-                if self.is_started() {
+                if self.as_running().is_some_and(|running| running.is_started) {
                     self.send_exception_breakpoints(cx);
                 }
 
@@ -1770,6 +1928,8 @@ impl Session {
         self.invalidate_command_type::<ThreadsCommand>();
         self.invalidate_command_type::<DataBreakpointInfoCommand>();
         self.invalidate_command_type::<ReadMemory>();
+        self.invalidate_command_type::<DisassembleCommand>();
+        self.disassembly.clear();
         let executor = self.as_running().map(|running| running.executor.clone());
         if let Some(executor) = executor {
             self.memory.clear(&executor);
@@ -2041,7 +2201,7 @@ impl Session {
             local
                 .send_exception_breakpoints(exception_filters, supports_exception_filters)
                 .detach_and_log_err(cx);
-        } else {
+        } else if !self.is_via_collab() {
             debug_assert!(false, "Not implemented");
         }
     }
@@ -2085,6 +2245,72 @@ impl Session {
         self.send_data_breakpoints(cx);
     }
 
+    /// Instruction references of the breakpoints set on individual
+    /// instructions, e.g. from the disassembly view.
+    pub fn instruction_breakpoints(&self) -> &BTreeSet<String> {
+        &self.instruction_breakpoints
+    }
+
+    pub fn toggle_instruction_breakpoint(
+        &mut self,
+        instruction_reference: String,
+        cx: &mut Context<Self>,
+    ) {
+        if !self.instruction_breakpoints.remove(&instruction_reference) {
+            self.instruction_breakpoints.insert(instruction_reference);
+        }
+        self.send_instruction_breakpoints(cx);
+        cx.notify();
+    }
+
+    /// Carries instruction breakpoints over from a previous session, e.g. when
+    /// restarting. They're sent to the adapter once the session initializes.
+    pub fn restore_instruction_breakpoints(&mut self, instruction_breakpoints: BTreeSet<String>) {
+        self.instruction_breakpoints = instruction_breakpoints;
+    }
+
+    fn send_instruction_breakpoints(&mut self, cx: &mut Context<Self>) {
+        if let Some(mode) = self.as_running() {
+            let command = SetInstructionBreakpointsCommand {
+                instruction_references: self.instruction_breakpoints.iter().cloned().collect(),
+            };
+            mode.request(command).detach_and_log_err(cx);
+        }
+    }
+
+    /// Disassembles `instruction_count` instructions starting
+    /// `instruction_offset` instructions away from `memory_reference`.
+    /// Returns `None` until the debug adapter has responded.
+    pub fn disassemble(
+        &mut self,
+        memory_reference: String,
+        instruction_offset: i64,
+        instruction_count: u64,
+        cx: &mut Context<Self>,
+    ) -> Option<Arc<[dap::DisassembledInstruction]>> {
+        let command = DisassembleCommand {
+            memory_reference,
+            instruction_offset,
+            instruction_count,
+        };
+        if let Some(instructions) = self.disassembly.get(&command) {
+            return Some(instructions.clone());
+        }
+
+        self.fetch(
+            command.clone(),
+            move |this, result, cx| {
+                let Some(instructions) = result.log_err() else {
+                    return;
+                };
+                this.disassembly.insert(command, instructions);
+                cx.notify();
+            },
+            cx,
+        );
+        None
+    }
+
     pub fn breakpoints_enabled(&self) -> bool {
         self.ignore_breakpoints
     }
@@ -2186,25 +2412,31 @@ impl Session {
             self.capabilities.supports_restart_request.unwrap_or(false) && !self.is_terminated();
 
         self.restart_task = Some(cx.spawn(async move |this, cx| {
-            this.update(cx, |session, cx| {
-                if supports_dap_restart {
-                    session.request(
-                        RestartCommand {
-                            raw: args.unwrap_or(Value::Null),
-                        },
-                        Self::fallback_to_manual_restart,
-                        cx,
-                    )
-                } else {
-                    cx.emit(SessionStateEvent::Restart);
-                    Task::ready(None)
-                }
-            })
-            .unwrap_or_else(|_| Task::ready(None))
-            .await;
+            let restarted = this
+                .update(cx, |session, cx| {
+                    if supports_dap_restart {
+                        session.request(
+                            RestartCommand {
+                                raw: args.unwrap_or(Value::Null),
+                            },
+                            Self::fallback_to_manual_restart,
+                            cx,
+                        )
+                    } else {
+                        cx.emit(SessionStateEvent::Restart);
+                        Task::ready(None)
+                    }
+                })
+                .unwrap_or_else(|_| Task::ready(None))
+                .await;
 
-            this.update(cx, |session, _cx| {
+            this.update(cx, |session, cx| {
                 session.restart_task = None;
+                // The adapter restarted in place, so send the instruction
+                // breakpoints again.
+                if restarted.is_some() {
+                    session.send_instruction_breakpoints(cx);
+                }
             })
             .ok();
         }));
@@ -2249,6 +2481,10 @@ impl Session {
                 build_task.take();
                 Task::ready(Some(()))
             }
+            SessionState::Collab(_) => {
+                self.clear_active_debug_line(cx);
+                Task::ready(Some(()))
+            }
         };
 
         cx.emit(SessionStateEvent::Shutdown);
@@ -2279,9 +2515,38 @@ impl Session {
         })
     }
 
+    pub fn is_thread_frozen(&self, thread_id: ThreadId) -> bool {
+        self.frozen_threads.contains(&thread_id)
+    }
+
+    /// Freezing a thread keeps it paused while the rest of the program
+    /// continues. This requires single-thread execution support in the adapter.
+    pub fn toggle_thread_frozen(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
+        if !self
+            .capabilities
+            .supports_single_thread_execution_requests
+            .unwrap_or_default()
+        {
+            return;
+        }
+
+        if !self.frozen_threads.remove(&thread_id) {
+            self.frozen_threads.insert(thread_id);
+            if self.thread_status(thread_id) == ThreadStatus::Running {
+                self.pause_thread(thread_id, cx);
+            }
+        }
+        cx.notify();
+    }
+
     pub fn continue_thread(&mut self, thread_id: ThreadId, cx: &mut Context<Self>) {
         self.select_historic_snapshot(None, cx);
 
+        if !self.frozen_threads.is_empty() {
+            self.continue_unfrozen_threads(cx);
+            return;
+        }
+
         let supports_single_thread_execution_requests =
             self.capabilities.supports_single_thread_execution_requests;
         self.active_snapshot
@@ -2300,10 +2565,40 @@ impl Session {
         .detach();
     }
 
+    fn continue_unfrozen_threads(&mut self, cx: &mut Context<Self>) {
+        let thread_ids = self
+            .active_snapshot
+            .threads
+            .keys()
+            .copied()
+            .filter(|thread_id| {
+                !self.frozen_threads.contains(thread_id)
+                    && self.thread_status(*thread_id) == ThreadStatus::Stopped
+            })
+            .collect::<Vec<_>>();
+
+        for thread_id in thread_ids {
+            self.active_snapshot
+                .thread_states
+                .continue_thread(thread_id);
+            self.request(
+                ContinueCommand {
+                    args: ContinueArguments {
+                        thread_id: thread_id.0,
+                        single_thread: Some(true),
+                    },
+                },
+                Self::on_step_response::<ContinueCommand>(thread_id),
+                cx,
+            )
+            .detach();
+        }
+    }
+
     pub fn adapter_client(&self) -> Option<Arc<DebugAdapterClient>> {
         match self.state {
             SessionState::Running(ref local) => Some(local.client.clone()),
-            SessionState::Booting(_) => None,
+            SessionState::Booting(_) | SessionState::Collab(_) => None,
         }
     }
 
@@ -2701,6 +2996,102 @@ impl Session {
             .unwrap_or_default()
     }
 
+    /// The in-flight or completed request for `request`, if one was sent.
+    fn pending_request<T: LocalDapCommand + PartialEq + Eq + Hash>(
+        &self,
+        request: T,
+    ) -> Option<Shared<Task<Option<()>>>> {
+        self.requests
+            .get(&TypeId::of::<T>())
+            .and_then(|requests| requests.get(&RequestSlot::from(request)))
+            .cloned()
+    }
+
+    /// Like [`Self::threads`], but resolves once the adapter has responded
+    /// instead of returning whatever is cached.
+    pub fn fetch_threads(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<(dap::Thread, ThreadStatus)>> {
+        self.threads(cx);
+        let pending = self.pending_request(ThreadsCommand);
+        cx.spawn(async move |this, cx| {
+            if let Some(pending) = pending {
+                pending.await;
+            }
+            this.update(cx, |this, cx| this.threads(cx))
+                .unwrap_or_default()
+        })
+    }
+
+    /// Like [`Self::stack_frames`], but resolves once the adapter has
+    /// responded instead of returning whatever is cached.
+    pub fn fetch_stack_frames(
+        &mut self,
+        thread_id: ThreadId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<StackFrame>>> {
+        let threads = self.fetch_threads(cx);
+        cx.spawn(async move |this, cx| {
+            threads.await;
+            let pending = this.update(cx, |this, cx| {
+                this.stack_frames(thread_id, cx).ok();
+                this.pending_request(StackTraceCommand {
+                    thread_id: thread_id.0,
+                    start_frame: None,
+                    levels: None,
+                })
+            })?;
+            if let Some(pending) = pending {
+                pending.await;
+            }
+            this.update(cx, |this, cx| this.stack_frames(thread_id, cx))?
+        })
+    }
+
+    /// Like [`Self::scopes`], but resolves once the adapter has responded
+    /// instead of returning whatever is cached. The frame's stack trace must
+    /// have been fetched first.
+    pub fn fetch_scopes(
+        &mut self,
+        stack_frame_id: StackFrameId,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<dap::Scope>> {
+        self.scopes(stack_frame_id, cx);
+        let pending = self.pending_request(ScopesCommand { stack_frame_id });
+        cx.spawn(async move |this, cx| {
+            if let Some(pending) = pending {
+                pending.await;
+            }
+            this.update(cx, |this, cx| this.scopes(stack_frame_id, cx).to_vec())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Like [`Self::variables`], but resolves once the adapter has responded
+    /// instead of returning whatever is cached.
+    pub fn fetch_variables(
+        &mut self,
+        variables_reference: VariableReference,
+        cx: &mut Context<Self>,
+    ) -> Task<Vec<dap::Variable>> {
+        self.variables(variables_reference, cx);
+        let pending = self.pending_request(VariablesCommand {
+            variables_reference,
+            filter: None,
+            start: None,
+            count: None,
+            format: None,
+        });
+        cx.spawn(async move |this, cx| {
+            if let Some(pending) = pending {
+                pending.await;
+            }
+            this.update(cx, |this, cx| this.variables(variables_reference, cx))
+                .unwrap_or_default()
+        })
+    }
+
     pub fn data_breakpoint_info(
         &mut self,
         context: Arc<DataBreakpointContext>,
@@ -0,0 +1,280 @@
+//! Showing the debug sessions of the host of a shared project to its guests.
+//!
+//! The host sends its sessions, the events of their debug adapters and their
+//! console output to the guests. The sessions of a guest send their DAP
+//! requests to the host, who runs the ones that inspect the debuggee, and the
+//! ones that step or continue it once the host lets the guests do so.
+
+use anyhow::{Context as _, Result};
+use dap::{
+    SteppingGranularity,
+    client::{SessionId, response_from_body},
+    messages::Events,
+    requests::{
+        Completions, Continue, DataBreakpointInfo, Disassemble, Evaluate, GotoTargets,
+        LoadedSources, Locations, Modules, Next, Pause, ReadMemory, Request, RestartFrame, Scopes,
+        SetVariable, StackTrace, StepBack, StepIn, StepOut, Threads, Variables,
+    },
+};
+use gpui::{BackgroundExecutor, Context, Task};
+use rpc::{AnyProtoClient, proto};
+use serde_json::Value;
+
+use super::{
+    dap_command::LocalDapCommand,
+    session::{Session, ThreadId},
+};
+
+/// The requests of guests that only read the state of the debuggee.
+const INSPECTION_COMMANDS: &[&str] = &[
+    Threads::COMMAND,
+    StackTrace::COMMAND,
+    Scopes::COMMAND,
+    Variables::COMMAND,
+    Modules::COMMAND,
+    LoadedSources::COMMAND,
+    Locations::COMMAND,
+    Completions::COMMAND,
+    Disassemble::COMMAND,
+    ReadMemory::COMMAND,
+    DataBreakpointInfo::COMMAND,
+    GotoTargets::COMMAND,
+];
+
+/// The requests of guests that run or change the debuggee, which the host has
+/// to allow. This includes `evaluate` in every context: the guest picks the
+/// context, and adapters run the expression as code whether it is for a hover
+/// or the REPL.
+const CONTROL_COMMANDS: &[&str] = &[
+    Next::COMMAND,
+    StepIn::COMMAND,
+    StepOut::COMMAND,
+    StepBack::COMMAND,
+    Continue::COMMAND,
+    Pause::COMMAND,
+    Evaluate::COMMAND,
+    SetVariable::COMMAND,
+    RestartFrame::COMMAND,
+];
+
+pub(super) fn is_inspection_command(command: &str) -> bool {
+    INSPECTION_COMMANDS.contains(&command)
+}
+
+pub(super) fn is_control_command(command: &str) -> bool {
+    CONTROL_COMMANDS.contains(&command)
+}
+
+/// Checks that a guest may send a request to a session of the host, either to
+/// inspect it or, when `control` is set, to control it.
+pub(super) fn check_guest_request(
+    command: &str,
+    control: bool,
+    guests_can_control: bool,
+) -> Result<()> {
+    if control {
+        anyhow::ensure!(
+            is_control_command(command),
+            "Guests cannot send `{command}` requests to a debug session"
+        );
+        anyhow::ensure!(
+            guests_can_control,
+            "The host doesn't let guests control this debug session"
+        );
+    } else {
+        anyhow::ensure!(
+            is_inspection_command(command),
+            "Guests cannot send `{command}` requests to inspect a debug session"
+        );
+    }
+    Ok(())
+}
+
+/// Whether the guests are sent this event of a debug adapter of the host.
+/// Output events are sent with the rest of the console output instead.
+pub(super) fn is_shared_with_guests(event: &Events) -> bool {
+    matches!(
+        event,
+        Events::Stopped(_)
+            | Events::Continued(_)
+            | Events::Exited(_)
+            | Events::Thread(_)
+            | Events::Breakpoint(_)
+            | Events::Module(_)
+            | Events::LoadedSource(_)
+            | Events::Capabilities(_)
+    )
+}
+
+/// How the session of a guest reaches the debug adapter of the host's session.
+#[derive(Clone)]
+pub struct CollabMode {
+    session_id: SessionId,
+    upstream_client: AnyProtoClient,
+    upstream_project_id: u64,
+    executor: BackgroundExecutor,
+    pub(super) guests_can_control: bool,
+}
+
+impl CollabMode {
+    pub(super) fn new(
+        session_id: SessionId,
+        upstream_client: AnyProtoClient,
+        upstream_project_id: u64,
+        executor: BackgroundExecutor,
+        guests_can_control: bool,
+    ) -> Self {
+        Self {
+            session_id,
+            upstream_client,
+            upstream_project_id,
+            executor,
+            guests_can_control,
+        }
+    }
+
+    pub(super) fn request<R: LocalDapCommand>(&self, request: R) -> Task<Result<R::Response>> {
+        let command = <R::DapRequest as Request>::COMMAND;
+        let arguments = match serde_json::to_string(&request.to_dap()) {
+            Ok(arguments) => arguments,
+            Err(error) => return Task::ready(Err(error.into())),
+        };
+        let client = self.upstream_client.clone();
+        let project_id = self.upstream_project_id;
+        let session_id = self.session_id.to_proto();
+
+        self.executor.spawn(async move {
+            let response = if is_inspection_command(command) {
+                client
+                    .request(proto::InspectDebugSession {
+                        project_id,
+                        session_id,
+                        command: command.to_string(),
+                        arguments,
+                    })
+                    .await?
+            } else {
+                client
+                    .request(proto::ControlDebugSession {
+                        project_id,
+                        session_id,
+                        command: command.to_string(),
+                        arguments,
+                    })
+                    .await?
+            };
+            let body = response
+                .body
+                .map(|body| serde_json::from_str(&body))
+                .transpose()?;
+            request.response_from_dap(response_from_body::<R::DapRequest>(body)?)
+        })
+    }
+}
+
+/// Runs the request of a guest on a session of the host. Requests that step or
+/// continue the debuggee go through the session, so that the host sees its
+/// threads run like it does for its own requests.
+pub(super) fn run_guest_request(
+    session: &mut Session,
+    command: &str,
+    arguments: Value,
+    cx: &mut Context<Session>,
+) -> Result<Task<Result<Option<Value>>>> {
+    // Adapters that don't support granularity ignore it, so any works then.
+    let granularity = |granularity: Option<SteppingGranularity>| {
+        granularity.unwrap_or(SteppingGranularity::Statement)
+    };
+
+    if command == Next::COMMAND {
+        let arguments: <Next as Request>::Arguments = serde_json::from_value(arguments)?;
+        let thread_id = ThreadId(arguments.thread_id);
+        session.step_over(thread_id, granularity(arguments.granularity), cx);
+    } else if command == StepIn::COMMAND {
+        let arguments: <StepIn as Request>::Arguments = serde_json::from_value(arguments)?;
+        let thread_id = ThreadId(arguments.thread_id);
+        session.step_in(thread_id, granularity(arguments.granularity), cx);
+    } else if command == StepOut::COMMAND {
+        let arguments: <StepOut as Request>::Arguments = serde_json::from_value(arguments)?;
+        let thread_id = ThreadId(arguments.thread_id);
+        session.step_out(thread_id, granularity(arguments.granularity), cx);
+    } else if command == StepBack::COMMAND {
+        let arguments: <StepBack as Request>::Arguments = serde_json::from_value(arguments)?;
+        let thread_id = ThreadId(arguments.thread_id);
+        session.step_back(thread_id, granularity(arguments.granularity), cx);
+    } else if command == Continue::COMMAND {
+        let arguments: <Continue as Request>::Arguments = serde_json::from_value(arguments)?;
+        session.continue_thread(ThreadId(arguments.thread_id), cx);
+    } else if command == Pause::COMMAND {
+        let arguments: <Pause as Request>::Arguments = serde_json::from_value(arguments)?;
+        session.pause_thread(ThreadId(arguments.thread_id), cx);
+    } else {
+        let client = session
+            .adapter_client()
+            .context("The debug adapter of the session is not running")?;
+        let command = command.to_string();
+        return Ok(cx.background_spawn(async move {
+            client.request_by_command(&command, Some(arguments)).await
+        }));
+    }
+    Ok(Task::ready(Ok(None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use dap::{ContinuedEvent, OutputEvent};
+
+    use super::*;
+
+    #[test]
+    fn test_guest_requests_need_the_right_permission() {
+        assert!(check_guest_request(StackTrace::COMMAND, false, false).is_ok());
+        assert!(check_guest_request(StackTrace::COMMAND, true, true).is_err());
+        assert!(check_guest_request(Continue::COMMAND, false, true).is_err());
+        assert!(check_guest_request(Continue::COMMAND, true, false).is_err());
+        assert!(check_guest_request(Continue::COMMAND, true, true).is_ok());
+
+        // Evaluating a hovered expression runs it in the debuggee like the
+        // REPL does, so it needs the host's permission too.
+        assert!(check_guest_request(Evaluate::COMMAND, false, false).is_err());
+        assert!(check_guest_request(Evaluate::COMMAND, false, true).is_err());
+        assert!(check_guest_request(Evaluate::COMMAND, true, false).is_err());
+        assert!(check_guest_request(Evaluate::COMMAND, true, true).is_ok());
+
+        // Guests never manage the adapter or the breakpoints of the host's
+        // session themselves.
+        for command in ["launch", "disconnect", "terminate", "setBreakpoints"] {
+            assert!(check_guest_request(command, false, true).is_err());
+            assert!(check_guest_request(command, true, true).is_err());
+        }
+    }
+
+    #[test]
+    fn test_events_shared_with_guests() {
+        let continued = Events::Continued(ContinuedEvent {
+            thread_id: 1,
+            all_threads_continued: Some(true),
+        });
+        assert!(is_shared_with_guests(&continued));
+
+        let event: Events = serde_json::from_value(serde_json::json!({
+            "event": "output",
+            "body": { "output": "hello\n" },
+        }))
+        .unwrap();
+        assert!(
+            matches!(&event, Events::Output(OutputEvent { output, .. }) if output == "hello\n")
+        );
+        assert!(!is_shared_with_guests(&event));
+
+        let round_tripped: Events =
+            serde_json::from_str(&serde_json::to_string(&continued).unwrap()).unwrap();
+        assert!(matches!(
+            round_tripped,
+            Events::Continued(ContinuedEvent {
+                thread_id: 1,
+                all_threads_continued: Some(true),
+            })
+        ));
+    }
+}
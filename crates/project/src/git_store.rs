@@ -38,8 +38,8 @@ use git::{
         CreateWorktreeTarget, DiffStatType, DiffType, FetchOptions, FileHistoryChangedFileSets,
         GitCommitTemplate, GitRepository, GitRepositoryCheckpoint, InitialGraphCommitData,
         LogOrder, LogSource, PushOptions, Remote, RemoteCommandOutput, RepoPath, ResetMode,
-        SearchCommitArgs, UpstreamTrackingStatus, Worktree as GitWorktree, delete_branch_flag,
-        is_binary_content,
+        SearchCommitArgs, SearchCommitTarget, UpstreamTrackingStatus, Worktree as GitWorktree,
+        delete_branch_flag, is_binary_content,
     },
     stash::{GitStash, StashEntry},
     status::{
@@ -235,6 +235,20 @@ impl CommitFile {
     }
 }
 
+/// The versions of a conflicted file recorded in the index during a merge.
+///
+/// Each stage is `None` if the file doesn't exist on that side, such as when
+/// it was added on only one branch.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeStages {
+    /// The version in the merge base (stage 1).
+    pub base: Option<String>,
+    /// The version on the current branch (stage 2).
+    pub ours: Option<String>,
+    /// The version on the branch being merged in (stage 3).
+    pub theirs: Option<String>,
+}
+
 fn decode_commit_diff(diff: git::repository::CommitDiff) -> CommitDiff {
     let files = diff
         .files
@@ -969,6 +983,7 @@ impl GitStore {
         client.add_entity_request_handler(Self::handle_git_diff);
         client.add_entity_request_handler(Self::handle_tree_diff);
         client.add_entity_request_handler(Self::handle_get_blob_content);
+        client.add_entity_request_handler(Self::handle_load_merge_stages);
         client.add_entity_request_handler(Self::handle_load_commit_template);
         client.add_entity_request_handler(Self::handle_open_unstaged_diff);
         client.add_entity_request_handler(Self::handle_open_uncommitted_diff);
@@ -3981,9 +3996,17 @@ impl GitStore {
                 .log_source
                 .context("missing search commit log source")?,
         )?;
+        let target = search_commit_target_from_proto(envelope.payload.target());
         let search_args = SearchCommitArgs {
             query: SharedString::from(envelope.payload.query),
             case_sensitive: envelope.payload.case_sensitive,
+            target,
+            pathspecs: envelope
+                .payload
+                .pathspecs
+                .into_iter()
+                .map(SharedString::from)
+                .collect(),
         };
 
         let (request_tx, request_rx) = async_channel::unbounded();
@@ -4646,6 +4669,27 @@ impl GitStore {
         Ok(proto::GetBlobContentResponse { content })
     }
 
+    async fn handle_load_merge_stages(
+        this: Entity<Self>,
+        request: TypedEnvelope<proto::LoadMergeStages>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::LoadMergeStagesResponse> {
+        let repository_id = RepositoryId(request.payload.repository_id);
+        let path = RepoPath::from_proto(&request.payload.path)?;
+        let stages = this
+            .update(&mut cx, |this, cx| {
+                let repository = this.repositories().get(&repository_id)?;
+                Some(repository.update(cx, |repo, cx| repo.load_merge_stages(path, cx)))
+            })
+            .context("missing repository")?
+            .await?;
+        Ok(proto::LoadMergeStagesResponse {
+            base: stages.base,
+            ours: stages.ours,
+            theirs: stages.theirs,
+        })
+    }
+
     async fn handle_load_commit_template(
         this: Entity<Self>,
         request: TypedEnvelope<proto::LoadCommitTemplate>,
@@ -7077,6 +7121,12 @@ impl Repository {
                             log_source: Some(log_source_to_proto(&log_source)),
                             query: search_args.query.to_string(),
                             case_sensitive: search_args.case_sensitive,
+                            target: search_commit_target_to_proto(search_args.target),
+                            pathspecs: search_args
+                                .pathspecs
+                                .iter()
+                                .map(|pathspec| pathspec.to_string())
+                                .collect(),
                         })
                         .await;
 
@@ -10144,6 +10194,47 @@ impl Repository {
         })
     }
 
+    /// Loads the base, ours and theirs versions of a conflicted file from the
+    /// index.
+    pub fn load_merge_stages(&mut self, path: RepoPath, cx: &App) -> Task<Result<MergeStages>> {
+        let repository_id = self.snapshot.id;
+        let rx = self.send_job("load_merge_stages", None, move |state, _| async move {
+            match state {
+                RepositoryState::Local(LocalRepositoryState { backend, .. }) => {
+                    let revisions = (1..=3)
+                        .map(|stage| format!(":{stage}:{}", path.as_unix_str()))
+                        .collect();
+                    let mut stages = backend
+                        .load_revisions(revisions)
+                        .await?
+                        .into_iter()
+                        .map(|bytes| bytes.map(decode_git_text).transpose());
+                    let mut next_stage = || stages.next().transpose().map(Option::flatten);
+                    Ok(MergeStages {
+                        base: next_stage()?,
+                        ours: next_stage()?,
+                        theirs: next_stage()?,
+                    })
+                }
+                RepositoryState::Remote(RemoteRepositoryState { client, project_id }) => {
+                    let response = client
+                        .request(proto::LoadMergeStages {
+                            project_id: project_id.to_proto(),
+                            repository_id: repository_id.0,
+                            path: path.as_unix_str().to_owned(),
+                        })
+                        .await?;
+                    Ok(MergeStages {
+                        base: response.base,
+                        ours: response.ours,
+                        theirs: response.theirs,
+                    })
+                }
+            }
+        });
+        cx.spawn(|_: &mut AsyncApp| async move { rx.await? })
+    }
+
     fn load_blob_content(&mut self, oid: Oid, cx: &App) -> Task<Result<String>> {
         let repository_id = self.snapshot.id;
         let rx = self.send_job("load_blob_content", None, move |state, _| async move {
@@ -10878,6 +10969,22 @@ fn log_order_from_proto(log_order: proto::get_initial_graph_data::LogOrder) -> L
     }
 }
 
+fn search_commit_target_to_proto(target: SearchCommitTarget) -> i32 {
+    match target {
+        SearchCommitTarget::Message => proto::search_commits::Target::Message as i32,
+        SearchCommitTarget::Pickaxe => proto::search_commits::Target::Pickaxe as i32,
+        SearchCommitTarget::PickaxeRegex => proto::search_commits::Target::PickaxeRegex as i32,
+    }
+}
+
+fn search_commit_target_from_proto(target: proto::search_commits::Target) -> SearchCommitTarget {
+    match target {
+        proto::search_commits::Target::Message => SearchCommitTarget::Message,
+        proto::search_commits::Target::Pickaxe => SearchCommitTarget::Pickaxe,
+        proto::search_commits::Target::PickaxeRegex => SearchCommitTarget::PickaxeRegex,
+    }
+}
+
 fn initial_graph_commit_to_proto(commit: &InitialGraphCommitData) -> proto::InitialGraphCommit {
     proto::InitialGraphCommit {
         sha: commit.sha.to_string(),
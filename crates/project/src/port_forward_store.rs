@@ -0,0 +1,419 @@
+//! Forwarding of ports on the remote host of a remote project to the local
+//! machine.
+//!
+//! The remote server reports the ports its processes listen on, and ports are
+//! also picked up from local URLs printed in terminals. Forwarded ports are
+//! listened on locally, and each connection to them is relayed to the remote
+//! host over the existing remote connection, so forwarding works the same way
+//! for every transport.
+
+use std::{
+    net::Ipv4Addr,
+    sync::{Arc, LazyLock},
+};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use gpui::{AsyncApp, Context, Entity, EventEmitter, Task};
+use regex::Regex;
+use remote::{
+    RemoteClient,
+    port_forwarding::{PortTunnel, relay_port_tunnel},
+};
+use rpc::{
+    AnyProtoClient, TypedEnvelope,
+    proto::{self, REMOTE_SERVER_PROJECT_ID},
+};
+use smol::net::{TcpListener, TcpStream};
+use util::{ResultExt as _, post_inc};
+
+/// Matches URLs pointing at a port on the machine that printed them.
+static LOCAL_URL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"https?://(localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):(\d{1,5})\b").unwrap()
+});
+
+/// Where a listening port was found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortSource {
+    /// A listening socket reported by the remote server.
+    Socket,
+    /// A local URL printed in a terminal.
+    Terminal,
+}
+
+/// A port on the remote host that can be forwarded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListeningPort {
+    pub port: u16,
+    /// The address the port is listened on, e.g. `127.0.0.1` or `0.0.0.0`.
+    pub host: Arc<str>,
+    pub process_name: Option<Arc<str>>,
+    pub source: PortSource,
+}
+
+/// A remote port that is forwarded to a local one.
+pub struct PortForward {
+    pub remote_port: u16,
+    pub local_port: u16,
+    pub process_name: Option<Arc<str>>,
+    /// Accepts local connections, until dropped. Forwards over connections
+    /// that share the local network have no listener of their own.
+    _accept_connections: Option<Task<()>>,
+}
+
+struct Tunnel {
+    remote_port: u16,
+    /// Receives the remote server's messages for the local connection.
+    tunnel: PortTunnel,
+}
+
+pub enum PortForwardStoreEvent {
+    /// A port started listening on the remote host after the project was
+    /// opened, and could be forwarded.
+    PortDetected(ListeningPort),
+}
+
+pub struct PortForwardStore {
+    client: AnyProtoClient,
+    /// Whether the remote host's ports are reachable locally as they are, as
+    /// they are for WSL.
+    shares_network_interface: bool,
+    listening_ports: Vec<ListeningPort>,
+    /// Whether the first report of listening sockets was received. The ports
+    /// in it were already listening before the project was opened, so they
+    /// aren't offered for forwarding.
+    received_listening_sockets: bool,
+    /// The ports already found in terminal output, which aren't offered again
+    /// while their URLs are still on screen.
+    terminal_url_ports: HashSet<u16>,
+    forwards: Vec<PortForward>,
+    tunnels: HashMap<u64, Tunnel>,
+    next_tunnel_id: u64,
+}
+
+impl EventEmitter<PortForwardStoreEvent> for PortForwardStore {}
+
+impl PortForwardStore {
+    pub fn init(client: &AnyProtoClient) {
+        client.add_entity_message_handler(Self::handle_update_listening_ports);
+        client.add_entity_message_handler(Self::handle_port_tunnel_data);
+        client.add_entity_message_handler(Self::handle_port_tunnel_ack);
+        client.add_entity_message_handler(Self::handle_close_port_tunnel);
+    }
+
+    pub fn new(remote_client: &Entity<RemoteClient>, cx: &mut Context<Self>) -> Self {
+        let remote_client = remote_client.read(cx);
+        Self {
+            client: remote_client.proto_client(),
+            shares_network_interface: remote_client.shares_network_interface(),
+            listening_ports: Vec::new(),
+            received_listening_sockets: false,
+            terminal_url_ports: HashSet::default(),
+            forwards: Vec::new(),
+            tunnels: HashMap::default(),
+            next_tunnel_id: 0,
+        }
+    }
+
+    pub fn listening_ports(&self) -> &[ListeningPort] {
+        &self.listening_ports
+    }
+
+    pub fn forwards(&self) -> &[PortForward] {
+        &self.forwards
+    }
+
+    pub fn forward(&self, remote_port: u16) -> Option<&PortForward> {
+        self.forwards
+            .iter()
+            .find(|forward| forward.remote_port == remote_port)
+    }
+
+    /// Forwards a remote port, returning the local port it is reachable on.
+    /// The same port is used locally when it is free.
+    pub fn forward_port(&mut self, remote_port: u16, cx: &mut Context<Self>) -> Task<Result<u16>> {
+        if let Some(forward) = self.forward(remote_port) {
+            return Task::ready(Ok(forward.local_port));
+        }
+        let listening_port = self
+            .listening_ports
+            .iter()
+            .find(|port| port.port == remote_port);
+        let process_name = listening_port.and_then(|port| port.process_name.clone());
+        if self.shares_network_interface {
+            self.add_forward(
+                PortForward {
+                    remote_port,
+                    local_port: remote_port,
+                    process_name,
+                    _accept_connections: None,
+                },
+                cx,
+            );
+            return Task::ready(Ok(remote_port));
+        }
+
+        let remote_host = listening_port
+            .map(|port| port.host.clone())
+            .unwrap_or_else(|| Ipv4Addr::LOCALHOST.to_string().into());
+        cx.spawn(async move |this, cx| {
+            let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, remote_port)).await {
+                Ok(listener) => listener,
+                Err(_) => TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                    .await
+                    .context("listening on a local port")?,
+            };
+            let local_port = listener.local_addr()?.port();
+            this.update(cx, |this, cx| {
+                if let Some(forward) = this.forward(remote_port) {
+                    return forward.local_port;
+                }
+                let accept_connections = cx.spawn(async move |this, cx| {
+                    while let Ok((stream, _)) = listener.accept().await {
+                        let opened = this.update(cx, |this, cx| {
+                            this.open_tunnel(remote_host.clone(), remote_port, stream, cx)
+                        });
+                        if opened.is_err() {
+                            break;
+                        }
+                    }
+                });
+                this.add_forward(
+                    PortForward {
+                        remote_port,
+                        local_port,
+                        process_name,
+                        _accept_connections: Some(accept_connections),
+                    },
+                    cx,
+                );
+                local_port
+            })
+        })
+    }
+
+    /// Stops forwarding a remote port, closing its open connections.
+    pub fn stop_forwarding(&mut self, remote_port: u16, cx: &mut Context<Self>) {
+        self.forwards
+            .retain(|forward| forward.remote_port != remote_port);
+        self.tunnels
+            .retain(|_, tunnel| tunnel.remote_port != remote_port);
+        cx.notify();
+    }
+
+    /// Picks up the ports of local URLs in lines printed in a terminal, which
+    /// finds servers that print their address before the next socket report.
+    pub fn detect_terminal_urls(&mut self, lines: &[String], cx: &mut Context<Self>) {
+        for (host, port) in lines.iter().flat_map(|line| local_url_ports(line)) {
+            if !self.terminal_url_ports.insert(port)
+                || self
+                    .listening_ports
+                    .iter()
+                    .any(|listening| listening.port == port)
+            {
+                continue;
+            }
+            let listening_port = ListeningPort {
+                port,
+                host: host.into(),
+                process_name: None,
+                source: PortSource::Terminal,
+            };
+            self.listening_ports.push(listening_port.clone());
+            self.listening_ports.sort_by_key(|port| port.port);
+            cx.emit(PortForwardStoreEvent::PortDetected(listening_port));
+            cx.notify();
+        }
+    }
+
+    fn add_forward(&mut self, forward: PortForward, cx: &mut Context<Self>) {
+        self.forwards.push(forward);
+        self.forwards.sort_by_key(|forward| forward.remote_port);
+        cx.notify();
+    }
+
+    fn open_tunnel(
+        &mut self,
+        remote_host: Arc<str>,
+        remote_port: u16,
+        stream: TcpStream,
+        cx: &mut Context<Self>,
+    ) {
+        let tunnel_id = post_inc(&mut self.next_tunnel_id);
+        let (tunnel, incoming) = PortTunnel::new();
+        self.tunnels.insert(
+            tunnel_id,
+            Tunnel {
+                remote_port,
+                tunnel,
+            },
+        );
+        let client = self.client.clone();
+        cx.spawn(async move |this, cx| {
+            let relay = async {
+                client
+                    .request(proto::OpenPortTunnel {
+                        project_id: REMOTE_SERVER_PROJECT_ID,
+                        tunnel_id,
+                        host: remote_host.to_string(),
+                        port: remote_port as u32,
+                    })
+                    .await?;
+                relay_port_tunnel(
+                    REMOTE_SERVER_PROJECT_ID,
+                    tunnel_id,
+                    stream,
+                    incoming,
+                    client.clone(),
+                )
+                .await
+            };
+            relay
+                .await
+                .with_context(|| format!("forwarding port {remote_port}"))
+                .log_err();
+            this.update(cx, |this, _| this.tunnels.remove(&tunnel_id))
+                .ok();
+        })
+        .detach();
+    }
+
+    async fn handle_update_listening_ports(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UpdateListeningPorts>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let sockets = envelope
+            .payload
+            .ports
+            .into_iter()
+            .filter_map(|port| {
+                Some(ListeningPort {
+                    port: u16::try_from(port.port).ok()?,
+                    host: port.host.into(),
+                    process_name: port.process_name.map(Into::into),
+                    source: PortSource::Socket,
+                })
+            })
+            .collect::<Vec<_>>();
+        this.update(&mut cx, |this, cx| {
+            let previous_ports = this
+                .listening_ports
+                .iter()
+                .map(|port| port.port)
+                .collect::<HashSet<_>>();
+            if this.received_listening_sockets {
+                for socket in &sockets {
+                    if !previous_ports.contains(&socket.port) {
+                        cx.emit(PortForwardStoreEvent::PortDetected(socket.clone()));
+                    }
+                }
+            }
+            this.received_listening_sockets = true;
+
+            // Ports found in terminals are replaced by their sockets once
+            // reported, and kept while they're still forwarded otherwise.
+            let socket_ports = sockets
+                .iter()
+                .map(|socket| socket.port)
+                .collect::<HashSet<_>>();
+            let forwarded_ports = this
+                .forwards
+                .iter()
+                .map(|forward| forward.remote_port)
+                .collect::<HashSet<_>>();
+            this.listening_ports.retain(|port| {
+                port.source == PortSource::Terminal
+                    && !socket_ports.contains(&port.port)
+                    && forwarded_ports.contains(&port.port)
+            });
+            this.listening_ports.extend(sockets);
+            this.listening_ports.sort_by_key(|port| port.port);
+            cx.notify();
+        });
+        Ok(())
+    }
+
+    async fn handle_port_tunnel_data(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::PortTunnelData>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let proto::PortTunnelData {
+            tunnel_id, data, ..
+        } = envelope.payload;
+        this.update(&mut cx, |this, _| {
+            if let Some(tunnel) = this.tunnels.get_mut(&tunnel_id)
+                && tunnel.tunnel.receive_data(data).log_err().is_none()
+            {
+                this.tunnels.remove(&tunnel_id);
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_port_tunnel_ack(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::PortTunnelAck>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let tunnel_id = envelope.payload.tunnel_id;
+        this.update(&mut cx, |this, _| {
+            if let Some(tunnel) = this.tunnels.get_mut(&tunnel_id)
+                && tunnel.tunnel.receive_ack().log_err().is_none()
+            {
+                this.tunnels.remove(&tunnel_id);
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_close_port_tunnel(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ClosePortTunnel>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, _| {
+            this.tunnels.remove(&envelope.payload.tunnel_id);
+        });
+        Ok(())
+    }
+}
+
+/// Returns the host and port of each local URL in a line of text, with the
+/// host written as an address the remote server can connect to.
+fn local_url_ports(line: &str) -> impl Iterator<Item = (&'static str, u16)> + '_ {
+    LOCAL_URL_REGEX.captures_iter(line).filter_map(|captures| {
+        let host = match &captures[1] {
+            "[::1]" | "[::]" => "::1",
+            _ => "127.0.0.1",
+        };
+        let port = captures[2].parse::<u16>().ok().filter(|port| *port != 0)?;
+        Some((host, port))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_url_ports() {
+        let ports = |line: &str| local_url_ports(line).collect::<Vec<_>>();
+        assert_eq!(
+            ports("  ➜  Local:   http://localhost:5173/"),
+            [("127.0.0.1", 5173)]
+        );
+        assert_eq!(
+            ports("Listening on http://0.0.0.0:8000 and https://[::1]:8443/api"),
+            [("127.0.0.1", 8000), ("::1", 8443)]
+        );
+        assert_eq!(
+            ports("Serving HTTP on 127.0.0.1 port 8000 (http://127.0.0.1:8000/)"),
+            [("127.0.0.1", 8000)]
+        );
+        assert!(ports("See https://example.com:8080/docs").is_empty());
+        assert!(ports("http://localhost:99999").is_empty());
+        assert!(ports("http://localhost:0").is_empty());
+    }
+}
@@ -12,10 +12,13 @@ pub mod image_store;
 pub mod lsp_command;
 pub mod lsp_store;
 pub mod manifest_tree;
+pub mod port_forward_store;
 pub mod prettier_store;
 pub mod project_search;
 pub mod project_settings;
+pub mod review_comment_store;
 pub mod search;
+pub mod shared_terminal_store;
 pub mod task_inventory;
 pub mod task_store;
 pub mod telemetry_snapshot;
@@ -40,7 +43,10 @@ use crate::{
     bookmark_store::BookmarkStore,
     git_store::GitStore,
     lsp_store::{SymbolLocation, log_store::LogKind},
+    port_forward_store::PortForwardStore,
     project_search::SearchResultsHandle,
+    review_comment_store::ReviewCommentStore,
+    shared_terminal_store::SharedTerminalStore,
     trusted_worktrees::{PathTrust, RemoteHostLocation, TrustedWorktrees},
     worktree_store::WorktreeIdCounter,
 };
@@ -221,6 +227,9 @@ pub struct Project {
 
     bookmark_store: Entity<BookmarkStore>,
     breakpoint_store: Entity<BreakpointStore>,
+    port_forward_store: Option<Entity<PortForwardStore>>,
+    review_comment_store: Entity<ReviewCommentStore>,
+    shared_terminal_store: Entity<SharedTerminalStore>,
     collab_client: Arc<client::Client>,
     join_project_response_message_id: u32,
     task_store: Entity<TaskStore>,
@@ -945,6 +954,8 @@ enum EntitySubscription {
     SettingsObserver(PendingEntitySubscription<SettingsObserver>),
     DapStore(PendingEntitySubscription<DapStore>),
     BreakpointStore(PendingEntitySubscription<BreakpointStore>),
+    ReviewCommentStore(PendingEntitySubscription<ReviewCommentStore>),
+    SharedTerminalStore(PendingEntitySubscription<SharedTerminalStore>),
 }
 
 #[derive(Debug, Clone)]
@@ -1200,6 +1211,8 @@ impl Project {
         ToolchainStore::init(&client);
         DapStore::init(&client, cx);
         BreakpointStore::init(&client);
+        ReviewCommentStore::init(&client);
+        SharedTerminalStore::init(&client);
         context_server_store::init(cx);
     }
 
@@ -1304,6 +1317,9 @@ impl Project {
                     cx,
                 )
             });
+            let review_comment_store =
+                cx.new(|cx| ReviewCommentStore::local(git_store.clone(), cx));
+            let shared_terminal_store = cx.new(|_| SharedTerminalStore::local());
 
             let task_store = cx.new(|cx| {
                 TaskStore::local(
@@ -1383,6 +1399,8 @@ impl Project {
                 remote_client: None,
                 bookmark_store,
                 breakpoint_store,
+                review_comment_store,
+                shared_terminal_store,
                 dap_store,
                 agent_server_store,
 
@@ -1404,6 +1422,7 @@ impl Project {
                 agent_location: None,
                 downloading_files: Default::default(),
                 last_worktree_paths: WorktreePaths::default(),
+                port_forward_store: None,
             }
         })
     }
@@ -1518,6 +1537,8 @@ impl Project {
             let bookmark_store =
                 cx.new(|_| BookmarkStore::new(worktree_store.clone(), buffer_store.clone()));
 
+            let port_forward_store = cx.new(|cx| PortForwardStore::new(&remote, cx));
+
             let breakpoint_store = cx.new(|_| {
                 BreakpointStore::remote(
                     REMOTE_SERVER_PROJECT_ID,
@@ -1549,6 +1570,10 @@ impl Project {
                     cx,
                 )
             });
+            // Review comments of remote projects are kept on this machine.
+            let review_comment_store =
+                cx.new(|cx| ReviewCommentStore::local(git_store.clone(), cx));
+            let shared_terminal_store = cx.new(|_| SharedTerminalStore::local());
 
             let task_store = cx.new(|cx| {
                 TaskStore::remote(
@@ -1595,6 +1620,8 @@ impl Project {
                 context_server_store,
                 bookmark_store,
                 breakpoint_store,
+                review_comment_store,
+                shared_terminal_store,
                 dap_store,
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
@@ -1646,6 +1673,7 @@ impl Project {
                 agent_location: None,
                 downloading_files: Default::default(),
                 last_worktree_paths: WorktreePaths::default(),
+                port_forward_store: Some(port_forward_store.clone()),
             };
 
             // remote server -> local machine handlers
@@ -1658,6 +1686,7 @@ impl Project {
             remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &this.settings_observer);
             remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &this.git_store);
             remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &this.agent_server_store);
+            remote_proto.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &port_forward_store);
 
             remote_proto.add_entity_message_handler(Self::handle_create_buffer_for_peer);
             remote_proto.add_entity_message_handler(Self::handle_create_image_for_peer);
@@ -1684,6 +1713,7 @@ impl Project {
             BreakpointStore::init(&remote_proto);
             GitStore::init(&remote_proto);
             AgentServerStore::init_remote(&remote_proto);
+            PortForwardStore::init(&remote_proto);
 
             this
         })
@@ -1714,6 +1744,12 @@ impl Project {
             EntitySubscription::BreakpointStore(
                 client.subscribe_to_entity::<BreakpointStore>(remote_id)?,
             ),
+            EntitySubscription::ReviewCommentStore(
+                client.subscribe_to_entity::<ReviewCommentStore>(remote_id)?,
+            ),
+            EntitySubscription::SharedTerminalStore(
+                client.subscribe_to_entity::<SharedTerminalStore>(remote_id)?,
+            ),
         ];
         let committer = get_git_committer(&cx).await;
         let response = client
@@ -1821,6 +1857,11 @@ impl Project {
                 cx,
             )
         });
+        let review_comment_store = cx.new(|cx| {
+            ReviewCommentStore::remote(remote_id, client.clone().into(), git_store.clone(), cx)
+        });
+        let shared_terminal_store =
+            cx.new(|_| SharedTerminalStore::remote(remote_id, client.clone().into(), path_style));
 
         let task_store = cx.new(|cx| {
             if run_tasks {
@@ -1916,6 +1957,8 @@ impl Project {
                 },
                 bookmark_store: bookmark_store.clone(),
                 breakpoint_store: breakpoint_store.clone(),
+                review_comment_store: review_comment_store.clone(),
+                shared_terminal_store: shared_terminal_store.clone(),
                 dap_store: dap_store.clone(),
                 git_store: git_store.clone(),
                 agent_server_store,
@@ -1934,6 +1977,7 @@ impl Project {
                 agent_location: None,
                 downloading_files: Default::default(),
                 last_worktree_paths: WorktreePaths::default(),
+                port_forward_store: None,
             };
             project.set_role(role, cx);
             for worktree in worktrees {
@@ -1977,6 +2021,12 @@ impl Project {
                 EntitySubscription::BreakpointStore(subscription) => {
                     subscription.set_entity(&breakpoint_store, &cx)
                 }
+                EntitySubscription::ReviewCommentStore(subscription) => {
+                    subscription.set_entity(&review_comment_store, &cx)
+                }
+                EntitySubscription::SharedTerminalStore(subscription) => {
+                    subscription.set_entity(&shared_terminal_store, &cx)
+                }
             })
             .collect::<Vec<_>>();
 
@@ -2206,6 +2256,22 @@ impl Project {
         self.breakpoint_store.clone()
     }
 
+    #[inline]
+    pub fn review_comment_store(&self) -> Entity<ReviewCommentStore> {
+        self.review_comment_store.clone()
+    }
+
+    #[inline]
+    pub fn shared_terminal_store(&self) -> Entity<SharedTerminalStore> {
+        self.shared_terminal_store.clone()
+    }
+
+    /// The ports forwarded from the remote host, for remote projects.
+    #[inline]
+    pub fn port_forward_store(&self) -> Option<Entity<PortForwardStore>> {
+        self.port_forward_store.clone()
+    }
+
     pub fn active_debug_session(&self, cx: &App) -> Option<(Entity<Session>, ActiveStackFrame)> {
         let active_position = self.breakpoint_store.read(cx).active_position()?;
         let session = self
@@ -2818,6 +2884,12 @@ impl Project {
             self.collab_client
                 .subscribe_to_entity(project_id)?
                 .set_entity(&self.breakpoint_store, &cx.to_async()),
+            self.collab_client
+                .subscribe_to_entity(project_id)?
+                .set_entity(&self.review_comment_store, &cx.to_async()),
+            self.collab_client
+                .subscribe_to_entity(project_id)?
+                .set_entity(&self.shared_terminal_store, &cx.to_async()),
             self.collab_client
                 .subscribe_to_entity(project_id)?
                 .set_entity(&self.git_store, &cx.to_async()),
@@ -2835,6 +2907,12 @@ impl Project {
         self.breakpoint_store.update(cx, |breakpoint_store, _| {
             breakpoint_store.shared(project_id, self.collab_client.clone().into())
         });
+        self.review_comment_store.update(cx, |review_comment_store, _| {
+            review_comment_store.shared(project_id, self.collab_client.clone().into())
+        });
+        self.shared_terminal_store.update(cx, |terminal_store, _| {
+            terminal_store.shared(project_id, self.collab_client.clone().into())
+        });
         self.dap_store.update(cx, |dap_store, cx| {
             dap_store.shared(project_id, self.collab_client.clone().into(), cx);
         });
@@ -2940,6 +3018,12 @@ impl Project {
             self.breakpoint_store.update(cx, |breakpoint_store, cx| {
                 breakpoint_store.unshared(cx);
             });
+            self.review_comment_store.update(cx, |review_comment_store, cx| {
+                review_comment_store.unshared(cx);
+            });
+            self.shared_terminal_store.update(cx, |terminal_store, cx| {
+                terminal_store.unshared(cx);
+            });
             self.dap_store.update(cx, |dap_store, cx| {
                 dap_store.unshared(cx);
             });
@@ -5327,6 +5411,11 @@ impl Project {
                 buffer_store.forget_shared_buffers_for(&collaborator.peer_id);
             });
             this.breakpoint_store.read(cx).broadcast();
+            this.dap_store
+                .update(cx, |dap_store, cx| dap_store.broadcast(cx));
+            this.review_comment_store.read(cx).broadcast(cx);
+            this.shared_terminal_store
+                .update(cx, |terminal_store, cx| terminal_store.broadcast(cx));
             cx.emit(Event::CollaboratorJoined(collaborator.peer_id));
             this.collaborators
                 .insert(collaborator.peer_id, collaborator);
@@ -5767,6 +5856,7 @@ impl Project {
         let buffer_store = this.read_with(&cx, |this, _| this.buffer_store().clone());
         let client = this.read_with(&cx, |this, _| this.client());
         let task = cx.spawn(async move |cx| {
+            let reported_query = query.clone();
             let results = this.update(cx, |this, cx| {
                 this.search_impl(query, cx).matching_buffers(cx)
             });
@@ -5811,7 +5901,9 @@ impl Project {
                     peer_id: Some(peer_id),
                     project_id,
                     variant: Some(proto::find_search_candidates_chunk::Variant::Done(
-                        proto::FindSearchCandidatesDone {},
+                        proto::FindSearchCandidatesDone {
+                            pattern_too_complex: reported_query.exceeded_backtrack_limit(),
+                        },
                     )),
                 })
                 .await?;
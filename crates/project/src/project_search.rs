@@ -31,7 +31,7 @@ use worktree::{
 use crate::{
     Project, ProjectItem, ProjectPath, RemotelyCreatedModels,
     buffer_store::BufferStore,
-    search::{MatchPositionHint, SearchQuery, SearchResult},
+    search::{MatchPositionHint, SearchQuery, SearchResult, SyntaxScope},
     worktree_store::WorktreeStore,
 };
 
@@ -266,9 +266,9 @@ impl Search {
                         remote_id,
                         models,
                     } => {
-                        let (handle, rx) = self
-                            .buffer_store
-                            .update(cx, |this, _| this.register_project_search_result_handle());
+                        let (handle, rx) = self.buffer_store.update(cx, |this, _| {
+                            this.register_project_search_result_handle(query.clone())
+                        });
 
                         let cancel_ongoing_search = util::defer({
                             let client = client.clone();
@@ -347,7 +347,12 @@ impl Search {
                 };
 
                 let should_find_all_matches = !tx.is_closed();
+                // Structural and syntax-scoped queries match against syntax
+                // trees, which are only available once the buffer has been parsed.
+                let wait_for_parsing =
+                    query.is_structural() || query.syntax_scope() != SyntaxScope::Everywhere;
 
+                let reported_query = query.clone();
                 let _executor = executor.clone();
                 let worker_pool = executor.spawn(async move {
                     let num_cpus = _executor.num_cpus();
@@ -385,6 +390,7 @@ impl Search {
                             grab_buffer_snapshot_rx,
                             find_all_matches_tx,
                             sorted_matches_tx,
+                            wait_for_parsing,
                             cx.clone(),
                         )
                         .boxed_local(),
@@ -395,8 +401,12 @@ impl Search {
                 };
                 let ensure_matches_are_reported_in_order = if should_find_all_matches {
                     Some(
-                        Self::ensure_matched_ranges_are_reported_in_order(sorted_matches_rx, tx)
-                            .boxed_local(),
+                        Self::ensure_matched_ranges_are_reported_in_order(
+                            reported_query,
+                            sorted_matches_rx,
+                            tx,
+                        )
+                        .boxed_local(),
                     )
                 } else {
                     drop(tx);
@@ -570,10 +580,16 @@ impl Search {
         rx: Receiver<(Entity<Buffer>, MatchPositionHint)>,
         find_all_matches_tx: Sender<FindAllMatchesRequest>,
         results: Sender<oneshot::Receiver<(Entity<Buffer>, Vec<Range<language::Anchor>>)>>,
+        wait_for_parsing: bool,
         mut cx: AsyncApp,
     ) {
         _ = maybe!(async move {
             while let Ok((buffer, line_hint)) = rx.recv().await {
+                if wait_for_parsing {
+                    buffer
+                        .read_with(&mut cx, |buffer, _| buffer.parsing_idle())
+                        .await;
+                }
                 let snapshot = buffer.read_with(&mut cx, |this, _| this.snapshot());
                 let (tx, rx) = oneshot::channel();
                 find_all_matches_tx
@@ -593,6 +609,7 @@ impl Search {
     }
 
     async fn ensure_matched_ranges_are_reported_in_order(
+        query: Arc<SearchQuery>,
         rx: Receiver<oneshot::Receiver<(Entity<Buffer>, Vec<Range<language::Anchor>>)>>,
         tx: Sender<SearchResult>,
     ) {
@@ -609,13 +626,16 @@ impl Search {
                     || matches > Search::MAX_SEARCH_RESULT_RANGES
                 {
                     _ = tx.send(SearchResult::LimitReached).await;
-                    break;
+                    return anyhow::Ok(());
                 }
                 matched_buffers += 1;
                 matches += ranges.len();
 
                 _ = tx.send(SearchResult::Buffer { buffer, ranges }).await?;
             }
+            if query.exceeded_backtrack_limit() {
+                _ = tx.send(SearchResult::PatternTooComplex).await;
+            }
             anyhow::Ok(())
         })
         .await;
@@ -0,0 +1,723 @@
+//! Review comments left on the lines of a repository, kept per repository and
+//! branch so that they outlive the editors they were written in.
+//!
+//! While the file a comment belongs to is open, the comment follows edits
+//! through buffer anchors. Otherwise it is remembered by its rows and the text
+//! of its lines, and relocated by that text when the file is opened again.
+
+pub mod export;
+
+use std::{ops::Range, path::Path, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use git::repository::RepoPath;
+use gpui::{
+    App, AppContext as _, AsyncApp, Context, Entity, EntityId, EventEmitter, SharedString,
+    Subscription, TaskExt as _,
+};
+use language::{Buffer, BufferEvent};
+use rpc::{AnyProtoClient, TypedEnvelope, proto};
+use serde::{Deserialize, Serialize};
+use text::Point;
+
+use crate::git_store::{GitStore, GitStoreEvent, RepositoryEvent, RepositorySnapshot};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ReviewCommentId(pub u64);
+
+impl ReviewCommentId {
+    /// Comments can be created by any collaborator, so ids are random rather
+    /// than sequential. They use the full 64 bits so that collisions between
+    /// collaborators are negligible.
+    fn new() -> Self {
+        Self(rand::random::<u64>())
+    }
+
+    pub fn from_proto(id: u64) -> Self {
+        Self(id)
+    }
+
+    pub fn to_proto(self) -> u64 {
+        self.0
+    }
+}
+
+/// The repository and branch a set of review comments belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ReviewCommentKey {
+    /// The absolute path of the repository's working directory.
+    pub repository: Arc<Path>,
+    /// The checked out branch, or `None` when the HEAD is detached.
+    pub branch: Option<SharedString>,
+}
+
+impl ReviewCommentKey {
+    pub fn for_repository(repository: &RepositorySnapshot) -> Self {
+        Self {
+            repository: repository.work_directory_abs_path.clone(),
+            branch: repository
+                .branch
+                .as_ref()
+                .map(|branch| SharedString::from(branch.name().to_string())),
+        }
+    }
+
+    fn from_proto(repository_path: String, branch: Option<String>) -> Self {
+        Self {
+            repository: Path::new(&repository_path).into(),
+            branch: branch.map(SharedString::from),
+        }
+    }
+}
+
+/// The lines a review comment is attached to, along with their text when the
+/// comment was last anchored.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineAnchor {
+    /// Zero-based first row.
+    pub start_row: u32,
+    /// Zero-based last row, inclusive.
+    pub end_row: u32,
+    /// The text of the rows, joined by newlines.
+    pub text: String,
+}
+
+impl LineAnchor {
+    pub fn for_rows(lines: &[&str], start_row: u32, end_row: u32) -> Self {
+        let last_row = lines.len().saturating_sub(1) as u32;
+        let start_row = start_row.min(last_row);
+        let end_row = end_row.clamp(start_row, last_row);
+        let text = lines
+            .get(start_row as usize..=end_row as usize)
+            .map(|lines| lines.join("\n"))
+            .unwrap_or_default();
+        Self {
+            start_row,
+            end_row,
+            text,
+        }
+    }
+
+    fn for_range(snapshot: &text::BufferSnapshot, range: &Range<text::Anchor>) -> Self {
+        let start_row = range.start.summary::<Point>(snapshot).row;
+        let end_row = range.end.summary::<Point>(snapshot).row.max(start_row);
+        let text = snapshot
+            .text_for_range(
+                Point::new(start_row, 0)..Point::new(end_row, snapshot.line_len(end_row)),
+            )
+            .collect();
+        Self {
+            start_row,
+            end_row,
+            text,
+        }
+    }
+
+    /// Finds where the anchored lines are in `lines`, which may have changed
+    /// since the anchor was taken.
+    ///
+    /// The lines are looked up by their text, preferring the occurrence
+    /// closest to the original rows. If they were edited, the comment follows
+    /// its first line. If that is gone too, the original rows are kept.
+    pub fn relocate(&self, lines: &[&str]) -> Self {
+        let span = self.end_row.saturating_sub(self.start_row);
+        if !self.text.is_empty() {
+            let anchored_lines = self.text.split('\n').collect::<Vec<_>>();
+            if let Some(start_row) = closest_match(lines, &anchored_lines, self.start_row) {
+                return Self::for_rows(
+                    lines,
+                    start_row,
+                    start_row + anchored_lines.len() as u32 - 1,
+                );
+            }
+
+            if let Some(first_line) = anchored_lines
+                .first()
+                .filter(|line| !line.trim().is_empty())
+                && let Some(start_row) = closest_match(lines, &[first_line], self.start_row)
+            {
+                return Self::for_rows(lines, start_row, start_row + span);
+            }
+        }
+        Self::for_rows(lines, self.start_row, self.start_row + span)
+    }
+}
+
+fn closest_match(lines: &[&str], needle: &[&str], preferred_row: u32) -> Option<u32> {
+    if needle.is_empty() || needle.len() > lines.len() {
+        return None;
+    }
+    (0..=lines.len() - needle.len())
+        .filter(|&row| {
+            lines[row..row + needle.len()]
+                .iter()
+                .zip(needle)
+                .all(|(line, needle)| line.trim_end() == needle.trim_end())
+        })
+        .min_by_key(|&row| (row as i64 - preferred_row as i64).abs())
+        .map(|row| row as u32)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReviewComment {
+    pub id: ReviewCommentId,
+    /// The path of the commented file, relative to the repository.
+    pub path: RepoPath,
+    pub lines: LineAnchor,
+    pub body: String,
+}
+
+impl ReviewComment {
+    pub fn new(path: RepoPath, lines: LineAnchor, body: String) -> Self {
+        Self {
+            id: ReviewCommentId::new(),
+            path,
+            lines,
+            body,
+        }
+    }
+
+    pub fn to_proto(&self) -> proto::ReviewComment {
+        proto::ReviewComment {
+            id: self.id.to_proto(),
+            path: self.path.as_unix_str().to_string(),
+            start_row: self.lines.start_row,
+            end_row: self.lines.end_row,
+            line_text: self.lines.text.clone(),
+            body: self.body.clone(),
+        }
+    }
+
+    pub fn from_proto(comment: proto::ReviewComment) -> Result<Self> {
+        Ok(Self {
+            id: ReviewCommentId::from_proto(comment.id),
+            path: RepoPath::from_proto(&comment.path)?,
+            lines: LineAnchor {
+                start_row: comment.start_row,
+                end_row: comment.end_row,
+                text: comment.line_text,
+            },
+            body: comment.body,
+        })
+    }
+}
+
+/// A review comment resolved against an open buffer.
+#[derive(Clone, Debug)]
+pub struct BufferReviewComment {
+    pub id: ReviewCommentId,
+    pub range: Range<text::Anchor>,
+    pub body: String,
+}
+
+struct LiveAnchor {
+    buffer: Entity<Buffer>,
+    range: Range<text::Anchor>,
+}
+
+#[derive(Clone)]
+struct RemoteReviewCommentStore {
+    upstream_client: AnyProtoClient,
+    upstream_project_id: u64,
+}
+
+#[derive(Clone)]
+enum ReviewCommentStoreMode {
+    Local,
+    Remote(RemoteReviewCommentStore),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReviewCommentStoreEvent {
+    /// Comments were added, edited or removed.
+    CommentsChanged(ReviewCommentKey),
+    /// Previously saved comments were loaded.
+    CommentsLoaded(ReviewCommentKey),
+    /// The HEAD of a repository moved, possibly to a branch with comments of
+    /// its own.
+    HeadChanged(ReviewCommentKey),
+}
+
+impl EventEmitter<ReviewCommentStoreEvent> for ReviewCommentStore {}
+
+pub struct ReviewCommentStore {
+    git_store: Entity<GitStore>,
+    comments: HashMap<ReviewCommentKey, Vec<ReviewComment>>,
+    loaded: HashSet<ReviewCommentKey>,
+    live_anchors: HashMap<ReviewCommentId, LiveAnchor>,
+    buffer_subscriptions: HashMap<EntityId, Subscription>,
+    mode: ReviewCommentStoreMode,
+    downstream_client: Option<(AnyProtoClient, u64)>,
+    _git_store_subscription: Subscription,
+}
+
+impl ReviewCommentStore {
+    pub fn init(client: &AnyProtoClient) {
+        client.add_entity_request_handler(Self::handle_update_review_comment);
+        client.add_entity_message_handler(Self::handle_review_comments_for_branch);
+    }
+
+    pub fn local(git_store: Entity<GitStore>, cx: &mut Context<Self>) -> Self {
+        Self::new(git_store, ReviewCommentStoreMode::Local, cx)
+    }
+
+    pub(crate) fn remote(
+        upstream_project_id: u64,
+        upstream_client: AnyProtoClient,
+        git_store: Entity<GitStore>,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new(
+            git_store,
+            ReviewCommentStoreMode::Remote(RemoteReviewCommentStore {
+                upstream_client,
+                upstream_project_id,
+            }),
+            cx,
+        )
+    }
+
+    fn new(
+        git_store: Entity<GitStore>,
+        mode: ReviewCommentStoreMode,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let git_store_subscription =
+            cx.subscribe(&git_store, |_, git_store, event: &GitStoreEvent, cx| {
+                if let GitStoreEvent::RepositoryUpdated(id, RepositoryEvent::HeadChanged, _) = event
+                    && let Some(repository) = git_store.read(cx).repositories().get(id)
+                {
+                    let key = ReviewCommentKey::for_repository(&repository.read(cx));
+                    cx.emit(ReviewCommentStoreEvent::HeadChanged(key));
+                    cx.notify();
+                }
+            });
+        Self {
+            git_store,
+            comments: HashMap::default(),
+            loaded: HashSet::default(),
+            live_anchors: HashMap::default(),
+            buffer_subscriptions: HashMap::default(),
+            mode,
+            downstream_client: None,
+            _git_store_subscription: git_store_subscription,
+        }
+    }
+
+    pub fn shared(&mut self, project_id: u64, downstream_client: AnyProtoClient) {
+        self.downstream_client = Some((downstream_client, project_id));
+    }
+
+    pub(crate) fn unshared(&mut self, cx: &mut Context<Self>) {
+        self.downstream_client.take();
+        cx.notify();
+    }
+
+    /// Sends every comment to the collaborators of a shared project.
+    pub(crate) fn broadcast(&self, cx: &App) {
+        for key in self.comments.keys() {
+            self.send_comments_downstream(key, cx);
+        }
+    }
+
+    /// The repository and branch of the active repository.
+    pub fn active_key(&self, cx: &App) -> Option<ReviewCommentKey> {
+        let repository = self.git_store.read(cx).active_repository()?;
+        Some(ReviewCommentKey::for_repository(&repository.read(cx)))
+    }
+
+    /// The repository and branch of every repository in the project.
+    pub fn repository_keys(&self, cx: &App) -> Vec<ReviewCommentKey> {
+        self.git_store
+            .read(cx)
+            .repositories()
+            .values()
+            .map(|repository| ReviewCommentKey::for_repository(&repository.read(cx)))
+            .collect()
+    }
+
+    /// Marks the comments of a branch as loaded from storage, returning
+    /// whether they had not been already.
+    pub fn mark_loaded(&mut self, key: ReviewCommentKey) -> bool {
+        self.loaded.insert(key)
+    }
+
+    /// Restores comments that were saved for a branch, keeping any that were
+    /// added in the meantime.
+    pub fn load_comments(
+        &mut self,
+        key: ReviewCommentKey,
+        comments: Vec<ReviewComment>,
+        cx: &mut Context<Self>,
+    ) {
+        if comments.is_empty() {
+            return;
+        }
+        let existing = self.comments.entry(key.clone()).or_default();
+        let added_since = std::mem::replace(existing, comments);
+        existing.extend(added_since);
+        self.send_comments_downstream(&key, cx);
+        cx.emit(ReviewCommentStoreEvent::CommentsLoaded(key));
+        cx.notify();
+    }
+
+    /// Returns the comments of a branch, with the lines of comments in open
+    /// buffers brought up to date.
+    pub fn comments(&self, key: &ReviewCommentKey, cx: &App) -> Vec<ReviewComment> {
+        self.comments
+            .get(key)
+            .map(|comments| {
+                comments
+                    .iter()
+                    .map(|comment| self.current_comment(comment, cx))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn comment_count(&self, key: &ReviewCommentKey) -> usize {
+        self.comments.get(key).map_or(0, Vec::len)
+    }
+
+    /// Returns the comments on the current branch that belong to the given
+    /// buffer, resolving the ones that have not been placed in it yet.
+    pub fn comments_for_buffer(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        cx: &mut Context<Self>,
+    ) -> Vec<BufferReviewComment> {
+        let Some((key, path)) = self.key_and_path_for_buffer(buffer, cx) else {
+            return Vec::new();
+        };
+        let Some(comments) = self.comments.get(&key) else {
+            return Vec::new();
+        };
+
+        let snapshot = buffer.read(cx).text_snapshot();
+        let mut text = None;
+        let mut result = Vec::new();
+        for comment in comments.iter().filter(|comment| comment.path == path) {
+            let range = match self.live_anchors.get(&comment.id) {
+                Some(live_anchor) if live_anchor.buffer == *buffer => live_anchor.range.clone(),
+                _ => {
+                    let text = text.get_or_insert_with(|| snapshot.text());
+                    let lines = text.split('\n').collect::<Vec<_>>();
+                    let lines = comment.lines.relocate(&lines);
+                    let range = snapshot.anchor_after(Point::new(lines.start_row, 0))
+                        ..snapshot.anchor_before(Point::new(
+                            lines.end_row,
+                            snapshot.line_len(lines.end_row),
+                        ));
+                    self.live_anchors.insert(
+                        comment.id,
+                        LiveAnchor {
+                            buffer: buffer.clone(),
+                            range: range.clone(),
+                        },
+                    );
+                    range
+                }
+            };
+            result.push(BufferReviewComment {
+                id: comment.id,
+                range,
+                body: comment.body.clone(),
+            });
+        }
+        if !result.is_empty() {
+            self.watch_buffer(buffer, cx);
+        }
+        result
+    }
+
+    /// Adds a comment on the given range of a buffer in a repository.
+    pub fn add_comment(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        range: Range<text::Anchor>,
+        body: String,
+        cx: &mut Context<Self>,
+    ) -> Option<ReviewCommentId> {
+        let (key, path) = self.key_and_path_for_buffer(buffer, cx)?;
+        let snapshot = buffer.read(cx).text_snapshot();
+        let comment = ReviewComment::new(path, LineAnchor::for_range(&snapshot, &range), body);
+        let id = comment.id;
+
+        self.live_anchors.insert(
+            id,
+            LiveAnchor {
+                buffer: buffer.clone(),
+                range,
+            },
+        );
+        self.watch_buffer(buffer, cx);
+        self.comments.entry(key.clone()).or_default().push(comment);
+        self.comment_changed(key, id, cx);
+        Some(id)
+    }
+
+    pub fn update_comment(
+        &mut self,
+        id: ReviewCommentId,
+        body: String,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        let Some((key, comment)) = self.comments.iter_mut().find_map(|(key, comments)| {
+            Some((key, comments.iter_mut().find(|comment| comment.id == id)?))
+        }) else {
+            return false;
+        };
+        comment.body = body;
+        let key = key.clone();
+        self.comment_changed(key, id, cx);
+        true
+    }
+
+    pub fn remove_comment(&mut self, id: ReviewCommentId, cx: &mut Context<Self>) -> bool {
+        let Some(key) = self.comments.iter_mut().find_map(|(key, comments)| {
+            let index = comments.iter().position(|comment| comment.id == id)?;
+            comments.remove(index);
+            Some(key.clone())
+        }) else {
+            return false;
+        };
+        if self.comments.get(&key).is_some_and(Vec::is_empty) {
+            self.comments.remove(&key);
+        }
+        self.live_anchors.remove(&id);
+        self.comment_changed(key, id, cx);
+        true
+    }
+
+    /// Adds comments that were imported from a file to a branch.
+    pub fn import_comments(
+        &mut self,
+        key: ReviewCommentKey,
+        comments: Vec<ReviewComment>,
+        cx: &mut Context<Self>,
+    ) {
+        for comment in comments {
+            let id = comment.id;
+            self.comments.entry(key.clone()).or_default().push(comment);
+            self.comment_changed(key.clone(), id, cx);
+        }
+    }
+
+    fn key_and_path_for_buffer(
+        &self,
+        buffer: &Entity<Buffer>,
+        cx: &App,
+    ) -> Option<(ReviewCommentKey, RepoPath)> {
+        let (repository, path) = self
+            .git_store
+            .read(cx)
+            .repository_and_path_for_buffer_id(buffer.read(cx).remote_id(), cx)?;
+        Some((ReviewCommentKey::for_repository(&repository.read(cx)), path))
+    }
+
+    fn current_comment(&self, comment: &ReviewComment, cx: &App) -> ReviewComment {
+        let Some(live_anchor) = self.live_anchors.get(&comment.id) else {
+            return comment.clone();
+        };
+        let snapshot = live_anchor.buffer.read(cx).text_snapshot();
+        if !snapshot.can_resolve(&live_anchor.range.start)
+            || !snapshot.can_resolve(&live_anchor.range.end)
+        {
+            return comment.clone();
+        }
+        ReviewComment {
+            lines: LineAnchor::for_range(&snapshot, &live_anchor.range),
+            ..comment.clone()
+        }
+    }
+
+    /// Saving a buffer is a good moment to record where its comments moved.
+    fn watch_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        self.buffer_subscriptions
+            .entry(buffer.entity_id())
+            .or_insert_with(|| {
+                cx.subscribe(buffer, |this, buffer, event: &BufferEvent, cx| {
+                    if !matches!(event, BufferEvent::Saved) {
+                        return;
+                    }
+                    if let ReviewCommentStoreMode::Remote(_) = this.mode {
+                        return;
+                    }
+                    let Some((key, _)) = this.key_and_path_for_buffer(&buffer, cx) else {
+                        return;
+                    };
+                    let has_comments = this
+                        .live_anchors
+                        .values()
+                        .any(|live_anchor| live_anchor.buffer == buffer);
+                    if has_comments {
+                        this.send_comments_downstream(&key, cx);
+                        cx.emit(ReviewCommentStoreEvent::CommentsChanged(key));
+                    }
+                })
+            });
+    }
+
+    fn comment_changed(
+        &mut self,
+        key: ReviewCommentKey,
+        id: ReviewCommentId,
+        cx: &mut Context<Self>,
+    ) {
+        match &self.mode {
+            ReviewCommentStoreMode::Remote(remote) => {
+                let comment = self
+                    .comments
+                    .get(&key)
+                    .and_then(|comments| comments.iter().find(|comment| comment.id == id))
+                    .map(|comment| self.current_comment(comment, cx).to_proto());
+                let request = remote.upstream_client.request(proto::UpdateReviewComment {
+                    project_id: remote.upstream_project_id,
+                    repository_path: key.repository.to_string_lossy().into_owned(),
+                    branch: key.branch.as_ref().map(ToString::to_string),
+                    comment_id: id.to_proto(),
+                    comment,
+                });
+                cx.background_spawn(request).detach_and_log_err(cx);
+            }
+            ReviewCommentStoreMode::Local => self.send_comments_downstream(&key, cx),
+        }
+        cx.emit(ReviewCommentStoreEvent::CommentsChanged(key));
+        cx.notify();
+    }
+
+    fn send_comments_downstream(&self, key: &ReviewCommentKey, cx: &App) {
+        let Some((client, project_id)) = &self.downstream_client else {
+            return;
+        };
+        client
+            .send(proto::ReviewCommentsForBranch {
+                project_id: *project_id,
+                repository_path: key.repository.to_string_lossy().into_owned(),
+                branch: key.branch.as_ref().map(ToString::to_string),
+                comments: self
+                    .comments(key, cx)
+                    .iter()
+                    .map(ReviewComment::to_proto)
+                    .collect(),
+            })
+            .ok();
+    }
+
+    async fn handle_update_review_comment(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UpdateReviewComment>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let payload = envelope.payload;
+        let key = ReviewCommentKey::from_proto(payload.repository_path, payload.branch);
+        let id = ReviewCommentId::from_proto(payload.comment_id);
+        let comment = payload
+            .comment
+            .map(ReviewComment::from_proto)
+            .transpose()
+            .context("invalid review comment")?;
+
+        this.update(&mut cx, |this, cx| {
+            // The lines were anchored by the collaborator, so let them be
+            // resolved again on this side.
+            this.live_anchors.remove(&id);
+            let comments = this.comments.entry(key.clone()).or_default();
+            match comment {
+                Some(comment) => {
+                    if let Some(existing) = comments.iter_mut().find(|comment| comment.id == id) {
+                        *existing = comment;
+                    } else {
+                        comments.push(comment);
+                    }
+                }
+                None => comments.retain(|comment| comment.id != id),
+            }
+            if comments.is_empty() {
+                this.comments.remove(&key);
+            }
+            this.comment_changed(key, id, cx);
+        });
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_review_comments_for_branch(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ReviewCommentsForBranch>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let payload = envelope.payload;
+        let key = ReviewCommentKey::from_proto(payload.repository_path, payload.branch);
+        let comments = payload
+            .comments
+            .into_iter()
+            .map(ReviewComment::from_proto)
+            .collect::<Result<Vec<_>>>()?;
+
+        this.update(&mut cx, |this, cx| {
+            let ids = comments
+                .iter()
+                .map(|comment| comment.id)
+                .collect::<HashSet<_>>();
+            if let Some(previous) = this.comments.get(&key) {
+                for comment in previous {
+                    if !ids.contains(&comment.id) {
+                        this.live_anchors.remove(&comment.id);
+                    }
+                }
+            }
+            if comments.is_empty() {
+                this.comments.remove(&key);
+            } else {
+                this.comments.insert(key.clone(), comments);
+            }
+            cx.emit(ReviewCommentStoreEvent::CommentsChanged(key));
+            cx.notify();
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_relocate_follows_moved_lines() {
+        let anchor = LineAnchor::for_rows(&["a", "fn f() {", "}", "b"], 1, 2);
+        assert_eq!(anchor.text, "fn f() {\n}");
+
+        let lines = ["x", "y", "a", "fn f() {", "}", "b"];
+        let relocated = anchor.relocate(&lines);
+        assert_eq!((relocated.start_row, relocated.end_row), (3, 4));
+
+        // The closest of several occurrences wins.
+        let lines = ["fn f() {", "}", "a", "b", "c", "fn f() {", "}"];
+        let relocated = LineAnchor {
+            start_row: 4,
+            ..anchor.clone()
+        }
+        .relocate(&lines);
+        assert_eq!((relocated.start_row, relocated.end_row), (5, 6));
+    }
+
+    #[test]
+    fn test_relocate_after_edits() {
+        let anchor = LineAnchor::for_rows(&["fn f() {", "    a();", "}"], 0, 1);
+        let lines = ["// doc", "fn f() {", "    b();", "}"];
+        let relocated = anchor.relocate(&lines);
+        assert_eq!((relocated.start_row, relocated.end_row), (1, 2));
+        assert_eq!(relocated.text, "fn f() {\n    b();");
+
+        let lines = ["completely", "different"];
+        let relocated = LineAnchor {
+            start_row: 5,
+            end_row: 6,
+            text: "gone".into(),
+        }
+        .relocate(&lines);
+        assert_eq!((relocated.start_row, relocated.end_row), (1, 1));
+    }
+}
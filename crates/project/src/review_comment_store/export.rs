@@ -0,0 +1,364 @@
+//! Exchanging review comments with other tools, either as Markdown or as the
+//! payload of GitHub's "create a review for a pull request" endpoint.
+
+use anyhow::{Context as _, Result};
+use git::repository::RepoPath;
+use serde::{Deserialize, Serialize};
+
+use super::{LineAnchor, ReviewComment};
+
+const COMMENT_HEADING: &str = "## ";
+
+/// Writes comments as Markdown, with a section per comment that quotes the
+/// commented lines, so that [`from_markdown`] can anchor them again.
+pub fn to_markdown(branch: Option<&str>, comments: &[ReviewComment]) -> String {
+    let mut markdown = match branch {
+        Some(branch) => format!("# Review comments on `{branch}`\n"),
+        None => "# Review comments\n".to_string(),
+    };
+    for comment in sorted(comments) {
+        let lines = &comment.lines;
+        markdown.push_str(&format!(
+            "\n{COMMENT_HEADING}`{}` {}\n",
+            comment.path.as_unix_str(),
+            if lines.start_row == lines.end_row {
+                format!("line {}", lines.start_row + 1)
+            } else {
+                format!("lines {}-{}", lines.start_row + 1, lines.end_row + 1)
+            }
+        ));
+        if !lines.text.is_empty() {
+            let fence = "`".repeat(longest_backtick_run(&lines.text).max(2) + 1);
+            markdown.push_str(&format!("\n{fence}\n{}\n{fence}\n", lines.text));
+        }
+        markdown.push_str(&format!("\n{}\n", comment.body.trim()));
+    }
+    markdown
+}
+
+/// Reads comments written by [`to_markdown`].
+pub fn from_markdown(markdown: &str) -> Result<Vec<ReviewComment>> {
+    let mut comments = Vec::new();
+    let mut lines = markdown.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some(heading) = line.strip_prefix(COMMENT_HEADING) else {
+            continue;
+        };
+        let (path, start_row, end_row) = parse_heading(heading)
+            .with_context(|| format!("invalid review comment heading: {heading:?}"))?;
+
+        while lines.next_if(|line| line.trim().is_empty()).is_some() {}
+        let mut text = String::new();
+        if let Some(fence) = lines.peek().copied().and_then(code_fence) {
+            lines.next();
+            let quoted_lines = lines
+                .by_ref()
+                .take_while(|line| line.trim_end() != fence)
+                .collect::<Vec<_>>();
+            text = quoted_lines.join("\n");
+        }
+
+        let mut body = Vec::new();
+        while let Some(line) = lines.next_if(|line| !line.starts_with(COMMENT_HEADING)) {
+            body.push(line);
+        }
+
+        comments.push(ReviewComment::new(
+            path,
+            LineAnchor {
+                start_row,
+                end_row,
+                text,
+            },
+            body.join("\n").trim().to_string(),
+        ));
+    }
+    Ok(comments)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GitHubReview {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    commit_id: Option<String>,
+    #[serde(default)]
+    body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    event: Option<String>,
+    #[serde(default)]
+    comments: Vec<GitHubReviewComment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GitHubReviewComment {
+    path: String,
+    line: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_line: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    side: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_side: Option<String>,
+    body: String,
+}
+
+/// Writes comments as the JSON body of a request to GitHub's
+/// `POST /repos/{owner}/{repo}/pulls/{pull_number}/reviews` endpoint.
+pub fn to_github_review(commit_id: Option<&str>, comments: &[ReviewComment]) -> Result<String> {
+    let review = GitHubReview {
+        commit_id: commit_id.map(ToString::to_string),
+        body: String::new(),
+        event: Some("COMMENT".to_string()),
+        comments: sorted(comments)
+            .into_iter()
+            .map(|comment| {
+                let lines = &comment.lines;
+                let is_multiline = lines.start_row != lines.end_row;
+                GitHubReviewComment {
+                    path: comment.path.as_unix_str().to_string(),
+                    line: lines.end_row + 1,
+                    start_line: is_multiline.then_some(lines.start_row + 1),
+                    side: Some("RIGHT".to_string()),
+                    start_side: is_multiline.then(|| "RIGHT".to_string()),
+                    body: comment.body.clone(),
+                }
+            })
+            .collect(),
+    };
+    Ok(serde_json::to_string_pretty(&review)?)
+}
+
+/// Reads the comments of a GitHub review payload.
+///
+/// Comments on the left side of a diff refer to lines that no longer exist in
+/// the working copy, so they are skipped.
+pub fn from_github_review(json: &str) -> Result<Vec<ReviewComment>> {
+    let review: GitHubReview = serde_json::from_str(json).context("parsing GitHub review")?;
+    review
+        .comments
+        .into_iter()
+        .filter(|comment| {
+            let is_left = comment.side.as_deref() == Some("LEFT");
+            if is_left {
+                log::warn!(
+                    "Skipping review comment on removed lines of {}",
+                    comment.path
+                );
+            }
+            !is_left
+        })
+        .map(|comment| {
+            let end_row = comment
+                .line
+                .checked_sub(1)
+                .context("review comment lines start at 1")?;
+            let start_row = comment
+                .start_line
+                .map_or(Some(end_row), |line| line.checked_sub(1))
+                .context("review comment lines start at 1")?
+                .min(end_row);
+            Ok(ReviewComment::new(
+                RepoPath::new(&comment.path)?,
+                LineAnchor {
+                    start_row,
+                    end_row,
+                    text: String::new(),
+                },
+                comment.body,
+            ))
+        })
+        .collect()
+}
+
+fn sorted(comments: &[ReviewComment]) -> Vec<&ReviewComment> {
+    let mut comments = comments.iter().collect::<Vec<_>>();
+    comments.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then(a.lines.start_row.cmp(&b.lines.start_row))
+    });
+    comments
+}
+
+fn parse_heading(heading: &str) -> Option<(RepoPath, u32, u32)> {
+    let (path, rows) = heading.strip_prefix('`')?.rsplit_once("` ")?;
+    let rows = rows
+        .strip_prefix("lines ")
+        .or_else(|| rows.strip_prefix("line "))?;
+    let (start, end): (u32, u32) = match rows.split_once('-') {
+        Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+        None => {
+            let row = rows.trim().parse().ok()?;
+            (row, row)
+        }
+    };
+    if start == 0 || end < start {
+        return None;
+    }
+    Some((RepoPath::new(path).ok()?, start - 1, end - 1))
+}
+
+fn code_fence(line: &str) -> Option<&str> {
+    let fence_len = line.chars().take_while(|c| *c == '`').count();
+    (fence_len >= 3).then(|| &line[..fence_len])
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::repository::repo_path;
+    use pretty_assertions::assert_eq;
+
+    fn comment(path: &str, start_row: u32, end_row: u32, text: &str, body: &str) -> ReviewComment {
+        ReviewComment::new(
+            repo_path(path),
+            LineAnchor {
+                start_row,
+                end_row,
+                text: text.to_string(),
+            },
+            body.to_string(),
+        )
+    }
+
+    fn without_ids(comments: &[ReviewComment]) -> Vec<(String, LineAnchor, String)> {
+        comments
+            .iter()
+            .map(|comment| {
+                (
+                    comment.path.as_unix_str().to_string(),
+                    comment.lines.clone(),
+                    comment.body.clone(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let comments = vec![
+            comment(
+                "src/main.rs",
+                4,
+                6,
+                "fn main() {\n    run();\n}",
+                "Handle errors here.",
+            ),
+            comment(
+                "README.md",
+                0,
+                0,
+                "Use ```rust``` blocks",
+                "Typo.\n\nSecond paragraph.",
+            ),
+        ];
+
+        let markdown = to_markdown(Some("feature"), &comments);
+        assert_eq!(
+            markdown,
+            concat!(
+                "# Review comments on `feature`\n",
+                "\n## `README.md` line 1\n",
+                "\n````\nUse ```rust``` blocks\n````\n",
+                "\nTypo.\n\nSecond paragraph.\n",
+                "\n## `src/main.rs` lines 5-7\n",
+                "\n```\nfn main() {\n    run();\n}\n```\n",
+                "\nHandle errors here.\n",
+            )
+        );
+
+        let mut expected = without_ids(&comments);
+        expected.reverse();
+        assert_eq!(without_ids(&from_markdown(&markdown).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_markdown_without_quoted_lines() {
+        let comments = from_markdown("## `lib.rs` line 3\nRename this.\n").unwrap();
+        assert_eq!(
+            without_ids(&comments),
+            vec![(
+                "lib.rs".to_string(),
+                LineAnchor {
+                    start_row: 2,
+                    end_row: 2,
+                    text: String::new(),
+                },
+                "Rename this.".to_string(),
+            )]
+        );
+
+        assert!(from_markdown("## `lib.rs` line 0\n").is_err());
+    }
+
+    #[test]
+    fn test_github_review_round_trip() {
+        let comments = vec![
+            comment(
+                "src/main.rs",
+                4,
+                6,
+                "fn main() {\n    run();\n}",
+                "Handle errors here.",
+            ),
+            comment("src/lib.rs", 9, 9, "pub mod a;", "Unused."),
+        ];
+
+        let json = to_github_review(Some("abc123"), &comments).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "commit_id": "abc123",
+                "body": "",
+                "event": "COMMENT",
+                "comments": [
+                    {
+                        "path": "src/lib.rs",
+                        "line": 10,
+                        "side": "RIGHT",
+                        "body": "Unused.",
+                    },
+                    {
+                        "path": "src/main.rs",
+                        "line": 7,
+                        "start_line": 5,
+                        "side": "RIGHT",
+                        "start_side": "RIGHT",
+                        "body": "Handle errors here.",
+                    },
+                ],
+            })
+        );
+
+        let imported = from_github_review(&json).unwrap();
+        assert_eq!(
+            imported
+                .iter()
+                .map(|comment| (
+                    comment.path.as_unix_str(),
+                    comment.lines.start_row,
+                    comment.lines.end_row
+                ))
+                .collect::<Vec<_>>(),
+            vec![("src/lib.rs", 9, 9), ("src/main.rs", 4, 6)]
+        );
+    }
+
+    #[test]
+    fn test_github_review_skips_removed_lines() {
+        let json = r#"{
+            "comments": [
+                { "path": "a.rs", "line": 2, "side": "LEFT", "body": "Old." },
+                { "path": "a.rs", "line": 3, "body": "New." }
+            ]
+        }"#;
+        let imported = from_github_review(json).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].body, "New.");
+        assert_eq!(imported[0].lines.start_row, 2);
+    }
+}
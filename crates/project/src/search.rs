@@ -11,7 +11,10 @@ use std::{
     collections::BTreeSet,
     io::{BufRead, BufReader, Read},
     ops::Range,
-    sync::{Arc, LazyLock},
+    sync::{
+        Arc, LazyLock,
+        atomic::{AtomicBool, Ordering},
+    },
 };
 use text::Anchor;
 use util::{
@@ -19,6 +22,10 @@ use util::{
     rel_path::RelPath,
 };
 
+pub mod structural;
+
+use structural::StructuralPattern;
+
 #[derive(Debug)]
 pub enum SearchResult {
     Buffer {
@@ -26,6 +33,9 @@ pub enum SearchResult {
         ranges: Vec<Range<Anchor>>,
     },
     LimitReached,
+    /// The regex needed too much backtracking on some input, so not every
+    /// match was found.
+    PatternTooComplex,
     WaitingForScan,
     Searching,
 }
@@ -44,6 +54,55 @@ pub struct SearchInputs {
     files_to_exclude: PathMatcher,
     match_full_paths: bool,
     buffers: Option<Vec<Entity<Buffer>>>,
+    syntax_scope: SyntaxScope,
+}
+
+/// Restricts matches to the parts of a buffer that its language's highlights
+/// query captures as a given category.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyntaxScope {
+    #[default]
+    Everywhere,
+    Comments,
+    Strings,
+    /// Everything but comments and strings.
+    Code,
+}
+
+impl SyntaxScope {
+    pub fn from_proto(scope: proto::SearchSyntaxScope) -> Self {
+        match scope {
+            proto::SearchSyntaxScope::Everywhere => Self::Everywhere,
+            proto::SearchSyntaxScope::Comments => Self::Comments,
+            proto::SearchSyntaxScope::Strings => Self::Strings,
+            proto::SearchSyntaxScope::Code => Self::Code,
+        }
+    }
+
+    pub fn to_proto(self) -> proto::SearchSyntaxScope {
+        match self {
+            Self::Everywhere => proto::SearchSyntaxScope::Everywhere,
+            Self::Comments => proto::SearchSyntaxScope::Comments,
+            Self::Strings => proto::SearchSyntaxScope::Strings,
+            Self::Code => proto::SearchSyntaxScope::Code,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxCategory {
+    Comment,
+    String,
+}
+
+impl SyntaxCategory {
+    fn for_capture_name(name: &str) -> Option<Self> {
+        match name.split('.').next()? {
+            "comment" => Some(Self::Comment),
+            "string" => Some(Self::String),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -71,6 +130,9 @@ impl SearchInputs {
     pub fn buffers(&self) -> &Option<Vec<Entity<Buffer>>> {
         &self.buffers
     }
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.syntax_scope
+    }
 }
 #[derive(Clone, Debug)]
 pub enum SearchQuery {
@@ -91,6 +153,16 @@ pub enum SearchQuery {
         one_match_per_line: bool,
         inner: SearchInputs,
         escaped: bool,
+        /// Set once matching gave up on some input, shared between clones.
+        exceeded_backtrack_limit: Arc<AtomicBool>,
+    },
+    /// A code-shaped pattern with metavariables, matched against syntax trees.
+    /// See [`structural`] for the pattern syntax.
+    Structural {
+        pattern: StructuralPattern,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
     },
 }
 
@@ -141,6 +213,7 @@ impl SearchQuery {
             files_to_include,
             match_full_paths,
             buffers,
+            syntax_scope: SyntaxScope::default(),
         };
         Ok(Self::Text {
             search,
@@ -175,6 +248,7 @@ impl SearchQuery {
             files_to_exclude,
             match_full_paths,
             buffers,
+            syntax_scope: SyntaxScope::default(),
         };
         Self::build_regex(
             query,
@@ -187,6 +261,35 @@ impl SearchQuery {
         )
     }
 
+    /// Create a structural query, matching a code-shaped pattern against the
+    /// syntax trees of the searched buffers.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let mut query = query.to_string();
+        text::LineEnding::normalize(&mut query);
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_include,
+            files_to_exclude,
+            match_full_paths,
+            buffers,
+            syntax_scope: SyntaxScope::default(),
+        };
+        Ok(Self::Structural {
+            pattern,
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     /// Create a regex query from a literal string, escaping any regex
     /// metacharacters so that the resulting query matches the literal text.
     ///
@@ -210,6 +313,7 @@ impl SearchQuery {
             files_to_exclude,
             match_full_paths,
             buffers,
+            syntax_scope: SyntaxScope::default(),
         };
         Self::build_regex(
             regex::escape(&query),
@@ -268,6 +372,7 @@ impl SearchQuery {
             inner,
             one_match_per_line,
             escaped,
+            exceeded_backtrack_limit: Arc::default(),
         })
     }
 
@@ -335,7 +440,17 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        let syntax_scope = SyntaxScope::from_proto(message.syntax_scope());
+        let query = if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                PathMatcher::new(files_to_include, path_style)?,
+                PathMatcher::new(files_to_exclude, path_style)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -358,7 +473,8 @@ impl SearchQuery {
                 message.match_full_paths,
                 None, // search opened only don't need search remote
             )
-        }
+        }?;
+        Ok(query.with_syntax_scope(syntax_scope))
     }
 
     pub fn with_replacement(mut self, new_replacement: String) -> Self {
@@ -370,6 +486,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -377,18 +497,31 @@ impl SearchQuery {
         }
     }
 
+    pub fn with_syntax_scope(mut self, syntax_scope: SyntaxScope) -> Self {
+        match self {
+            Self::Text { ref mut inner, .. }
+            | Self::Regex { ref mut inner, .. }
+            | Self::Structural { ref mut inner, .. } => {
+                inner.syntax_scope = syntax_scope;
+                self
+            }
+        }
+    }
+
     pub fn to_proto(&self) -> proto::SearchQuery {
         let mut files_to_include = self.files_to_include().sources();
         let mut files_to_exclude = self.files_to_exclude().sources();
         proto::SearchQuery {
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
             files_to_include: files_to_include.clone().map(ToOwned::to_owned).collect(),
             files_to_exclude: files_to_exclude.clone().map(ToOwned::to_owned).collect(),
             match_full_paths: self.match_full_paths(),
+            syntax_scope: self.syntax_scope().to_proto() as i32,
             // Populate legacy fields for backwards compatibility
             files_to_include_legacy: files_to_include.join(","),
             files_to_exclude_legacy: files_to_exclude.join(","),
@@ -436,25 +569,46 @@ impl SearchQuery {
                     Ok(None)
                 }
             }
-            Self::Regex { regex, .. } => {
+            Self::Regex {
+                regex,
+                exceeded_backtrack_limit,
+                ..
+            } => {
                 let mut text = String::new();
 
                 reader.read_to_string(&mut text)?;
                 text::LineEnding::normalize(&mut text);
-                if let Some(m) = regex.find(&text)? {
+                let found = regex.find(&text).inspect_err(|error| {
+                    if matches!(error, fancy_regex::Error::RuntimeError(_)) {
+                        exceeded_backtrack_limit.store(true, Ordering::Relaxed);
+                    }
+                })?;
+                if let Some(m) = found {
                     Ok(Some(MatchPositionHint::ByteOffset(m.start())))
                 } else {
                     Ok(None)
                 }
             }
+            // Whether a file matches can only be told once it's parsed, so
+            // only rule out files that lack some literal part of the pattern.
+            Self::Structural { pattern, .. } => {
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                text::LineEnding::normalize(&mut text);
+                if pattern.could_match(&text) {
+                    Ok(Some(MatchPositionHint::default()))
+                } else {
+                    Ok(None)
+                }
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Expands `hit` against its line so lookaround assertions retain context.
@@ -503,9 +657,32 @@ impl SearchQuery {
             SearchQuery::Regex {
                 replacement: None, ..
             } => None,
+
+            // Structural replacements depend on the syntax tree around the
+            // hit, see `structural_replacement_for`.
+            SearchQuery::Structural { .. } => None,
         }
     }
 
+    /// Expands the replacement template of a structural query with the
+    /// metavariables captured by the match at `hit` in `buffer`.
+    pub fn structural_replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        hit: Range<usize>,
+    ) -> Option<String> {
+        let SearchQuery::Structural {
+            pattern,
+            replacement: Some(replacement),
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let captures = pattern.captures_for_range(buffer, hit)?;
+        Some(structural::expand_template(replacement, &captures, buffer))
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -518,6 +695,18 @@ impl SearchQuery {
         }
 
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
+        if let Self::Structural { pattern, .. } = self {
+            let subrange = subrange.unwrap_or(0..buffer.len());
+            let matches = pattern
+                .find_matches(buffer)
+                .await
+                .into_iter()
+                .filter(|range| subrange.start <= range.start && range.end <= subrange.end)
+                .map(|range| range.start - subrange.start..range.end - subrange.start)
+                .collect();
+            return self.retain_in_syntax_scope(buffer, range_offset, matches);
+        }
+
         let rope = if let Some(range) = subrange {
             buffer.as_rope().slice(range)
         } else {
@@ -563,6 +752,7 @@ impl SearchQuery {
             Self::Regex {
                 regex,
                 one_match_per_line,
+                exceeded_backtrack_limit,
                 ..
             } => {
                 let text = rope.to_string();
@@ -572,22 +762,67 @@ impl SearchQuery {
                         yield_now().await;
                     }
 
-                    if let std::result::Result::Ok(mat) = mat {
-                        let should_push = if *one_match_per_line {
-                            // ensure that only one match per line is returned.
-                            let pos = buffer.offset_to_point(mat.start());
-                            seen_lines.insert(pos.row)
-                        } else {
-                            true
-                        };
-                        if should_push {
-                            matches.push(mat.start()..mat.end());
+                    let mat = match mat {
+                        std::result::Result::Ok(mat) => mat,
+                        // Patterns with lookaround or backreferences are matched by
+                        // backtracking, which gives up on pathological inputs.
+                        Err(error) => {
+                            log::warn!("stopped searching for {:?}: {error}", regex.as_str());
+                            exceeded_backtrack_limit.store(true, Ordering::Relaxed);
+                            break;
                         }
+                    };
+                    let should_push = if *one_match_per_line {
+                        // ensure that only one match per line is returned.
+                        let pos = buffer.offset_to_point(mat.start());
+                        seen_lines.insert(pos.row)
+                    } else {
+                        true
+                    };
+                    if should_push {
+                        matches.push(mat.start()..mat.end());
                     }
                 }
             }
+            Self::Structural { .. } => {}
         }
 
+        self.retain_in_syntax_scope(buffer, range_offset, matches)
+    }
+
+    /// Drops the matches that fall outside of this query's [`SyntaxScope`],
+    /// given as offsets relative to `range_offset`.
+    fn retain_in_syntax_scope(
+        &self,
+        buffer: &BufferSnapshot,
+        range_offset: usize,
+        mut matches: Vec<Range<usize>>,
+    ) -> Vec<Range<usize>> {
+        let syntax_scope = self.syntax_scope();
+        if syntax_scope == SyntaxScope::Everywhere || matches.is_empty() {
+            return matches;
+        }
+
+        let start = matches.iter().map(|range| range.start).min().unwrap_or(0);
+        let end = matches.iter().map(|range| range.end).max().unwrap_or(0);
+        let categorized_ranges =
+            syntax_categories(buffer, range_offset + start..range_offset + end);
+        matches.retain(|range| {
+            let range = range.start + range_offset..range.end + range_offset;
+            let ix = categorized_ranges
+                .partition_point(|(categorized, _)| categorized.start <= range.start);
+            let category = ix
+                .checked_sub(1)
+                .map(|ix| &categorized_ranges[ix])
+                .filter(|(categorized, _)| range.end <= categorized.end)
+                .map(|(_, category)| *category);
+            match syntax_scope {
+                SyntaxScope::Everywhere => true,
+                SyntaxScope::Comments => category == Some(SyntaxCategory::Comment),
+                SyntaxScope::Strings => category == Some(SyntaxCategory::String),
+                SyntaxScope::Code => category.is_none(),
+            }
+        });
         matches
     }
 
@@ -603,6 +838,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -610,6 +846,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -621,13 +858,24 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        self.as_inner().syntax_scope()
+    }
+
     pub fn is_regex(&self) -> bool {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn replacement_requires_context(&self) -> bool {
         matches!(self, Self::Regex { escaped: false, .. })
     }
@@ -673,9 +921,36 @@ impl SearchQuery {
             }
         }
     }
+
+    /// Whether a regex gave up matching some input because it needed too much
+    /// backtracking, which means that searches with it missed matches.
+    pub fn exceeded_backtrack_limit(&self) -> bool {
+        match self {
+            Self::Regex {
+                exceeded_backtrack_limit,
+                ..
+            } => exceeded_backtrack_limit.load(Ordering::Relaxed),
+            Self::Text { .. } | Self::Structural { .. } => false,
+        }
+    }
+
+    /// Records that the remote host searching for candidates with this query
+    /// exceeded the backtrack limit.
+    pub(crate) fn set_exceeded_backtrack_limit(&self) {
+        if let Self::Regex {
+            exceeded_backtrack_limit,
+            ..
+        } = self
+        {
+            exceeded_backtrack_limit.store(true, Ordering::Relaxed);
+        }
+    }
+
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 
@@ -704,12 +979,59 @@ impl SearchQuery {
                     matches.push(mat.start()..mat.end());
                 }
             }
-            Self::Regex { regex, .. } => {
-                for mat in regex.find_iter(text).flatten() {
+            Self::Regex {
+                regex,
+                exceeded_backtrack_limit,
+                ..
+            } => {
+                for mat in regex.find_iter(text) {
+                    let std::result::Result::Ok(mat) = mat else {
+                        exceeded_backtrack_limit.store(true, Ordering::Relaxed);
+                        break;
+                    };
                     matches.push(mat.start()..mat.end());
                 }
             }
+            // Structural patterns can't be matched without a syntax tree.
+            Self::Structural { .. } => {}
         }
         matches
     }
 }
+
+/// Returns the outermost comment and string ranges intersecting `range`, as
+/// captured by the highlights queries of the buffer's syntax layers, sorted by
+/// their start.
+fn syntax_categories(
+    buffer: &BufferSnapshot,
+    range: Range<usize>,
+) -> Vec<(Range<usize>, SyntaxCategory)> {
+    let mut captures = buffer.captures(range, |grammar| {
+        grammar
+            .highlights_config
+            .as_ref()
+            .map(|config| &config.query)
+    });
+
+    let mut categorized_ranges: Vec<(Range<usize>, SyntaxCategory)> = Vec::new();
+    while let Some(capture) = captures.peek() {
+        let category = captures.grammars()[capture.grammar_index]
+            .highlights_config
+            .as_ref()
+            .and_then(|config| {
+                let name = config.query.capture_names().get(capture.index as usize)?;
+                SyntaxCategory::for_capture_name(name)
+            });
+        if let Some(category) = category {
+            let node_range = capture.node.byte_range();
+            let is_nested = categorized_ranges
+                .last()
+                .is_some_and(|(outer, _)| outer.end >= node_range.end);
+            if !is_nested {
+                categorized_ranges.push((node_range, category));
+            }
+        }
+        captures.advance();
+    }
+    categorized_ranges
+}
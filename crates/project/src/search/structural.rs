@@ -0,0 +1,395 @@
+//! Structural search: matching code-shaped patterns against syntax trees.
+//!
+//! A pattern is a snippet of code in the searched buffer's language, in which
+//! `$NAME` stands for any single syntax node and `$$$NAME` for any sequence of
+//! sibling nodes (including none). `$_` and `$$$` match without capturing.
+//! Using the same metavariable twice requires both occurrences to match the
+//! same text. Replacement templates refer to captures with the same syntax.
+
+use anyhow::{Result, bail};
+use collections::HashMap;
+use language::{BufferSnapshot, Language, Node};
+use smol::future::yield_now;
+use std::{ops::Range, sync::Arc};
+
+/// Placeholders substituted for metavariables before the pattern is parsed, as
+/// they are valid identifiers in most languages while `$NAME` often isn't.
+const SINGLE_PLACEHOLDER_PREFIX: &str = "__zed_meta_";
+const MULTI_PLACEHOLDER_PREFIX: &str = "__zed_metas_";
+const ANONYMOUS_NAME: &str = "_";
+
+#[derive(Clone, Debug)]
+pub struct StructuralPattern {
+    /// The pattern with metavariables replaced by placeholder identifiers.
+    source: Arc<str>,
+    /// Literal words of the pattern, used to cheaply rule out files.
+    literals: Vec<String>,
+}
+
+/// Byte ranges captured by metavariables, keyed by metavariable name.
+pub type Captures = HashMap<String, Range<usize>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Metavariable<'a> {
+    Single(&'a str),
+    Multi(&'a str),
+}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let pattern = pattern.trim();
+        let mut source = String::with_capacity(pattern.len());
+        let mut literals = Vec::new();
+        let mut literal = String::new();
+        let mut has_metavariables = false;
+
+        let mut rest = pattern;
+        while let Some(c) = rest.chars().next() {
+            if let Some((metavariable, len)) = parse_metavariable(rest) {
+                has_metavariables = true;
+                match metavariable {
+                    Metavariable::Single(name) => {
+                        source.push_str(SINGLE_PLACEHOLDER_PREFIX);
+                        source.push_str(name);
+                    }
+                    Metavariable::Multi(name) => {
+                        source.push_str(MULTI_PLACEHOLDER_PREFIX);
+                        source.push_str(name);
+                    }
+                }
+                literals.extend(take_literal(&mut literal));
+                rest = &rest[len..];
+                continue;
+            }
+
+            if c.is_alphanumeric() || c == '_' {
+                literal.push(c);
+            } else {
+                literals.extend(take_literal(&mut literal));
+            }
+            source.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        literals.extend(take_literal(&mut literal));
+
+        if has_metavariables && source_is_single_metavariable(&source) {
+            bail!("Structural patterns must contain code besides metavariables");
+        }
+
+        Ok(Self {
+            source: source.into(),
+            literals,
+        })
+    }
+
+    /// Whether `text` contains every literal word of the pattern, which is
+    /// necessary (but not sufficient) for it to contain a match.
+    pub fn could_match(&self, text: &str) -> bool {
+        self.literals.iter().all(|literal| text.contains(literal))
+    }
+
+    /// Finds all non-overlapping matches of this pattern in the buffer's
+    /// syntax trees.
+    pub async fn find_matches(&self, buffer: &BufferSnapshot) -> Vec<Range<usize>> {
+        const YIELD_INTERVAL: usize = 10000;
+
+        let mut patterns = HashMap::default();
+        let mut matches = Vec::new();
+        let mut visited = 0;
+        for layer in buffer.syntax_layers() {
+            let pattern_tree = patterns
+                .entry(layer.language.id())
+                .or_insert_with(|| PatternTree::parse(&self.source, layer.language));
+            let Some(pattern_tree) = pattern_tree else {
+                continue;
+            };
+            let pattern_root = pattern_tree.root();
+
+            let mut cursor = layer.node().walk();
+            'traversal: loop {
+                let node = cursor.node();
+                visited += 1;
+                if visited % YIELD_INTERVAL == 0 {
+                    yield_now().await;
+                }
+
+                let mut captures = Captures::default();
+                let is_match = node.is_named()
+                    && pattern_tree.matches(pattern_root, node, buffer, &mut captures);
+                if is_match {
+                    matches.push(node.byte_range());
+                } else if cursor.goto_first_child() {
+                    continue;
+                }
+
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        break 'traversal;
+                    }
+                }
+            }
+        }
+
+        matches.sort_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        let mut last_end = 0;
+        matches.retain(|range| {
+            let keep = range.start >= last_end;
+            if keep {
+                last_end = range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Matches the pattern against the syntax node spanning exactly `range`,
+    /// returning the captured metavariables.
+    pub fn captures_for_range(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<Captures> {
+        let layers = buffer
+            .syntax_layers_for_range(range.clone(), false)
+            .collect::<Vec<_>>();
+        // Prefer the innermost layer, as injected languages are nested within
+        // their host.
+        layers.into_iter().rev().find_map(|layer| {
+            let pattern_tree = PatternTree::parse(&self.source, layer.language)?;
+            let mut node = layer
+                .node()
+                .descendant_for_byte_range(range.start, range.end)?;
+            while node.byte_range() == range {
+                let mut captures = Captures::default();
+                if pattern_tree.matches(pattern_tree.root(), node, buffer, &mut captures) {
+                    return Some(captures);
+                }
+                node = node.parent()?;
+            }
+            None
+        })
+    }
+}
+
+/// Expands `$NAME` and `$$$NAME` references in a replacement template with
+/// the text they captured. References to unknown metavariables are kept as is.
+pub fn expand_template(template: &str, captures: &Captures, buffer: &BufferSnapshot) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(c) = rest.chars().next() {
+        if let Some((metavariable, len)) = parse_metavariable(rest) {
+            let (Metavariable::Single(name) | Metavariable::Multi(name)) = metavariable;
+            if let Some(range) = captures.get(name) {
+                result.extend(buffer.text_for_range(range.clone()));
+                rest = &rest[len..];
+                continue;
+            }
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
+
+fn take_literal(literal: &mut String) -> Option<String> {
+    let literal = std::mem::take(literal);
+    (!literal.is_empty()).then_some(literal)
+}
+
+/// Parses a metavariable at the start of `text`, returning it and its length
+/// in bytes.
+fn parse_metavariable(text: &str) -> Option<(Metavariable<'_>, usize)> {
+    let (is_multi, prefix_len) = if text.starts_with("$$$") {
+        (true, 3)
+    } else if text.starts_with('$') {
+        (false, 1)
+    } else {
+        return None;
+    };
+
+    let name_len = text[prefix_len..]
+        .char_indices()
+        .find(|(ix, c)| !(c.is_ascii_uppercase() || *c == '_' || (*ix > 0 && c.is_ascii_digit())))
+        .map_or(text.len() - prefix_len, |(ix, _)| ix);
+    let name = &text[prefix_len..prefix_len + name_len];
+    if is_multi {
+        let name = if name.is_empty() {
+            ANONYMOUS_NAME
+        } else {
+            name
+        };
+        Some((Metavariable::Multi(name), prefix_len + name_len))
+    } else if name.is_empty() {
+        None
+    } else {
+        Some((Metavariable::Single(name), prefix_len + name_len))
+    }
+}
+
+fn source_is_single_metavariable(source: &str) -> bool {
+    metavariable_for_placeholder(source).is_some()
+}
+
+fn metavariable_for_placeholder(text: &str) -> Option<Metavariable<'_>> {
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+    };
+    if let Some(name) = text.strip_prefix(MULTI_PLACEHOLDER_PREFIX) {
+        is_name(name).then_some(Metavariable::Multi(name))
+    } else if let Some(name) = text.strip_prefix(SINGLE_PLACEHOLDER_PREFIX) {
+        is_name(name).then_some(Metavariable::Single(name))
+    } else {
+        None
+    }
+}
+
+struct PatternTree {
+    source: Arc<str>,
+    tree: language::Tree,
+}
+
+impl PatternTree {
+    fn parse(source: &Arc<str>, language: &Arc<Language>) -> Option<Self> {
+        let tree = language::parse_snippet(language, source)?;
+        let pattern_tree = Self {
+            source: source.clone(),
+            tree,
+        };
+        (!pattern_tree.root().is_error()).then_some(pattern_tree)
+    }
+
+    /// The outermost node that covers the whole pattern, skipping wrappers
+    /// such as the source file and statements the pattern was parsed into.
+    fn root(&self) -> Node<'_> {
+        let mut node = self.tree.root_node();
+        while let Some(child) = significant_children(node).next()
+            && self.text(child).trim() == self.text(node).trim()
+            && self.metavariable(node).is_none()
+        {
+            node = child;
+        }
+        node
+    }
+
+    fn text(&self, node: Node) -> &str {
+        &self.source[node.byte_range()]
+    }
+
+    fn metavariable(&self, node: Node) -> Option<Metavariable<'_>> {
+        metavariable_for_placeholder(self.text(node).trim())
+    }
+
+    fn matches(
+        &self,
+        pattern: Node,
+        target: Node,
+        buffer: &BufferSnapshot,
+        captures: &mut Captures,
+    ) -> bool {
+        if let Some(metavariable) = self.metavariable(pattern) {
+            return match metavariable {
+                Metavariable::Single(name) => {
+                    target.is_named() && capture(name, target.byte_range(), buffer, captures)
+                }
+                // A sequence metavariable on its own matches a single node.
+                Metavariable::Multi(name) => capture(name, target.byte_range(), buffer, captures),
+            };
+        }
+
+        if pattern.kind_id() != target.kind_id() {
+            return false;
+        }
+
+        let pattern_children = significant_children(pattern).collect::<Vec<_>>();
+        let target_children = significant_children(target).collect::<Vec<_>>();
+        if pattern_children.is_empty() {
+            return target_children.is_empty()
+                && buffer
+                    .text_for_range(target.byte_range())
+                    .collect::<String>()
+                    == self.text(pattern);
+        }
+        self.matches_sequence(&pattern_children, &target_children, buffer, captures)
+    }
+
+    fn matches_sequence(
+        &self,
+        pattern: &[Node],
+        target: &[Node],
+        buffer: &BufferSnapshot,
+        captures: &mut Captures,
+    ) -> bool {
+        let Some((first, pattern_rest)) = pattern.split_first() else {
+            return target.is_empty();
+        };
+
+        if let Some(Metavariable::Multi(name)) = self.metavariable(*first) {
+            for len in 0..=target.len() {
+                let range = match &target[..len] {
+                    [] => {
+                        let offset = target.first().map_or(0, |node| node.start_byte());
+                        offset..offset
+                    }
+                    [first_node, .., last_node] => first_node.start_byte()..last_node.end_byte(),
+                    [node] => node.byte_range(),
+                };
+                let mut attempt = captures.clone();
+                if capture(name, range, buffer, &mut attempt)
+                    && self.matches_sequence(pattern_rest, &target[len..], buffer, &mut attempt)
+                {
+                    *captures = attempt;
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        let Some((target_first, target_rest)) = target.split_first() else {
+            return false;
+        };
+        let mut attempt = captures.clone();
+        if self.matches(*first, *target_first, buffer, &mut attempt)
+            && self.matches_sequence(pattern_rest, target_rest, buffer, &mut attempt)
+        {
+            *captures = attempt;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Children that take part in matching: comments and other extras, as well
+/// as nodes inserted by error recovery, are ignored.
+fn significant_children(node: Node) -> impl Iterator<Item = Node> {
+    (0..node.child_count()).filter_map(move |ix| {
+        let child = node.child(ix)?;
+        (!child.is_extra() && !child.is_missing()).then_some(child)
+    })
+}
+
+/// Records a capture, or checks that it agrees with an earlier capture of the
+/// same metavariable.
+fn capture(
+    name: &str,
+    range: Range<usize>,
+    buffer: &BufferSnapshot,
+    captures: &mut Captures,
+) -> bool {
+    if name == ANONYMOUS_NAME {
+        return true;
+    }
+    match captures.get(name) {
+        Some(existing) => buffer
+            .text_for_range(existing.clone())
+            .flat_map(str::chars)
+            .eq(buffer.text_for_range(range).flat_map(str::chars)),
+        None => {
+            captures.insert(name.to_string(), range);
+            true
+        }
+    }
+}
@@ -0,0 +1,524 @@
+//! Terminals that the host of a shared project shows to its guests.
+//!
+//! The host sends the lines of a shared terminal's grid that changed since the
+//! previous update, and each guest draws them in a display-only terminal of
+//! its own. When the host allows it, what a guest types in that terminal is
+//! sent back and written to the host's terminal.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, Result};
+use collections::HashMap;
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{
+    App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Subscription, Task, WeakEntity,
+};
+use rpc::{AnyProtoClient, TypedEnvelope, proto};
+use terminal::{
+    Event as TerminalEvent, GridSyncState, GridUpdate, Terminal, TerminalBuilder,
+    terminal_settings::{AlternateScroll, CursorShape},
+};
+use util::{ResultExt as _, paths::PathStyle};
+
+/// How often the guests are sent the output of a busy terminal.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How many lines of scrollback the guests are sent when they first see a
+/// terminal.
+const MAX_SCROLLBACK_LINES: usize = 1000;
+
+/// What the guests of a project may do with a terminal shared with them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TerminalSharePermission {
+    /// Guests can only watch the terminal.
+    ReadOnly,
+    /// Guests can also type in the terminal.
+    ReadWrite,
+}
+
+impl TerminalSharePermission {
+    fn from_proto(permission: proto::TerminalSharePermission) -> Self {
+        match permission {
+            proto::TerminalSharePermission::ReadOnly => Self::ReadOnly,
+            proto::TerminalSharePermission::ReadWrite => Self::ReadWrite,
+        }
+    }
+
+    fn to_proto(self) -> proto::TerminalSharePermission {
+        match self {
+            Self::ReadOnly => proto::TerminalSharePermission::ReadOnly,
+            Self::ReadWrite => proto::TerminalSharePermission::ReadWrite,
+        }
+    }
+}
+
+/// A terminal of the host that is shown to the guests.
+struct HostedTerminal {
+    terminal: WeakEntity<Terminal>,
+    permission: TerminalSharePermission,
+    sync_state: GridSyncState,
+    title: String,
+    pending_update: Option<Task<()>>,
+    _subscriptions: [Subscription; 2],
+}
+
+/// A guest's copy of a terminal shared by the host.
+struct MirroredTerminal {
+    terminal: Entity<Terminal>,
+    permission: TerminalSharePermission,
+    _forward_input: Option<Task<()>>,
+}
+
+#[derive(Clone)]
+struct RemoteSharedTerminalStore {
+    upstream_client: AnyProtoClient,
+    upstream_project_id: u64,
+    path_style: PathStyle,
+}
+
+#[derive(Clone)]
+enum SharedTerminalStoreMode {
+    Local,
+    Remote(RemoteSharedTerminalStore),
+}
+
+#[derive(Clone, Debug)]
+pub enum SharedTerminalStoreEvent {
+    /// The host shared a terminal, shown to this guest by the given terminal.
+    TerminalShared(Entity<Terminal>),
+}
+
+impl EventEmitter<SharedTerminalStoreEvent> for SharedTerminalStore {}
+
+pub struct SharedTerminalStore {
+    mode: SharedTerminalStoreMode,
+    hosted: HashMap<u64, HostedTerminal>,
+    mirrored: HashMap<u64, MirroredTerminal>,
+    downstream_client: Option<(AnyProtoClient, u64)>,
+}
+
+impl SharedTerminalStore {
+    pub fn init(client: &AnyProtoClient) {
+        client.add_entity_message_handler(Self::handle_share_terminal);
+        client.add_entity_message_handler(Self::handle_update_shared_terminal);
+        client.add_entity_message_handler(Self::handle_unshare_terminal);
+        client.add_entity_request_handler(Self::handle_shared_terminal_input);
+    }
+
+    pub fn local() -> Self {
+        Self::new(SharedTerminalStoreMode::Local)
+    }
+
+    pub(crate) fn remote(
+        upstream_project_id: u64,
+        upstream_client: AnyProtoClient,
+        path_style: PathStyle,
+    ) -> Self {
+        Self::new(SharedTerminalStoreMode::Remote(RemoteSharedTerminalStore {
+            upstream_client,
+            upstream_project_id,
+            path_style,
+        }))
+    }
+
+    fn new(mode: SharedTerminalStoreMode) -> Self {
+        Self {
+            mode,
+            hosted: HashMap::default(),
+            mirrored: HashMap::default(),
+            downstream_client: None,
+        }
+    }
+
+    pub fn shared(&mut self, project_id: u64, downstream_client: AnyProtoClient) {
+        self.downstream_client = Some((downstream_client, project_id));
+    }
+
+    /// Terminals are only shared while the project is.
+    pub(crate) fn unshared(&mut self, cx: &mut Context<Self>) {
+        self.downstream_client.take();
+        self.hosted.clear();
+        cx.notify();
+    }
+
+    /// Sends every shared terminal to the guests of the project, from scratch.
+    pub(crate) fn broadcast(&mut self, cx: &App) {
+        let terminal_ids = self.hosted.keys().copied().collect::<Vec<_>>();
+        for terminal_id in terminal_ids {
+            self.send_share(terminal_id, cx);
+        }
+    }
+
+    /// Whether this is the host's side of the project, which can share
+    /// terminals.
+    pub fn can_share(&self) -> bool {
+        matches!(self.mode, SharedTerminalStoreMode::Local) && self.downstream_client.is_some()
+    }
+
+    /// Shows one of the host's terminals to the guests of the project, or
+    /// changes what they may do with it if it is already shared.
+    pub fn share_terminal(
+        &mut self,
+        terminal: &Entity<Terminal>,
+        permission: TerminalSharePermission,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        anyhow::ensure!(self.can_share(), "the project is not shared");
+        let terminal_id = terminal_id(terminal);
+        if let Some(hosted) = self.hosted.get_mut(&terminal_id) {
+            hosted.permission = permission;
+        } else {
+            let subscriptions = [
+                cx.subscribe(terminal, move |this, _, event: &TerminalEvent, cx| {
+                    if matches!(event, TerminalEvent::Wakeup | TerminalEvent::TitleChanged) {
+                        this.schedule_update(terminal_id, cx);
+                    }
+                }),
+                cx.observe_release(terminal, move |this, _, cx| {
+                    this.unshare_terminal(terminal_id, cx);
+                }),
+            ];
+            self.hosted.insert(
+                terminal_id,
+                HostedTerminal {
+                    terminal: terminal.downgrade(),
+                    permission,
+                    sync_state: GridSyncState::default(),
+                    title: String::new(),
+                    pending_update: None,
+                    _subscriptions: subscriptions,
+                },
+            );
+        }
+        self.send_share(terminal_id, cx);
+        cx.notify();
+        Ok(())
+    }
+
+    /// Stops showing one of the host's terminals to the guests.
+    pub fn unshare_terminal(&mut self, terminal_id: u64, cx: &mut Context<Self>) {
+        if self.hosted.remove(&terminal_id).is_none() {
+            return;
+        }
+        if let Some((client, project_id)) = &self.downstream_client {
+            client
+                .send(proto::UnshareTerminal {
+                    project_id: *project_id,
+                    terminal_id,
+                })
+                .log_err();
+        }
+        cx.notify();
+    }
+
+    /// What the guests may do with a terminal, if the host shares it with
+    /// them or this guest is shown it.
+    pub fn permission(&self, terminal: &Entity<Terminal>) -> Option<TerminalSharePermission> {
+        let terminal_id = terminal_id(terminal);
+        if let Some(hosted) = self.hosted.get(&terminal_id) {
+            return Some(hosted.permission);
+        }
+        self.mirrored
+            .values()
+            .find(|mirrored| mirrored.terminal == *terminal)
+            .map(|mirrored| mirrored.permission)
+    }
+
+    /// The terminals the host shared with this guest.
+    pub fn mirrored_terminals(&self) -> impl Iterator<Item = &Entity<Terminal>> {
+        self.mirrored.values().map(|mirrored| &mirrored.terminal)
+    }
+
+    fn send_share(&mut self, terminal_id: u64, cx: &App) {
+        let Some((client, project_id)) = &self.downstream_client else {
+            return;
+        };
+        let Some(hosted) = self.hosted.get_mut(&terminal_id) else {
+            return;
+        };
+        let Some(terminal) = hosted.terminal.upgrade() else {
+            return;
+        };
+        let terminal = terminal.read(cx);
+        hosted.sync_state = GridSyncState::default();
+        hosted.title = terminal.title(false);
+        let grid = terminal
+            .grid_update(&mut hosted.sync_state, MAX_SCROLLBACK_LINES)
+            .map(|update| grid_update_to_proto(&update));
+        client
+            .send(proto::ShareTerminal {
+                project_id: *project_id,
+                terminal_id,
+                title: hosted.title.clone(),
+                permission: hosted.permission.to_proto().into(),
+                grid,
+            })
+            .log_err();
+    }
+
+    /// Terminals can print faster than they are worth sending, so output is
+    /// sent at most once per [`UPDATE_INTERVAL`].
+    fn schedule_update(&mut self, terminal_id: u64, cx: &mut Context<Self>) {
+        let Some(hosted) = self.hosted.get_mut(&terminal_id) else {
+            return;
+        };
+        if hosted.pending_update.is_some() {
+            return;
+        }
+        hosted.pending_update = Some(cx.spawn(async move |this, cx| {
+            cx.background_executor().timer(UPDATE_INTERVAL).await;
+            this.update(cx, |this, cx| this.send_update(terminal_id, cx))
+                .ok();
+        }));
+    }
+
+    fn send_update(&mut self, terminal_id: u64, cx: &mut Context<Self>) {
+        let Some((client, project_id)) = &self.downstream_client else {
+            return;
+        };
+        let Some(hosted) = self.hosted.get_mut(&terminal_id) else {
+            return;
+        };
+        hosted.pending_update = None;
+        let Some(terminal) = hosted.terminal.upgrade() else {
+            return;
+        };
+        let terminal = terminal.read(cx);
+        let grid = terminal
+            .grid_update(&mut hosted.sync_state, MAX_SCROLLBACK_LINES)
+            .map(|update| grid_update_to_proto(&update));
+        let title = terminal.title(false);
+        let title = (title != hosted.title).then(|| {
+            hosted.title = title.clone();
+            title
+        });
+        if grid.is_none() && title.is_none() {
+            return;
+        }
+        client
+            .send(proto::UpdateSharedTerminal {
+                project_id: *project_id,
+                terminal_id,
+                title,
+                grid,
+            })
+            .log_err();
+    }
+
+    fn mirror_terminal(
+        &mut self,
+        terminal_id: u64,
+        permission: TerminalSharePermission,
+        cx: &mut Context<Self>,
+    ) -> Option<Entity<Terminal>> {
+        let SharedTerminalStoreMode::Remote(remote) = &self.mode else {
+            return None;
+        };
+        let remote = remote.clone();
+        let mut created = None;
+        let mirrored = self.mirrored.entry(terminal_id).or_insert_with(|| {
+            let terminal = cx.new(|cx| {
+                TerminalBuilder::new_display_only(
+                    CursorShape::default(),
+                    AlternateScroll::On,
+                    None,
+                    0,
+                    cx.background_executor(),
+                    remote.path_style,
+                )
+                .subscribe(cx)
+            });
+            created = Some(terminal.clone());
+            MirroredTerminal {
+                terminal,
+                permission,
+                _forward_input: None,
+            }
+        });
+
+        mirrored.permission = permission;
+        mirrored._forward_input = None;
+        let input_tx = match permission {
+            TerminalSharePermission::ReadOnly => None,
+            TerminalSharePermission::ReadWrite => {
+                let (input_tx, mut input_rx) = mpsc::unbounded::<Vec<u8>>();
+                mirrored._forward_input = Some(cx.background_spawn(async move {
+                    while let Some(input) = input_rx.next().await {
+                        remote
+                            .upstream_client
+                            .request(proto::SharedTerminalInput {
+                                project_id: remote.upstream_project_id,
+                                terminal_id,
+                                input,
+                            })
+                            .await
+                            .log_err();
+                    }
+                }));
+                Some(input_tx)
+            }
+        };
+        mirrored
+            .terminal
+            .update(cx, |terminal, _| terminal.set_mirror_input(input_tx));
+
+        if let Some(terminal) = &created {
+            cx.emit(SharedTerminalStoreEvent::TerminalShared(terminal.clone()));
+        }
+        cx.notify();
+        Some(mirrored.terminal.clone())
+    }
+
+    async fn handle_share_terminal(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ShareTerminal>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let payload = envelope.payload;
+        let permission = TerminalSharePermission::from_proto(payload.permission());
+        let grid = payload.grid.map(grid_update_from_proto);
+        this.update(&mut cx, |this, cx| {
+            let terminal = this
+                .mirror_terminal(payload.terminal_id, permission, cx)
+                .context("only guests can be shown shared terminals")?;
+            terminal.update(cx, |terminal, cx| {
+                terminal.set_title_override(Some(payload.title), cx);
+                if let Some(grid) = &grid {
+                    terminal.apply_grid_update(grid, cx);
+                }
+            });
+            anyhow::Ok(())
+        })
+    }
+
+    async fn handle_update_shared_terminal(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UpdateSharedTerminal>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let payload = envelope.payload;
+        let grid = payload.grid.map(grid_update_from_proto);
+        this.update(&mut cx, |this, cx| {
+            let Some(mirrored) = this.mirrored.get(&payload.terminal_id) else {
+                return;
+            };
+            mirrored.terminal.update(cx, |terminal, cx| {
+                if let Some(title) = payload.title {
+                    terminal.set_title_override(Some(title), cx);
+                }
+                if let Some(grid) = &grid {
+                    terminal.apply_grid_update(grid, cx);
+                }
+            });
+        });
+        Ok(())
+    }
+
+    async fn handle_unshare_terminal(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::UnshareTerminal>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, cx| {
+            let Some(mirrored) = this.mirrored.remove(&envelope.payload.terminal_id) else {
+                return;
+            };
+            mirrored
+                .terminal
+                .update(cx, |terminal, _| terminal.set_mirror_input(None));
+            cx.notify();
+        });
+        Ok(())
+    }
+
+    async fn handle_shared_terminal_input(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::SharedTerminalInput>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let payload = envelope.payload;
+        this.update(&mut cx, |this, cx| {
+            let hosted = this
+                .hosted
+                .get(&payload.terminal_id)
+                .context("the terminal is not shared")?;
+            // The server checks the permission too, but it may have changed
+            // since the input was sent.
+            anyhow::ensure!(
+                hosted.permission == TerminalSharePermission::ReadWrite,
+                "the terminal is shared read-only"
+            );
+            let terminal = hosted
+                .terminal
+                .upgrade()
+                .context("the terminal was closed")?;
+            terminal.update(cx, |terminal, _| terminal.input(payload.input));
+            Ok(proto::Ack {})
+        })
+    }
+}
+
+/// Terminals are identified to the guests by the id of the host's entity.
+pub fn terminal_id(terminal: &Entity<Terminal>) -> u64 {
+    terminal.entity_id().as_u64()
+}
+
+fn grid_update_to_proto(update: &GridUpdate) -> proto::TerminalGridUpdate {
+    proto::TerminalGridUpdate {
+        reset: update.reset,
+        columns: update.columns as u32,
+        rows: update.rows as u32,
+        scrolled_lines: update.scrolled_lines.clone(),
+        changed_rows: update
+            .changed_rows
+            .iter()
+            .map(|(row, text)| proto::TerminalRow {
+                row: *row as u32,
+                text: text.clone(),
+            })
+            .collect(),
+        cursor_row: update.cursor_row as u32,
+        cursor_column: update.cursor_column as u32,
+        cursor_visible: update.cursor_visible,
+    }
+}
+
+fn grid_update_from_proto(update: proto::TerminalGridUpdate) -> GridUpdate {
+    GridUpdate {
+        reset: update.reset,
+        columns: update.columns as usize,
+        rows: update.rows as usize,
+        scrolled_lines: update.scrolled_lines,
+        changed_rows: update
+            .changed_rows
+            .into_iter()
+            .map(|row| (row.row as usize, row.text))
+            .collect(),
+        cursor_row: update.cursor_row as usize,
+        cursor_column: update.cursor_column as usize,
+        cursor_visible: update.cursor_visible,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_update_round_trips_through_proto() {
+        let update = GridUpdate {
+            reset: true,
+            columns: 80,
+            rows: 24,
+            scrolled_lines: vec!["\x1b[0;31mfirst".into(), String::new()],
+            changed_rows: vec![(0, "$ ls".into()), (3, "README.md".into())],
+            cursor_row: 4,
+            cursor_column: 2,
+            cursor_visible: true,
+        };
+        assert_eq!(
+            grid_update_from_proto(grid_update_to_proto(&update)),
+            update
+        );
+    }
+}
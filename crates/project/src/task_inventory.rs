@@ -12,7 +12,7 @@ use anyhow::Result;
 use collections::{HashMap, HashSet, VecDeque};
 use dap::DapRegistry;
 use gpui::{App, AppContext as _, Context, Entity, SharedString, Task, WeakEntity};
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use language::{
     Buffer, ContextLocation, ContextProvider, File, Language, LanguageToolchainStore, Location,
     language_settings::LanguageSettings,
@@ -21,8 +21,8 @@ use lsp::{LanguageServerId, LanguageServerName};
 use paths::{debug_task_file_name, task_file_name};
 use settings::{InvalidSettingsError, parse_json_with_comments};
 use task::{
-    DebugScenario, ResolvedTask, SharedTaskContext, TaskContext, TaskHook, TaskId, TaskTemplate,
-    TaskTemplates, TaskVariables, VariableName,
+    DebugCompound, DebugScenario, DebugTaskFileEntry, ResolvedTask, SharedTaskContext, TaskContext,
+    TaskHook, TaskId, TaskTemplate, TaskTemplates, TaskVariables, VariableName,
 };
 use text::{BufferId, Point, ToPoint};
 use util::{NumericPrefixWithSuffix, ResultExt as _, post_inc, rel_path::RelPath};
@@ -45,6 +45,7 @@ pub struct Inventory {
     last_scheduled_scenarios: VecDeque<(DebugScenario, DebugScenarioContext)>,
     templates_from_settings: InventoryFor<TaskTemplate>,
     scenarios_from_settings: InventoryFor<DebugScenario>,
+    compounds_from_settings: InventoryFor<DebugCompound>,
 }
 
 impl std::fmt::Debug for Inventory {
@@ -54,6 +55,7 @@ impl std::fmt::Debug for Inventory {
             .field("last_scheduled_scenarios", &self.last_scheduled_scenarios)
             .field("templates_from_settings", &self.templates_from_settings)
             .field("scenarios_from_settings", &self.scenarios_from_settings)
+            .field("compounds_from_settings", &self.compounds_from_settings)
             .finish()
     }
 }
@@ -75,6 +77,12 @@ impl InventoryContents for DebugScenario {
     const LABEL: &'static str = "debug scenarios";
 }
 
+impl InventoryContents for DebugCompound {
+    const GLOBAL_SOURCE_FILE: &'static str = "debug.json";
+
+    const LABEL: &'static str = "debug compounds";
+}
+
 #[derive(Debug)]
 struct InventoryFor<T> {
     global: HashMap<PathBuf, Vec<T>>,
@@ -133,6 +141,28 @@ impl<T: InventoryContents> InventoryFor<T> {
     }
 }
 
+impl<T> InventoryFor<T> {
+    fn update(&mut self, location: &TaskSettingsLocation<'_>, items: Vec<T>) {
+        match location {
+            TaskSettingsLocation::Global(path) => {
+                self.global.insert(path.to_path_buf(), items);
+            }
+            TaskSettingsLocation::Worktree(location) => {
+                if items.is_empty() {
+                    if let Some(worktree_items) = self.worktree.get_mut(&location.worktree_id) {
+                        worktree_items.remove(location.path);
+                    }
+                } else {
+                    self.worktree
+                        .entry(location.worktree_id)
+                        .or_default()
+                        .insert(Arc::from(location.path), items);
+                }
+            }
+        }
+    }
+}
+
 impl<T> Default for InventoryFor<T> {
     fn default() -> Self {
         Self {
@@ -302,6 +332,7 @@ impl Inventory {
             last_scheduled_scenarios: VecDeque::default(),
             templates_from_settings: InventoryFor::default(),
             scenarios_from_settings: InventoryFor::default(),
+            compounds_from_settings: InventoryFor::default(),
         })
     }
 
@@ -735,6 +766,30 @@ impl Inventory {
         self.templates_from_settings.global_scenarios()
     }
 
+    /// Debug scenarios defined in `debug.json` files for `worktree`, followed
+    /// by the global ones.
+    pub fn debug_scenarios_from_settings(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> impl '_ + Iterator<Item = (TaskSourceKind, DebugScenario)> {
+        worktree
+            .into_iter()
+            .flat_map(|worktree| self.worktree_scenarios_from_settings(worktree))
+            .chain(self.global_debug_scenarios_from_settings())
+    }
+
+    /// Compound debug scenarios defined in `debug.json` files for `worktree`,
+    /// followed by the global ones.
+    pub fn debug_compounds_from_settings(
+        &self,
+        worktree: Option<WorktreeId>,
+    ) -> impl '_ + Iterator<Item = (TaskSourceKind, DebugCompound)> {
+        worktree
+            .into_iter()
+            .flat_map(|worktree| self.compounds_from_settings.worktree_scenarios(worktree))
+            .chain(self.compounds_from_settings.global_scenarios())
+    }
+
     fn global_debug_scenarios_from_settings(
         &self,
     ) -> impl '_ + Iterator<Item = (TaskSourceKind, DebugScenario)> {
@@ -895,12 +950,17 @@ impl Inventory {
             }
         };
 
-        let new_templates = raw_tasks
+        let (new_templates, new_compounds): (Vec<_>, Vec<_>) = raw_tasks
             .into_iter()
             .filter_map(|raw_template| {
-                serde_json::from_value::<DebugScenario>(raw_template).log_err()
+                serde_json::from_value::<DebugTaskFileEntry>(raw_template).log_err()
             })
-            .collect::<Vec<_>>();
+            .partition_map(|entry| match entry {
+                DebugTaskFileEntry::Scenario(scenario) => Either::Left(scenario),
+                DebugTaskFileEntry::Compound(compound) => Either::Right(compound),
+            });
+        self.compounds_from_settings
+            .update(&location, new_compounds);
 
         let parsed_scenarios = &mut self.scenarios_from_settings;
         let mut new_definitions: HashMap<_, _> = new_templates
@@ -25,6 +25,10 @@ use util::{
 
 use crate::{Project, ProjectPath};
 
+/// How many of the last lines of a remote terminal are checked for local URLs
+/// when it prints output.
+const TERMINAL_URL_DETECTION_LINES: usize = 5;
+
 pub struct Terminals {
     pub(crate) local_handles: Vec<WeakEntity<terminal::Terminal>>,
 }
@@ -268,6 +272,7 @@ impl Project {
                 this.terminals
                     .local_handles
                     .push(terminal_handle.downgrade());
+                this.detect_forwardable_ports(&terminal_handle, cx);
 
                 let id = terminal_handle.entity_id();
                 cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
@@ -438,6 +443,7 @@ impl Project {
                 this.terminals
                     .local_handles
                     .push(terminal_handle.downgrade());
+                this.detect_forwardable_ports(&terminal_handle, cx);
 
                 let id = terminal_handle.entity_id();
                 cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
@@ -485,6 +491,7 @@ impl Project {
                     .terminals
                     .local_handles
                     .push(terminal_handle.downgrade());
+                project.detect_forwardable_ports(&terminal_handle, cx);
 
                 let id = terminal_handle.entity_id();
                 cx.observe_release(&terminal_handle, move |project, _terminal, cx| {
@@ -505,6 +512,26 @@ impl Project {
         })
     }
 
+    /// Offers the ports of local URLs printed in a remote terminal for
+    /// forwarding, as servers often print their address on startup.
+    fn detect_forwardable_ports(&self, terminal: &Entity<Terminal>, cx: &mut Context<Self>) {
+        let Some(port_forward_store) = self.port_forward_store.clone() else {
+            return;
+        };
+        if !terminal.read(cx).is_remote_terminal() {
+            return;
+        }
+        cx.subscribe(terminal, move |_, terminal, event, cx| {
+            if let terminal::Event::Wakeup = event {
+                let lines = terminal
+                    .read(cx)
+                    .last_n_non_empty_lines(TERMINAL_URL_DETECTION_LINES);
+                port_forward_store.update(cx, |store, cx| store.detect_terminal_urls(&lines, cx));
+            }
+        })
+        .detach();
+    }
+
     pub fn terminal_settings<'a>(
         &'a self,
         path: &'a Option<PathBuf>,
@@ -171,6 +171,21 @@ impl From<RemoteConnectionOptions> for RemoteHostLocation {
                 Some(SharedString::new(docker_connection_options.name)),
                 SharedString::new(docker_connection_options.container_id),
             ),
+            RemoteConnectionOptions::Kubernetes(kubernetes_connection_options) => (
+                None,
+                SharedString::new(
+                    [
+                        kubernetes_connection_options.context,
+                        kubernetes_connection_options.namespace,
+                        Some(kubernetes_connection_options.pod),
+                        kubernetes_connection_options.container,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                ),
+            ),
             #[cfg(feature = "test-support")]
             RemoteConnectionOptions::Mock(mock) => {
                 (None, SharedString::new(format!("mock-{}", mock.id)))
@@ -273,6 +273,8 @@ mod python_locator {
             adapter: "Debugpy".into(),
             label: "run module 'main.py'".into(),
             build: None,
+            pre_launch_task: None,
+            post_debug_task: None,
             config: json!({
                 "request": "launch",
                 "python": "$ZED_CUSTOM_PYTHON_ACTIVE_ZED_TOOLCHAIN",
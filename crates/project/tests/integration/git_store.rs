@@ -629,6 +629,86 @@ mod conflict_set_tests {
             assert_eq!(conflict_set.snapshot.conflicts.len(), 0);
         });
     }
+
+    #[gpui::test]
+    async fn test_load_merge_stages(executor: BackgroundExecutor, cx: &mut TestAppContext) {
+        let fs = FakeFs::new(executor);
+        fs.insert_tree(
+            path!("/project"),
+            json!({
+                ".git": {},
+                "a.txt": "<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> branch\n",
+                "b.txt": "added on their side\n",
+            }),
+        )
+        .await;
+        fs.with_git_state(path!("/project/.git").as_ref(), true, |state| {
+            state.merge_stage_contents.insert(
+                repo_path("a.txt"),
+                [
+                    Some(b"base\n".to_vec()),
+                    Some(b"ours\n".to_vec()),
+                    Some(b"theirs\n".to_vec()),
+                ],
+            );
+            state.merge_stage_contents.insert(
+                repo_path("b.txt"),
+                [None, None, Some(b"added on their side\n".to_vec())],
+            );
+        })
+        .unwrap();
+
+        let project = Project::test(fs.clone(), [path!("/project").as_ref()], cx).await;
+        cx.run_until_parked();
+        let repository =
+            project.read_with(cx, |project, cx| project.active_repository(cx).unwrap());
+
+        let stages = repository
+            .update(cx, |repository, cx| {
+                repository.load_merge_stages(repo_path("a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            stages,
+            MergeStages {
+                base: Some("base\n".into()),
+                ours: Some("ours\n".into()),
+                theirs: Some("theirs\n".into()),
+            }
+        );
+
+        // A file added on only one side has no base or ours stage.
+        let stages = repository
+            .update(cx, |repository, cx| {
+                repository.load_merge_stages(repo_path("b.txt"), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            stages,
+            MergeStages {
+                base: None,
+                ours: None,
+                theirs: Some("added on their side\n".into()),
+            }
+        );
+
+        // Staging the file resolves the conflict and drops its stages.
+        repository
+            .update(cx, |repository, cx| {
+                repository.stage_entries(vec![repo_path("a.txt")], cx)
+            })
+            .await
+            .unwrap();
+        let stages = repository
+            .update(cx, |repository, cx| {
+                repository.load_merge_stages(repo_path("a.txt"), cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(stages, MergeStages::default());
+    }
 }
 
 mod git_traversal {
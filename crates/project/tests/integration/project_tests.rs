@@ -8390,6 +8390,95 @@ async fn test_search_multiline_crlf(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_search_regex_with_lookaround_and_multiline(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "let foo_bar = foo();\nlet foo = bar;\n",
+            "two.rs": "fn a() {\n    bar\n    baz\n}\n",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let regex = |pattern: &str| {
+        SearchQuery::regex(
+            pattern,
+            false,
+            true,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        search(&project, regex(r"foo(?=\()"), cx).await.unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![14..17])])
+    );
+    assert_eq!(
+        search(&project, regex(r"(?<=let )foo\b"), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([(path!("dir/one.rs").to_string(), vec![25..28])])
+    );
+    assert_eq!(
+        search(&project, regex(r"^\s+bar\n\s+baz$"), cx)
+            .await
+            .unwrap(),
+        HashMap::from_iter([(path!("dir/two.rs").to_string(), vec![9..24])])
+    );
+}
+
+#[gpui::test]
+async fn test_search_reports_too_complex_regex(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "slow.rs": "ab".repeat(100),
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+
+    // The lookahead makes this pattern run on the backtracking engine, which
+    // gives up on the exponentially many ways to split the text.
+    let query = SearchQuery::regex(
+        r"(a|b|ab)*(?=c)",
+        false,
+        true,
+        false,
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    let search_rx = project.update(cx, |project, cx| project.search(query.clone(), cx));
+    let mut pattern_too_complex = false;
+    while let Ok(search_result) = search_rx.rx.recv().await {
+        match search_result {
+            SearchResult::Buffer { ranges, .. } => assert!(ranges.is_empty()),
+            SearchResult::PatternTooComplex => pattern_too_complex = true,
+            SearchResult::LimitReached | SearchResult::WaitingForScan | SearchResult::Searching => {
+            }
+        }
+    }
+    assert!(pattern_too_complex);
+    assert!(query.exceeded_backtrack_limit());
+}
+
 #[gpui::test]
 async fn test_search_with_inclusions(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -16053,8 +16142,10 @@ async fn search(
             SearchResult::Buffer { buffer, ranges } => {
                 results.entry(buffer).or_insert(ranges);
             }
-            SearchResult::LimitReached | SearchResult::WaitingForScan | SearchResult::Searching => {
-            }
+            SearchResult::LimitReached
+            | SearchResult::PatternTooComplex
+            | SearchResult::WaitingForScan
+            | SearchResult::Searching => {}
         }
     }
     Ok(results
@@ -1,7 +1,7 @@
 use std::io::BufReader;
 
-use language::Buffer;
-use project::search::SearchQuery;
+use language::{Buffer, rust_lang};
+use project::search::{SearchQuery, SyntaxScope};
 use text::Rope;
 use util::{
     paths::{PathMatcher, PathStyle},
@@ -224,3 +224,168 @@ async fn multi_line_regex_detects_matches() {
         .unwrap();
     assert!(result.is_some());
 }
+
+#[gpui::test]
+async fn test_structural_search_and_replace(cx: &mut gpui::TestAppContext) {
+    let text = Rope::from(
+        "fn main() {\n    foo(1, 2);\n    foo(bar(3, 4), 5);\n    baz(1, 2);\n    foo(x);\n}\n",
+    );
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(text.clone(), Some(rust_lang()), None, None, app))
+        .await;
+
+    let query = SearchQuery::structural(
+        "foo($A, $B)",
+        false,
+        PathMatcher::default(),
+        PathMatcher::default(),
+        false,
+        None,
+    )
+    .unwrap()
+    .with_replacement("foo($B, $A)".to_string());
+    assert!(query.is_structural());
+
+    let results = query.search(&snapshot, None).await;
+    let matched_text = results
+        .iter()
+        .map(|range| text.slice(range.clone()).to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(matched_text, ["foo(1, 2)", "foo(bar(3, 4), 5)"]);
+
+    let replacements = results
+        .iter()
+        .map(|range| query.structural_replacement_for(&snapshot, range.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        replacements,
+        [
+            Some("foo(2, 1)".to_string()),
+            Some("foo(5, bar(3, 4))".to_string())
+        ]
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search_metavariables(cx: &mut gpui::TestAppContext) {
+    let text = Rope::from(
+        "fn main() {\n    let a = x == x;\n    let b = x == y;\n    call();\n    call(1, 2, 3);\n}\n",
+    );
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(text.clone(), Some(rust_lang()), None, None, app))
+        .await;
+    let matched_text = async |query: SearchQuery| {
+        query
+            .search(&snapshot, None)
+            .await
+            .into_iter()
+            .map(|range| text.slice(range).to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // Repeated metavariables must capture the same text.
+    let query = SearchQuery::structural(
+        "$A == $A",
+        false,
+        PathMatcher::default(),
+        PathMatcher::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    assert_eq!(matched_text(query).await, ["x == x"]);
+
+    // Sequence metavariables match any number of nodes.
+    let query = SearchQuery::structural(
+        "call($$$ARGS)",
+        false,
+        PathMatcher::default(),
+        PathMatcher::default(),
+        false,
+        None,
+    )
+    .unwrap()
+    .with_replacement("call_all(&[$$$ARGS])".to_string());
+    let results = query.search(&snapshot, None).await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        query.structural_replacement_for(&snapshot, results[1].clone()),
+        Some("call_all(&[1, 2, 3])".to_string())
+    );
+
+    // Files lacking the pattern's literal parts are ruled out without parsing.
+    let input = "fn main() {}";
+    let result = query
+        .detect(BufReader::new(Box::new(input.as_bytes())))
+        .await
+        .unwrap();
+    assert!(result.is_none());
+
+    assert!(
+        SearchQuery::structural(
+            "$A",
+            false,
+            PathMatcher::default(),
+            PathMatcher::default(),
+            false,
+            None,
+        )
+        .is_err(),
+        "A lone metavariable would match every node"
+    );
+}
+
+#[gpui::test]
+async fn test_search_syntax_scope(cx: &mut gpui::TestAppContext) {
+    let text = Rope::from(
+        "// TODO: handle errors\nfn todo() {\n    let message = \"TODO\";\n    todo();\n}\n",
+    );
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(text.clone(), Some(rust_lang()), None, None, app))
+        .await;
+    let query = SearchQuery::text(
+        "todo",
+        false,
+        false,
+        false,
+        PathMatcher::default(),
+        PathMatcher::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    let matched_rows = async |syntax_scope: SyntaxScope| {
+        query
+            .clone()
+            .with_syntax_scope(syntax_scope)
+            .search(&snapshot, None)
+            .await
+            .into_iter()
+            .map(|range| text.offset_to_point(range.start).row)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(matched_rows(SyntaxScope::Everywhere).await, [0, 1, 2, 3]);
+    assert_eq!(matched_rows(SyntaxScope::Comments).await, [0]);
+    assert_eq!(matched_rows(SyntaxScope::Strings).await, [2]);
+    assert_eq!(matched_rows(SyntaxScope::Code).await, [1, 3]);
+
+    // Matches within a subrange are scoped by their position in the buffer.
+    let subrange = text.point_to_offset(text::Point::new(2, 0))..text.len();
+    let results = query
+        .clone()
+        .with_syntax_scope(SyntaxScope::Strings)
+        .search(&snapshot, Some(subrange.clone()))
+        .await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        text.slice(subrange.start + results[0].start..subrange.start + results[0].end)
+            .to_string(),
+        "TODO"
+    );
+
+    // The scope survives a round trip through the search RPC.
+    let query = query.with_syntax_scope(SyntaxScope::Comments);
+    let query = SearchQuery::from_proto(query.to_proto(), PathStyle::local()).unwrap();
+    assert_eq!(query.syntax_scope(), SyntaxScope::Comments);
+}
@@ -409,6 +409,62 @@ async fn test_reloading_debug_scenarios(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_debug_compounds_from_settings(cx: &mut TestAppContext) {
+    init_test(cx);
+    let inventory = cx.update(|cx| Inventory::new(cx));
+    inventory.update(cx, |inventory, _| {
+        inventory
+            .update_file_based_scenarios(
+                TaskSettingsLocation::Global(Path::new("")),
+                Some(
+                    r#"
+                        [
+                            {
+                                "label": "server",
+                                "adapter": "Debugpy",
+                                "request": "launch",
+                                "program": "server.py",
+                            },
+                            {
+                                "label": "client and server",
+                                "scenarios": ["server", "client"],
+                                "stop_all": true,
+                            }
+                        ]
+                        "#,
+                ),
+            )
+            .unwrap();
+    });
+
+    inventory.read_with(cx, |inventory, _| {
+        let scenarios = inventory
+            .debug_scenarios_from_settings(None)
+            .map(|(_, scenario)| scenario.label)
+            .collect::<Vec<_>>();
+        assert_eq!(scenarios, ["server"]);
+
+        let compounds = inventory
+            .debug_compounds_from_settings(None)
+            .map(|(_, compound)| compound)
+            .collect::<Vec<_>>();
+        assert_eq!(compounds.len(), 1);
+        assert_eq!(compounds[0].label, "client and server");
+        assert_eq!(compounds[0].scenarios, ["server", "client"]);
+        assert!(compounds[0].stop_all);
+    });
+
+    inventory.update(cx, |inventory, _| {
+        inventory
+            .update_file_based_scenarios(TaskSettingsLocation::Global(Path::new("")), None)
+            .unwrap();
+    });
+    inventory.read_with(cx, |inventory, _| {
+        assert_eq!(inventory.debug_compounds_from_settings(None).count(), 0);
+    });
+}
+
 #[gpui::test]
 async fn test_inventory_static_task_filters(cx: &mut TestAppContext) {
     init_test(cx);
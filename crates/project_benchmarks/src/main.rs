@@ -217,7 +217,7 @@ fn main() -> Result<(), anyhow::Error> {
                             matched_files += 1;
                             matched_chunks += ranges.len();
                         }
-                        SearchResult::LimitReached => break,
+                        SearchResult::LimitReached | SearchResult::PatternTooComplex => break,
                         SearchResult::WaitingForScan | SearchResult::Searching => continue,
                     }
                 }
@@ -276,6 +276,11 @@ messages!(
     (TelemetryEvent, Background),
     (Test, Foreground),
     (Toast, Background),
+    (UpdateListeningPorts, Background),
+    (OpenPortTunnel, Background),
+    (PortTunnelData, Background),
+    (PortTunnelAck, Background),
+    (ClosePortTunnel, Background),
     (Unfollow, Foreground),
     (UnshareProject, Foreground),
     (Unstage, Background),
@@ -360,6 +365,20 @@ messages!(
     (GetBlobContentResponse, Background),
     (BlameBufferAtRevision, Background),
     (BlameBufferAtRevisionResponse, Background),
+    (LoadMergeStages, Background),
+    (LoadMergeStagesResponse, Background),
+    (ReviewCommentsForBranch, Background),
+    (UpdateReviewComment, Foreground),
+    (ShareTerminal, Foreground),
+    (UpdateSharedTerminal, Foreground),
+    (UnshareTerminal, Foreground),
+    (SharedTerminalInput, Foreground),
+    (ShareDebugSession, Background),
+    (UpdateDebugSession, Background),
+    (UnshareDebugSession, Background),
+    (InspectDebugSession, Background),
+    (ControlDebugSession, Background),
+    (DebugSessionResponse, Background),
     (LoadCommitTemplate, Background),
     (LoadCommitTemplateResponse, Background),
     (GitClone, Background),
@@ -552,6 +571,7 @@ request_messages!(
     (LspExtClearFlycheck, Ack),
     (AddWorktree, AddWorktreeResponse),
     (ShutdownRemoteServer, Ack),
+    (OpenPortTunnel, Ack),
     (RemoveWorktree, Ack),
     (OpenServerSettings, OpenBufferResponse),
     (GetPermalinkToLine, GetPermalinkToLineResponse),
@@ -606,6 +626,11 @@ request_messages!(
     (GetDefaultBranch, GetDefaultBranchResponse),
     (GetBlobContent, GetBlobContentResponse),
     (BlameBufferAtRevision, BlameBufferAtRevisionResponse),
+    (LoadMergeStages, LoadMergeStagesResponse),
+    (UpdateReviewComment, Ack),
+    (SharedTerminalInput, Ack),
+    (InspectDebugSession, DebugSessionResponse),
+    (ControlDebugSession, DebugSessionResponse),
     (LoadCommitTemplate, LoadCommitTemplateResponse),
     (GetTreeDiff, GetTreeDiffResponse),
     (GitClone, GitCloneResponse),
@@ -780,6 +805,11 @@ entity_messages!(
     TelemetryEvent,
     Toast,
     HideToast,
+    UpdateListeningPorts,
+    OpenPortTunnel,
+    PortTunnelData,
+    PortTunnelAck,
+    ClosePortTunnel,
     OpenServerSettings,
     GetPermalinkToLine,
     LanguageServerPromptRequest,
@@ -831,6 +861,18 @@ entity_messages!(
     GetTreeDiff,
     GetBlobContent,
     BlameBufferAtRevision,
+    LoadMergeStages,
+    ReviewCommentsForBranch,
+    UpdateReviewComment,
+    ShareTerminal,
+    UpdateSharedTerminal,
+    UnshareTerminal,
+    SharedTerminalInput,
+    ShareDebugSession,
+    UpdateDebugSession,
+    UnshareDebugSession,
+    InspectDebugSession,
+    ControlDebugSession,
     LoadCommitTemplate,
     GitClone,
     GetAgentServerCommand,
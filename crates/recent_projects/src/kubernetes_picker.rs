@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use gpui::{EventEmitter, SharedString, Task};
+use picker::Picker;
+use remote::{KubernetesConnectionOptions, KubernetesPod};
+use ui::{
+    App, Context, HighlightedLabel, Icon, IconName, ListItem, ParentElement, Styled, Toggleable,
+    Window, h_flex, v_flex,
+};
+use util::ResultExt as _;
+
+#[derive(Clone, Debug)]
+pub struct KubernetesPodSelected;
+
+#[derive(Clone, Debug)]
+pub struct KubernetesPickerDismissed;
+
+struct PodCandidate {
+    label: String,
+    connection_options: KubernetesConnectionOptions,
+}
+
+/// Lists the running pods of the current kubeconfig context, with an entry for
+/// each container of pods running more than one.
+pub(crate) struct KubernetesPickerDelegate {
+    selected_index: usize,
+    candidates: Option<Vec<PodCandidate>>,
+    error: Option<SharedString>,
+    matches: Vec<fuzzy_nucleo::StringMatch>,
+}
+
+impl KubernetesPickerDelegate {
+    pub fn new() -> Self {
+        KubernetesPickerDelegate {
+            selected_index: 0,
+            candidates: None,
+            error: None,
+            matches: Vec::new(),
+        }
+    }
+
+    pub fn selected_pod(&self) -> Option<KubernetesConnectionOptions> {
+        let candidate_id = self.matches.get(self.selected_index)?.candidate_id;
+        let candidate = self.candidates.as_ref()?.get(candidate_id)?;
+        Some(candidate.connection_options.clone())
+    }
+
+    fn set_pods(&mut self, pods: anyhow::Result<Vec<KubernetesPod>>) {
+        let pods = match pods {
+            Ok(pods) => pods,
+            Err(error) => {
+                log::error!("Failed to list Kubernetes pods: {error:#}");
+                self.error = Some(format!("Failed to list pods: {error}").into());
+                Vec::new()
+            }
+        };
+        self.candidates = Some(
+            pods.iter()
+                .flat_map(|pod| {
+                    let label = format!("{}/{}", pod.namespace, pod.name);
+                    if pod.containers.len() > 1 {
+                        pod.containers
+                            .iter()
+                            .map(|container| PodCandidate {
+                                label: format!("{label}/{container}"),
+                                connection_options: pod.connection_options(Some(container.clone())),
+                            })
+                            .collect::<Vec<_>>()
+                    } else {
+                        vec![PodCandidate {
+                            label,
+                            connection_options: pod.connection_options(None),
+                        }]
+                    }
+                })
+                .collect(),
+        );
+    }
+
+    fn match_candidates(&mut self, query: &str) {
+        use fuzzy_nucleo::StringMatchCandidate;
+        use ordered_float::OrderedFloat;
+
+        let Some(candidates) = &self.candidates else {
+            return;
+        };
+        let candidates = candidates
+            .iter()
+            .enumerate()
+            .map(|(id, candidate)| StringMatchCandidate::new(id, &candidate.label))
+            .collect::<Vec<_>>();
+
+        let query = query.trim_start();
+        let case = fuzzy_nucleo::Case::smart_if_uppercase_in(query);
+        self.matches = fuzzy_nucleo::match_strings(
+            &candidates,
+            query,
+            case,
+            fuzzy_nucleo::LengthPenalty::On,
+            100,
+        );
+        self.matches.sort_unstable_by_key(|m| m.candidate_id);
+
+        self.selected_index = self
+            .matches
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, m)| OrderedFloat(m.score))
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+    }
+}
+
+impl EventEmitter<KubernetesPodSelected> for Picker<KubernetesPickerDelegate> {}
+
+impl EventEmitter<KubernetesPickerDismissed> for Picker<KubernetesPickerDelegate> {}
+
+impl picker::PickerDelegate for KubernetesPickerDelegate {
+    type ListItem = ListItem;
+
+    fn name() -> &'static str {
+        "kubernetes-pod-picker"
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+        cx.notify();
+    }
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        Arc::from("Search Kubernetes pods…")
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        if let Some(error) = &self.error {
+            Some(error.clone())
+        } else if self.candidates.is_none() {
+            Some("Loading pods…".into())
+        } else {
+            Some("No running pods found.".into())
+        }
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        if self.candidates.is_some() {
+            self.match_candidates(&query);
+            return Task::ready(());
+        }
+
+        cx.spawn_in(window, async move |picker, cx| {
+            let pods = cx.background_spawn(remote::list_kubernetes_pods()).await;
+            picker
+                .update(cx, |picker, cx| {
+                    picker.delegate.set_pods(pods);
+                    picker.delegate.match_candidates(&query);
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _secondary: bool, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if self.selected_pod().is_some() {
+            cx.emit(KubernetesPodSelected);
+        }
+    }
+
+    fn dismissed(&mut self, _window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        cx.emit(KubernetesPickerDismissed);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let matched = self.matches.get(ix)?;
+        Some(
+            ListItem::new(ix)
+                .toggle_state(selected)
+                .inset(true)
+                .spacing(ui::ListItemSpacing::Sparse)
+                .child(
+                    h_flex()
+                        .flex_grow_1()
+                        .gap_3()
+                        .child(Icon::new(IconName::Box))
+                        .child(v_flex().child(HighlightedLabel::new(
+                            matched.string.clone(),
+                            matched.positions.clone(),
+                        ))),
+                ),
+        )
+    }
+}
@@ -1,5 +1,6 @@
 mod dev_container_suggest;
 pub mod disconnected_overlay;
+mod kubernetes_picker;
 mod remote_connections;
 mod remote_servers;
 pub mod sidebar_recent_projects;
@@ -601,6 +602,44 @@ pub fn add_wsl_distro(
     });
 }
 
+pub fn add_kubernetes_pod(
+    fs: Arc<dyn project::Fs>,
+    connection_options: &remote::KubernetesConnectionOptions,
+    cx: &App,
+) {
+    use gpui::ReadGlobal;
+    use settings::SettingsStore;
+
+    let remote::KubernetesConnectionOptions {
+        context,
+        namespace,
+        pod,
+        container,
+        ..
+    } = connection_options.clone();
+    SettingsStore::global(cx).update_settings_file(fs, move |setting, _| {
+        let connections = setting
+            .remote
+            .kubernetes_connections
+            .get_or_insert(Default::default());
+
+        if !connections.iter().any(|conn| {
+            conn.context == context
+                && conn.namespace == namespace
+                && conn.pod == pod
+                && conn.container == container
+        }) {
+            connections.push(settings::KubernetesConnection {
+                context,
+                namespace,
+                pod,
+                container,
+                projects: Default::default(),
+            })
+        }
+    });
+}
+
 pub struct RecentProjects {
     pub picker: Entity<Picker<RecentProjectsDelegate>>,
     _subscriptions: Vec<Subscription>,
@@ -1994,7 +2033,9 @@ pub(crate) fn icon_for_remote_connection(options: Option<&RemoteConnectionOption
         Some(options) => match options {
             RemoteConnectionOptions::Ssh(_) => IconName::Server,
             RemoteConnectionOptions::Wsl(_) => IconName::Linux,
-            RemoteConnectionOptions::Docker(_) => IconName::Box,
+            RemoteConnectionOptions::Docker(_) | RemoteConnectionOptions::Kubernetes(_) => {
+                IconName::Box
+            }
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(_) => IconName::Server,
         },
@@ -16,7 +16,10 @@ use remote::{
     SshConnectionOptions,
 };
 pub use settings::SshConnection;
-use settings::{DevContainerConnection, ExtendingVec, RegisterSetting, Settings, WslConnection};
+use settings::{
+    DevContainerConnection, ExtendingVec, KubernetesConnection, RegisterSetting, Settings,
+    WslConnection,
+};
 use util::paths::PathWithPosition;
 use workspace::{
     AppState, MultiWorkspace, OpenOptions, SerializedWorkspaceLocation, Workspace,
@@ -32,6 +35,7 @@ pub use remote_connection::{
 pub struct RemoteSettings {
     pub ssh_connections: ExtendingVec<SshConnection>,
     pub wsl_connections: ExtendingVec<WslConnection>,
+    pub kubernetes_connections: ExtendingVec<KubernetesConnection>,
     /// Whether to read ~/.ssh/config for ssh connection sources.
     pub read_ssh_config: bool,
 }
@@ -45,6 +49,10 @@ impl RemoteSettings {
         self.wsl_connections.clone().0.into_iter()
     }
 
+    pub fn kubernetes_connections(&self) -> impl Iterator<Item = KubernetesConnection> + use<> {
+        self.kubernetes_connections.clone().0.into_iter()
+    }
+
     pub fn fill_connection_options_from_settings(&self, options: &mut SshConnectionOptions) {
         for conn in self.ssh_connections() {
             if conn.host == options.host.to_string()
@@ -81,6 +89,7 @@ impl RemoteSettings {
 pub enum Connection {
     Ssh(SshConnection),
     Wsl(WslConnection),
+    Kubernetes(KubernetesConnection),
     DevContainer(DevContainerConnection),
 }
 
@@ -89,6 +98,7 @@ impl From<Connection> for RemoteConnectionOptions {
         match val {
             Connection::Ssh(conn) => RemoteConnectionOptions::Ssh(conn.into()),
             Connection::Wsl(conn) => RemoteConnectionOptions::Wsl(conn.into()),
+            Connection::Kubernetes(conn) => RemoteConnectionOptions::Kubernetes(conn.into()),
             Connection::DevContainer(conn) => {
                 RemoteConnectionOptions::Docker(DockerConnectionOptions {
                     name: conn.name,
@@ -115,12 +125,23 @@ impl From<WslConnection> for Connection {
     }
 }
 
+impl From<KubernetesConnection> for Connection {
+    fn from(val: KubernetesConnection) -> Self {
+        Connection::Kubernetes(val)
+    }
+}
+
 impl Settings for RemoteSettings {
     fn from_settings(content: &settings::SettingsContent) -> Self {
         let remote = &content.remote;
         Self {
             ssh_connections: remote.ssh_connections.clone().unwrap_or_default().into(),
             wsl_connections: remote.wsl_connections.clone().unwrap_or_default().into(),
+            kubernetes_connections: remote
+                .kubernetes_connections
+                .clone()
+                .unwrap_or_default()
+                .into(),
             read_ssh_config: remote.read_ssh_config.unwrap(),
         }
     }
@@ -321,6 +342,9 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Kubernetes(_) => {
+                                    "Failed to connect to Kubernetes Pod"
+                                }
                                 #[cfg(any(test, feature = "test-support"))]
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
@@ -382,6 +406,9 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Kubernetes(_) => {
+                                    "Failed to connect to Kubernetes Pod"
+                                }
                                 #[cfg(any(test, feature = "test-support"))]
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
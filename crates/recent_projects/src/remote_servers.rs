@@ -25,8 +25,8 @@ use paths::{global_ssh_config_file, user_ssh_config_file};
 use picker::{Picker, PickerDelegate, PickerEditorPosition};
 use project::{Fs, Project};
 use remote::{
-    RemoteClient, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
-    remote_client::ConnectionIdentifier,
+    KubernetesConnectionOptions, RemoteClient, RemoteConnectionOptions, SshConnectionOptions,
+    WslConnectionOptions, remote_client::ConnectionIdentifier,
 };
 use settings::{
     RemoteProject, RemoteSettingsContent, Settings as _, SettingsStore, update_settings_file,
@@ -157,6 +157,47 @@ impl AddWslDistro {
     }
 }
 
+struct AddKubernetesPod {
+    picker: Entity<Picker<crate::kubernetes_picker::KubernetesPickerDelegate>>,
+    connection_prompt: Option<Entity<RemoteConnectionPrompt>>,
+    _creating: Option<Task<()>>,
+}
+
+impl AddKubernetesPod {
+    fn new(window: &mut Window, cx: &mut Context<RemoteServerProjects>) -> Self {
+        use crate::kubernetes_picker::{
+            KubernetesPickerDelegate, KubernetesPickerDismissed, KubernetesPodSelected,
+        };
+
+        let delegate = KubernetesPickerDelegate::new();
+        let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx).embedded());
+
+        cx.subscribe_in(
+            &picker,
+            window,
+            |this, _, _: &KubernetesPodSelected, window, cx| {
+                this.confirm(&menu::Confirm, window, cx);
+            },
+        )
+        .detach();
+
+        cx.subscribe_in(
+            &picker,
+            window,
+            |this, _, _: &KubernetesPickerDismissed, window, cx| {
+                this.cancel(&menu::Cancel, window, cx);
+            },
+        )
+        .detach();
+
+        AddKubernetesPod {
+            picker,
+            connection_prompt: None,
+            _creating: None,
+        }
+    }
+}
+
 enum ProjectPickerData {
     Ssh {
         connection_string: SharedString,
@@ -165,6 +206,9 @@ enum ProjectPickerData {
     Wsl {
         distro_name: SharedString,
     },
+    Kubernetes {
+        pod: SharedString,
+    },
 }
 
 struct ProjectPicker {
@@ -408,6 +452,9 @@ impl ProjectPicker {
             RemoteConnectionOptions::Wsl(connection) => ProjectPickerData::Wsl {
                 distro_name: connection.distro_name.clone().into(),
             },
+            RemoteConnectionOptions::Kubernetes(_) => ProjectPickerData::Kubernetes {
+                pod: connection.display_name().into(),
+            },
             RemoteConnectionOptions::Docker(_) => ProjectPickerData::Ssh {
                 // Not implemented as a project picker at this time
                 connection_string: "".into(),
@@ -481,6 +528,16 @@ impl ProjectPicker {
                                         server.projects.insert(RemoteProject { paths });
                                     };
                                 }
+                                ServerIndex::Kubernetes(index) => {
+                                    if let Some(server) = settings
+                                        .remote
+                                        .kubernetes_connections
+                                        .as_mut()
+                                        .and_then(|connections| connections.get_mut(index.0))
+                                    {
+                                        server.projects.insert(RemoteProject { paths });
+                                    };
+                                }
                             }
                         });
                     })
@@ -578,6 +635,14 @@ impl gpui::Render for ProjectPicker {
                     is_devcontainer: false,
                 }
                 .render(window, cx),
+                ProjectPickerData::Kubernetes { pod } => SshConnectionHeader {
+                    connection_string: pod.clone(),
+                    paths: Default::default(),
+                    nickname: None,
+                    is_wsl: false,
+                    is_devcontainer: true,
+                }
+                .render(window, cx),
             })
             .child(
                 div()
@@ -606,10 +671,20 @@ impl std::fmt::Display for WslServerIndex {
     }
 }
 
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct KubernetesServerIndex(usize);
+impl std::fmt::Display for KubernetesServerIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum ServerIndex {
     Ssh(SshServerIndex),
     Wsl(WslServerIndex),
+    Kubernetes(KubernetesServerIndex),
 }
 impl From<SshServerIndex> for ServerIndex {
     fn from(index: SshServerIndex) -> Self {
@@ -621,6 +696,11 @@ impl From<WslServerIndex> for ServerIndex {
         Self::Wsl(index)
     }
 }
+impl From<KubernetesServerIndex> for ServerIndex {
+    fn from(index: KubernetesServerIndex) -> Self {
+        Self::Kubernetes(index)
+    }
+}
 
 #[derive(Clone)]
 struct ProjectEntry {
@@ -645,6 +725,7 @@ impl RemoteEntry {
             Self::Project { connection, .. } => match connection {
                 Connection::Ssh(c) => c.nickname.as_deref().unwrap_or(&c.host),
                 Connection::Wsl(c) => &c.distro_name,
+                Connection::Kubernetes(c) => &c.pod,
                 Connection::DevContainer(c) => &c.name,
             },
             Self::SshConfig { host, .. } => host,
@@ -729,7 +810,29 @@ impl DefaultState {
                 }
             });
 
-        let mut servers = ssh_servers.chain(wsl_servers).collect::<Vec<RemoteEntry>>();
+        let kubernetes_servers =
+            ssh_settings
+                .kubernetes_connections()
+                .enumerate()
+                .map(|(index, connection)| {
+                    let projects = connection
+                        .projects
+                        .iter()
+                        .map(|project| ProjectEntry {
+                            project: project.clone(),
+                        })
+                        .collect();
+                    RemoteEntry::Project {
+                        projects,
+                        index: ServerIndex::Kubernetes(KubernetesServerIndex(index)),
+                        connection: connection.into(),
+                    }
+                });
+
+        let mut servers = ssh_servers
+            .chain(wsl_servers)
+            .chain(kubernetes_servers)
+            .collect::<Vec<RemoteEntry>>();
 
         if read_ssh_config {
             let mut extra_servers_from_config = ssh_config_servers.clone();
@@ -778,6 +881,11 @@ enum ViewServerOptionsState {
         server_index: WslServerIndex,
         entries: [NavigableEntry; 2],
     },
+    Kubernetes {
+        connection: KubernetesConnectionOptions,
+        server_index: KubernetesServerIndex,
+        entries: [NavigableEntry; 2],
+    },
 }
 
 impl ViewServerOptionsState {
@@ -785,6 +893,7 @@ impl ViewServerOptionsState {
         match self {
             Self::Ssh { entries, .. } => entries,
             Self::Wsl { entries, .. } => entries,
+            Self::Kubernetes { entries, .. } => entries,
         }
     }
 }
@@ -798,6 +907,7 @@ enum Mode {
     CreateRemoteDevContainer(CreateRemoteDevContainer),
     #[cfg(target_os = "windows")]
     AddWslDistro(AddWslDistro),
+    AddKubernetesPod(AddKubernetesPod),
 }
 
 impl Mode {
@@ -812,6 +922,7 @@ impl Mode {
 enum RemoteMatch {
     AddServer,
     AddDevContainer,
+    AddKubernetesPod,
     AddWsl,
     Separator,
     ServerHeader {
@@ -901,6 +1012,7 @@ impl RemoteServerPickerDelegate {
             if has_open_project && is_local {
                 matches.push(RemoteMatch::AddDevContainer);
             }
+            matches.push(RemoteMatch::AddKubernetesPod);
             if cfg!(target_os = "windows") {
                 matches.push(RemoteMatch::AddWsl);
             }
@@ -997,17 +1109,30 @@ impl RemoteServerPickerDelegate {
     ) -> Option<AnyElement> {
         let server = self.state.servers.get(server_index)?;
         let connection = server.connection().into_owned();
-        let (main_label, aux_label, is_wsl) = match &connection {
+        let (main_label, aux_label, prefix) = match &connection {
             Connection::Ssh(connection) => {
                 if let Some(nickname) = connection.nickname.clone() {
                     let aux_label = SharedString::from(format!("({})", connection.host));
-                    (nickname, Some(aux_label), false)
+                    (nickname, Some(aux_label), None)
                 } else {
-                    (connection.host.clone(), None, false)
+                    (connection.host.clone(), None, None)
                 }
             }
-            Connection::Wsl(connection) => (connection.distro_name.clone(), None, true),
-            Connection::DevContainer(connection) => (connection.name.clone(), None, false),
+            Connection::Wsl(connection) => (connection.distro_name.clone(), None, Some("WSL:")),
+            Connection::Kubernetes(connection) => {
+                let aux_label = [
+                    connection.namespace.as_deref(),
+                    connection.container.as_deref(),
+                ]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join("/");
+                let aux_label =
+                    (!aux_label.is_empty()).then(|| SharedString::from(format!("({aux_label})")));
+                (connection.pod.clone(), aux_label, Some("Pod:"))
+            }
+            Connection::DevContainer(connection) => (connection.name.clone(), None, None),
         };
         Some(
             h_flex()
@@ -1022,9 +1147,9 @@ impl RemoteServerPickerDelegate {
                         .max_w_96()
                         .overflow_hidden()
                         .text_ellipsis()
-                        .when(is_wsl, |this| {
+                        .when_some(prefix, |this, prefix| {
                             this.child(
-                                Label::new("WSL:")
+                                Label::new(prefix)
                                     .size(LabelSize::Small)
                                     .color(Color::Muted),
                             )
@@ -1159,6 +1284,14 @@ impl PickerDelegate for RemoteServerPickerDelegate {
                     })
                     .ok();
             }
+            RemoteMatch::AddKubernetesPod => {
+                remote_server_projects
+                    .update(cx, |this, cx| {
+                        this.mode = Mode::AddKubernetesPod(AddKubernetesPod::new(window, cx));
+                        cx.notify();
+                    })
+                    .ok();
+            }
             RemoteMatch::AddWsl => {
                 #[cfg(target_os = "windows")]
                 remote_server_projects
@@ -1267,6 +1400,12 @@ impl PickerDelegate for RemoteServerPickerDelegate {
             RemoteMatch::AddDevContainer => {
                 Some(self.render_action_item(ix, IconName::Plus, "Connect Dev Container", selected))
             }
+            RemoteMatch::AddKubernetesPod => Some(self.render_action_item(
+                ix,
+                IconName::Plus,
+                "Connect Kubernetes Pod",
+                selected,
+            )),
             RemoteMatch::AddWsl => {
                 Some(self.render_action_item(ix, IconName::Plus, "Add WSL Distro", selected))
             }
@@ -1731,6 +1870,72 @@ impl RemoteServerProjects {
         });
     }
 
+    fn connect_kubernetes_pod(
+        &mut self,
+        picker: Entity<Picker<crate::kubernetes_picker::KubernetesPickerDelegate>>,
+        connection_options: KubernetesConnectionOptions,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let prompt = cx.new(|cx| {
+            RemoteConnectionPrompt::new(
+                RemoteConnectionOptions::from(connection_options.clone()).display_name(),
+                None,
+                false,
+                true,
+                window,
+                cx,
+            )
+        });
+        let connection = connect(
+            ConnectionIdentifier::setup(),
+            connection_options.clone().into(),
+            prompt.clone(),
+            window,
+            cx,
+        )
+        .prompt_err("Failed to connect", window, cx, |_, _, _| None);
+
+        let kubernetes_picker = picker.clone();
+        let creating = cx.spawn_in(window, async move |this, cx| {
+            match connection.await {
+                Some(Some(client)) => this.update_in(cx, |this, window, cx| {
+                    telemetry::event!("Kubernetes Pod Added");
+                    this.retained_connections.push(client);
+                    let Some(fs) = this
+                        .workspace
+                        .read_with(cx, |workspace, cx| {
+                            workspace.project().read(cx).fs().clone()
+                        })
+                        .log_err()
+                    else {
+                        return;
+                    };
+
+                    crate::add_kubernetes_pod(fs, &connection_options, cx);
+                    this.mode = Mode::default_mode(&BTreeSet::new(), cx);
+                    this.focus_handle(cx).focus(window, cx);
+                    cx.notify();
+                }),
+                _ => this.update(cx, |this, cx| {
+                    this.mode = Mode::AddKubernetesPod(AddKubernetesPod {
+                        picker: kubernetes_picker,
+                        connection_prompt: None,
+                        _creating: None,
+                    });
+                    cx.notify();
+                }),
+            }
+            .log_err();
+        });
+
+        self.mode = Mode::AddKubernetesPod(AddKubernetesPod {
+            picker,
+            connection_prompt: Some(prompt),
+            _creating: Some(creating),
+        });
+    }
+
     fn view_server_options(
         &mut self,
         (server_index, connection): (ServerIndex, RemoteConnectionOptions),
@@ -1752,6 +1957,14 @@ impl RemoteServerProjects {
                     entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
                 }
             }
+            (
+                ServerIndex::Kubernetes(server_index),
+                RemoteConnectionOptions::Kubernetes(connection),
+            ) => ViewServerOptionsState::Kubernetes {
+                connection,
+                server_index,
+                entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
+            },
             _ => {
                 log::error!("server index and connection options mismatch");
                 self.mode = Mode::default_mode(&BTreeSet::default(), cx);
@@ -1910,6 +2123,15 @@ impl RemoteServerProjects {
                 let distro = delegate.selected_distro().unwrap();
                 self.connect_wsl_distro(state.picker.clone(), distro, window, cx);
             }
+            Mode::AddKubernetesPod(state) => {
+                if state.connection_prompt.is_some() {
+                    return;
+                }
+                let Some(pod) = state.picker.read(cx).delegate.selected_pod() else {
+                    return;
+                };
+                self.connect_kubernetes_pod(state.picker.clone(), pod, window, cx);
+            }
         }
     }
 
@@ -2068,6 +2290,9 @@ impl RemoteServerProjects {
             ServerIndex::Wsl(server) => {
                 self.delete_wsl_project(server, project, cx);
             }
+            ServerIndex::Kubernetes(server) => {
+                self.delete_kubernetes_project(server, project, cx);
+            }
         }
     }
 
@@ -2115,6 +2340,34 @@ impl RemoteServerProjects {
         });
     }
 
+    fn delete_kubernetes_project(
+        &mut self,
+        server: KubernetesServerIndex,
+        project: &RemoteProject,
+        cx: &mut Context<Self>,
+    ) {
+        let project = project.clone();
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(server) = setting
+                .kubernetes_connections
+                .as_mut()
+                .and_then(|connections| connections.get_mut(server.0))
+            {
+                server.projects.remove(&project);
+            }
+        });
+    }
+
+    fn delete_kubernetes_pod(&mut self, server: KubernetesServerIndex, cx: &mut Context<Self>) {
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(connections) = setting.kubernetes_connections.as_mut()
+                && connections.get(server.0).is_some()
+            {
+                connections.remove(server.0);
+            }
+        });
+    }
+
     fn add_ssh_server(
         &mut self,
         connection_options: remote::SshConnectionOptions,
@@ -2573,6 +2826,32 @@ impl RemoteServerProjects {
             })
     }
 
+    fn render_add_kubernetes_pod(
+        &self,
+        state: &AddKubernetesPod,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let connection_prompt = state.connection_prompt.clone();
+
+        state.picker.update(cx, |picker, cx| {
+            picker.focus_handle(cx).focus(window, cx);
+        });
+
+        v_flex()
+            .id("add-kubernetes-pod")
+            .overflow_hidden()
+            .size_full()
+            .flex_1()
+            .map(|this| {
+                if let Some(connection_prompt) = connection_prompt {
+                    this.child(connection_prompt)
+                } else {
+                    this.child(state.picker.clone())
+                }
+            })
+    }
+
     fn render_view_options(
         &mut self,
         options: ViewServerOptionsState,
@@ -2604,6 +2883,17 @@ impl RemoteServerProjects {
                     }
                     .render(window, cx)
                     .into_any_element(),
+                    ViewServerOptionsState::Kubernetes { connection, .. } => SshConnectionHeader {
+                        connection_string: RemoteConnectionOptions::Kubernetes(connection.clone())
+                            .display_name()
+                            .into(),
+                        paths: Default::default(),
+                        nickname: None,
+                        is_wsl: false,
+                        is_devcontainer: true,
+                    }
+                    .render(window, cx)
+                    .into_any_element(),
                 })
                 .child(
                     v_flex()
@@ -2632,6 +2922,17 @@ impl RemoteServerProjects {
                                 window,
                                 cx,
                             )),
+                            ViewServerOptionsState::Kubernetes {
+                                connection,
+                                entries,
+                                server_index,
+                            } => this.child(self.render_edit_kubernetes(
+                                connection,
+                                *server_index,
+                                entries,
+                                window,
+                                cx,
+                            )),
                         })
                         .child(ListSeparator)
                         .child({
@@ -2740,6 +3041,73 @@ impl RemoteServerProjects {
         })
     }
 
+    fn render_edit_kubernetes(
+        &self,
+        connection: &KubernetesConnectionOptions,
+        index: KubernetesServerIndex,
+        entries: &[NavigableEntry],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let pod = SharedString::new(connection.pod.clone());
+
+        v_flex().child({
+            fn remove_kubernetes_pod(
+                remote_servers: Entity<RemoteServerProjects>,
+                index: KubernetesServerIndex,
+                pod: SharedString,
+                window: &mut Window,
+                cx: &mut App,
+            ) {
+                let prompt_message = format!("Remove Kubernetes pod `{}`?", pod);
+
+                let confirmation = window.prompt(
+                    PromptLevel::Warning,
+                    &prompt_message,
+                    None,
+                    &["Yes, remove it", "No, keep it"],
+                    cx,
+                );
+
+                cx.spawn(async move |cx| {
+                    if confirmation.await.ok() == Some(0) {
+                        remote_servers.update(cx, |this, cx| {
+                            this.delete_kubernetes_pod(index, cx);
+                        });
+                        remote_servers.update(cx, |this, cx| {
+                            this.mode = Mode::default_mode(&this.ssh_config_servers, cx);
+                            cx.notify();
+                        });
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+            div()
+                .id("kubernetes-options-remove-pod")
+                .track_focus(&entries[0].focus_handle)
+                .on_action(cx.listener({
+                    let pod = pod.clone();
+                    move |_, _: &menu::Confirm, window, cx| {
+                        remove_kubernetes_pod(cx.entity(), index, pod.clone(), window, cx);
+                        cx.focus_self(window);
+                    }
+                }))
+                .child(
+                    ListItem::new("remove-pod")
+                        .toggle_state(entries[0].focus_handle.contains_focused(window, cx))
+                        .inset(true)
+                        .spacing(ui::ListItemSpacing::Sparse)
+                        .start_slot(Icon::new(IconName::Trash).color(Color::Error))
+                        .child(Label::new("Remove Pod").color(Color::Error))
+                        .on_click(cx.listener(move |_, _, window, cx| {
+                            remove_kubernetes_pod(cx.entity(), index, pod.clone(), window, cx);
+                            cx.focus_self(window);
+                        })),
+                )
+        })
+    }
+
     fn render_edit_ssh(
         &self,
         connection: &SshConnectionOptions,
@@ -3109,6 +3477,9 @@ impl Render for RemoteServerProjects {
                 Mode::AddWslDistro(state) => self
                     .render_add_wsl_distro(state, window, cx)
                     .into_any_element(),
+                Mode::AddKubernetesPod(state) => self
+                    .render_add_kubernetes_pod(state, window, cx)
+                    .into_any_element(),
             })
     }
 }
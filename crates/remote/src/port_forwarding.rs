@@ -0,0 +1,125 @@
+//! TCP connections relayed over the remote connection itself, which lets
+//! ports be forwarded the same way for every transport.
+
+use std::net::Shutdown;
+
+use anyhow::{Result, anyhow};
+use futures::{AsyncReadExt as _, AsyncWriteExt as _, StreamExt as _, channel::mpsc};
+use rpc::{AnyProtoClient, proto};
+use smol::net::TcpStream;
+
+/// The most bytes sent in a single `PortTunnelData` message.
+const TUNNEL_CHUNK_SIZE: usize = 64 * 1024;
+/// The most `PortTunnelData` messages sent over a tunnel before the other
+/// side acknowledges writing them, which stops a fast reader from queueing
+/// an unbounded amount of data in front of a slow writer.
+const TUNNEL_WINDOW: usize = 16;
+
+/// The receiving end of a tunnel's messages from the other side, held by
+/// whoever handles the messages until the tunnel is closed.
+pub struct PortTunnel {
+    data_tx: mpsc::Sender<Vec<u8>>,
+    ack_tx: mpsc::Sender<()>,
+}
+
+/// The messages received for a tunnel, passed to [`relay_port_tunnel`].
+pub struct PortTunnelIncoming {
+    data_rx: mpsc::Receiver<Vec<u8>>,
+    ack_rx: mpsc::Receiver<()>,
+}
+
+impl PortTunnel {
+    pub fn new() -> (Self, PortTunnelIncoming) {
+        let (data_tx, data_rx) = mpsc::channel(TUNNEL_WINDOW);
+        let (ack_tx, ack_rx) = mpsc::channel(TUNNEL_WINDOW);
+        (
+            Self { data_tx, ack_tx },
+            PortTunnelIncoming { data_rx, ack_rx },
+        )
+    }
+
+    /// Queues bytes received from the other side to be written to the
+    /// connection. Fails if the other side sent more than its window, in
+    /// which case the tunnel should be closed.
+    pub fn receive_data(&mut self, data: Vec<u8>) -> Result<()> {
+        self.data_tx
+            .try_send(data)
+            .map_err(|_| anyhow!("port tunnel data exceeded its window"))
+    }
+
+    /// Records that the other side wrote one of the chunks sent to it.
+    pub fn receive_ack(&mut self) -> Result<()> {
+        self.ack_tx
+            .try_send(())
+            .map_err(|_| anyhow!("port tunnel acknowledged unsent data"))
+    }
+}
+
+/// Relays a tunnelled connection until either end closes it, then tells the
+/// other side that the tunnel is closed.
+///
+/// Bytes read from `stream` are sent to the other side as `PortTunnelData`
+/// messages, and the bytes it sends back are passed in through `incoming`.
+/// Each chunk written to `stream` is acknowledged with a `PortTunnelAck`, and
+/// reading pauses while [`TUNNEL_WINDOW`] chunks are unacknowledged. Dropping
+/// the [`PortTunnel`] closes the connection.
+pub async fn relay_port_tunnel(
+    project_id: u64,
+    tunnel_id: u64,
+    stream: TcpStream,
+    incoming: PortTunnelIncoming,
+    client: AnyProtoClient,
+) -> Result<()> {
+    let PortTunnelIncoming {
+        mut data_rx,
+        mut ack_rx,
+    } = incoming;
+    let mut reader = stream.clone();
+    let mut writer = stream.clone();
+    let read = async {
+        let mut buffer = vec![0; TUNNEL_CHUNK_SIZE];
+        let mut unacknowledged_chunks: usize = 0;
+        loop {
+            while let Ok(Some(())) = ack_rx.try_next() {
+                unacknowledged_chunks = unacknowledged_chunks.saturating_sub(1);
+            }
+            while unacknowledged_chunks >= TUNNEL_WINDOW {
+                if ack_rx.next().await.is_none() {
+                    return anyhow::Ok(());
+                }
+                unacknowledged_chunks -= 1;
+            }
+
+            let len = reader.read(&mut buffer).await?;
+            if len == 0 {
+                return anyhow::Ok(());
+            }
+            client.send(proto::PortTunnelData {
+                project_id,
+                tunnel_id,
+                data: buffer[..len].to_vec(),
+            })?;
+            unacknowledged_chunks += 1;
+        }
+    };
+    let write = async {
+        while let Some(data) = data_rx.next().await {
+            writer.write_all(&data).await?;
+            client.send(proto::PortTunnelAck {
+                project_id,
+                tunnel_id,
+            })?;
+        }
+        anyhow::Ok(())
+    };
+
+    let result = smol::future::or(read, write).await;
+    stream.shutdown(Shutdown::Both).ok();
+    client
+        .send(proto::ClosePortTunnel {
+            project_id,
+            tunnel_id,
+        })
+        .ok();
+    result
+}
@@ -1,4 +1,5 @@
 pub mod json_log;
+pub mod port_forwarding;
 pub mod protocol;
 pub mod proxy;
 pub mod remote_client;
@@ -16,6 +17,7 @@ pub use remote_identity::{
     RemoteConnectionIdentity, remote_connection_identity, same_remote_connection_identity,
 };
 pub use transport::docker::DockerConnectionOptions;
+pub use transport::kubernetes::{KubernetesConnectionOptions, KubernetesPod, list_kubernetes_pods};
 pub use transport::ssh::{SshConnectionOptions, SshPortForwardOption};
 pub use transport::wsl::WslConnectionOptions;
 #[cfg(target_os = "windows")]
@@ -22,6 +22,12 @@ pub enum RemoteConnectionIdentity {
         name: String,
         remote_user: String,
     },
+    Kubernetes {
+        context: Option<String>,
+        namespace: Option<String>,
+        pod: String,
+        container: Option<String>,
+    },
     #[cfg(any(test, feature = "test-support"))]
     Mock { id: u64 },
 }
@@ -51,6 +57,18 @@ impl RemoteConnectionIdentity {
                 name,
                 remote_user,
             } => format!("docker:{remote_user}@{name}:{container_id}"),
+            Self::Kubernetes {
+                context,
+                namespace,
+                pod,
+                container,
+            } => format!(
+                "kubernetes:{}/{}/{}:{}",
+                context.as_deref().unwrap_or_default(),
+                namespace.as_deref().unwrap_or_default(),
+                pod,
+                container.as_deref().unwrap_or_default()
+            ),
             #[cfg(any(test, feature = "test-support"))]
             Self::Mock { id } => format!("mock:{id}"),
         }
@@ -74,6 +92,12 @@ impl From<&RemoteConnectionOptions> for RemoteConnectionIdentity {
                 name: options.name.clone(),
                 remote_user: options.remote_user.clone(),
             },
+            RemoteConnectionOptions::Kubernetes(options) => Self::Kubernetes {
+                context: options.context.clone(),
+                namespace: options.namespace.clone(),
+                pod: options.pod.clone(),
+                container: options.container.clone(),
+            },
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(options) => Self::Mock { id: options.id },
         }
@@ -102,7 +126,10 @@ mod tests {
     use std::collections::BTreeMap;
 
     use super::*;
-    use crate::{DockerConnectionOptions, SshConnectionOptions, WslConnectionOptions};
+    use crate::{
+        DockerConnectionOptions, KubernetesConnectionOptions, SshConnectionOptions,
+        WslConnectionOptions,
+    };
 
     #[test]
     fn ssh_identity_ignores_non_persisted_runtime_fields() {
@@ -186,6 +213,28 @@ mod tests {
         assert!(same_remote_connection_identity(Some(&left), Some(&right),));
     }
 
+    #[test]
+    fn kubernetes_identity_includes_container() {
+        let options = |container: &str, upload_binary_over_kubectl_exec| {
+            RemoteConnectionOptions::Kubernetes(KubernetesConnectionOptions {
+                context: Some("dev-cluster".to_string()),
+                namespace: Some("team".to_string()),
+                pod: "api".to_string(),
+                container: Some(container.to_string()),
+                upload_binary_over_kubectl_exec,
+            })
+        };
+
+        assert!(same_remote_connection_identity(
+            Some(&options("app", true)),
+            Some(&options("app", false)),
+        ));
+        assert!(!same_remote_connection_identity(
+            Some(&options("app", false)),
+            Some(&options("sidecar", false)),
+        ));
+    }
+
     #[test]
     fn local_identity_matches_only_local_identity() {
         let remote = RemoteConnectionOptions::Wsl(WslConnectionOptions {
@@ -0,0 +1,1132 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use async_trait::async_trait;
+use collections::HashMap;
+use parking_lot::Mutex;
+use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
+use semver::Version as SemanticVersion;
+use std::time::Instant;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt;
+use util::command::Stdio;
+use util::shell::ShellKind;
+use util::{
+    paths::{PathStyle, RemotePathBuf},
+    rel_path::RelPath,
+};
+
+use futures::channel::mpsc::{Sender, UnboundedReceiver, UnboundedSender};
+use gpui::{App, AppContext, AsyncApp, Task};
+use rpc::proto::Envelope;
+
+use crate::{
+    RemoteArch, RemoteClientDelegate, RemoteConnection, RemoteConnectionOptions, RemoteOs,
+    RemotePlatform,
+    remote_client::{CommandTemplate, Interactive},
+    transport::parse_platform,
+};
+
+const KUBECTL_CLI: &str = "kubectl";
+
+/// Runs its arguments as a command after changing into the directory given as
+/// the first argument, since `kubectl exec` has no flag for the working
+/// directory.
+const CHANGE_DIRECTORY_SCRIPT: &str = "cd \"$1\" && shift && exec \"$@\"";
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct KubernetesConnectionOptions {
+    /// The kubeconfig context to use, or the current context when unset.
+    pub context: Option<String>,
+    /// The namespace of the pod, or the context's namespace when unset.
+    pub namespace: Option<String>,
+    pub pod: String,
+    /// The container to run in, or the pod's default container when unset.
+    pub container: Option<String>,
+    pub upload_binary_over_kubectl_exec: bool,
+}
+
+impl From<settings::KubernetesConnection> for KubernetesConnectionOptions {
+    fn from(val: settings::KubernetesConnection) -> Self {
+        KubernetesConnectionOptions {
+            context: val.context,
+            namespace: val.namespace,
+            pod: val.pod,
+            container: val.container,
+            upload_binary_over_kubectl_exec: false,
+        }
+    }
+}
+
+impl KubernetesConnectionOptions {
+    /// The arguments selecting the context and namespace, which precede the
+    /// `kubectl` subcommand.
+    fn global_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(context) = &self.context {
+            args.push("--context".to_string());
+            args.push(context.clone());
+        }
+        if let Some(namespace) = &self.namespace {
+            args.push("--namespace".to_string());
+            args.push(namespace.clone());
+        }
+        args
+    }
+
+    /// The arguments of `kubectl exec` that run `command` in the pod's
+    /// container, with the given working directory and environment.
+    fn exec_args(
+        &self,
+        interactive: Interactive,
+        working_directory: Option<&str>,
+        env: &[(String, String)],
+        command: Vec<String>,
+    ) -> Vec<String> {
+        let mut args = self.global_args();
+        args.push("exec".to_string());
+        args.push(match interactive {
+            Interactive::Yes => "-it".to_string(),
+            Interactive::No => "-i".to_string(),
+        });
+        args.push(self.pod.clone());
+        if let Some(container) = &self.container {
+            args.push("-c".to_string());
+            args.push(container.clone());
+        }
+        args.push("--".to_string());
+
+        if let Some(working_directory) = working_directory {
+            args.extend([
+                "sh".to_string(),
+                "-c".to_string(),
+                CHANGE_DIRECTORY_SCRIPT.to_string(),
+                "sh".to_string(),
+                working_directory.to_string(),
+            ]);
+        }
+        if !env.is_empty() {
+            args.push("env".to_string());
+            args.extend(env.iter().map(|(k, v)| format!("{k}={v}")));
+        }
+        args.extend(command);
+        args
+    }
+
+    /// The arguments of `kubectl cp` that copy a local path into the pod's
+    /// container.
+    fn copy_args(&self, src_path: &str, dst_path: &str) -> Vec<String> {
+        let mut args = self.global_args();
+        args.push("cp".to_string());
+        args.push(src_path.to_string());
+        args.push(format!("{}:{}", self.pod, dst_path));
+        if let Some(container) = &self.container {
+            args.push("-c".to_string());
+            args.push(container.clone());
+        }
+        args
+    }
+}
+
+/// A running pod that projects can be opened in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KubernetesPod {
+    pub context: Option<String>,
+    pub namespace: String,
+    pub name: String,
+    pub containers: Vec<String>,
+}
+
+impl KubernetesPod {
+    pub fn connection_options(&self, container: Option<String>) -> KubernetesConnectionOptions {
+        KubernetesConnectionOptions {
+            context: self.context.clone(),
+            namespace: Some(self.namespace.clone()),
+            pod: self.name.clone(),
+            container,
+            upload_binary_over_kubectl_exec: false,
+        }
+    }
+}
+
+const LIST_PODS_JSONPATH: &str = "jsonpath={range .items[*]}{.metadata.namespace}{\"\\t\"}{.metadata.name}{\"\\t\"}{.status.phase}{\"\\t\"}{range .spec.containers[*]}{.name}{\",\"}{end}{\"\\n\"}{end}";
+
+/// Lists the running pods of the current kubeconfig context, across all
+/// namespaces when permitted and in the context's namespace otherwise.
+pub async fn list_kubernetes_pods() -> Result<Vec<KubernetesPod>> {
+    list_pods_with_cli(KUBECTL_CLI).await
+}
+
+async fn list_pods_with_cli(kubectl_cli: &str) -> Result<Vec<KubernetesPod>> {
+    let context = run_kubectl(kubectl_cli, &["config", "current-context"])
+        .await
+        .context("failed to determine the current kubectl context")?;
+    let context = Some(context.trim().to_string()).filter(|context| !context.is_empty());
+
+    let output = match run_kubectl(
+        kubectl_cli,
+        &["get", "pods", "--all-namespaces", "-o", LIST_PODS_JSONPATH],
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(error) => {
+            log::info!("Failed to list pods in all namespaces, listing current namespace: {error}");
+            run_kubectl(kubectl_cli, &["get", "pods", "-o", LIST_PODS_JSONPATH]).await?
+        }
+    };
+    Ok(parse_pods(context, &output))
+}
+
+fn parse_pods(context: Option<String>, output: &str) -> Vec<KubernetesPod> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.trim().split('\t');
+            let namespace = fields.next()?;
+            let name = fields.next()?;
+            let phase = fields.next()?;
+            let containers = fields.next().unwrap_or_default();
+            if name.is_empty() || phase != "Running" {
+                return None;
+            }
+            Some(KubernetesPod {
+                context: context.clone(),
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+                containers: containers
+                    .split(',')
+                    .filter(|container| !container.is_empty())
+                    .map(ToString::to_string)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+async fn run_kubectl(kubectl_cli: &str, args: &[impl AsRef<str>]) -> Result<String> {
+    let mut command = util::command::new_command(kubectl_cli);
+    for arg in args {
+        command.arg(arg.as_ref());
+    }
+    let output = command.output().await?;
+    log::debug!("{:?}: {:?}", command, output);
+    anyhow::ensure!(
+        output.status.success(),
+        "failed to run command {command:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+pub(crate) struct KubectlExecConnection {
+    proxy_process: Mutex<Option<u32>>,
+    kubectl_cli: String,
+    remote_dir_for_server: String,
+    remote_binary_relpath: Option<Arc<RelPath>>,
+    connection_options: KubernetesConnectionOptions,
+    remote_platform: Option<RemotePlatform>,
+    os_version: Option<String>,
+    path_style: Option<PathStyle>,
+    shell: String,
+}
+
+impl KubectlExecConnection {
+    pub async fn new(
+        connection_options: KubernetesConnectionOptions,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        let mut this = Self::with_cli(KUBECTL_CLI, connection_options);
+        let (release_channel, version, commit) = cx.update(|cx| {
+            (
+                ReleaseChannel::global(cx),
+                AppVersion::global(cx),
+                AppCommitSha::try_global(cx),
+            )
+        });
+        let remote_platform = this.check_remote_platform().await?;
+
+        this.path_style = match remote_platform.os {
+            RemoteOs::Windows => Some(PathStyle::Windows),
+            _ => Some(PathStyle::Unix),
+        };
+
+        this.remote_platform = Some(remote_platform);
+        log::info!("Remote platform discovered: {:?}", this.remote_platform);
+
+        this.os_version = this.discover_os_version(remote_platform.os).await;
+        log::info!("Remote OS version discovered: {:?}", this.os_version);
+
+        this.shell = this.discover_shell().await;
+        log::info!("Remote shell discovered: {}", this.shell);
+
+        this.remote_dir_for_server = this.pod_user_home_dir().await?.trim().to_string();
+
+        this.remote_binary_relpath = Some(
+            this.ensure_server_binary(
+                &delegate,
+                release_channel,
+                version,
+                &this.remote_dir_for_server,
+                commit,
+                cx,
+            )
+            .await?,
+        );
+
+        Ok(this)
+    }
+
+    fn with_cli(kubectl_cli: &str, connection_options: KubernetesConnectionOptions) -> Self {
+        Self {
+            proxy_process: Mutex::new(None),
+            kubectl_cli: kubectl_cli.to_string(),
+            remote_dir_for_server: "/".to_string(),
+            remote_binary_relpath: None,
+            connection_options,
+            remote_platform: None,
+            os_version: None,
+            path_style: None,
+            shell: "sh".to_owned(),
+        }
+    }
+
+    /// Run a shell command inside the container and reliably extract its output
+    /// using unique delimiters, so that shell initialization noise does not
+    /// corrupt the result.
+    async fn run_kubectl_exec_delimited(&self, script: &str) -> Result<String> {
+        const MARKER: &str = "=====ZED_DELIM_7f3a9c=====";
+        let wrapped =
+            format!("printf '{MARKER}'; {script}; __exit=$?; printf '{MARKER}'; exit $__exit");
+        let output = self
+            .run_kubectl_exec("sh", None, &Default::default(), &["-c", &wrapped])
+            .await?;
+        let start = output.find(MARKER).map(|i| i + MARKER.len()).unwrap_or(0);
+        let end = output[start..]
+            .find(MARKER)
+            .map(|i| start + i)
+            .unwrap_or(output.len());
+        Ok(output[start..end].to_string())
+    }
+
+    async fn discover_shell(&self) -> String {
+        let default_shell = "sh";
+        match self.run_kubectl_exec_delimited("echo $SHELL").await {
+            Ok(shell) => match shell.trim() {
+                "" => {
+                    log::info!("$SHELL is not set, checking passwd for user");
+                }
+                shell => {
+                    return shell.to_owned();
+                }
+            },
+            Err(e) => {
+                log::error!("Failed to get $SHELL: {e}. Checking passwd for user");
+            }
+        }
+
+        match self
+            .run_kubectl_exec_delimited("getent passwd \"$(id -un)\" | cut -d: -f7")
+            .await
+        {
+            Ok(shell) => match shell.trim() {
+                "" => {
+                    log::info!("No shell found in passwd, falling back to {default_shell}");
+                }
+                shell => {
+                    return shell.to_owned();
+                }
+            },
+            Err(e) => {
+                log::info!("Error getting shell from passwd: {e}. Falling back to {default_shell}");
+            }
+        }
+        default_shell.to_owned()
+    }
+
+    async fn check_remote_platform(&self) -> Result<RemotePlatform> {
+        let uname = self.run_kubectl_exec_delimited("uname -sm").await?;
+        parse_platform(&uname)
+    }
+
+    /// Best-effort detection of the container's OS version for telemetry.
+    async fn discover_os_version(&self, os: RemoteOs) -> Option<String> {
+        let (program, args) = super::os_version_command(os);
+        match self
+            .run_kubectl_exec(program, None, &Default::default(), args)
+            .await
+        {
+            Ok(output) => super::parse_os_version(os, &output),
+            Err(error) => {
+                log::warn!("Failed to determine remote OS version: {error:#}");
+                None
+            }
+        }
+    }
+
+    async fn ensure_server_binary(
+        &self,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        release_channel: ReleaseChannel,
+        version: SemanticVersion,
+        remote_dir_for_server: &str,
+        commit: Option<AppCommitSha>,
+        cx: &mut AsyncApp,
+    ) -> Result<Arc<RelPath>> {
+        let remote_platform = self
+            .remote_platform
+            .context("No remote platform defined; cannot proceed.")?;
+
+        let version_str = match release_channel {
+            ReleaseChannel::Nightly => {
+                let commit = commit.map(|s| s.full()).unwrap_or_default();
+                format!("{}-{}", version, commit)
+            }
+            ReleaseChannel::Dev => "build".to_string(),
+            _ => version.to_string(),
+        };
+        let binary_name = format!(
+            "zed-remote-server-{}-{}",
+            release_channel.dev_name(),
+            version_str
+        );
+        let dst_path =
+            paths::remote_server_dir_relative().join(RelPath::from_unix_str(&binary_name).unwrap());
+
+        let binary_exists_on_server = self
+            .run_kubectl_exec(
+                &dst_path.display(self.path_style()),
+                Some(&remote_dir_for_server),
+                &Default::default(),
+                &["version"],
+            )
+            .await
+            .is_ok();
+        #[cfg(any(debug_assertions, feature = "build-remote-server-binary"))]
+        if let Some(remote_server_path) = super::build_remote_server_from_source(
+            &remote_platform,
+            delegate.as_ref(),
+            binary_exists_on_server,
+            cx,
+        )
+        .await?
+        {
+            let tmp_path = paths::remote_server_dir_relative().join(
+                RelPath::from_unix_str(&format!(
+                    "download-{}-{}",
+                    std::process::id(),
+                    remote_server_path.file_name().unwrap().to_string_lossy()
+                ))
+                .unwrap(),
+            );
+            self.upload_local_server_binary(
+                &remote_server_path,
+                &tmp_path,
+                &remote_dir_for_server,
+                delegate,
+                cx,
+            )
+            .await?;
+            self.extract_server_binary(&dst_path, &tmp_path, &remote_dir_for_server, delegate, cx)
+                .await?;
+            return Ok(dst_path.into());
+        }
+
+        if binary_exists_on_server {
+            return Ok(dst_path.into());
+        }
+
+        let wanted_version = cx.update(|cx| match release_channel {
+            ReleaseChannel::Nightly => Ok(None),
+            ReleaseChannel::Dev => {
+                anyhow::bail!(
+                    "ZED_BUILD_REMOTE_SERVER is not set and no remote server exists at ({:?})",
+                    dst_path
+                )
+            }
+            _ => Ok(Some(AppVersion::global(cx))),
+        })?;
+
+        let tmp_path_gz = paths::remote_server_dir_relative().join(
+            RelPath::from_unix_str(&format!(
+                "{}-download-{}.gz",
+                binary_name,
+                std::process::id()
+            ))
+            .unwrap(),
+        );
+        if !self.connection_options.upload_binary_over_kubectl_exec
+            && let Some(url) = delegate
+                .get_download_url(remote_platform, release_channel, wanted_version.clone(), cx)
+                .await?
+        {
+            match self
+                .download_binary_on_server(&url, &tmp_path_gz, &remote_dir_for_server, delegate, cx)
+                .await
+            {
+                Ok(_) => {
+                    self.extract_server_binary(
+                        &dst_path,
+                        &tmp_path_gz,
+                        &remote_dir_for_server,
+                        delegate,
+                        cx,
+                    )
+                    .await
+                    .context("extracting server binary")?;
+                    return Ok(dst_path.into());
+                }
+                Err(e) => {
+                    log::error!(
+                        "Failed to download binary on server, attempting to download locally and then upload it the server: {e:#}",
+                    )
+                }
+            }
+        }
+
+        let src_path = delegate
+            .download_server_binary_locally(remote_platform, release_channel, wanted_version, cx)
+            .await
+            .context("downloading server binary locally")?;
+        self.upload_local_server_binary(
+            &src_path,
+            &tmp_path_gz,
+            &remote_dir_for_server,
+            delegate,
+            cx,
+        )
+        .await
+        .context("uploading server binary")?;
+        self.extract_server_binary(
+            &dst_path,
+            &tmp_path_gz,
+            &remote_dir_for_server,
+            delegate,
+            cx,
+        )
+        .await
+        .context("extracting server binary")?;
+        Ok(dst_path.into())
+    }
+
+    async fn pod_user_home_dir(&self) -> Result<String> {
+        self.run_kubectl_exec_delimited("echo $HOME").await
+    }
+
+    async fn extract_server_binary(
+        &self,
+        dst_path: &RelPath,
+        tmp_path: &RelPath,
+        remote_dir_for_server: &str,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        delegate.set_status(Some("Extracting remote development server"), cx);
+        let server_mode = 0o755;
+
+        let shell_kind = ShellKind::Posix;
+        let orig_tmp_path = tmp_path.display(self.path_style());
+        let server_mode = format!("{:o}", server_mode);
+        let server_mode = shell_kind
+            .try_quote(&server_mode)
+            .context("shell quoting")?;
+        let dst_path = dst_path.display(self.path_style());
+        let dst_path = shell_kind.try_quote(&dst_path).context("shell quoting")?;
+        let script = if let Some(tmp_path) = orig_tmp_path.strip_suffix(".gz") {
+            let orig_tmp_path = shell_kind
+                .try_quote(&orig_tmp_path)
+                .context("shell quoting")?;
+            let tmp_path = shell_kind.try_quote(&tmp_path).context("shell quoting")?;
+            format!(
+                "gunzip -f {orig_tmp_path} && chmod {server_mode} {tmp_path} && mv {tmp_path} {dst_path}",
+            )
+        } else {
+            let orig_tmp_path = shell_kind
+                .try_quote(&orig_tmp_path)
+                .context("shell quoting")?;
+            format!("chmod {server_mode} {orig_tmp_path} && mv {orig_tmp_path} {dst_path}",)
+        };
+        let args = shell_kind.args_for_shell(false, script.to_string());
+        self.run_kubectl_exec(
+            "sh",
+            Some(&remote_dir_for_server),
+            &Default::default(),
+            &args,
+        )
+        .await
+        .log_err();
+        Ok(())
+    }
+
+    async fn upload_local_server_binary(
+        &self,
+        src_path: &Path,
+        tmp_path_gz: &RelPath,
+        remote_dir_for_server: &str,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        if let Some(parent) = tmp_path_gz.parent() {
+            self.run_kubectl_exec(
+                "mkdir",
+                Some(remote_dir_for_server),
+                &Default::default(),
+                &["-p", parent.display(self.path_style()).as_ref()],
+            )
+            .await?;
+        }
+
+        let src_stat = smol::fs::metadata(&src_path).await?;
+        let size = src_stat.len();
+
+        let t0 = Instant::now();
+        delegate.set_status(Some("Uploading remote development server"), cx);
+        log::info!(
+            "uploading remote development server to {:?} ({}kb)",
+            tmp_path_gz,
+            size / 1024
+        );
+        let src_path = src_path.display().to_string();
+        let dst_path = format!(
+            "{}/{}",
+            remote_dir_for_server,
+            tmp_path_gz.display(self.path_style())
+        );
+        Self::upload(
+            self.kubectl_cli.clone(),
+            self.connection_options.clone(),
+            src_path,
+            dst_path,
+        )
+        .await
+        .context("failed to upload server binary")?;
+        log::info!("uploaded remote development server in {:?}", t0.elapsed());
+        Ok(())
+    }
+
+    async fn upload(
+        kubectl_cli: String,
+        connection_options: KubernetesConnectionOptions,
+        src_path: String,
+        dst_path: String,
+    ) -> Result<()> {
+        log::debug!("uploading {src_path} to {dst_path}");
+        run_kubectl(
+            &kubectl_cli,
+            &connection_options.copy_args(&src_path, &dst_path),
+        )
+        .await
+        .with_context(|| format!("failed to upload via kubectl cp {src_path} -> {dst_path}"))?;
+        Ok(())
+    }
+
+    async fn run_kubectl_exec(
+        &self,
+        inner_program: &str,
+        working_directory: Option<&str>,
+        env: &HashMap<String, String>,
+        program_args: &[impl AsRef<str>],
+    ) -> Result<String> {
+        let env = env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        let mut command = vec![inner_program.to_string()];
+        command.extend(program_args.iter().map(|arg| arg.as_ref().to_owned()));
+        let args =
+            self.connection_options
+                .exec_args(Interactive::No, working_directory, &env, command);
+        run_kubectl(&self.kubectl_cli, &args).await
+    }
+
+    async fn download_binary_on_server(
+        &self,
+        url: &str,
+        tmp_path_gz: &RelPath,
+        remote_dir_for_server: &str,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        if let Some(parent) = tmp_path_gz.parent() {
+            self.run_kubectl_exec(
+                "mkdir",
+                Some(remote_dir_for_server),
+                &Default::default(),
+                &["-p", parent.display(self.path_style()).as_ref()],
+            )
+            .await?;
+        }
+
+        delegate.set_status(Some("Downloading remote development server on host"), cx);
+
+        match self
+            .run_kubectl_exec(
+                "curl",
+                Some(remote_dir_for_server),
+                &Default::default(),
+                &[
+                    "-f",
+                    "-L",
+                    url,
+                    "-o",
+                    &tmp_path_gz.display(self.path_style()),
+                ],
+            )
+            .await
+        {
+            Ok(_) => {}
+            Err(e) => {
+                if self
+                    .run_kubectl_exec("which", None, &Default::default(), &["curl"])
+                    .await
+                    .is_ok()
+                {
+                    return Err(e);
+                }
+
+                log::info!("curl is not available, trying wget");
+                match self
+                    .run_kubectl_exec(
+                        "wget",
+                        Some(remote_dir_for_server),
+                        &Default::default(),
+                        &[url, "-O", &tmp_path_gz.display(self.path_style())],
+                    )
+                    .await
+                {
+                    Ok(_) => {}
+                    Err(e) => {
+                        if self
+                            .run_kubectl_exec("which", None, &Default::default(), &["wget"])
+                            .await
+                            .is_ok()
+                        {
+                            return Err(e);
+                        } else {
+                            anyhow::bail!("Neither curl nor wget is available");
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn kill_inner(&self) -> Result<()> {
+        if let Some(pid) = self.proxy_process.lock().take() {
+            if let Ok(_) = util::command::new_command("kill")
+                .arg(pid.to_string())
+                .spawn()
+            {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!("Failed to kill process"))
+            }
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for KubectlExecConnection {
+    fn has_wsl_interop(&self) -> bool {
+        false
+    }
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        // We'll try connecting anew every time we open a pod, so proactively try to kill any old connections.
+        if !self.has_been_killed() {
+            if let Err(e) = self.kill_inner() {
+                return Task::ready(Err(e));
+            };
+        }
+
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let Some(remote_binary_relpath) = self.remote_binary_relpath.clone() else {
+            return Task::ready(Err(anyhow!("Remote binary path not set")));
+        };
+
+        let env = ["RUST_LOG", "RUST_BACKTRACE", "ZED_GENERATE_MINIDUMPS"]
+            .into_iter()
+            .filter_map(|env_var| Some((env_var.to_string(), std::env::var(env_var).ok()?)))
+            .collect::<Vec<_>>();
+        let mut proxy_command = vec![
+            remote_binary_relpath
+                .display(self.path_style())
+                .into_owned(),
+            "proxy".to_string(),
+            "--identifier".to_string(),
+            unique_identifier,
+        ];
+        if reconnect {
+            proxy_command.push("--reconnect".to_string());
+        }
+        let kubectl_args = self.connection_options.exec_args(
+            Interactive::No,
+            Some(&self.remote_dir_for_server),
+            &env,
+            proxy_command,
+        );
+
+        let mut command = util::command::new_command(&self.kubectl_cli);
+        command
+            .kill_on_drop(true)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .args(kubectl_args);
+
+        let Ok(child) = command.spawn() else {
+            return Task::ready(Err(anyhow::anyhow!(
+                "Failed to start remote server process"
+            )));
+        };
+
+        let mut proxy_process = self.proxy_process.lock();
+        *proxy_process = Some(child.id());
+
+        cx.spawn(async move |cx| {
+            super::handle_rpc_messages_over_child_process_stdio(
+                child,
+                incoming_tx,
+                outgoing_rx,
+                connection_activity_tx,
+                cx,
+            )
+            .await
+            .and_then(|status| {
+                if status != 0 {
+                    anyhow::bail!("Remote server exited with status {status}");
+                }
+                Ok(0)
+            })
+        })
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: RemotePathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let upload_task = Self::upload(
+            self.kubectl_cli.clone(),
+            self.connection_options.clone(),
+            src_path.display().to_string(),
+            dest_path.to_string(),
+        );
+
+        cx.background_spawn(upload_task)
+    }
+
+    async fn kill(&self) -> Result<()> {
+        self.kill_inner()
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.proxy_process.lock().is_none()
+    }
+
+    fn build_command(
+        &self,
+        program: Option<String>,
+        args: &[String],
+        env: &HashMap<String, String>,
+        working_dir: Option<String>,
+        _port_forward: Option<(u16, String, u16)>,
+        interactive: Interactive,
+    ) -> Result<CommandTemplate> {
+        let mut parsed_working_dir = None;
+
+        let path_style = self.path_style();
+
+        if let Some(working_dir) = working_dir {
+            let working_dir = RemotePathBuf::new(working_dir, path_style).to_string();
+
+            const TILDE_PREFIX: &'static str = "~/";
+            if working_dir.starts_with(TILDE_PREFIX) {
+                let working_dir = working_dir.trim_start_matches("~").trim_start_matches("/");
+                parsed_working_dir =
+                    Some(format!("{}/{}", self.remote_dir_for_server, working_dir));
+            } else {
+                parsed_working_dir = Some(working_dir);
+            }
+        }
+
+        let mut inner_program = Vec::new();
+
+        if let Some(program) = program {
+            inner_program.push(program);
+            for arg in args {
+                inner_program.push(arg.clone());
+            }
+        } else {
+            inner_program.push(self.shell());
+            inner_program.push("-l".to_string());
+        };
+
+        let mut env = env
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Vec<_>>();
+        env.sort();
+
+        Ok(CommandTemplate {
+            program: self.kubectl_cli.clone(),
+            args: self.connection_options.exec_args(
+                interactive,
+                parsed_working_dir.as_deref(),
+                &env,
+                inner_program,
+            ),
+            // kubectl exec cannot set the environment, so it is passed via `env`
+            env: Default::default(),
+        })
+    }
+
+    fn build_forward_ports_command(
+        &self,
+        _forwards: Vec<(u16, String, u16)>,
+    ) -> Result<CommandTemplate> {
+        Err(anyhow::anyhow!("Not currently supported for kubectl_exec"))
+    }
+
+    fn connection_options(&self) -> RemoteConnectionOptions {
+        RemoteConnectionOptions::Kubernetes(self.connection_options.clone())
+    }
+
+    fn path_style(&self) -> PathStyle {
+        self.path_style.unwrap_or(PathStyle::Unix)
+    }
+
+    fn remote_platform(&self) -> RemotePlatform {
+        // Pods are always Linux; the platform is populated during setup, so
+        // this fallback is only for the brief pre-detection window.
+        self.remote_platform.unwrap_or(RemotePlatform {
+            os: RemoteOs::Linux,
+            arch: RemoteArch::X86_64,
+        })
+    }
+
+    fn remote_os_version(&self) -> Option<String> {
+        self.os_version.clone()
+    }
+
+    fn shell(&self) -> String {
+        self.shell.clone()
+    }
+
+    fn default_system_shell(&self) -> String {
+        String::from("/bin/sh")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> KubernetesConnectionOptions {
+        KubernetesConnectionOptions {
+            context: Some("dev-cluster".to_string()),
+            namespace: Some("team".to_string()),
+            pod: "api-7d9f".to_string(),
+            container: Some("app".to_string()),
+            upload_binary_over_kubectl_exec: false,
+        }
+    }
+
+    #[test]
+    fn test_exec_args() {
+        assert_eq!(
+            options().exec_args(
+                Interactive::Yes,
+                Some("/home/dev"),
+                &[("TERM".to_string(), "xterm".to_string())],
+                vec!["bash".to_string(), "-l".to_string()],
+            ),
+            [
+                "--context",
+                "dev-cluster",
+                "--namespace",
+                "team",
+                "exec",
+                "-it",
+                "api-7d9f",
+                "-c",
+                "app",
+                "--",
+                "sh",
+                "-c",
+                CHANGE_DIRECTORY_SCRIPT,
+                "sh",
+                "/home/dev",
+                "env",
+                "TERM=xterm",
+                "bash",
+                "-l",
+            ]
+        );
+
+        let options = KubernetesConnectionOptions {
+            pod: "api-7d9f".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.exec_args(Interactive::No, None, &[], vec!["uname".to_string()]),
+            ["exec", "-i", "api-7d9f", "--", "uname"]
+        );
+    }
+
+    #[test]
+    fn test_copy_args() {
+        assert_eq!(
+            options().copy_args("/tmp/server.gz", "/home/dev/.zed_server/server.gz"),
+            [
+                "--context",
+                "dev-cluster",
+                "--namespace",
+                "team",
+                "cp",
+                "/tmp/server.gz",
+                "api-7d9f:/home/dev/.zed_server/server.gz",
+                "-c",
+                "app",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_pods() {
+        let output = "team\tapi-7d9f\tRunning\tapp,sidecar,\n\
+                      team\tjob-1\tSucceeded\tjob,\n\
+                      default\tdev\tRunning\tdev,\n";
+        assert_eq!(
+            parse_pods(Some("dev-cluster".to_string()), output),
+            [
+                KubernetesPod {
+                    context: Some("dev-cluster".to_string()),
+                    namespace: "team".to_string(),
+                    name: "api-7d9f".to_string(),
+                    containers: vec!["app".to_string(), "sidecar".to_string()],
+                },
+                KubernetesPod {
+                    context: Some("dev-cluster".to_string()),
+                    namespace: "default".to_string(),
+                    name: "dev".to_string(),
+                    containers: vec!["dev".to_string()],
+                },
+            ]
+        );
+    }
+
+    /// Writes an executable standing in for `kubectl`, which records its
+    /// arguments and runs the command after `--` for `exec`.
+    #[cfg(unix)]
+    fn fake_kubectl(dir: &Path) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let log_path = dir.join("invocations");
+        let kubectl_path = dir.join("kubectl");
+        std::fs::write(
+            &kubectl_path,
+            format!(
+                r#"#!/bin/sh
+echo "$*" >> '{}'
+case "$*" in
+    "config current-context") echo dev-cluster ;;
+    "get pods --all-namespaces"*) echo forbidden >&2; exit 1 ;;
+    "get pods"*) printf 'team\tapi\tRunning\tapp,\n' ;;
+    *" exec "*|"exec "*)
+        while [ "$1" != "--" ]; do shift; done
+        shift
+        exec "$@"
+        ;;
+    *" cp "*|"cp "*) ;;
+    *) exit 1 ;;
+esac
+"#,
+                log_path.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&kubectl_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        kubectl_path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_kubectl_exec_with_fake_kubectl() {
+        let dir = tempfile::tempdir().unwrap();
+        let kubectl = fake_kubectl(dir.path());
+        let connection = KubectlExecConnection::with_cli(kubectl.to_str().unwrap(), options());
+
+        let output = smol::block_on(connection.run_kubectl_exec(
+            "sh",
+            Some(dir.path().to_str().unwrap()),
+            &HashMap::from_iter([("GREETING".to_string(), "hello".to_string())]),
+            &["-c", "echo \"$GREETING from $(pwd -P)\""],
+        ))
+        .unwrap();
+        assert_eq!(
+            output.trim(),
+            format!(
+                "hello from {}",
+                dir.path().canonicalize().unwrap().display()
+            )
+        );
+
+        smol::block_on(KubectlExecConnection::upload(
+            kubectl.to_str().unwrap().to_string(),
+            options(),
+            "/tmp/server.gz".to_string(),
+            "/home/dev/server.gz".to_string(),
+        ))
+        .unwrap();
+        let invocations = std::fs::read_to_string(dir.path().join("invocations")).unwrap();
+        assert!(invocations.lines().any(|line| line
+            == "--context dev-cluster --namespace team cp /tmp/server.gz api-7d9f:/home/dev/server.gz -c app"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_pods_with_fake_kubectl() {
+        let dir = tempfile::tempdir().unwrap();
+        let kubectl = fake_kubectl(dir.path());
+
+        let pods = smol::block_on(list_pods_with_cli(kubectl.to_str().unwrap())).unwrap();
+        assert_eq!(
+            pods,
+            [KubernetesPod {
+                context: Some("dev-cluster".to_string()),
+                namespace: "team".to_string(),
+                name: "api".to_string(),
+                containers: vec!["app".to_string()],
+            }]
+        );
+    }
+}
@@ -49,6 +49,8 @@ use sysinfo::{ProcessRefreshKind, RefreshKind, System, UpdateKind};
 use util::{ResultExt, paths::PathStyle, rel_path::RelPath};
 use worktree::Worktree;
 
+use crate::port_forwarder::PortForwarder;
+
 pub struct HeadlessProject {
     pub fs: Arc<dyn Fs>,
     pub session: AnyProtoClient,
@@ -71,6 +73,7 @@ pub struct HeadlessProject {
     // Local variant is used within LSP store, but that's a separate entity.
     pub _toolchain_store: Entity<ToolchainStore>,
     pub kernels: HashMap<String, Child>,
+    pub port_forwarder: Entity<PortForwarder>,
 }
 
 pub struct HeadlessAppState {
@@ -252,6 +255,8 @@ impl HeadlessProject {
             context_server_store
         });
 
+        let port_forwarder = cx.new(|cx| PortForwarder::new(session.clone(), cx));
+
         cx.subscribe(&lsp_store, Self::on_lsp_store_event).detach();
         language_extension::init(
             language_extension::LspAccess::ViaLspStore(lsp_store.downgrade()),
@@ -288,6 +293,7 @@ impl HeadlessProject {
         session.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &git_store);
         session.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &agent_server_store);
         session.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &context_server_store);
+        session.subscribe_to_entity(REMOTE_SERVER_PROJECT_ID, &port_forwarder);
 
         session.add_request_handler(cx.weak_entity(), Self::handle_list_remote_directory);
         session.add_request_handler(cx.weak_entity(), Self::handle_get_path_metadata);
@@ -338,6 +344,7 @@ impl HeadlessProject {
         GitStore::init(&session);
         AgentServerStore::init_headless(&session);
         ContextServerStore::init_headless(&session);
+        PortForwarder::init(&session);
 
         HeadlessProject {
             next_entry_id: Default::default(),
@@ -359,6 +366,7 @@ impl HeadlessProject {
             profiling_collector: gpui::ProfilingCollector::new(startup_time),
             _toolchain_store: toolchain_store,
             kernels: Default::default(),
+            port_forwarder,
         }
     }
 
@@ -1071,6 +1079,7 @@ impl HeadlessProject {
         let _buffer_store = buffer_store.clone();
         let client = this.read_with(&cx, |this, _| this.session.clone());
         let task = cx.spawn(async move |cx| {
+            let reported_query = query.clone();
             let results = this.update(cx, |this, cx| {
                 project::Search::local(
                     this.fs.clone(),
@@ -1127,7 +1136,9 @@ impl HeadlessProject {
                     peer_id: Some(peer_id),
                     project_id,
                     variant: Some(proto::find_search_candidates_chunk::Variant::Done(
-                        proto::FindSearchCandidatesDone {},
+                        proto::FindSearchCandidatesDone {
+                            pattern_too_complex: reported_query.exceeded_backtrack_limit(),
+                        },
                     )),
                 })
                 .await?;
@@ -0,0 +1,340 @@
+use anyhow::{Context as _, Result};
+use collections::{HashMap, HashSet};
+use gpui::{AppContext as _, AsyncApp, Context, Entity, Task};
+use remote::port_forwarding::{PortTunnel, relay_port_tunnel};
+use rpc::{
+    AnyProtoClient, TypedEnvelope,
+    proto::{self, REMOTE_SERVER_PROJECT_ID},
+};
+use smol::net::TcpStream;
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+    time::Duration,
+};
+use util::ResultExt as _;
+
+/// How often the listening sockets are checked for changes.
+const LISTENING_PORTS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// The state of a listening socket in `/proc/net/tcp`.
+const TCP_LISTEN_STATE: &str = "0A";
+
+/// Reports the TCP ports listened on by processes on this machine to the
+/// client, and relays the connections the client forwards to them.
+pub struct PortForwarder {
+    session: AnyProtoClient,
+    /// The open tunnels, which receive the client's messages for them.
+    tunnels: HashMap<u64, PortTunnel>,
+    _watch_listening_ports: Task<()>,
+}
+
+impl PortForwarder {
+    pub fn init(session: &AnyProtoClient) {
+        session.add_entity_request_handler(Self::handle_open_port_tunnel);
+        session.add_entity_message_handler(Self::handle_port_tunnel_data);
+        session.add_entity_message_handler(Self::handle_port_tunnel_ack);
+        session.add_entity_message_handler(Self::handle_close_port_tunnel);
+    }
+
+    pub fn new(session: AnyProtoClient, cx: &mut Context<Self>) -> Self {
+        let executor = cx.background_executor().clone();
+        let watch_listening_ports = cx.background_spawn({
+            let session = session.clone();
+            async move {
+                let mut last_sockets = Vec::new();
+                loop {
+                    executor.timer(LISTENING_PORTS_POLL_INTERVAL).await;
+                    let sockets = listening_sockets();
+                    if sockets == last_sockets {
+                        continue;
+                    }
+
+                    let inodes = sockets.iter().map(|socket| socket.inode).collect();
+                    let process_names = process_names_by_socket(&inodes);
+                    let ports = sockets
+                        .iter()
+                        .map(|socket| proto::ListeningPort {
+                            port: socket.port as u32,
+                            host: socket.host.to_string(),
+                            process_name: process_names.get(&socket.inode).cloned(),
+                        })
+                        .collect();
+                    session
+                        .send(proto::UpdateListeningPorts {
+                            project_id: REMOTE_SERVER_PROJECT_ID,
+                            ports,
+                        })
+                        .log_err();
+                    last_sockets = sockets;
+                }
+            }
+        });
+
+        Self {
+            session,
+            tunnels: HashMap::default(),
+            _watch_listening_ports: watch_listening_ports,
+        }
+    }
+
+    async fn handle_open_port_tunnel(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::OpenPortTunnel>,
+        mut cx: AsyncApp,
+    ) -> Result<proto::Ack> {
+        let proto::OpenPortTunnel {
+            tunnel_id,
+            host,
+            port,
+            ..
+        } = envelope.payload;
+        let port = u16::try_from(port).context("invalid port")?;
+        let host = connectable_host(&host).with_context(|| format!("invalid host {host:?}"))?;
+        let stream = TcpStream::connect((host, port))
+            .await
+            .with_context(|| format!("connecting to {host}:{port}"))?;
+
+        this.update(&mut cx, |this, cx| {
+            let (tunnel, incoming) = PortTunnel::new();
+            this.tunnels.insert(tunnel_id, tunnel);
+            let session = this.session.clone();
+            cx.spawn(async move |this, cx| {
+                relay_port_tunnel(
+                    REMOTE_SERVER_PROJECT_ID,
+                    tunnel_id,
+                    stream,
+                    incoming,
+                    session,
+                )
+                .await
+                .log_err();
+                this.update(cx, |this, _| this.tunnels.remove(&tunnel_id))
+                    .ok();
+            })
+            .detach();
+        });
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_port_tunnel_data(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::PortTunnelData>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let proto::PortTunnelData {
+            tunnel_id, data, ..
+        } = envelope.payload;
+        this.update(&mut cx, |this, _| {
+            if let Some(tunnel) = this.tunnels.get_mut(&tunnel_id)
+                && tunnel.receive_data(data).log_err().is_none()
+            {
+                this.tunnels.remove(&tunnel_id);
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_port_tunnel_ack(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::PortTunnelAck>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        let tunnel_id = envelope.payload.tunnel_id;
+        this.update(&mut cx, |this, _| {
+            if let Some(tunnel) = this.tunnels.get_mut(&tunnel_id)
+                && tunnel.receive_ack().log_err().is_none()
+            {
+                this.tunnels.remove(&tunnel_id);
+            }
+        });
+        Ok(())
+    }
+
+    async fn handle_close_port_tunnel(
+        this: Entity<Self>,
+        envelope: TypedEnvelope<proto::ClosePortTunnel>,
+        mut cx: AsyncApp,
+    ) -> Result<()> {
+        this.update(&mut cx, |this, _| {
+            this.tunnels.remove(&envelope.payload.tunnel_id);
+        });
+        Ok(())
+    }
+}
+
+/// The address to connect to for a socket bound to `host`. Sockets bound to
+/// every interface are reached over loopback.
+fn connectable_host(host: &str) -> Option<IpAddr> {
+    match host.parse::<IpAddr>().ok()? {
+        IpAddr::V4(address) if address.is_unspecified() => Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        IpAddr::V6(address) if address.is_unspecified() => Some(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+        address => Some(address),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ListeningSocket {
+    host: IpAddr,
+    port: u16,
+    inode: u64,
+}
+
+/// Returns the listening TCP sockets on this machine, one per port, read from
+/// `/proc/net/tcp` and `/proc/net/tcp6`. Platforms without them report none.
+fn listening_sockets() -> Vec<ListeningSocket> {
+    let mut sockets = Vec::new();
+    for table in ["/proc/net/tcp", "/proc/net/tcp6"] {
+        if let Ok(table) = std::fs::read_to_string(table) {
+            sockets.extend(parse_listening_sockets(&table));
+        }
+    }
+    sockets.sort_by_key(|socket| socket.port);
+    sockets.dedup_by_key(|socket| socket.port);
+    sockets
+}
+
+fn parse_listening_sockets(table: &str) -> Vec<ListeningSocket> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if *fields.get(3)? != TCP_LISTEN_STATE {
+                return None;
+            }
+            let (host, port) = fields.get(1)?.split_once(':')?;
+            Some(ListeningSocket {
+                host: parse_proc_address(host)?,
+                port: u16::from_str_radix(port, 16).ok()?,
+                inode: fields.get(9)?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Parses an address from `/proc/net/tcp`, written as hex 32-bit words in
+/// host byte order.
+fn parse_proc_address(hex: &str) -> Option<IpAddr> {
+    let mut bytes = Vec::with_capacity(16);
+    for start in (0..hex.len()).step_by(8) {
+        let word = u32::from_str_radix(hex.get(start..start + 8)?, 16).ok()?;
+        bytes.extend(word.to_ne_bytes());
+    }
+    match bytes.len() {
+        4 => Some(IpAddr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => Some(IpAddr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => None,
+    }
+}
+
+/// Finds the names of the processes owning the given socket inodes by looking
+/// through the open file descriptors in `/proc`. Processes of other users
+/// can't be inspected, so their sockets have no name.
+fn process_names_by_socket(inodes: &HashSet<u64>) -> HashMap<u64, String> {
+    let mut names = HashMap::default();
+    let Ok(processes) = std::fs::read_dir("/proc") else {
+        return names;
+    };
+    for process in processes.flatten() {
+        let process_dir = process.path();
+        let Ok(fds) = std::fs::read_dir(process_dir.join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            let Some(inode) = std::fs::read_link(fd.path())
+                .ok()
+                .and_then(|target| socket_inode(&target))
+            else {
+                continue;
+            };
+            if inodes.contains(&inode)
+                && let Ok(name) = std::fs::read_to_string(process_dir.join("comm"))
+            {
+                names.insert(inode, name.trim().to_string());
+            }
+        }
+        if names.len() == inodes.len() {
+            break;
+        }
+    }
+    names
+}
+
+/// Parses the inode from a file descriptor link like `socket:[41234]`.
+fn socket_inode(target: &Path) -> Option<u64> {
+    target
+        .to_str()?
+        .strip_prefix("socket:[")?
+        .strip_suffix(']')?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The addresses in these tables are written as on little-endian machines.
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn test_parse_listening_sockets() {
+        let table = "\
+  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41234 1 0000000000000000 100 0 0 10 0
+   1: 00000000:1F90 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 41235 1 0000000000000000 100 0 0 10 0
+   2: 0100007F:0BB8 0100007F:D2F0 01 00000000:00000000 00:00000000 00000000  1000        0 41236 1 0000000000000000 20 4 30 10 -1
+";
+        assert_eq!(
+            parse_listening_sockets(table),
+            [
+                ListeningSocket {
+                    host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                    port: 3000,
+                    inode: 41234,
+                },
+                ListeningSocket {
+                    host: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                    port: 8080,
+                    inode: 41235,
+                },
+            ]
+        );
+
+        let table6 = "\
+  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000000000000000000001000000:1388 00000000000000000000000000000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 51234 1 0000000000000000 100 0 0 10 0
+";
+        assert_eq!(
+            parse_listening_sockets(table6),
+            [ListeningSocket {
+                host: IpAddr::V6(Ipv6Addr::LOCALHOST),
+                port: 5000,
+                inode: 51234,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_connectable_host() {
+        assert_eq!(
+            connectable_host("0.0.0.0"),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+        assert_eq!(
+            connectable_host("::"),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(
+            connectable_host("192.168.1.2"),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)))
+        );
+        assert_eq!(connectable_host("localhost"), None);
+    }
+
+    #[test]
+    fn test_socket_inode() {
+        assert_eq!(socket_inode(Path::new("socket:[41234]")), Some(41234));
+        assert_eq!(socket_inode(Path::new("pipe:[41234]")), None);
+        assert_eq!(socket_inode(Path::new("/dev/null")), None);
+    }
+}
@@ -20,6 +20,7 @@ use editor::{
 };
 use extension::ExtensionHostProxy;
 use fs::{FakeFs, Fs};
+use futures::{AsyncReadExt as _, AsyncWriteExt as _};
 use git::{
     Oid,
     repository::{CommitData, GitCommitTemplate, RepoPath, Worktree as GitWorktree},
@@ -50,6 +51,7 @@ use serde_json::json;
 use settings::{Settings, SettingsLocation, SettingsStore, initial_server_settings_content};
 use smol::stream::StreamExt;
 use std::{
+    net::Ipv4Addr,
     path::{Path, PathBuf},
     rc::Rc,
     str::FromStr,
@@ -327,7 +329,9 @@ async fn do_search_and_assert(
             let response = receiver.rx.recv().await.unwrap();
             match response {
                 SearchResult::Buffer { buffer, .. } => break buffer,
-                SearchResult::LimitReached => panic!("incorrect result"),
+                SearchResult::LimitReached | SearchResult::PatternTooComplex => {
+                    panic!("incorrect result")
+                }
                 SearchResult::WaitingForScan | SearchResult::Searching => continue,
             }
         };
@@ -499,6 +503,59 @@ async fn test_remote_project_search(cx: &mut TestAppContext, server_cx: &mut Tes
     .await;
 }
 
+#[gpui::test]
+async fn test_remote_project_search_reports_too_complex_regex(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        path!("/code"),
+        json!({
+            "project1": {
+                "slow.rs": "ab".repeat(100),
+            },
+        }),
+    )
+    .await;
+
+    let (project, _headless) = init_test(&fs, cx, server_cx).await;
+    project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree(path!("/code/project1"), true, cx)
+        })
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    // Only the server reads the file, so it has to report that the regex gave
+    // up on it.
+    let query = SearchQuery::regex(
+        r"(a|b|ab)*(?=c)",
+        false,
+        true,
+        false,
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    let receiver = project.update(cx, |project, cx| project.search(query.clone(), cx));
+    let mut pattern_too_complex = false;
+    while let Ok(response) = receiver.rx.recv().await {
+        match response {
+            SearchResult::Buffer { ranges, .. } => assert!(ranges.is_empty()),
+            SearchResult::PatternTooComplex => pattern_too_complex = true,
+            SearchResult::LimitReached | SearchResult::WaitingForScan | SearchResult::Searching => {
+            }
+        }
+    }
+    assert!(pattern_too_complex);
+    assert!(query.exceeded_backtrack_limit());
+}
+
 #[gpui::test]
 async fn test_remote_project_search_single_cpu(
     cx: &mut TestAppContext,
@@ -4315,6 +4372,54 @@ async fn test_remote_trash_restore(cx: &mut TestAppContext, server_cx: &mut Test
     });
 }
 
+#[gpui::test]
+async fn test_remote_port_forwarding(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let fs = FakeFs::new(server_cx.executor());
+    let (project, _headless_project) = init_test(&fs, cx, server_cx).await;
+    cx.executor().allow_parking();
+
+    // Both ends run on this machine, so the remote port is taken locally too
+    // and the forward has to pick another local port.
+    let echo_listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let remote_port = echo_listener.local_addr().unwrap().port();
+    std::thread::spawn(move || {
+        let (mut stream, _) = echo_listener.accept().unwrap();
+        std::io::copy(&mut stream.try_clone().unwrap(), &mut stream).ok();
+    });
+
+    let port_forward_store =
+        project.read_with(cx, |project, _| project.port_forward_store().unwrap());
+    let local_port = port_forward_store
+        .update(cx, |store, cx| store.forward_port(remote_port, cx))
+        .await
+        .unwrap();
+    assert_ne!(local_port, remote_port);
+    port_forward_store.read_with(cx, |store, _| {
+        assert_eq!(store.forward(remote_port).unwrap().local_port, local_port);
+    });
+
+    // Send many times the tunnel's window, which only gets through if the
+    // acknowledgements of written chunks flow back.
+    let sent = (0..4 * 1024 * 1024)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    let stream = smol::net::TcpStream::connect((Ipv4Addr::LOCALHOST, local_port))
+        .await
+        .unwrap();
+    let mut writer = stream.clone();
+    let mut reader = stream;
+    let write = async {
+        writer.write_all(&sent).await.unwrap();
+    };
+    let read = async {
+        let mut received = vec![0; sent.len()];
+        reader.read_exact(&mut received).await.unwrap();
+        received
+    };
+    let ((), received) = futures::future::join(write, read).await;
+    assert!(received == sent, "echoed bytes differ from the sent ones");
+}
+
 pub async fn init_test(
     server_fs: &Arc<FakeFs>,
     cx: &mut TestAppContext,
@@ -1,4 +1,5 @@
 mod headless_project;
+mod port_forwarder;
 
 #[cfg(test)]
 mod remote_editing_tests;
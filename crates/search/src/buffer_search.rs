@@ -1,14 +1,15 @@
 mod registrar;
 
 use crate::{
-    FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOption,
-    SearchOptions, SearchSource, SelectAllMatches, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    FocusSearch, NextHistoryQuery, PATTERN_TOO_COMPLEX, PreviousHistoryQuery, ReplaceAll,
+    ReplaceNext, SearchOption, SearchOptions, SearchSource, SelectAllMatches, SelectNextMatch,
+    SelectPreviousMatch, ToggleCaseSensitive, ToggleCodeOnly, ToggleCommentsOnly, ToggleRegex,
+    ToggleReplace, ToggleSelection, ToggleStringsOnly, ToggleWholeWord,
     buffer_search::registrar::WithResultsOrExternalQuery,
     search_bar::{
         ActionButtonState, HistoryNavigationDirection, alignment_element,
-        filter_search_results_input, input_base_styles, render_action_button, render_text_input,
-        should_navigate_history,
+        filter_search_results_input, input_base_styles, render_action_button,
+        render_syntax_scope_menu, render_text_input, should_navigate_history,
     },
 };
 use any_vec::AnyVec;
@@ -173,6 +174,7 @@ impl Render for BufferSearchBar {
             selection,
             select_all,
             find_in_results,
+            syntax_scope,
         } = self.supported_options(cx);
 
         self.query_editor.update(cx, |query_editor, cx| {
@@ -259,6 +261,13 @@ impl Render for BufferSearchBar {
                             SearchSource::Buffer,
                             focus_handle.clone(),
                         ))
+                    })
+                    .when(syntax_scope, |div| {
+                        div.child(render_syntax_scope_menu(
+                            "buffer-search-syntax-scope",
+                            self.search_options,
+                            focus_handle.clone(),
+                        ))
                     }),
             );
 
@@ -650,6 +659,21 @@ impl BufferSearchBar {
                 this.toggle_regex(action, window, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(|this, _: &ToggleCommentsOnly, window, cx| {
+            if this.supported_options(cx).syntax_scope {
+                this.toggle_search_option(SearchOptions::COMMENTS_ONLY, window, cx);
+            }
+        }));
+        registrar.register_handler(ForDeployed(|this, _: &ToggleStringsOnly, window, cx| {
+            if this.supported_options(cx).syntax_scope {
+                this.toggle_search_option(SearchOptions::STRINGS_ONLY, window, cx);
+            }
+        }));
+        registrar.register_handler(ForDeployed(|this, _: &ToggleCodeOnly, window, cx| {
+            if this.supported_options(cx).syntax_scope {
+                this.toggle_search_option(SearchOptions::CODE_ONLY, window, cx);
+            }
+        }));
         registrar.register_handler(ForDeployed(|this, action: &ToggleSelection, window, cx| {
             if this.supported_options(cx).selection {
                 this.toggle_selection(action, window, cx);
@@ -1207,6 +1231,13 @@ impl BufferSearchBar {
         cx: &mut Context<Self>,
     ) {
         self.search_options.toggle(search_option);
+        // Matches can only be restricted to a single syntax category.
+        if SearchOptions::SYNTAX_SCOPES.contains(search_option)
+            && self.search_options.contains(search_option)
+        {
+            self.search_options
+                .remove(SearchOptions::SYNTAX_SCOPES.difference(search_option));
+        }
         self.default_options = self.search_options;
         drop(self.update_matches(false, false, window, cx));
         self.adjust_query_regex_language(cx);
@@ -1539,7 +1570,9 @@ impl BufferSearchBar {
                             false,
                             None,
                         ) {
-                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Ok(query) => query
+                                .with_replacement(self.replacement(cx))
+                                .with_syntax_scope(self.search_options.syntax_scope()),
                             Err(e) => {
                                 self.query_error = Some(e.to_string());
                                 self.clear_active_searchable_item_matches(window, cx);
@@ -1558,7 +1591,9 @@ impl BufferSearchBar {
                             false,
                             None,
                         ) {
-                            Ok(query) => query.with_replacement(self.replacement(cx)),
+                            Ok(query) => query
+                                .with_replacement(self.replacement(cx))
+                                .with_syntax_scope(self.search_options.syntax_scope()),
                             Err(e) => {
                                 self.query_error = Some(e.to_string());
                                 self.clear_active_searchable_item_matches(window, cx);
@@ -1574,7 +1609,7 @@ impl BufferSearchBar {
                 let query_text = query.as_str().to_string();
 
                 let matches_with_token =
-                    active_searchable_item.find_matches_with_token(query, window, cx);
+                    active_searchable_item.find_matches_with_token(query.clone(), window, cx);
 
                 let active_searchable_item = active_searchable_item.downgrade();
                 self.pending_search = Some(cx.spawn_in(window, async move |this, cx| {
@@ -1586,6 +1621,9 @@ impl BufferSearchBar {
                         {
                             this.searchable_items_with_matches
                                 .insert(active_searchable_item.downgrade(), (matches, token));
+                            if query.exceeded_backtrack_limit() {
+                                this.query_error = Some(PATTERN_TOO_COMPLEX.to_string());
+                            }
 
                             this.update_match_index(window, cx);
 
@@ -3229,6 +3267,78 @@ mod tests {
         .await;
     }
 
+    #[gpui::test]
+    async fn test_replace_with_backreferences(cx: &mut TestAppContext) {
+        let (editor, search_bar, cx) = init_test(cx);
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_text("foo foo bar bar baz\n", window, cx)
+        });
+
+        run_replacement_test(ReplacementTestParams {
+            editor: &editor,
+            search_bar: &search_bar,
+            cx,
+            search_text: r"\b(\w+) \1\b",
+            search_options: Some(SearchOptions::REGEX),
+            replacement_text: "$1",
+            replace_all: true,
+            expected_text: "foo bar baz\n".to_string(),
+        })
+        .await;
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_text("let width = 80;\nlet height = 24;\n", window, cx)
+        });
+
+        run_replacement_test(ReplacementTestParams {
+            editor: &editor,
+            search_bar: &search_bar,
+            cx,
+            search_text: r"let (?<name>\w+) = (?<value>\d+);",
+            search_options: Some(SearchOptions::REGEX),
+            replacement_text: "const ${name}: i32 = ${value};",
+            replace_all: true,
+            expected_text: "const width: i32 = 80;\nconst height: i32 = 24;\n".to_string(),
+        })
+        .await;
+    }
+
+    #[gpui::test]
+    async fn test_search_stops_on_too_complex_pattern(cx: &mut TestAppContext) {
+        let (editor, search_bar, cx) = init_test(cx);
+
+        editor.update_in(cx, |editor, window, cx| {
+            editor.set_text(format!("{}\nabc\n", "ab".repeat(100)), window, cx)
+        });
+
+        search_bar
+            .update_in(cx, |search_bar, window, cx| {
+                search_bar.search(
+                    r"(a|b|ab)*(?=c)",
+                    Some(SearchOptions::REGEX),
+                    true,
+                    window,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        search_bar.update(cx, |search_bar, _| {
+            assert_eq!(search_bar.query_error.as_deref(), Some(PATTERN_TOO_COMPLEX));
+        });
+
+        search_bar
+            .update_in(cx, |search_bar, window, cx| {
+                search_bar.search("ab+c", Some(SearchOptions::REGEX), true, window, cx)
+            })
+            .await
+            .unwrap();
+        search_bar.update(cx, |search_bar, _| {
+            assert_eq!(search_bar.query_error, None);
+        });
+    }
+
     #[gpui::test]
     async fn test_replace_with_lookaround_in_multibuffer(cx: &mut TestAppContext) {
         let (editor, search_bar, cx) = init_multibuffer_test(cx);
@@ -1,12 +1,13 @@
 use crate::{
     BufferSearchBar, EXCLUDE_PLACEHOLDER, FocusSearch, HighlightKey, INCLUDE_PLACEHOLDER,
-    NextHistoryQuery, PreviousHistoryQuery, REPLACE_PLACEHOLDER, ReplaceAll, ReplaceNext,
-    SearchOption, SearchOptions, SearchSource, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    NextHistoryQuery, PATTERN_TOO_COMPLEX, PreviousHistoryQuery, REPLACE_PLACEHOLDER, ReplaceAll,
+    ReplaceNext, SearchOption, SearchOptions, SearchSource, SelectNextMatch, SelectPreviousMatch,
+    ToggleCaseSensitive, ToggleCodeOnly, ToggleCommentsOnly, ToggleIncludeIgnored, ToggleRegex,
+    ToggleReplace, ToggleStringsOnly, ToggleStructural, ToggleWholeWord,
     buffer_search::Deploy,
     search_bar::{
         ActionButtonState, HistoryNavigationDirection, alignment_element, input_base_styles,
-        render_action_button, render_text_input, should_navigate_history,
+        render_action_button, render_syntax_scope_menu, render_text_input, should_navigate_history,
     },
     text_finder::TextFinder,
 };
@@ -138,6 +139,30 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, window, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, window, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleStructural, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::STRUCTURAL, window, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleCommentsOnly, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::COMMENTS_ONLY, window, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleStringsOnly, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::STRINGS_ONLY, window, cx);
+            },
+        );
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleCodeOnly, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::CODE_ONLY, window, cx);
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
@@ -255,6 +280,9 @@ pub struct ProjectSearch {
     last_search_query_text: Option<String>,
     pub search_id: usize,
     search_state: SearchState,
+    /// Whether the last search gave up on some files because its regex needed
+    /// too much backtracking.
+    pattern_too_complex: bool,
     search_history_cursor: SearchHistoryCursor,
     search_included_history_cursor: SearchHistoryCursor,
     search_excluded_history_cursor: SearchHistoryCursor,
@@ -348,6 +376,7 @@ impl ProjectSearch {
             last_search_query_text: None,
             search_id: 0,
             search_state: SearchState::Idle,
+            pattern_too_complex: false,
             search_history_cursor: Default::default(),
             search_included_history_cursor: Default::default(),
             search_excluded_history_cursor: Default::default(),
@@ -376,6 +405,7 @@ impl ProjectSearch {
                 } else {
                     self.search_state
                 },
+                pattern_too_complex: self.pattern_too_complex,
                 search_history_cursor: self.search_history_cursor.clone(),
                 search_included_history_cursor: self.search_included_history_cursor.clone(),
                 search_excluded_history_cursor: self.search_excluded_history_cursor.clone(),
@@ -467,6 +497,7 @@ impl ProjectSearch {
         self.active_query = Some(query);
         self.match_ranges.clear();
         self.search_state = SearchState::Running(SearchActivity::Searching);
+        self.pattern_too_complex = false;
         self.pending_search = Some(cx.spawn(async move |project_search, cx| {
             project_search
                 .update(cx, |project_search, cx| {
@@ -522,11 +553,13 @@ async fn consume_search_stream(
     let mut matches = pin!(search_results.rx.clone().ready_chunks(1024));
 
     let mut limit_reached = false;
+    let mut pattern_too_complex = false;
     while let Some(results) = matches.next().await {
-        let (buffers_with_ranges, has_reached_limit, search_activity) = cx
+        let (buffers_with_ranges, has_reached_limit, stopped_on_pattern, search_activity) = cx
             .background_executor()
             .spawn(async move {
                 let mut limit_reached = false;
+                let mut pattern_too_complex = false;
                 let mut search_activity = None;
                 let mut buffers_with_ranges = Vec::with_capacity(results.len());
                 for result in results {
@@ -537,6 +570,9 @@ async fn consume_search_stream(
                         project::search::SearchResult::LimitReached => {
                             limit_reached = true;
                         }
+                        project::search::SearchResult::PatternTooComplex => {
+                            pattern_too_complex = true;
+                        }
                         project::search::SearchResult::WaitingForScan => {
                             search_activity = Some(SearchActivity::WaitingForScan);
                         }
@@ -545,10 +581,16 @@ async fn consume_search_stream(
                         }
                     }
                 }
-                (buffers_with_ranges, limit_reached, search_activity)
+                (
+                    buffers_with_ranges,
+                    limit_reached,
+                    pattern_too_complex,
+                    search_activity,
+                )
             })
             .await;
         limit_reached |= has_reached_limit;
+        pattern_too_complex |= stopped_on_pattern;
         if let Some(search_activity) = search_activity {
             project_search
                 .update(cx, |project_search, cx| {
@@ -606,6 +648,7 @@ async fn consume_search_stream(
             } else {
                 SearchState::Completed(SearchCompletion::Results { limit_reached })
             };
+            project_search.pattern_too_complex = pattern_too_complex;
             project_search.pending_search.take();
             cx.notify();
         })
@@ -915,6 +958,18 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
         self.search_options.toggle(option);
+        // Structural and regex searches are distinct modes, as a pattern is
+        // either code or a regular expression.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            } else if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if SearchOptions::SYNTAX_SCOPES.contains(option) {
+                self.search_options
+                    .remove(SearchOptions::SYNTAX_SCOPES.difference(option));
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.entity.read(cx).project.downgrade(),
@@ -2401,6 +2456,16 @@ impl Render for ProjectSearchBar {
                         search.search_options,
                         SearchSource::Project(cx),
                         focus_handle.clone(),
+                    ))
+                    .child(SearchOption::Structural.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
+                    ))
+                    .child(render_syntax_scope_menu(
+                        "project-search-syntax-scope",
+                        search.search_options,
+                        focus_handle.clone(),
                     )),
             );
 
@@ -2611,7 +2676,21 @@ impl Render for ProjectSearchBar {
                     search.search_options,
                     SearchSource::Project(cx),
                     focus_handle,
-                ));
+                ))
+                .child({
+                    let query = search.query_editor.read(cx).text(cx);
+                    IconButton::new("project-search-history", IconName::HistoryRerun)
+                        .shape(IconButtonShape::Square)
+                        .tooltip(Tooltip::text("Search Git History"))
+                        .on_click(move |_, window, cx| {
+                            window.dispatch_action(
+                                Box::new(zed_actions::git::SearchHistory {
+                                    query: Some(query.clone()),
+                                }),
+                                cx,
+                            );
+                        })
+                });
 
             h_flex()
                 .w_full()
@@ -2640,6 +2719,14 @@ impl Render for ProjectSearchBar {
         let query_error_line = search
             .panels_with_errors
             .get(&InputPanel::Query)
+            .map(String::as_str)
+            .or_else(|| {
+                search
+                    .entity
+                    .read(cx)
+                    .pattern_too_complex
+                    .then_some(PATTERN_TOO_COMPLEX)
+            })
             .map(|error| {
                 Label::new(error)
                     .size(LabelSize::Small)
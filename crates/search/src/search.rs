@@ -3,7 +3,7 @@ pub use buffer_search::BufferSearchBar;
 pub use editor::HighlightKey;
 use editor::SearchSettings;
 use gpui::{Action, App, ClickEvent, Entity, FocusHandle, IntoElement, actions};
-use project::search::SearchQuery;
+use project::search::{SearchQuery, SyntaxScope};
 pub use project_search::ProjectSearchView;
 use ui::{IconButtonShape, Tooltip, prelude::*};
 use util::paths::PathMatcher;
@@ -37,6 +37,15 @@ actions!(
         ToggleWholeWord,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles structural search mode, matching code-shaped patterns
+        /// against syntax trees.
+        ToggleStructural,
+        /// Toggles restricting matches to comments.
+        ToggleCommentsOnly,
+        /// Toggles restricting matches to string literals.
+        ToggleStringsOnly,
+        /// Toggles restricting matches to code, skipping comments and strings.
+        ToggleCodeOnly,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -58,15 +67,23 @@ actions!(
 
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
-    pub struct SearchOptions: u8 {
+    pub struct SearchOptions: u16 {
         const NONE = 0;
-        const WHOLE_WORD = 1 << SearchOption::WholeWord as u8;
-        const CASE_SENSITIVE = 1 << SearchOption::CaseSensitive as u8;
-        const INCLUDE_IGNORED = 1 << SearchOption::IncludeIgnored as u8;
-        const REGEX = 1 << SearchOption::Regex as u8;
-        const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
+        const WHOLE_WORD = 1 << SearchOption::WholeWord as u16;
+        const CASE_SENSITIVE = 1 << SearchOption::CaseSensitive as u16;
+        const INCLUDE_IGNORED = 1 << SearchOption::IncludeIgnored as u16;
+        const REGEX = 1 << SearchOption::Regex as u16;
+        const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u16;
         /// If set, reverse direction when finding the active match
-        const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        const BACKWARDS = 1 << SearchOption::Backwards as u16;
+        const STRUCTURAL = 1 << SearchOption::Structural as u16;
+        const COMMENTS_ONLY = 1 << SearchOption::CommentsOnly as u16;
+        const STRINGS_ONLY = 1 << SearchOption::StringsOnly as u16;
+        const CODE_ONLY = 1 << SearchOption::CodeOnly as u16;
+        /// The mutually exclusive options restricting matches to a syntax category.
+        const SYNTAX_SCOPES = Self::COMMENTS_ONLY.bits()
+            | Self::STRINGS_ONLY.bits()
+            | Self::CODE_ONLY.bits();
     }
 }
 
@@ -79,11 +96,16 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    Structural,
+    CommentsOnly,
+    StringsOnly,
+    CodeOnly,
 }
 
 const REPLACE_PLACEHOLDER: &str = "Replace in project…";
 const INCLUDE_PLACEHOLDER: &str = "Include: e.g. src/**/*.rs";
 const EXCLUDE_PLACEHOLDER: &str = "Exclude: e.g. vendor/*, *.lock";
+const PATTERN_TOO_COMPLEX: &str = "search stopped: pattern too complex";
 
 pub enum SearchSource<'a, 'b> {
     Buffer,
@@ -92,7 +114,7 @@ pub enum SearchSource<'a, 'b> {
 
 impl SearchOption {
     pub fn as_options(&self) -> SearchOptions {
-        SearchOptions::from_bits(1 << *self as u8).unwrap()
+        SearchOptions::from_bits(1 << *self as u16).unwrap()
     }
 
     pub fn label(&self) -> &'static str {
@@ -103,6 +125,10 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::Structural => "Match Code Structure",
+            SearchOption::CommentsOnly => "Only in Comments",
+            SearchOption::StringsOnly => "Only in Strings",
+            SearchOption::CodeOnly => "Skip Comments and Strings",
         }
     }
 
@@ -112,6 +138,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => IconName::CaseSensitive,
             SearchOption::IncludeIgnored => IconName::FileIgnored,
             SearchOption::Regex => IconName::Regex,
+            SearchOption::Structural => IconName::ListTree,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +149,10 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Structural => &ToggleStructural,
+            SearchOption::CommentsOnly => &ToggleCommentsOnly,
+            SearchOption::StringsOnly => &ToggleStringsOnly,
+            SearchOption::CodeOnly => &ToggleCodeOnly,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -173,9 +204,32 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
+        options.insert(Self::from_syntax_scope(query.syntax_scope()));
         options
     }
 
+    pub fn from_syntax_scope(syntax_scope: SyntaxScope) -> SearchOptions {
+        match syntax_scope {
+            SyntaxScope::Everywhere => SearchOptions::NONE,
+            SyntaxScope::Comments => SearchOptions::COMMENTS_ONLY,
+            SyntaxScope::Strings => SearchOptions::STRINGS_ONLY,
+            SyntaxScope::Code => SearchOptions::CODE_ONLY,
+        }
+    }
+
+    pub fn syntax_scope(&self) -> SyntaxScope {
+        if self.contains(SearchOptions::COMMENTS_ONLY) {
+            SyntaxScope::Comments
+        } else if self.contains(SearchOptions::STRINGS_ONLY) {
+            SyntaxScope::Strings
+        } else if self.contains(SearchOptions::CODE_ONLY) {
+            SyntaxScope::Code
+        } else {
+            SyntaxScope::Everywhere
+        }
+    }
+
     pub fn from_settings(settings: &SearchSettings) -> SearchOptions {
         let mut options = SearchOptions::NONE;
         options.set(SearchOptions::WHOLE_WORD, settings.whole_word);
@@ -185,9 +239,10 @@ impl SearchOptions {
         options
     }
 
-    /// Build a [`SearchQuery`] from these options, selecting the regex or text
-    /// constructor based on [`SearchOptions::REGEX`]. Inverse of
-    /// [`SearchOptions::from_query`].
+    /// Build a [`SearchQuery`] from these options, selecting the structural,
+    /// regex or text constructor based on [`SearchOptions::STRUCTURAL`] and
+    /// [`SearchOptions::REGEX`], and scoping it with [`SearchOptions::syntax_scope`].
+    /// Inverse of [`SearchOptions::from_query`].
     pub fn build_query(
         &self,
         query: impl ToString,
@@ -196,7 +251,16 @@ impl SearchOptions {
         match_full_paths: bool,
         buffers: Option<Vec<Entity<language::Buffer>>>,
     ) -> anyhow::Result<SearchQuery> {
-        if self.contains(SearchOptions::REGEX) {
+        let query = if self.contains(SearchOptions::STRUCTURAL) {
+            SearchQuery::structural(
+                query,
+                self.contains(SearchOptions::INCLUDE_IGNORED),
+                files_to_include,
+                files_to_exclude,
+                match_full_paths,
+                buffers,
+            )
+        } else if self.contains(SearchOptions::REGEX) {
             SearchQuery::regex(
                 query,
                 self.contains(SearchOptions::WHOLE_WORD),
@@ -219,7 +283,8 @@ impl SearchOptions {
                 match_full_paths,
                 buffers,
             )
-        }
+        }?;
+        Ok(query.with_syntax_scope(self.syntax_scope()))
     }
 }
 
@@ -1,10 +1,12 @@
 use editor::{Editor, EditorElement, EditorStyle, MultiBufferOffset, ToOffset};
-use gpui::{Action, App, Entity, FocusHandle, Hsla, IntoElement, TextStyle};
+use gpui::{Action, Anchor, App, Entity, FocusHandle, Hsla, IntoElement, TextStyle};
 use settings::Settings;
 use theme_settings::ThemeSettings;
-use ui::{IconButton, IconButtonShape};
+use ui::{ContextMenu, IconButton, IconButtonShape, PopoverMenu};
 use ui::{Tooltip, prelude::*};
 
+use crate::{SearchOption, SearchOptions};
+
 pub(super) enum HistoryNavigationDirection {
     Previous,
     Next,
@@ -66,6 +68,53 @@ pub(super) fn render_action_button(
     })
 }
 
+/// Renders a menu for restricting matches to comments, strings or code, which
+/// toggles the corresponding [`SearchOptions`] via their actions.
+pub(super) fn render_syntax_scope_menu(
+    id: &'static str,
+    active: SearchOptions,
+    focus_handle: FocusHandle,
+) -> impl IntoElement {
+    PopoverMenu::new(id)
+        .trigger_with_tooltip(
+            IconButton::new(
+                SharedString::from(format!("{id}-trigger")),
+                IconName::Filter,
+            )
+            .shape(IconButtonShape::Square)
+            .toggle_state(active.intersects(SearchOptions::SYNTAX_SCOPES)),
+            Tooltip::text("Search Within"),
+        )
+        .menu(move |window, cx| {
+            let focus_handle = focus_handle.clone();
+            Some(ContextMenu::build(window, cx, move |menu, _, _| {
+                [
+                    SearchOption::CommentsOnly,
+                    SearchOption::StringsOnly,
+                    SearchOption::CodeOnly,
+                ]
+                .into_iter()
+                .fold(menu.context(focus_handle.clone()), |menu, option| {
+                    let focus_handle = focus_handle.clone();
+                    let action = option.to_toggle_action();
+                    menu.toggleable_entry(
+                        option.label(),
+                        active.contains(option.as_options()),
+                        IconPosition::Start,
+                        Some(action.boxed_clone()),
+                        move |window, cx| {
+                            if !focus_handle.is_focused(window) {
+                                window.focus(&focus_handle, cx);
+                            }
+                            window.dispatch_action(action.boxed_clone(), cx);
+                        },
+                    )
+                })
+            }))
+        })
+        .anchor(Anchor::TopRight)
+}
+
 pub(crate) fn input_base_styles(border_color: Hsla, map: impl FnOnce(Div) -> Div) -> Div {
     h_flex()
         .map(map)
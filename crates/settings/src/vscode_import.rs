@@ -196,12 +196,15 @@ impl VsCodeSettings {
             image_viewer: None,
             markdown_preview: None,
             journal: None,
+            keyboard_macros: None,
             language_models: None,
+            large_file: None,
             line_indicator_format: None,
             log: None,
             node: self.node_binary_settings(),
 
             outline_panel: self.outline_panel_settings_content(),
+            ports_panel: None,
             preview_tabs: self.preview_tabs_settings_content(),
             project: self.project_settings_content(),
             project_panel: self.project_panel_settings_content(),
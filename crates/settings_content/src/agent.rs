@@ -357,6 +357,17 @@ pub struct AgentSettingsContent {
     /// These are populated when choosing "Allow always" from a sandbox
     /// escalation prompt.
     pub sandbox_permissions: Option<SandboxPermissionsContent>,
+
+    /// Shell commands to run at points in the agent's lifecycle, e.g. to
+    /// format files after every edit, block edits to generated files, or log
+    /// every terminal command. Each hook receives a JSON description of the
+    /// event on stdin. Hooks only run in trusted, local workspaces.
+    pub hooks: Option<AgentHooksContent>,
+
+    /// Spending limits for the agent, in tokens and in estimated cost. When
+    /// the next request would exceed a budget, the agent pauses and asks
+    /// whether to continue.
+    pub budgets: Option<AgentBudgetsContent>,
 }
 
 impl AgentSettingsContent {
@@ -531,6 +542,8 @@ pub struct AgentProfileContent {
     pub context_servers: IndexMap<Arc<str>, ContextServerPresetContent>,
     /// The default language model selected when using this profile.
     pub default_model: Option<LanguageModelSelection>,
+    /// A daily budget shared by all threads using this profile.
+    pub budget: Option<AgentBudgetContent>,
 }
 
 #[with_fallible_options]
@@ -957,6 +970,68 @@ pub struct SandboxPermissionsContent {
     pub warn_ntfs_grants: Option<bool>,
 }
 
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHooksContent {
+    /// Hooks that run before a tool is called. A hook can veto the call by
+    /// exiting with status 2 (stderr is sent to the model as the reason), or
+    /// by printing `{"decision": "deny", "reason": "..."}`. Printing
+    /// `{"tool_input": {...}}` replaces the tool's input.
+    /// Default: []
+    pub pre_tool_use: Option<Vec<AgentHookContent>>,
+    /// Hooks that run after a tool call completes. Exiting with status 2 sends
+    /// stderr to the model alongside the tool's result.
+    /// Default: []
+    pub post_tool_use: Option<Vec<AgentHookContent>>,
+    /// Hooks that run whenever the agent's turn ends, including when it fails
+    /// or is cancelled. The payload's `stop_reason` is one of `end_turn`,
+    /// `refusal`, `max_tokens`, `error` or `cancelled`.
+    /// Default: []
+    pub turn_end: Option<Vec<AgentHookContent>>,
+    /// Hooks that run when the first message of a thread is sent.
+    /// Default: []
+    pub thread_start: Option<Vec<AgentHookContent>>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHookContent {
+    /// The shell command to run. It is run from the project's first worktree
+    /// root with the event's JSON on stdin.
+    #[serde(default)]
+    pub command: String,
+    /// Names of the tools this hook applies to (e.g. `edit_file`, `terminal`).
+    /// Only used by `pre_tool_use` and `post_tool_use` hooks; when empty, the
+    /// hook applies to every tool.
+    /// Default: []
+    pub tools: Option<Vec<String>>,
+    /// How long to wait for the command before killing it, in milliseconds.
+    /// Default: 60000
+    pub timeout_ms: Option<u64>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentBudgetsContent {
+    /// The budget for a single thread, across all of its turns.
+    pub per_thread: Option<AgentBudgetContent>,
+    /// The budget for all threads combined, per calendar day.
+    pub per_day: Option<AgentBudgetContent>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentBudgetContent {
+    /// The maximum number of tokens (input, output and cached) to spend.
+    /// Default: null (unlimited)
+    pub max_tokens: Option<u64>,
+    /// The maximum estimated cost to spend, in US dollars. Cost is estimated
+    /// from the model's published pricing. The agent pauses before requests
+    /// to models without known pricing, since their cost can't be tracked.
+    /// Default: null (unlimited)
+    pub max_cost: Option<f64>,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct ToolPermissionsContent {
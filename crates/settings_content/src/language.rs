@@ -514,6 +514,11 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: false
     pub hard_tabs: Option<bool>,
+    /// Whether tabs should expand to align with the tabs on neighboring
+    /// lines, rather than to the next multiple of the tab size.
+    ///
+    /// Default: false
+    pub elastic_tabstops: Option<bool>,
     /// How to soft-wrap long lines of text.
     ///
     /// Default: none
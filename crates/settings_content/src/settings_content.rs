@@ -236,6 +236,9 @@ pub struct SettingsContent {
     /// The settings for the image viewer.
     pub image_viewer: Option<ImageViewerSettingsContent>,
 
+    /// The settings for opening large files.
+    pub large_file: Option<LargeFileSettingsContent>,
+
     /// The settings for the markdown preview.
     pub markdown_preview: Option<MarkdownPreviewSettingsContent>,
 
@@ -255,6 +258,12 @@ pub struct SettingsContent {
 
     pub journal: Option<JournalSettingsContent>,
 
+    /// Named keyboard macros that can be played back with the
+    /// `keyboard_macros::Play` action.
+    ///
+    /// Example: {"keyboard_macros": {"quote_word": [{"action": "editor::SelectToNextWordEnd"}, {"keystroke": "\""}]}}
+    pub keyboard_macros: Option<HashMap<String, Vec<KeyboardMacroStep>>>,
+
     /// A map of log scopes to the desired log level.
     /// Useful for filtering out noisy logs or enabling more verbose logging.
     ///
@@ -267,6 +276,9 @@ pub struct SettingsContent {
 
     pub outline_panel: Option<OutlinePanelSettingsContent>,
 
+    /// Configuration for the ports panel of remote projects.
+    pub ports_panel: Option<PortsPanelSettingsContent>,
+
     pub project_panel: Option<ProjectPanelSettingsContent>,
 
     /// Configuration for Node-related features
@@ -901,6 +913,29 @@ pub struct PanelSettingsContent {
     pub default_width: Option<PixelSetting>,
 }
 
+#[with_fallible_options]
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
+pub struct PortsPanelSettingsContent {
+    /// Whether to show the ports panel button in the status bar of remote
+    /// projects.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the ports panel.
+    ///
+    /// Default: right
+    pub dock: Option<DockPosition>,
+    /// Default width of the ports panel in pixels.
+    ///
+    /// Default: 240
+    pub default_width: Option<PixelSetting>,
+    /// Whether to show a notification offering to forward ports that start
+    /// listening on the remote host.
+    ///
+    /// Default: true
+    pub notify_on_new_ports: Option<bool>,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, MergeFrom, Debug, PartialEq)]
 pub struct FileFinderSettingsContent {
@@ -1250,6 +1285,32 @@ pub struct ImageViewerSettingsContent {
     pub unit: Option<ImageFileSizeUnit>,
 }
 
+/// The settings for opening large files.
+#[with_fallible_options]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, MergeFrom, Default, PartialEq)]
+pub struct LargeFileSettingsContent {
+    /// Whether files above `threshold_mb` open in a read-only large-file view
+    /// instead of an editor.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The file size, in megabytes, above which a file is opened in the
+    /// large-file view.
+    ///
+    /// Default: 256
+    pub threshold_mb: Option<u64>,
+}
+
+/// A single step of a saved keyboard macro.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyboardMacroStep {
+    /// Dispatches the action with the given name, e.g. `"editor::MoveDown"`.
+    Action(String),
+    /// Sends a keystroke to the focused element, e.g. `"a"` or `"ctrl-s"`.
+    Keystroke(String),
+}
+
 #[with_fallible_options]
 #[derive(
     Clone,
@@ -1278,6 +1339,7 @@ pub enum ImageFileSizeUnit {
 pub struct RemoteSettingsContent {
     pub ssh_connections: Option<Vec<SshConnection>>,
     pub wsl_connections: Option<Vec<WslConnection>>,
+    pub kubernetes_connections: Option<Vec<KubernetesConnection>>,
     pub dev_container_connections: Option<Vec<DevContainerConnection>>,
     pub read_ssh_config: Option<bool>,
     pub use_podman: Option<bool>,
@@ -1338,6 +1400,19 @@ pub struct WslConnection {
     pub projects: BTreeSet<RemoteProject>,
 }
 
+#[derive(Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema, MergeFrom, Debug)]
+pub struct KubernetesConnection {
+    /// The kubeconfig context of the pod, or the current context when unset.
+    pub context: Option<String>,
+    /// The namespace of the pod, or the context's namespace when unset.
+    pub namespace: Option<String>,
+    pub pod: String,
+    /// The container to open projects in, or the pod's default container when unset.
+    pub container: Option<String>,
+    #[serde(default)]
+    pub projects: BTreeSet<RemoteProject>,
+}
+
 #[with_fallible_options]
 #[derive(
     Clone, Debug, Default, Serialize, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema,
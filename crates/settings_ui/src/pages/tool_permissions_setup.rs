@@ -80,6 +80,30 @@ const TOOLS: &[ToolInfo] = &[
         description: "Loading agent skill instructions",
         regex_explanation: "Patterns are matched against the absolute path to the skill's SKILL.md file.",
     },
+    ToolInfo {
+        id: "start_debugging",
+        name: "Start Debugging",
+        description: "Starting debug sessions",
+        regex_explanation: "Patterns are matched against the label of the debug scenario being started.",
+    },
+    ToolInfo {
+        id: "set_breakpoint",
+        name: "Set Breakpoint",
+        description: "Setting and removing breakpoints",
+        regex_explanation: "Patterns are matched against the path of the file the breakpoint is in.",
+    },
+    ToolInfo {
+        id: "debugger_control",
+        name: "Debugger Control",
+        description: "Continuing, stepping, pausing and stopping debug sessions",
+        regex_explanation: "Patterns are matched against the action: `continue`, `step over`, `step into`, `step out`, `pause` or `stop`.",
+    },
+    ToolInfo {
+        id: "debugger_inspect",
+        name: "Debugger Inspect",
+        description: "Reading stack frames and variables from debug sessions",
+        regex_explanation: "Patterns are matched against the label of the debug session being inspected.",
+    },
 ];
 
 pub(crate) struct ToolInfo {
@@ -312,6 +336,10 @@ fn get_tool_render_fn(
         "fetch" => render_fetch_tool_config,
         "search_web" => render_web_search_tool_config,
         "skill" => render_skill_tool_config,
+        "start_debugging" => render_start_debugging_tool_config,
+        "set_breakpoint" => render_set_breakpoint_tool_config,
+        "debugger_control" => render_debugger_control_tool_config,
+        "debugger_inspect" => render_debugger_inspect_tool_config,
         _ => render_terminal_tool_config, // fallback
     }
 }
@@ -1390,6 +1418,10 @@ tool_config_page_fn!(render_create_directory_tool_config, "create_directory");
 tool_config_page_fn!(render_fetch_tool_config, "fetch");
 tool_config_page_fn!(render_web_search_tool_config, "search_web");
 tool_config_page_fn!(render_skill_tool_config, "skill");
+tool_config_page_fn!(render_start_debugging_tool_config, "start_debugging");
+tool_config_page_fn!(render_set_breakpoint_tool_config, "set_breakpoint");
+tool_config_page_fn!(render_debugger_control_tool_config, "debugger_control");
+tool_config_page_fn!(render_debugger_inspect_tool_config, "debugger_inspect");
 
 #[cfg(test)]
 mod tests {
@@ -2239,7 +2239,9 @@ impl Sidebar {
     ) -> Option<AnyElement> {
         let remote_icon_per_type = match host? {
             RemoteConnectionOptions::Wsl(_) => IconName::Linux,
-            RemoteConnectionOptions::Docker(_) => IconName::Box,
+            RemoteConnectionOptions::Docker(_) | RemoteConnectionOptions::Kubernetes(_) => {
+                IconName::Box
+            }
             _ => IconName::Server,
         };
 
@@ -269,6 +269,13 @@ pub struct DebugScenario {
     /// A task to run prior to spawning the debuggee.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub build: Option<BuildTaskDefinition>,
+    /// The label of a task to run before the debug session starts. The
+    /// session is not started if the task fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_launch_task: Option<SharedString>,
+    /// The label of a task to run after the debug session ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_debug_task: Option<SharedString>,
     /// The main arguments to be sent to the debug adapter
     #[serde(default, flatten)]
     pub config: serde_json::Value,
@@ -281,10 +288,35 @@ pub struct DebugScenario {
     pub tcp_connection: Option<TcpArgumentsTemplate>,
 }
 
+/// Several debug scenarios that are started together, e.g. a client and the
+/// server it talks to.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub struct DebugCompound {
+    /// Name of the compound
+    pub label: SharedString,
+    /// Labels of the debug scenarios to start, in order
+    pub scenarios: Vec<SharedString>,
+    /// The label of a task to run once before any of the scenarios starts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_launch_task: Option<SharedString>,
+    /// Whether ending one of the sessions stops all the others
+    #[serde(default)]
+    pub stop_all: bool,
+}
+
+/// An entry of a debug task file.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug, JsonSchema)]
+#[serde(untagged)]
+pub enum DebugTaskFileEntry {
+    Compound(DebugCompound),
+    Scenario(DebugScenario),
+}
+
 /// A group of Debug Tasks defined in a JSON file.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(transparent)]
-pub struct DebugTaskFile(pub Vec<DebugScenario>);
+pub struct DebugTaskFile(pub Vec<DebugTaskFileEntry>);
 
 impl DebugTaskFile {
     pub fn generate_json_schema(schemas: &AdapterSchemas) -> serde_json::Value {
@@ -347,6 +379,9 @@ impl DebugTaskFile {
             build_task_value,
         );
 
+        let scenario_schema = Self::scenario_schema(build_task_definition_ref, adapter_conditions);
+        let compound_schema = Self::compound_schema();
+
         let meta_schema = generator
             .settings()
             .meta_schema
@@ -361,47 +396,91 @@ impl DebugTaskFile {
             "allowTrailingCommas": true,
             "type": "array",
             "items": {
-                "type": "object",
-                "required": ["adapter", "label"],
-                // TODO: Uncommenting this will cause json-language-server to provide warnings for
-                // unrecognized properties. It should be enabled if/when there's an adapter JSON
-                // schema that's comprehensive. In order to not get warnings for the other schemas,
-                // `additionalProperties` or `unevaluatedProperties` (to handle "allOf" etc style
-                // schema combinations) could be set to `true` for that schema.
-                //
-                // "unevaluatedProperties": false,
-                "properties": {
-                    "adapter": {
-                        "type": "string",
-                        "description": "The name of the debug adapter"
-                    },
-                    "label": {
-                        "type": "string",
-                        "description": "The name of the debug configuration"
-                    },
-                    "build": build_task_definition_ref,
-                    "tcp_connection": {
-                        "type": "object",
-                        "description": "Optional TCP connection information for connecting to an already running debug adapter",
-                        "properties": {
-                            "port": {
-                                "type": "integer",
-                                "description": "The port that the debug adapter is listening on (default: auto-find open port)"
-                            },
-                            "host": {
-                                "type": "string",
-                                "description": "The host that the debug adapter is listening to, as an IPv4 or IPv6 address (default: 127.0.0.1)"
-                            },
-                            "timeout": {
-                                "type": "integer",
-                                "description": "The max amount of time in milliseconds to connect to a tcp DAP before returning an error (default: 2000ms)"
-                            }
+                "anyOf": [scenario_schema, compound_schema],
+            },
+            "$defs": generator.take_definitions(true),
+        })
+    }
+
+    fn scenario_schema(
+        build_task_definition_ref: serde_json::Value,
+        adapter_conditions: Vec<serde_json::Value>,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["adapter", "label"],
+            // TODO: Uncommenting this will cause json-language-server to provide warnings for
+            // unrecognized properties. It should be enabled if/when there's an adapter JSON
+            // schema that's comprehensive. In order to not get warnings for the other schemas,
+            // `additionalProperties` or `unevaluatedProperties` (to handle "allOf" etc style
+            // schema combinations) could be set to `true` for that schema.
+            //
+            // "unevaluatedProperties": false,
+            "properties": {
+                "adapter": {
+                    "type": "string",
+                    "description": "The name of the debug adapter"
+                },
+                "label": {
+                    "type": "string",
+                    "description": "The name of the debug configuration"
+                },
+                "build": build_task_definition_ref,
+                "pre_launch_task": {
+                    "type": "string",
+                    "description": "The label of a task to run before the debug session starts. The session is not started if the task fails"
+                },
+                "post_debug_task": {
+                    "type": "string",
+                    "description": "The label of a task to run after the debug session ends"
+                },
+                "tcp_connection": {
+                    "type": "object",
+                    "description": "Optional TCP connection information for connecting to an already running debug adapter",
+                    "properties": {
+                        "port": {
+                            "type": "integer",
+                            "description": "The port that the debug adapter is listening on (default: auto-find open port)"
+                        },
+                        "host": {
+                            "type": "string",
+                            "description": "The host that the debug adapter is listening to, as an IPv4 or IPv6 address (default: 127.0.0.1)"
+                        },
+                        "timeout": {
+                            "type": "integer",
+                            "description": "The max amount of time in milliseconds to connect to a tcp DAP before returning an error (default: 2000ms)"
                         }
                     }
-                },
-                "allOf": adapter_conditions
+                }
             },
-            "$defs": generator.take_definitions(true),
+            "allOf": adapter_conditions
+        })
+    }
+
+    fn compound_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["label", "scenarios"],
+            "additionalProperties": false,
+            "properties": {
+                "label": {
+                    "type": "string",
+                    "description": "The name of the compound"
+                },
+                "scenarios": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Labels of the debug scenarios to start together, in order"
+                },
+                "pre_launch_task": {
+                    "type": "string",
+                    "description": "The label of a task to run once before any of the scenarios starts"
+                },
+                "stop_all": {
+                    "type": "boolean",
+                    "description": "Whether ending one of the sessions stops all the others (default: false)"
+                }
+            }
         })
     }
 }
@@ -531,4 +610,71 @@ mod tests {
             _ => panic!("Expected Template variant"),
         }
     }
+
+    #[test]
+    fn test_debug_task_file_with_compound() {
+        use crate::{DebugCompound, DebugTaskFile, DebugTaskFileEntry};
+
+        let json = r#"[
+            {
+                "label": "Server",
+                "adapter": "Debugpy",
+                "program": "server.py",
+                "pre_launch_task": "start database",
+                "post_debug_task": "stop database"
+            },
+            {
+                "label": "Client and server",
+                "scenarios": ["Server", "Client"],
+                "stop_all": true
+            }
+        ]"#;
+
+        let deserialized: DebugTaskFile = serde_json::from_str(json).unwrap();
+        let [
+            DebugTaskFileEntry::Scenario(scenario),
+            DebugTaskFileEntry::Compound(compound),
+        ] = deserialized.0.as_slice()
+        else {
+            panic!("unexpected entries: {deserialized:?}");
+        };
+        assert_eq!(Some("start database"), scenario.pre_launch_task.as_deref());
+        assert_eq!(Some("stop database"), scenario.post_debug_task.as_deref());
+        assert_eq!(json!({ "program": "server.py" }), scenario.config);
+        assert_eq!(
+            &DebugCompound {
+                label: "Client and server".into(),
+                scenarios: vec!["Server".into(), "Client".into()],
+                pre_launch_task: None,
+                stop_all: true,
+            },
+            compound
+        );
+    }
+
+    #[test]
+    fn test_scenario_with_scenarios_field_is_not_a_compound() {
+        use crate::{DebugTaskFile, DebugTaskFileEntry};
+
+        // Entries are untagged, so an adapter config that happens to have a
+        // `scenarios` field must not be mistaken for a compound.
+        let json = r#"[
+            {
+                "label": "Test runner",
+                "adapter": "JavaScript",
+                "request": "launch",
+                "scenarios": ["unit", "integration"]
+            }
+        ]"#;
+
+        let deserialized: DebugTaskFile = serde_json::from_str(json).unwrap();
+        let [DebugTaskFileEntry::Scenario(scenario)] = deserialized.0.as_slice() else {
+            panic!("unexpected entries: {deserialized:?}");
+        };
+        assert_eq!("JavaScript", scenario.adapter.as_ref());
+        assert_eq!(
+            json!({ "request": "launch", "scenarios": ["unit", "integration"] }),
+            scenario.config
+        );
+    }
 }
@@ -3,10 +3,10 @@ use serde::Deserialize;
 use util::ResultExt as _;
 
 use crate::{
-    DebugScenario, DebugTaskFile, EnvVariableReplacer, TcpArgumentsTemplate, VariableName,
+    DebugCompound, DebugScenario, DebugTaskFile, DebugTaskFileEntry, EnvVariableReplacer,
+    TcpArgumentsTemplate, VariableName,
 };
 
-// TODO support preLaunchTask linkage with other tasks
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 struct VsCodeDebugTaskDefinition {
@@ -14,6 +14,10 @@ struct VsCodeDebugTaskDefinition {
     name: String,
     #[serde(default)]
     port: Option<u16>,
+    #[serde(default)]
+    pre_launch_task: Option<String>,
+    #[serde(default)]
+    post_debug_task: Option<String>,
     #[serde(flatten)]
     other_attributes: serde_json::Value,
 }
@@ -34,6 +38,12 @@ impl VsCodeDebugTaskDefinition {
         let definition = DebugScenario {
             label: label.into(),
             build: None,
+            pre_launch_task: self
+                .pre_launch_task
+                .map(|task| replacer.replace(&task).into()),
+            post_debug_task: self
+                .post_debug_task
+                .map(|task| replacer.replace(&task).into()),
             adapter: adapter.into(),
             tcp_connection: self.port.map(|port| TcpArgumentsTemplate {
                 port: Some(port),
@@ -46,12 +56,49 @@ impl VsCodeDebugTaskDefinition {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct VsCodeDebugCompound {
+    name: String,
+    /// Either configuration names, or `{ "name": ..., "folder": ... }` objects
+    /// referring to configurations of other workspace folders.
+    configurations: Vec<serde_json::Value>,
+    #[serde(default)]
+    pre_launch_task: Option<String>,
+    #[serde(default)]
+    stop_all: bool,
+}
+
+impl VsCodeDebugCompound {
+    fn into_zed(self, replacer: &EnvVariableReplacer) -> DebugCompound {
+        DebugCompound {
+            label: replacer.replace(&self.name).into(),
+            scenarios: self
+                .configurations
+                .iter()
+                .filter_map(|configuration| {
+                    configuration
+                        .as_str()
+                        .or_else(|| configuration.get("name")?.as_str())
+                })
+                .map(|name| replacer.replace(name).into())
+                .collect(),
+            pre_launch_task: self
+                .pre_launch_task
+                .map(|task| replacer.replace(&task).into()),
+            stop_all: self.stop_all,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct VsCodeDebugTaskFile {
     #[serde(default)]
     version: Option<String>,
     configurations: Vec<VsCodeDebugTaskDefinition>,
+    #[serde(default)]
+    compounds: Vec<VsCodeDebugCompound>,
 }
 
 impl TryFrom<VsCodeDebugTaskFile> for DebugTaskFile {
@@ -77,6 +124,12 @@ impl TryFrom<VsCodeDebugTaskFile> for DebugTaskFile {
             .configurations
             .into_iter()
             .filter_map(|config| config.try_to_zed(&replacer).log_err())
+            .map(DebugTaskFileEntry::Scenario)
+            .chain(
+                file.compounds
+                    .into_iter()
+                    .map(|compound| DebugTaskFileEntry::Compound(compound.into_zed(&replacer))),
+            )
             .collect::<Vec<_>>();
         Ok(DebugTaskFile(templates))
     }
@@ -100,7 +153,7 @@ fn task_type_to_adapter_name(task_type: &str) -> String {
 mod tests {
     use serde_json::json;
 
-    use crate::{DebugScenario, DebugTaskFile, VariableName};
+    use crate::{DebugCompound, DebugScenario, DebugTaskFile, DebugTaskFileEntry, VariableName};
 
     use super::VsCodeDebugTaskFile;
 
@@ -132,7 +185,7 @@ mod tests {
         let zed = DebugTaskFile::try_from(parsed).expect("converting to Zed debug templates");
         pretty_assertions::assert_eq!(
             zed,
-            DebugTaskFile(vec![DebugScenario {
+            DebugTaskFile(vec![DebugTaskFileEntry::Scenario(DebugScenario {
                 label: "Debug my JS app".into(),
                 adapter: "JavaScript".into(),
                 config: json!({
@@ -152,8 +205,10 @@ mod tests {
                     "port": 17,
                 }),
                 tcp_connection: None,
-                build: None
-            }])
+                build: None,
+                pre_launch_task: None,
+                post_debug_task: None,
+            })])
         );
     }
 
@@ -179,7 +234,7 @@ mod tests {
         let expected_placeholder = format!("${{{}}}", VariableName::PickProcessId);
         pretty_assertions::assert_eq!(
             zed,
-            DebugTaskFile(vec![DebugScenario {
+            DebugTaskFile(vec![DebugTaskFileEntry::Scenario(DebugScenario {
                 label: "Attach to Process".into(),
                 adapter: "CodeLLDB".into(),
                 config: json!({
@@ -187,8 +242,67 @@ mod tests {
                     "processId": expected_placeholder,
                 }),
                 tcp_connection: None,
-                build: None
-            }])
+                build: None,
+                pre_launch_task: None,
+                post_debug_task: None,
+            })])
+        );
+    }
+
+    #[test]
+    fn test_pre_launch_tasks_and_compounds() {
+        let raw = r#"
+            {
+                "version": "0.2.0",
+                "configurations": [
+                    {
+                        "name": "Server",
+                        "request": "launch",
+                        "type": "debugpy",
+                        "program": "${workspaceFolder}/server.py",
+                        "preLaunchTask": "start database",
+                        "postDebugTask": "stop database"
+                    },
+                    {
+                        "name": "Client",
+                        "request": "launch",
+                        "type": "node",
+                        "program": "${workspaceFolder}/client.js"
+                    }
+                ],
+                "compounds": [
+                    {
+                        "name": "Client + Server",
+                        "configurations": ["Server", { "name": "Client", "folder": "web" }],
+                        "preLaunchTask": "build",
+                        "stopAll": true
+                    }
+                ]
+            }
+        "#;
+        let parsed: VsCodeDebugTaskFile =
+            serde_json_lenient::from_str(raw).expect("deserializing launch.json");
+        let zed = DebugTaskFile::try_from(parsed).expect("converting to Zed debug templates");
+
+        let [
+            DebugTaskFileEntry::Scenario(server),
+            DebugTaskFileEntry::Scenario(client),
+            DebugTaskFileEntry::Compound(compound),
+        ] = zed.0.as_slice()
+        else {
+            panic!("unexpected debug task file entries: {zed:?}");
+        };
+        assert_eq!(server.pre_launch_task.as_deref(), Some("start database"));
+        assert_eq!(server.post_debug_task.as_deref(), Some("stop database"));
+        assert_eq!(client.pre_launch_task, None);
+        pretty_assertions::assert_eq!(
+            compound,
+            &DebugCompound {
+                label: "Client + Server".into(),
+                scenarios: vec!["Server".into(), "Client".into()],
+                pre_launch_task: Some("build".into()),
+                stop_all: true,
+            }
         );
     }
 }
@@ -37,9 +37,9 @@ use windows::Win32::{Foundation::HANDLE, System::Threading::GetProcessId};
 
 use crate::{
     Cell, Color, Content, Cursor, CursorShape, GridLinesChange, HoveredWord, Hyperlink,
-    HyperlinkData, IndexedCell, Modes, Point, PtyEvent, Range, RenderableCells, Scroll, Search,
-    Selection, SelectionRange, SelectionSide, SelectionType, TerminalBackendEvent, TerminalBounds,
-    ViMotion,
+    HyperlinkData, IndexedCell, Modes, NamedColor, Point, PtyEvent, Range, RenderableCells, Scroll,
+    Search, Selection, SelectionRange, SelectionSide, SelectionType, TerminalBackendEvent,
+    TerminalBounds, ViMotion,
     pty_info::ProcessIdGetter,
     terminal_settings::{AlternateScroll, CursorShape as SettingsCursorShape},
 };
@@ -1042,6 +1042,141 @@ fn row_to_string(row: &Row<AlacCell>) -> String {
         .collect::<String>()
 }
 
+/// The screen of a terminal, with the style of each line encoded as SGR escape
+/// sequences so that another terminal can draw it again.
+pub(super) struct StyledScreen {
+    pub(super) columns: usize,
+    pub(super) rows: usize,
+    pub(super) history_size: usize,
+    pub(super) lines: Vec<String>,
+    pub(super) cursor_row: usize,
+    pub(super) cursor_column: usize,
+    pub(super) cursor_visible: bool,
+}
+
+pub(super) fn styled_screen(term: &Term<ZedListener>) -> StyledScreen {
+    let grid = term.grid();
+    let cursor = grid.cursor.point;
+    StyledScreen {
+        columns: grid.columns(),
+        rows: grid.screen_lines(),
+        history_size: grid.history_size(),
+        lines: (0..grid.screen_lines())
+            .map(|line| styled_row(&grid[Line(line as i32)]))
+            .collect(),
+        cursor_row: cursor.line.0.max(0) as usize,
+        cursor_column: cursor.column.0,
+        cursor_visible: term.mode().contains(TermMode::SHOW_CURSOR),
+    }
+}
+
+/// Returns the newest `count` lines of the scrollback, oldest first.
+pub(super) fn styled_history_lines(term: &Term<ZedListener>, count: usize) -> Vec<String> {
+    let grid = term.grid();
+    let count = count.min(grid.history_size()) as i32;
+    (-count..0)
+        .map(|line| styled_row(&grid[Line(line)]))
+        .collect()
+}
+
+const STYLE_FLAGS: Flags = Flags::BOLD
+    .union(Flags::DIM)
+    .union(Flags::ITALIC)
+    .union(Flags::UNDERLINE)
+    .union(Flags::DOUBLE_UNDERLINE)
+    .union(Flags::UNDERCURL)
+    .union(Flags::INVERSE)
+    .union(Flags::HIDDEN)
+    .union(Flags::STRIKEOUT);
+
+fn styled_row(row: &Row<AlacCell>) -> String {
+    let cells = &row[..Column(row.len())];
+    let default_style = (
+        Color::Named(NamedColor::Foreground),
+        Color::Named(NamedColor::Background),
+        Flags::empty(),
+    );
+    let end = cells
+        .iter()
+        .rposition(|cell| {
+            cell.c != ' '
+                || cell.bg != default_style.1
+                || cell.flags.intersects(Flags::INVERSE | Flags::UNDERLINE)
+        })
+        .map_or(0, |last| last + 1);
+
+    let mut line = String::new();
+    let mut current_style = default_style;
+    for cell in &cells[..end] {
+        if cell
+            .flags
+            .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+        {
+            continue;
+        }
+        let style = (cell.fg, cell.bg, cell.flags & STYLE_FLAGS);
+        if style != current_style {
+            line.push_str(&sgr_sequence(style.0, style.1, style.2));
+            current_style = style;
+        }
+        line.push(cell.c);
+        if let Some(zerowidth) = cell.zerowidth() {
+            line.extend(zerowidth);
+        }
+    }
+    if current_style != default_style {
+        line.push_str("\x1b[0m");
+    }
+    line
+}
+
+fn sgr_sequence(foreground: Color, background: Color, flags: Flags) -> String {
+    let mut sequence = String::from("\x1b[0");
+    for (flag, parameter) in [
+        (Flags::BOLD, "1"),
+        (Flags::DIM, "2"),
+        (Flags::ITALIC, "3"),
+        (Flags::UNDERLINE, "4"),
+        (Flags::DOUBLE_UNDERLINE, "21"),
+        (Flags::UNDERCURL, "4:3"),
+        (Flags::INVERSE, "7"),
+        (Flags::HIDDEN, "8"),
+        (Flags::STRIKEOUT, "9"),
+    ] {
+        if flags.contains(flag) {
+            sequence.push(';');
+            sequence.push_str(parameter);
+        }
+    }
+    for (color, is_foreground) in [(foreground, true), (background, false)] {
+        if let Some(parameter) = sgr_color(color, is_foreground) {
+            sequence.push(';');
+            sequence.push_str(&parameter);
+        }
+    }
+    sequence.push('m');
+    sequence
+}
+
+/// Colors of the theme, like the default foreground, are left to the terminal
+/// drawing the line.
+fn sgr_color(color: Color, is_foreground: bool) -> Option<String> {
+    let (normal, bright, extended) = if is_foreground {
+        (30, 90, 38)
+    } else {
+        (40, 100, 48)
+    };
+    match color {
+        Color::Named(named) => match named as usize {
+            index @ 0..8 => Some((normal + index).to_string()),
+            index @ 8..16 => Some((bright + index - 8).to_string()),
+            _ => None,
+        },
+        Color::Indexed(index) => Some(format!("{extended};5;{index}")),
+        Color::Spec(rgb) => Some(format!("{extended};2;{};{};{}", rgb.r, rgb.g, rgb.b)),
+    }
+}
+
 fn process_line(line: String) -> Option<String> {
     let trimmed = line.trim_end().to_string();
     if !trimmed.is_empty() {
@@ -0,0 +1,315 @@
+//! Mirroring a terminal in a display-only terminal on another machine, by
+//! sending the lines of its grid that changed since the previous update.
+
+use futures::channel::mpsc::UnboundedSender;
+use gpui::Context;
+
+use crate::{
+    Terminal, TerminalBounds,
+    alacritty::{resize, styled_history_lines, styled_screen},
+};
+
+/// The changes to a terminal's grid since the previous update sent to its
+/// mirrors.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GridUpdate {
+    /// Whether the mirror should clear its screen and scrollback first.
+    pub reset: bool,
+    pub columns: usize,
+    pub rows: usize,
+    /// Lines that scrolled off the top of the screen, oldest first.
+    pub scrolled_lines: Vec<String>,
+    /// The rows of the screen that changed once the scrolled lines are gone,
+    /// with their content.
+    pub changed_rows: Vec<(usize, String)>,
+    pub cursor_row: usize,
+    pub cursor_column: usize,
+    pub cursor_visible: bool,
+}
+
+/// What the mirrors of a terminal have been sent so far.
+#[derive(Default)]
+pub struct GridSyncState {
+    columns: usize,
+    rows: usize,
+    history_size: usize,
+    screen: Vec<String>,
+    /// The newest lines of the scrollback, to tell how far the screen scrolled
+    /// once the scrollback is full and stops growing.
+    recent_history: Vec<String>,
+    cursor: Option<(usize, usize, bool)>,
+}
+
+/// Set on terminals that mirror the grid of a terminal on another machine.
+#[derive(Default)]
+pub(crate) struct GridMirror {
+    columns: usize,
+    rows: usize,
+    input_tx: Option<UnboundedSender<Vec<u8>>>,
+}
+
+impl GridMirror {
+    /// The grid of a mirror keeps the size of the mirrored terminal, whatever
+    /// the size of the view showing it.
+    pub(crate) fn bounds(&self, mut bounds: TerminalBounds) -> TerminalBounds {
+        bounds.bounds.size.width = bounds.cell_width * self.columns.max(1) as f32;
+        bounds.bounds.size.height = bounds.line_height * self.rows.max(1) as f32;
+        bounds
+    }
+
+    pub(crate) fn send_input(&self, input: &[u8]) {
+        if let Some(input_tx) = &self.input_tx {
+            input_tx.unbounded_send(input.to_vec()).ok();
+        }
+    }
+}
+
+impl Terminal {
+    /// Returns the changes to send to the mirrors of this terminal, or `None`
+    /// if nothing changed since `state` was last updated.
+    pub fn grid_update(
+        &self,
+        state: &mut GridSyncState,
+        max_scrollback_lines: usize,
+    ) -> Option<GridUpdate> {
+        let term = self.term.lock_unfair();
+        let screen = styled_screen(&term);
+        let recent_history = styled_history_lines(&term, screen.rows);
+        let reset = state.cursor.is_none()
+            || state.columns != screen.columns
+            || state.rows != screen.rows
+            || screen.history_size < state.history_size;
+
+        let (scrolled_lines, previous_screen) = if reset {
+            (
+                styled_history_lines(&term, max_scrollback_lines),
+                vec![String::new(); screen.rows],
+            )
+        } else {
+            let scrolled_count = if screen.history_size > state.history_size {
+                screen.history_size - state.history_size
+            } else {
+                scroll_distance(&state.recent_history, &recent_history)
+            };
+            let scrolled_lines =
+                styled_history_lines(&term, scrolled_count.min(max_scrollback_lines));
+            let mut previous_screen = state
+                .screen
+                .iter()
+                .skip(scrolled_lines.len())
+                .cloned()
+                .collect::<Vec<_>>();
+            previous_screen.resize(screen.rows, String::new());
+            (scrolled_lines, previous_screen)
+        };
+        drop(term);
+
+        let changed_rows = screen
+            .lines
+            .iter()
+            .zip(&previous_screen)
+            .enumerate()
+            .filter(|(_, (line, previous_line))| line != previous_line)
+            .map(|(row, (line, _))| (row, line.clone()))
+            .collect::<Vec<_>>();
+        let cursor = (
+            screen.cursor_row,
+            screen.cursor_column,
+            screen.cursor_visible,
+        );
+        if !reset
+            && scrolled_lines.is_empty()
+            && changed_rows.is_empty()
+            && state.cursor == Some(cursor)
+        {
+            return None;
+        }
+
+        *state = GridSyncState {
+            columns: screen.columns,
+            rows: screen.rows,
+            history_size: screen.history_size,
+            screen: screen.lines,
+            recent_history,
+            cursor: Some(cursor),
+        };
+        Some(GridUpdate {
+            reset,
+            columns: screen.columns,
+            rows: screen.rows,
+            scrolled_lines,
+            changed_rows,
+            cursor_row: screen.cursor_row,
+            cursor_column: screen.cursor_column,
+            cursor_visible: screen.cursor_visible,
+        })
+    }
+
+    /// Draws the changes to a mirrored terminal's grid in this display-only
+    /// terminal.
+    pub fn apply_grid_update(&mut self, update: &GridUpdate, cx: &mut Context<Self>) {
+        let mirror = self.mirror.get_or_insert_default();
+        mirror.columns = update.columns;
+        mirror.rows = update.rows;
+        let bounds = mirror.bounds(self.last_content.terminal_bounds);
+        if bounds != self.last_content.terminal_bounds {
+            self.last_content.terminal_bounds = bounds;
+            resize(&mut self.term.lock(), bounds);
+        }
+
+        let mut output = String::new();
+        if update.reset {
+            output.push_str("\x1b[0m\x1b[H\x1b[2J\x1b[3J");
+        }
+        // Each scrolled line is drawn on the top row, then scrolled into the
+        // scrollback.
+        for line in &update.scrolled_lines {
+            output.push_str("\x1b[1;1H\x1b[2K");
+            output.push_str(line);
+            output.push_str("\x1b[0m\x1b[S");
+        }
+        for (row, line) in &update.changed_rows {
+            output.push_str(&format!("\x1b[{};1H\x1b[2K", row + 1));
+            output.push_str(line);
+            output.push_str("\x1b[0m");
+        }
+        output.push_str(&format!(
+            "\x1b[{};{}H\x1b[?25{}",
+            update.cursor_row + 1,
+            update.cursor_column + 1,
+            if update.cursor_visible { 'h' } else { 'l' }
+        ));
+        self.write_output(output.as_bytes(), cx);
+    }
+
+    /// Makes this display-only terminal forward what is typed in it to the
+    /// terminal it mirrors, or stop doing so when `input_tx` is `None`.
+    pub fn set_mirror_input(&mut self, input_tx: Option<UnboundedSender<Vec<u8>>>) {
+        self.mirror.get_or_insert_default().input_tx = input_tx;
+    }
+
+    pub fn is_mirror(&self) -> bool {
+        self.mirror.is_some()
+    }
+}
+
+/// Returns how many lines were added to a full scrollback, by finding how far
+/// its newest lines moved up.
+fn scroll_distance(previous: &[String], current: &[String]) -> usize {
+    if previous.len() != current.len() {
+        return current.len();
+    }
+    (0..=current.len())
+        .find(|&distance| previous[distance..] == current[..current.len() - distance])
+        .unwrap_or(current.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use gpui::{Bounds, Entity, TestAppContext, point, px, size};
+    use util::paths::PathStyle;
+
+    use super::*;
+    use crate::{
+        TerminalBuilder,
+        terminal_settings::{AlternateScroll, CursorShape},
+    };
+
+    fn display_only_terminal(bounds: TerminalBounds, cx: &mut TestAppContext) -> Entity<Terminal> {
+        cx.new(|cx| {
+            TerminalBuilder::new_display_only_with_bounds(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+                bounds,
+            )
+            .subscribe(cx)
+        })
+    }
+
+    fn sync(
+        terminal: &Entity<Terminal>,
+        mirror: &Entity<Terminal>,
+        state: &mut GridSyncState,
+        cx: &mut TestAppContext,
+    ) -> Option<GridUpdate> {
+        let update = terminal.read_with(cx, |terminal, _| terminal.grid_update(state, 1000))?;
+        mirror.update(cx, |mirror, cx| mirror.apply_grid_update(&update, cx));
+        Some(update)
+    }
+
+    fn text(terminal: &Entity<Terminal>, cx: &mut TestAppContext) -> String {
+        terminal.read_with(cx, |terminal, _| {
+            terminal
+                .get_content()
+                .lines()
+                .map(str::trim_end)
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim_end()
+                .to_string()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_mirror_follows_terminal_output(cx: &mut TestAppContext) {
+        let bounds = TerminalBounds::new(
+            px(10.),
+            px(10.),
+            Bounds::new(point(px(0.), px(0.)), size(px(200.), px(40.))),
+        );
+        let terminal = display_only_terminal(bounds, cx);
+        let mirror = display_only_terminal(TerminalBounds::default(), cx);
+        let mut state = GridSyncState::default();
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(b"one\n\x1b[1;31mtwo\x1b[0m\nthree", cx);
+        });
+        let update = sync(&terminal, &mirror, &mut state, cx).unwrap();
+        assert!(update.reset);
+        assert_eq!((update.columns, update.rows), (20, 4));
+        assert_eq!(text(&mirror, cx), text(&terminal, cx));
+        assert_eq!(sync(&terminal, &mirror, &mut state, cx), None);
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(b"\nfour\nfive\nsix\nseven", cx);
+        });
+        let update = sync(&terminal, &mirror, &mut state, cx).unwrap();
+        assert!(!update.reset);
+        assert_eq!(update.scrolled_lines.len(), 3);
+        assert_eq!(text(&mirror, cx), text(&terminal, cx));
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(b"\x1b[2;1H\x1b[2Kchanged", cx);
+        });
+        let update = sync(&terminal, &mirror, &mut state, cx).unwrap();
+        assert!(update.scrolled_lines.is_empty());
+        assert_eq!(update.changed_rows, vec![(1, "changed".to_string())]);
+        assert_eq!(text(&mirror, cx), text(&terminal, cx));
+    }
+
+    #[test]
+    fn test_scroll_distance() {
+        let lines = |lines: &[&str]| {
+            lines
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            scroll_distance(&lines(&["a", "b", "c"]), &lines(&["a", "b", "c"])),
+            0
+        );
+        assert_eq!(
+            scroll_distance(&lines(&["a", "b", "c"]), &lines(&["c", "d", "e"])),
+            2
+        );
+        assert_eq!(
+            scroll_distance(&lines(&["a", "b", "c"]), &lines(&["x", "y", "z"])),
+            3
+        );
+    }
+}
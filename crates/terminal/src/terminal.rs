@@ -1,6 +1,7 @@
 mod mappings;
 
 mod alacritty;
+mod mirror;
 mod pty_info;
 pub mod terminal_settings;
 
@@ -75,6 +76,8 @@ use crate::alacritty::{
 };
 use crate::mappings::colors::to_vte_rgb;
 use crate::mappings::keys::to_esc_str;
+use crate::mirror::GridMirror;
+pub use crate::mirror::{GridSyncState, GridUpdate};
 
 /// Process-wide flag set by headless hosts (e.g. the eval CLI) that have no
 /// controlling TTY. In such sandboxes PTY allocation and acquiring a
@@ -1023,6 +1026,7 @@ impl TerminalBuilder {
             path_style,
             cwd_history: Vec::new(),
             pending_cwd_boundary: None,
+            mirror: None,
             #[cfg(any(test, feature = "test-support"))]
             input_log: Vec::new(),
             #[cfg(test)]
@@ -1312,6 +1316,7 @@ impl TerminalBuilder {
                         .unwrap_or_default()
                 },
                 pending_cwd_boundary: None,
+                mirror: None,
                 #[cfg(any(test, feature = "test-support"))]
                 input_log: Vec::new(),
                 #[cfg(test)]
@@ -1485,6 +1490,7 @@ pub struct Terminal {
     path_style: PathStyle,
     cwd_history: Vec<CwdHistoryEntry>,
     pending_cwd_boundary: Option<i32>,
+    mirror: Option<GridMirror>,
     #[cfg(any(test, feature = "test-support"))]
     input_log: Vec<Vec<u8>>,
     #[cfg(test)]
@@ -2019,7 +2025,10 @@ impl Terminal {
 
     ///Resize the terminal and the PTY.
     pub fn set_size(&mut self, new_bounds: TerminalBounds) {
-        let new_bounds = normalize_terminal_bounds(new_bounds);
+        let new_bounds = match &self.mirror {
+            Some(mirror) => mirror.bounds(new_bounds),
+            None => normalize_terminal_bounds(new_bounds),
+        };
 
         let old_bounds = self.last_content.terminal_bounds;
         self.last_content.terminal_bounds = new_bounds;
@@ -2042,12 +2051,14 @@ impl Terminal {
     }
 
     /// Write the Input payload to the PTY, if applicable.
-    /// (This is a no-op for display-only terminals.)
+    /// (Display-only terminals drop it, unless they mirror another terminal.)
     fn write_to_pty(&self, input: impl Into<Cow<'static, [u8]>>) {
         let input = input.into();
         #[cfg(any(test, feature = "test-support"))]
         self.pty_write_log.borrow_mut().push(input.to_vec());
-        if let TerminalType::Pty { pty_tx, .. } = &self.terminal_type {
+        if let Some(mirror) = &self.mirror {
+            mirror.send_input(&input);
+        } else if let TerminalType::Pty { pty_tx, .. } = &self.terminal_type {
             if log::log_enabled!(log::Level::Debug) {
                 if let Ok(str) = str::from_utf8(&input) {
                     log::debug!("Writing to PTY: {:?}", str);
@@ -2941,6 +2952,11 @@ impl Terminal {
         }
     }
 
+    pub fn set_title_override(&mut self, title: Option<String>, cx: &mut Context<Self>) {
+        self.title_override = title;
+        cx.emit(Event::TitleChanged);
+    }
+
     pub fn kill_active_task(&mut self) {
         if let Some(task) = self.task()
             && task.status == TaskStatus::Running
@@ -2981,6 +2997,11 @@ impl Terminal {
         self.task.as_ref()
     }
 
+    /// Whether the terminal's shell runs on the remote host of a remote project.
+    pub fn is_remote_terminal(&self) -> bool {
+        self.is_remote_terminal
+    }
+
     pub fn wait_for_completed_task(&self, cx: &App) -> Task<Option<ExitStatus>> {
         if let Some(task) = self.task() {
             if task.status == TaskStatus::Running {
@@ -0,0 +1,231 @@
+//! Showing terminals to the guests of a shared project.
+//!
+//! The host shares a terminal from its context menu or the command palette.
+//! Guests get a tab with a copy of the terminal as soon as it is shared, and
+//! both sides show who the terminal belongs to and whether guests can type in
+//! it.
+
+use gpui::{AnyElement, App, Context, Entity, SharedUri, Window};
+use project::{
+    Project,
+    shared_terminal_store::{SharedTerminalStoreEvent, TerminalSharePermission},
+};
+use terminal::Terminal;
+use ui::{Avatar, Tooltip, prelude::*};
+use workspace::{Toast, Workspace, notifications::NotificationId};
+
+use crate::{ShareTerminalReadOnly, ShareTerminalReadWrite, StopSharingTerminal, TerminalView};
+
+/// Opens the terminals the host shares in the workspace of a guest.
+pub(crate) fn init(
+    workspace: &mut Workspace,
+    window: Option<&mut Window>,
+    cx: &mut Context<Workspace>,
+) {
+    let Some(window) = window else {
+        return;
+    };
+    let project = workspace.project().read(cx);
+    if !project.is_via_collab() {
+        return;
+    }
+    let store = project.shared_terminal_store();
+    cx.subscribe_in(
+        &store,
+        window,
+        |workspace, _, event: &SharedTerminalStoreEvent, window, cx| {
+            let SharedTerminalStoreEvent::TerminalShared(terminal) = event;
+            open_shared_terminal(workspace, terminal.clone(), window, cx);
+        },
+    )
+    .detach();
+
+    // Terminals shared while the project was being joined.
+    let terminals = store
+        .read(cx)
+        .mirrored_terminals()
+        .cloned()
+        .collect::<Vec<_>>();
+    for terminal in terminals {
+        open_shared_terminal(workspace, terminal, window, cx);
+    }
+}
+
+fn open_shared_terminal(
+    workspace: &mut Workspace,
+    terminal: Entity<Terminal>,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let weak_workspace = workspace.weak_handle();
+    let workspace_id = workspace.database_id();
+    let project = workspace.project().downgrade();
+    let terminal_view =
+        cx.new(|cx| TerminalView::new(terminal, weak_workspace, workspace_id, project, window, cx));
+    workspace.add_item_to_active_pane(Box::new(terminal_view), None, false, window, cx);
+}
+
+impl TerminalView {
+    pub(crate) fn share_terminal_read_only(
+        &mut self,
+        _: &ShareTerminalReadOnly,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.share_terminal(TerminalSharePermission::ReadOnly, cx);
+    }
+
+    pub(crate) fn share_terminal_read_write(
+        &mut self,
+        _: &ShareTerminalReadWrite,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.share_terminal(TerminalSharePermission::ReadWrite, cx);
+    }
+
+    pub(crate) fn stop_sharing_terminal(
+        &mut self,
+        _: &StopSharingTerminal,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let terminal_id = project::shared_terminal_store::terminal_id(&self.terminal);
+        project
+            .read(cx)
+            .shared_terminal_store()
+            .update(cx, |store, cx| store.unshare_terminal(terminal_id, cx));
+    }
+
+    fn share_terminal(&mut self, permission: TerminalSharePermission, cx: &mut Context<Self>) {
+        let Some(project) = self.project.upgrade() else {
+            return;
+        };
+        let result = project
+            .read(cx)
+            .shared_terminal_store()
+            .update(cx, |store, cx| {
+                store.share_terminal(&self.terminal, permission, cx)
+            });
+        if let Err(error) = result {
+            struct ShareTerminalToast;
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    workspace.show_toast(
+                        Toast::new(
+                            NotificationId::unique::<ShareTerminalToast>(),
+                            format!("Failed to share the terminal: {error}"),
+                        ),
+                        cx,
+                    );
+                })
+                .ok();
+        }
+    }
+
+    /// Whether the terminal can be shared with the guests of the project, and
+    /// what they may do with it if it already is.
+    pub(crate) fn sharing_state(&self, cx: &App) -> (bool, Option<TerminalSharePermission>) {
+        let Some(project) = self.project.upgrade() else {
+            return (false, None);
+        };
+        let store = project.read(cx).shared_terminal_store();
+        let store = store.read(cx);
+        (store.can_share(), store.permission(&self.terminal))
+    }
+
+    /// Shows the host's terminals as shared, and the terminals of the host as
+    /// belonging to them.
+    pub(crate) fn render_sharing_indicator(&self, cx: &App) -> Option<AnyElement> {
+        let project = self.project.upgrade()?;
+        let permission = project
+            .read(cx)
+            .shared_terminal_store()
+            .read(cx)
+            .permission(&self.terminal);
+        let permission_label = |permission| match permission {
+            TerminalSharePermission::ReadOnly => "read-only",
+            TerminalSharePermission::ReadWrite => "read-write",
+        };
+
+        let (avatar, label, tooltip) = if self.terminal.read(cx).is_mirror() {
+            let host = host_user(&project, cx);
+            let host_name = host
+                .as_ref()
+                .map(|(_, name)| name.clone())
+                .unwrap_or_else(|| "Host".into());
+            let (label, tooltip) = match permission {
+                Some(permission) => (
+                    format!("{host_name} · {}", permission_label(permission)),
+                    match permission {
+                        TerminalSharePermission::ReadOnly => {
+                            format!("{host_name} shares this terminal for you to watch")
+                        }
+                        TerminalSharePermission::ReadWrite => {
+                            format!("{host_name} lets you type in this terminal")
+                        }
+                    },
+                ),
+                None => (
+                    format!("{host_name} · stopped sharing"),
+                    format!("{host_name} no longer shares this terminal"),
+                ),
+            };
+            (host.map(|(avatar_uri, _)| avatar_uri), label, tooltip)
+        } else {
+            let permission = permission?;
+            (
+                None,
+                format!("Shared · {}", permission_label(permission)),
+                match permission {
+                    TerminalSharePermission::ReadOnly => {
+                        "Collaborators can watch this terminal".to_string()
+                    }
+                    TerminalSharePermission::ReadWrite => {
+                        "Collaborators can watch and type in this terminal".to_string()
+                    }
+                },
+            )
+        };
+
+        let colors = cx.theme().colors();
+        Some(
+            h_flex()
+                .id("terminal-sharing-indicator")
+                .absolute()
+                .top_1()
+                .right_4()
+                .gap_1()
+                .px_1p5()
+                .py_0p5()
+                .rounded_sm()
+                .border_1()
+                .border_color(colors.border_variant)
+                .bg(colors.elevated_surface_background)
+                .map(|this| match avatar {
+                    Some(avatar_uri) => this.child(Avatar::new(avatar_uri).size(rems(0.875))),
+                    None => this.child(
+                        Icon::new(IconName::Screen)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    ),
+                })
+                .child(Label::new(label).size(LabelSize::Small).color(Color::Muted))
+                .tooltip(Tooltip::text(tooltip))
+                .into_any_element(),
+        )
+    }
+}
+
+fn host_user(project: &Entity<Project>, cx: &App) -> Option<(SharedUri, SharedString)> {
+    let project = project.read(cx);
+    let host = project.host()?;
+    let user = project
+        .user_store()
+        .read(cx)
+        .get_cached_user(host.user_id)?;
+    Some((user.avatar_uri.clone(), user.username.clone()))
+}
@@ -1,4 +1,5 @@
 mod persistence;
+mod shared_terminals;
 pub mod terminal_element;
 pub mod terminal_panel;
 mod terminal_path_like_target;
@@ -16,7 +17,9 @@ use gpui::{
 };
 use menu;
 use persistence::TerminalDb;
-use project::{Project, ProjectEntryId, search::SearchQuery};
+use project::{
+    Project, ProjectEntryId, search::SearchQuery, shared_terminal_store::TerminalSharePermission,
+};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use settings::{
@@ -96,6 +99,14 @@ actions!(
     [
         /// Reruns the last executed task in the terminal.
         RerunTask,
+        /// Shows the terminal to the guests of the shared project, without
+        /// letting them type in it.
+        ShareTerminalReadOnly,
+        /// Shows the terminal to the guests of the shared project, and lets
+        /// them type in it.
+        ShareTerminalReadWrite,
+        /// Stops showing the terminal to the guests of the shared project.
+        StopSharingTerminal,
     ]
 );
 
@@ -109,8 +120,9 @@ pub fn init(cx: &mut App) {
 
     register_serializable_item::<TerminalView>(cx);
 
-    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+    cx.observe_new(|workspace: &mut Workspace, window, cx| {
         workspace.register_action(TerminalView::deploy);
+        shared_terminals::init(workspace, window, cx);
     })
     .detach();
 }
@@ -270,12 +282,16 @@ impl TerminalView {
             )
         });
 
-        let subscriptions = vec![
+        let mut subscriptions = vec![
             focus_in,
             focus_out,
             cx.observe(&blink_manager, |_, _, cx| cx.notify()),
             cx.observe_global::<SettingsStore>(Self::settings_changed),
         ];
+        if let Some(project) = project.upgrade() {
+            let shared_terminal_store = project.read(cx).shared_terminal_store();
+            subscriptions.push(cx.observe(&shared_terminal_store, |_, _, cx| cx.notify()));
+        }
 
         Self {
             terminal,
@@ -531,6 +547,7 @@ impl TerminalView {
             .upgrade()
             .and_then(|workspace| workspace.read(cx).panel::<TerminalPanel>(cx))
             .is_some_and(|terminal_panel| terminal_panel.read(cx).assistant_enabled());
+        let (can_share, share_permission) = self.sharing_state(cx);
         let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
             menu.context(self.focus_handle.clone())
                 .when(self.shows_workspace_actions(), |menu| {
@@ -564,6 +581,22 @@ impl TerminalView {
                             })
                     },
                 )
+                .when(can_share, |menu| {
+                    menu.separator()
+                        .when(
+                            share_permission != Some(TerminalSharePermission::ReadOnly),
+                            |menu| menu.action("Share Read-Only", Box::new(ShareTerminalReadOnly)),
+                        )
+                        .when(
+                            share_permission != Some(TerminalSharePermission::ReadWrite),
+                            |menu| {
+                                menu.action("Share Read-Write", Box::new(ShareTerminalReadWrite))
+                            },
+                        )
+                        .when(share_permission.is_some(), |menu| {
+                            menu.action("Stop Sharing", Box::new(StopSharingTerminal))
+                        })
+                })
                 .when(self.shows_workspace_actions(), |menu| {
                     menu.separator().action(
                         "Close Terminal Tab",
@@ -1370,6 +1403,9 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::select_all))
             .on_action(cx.listener(TerminalView::rerun_task))
             .on_action(cx.listener(TerminalView::rename_terminal))
+            .on_action(cx.listener(TerminalView::share_terminal_read_only))
+            .on_action(cx.listener(TerminalView::share_terminal_read_write))
+            .on_action(cx.listener(TerminalView::stop_sharing_terminal))
             .on_key_down(cx.listener(Self::key_down))
             .on_mouse_down(
                 MouseButton::Right,
@@ -1425,6 +1461,7 @@ impl Render for TerminalView {
                         )
                     }),
             )
+            .children(self.render_sharing_indicator(cx))
             .children(self.context_menu.as_ref().map(|(menu, position, _)| {
                 deferred(
                     anchored()
@@ -1752,6 +1789,11 @@ impl Item for TerminalView {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Task<Option<Entity<Self>>> {
+        // Guests cannot start terminals on the host, so a shared terminal has
+        // nothing to split into.
+        if self.terminal.read(cx).is_mirror() {
+            return Task::ready(None);
+        }
         let Ok(terminal) = self.project.update(cx, |project, cx| {
             let cwd = project
                 .active_project_directory(cx)
@@ -1870,7 +1912,7 @@ impl SerializableItem for TerminalView {
         cx: &mut Context<Self>,
     ) -> Option<Task<anyhow::Result<()>>> {
         let terminal = self.terminal().read(cx);
-        if terminal.task().is_some() {
+        if terminal.task().is_some() || terminal.is_mirror() {
             return None;
         }
 
@@ -1969,6 +2011,7 @@ impl SearchableItem for TerminalView {
             replacement: false,
             selection: false,
             select_all: false,
+            syntax_scope: false,
             find_in_results: false,
         }
     }
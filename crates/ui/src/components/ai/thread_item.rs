@@ -45,6 +45,7 @@ pub struct ThreadItem {
     title_label_color: Option<Color>,
     title_generating: bool,
     highlight_positions: Vec<usize>,
+    snippet: Option<(SharedString, Vec<usize>)>,
     timestamp: SharedString,
     notified: bool,
     status: AgentThreadStatus,
@@ -80,6 +81,7 @@ impl ThreadItem {
             title_label_color: None,
             title_generating: false,
             highlight_positions: Vec::new(),
+            snippet: None,
             timestamp: "".into(),
             notified: false,
             status: AgentThreadStatus::default(),
@@ -164,6 +166,18 @@ impl ThreadItem {
         self
     }
 
+    /// An excerpt of the thread's content shown below the metadata row, e.g.
+    /// the text that matched a search. `highlight_positions` are byte offsets
+    /// into `text`.
+    pub fn snippet(
+        mut self,
+        text: impl Into<SharedString>,
+        highlight_positions: Vec<usize>,
+    ) -> Self {
+        self.snippet = Some((text.into(), highlight_positions));
+        self
+    }
+
     pub fn selected(mut self, selected: bool) -> Self {
         self.selected = selected;
         self
@@ -606,6 +620,20 @@ impl RenderOnce for ThreadItem {
                         }),
                 )
             })
+            .when_some(self.snippet, |this, (text, highlight_positions)| {
+                this.child(
+                    h_flex()
+                        .min_w_0()
+                        .gap_1p5()
+                        .child(icon_container()) // Icon Spacing
+                        .child(
+                            HighlightedLabel::new(text, highlight_positions)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .truncate(),
+                        ),
+                )
+            })
             .when(show_tooltip, |this| {
                 let status = self.status;
                 this.tooltip(Tooltip::element(move |_, _| match status {
@@ -6,7 +6,7 @@ use ui::{
     KeyBinding, Label, LabelCommon, LabelSize, ParentElement, Render, SharedString, Styled as _,
     Window, h_flex, v_flex,
 };
-use zed_actions::workspace::OpenWithSystem;
+use zed_actions::{hex_editor::OpenActiveFile, workspace::OpenWithSystem};
 
 use crate::Item;
 
@@ -98,14 +98,28 @@ impl Render for InvalidItemView {
                         )
                         .when(self.is_local, |contents| {
                             contents.child(
-                                h_flex().justify_center().child(
-                                    Button::new("open-with-system", "Open in Default App")
-                                        .on_click(move |_, _, cx| {
-                                            cx.open_with_system(&abs_path);
-                                        })
-                                        .style(ButtonStyle::Outlined)
-                                        .key_binding(KeyBinding::for_action(&OpenWithSystem, cx)),
-                                ),
+                                h_flex()
+                                    .justify_center()
+                                    .gap_2()
+                                    .child(
+                                        Button::new("open-with-system", "Open in Default App")
+                                            .on_click(move |_, _, cx| {
+                                                cx.open_with_system(&abs_path);
+                                            })
+                                            .style(ButtonStyle::Outlined)
+                                            .key_binding(KeyBinding::for_action(
+                                                &OpenWithSystem,
+                                                cx,
+                                            )),
+                                    )
+                                    .child(
+                                        Button::new("open-in-hex-editor", "Open in Hex Editor")
+                                            .on_click(|_, window, cx| {
+                                                window
+                                                    .dispatch_action(Box::new(OpenActiveFile), cx);
+                                            })
+                                            .style(ButtonStyle::Outlined),
+                                    ),
                             )
                         }),
                 ),
@@ -29,8 +29,9 @@ use project::{
 
 use language::{LanguageName, Toolchain, ToolchainScope};
 use remote::{
-    DockerConnectionOptions, RemoteConnectionIdentity, RemoteConnectionOptions,
-    SshConnectionOptions, WslConnectionOptions, remote_connection_identity,
+    DockerConnectionOptions, KubernetesConnectionOptions, RemoteConnectionIdentity,
+    RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
+    remote_connection_identity,
 };
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -1738,6 +1739,21 @@ impl WorkspaceDb {
                 name = Some(identity_name);
                 user = Some(remote_user);
             }
+            // Kubernetes connections reuse the existing columns: the context is
+            // stored as the host and the namespace as the distro.
+            RemoteConnectionIdentity::Kubernetes {
+                context,
+                namespace,
+                pod,
+                container,
+            } => {
+                kind = RemoteConnectionKind::Kubernetes;
+                host = context;
+                distro = namespace;
+                name = Some(pod);
+                container_id = container;
+                user = None;
+            }
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionIdentity::Mock { id } => {
                 kind = RemoteConnectionKind::Ssh;
@@ -2029,6 +2045,15 @@ impl WorkspaceDb {
                     remote_env,
                 }))
             }
+            RemoteConnectionKind::Kubernetes => Some(RemoteConnectionOptions::Kubernetes(
+                KubernetesConnectionOptions {
+                    context: host,
+                    namespace: distro,
+                    pod: name?,
+                    container: container_id,
+                    upload_binary_over_kubectl_exec: false,
+                },
+            )),
         }
     }
 
@@ -4339,6 +4364,43 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_kubernetes_remote_connection_round_trip() {
+        let db = WorkspaceDb::open_test_db("test_kubernetes_remote_connection_round_trip").await;
+
+        let options = RemoteConnectionOptions::Kubernetes(KubernetesConnectionOptions {
+            context: Some("dev-cluster".to_string()),
+            namespace: Some("team".to_string()),
+            pod: "api".to_string(),
+            container: Some("app".to_string()),
+            upload_binary_over_kubectl_exec: false,
+        });
+        let id = db
+            .get_or_create_remote_connection(options.clone())
+            .await
+            .unwrap();
+        let same_id = db
+            .get_or_create_remote_connection(options.clone())
+            .await
+            .unwrap();
+        assert_eq!(id, same_id);
+        assert_eq!(db.remote_connection(id).unwrap(), options);
+
+        let sidecar_id = db
+            .get_or_create_remote_connection(RemoteConnectionOptions::Kubernetes(
+                KubernetesConnectionOptions {
+                    context: Some("dev-cluster".to_string()),
+                    namespace: Some("team".to_string()),
+                    pod: "api".to_string(),
+                    container: Some("sidecar".to_string()),
+                    upload_binary_over_kubectl_exec: false,
+                },
+            ))
+            .await
+            .unwrap();
+        assert_ne!(id, sidecar_id);
+    }
+
     #[gpui::test]
     async fn test_simple_split() {
         zlog::init_test();
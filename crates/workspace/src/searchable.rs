@@ -58,6 +58,8 @@ pub struct SearchOptions {
     pub selection: bool,
     pub select_all: bool,
     pub find_in_results: bool,
+    /// Specifies whether matches can be restricted to comments, strings or code.
+    pub syntax_scope: bool,
 }
 
 // Whether to always select the current selection (even if empty)
@@ -81,6 +83,7 @@ pub trait SearchableItem: Item + EventEmitter<SearchEvent> {
             replacement: true,
             selection: true,
             select_all: true,
+            syntax_scope: false,
             find_in_results: false,
         }
     }
@@ -10277,6 +10277,10 @@ pub fn workspace_windows_for_location(
                 (RemoteConnectionOptions::Docker(a), RemoteConnectionOptions::Docker(b)) => {
                     a.container_id == b.container_id
                 }
+                (RemoteConnectionOptions::Kubernetes(a), RemoteConnectionOptions::Kubernetes(b)) => {
+                    (&a.context, &a.namespace, &a.pod, &a.container)
+                        == (&b.context, &b.namespace, &b.pod, &b.container)
+                }
                 #[cfg(any(test, feature = "test-support"))]
                 (RemoteConnectionOptions::Mock(a), RemoteConnectionOptions::Mock(b)) => {
                     a.id == b.id
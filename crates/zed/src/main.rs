@@ -735,6 +735,9 @@ fn main() {
 
         editor::init(cx);
         image_viewer::init(cx);
+        hex_editor::init(cx);
+        large_file_viewer::init(cx);
+        log_viewer::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -749,6 +752,7 @@ fn main() {
         project_symbols::init(cx);
         project_panel::init(cx);
         outline_panel::init(cx);
+        ports_panel::init(cx);
         tasks_ui::init(cx);
         snippets_ui::init(cx);
         channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
@@ -784,6 +788,7 @@ fn main() {
         onboarding::init(cx);
         settings_ui::init(cx);
         keymap_editor::init(cx);
+        keyboard_macros::init(cx);
         extensions_ui::init(cx);
         edit_prediction::init(cx);
         inspector_ui::init(app_state.clone(), cx);
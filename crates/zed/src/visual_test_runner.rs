@@ -2729,6 +2729,7 @@ fn run_multi_workspace_sidebar_visual_tests(
                             detailed_summary: None,
                             initial_project_snapshot: None,
                             cumulative_token_usage: Default::default(),
+                            cumulative_cost: 0.0,
                             request_token_usage: Default::default(),
                             model: None,
                             profile: None,
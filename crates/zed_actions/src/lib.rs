@@ -344,7 +344,9 @@ pub struct OpenWorktreeInNewWindow {
 }
 
 pub mod git {
-    use gpui::actions;
+    use gpui::{Action, actions};
+    use schemars::JsonSchema;
+    use serde::Deserialize;
 
     actions!(
         git,
@@ -376,6 +378,16 @@ pub mod git {
             CreatePullRequest
         ]
     );
+
+    /// Searches the git history for commits that added or removed some text.
+    #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+    #[action(namespace = git)]
+    #[serde(deny_unknown_fields)]
+    pub struct SearchHistory {
+        /// The text to search for. When omitted, the search starts out empty.
+        #[serde(default)]
+        pub query: Option<String>,
+    }
 }
 
 pub mod toast {
@@ -427,6 +439,18 @@ pub mod project_panel {
         ]
     );
 }
+pub mod hex_editor {
+    use gpui::actions;
+
+    actions!(
+        hex_editor,
+        [
+            /// Opens the file of the active item in the hex editor.
+            OpenActiveFile,
+        ]
+    );
+}
+
 pub mod feedback {
     use gpui::actions;
 